@@ -32,8 +32,11 @@ async fn main() -> anyhow::Result<()> {
     // init logger
     rocketmq_common::log::init_logger();
     let (broker_config, message_store_config) = parse_config_file();
+    let mut server_config = broker_config.broker_server_config.clone();
+    server_config.listen_port = broker_config.listen_port;
     // boot strap broker
     Builder::new()
+        .set_server_config(server_config)
         .set_broker_config(broker_config)
         .set_message_store_config(message_store_config)
         .build()
@@ -33,6 +33,7 @@ impl BrokerBootstrap {
             return;
         }
         let (_start_result, _ctrl_c) = tokio::join!(self.start(), tokio::signal::ctrl_c());
+        self.broker_runtime.shutdown_gracefully().await;
     }
 
     async fn initialize(&mut self) -> bool {
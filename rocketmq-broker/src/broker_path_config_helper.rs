@@ -71,6 +71,15 @@ pub fn get_consumer_order_info_path(root_dir: &str) -> String {
         .into_owned()
 }
 
+// Broker id applied from the controller, see `enable_controller_mode`
+pub fn get_broker_identity_path(root_dir: &str) -> String {
+    PathBuf::from(root_dir)
+        .join("config")
+        .join("brokerIdentity.json")
+        .to_string_lossy()
+        .into_owned()
+}
+
 // Subscription group path
 pub fn get_subscription_group_path(root_dir: &str) -> String {
     PathBuf::from(root_dir)
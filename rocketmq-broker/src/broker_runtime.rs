@@ -30,13 +30,23 @@ use rocketmq_common::common::statistics::state_getter::StateGetter;
 use rocketmq_common::ArcRefCellWrapper;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_common::UtilAll::compute_next_morning_time_millis;
+use rocketmq_remoting::acl::plain_access_validator::PlainAccessValidator;
+use rocketmq_remoting::acl::plain_permission_manager::PlainPermissionManager;
+use rocketmq_remoting::acl::plain_permission_manager::DEFAULT_WATCH_INTERVAL;
+use rocketmq_remoting::base::remoting_metrics::StatsRemotingMetricsSink;
+use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigSerializeWrapper;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_detail::TopicQueueMappingDetail;
 use rocketmq_remoting::protocol::DataVersion;
+use rocketmq_remoting::remoting_server::request_dispatcher::ExecutorPoolConfig;
+use rocketmq_remoting::remoting_server::request_dispatcher::RequestDispatchConfig;
+use rocketmq_remoting::remoting_server::request_dispatcher::RequestDispatcher;
 use rocketmq_remoting::remoting_server::server::RocketMQServer;
+use rocketmq_remoting::remoting_server::server::ServerShutdownHandle;
 use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_runtime::RocketMQRuntime;
 use rocketmq_store::base::store_enum::StoreType;
 use rocketmq_store::config::message_store_config::MessageStoreConfig;
@@ -45,6 +55,7 @@ use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
 use rocketmq_store::stats::broker_stats::BrokerStats;
 use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
 use rocketmq_store::timer::timer_message_store::TimerMessageStore;
+use tracing::error;
 use tracing::info;
 use tracing::warn;
 
@@ -53,6 +64,7 @@ use crate::client::default_consumer_ids_change_listener::DefaultConsumerIdsChang
 use crate::client::manager::consumer_manager::ConsumerManager;
 use crate::client::manager::producer_manager::ProducerManager;
 use crate::client::rebalance::rebalance_lock_manager::RebalanceLockManager;
+use crate::controller::replicas_manager::ReplicasManager;
 use crate::filter::manager::consumer_filter_manager::ConsumerFilterManager;
 use crate::hook::batch_check_before_put_message::BatchCheckBeforePutMessageHook;
 use crate::hook::check_before_put_message::CheckBeforePutMessageHook;
@@ -114,6 +126,11 @@ pub(crate) struct BrokerRuntime {
     #[cfg(feature = "local_file_store")]
     pull_request_hold_service: Option<PullRequestHoldService<DefaultMessageStore>>,
     rebalance_lock_manager: Arc<RebalanceLockManager>,
+    plain_permission_manager: Option<Arc<PlainPermissionManager>>,
+    rpc_hooks: Vec<Arc<Box<dyn RPCHook>>>,
+    server_shutdown_handles: Vec<ServerShutdownHandle>,
+    remoting_metrics_sink: Arc<StatsRemotingMetricsSink>,
+    replicas_manager: Arc<ReplicasManager>,
 }
 
 impl Clone for BrokerRuntime {
@@ -147,6 +164,11 @@ impl Clone for BrokerRuntime {
             is_isolated: self.is_isolated.clone(),
             pull_request_hold_service: self.pull_request_hold_service.clone(),
             rebalance_lock_manager: self.rebalance_lock_manager.clone(),
+            plain_permission_manager: self.plain_permission_manager.clone(),
+            rpc_hooks: self.rpc_hooks.clone(),
+            server_shutdown_handles: self.server_shutdown_handles.clone(),
+            remoting_metrics_sink: self.remoting_metrics_sink.clone(),
+            replicas_manager: self.replicas_manager.clone(),
         }
     }
 }
@@ -165,8 +187,15 @@ impl BrokerRuntime {
         let message_store_config = Arc::new(message_store_config);
         let topic_queue_mapping_manager =
             Arc::new(TopicQueueMappingManager::new(broker_config.clone()));
+        let replicas_manager = Arc::new(ReplicasManager::new(
+            broker_config.clone(),
+            broker_outer_api.clone(),
+            message_store_config.store_path_root_dir.as_str(),
+            format!("{}:{}", broker_config.broker_ip1, server_config.listen_port),
+        ));
         let broker_runtime_inner = Arc::new(BrokerRuntimeInner {
             broker_out_api: broker_outer_api.clone(),
+            replicas_manager: replicas_manager.clone(),
             broker_config: broker_config.clone(),
             message_store_config: message_store_config.clone(),
             server_config: server_config.clone(),
@@ -222,6 +251,11 @@ impl BrokerRuntime {
             is_isolated: Arc::new(AtomicBool::new(false)),
             pull_request_hold_service: None,
             rebalance_lock_manager: Arc::new(Default::default()),
+            plain_permission_manager: None,
+            rpc_hooks: Vec::new(),
+            server_shutdown_handles: Vec::new(),
+            remoting_metrics_sink: Arc::new(StatsRemotingMetricsSink::new()),
+            replicas_manager,
         }
     }
 
@@ -233,7 +267,29 @@ impl BrokerRuntime {
         &self.message_store_config
     }
 
+    /// Drains the remoting servers before shutting down the services their requests use.
+    pub async fn shutdown_gracefully(&mut self) {
+        for handle in &self.server_shutdown_handles {
+            handle.shutdown();
+        }
+        for handle in &self.server_shutdown_handles {
+            handle.wait_for_termination().await;
+        }
+        info!("[Broker shutdown]remoting servers terminated");
+        let result =
+            self.drop
+                .clone()
+                .compare_exchange(false, true, Ordering::SeqCst, Ordering::Relaxed);
+        if result.is_ok() {
+            self.shutdown();
+        }
+    }
+
     pub fn shutdown(&mut self) {
+        for handle in &self.server_shutdown_handles {
+            handle.shutdown();
+        }
+        self.remoting_metrics_sink.shutdown();
         self.broker_out_api.shutdown();
         if let Some(message_store) = &mut self.message_store {
             message_store.shutdown()
@@ -333,8 +389,10 @@ impl BrokerRuntime {
         let mut result: bool = true;
 
         if self.broker_config.enable_controller_mode {
-            info!("Start controller mode(Support for future versions)");
-            unimplemented!()
+            info!(
+                "Start controller mode, controller: {:?}",
+                self.broker_config.controller_addr
+            );
         }
         if self.message_store.is_some() {
             self.register_message_store_hook();
@@ -464,6 +522,7 @@ impl BrokerRuntime {
             self.message_store_config.clone(),
             self.topic_config_manager.clone(),
             self.consumer_offset_manager.clone(),
+            self.subscription_group_manager.clone(),
             self.topic_queue_mapping_manager.clone(),
             self.message_store.as_ref().unwrap().clone(),
             self.schedule_message_service.clone(),
@@ -471,6 +530,8 @@ impl BrokerRuntime {
             self.consumer_manager.clone(),
             self.broker_out_api.clone(),
             self.broker_stats_manager.clone(),
+            self.plain_permission_manager.clone(),
+            self.remoting_metrics_sink.clone(),
         );
 
         BrokerRequestProcessor {
@@ -495,6 +556,7 @@ impl BrokerRuntime {
             query_assignment_processor: Default::default(),
             query_message_processor,
             end_transaction_processor: Default::default(),
+            replicas_manager: self.replicas_manager.clone(),
         }
     }
 
@@ -629,31 +691,150 @@ impl BrokerRuntime {
 
     fn initial_transaction(&mut self) {}
 
-    fn initial_acl(&mut self) {}
+    fn initial_acl(&mut self) {
+        if !self.broker_config.acl_enable {
+            info!("The broker does not enable acl");
+            return;
+        }
+        let permission_manager = Arc::new(PlainPermissionManager::new(
+            self.broker_config.plain_acl_file_path.as_str(),
+        ));
+        // Without a valid file only the requests from global white addresses get through
+        if let Err(err) = permission_manager.load() {
+            error!("load acl config failed: {}", err);
+        }
+        permission_manager.start_watcher(DEFAULT_WATCH_INTERVAL);
+        info!(
+            "The broker enables acl, config file: {}",
+            self.broker_config.plain_acl_file_path
+        );
+        self.plain_permission_manager = Some(permission_manager);
+    }
 
-    fn initial_rpc_hooks(&mut self) {}
+    fn initial_rpc_hooks(&mut self) {
+        if let Some(permission_manager) = self.plain_permission_manager.as_ref() {
+            self.rpc_hooks
+                .push(Arc::new(Box::new(PlainAccessValidator::new(
+                    permission_manager.clone(),
+                ))));
+        }
+    }
 
     fn initial_request_pipeline(&mut self) {}
 
     fn protect_broker(&mut self) {}
 
+    /// Executor pools of the broker servers, one per processor family like upstream's
+    /// separate thread pools. Codes without a pool of their own go to the admin pool.
+    fn request_dispatch_config(&self) -> RequestDispatchConfig {
+        let broker_config = &self.broker_config;
+        RequestDispatchConfig::new(ExecutorPoolConfig::new(
+            "admin",
+            broker_config.admin_broker_thread_pool_nums,
+            broker_config.admin_broker_thread_pool_queue_capacity,
+        ))
+        .register_pool(
+            ExecutorPoolConfig::new(
+                "send",
+                broker_config.send_message_thread_pool_nums,
+                broker_config.send_thread_pool_queue_capacity,
+            ),
+            [
+                RequestCode::SendMessage,
+                RequestCode::SendMessageV2,
+                RequestCode::SendBatchMessage,
+                RequestCode::ConsumerSendMsgBack,
+                RequestCode::SendReplyMessage,
+                RequestCode::SendReplyMessageV2,
+            ],
+        )
+        .register_pool(
+            ExecutorPoolConfig::new(
+                "pull",
+                broker_config.pull_message_thread_pool_nums,
+                broker_config.pull_thread_pool_queue_capacity,
+            ),
+            [RequestCode::PullMessage, RequestCode::LitePullMessage],
+        )
+        .register_pool(
+            ExecutorPoolConfig::new(
+                "query",
+                broker_config.query_message_thread_pool_nums,
+                broker_config.query_thread_pool_queue_capacity,
+            ),
+            [RequestCode::QueryMessage, RequestCode::ViewMessageById],
+        )
+        .register_pool(
+            ExecutorPoolConfig::new(
+                "heartbeat",
+                broker_config.heartbeat_thread_pool_nums,
+                broker_config.heartbeat_thread_pool_queue_capacity,
+            ),
+            [
+                RequestCode::HeartBeat,
+                RequestCode::UnregisterClient,
+                RequestCode::CheckClientConfig,
+            ],
+        )
+        .register_pool(
+            ExecutorPoolConfig::new(
+                "consumer-manage",
+                broker_config.consumer_manage_thread_pool_nums,
+                broker_config.consumer_manager_thread_pool_queue_capacity,
+            ),
+            [
+                RequestCode::GetConsumerListByGroup,
+                RequestCode::UpdateConsumerOffset,
+                RequestCode::QueryConsumerOffset,
+            ],
+        )
+        .set_max_in_flight_per_connection(broker_config.max_in_flight_requests_per_connection)
+    }
+
     fn start_basic_service(&mut self) {
         let request_processor = self.init_processor();
         let fast_request_processor = request_processor.clone();
+        // The normal and the fast server share the executor pools
+        let request_dispatcher = RequestDispatcher::new(&self.request_dispatch_config());
         self.message_store
             .as_mut()
             .unwrap()
             .start()
             .expect("Message store start error");
 
-        let server = RocketMQServer::new(self.server_config.clone());
+        // Both servers record into the same per request code stats
+        self.remoting_metrics_sink.init();
+        let server = self.rpc_hooks.iter().fold(
+            RocketMQServer::new(self.server_config.clone())
+                .set_request_dispatcher(request_dispatcher.clone())
+                .set_metrics_sink(self.remoting_metrics_sink.clone()),
+            |server, hook| server.register_rpc_hook(hook.clone()),
+        );
+        self.server_shutdown_handles.push(server.shutdown_handle());
         //start nomarl broker remoting_server
-        tokio::spawn(async move { server.run(request_processor).await });
+        tokio::spawn(async move {
+            if let Err(err) = server.run(request_processor).await {
+                error!("broker remoting server failed to start: {}", err);
+            }
+        });
         //start fast broker remoting_server
         let mut fast_server_config = (*self.server_config).clone();
         fast_server_config.listen_port = self.server_config.listen_port - 2;
-        let fast_server = RocketMQServer::new(Arc::new(fast_server_config));
-        tokio::spawn(async move { fast_server.run(fast_request_processor).await });
+        // Only the normal server listens on the unix socket
+        fast_server_config.unix_socket_path = None;
+        let fast_server = self.rpc_hooks.iter().fold(
+            RocketMQServer::new(Arc::new(fast_server_config))
+                .set_request_dispatcher(request_dispatcher)
+                .set_metrics_sink(self.remoting_metrics_sink.clone()),
+            |server, hook| server.register_rpc_hook(hook.clone()),
+        );
+        self.server_shutdown_handles
+            .push(fast_server.shutdown_handle());
+        tokio::spawn(async move {
+            if let Err(err) = fast_server.run(fast_request_processor).await {
+                error!("broker fast remoting server failed to start: {}", err);
+            }
+        });
 
         if let Some(pull_request_hold_service) = self.pull_request_hold_service.as_mut() {
             pull_request_hold_service.start();
@@ -689,6 +870,11 @@ impl BrokerRuntime {
         self.broker_out_api.start().await;
         self.start_basic_service();
 
+        if self.broker_config.enable_controller_mode {
+            // Learn the role from the controller before registering to the name servers
+            self.replicas_manager.start().await;
+        }
+
         if !self.is_isolated.load(Ordering::Acquire)
             && !self.message_store_config.enable_dledger_commit_log
             && !self.broker_config.duplication_enable
@@ -736,12 +922,14 @@ impl BrokerRuntime {
                 }
             });
 
-        if self.broker_config.enable_slave_acting_master {
+        if self.broker_config.enable_slave_acting_master
+            || self.broker_config.enable_controller_mode
+        {
             self.schedule_send_heartbeat();
         }
 
         if self.broker_config.enable_controller_mode {
-            self.schedule_send_heartbeat();
+            self.schedule_sync_broker_metadata();
         }
 
         if self.broker_config.skip_pre_online {
@@ -777,7 +965,54 @@ impl BrokerRuntime {
         );
     }
 
-    pub(crate) fn schedule_send_heartbeat(&mut self) {}
+    pub(crate) fn schedule_send_heartbeat(&mut self) {
+        if !self.broker_config.enable_controller_mode {
+            return;
+        }
+        let replicas_manager = self.replicas_manager.clone();
+        let message_store = self.message_store.clone();
+        let period = Duration::from_millis(self.broker_config.broker_heartbeat_interval);
+        self.broker_runtime
+            .as_ref()
+            .unwrap()
+            .get_handle()
+            .spawn(async move {
+                loop {
+                    let max_offset = message_store
+                        .as_ref()
+                        .map_or(0, |message_store| message_store.get_max_phy_offset());
+                    replicas_manager.send_heartbeat(max_offset).await;
+                    tokio::time::sleep(period).await;
+                }
+            });
+    }
+
+    /// Follows the master elected by the controller, registering to the name servers again
+    /// whenever the role of the broker changed.
+    fn schedule_sync_broker_metadata(&mut self) {
+        let mut cloned_broker_runtime = self.clone();
+        let replicas_manager = self.replicas_manager.clone();
+        let period = Duration::from_millis(self.broker_config.sync_broker_metadata_period);
+        self.broker_runtime
+            .as_ref()
+            .unwrap()
+            .get_handle()
+            .spawn(async move {
+                loop {
+                    tokio::select! {
+                        _ = tokio::time::sleep(period) => {
+                            replicas_manager.sync_broker_metadata().await;
+                        }
+                        _ = replicas_manager.role_changed().notified() => {}
+                    }
+                    if replicas_manager.take_need_register() {
+                        cloned_broker_runtime
+                            .register_broker_all(true, false, true)
+                            .await;
+                    }
+                }
+            });
+    }
 
     pub(crate) fn start_service_without_condition(&mut self) {}
 
@@ -894,7 +1129,7 @@ impl BrokerRuntime {
             "{}:{}",
             self.broker_config.broker_ip1, self.server_config.listen_port
         );
-        let broker_id = self.broker_config.broker_identity.broker_id;
+        let broker_id = self.replicas_manager.register_broker_id();
         self.broker_out_api
             .register_broker_all(
                 cluster_name,
@@ -909,6 +1144,7 @@ impl BrokerRuntime {
                 false,
                 false,
                 None,
+                self.broker_config.zone_name.clone(),
                 Default::default(),
             )
             .await;
@@ -918,6 +1154,7 @@ impl BrokerRuntime {
 #[derive(Clone)]
 pub(crate) struct BrokerRuntimeInner {
     pub(crate) broker_out_api: Arc<BrokerOuterAPI>,
+    pub(crate) replicas_manager: Arc<ReplicasManager>,
     pub(crate) broker_config: Arc<BrokerConfig>,
     pub(crate) message_store_config: Arc<MessageStoreConfig>,
     pub(crate) server_config: Arc<ServerConfig>,
@@ -1011,7 +1248,7 @@ impl BrokerRuntimeInner {
             "{}:{}",
             self.broker_config.broker_ip1, self.server_config.listen_port
         );
-        let broker_id = self.broker_config.broker_identity.broker_id;
+        let broker_id = self.replicas_manager.register_broker_id();
         self.broker_out_api
             .register_broker_all(
                 cluster_name,
@@ -1026,6 +1263,7 @@ impl BrokerRuntimeInner {
                 false,
                 false,
                 None,
+                self.broker_config.zone_name.clone(),
                 Default::default(),
             )
             .await;
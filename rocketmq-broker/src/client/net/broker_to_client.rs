@@ -35,4 +35,16 @@ impl Broker2Client {
             Err(e) => Err(BrokerClientError(e)),
         }
     }
+
+    /// Sends `request` to the client without waiting for a response.
+    pub async fn call_client_oneway(
+        &self,
+        channel: &Channel,
+        request: RemotingCommand,
+    ) -> BrokerResult<()> {
+        channel
+            .write_and_flush(request.mark_oneway_rpc())
+            .await
+            .map_err(BrokerClientError)
+    }
 }
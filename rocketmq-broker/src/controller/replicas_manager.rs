@@ -14,9 +14,578 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-#[derive(Default)]
-pub struct ReplicasManager {}
+use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::mix_all::MASTER_ID;
+use rocketmq_common::FileUtils;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
+use rocketmq_remoting::protocol::header::controller::notify_broker_role_changed_request_header::NotifyBrokerRoleChangedRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::broker_request::BrokerHeartbeatRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Notify;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::broker_path_config_helper::get_broker_identity_path;
+use crate::error::BrokerError;
+use crate::out_api::broker_outer_api::BrokerOuterAPI;
+use crate::BrokerResult;
+
+/// The broker id applied from the controller, persisted so the broker keeps it across
+/// restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrokerIdentityMetadata {
+    cluster_name: String,
+    broker_name: String,
+    broker_id: i64,
+    register_check_code: String,
+    /// Unset until the controller accepted the id, the id is applied again on restart.
+    applied: bool,
+}
+
+/// The master of the broker set as last told by the controller.
+#[derive(Debug, Default)]
+struct ReplicasState {
+    controller_leader_address: Option<String>,
+    broker_controller_id: Option<i64>,
+    master_broker_id: Option<i64>,
+    master_address: Option<String>,
+    master_epoch: i32,
+    sync_state_set: HashSet<i64>,
+    sync_state_set_epoch: i32,
+}
+
+impl ReplicasState {
+    fn is_master(&self) -> bool {
+        self.broker_controller_id.is_some() && self.master_broker_id == self.broker_controller_id
+    }
+}
+
+/// Drives the role of the broker when `enable_controller_mode` is set.
+///
+/// The broker applies for an id of its broker set from the controller, registers to it and
+/// becomes master or slave depending on the master the controller elected. Heartbeats keep it
+/// alive on the controller, which elects another master when they stop.
+///
+/// The commit log is not replicated yet, so the SyncStateSet of a broker set only holds its
+/// master and the controller needs `enable_elect_unclean_master` to fail over to a slave.
+pub(crate) struct ReplicasManager {
+    broker_config: Arc<BrokerConfig>,
+    broker_out_api: Arc<BrokerOuterAPI>,
+    broker_identity_path: String,
+    local_address: String,
+    state: RwLock<ReplicasState>,
+    /// Set when the broker must register to the name servers again with its new broker id.
+    need_register: AtomicBool,
+    role_changed: Notify,
+}
 
 impl ReplicasManager {
-    pub fn start(&mut self) {}
+    pub fn new(
+        broker_config: Arc<BrokerConfig>,
+        broker_out_api: Arc<BrokerOuterAPI>,
+        store_path_root_dir: &str,
+        local_address: String,
+    ) -> Self {
+        Self {
+            broker_config,
+            broker_out_api,
+            broker_identity_path: get_broker_identity_path(store_path_root_dir),
+            local_address,
+            state: RwLock::new(ReplicasState::default()),
+            need_register: AtomicBool::new(false),
+            role_changed: Notify::new(),
+        }
+    }
+
+    /// Finds the controller leader, applies for a broker id and registers to the controller.
+    /// Failed steps are retried by [`Self::sync_broker_metadata`].
+    pub async fn start(&self) {
+        if let Err(err) = self.register_to_controller().await {
+            warn!(
+                "failed to register broker to controller, will retry: {}",
+                err
+            );
+        }
+    }
+
+    pub fn is_master(&self) -> bool {
+        self.state.read().is_master()
+    }
+
+    /// The broker id registered to the name servers: the master registers as `MASTER_ID`,
+    /// slaves with the id the controller assigned to them.
+    pub fn register_broker_id(&self) -> u64 {
+        if !self.broker_config.enable_controller_mode {
+            return self.broker_config.broker_identity.broker_id;
+        }
+        let state = self.state.read();
+        if state.is_master() {
+            return MASTER_ID;
+        }
+        state
+            .broker_controller_id
+            .map_or(self.broker_config.broker_identity.broker_id, |broker_id| {
+                broker_id as u64
+            })
+    }
+
+    pub fn master_address(&self) -> Option<String> {
+        self.state.read().master_address.clone()
+    }
+
+    /// Notified when the role or the master of the broker changed.
+    pub fn role_changed(&self) -> &Notify {
+        &self.role_changed
+    }
+
+    pub fn take_need_register(&self) -> bool {
+        self.need_register.swap(false, Ordering::AcqRel)
+    }
+
+    fn controller_addresses(&self) -> Vec<String> {
+        self.broker_config
+            .controller_addr
+            .as_deref()
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    async fn controller_leader_address(&self) -> BrokerResult<String> {
+        if let Some(address) = self.state.read().controller_leader_address.clone() {
+            return Ok(address);
+        }
+        for controller_address in self.controller_addresses() {
+            match self
+                .broker_out_api
+                .get_controller_metadata(&controller_address)
+                .await
+            {
+                Ok(metadata) => {
+                    let leader_address = if metadata.is_leader {
+                        Some(controller_address)
+                    } else {
+                        metadata.controller_leader_address
+                    };
+                    if let Some(leader_address) = leader_address {
+                        info!("controller leader is {}", leader_address);
+                        self.state.write().controller_leader_address = Some(leader_address.clone());
+                        return Ok(leader_address);
+                    }
+                }
+                Err(err) => warn!(
+                    "failed to get metadata from controller {}: {}",
+                    controller_address, err
+                ),
+            }
+        }
+        Err(BrokerError::ControllerResponseError(
+            self.broker_config
+                .controller_addr
+                .clone()
+                .unwrap_or_default(),
+            ResponseCode::ControllerNotLeader.into(),
+            "no controller leader available".to_string(),
+        ))
+    }
+
+    /// Forgets the leader after a failed request so the next attempt looks it up again.
+    fn on_controller_error(&self, err: &BrokerError) {
+        warn!("controller request failed: {}", err);
+        self.state.write().controller_leader_address = None;
+    }
+
+    fn load_broker_identity(&self) -> Option<BrokerIdentityMetadata> {
+        let content = FileUtils::file_to_string(&self.broker_identity_path).ok()?;
+        if content.is_empty() {
+            return None;
+        }
+        match serde_json::from_str::<BrokerIdentityMetadata>(&content) {
+            Ok(metadata)
+                if metadata.broker_name == self.broker_config.broker_identity.broker_name =>
+            {
+                Some(metadata)
+            }
+            Ok(metadata) => {
+                warn!(
+                    "ignore broker identity of broker set {}, the broker now belongs to {}",
+                    metadata.broker_name, self.broker_config.broker_identity.broker_name
+                );
+                None
+            }
+            Err(err) => {
+                error!(
+                    "failed to parse broker identity {}: {}",
+                    self.broker_identity_path, err
+                );
+                None
+            }
+        }
+    }
+
+    fn persist_broker_identity(&self, metadata: &BrokerIdentityMetadata) {
+        let content = serde_json::to_string(metadata).unwrap_or_default();
+        if let Err(err) = FileUtils::string_to_file(&content, &self.broker_identity_path) {
+            error!(
+                "failed to persist broker identity {}: {}",
+                self.broker_identity_path, err
+            );
+        }
+    }
+
+    /// Returns the broker id of this broker in its broker set, applying for one if needed.
+    async fn apply_broker_id(&self, controller_address: &str) -> BrokerResult<i64> {
+        if let Some(broker_id) = self.state.read().broker_controller_id {
+            return Ok(broker_id);
+        }
+        let cluster_name = self
+            .broker_config
+            .broker_identity
+            .broker_cluster_name
+            .as_str();
+        let broker_name = self.broker_config.broker_identity.broker_name.as_str();
+        let mut metadata = match self.load_broker_identity() {
+            Some(metadata) if metadata.applied => {
+                self.state.write().broker_controller_id = Some(metadata.broker_id);
+                return Ok(metadata.broker_id);
+            }
+            Some(metadata) => metadata,
+            None => {
+                let next_broker_id = self
+                    .broker_out_api
+                    .get_next_broker_id(controller_address, cluster_name, broker_name)
+                    .await?
+                    .next_broker_id
+                    .unwrap_or_default();
+                let metadata = BrokerIdentityMetadata {
+                    cluster_name: cluster_name.to_string(),
+                    broker_name: broker_name.to_string(),
+                    broker_id: next_broker_id,
+                    register_check_code: format!("{};{}", self.local_address, get_current_millis()),
+                    applied: false,
+                };
+                // Persisted first, so a broker crashing before the answer applies the same id
+                // with the same check code again
+                self.persist_broker_identity(&metadata);
+                metadata
+            }
+        };
+        if let Err(err) = self
+            .broker_out_api
+            .apply_broker_id(
+                controller_address,
+                cluster_name,
+                broker_name,
+                metadata.broker_id,
+                &metadata.register_check_code,
+            )
+            .await
+        {
+            if let BrokerError::ControllerResponseError(_, code, _) = &err {
+                if *code == i32::from(ResponseCode::ControllerBrokerIdInvalid) {
+                    // Taken by another broker meanwhile, apply for the next one next time
+                    let _ = std::fs::remove_file(&self.broker_identity_path);
+                }
+            }
+            return Err(err);
+        }
+        metadata.applied = true;
+        self.persist_broker_identity(&metadata);
+        info!(
+            "applied broker id {} of broker set {} from controller {}",
+            metadata.broker_id, broker_name, controller_address
+        );
+        self.state.write().broker_controller_id = Some(metadata.broker_id);
+        Ok(metadata.broker_id)
+    }
+
+    async fn register_to_controller(&self) -> BrokerResult<()> {
+        let controller_address = self.controller_leader_address().await?;
+        let result = self.do_register_to_controller(&controller_address).await;
+        if let Err(err) = &result {
+            self.on_controller_error(err);
+        }
+        result
+    }
+
+    async fn do_register_to_controller(&self, controller_address: &str) -> BrokerResult<()> {
+        let broker_id = self.apply_broker_id(controller_address).await?;
+        let (response, sync_state_set) = self
+            .broker_out_api
+            .register_broker_to_controller(
+                controller_address,
+                RegisterBrokerToControllerRequestHeader {
+                    cluster_name: self
+                        .broker_config
+                        .broker_identity
+                        .broker_cluster_name
+                        .clone(),
+                    broker_name: self.broker_config.broker_identity.broker_name.clone(),
+                    broker_id,
+                    broker_address: self.local_address.clone(),
+                    invoke_time: get_current_millis() as i64,
+                },
+            )
+            .await?;
+        match response.master_broker_id {
+            Some(master_broker_id) => {
+                self.change_role(
+                    master_broker_id,
+                    response.master_address,
+                    response.master_epoch,
+                    sync_state_set,
+                );
+                Ok(())
+            }
+            None => self.broker_elect(controller_address, broker_id).await,
+        }
+    }
+
+    /// Asks the controller for a master when the broker set has none.
+    async fn broker_elect(&self, controller_address: &str, broker_id: i64) -> BrokerResult<()> {
+        let response = self
+            .broker_out_api
+            .elect_master(
+                controller_address,
+                &self.broker_config.broker_identity.broker_cluster_name,
+                &self.broker_config.broker_identity.broker_name,
+                broker_id,
+            )
+            .await?;
+        if let Some(master_broker_id) = response.master_broker_id {
+            self.change_role(
+                master_broker_id,
+                response.master_address,
+                response.master_epoch,
+                Some(SyncStateSet::new(
+                    HashSet::from([master_broker_id]),
+                    response.sync_state_set_epoch,
+                )),
+            );
+        }
+        Ok(())
+    }
+
+    /// Fetches the master of the broker set from the controller, registering first or asking
+    /// for an election when needed.
+    pub async fn sync_broker_metadata(&self) {
+        if self.state.read().broker_controller_id.is_none() {
+            self.start().await;
+            return;
+        }
+        let result = async {
+            let controller_address = self.controller_leader_address().await?;
+            let (response, sync_state_set) = self
+                .broker_out_api
+                .get_replica_info(
+                    &controller_address,
+                    &self.broker_config.broker_identity.broker_name,
+                )
+                .await?;
+            match response.master_broker_id {
+                Some(master_broker_id) => {
+                    self.change_role(
+                        master_broker_id,
+                        response.master_address,
+                        response.master_epoch,
+                        sync_state_set,
+                    );
+                    Ok(())
+                }
+                None => {
+                    let broker_id = self.state.read().broker_controller_id.unwrap_or_default();
+                    self.broker_elect(&controller_address, broker_id).await
+                }
+            }
+        }
+        .await;
+        if let Err(err) = result {
+            self.on_controller_error(&err);
+        }
+    }
+
+    /// Follows the master told by the controller, ignoring masters of older epochs.
+    fn change_role(
+        &self,
+        master_broker_id: i64,
+        master_address: Option<String>,
+        master_epoch: i32,
+        sync_state_set: Option<SyncStateSet>,
+    ) {
+        let mut state = self.state.write();
+        if master_epoch < state.master_epoch {
+            info!(
+                "ignore master {} of fenced epoch {}, current master epoch is {}",
+                master_broker_id, master_epoch, state.master_epoch
+            );
+            return;
+        }
+        let was_master = state.is_master();
+        let master_changed =
+            state.master_broker_id != Some(master_broker_id) || state.master_epoch != master_epoch;
+        state.master_broker_id = Some(master_broker_id);
+        state.master_address = master_address;
+        state.master_epoch = master_epoch;
+        if let Some(sync_state_set) = sync_state_set {
+            if sync_state_set.sync_state_set_epoch >= state.sync_state_set_epoch {
+                state.sync_state_set = sync_state_set.sync_state_set;
+                state.sync_state_set_epoch = sync_state_set.sync_state_set_epoch;
+            }
+        }
+        if !master_changed {
+            return;
+        }
+        let is_master = state.is_master();
+        info!(
+            "broker {:?} of broker set {} is now {}, master: {} {:?}, master epoch: {}",
+            state.broker_controller_id,
+            self.broker_config.broker_identity.broker_name,
+            if is_master { "master" } else { "slave" },
+            master_broker_id,
+            state.master_address,
+            master_epoch
+        );
+        drop(state);
+        if was_master != is_master {
+            self.need_register.store(true, Ordering::Release);
+        }
+        self.role_changed.notify_one();
+    }
+
+    pub fn handle_notify_broker_role_changed(&self, request: &RemotingCommand) -> RemotingCommand {
+        let Some(header) =
+            request.decode_command_custom_header::<NotifyBrokerRoleChangedRequestHeader>()
+        else {
+            return RemotingCommand::create_response_command_with_code_remark(
+                ResponseCode::ControllerInvalidRequest,
+                "invalid request header",
+            );
+        };
+        info!("receive notify broker role changed: {:?}", header);
+        let sync_state_set = request
+            .body()
+            .as_ref()
+            .and_then(|body| SyncStateSet::decode(body.as_ref()).ok());
+        if let Some(master_broker_id) = header.master_broker_id {
+            self.change_role(
+                master_broker_id,
+                header.master_address,
+                header.master_epoch,
+                sync_state_set,
+            );
+        }
+        RemotingCommand::create_response_command()
+    }
+
+    /// Sends a heartbeat to every controller, each of them tracks the alive brokers.
+    pub async fn send_heartbeat(&self, max_offset: i64) {
+        let (broker_id, master_epoch) = {
+            let state = self.state.read();
+            (state.broker_controller_id, state.master_epoch)
+        };
+        let Some(broker_id) = broker_id else {
+            return;
+        };
+        for controller_address in self.controller_addresses() {
+            self.broker_out_api
+                .send_heartbeat_to_controller(
+                    &controller_address,
+                    BrokerHeartbeatRequestHeader::new(
+                        self.broker_config
+                            .broker_identity
+                            .broker_cluster_name
+                            .as_str(),
+                        self.local_address.as_str(),
+                        self.broker_config.broker_identity.broker_name.as_str(),
+                        Some(broker_id),
+                        Some(master_epoch),
+                        Some(max_offset),
+                        Some(max_offset),
+                        Some(self.broker_config.controller_heart_beat_timeout_mills as i64),
+                        Some(self.broker_config.broker_election_priority),
+                    ),
+                )
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+
+    use super::*;
+
+    fn replicas_manager(root_dir: &str) -> ReplicasManager {
+        let broker_config = BrokerConfig {
+            enable_controller_mode: true,
+            ..BrokerConfig::default()
+        };
+        ReplicasManager::new(
+            Arc::new(broker_config),
+            Arc::new(BrokerOuterAPI::new(Arc::new(TokioClientConfig::default()))),
+            root_dir,
+            "127.0.0.1:10911".to_string(),
+        )
+    }
+
+    #[test]
+    fn follows_masters_of_newer_epochs_only() {
+        let root_dir = std::env::temp_dir().join("replicas_manager_follows_masters");
+        let manager = replicas_manager(root_dir.to_string_lossy().as_ref());
+        manager.state.write().broker_controller_id = Some(2);
+        assert_eq!(manager.register_broker_id(), 2);
+
+        manager.change_role(2, Some("127.0.0.1:10911".to_string()), 1, None);
+        assert!(manager.is_master());
+        assert_eq!(manager.register_broker_id(), MASTER_ID);
+        assert!(manager.take_need_register());
+
+        manager.change_role(1, Some("127.0.0.1:10912".to_string()), 2, None);
+        assert!(!manager.is_master());
+        assert_eq!(manager.register_broker_id(), 2);
+        assert_eq!(manager.master_address().as_deref(), Some("127.0.0.1:10912"));
+        assert!(manager.take_need_register());
+
+        manager.change_role(2, Some("127.0.0.1:10911".to_string()), 1, None);
+        assert!(!manager.is_master());
+        assert!(!manager.take_need_register());
+    }
+
+    #[test]
+    fn loads_applied_broker_id_of_same_broker_set_only() {
+        let root_dir = std::env::temp_dir().join("replicas_manager_loads_broker_id");
+        let manager = replicas_manager(root_dir.to_string_lossy().as_ref());
+        let mut metadata = BrokerIdentityMetadata {
+            cluster_name: "DefaultCluster".to_string(),
+            broker_name: manager.broker_config.broker_identity.broker_name.clone(),
+            broker_id: 3,
+            register_check_code: "127.0.0.1:10911;1".to_string(),
+            applied: true,
+        };
+        manager.persist_broker_identity(&metadata);
+        assert_eq!(manager.load_broker_identity().unwrap().broker_id, 3);
+
+        metadata.broker_name = "another-broker".to_string();
+        manager.persist_broker_identity(&metadata);
+        assert!(manager.load_broker_identity().is_none());
+        let _ = std::fs::remove_dir_all(root_dir);
+    }
 }
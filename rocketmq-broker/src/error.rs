@@ -20,4 +20,7 @@ use thiserror::Error;
 pub enum BrokerError {
     #[error("broker client error: {0}")]
     BrokerClientError(#[from] rocketmq_remoting::error::Error),
+
+    #[error("controller {0} answered code {1}, remark: {2}")]
+    ControllerResponseError(String, i32, String),
 }
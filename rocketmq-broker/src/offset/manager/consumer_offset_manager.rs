@@ -92,6 +92,28 @@ impl ConsumerOffsetManager {
         }
     }
 
+    /// Removes the committed, reset and pull offsets of `group` on every topic.
+    pub fn remove_offset(&self, group: &str) {
+        let is_group_key = |topic_at_group: &String| {
+            topic_at_group
+                .split_once(TOPIC_GROUP_SEPARATOR)
+                .is_some_and(|(_, key_group)| key_group == group)
+        };
+        for table in [
+            &self.consumer_offset_wrapper.offset_table,
+            &self.consumer_offset_wrapper.reset_offset_table,
+            &self.consumer_offset_wrapper.pull_offset_table,
+        ] {
+            table.write().retain(|topic_at_group, _| {
+                if is_group_key(topic_at_group) {
+                    warn!("Clean group's offset, {}, {}", topic_at_group, group);
+                    return false;
+                }
+                true
+            });
+        }
+    }
+
     pub fn which_group_by_topic(&self, topic: &str) -> HashSet<String> {
         let read_guard = self.consumer_offset_wrapper.offset_table.read();
         let mut groups = HashSet::new();
@@ -138,19 +160,11 @@ impl ConsumerOffsetManager {
             % self.broker_config.consumer_offset_update_version_step
             == 0
         {
-            let state_machine_version = if let Some(ref message_store) = self.message_store {
-                message_store.get_state_machine_version()
-            } else {
-                0
-            };
-            self.consumer_offset_wrapper
-                .data_version
-                .mut_from_ref()
-                .next_version_with(state_machine_version);
+            self.update_data_version();
         }
     }
 
-    pub fn has_offset_reset(&self, group: &str, topic: &str, queue_id: i32) -> bool {
+    pub fn has_offset_reset(&self, topic: &str, group: &str, queue_id: i32) -> bool {
         let key = format!("{}{}{}", topic, TOPIC_GROUP_SEPARATOR, group);
         match self
             .consumer_offset_wrapper
@@ -190,6 +204,113 @@ impl ConsumerOffsetManager {
         -1
     }
 
+    /// Returns the committed offset of every queue of `topic` consumed by `group`.
+    pub fn query_offset_table(&self, group: &str, topic: &str) -> Option<HashMap<i32, i64>> {
+        let key = format!("{}{}{}", topic, TOPIC_GROUP_SEPARATOR, group);
+        self.consumer_offset_wrapper
+            .offset_table
+            .read()
+            .get(key.as_str())
+            .cloned()
+    }
+
+    /// Returns, for every queue of `topic`, the smallest offset committed by the groups not
+    /// listed in the comma separated `filter_groups`. Offsets already behind the queue are
+    /// ignored.
+    pub fn query_min_offset_in_all_group(
+        &self,
+        topic: &str,
+        filter_groups: Option<&str>,
+    ) -> HashMap<i32, i64> {
+        let filter_groups: HashSet<&str> = filter_groups
+            .map(|groups| {
+                groups
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|group| !group.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut queue_min_offset = HashMap::new();
+        for (topic_at_group, offsets) in self.consumer_offset_wrapper.offset_table.read().iter() {
+            let Some((key_topic, key_group)) = topic_at_group.split_once(TOPIC_GROUP_SEPARATOR)
+            else {
+                continue;
+            };
+            if key_topic != topic || filter_groups.contains(key_group) {
+                continue;
+            }
+            for (queue_id, offset) in offsets {
+                let min_offset = self
+                    .message_store
+                    .as_ref()
+                    .map_or(0, |store| store.get_min_offset_in_queue(topic, *queue_id));
+                if *offset >= min_offset {
+                    queue_min_offset
+                        .entry(*queue_id)
+                        .and_modify(|min: &mut i64| *min = (*min).min(*offset))
+                        .or_insert(*offset);
+                }
+            }
+        }
+        queue_min_offset
+    }
+
+    /// Copies the committed offsets of `src_group` on `topic` to `dest_group`.
+    pub fn clone_offset(&self, src_group: &str, dest_group: &str, topic: &str) {
+        let src_key = format!("{}{}{}", topic, TOPIC_GROUP_SEPARATOR, src_group);
+        let mut offset_table = self.consumer_offset_wrapper.offset_table.write();
+        let Some(offsets) = offset_table.get(src_key.as_str()).cloned() else {
+            return;
+        };
+        let dest_key = format!("{}{}{}", topic, TOPIC_GROUP_SEPARATOR, dest_group);
+        offset_table.insert(dest_key, offsets);
+        drop(offset_table);
+        self.update_data_version();
+    }
+
+    /// Moves the offset of `group` on the queue to `offset`. The reset is handed to the next
+    /// pull of the queue, and commits from clients are rejected until then when
+    /// `use_server_side_reset_offset` is on.
+    pub fn assign_reset_offset(&self, topic: &str, group: &str, queue_id: i32, offset: i64) {
+        if topic.is_empty() || group.is_empty() || queue_id < 0 || offset < 0 {
+            warn!(
+                "Illegal arguments when assigning reset offsets. Topic={}, group={}, queueId={}, \
+                 offset={}",
+                topic, group, queue_id, offset
+            );
+            return;
+        }
+        let key = format!("{}{}{}", topic, TOPIC_GROUP_SEPARATOR, group);
+        self.consumer_offset_wrapper
+            .reset_offset_table
+            .write()
+            .entry(key.clone())
+            .or_default()
+            .insert(queue_id, offset);
+        // also update the committed offset, which may be overwritten by the clients right away
+        // but keeps the reset when they are offline
+        self.consumer_offset_wrapper
+            .offset_table
+            .write()
+            .entry(key)
+            .or_default()
+            .insert(queue_id, offset);
+        self.update_data_version();
+    }
+
+    fn update_data_version(&self) {
+        let state_machine_version = if let Some(ref message_store) = self.message_store {
+            message_store.get_state_machine_version()
+        } else {
+            0
+        };
+        self.consumer_offset_wrapper
+            .data_version
+            .mut_from_ref()
+            .next_version_with(state_machine_version);
+    }
+
     pub fn which_topic_by_consumer(&self, group: &str) -> HashSet<String> {
         let read_guard = self.consumer_offset_wrapper.offset_table.read();
         let mut topics = HashSet::new();
@@ -427,3 +548,52 @@ impl<'de> Deserialize<'de> for ConsumerOffsetWrapper {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_host() -> SocketAddr {
+        "127.0.0.1:10911".parse().unwrap()
+    }
+
+    #[test]
+    fn assign_reset_offset_is_taken_by_next_pull() {
+        let manager = ConsumerOffsetManager::new(Arc::new(BrokerConfig::default()), None);
+        manager.commit_offset(client_host(), "group_a", "topic_a", 0, 100);
+
+        manager.assign_reset_offset("topic_a", "group_a", 0, 10);
+        assert!(manager.has_offset_reset("topic_a", "group_a", 0));
+        assert_eq!(manager.query_offset("group_a", "topic_a", 0), 10);
+
+        assert_eq!(
+            manager.query_then_erase_reset_offset("topic_a", "group_a", 0),
+            Some(10)
+        );
+        assert!(!manager.has_offset_reset("topic_a", "group_a", 0));
+
+        manager.assign_reset_offset("topic_a", "group_a", -1, 10);
+        assert!(!manager.has_offset_reset("topic_a", "group_a", -1));
+    }
+
+    #[test]
+    fn clone_offset_and_query_min_offset() {
+        let manager = ConsumerOffsetManager::new(Arc::new(BrokerConfig::default()), None);
+        manager.commit_offset(client_host(), "group_a", "topic_a", 0, 100);
+        manager.commit_offset(client_host(), "group_a", "topic_a", 1, 200);
+        manager.commit_offset(client_host(), "group_b", "topic_a", 0, 50);
+
+        manager.clone_offset("group_a", "group_c", "topic_a");
+        assert_eq!(
+            manager.query_offset_table("group_c", "topic_a"),
+            manager.query_offset_table("group_a", "topic_a")
+        );
+
+        let min_offsets = manager.query_min_offset_in_all_group("topic_a", None);
+        assert_eq!(min_offsets.get(&0), Some(&50));
+        assert_eq!(min_offsets.get(&1), Some(&200));
+
+        let min_offsets = manager.query_min_offset_in_all_group("topic_a", Some("group_b"));
+        assert_eq!(min_offsets.get(&0), Some(&100));
+    }
+}
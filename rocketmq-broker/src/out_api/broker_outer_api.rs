@@ -19,15 +19,31 @@ use std::sync::Arc;
 use dns_lookup::lookup_host;
 use rocketmq_common::common::broker::broker_config::BrokerIdentity;
 use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::utils::crc32_utils;
 use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
+use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
 use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::protocol::body::broker_body::register_broker_body::RegisterBrokerBody;
 use rocketmq_remoting::protocol::body::kv_table::KVTable;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
+use rocketmq_remoting::protocol::command_custom_header::FromMap;
+use rocketmq_remoting::protocol::header::controller::apply_broker_id_request_header::ApplyBrokerIdRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_meta_data_response_header::GetMetaDataResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_next_broker_id_request_header::GetNextBrokerIdRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_next_broker_id_request_header::GetNextBrokerIdResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoResponseHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerResponseHeader;
+use rocketmq_remoting::protocol::header::namesrv::broker_request::BrokerHeartbeatRequestHeader;
 use rocketmq_remoting::protocol::header::namesrv::register_broker_header::RegisterBrokerRequestHeader;
 use rocketmq_remoting::protocol::header::namesrv::register_broker_header::RegisterBrokerResponseHeader;
 use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::RegisterTopicRequestHeader;
@@ -35,6 +51,7 @@ use rocketmq_remoting::protocol::namesrv::RegisterBrokerResult;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::route::route_data_view::QueueData;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::remoting::RemotingService;
 use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
@@ -46,6 +63,11 @@ use tracing::debug;
 use tracing::error;
 use tracing::info;
 
+use crate::error::BrokerError;
+use crate::BrokerResult;
+
+const CONTROLLER_TIMEOUT_MILLIS: u64 = 3000;
+
 #[derive(Clone)]
 pub struct BrokerOuterAPI {
     remoting_client: RocketmqDefaultClient,
@@ -142,6 +164,7 @@ impl BrokerOuterAPI {
         enable_acting_master: bool,
         compressed: bool,
         heartbeat_timeout_millis: Option<i64>,
+        zone_name: Option<String>,
         _broker_identity: BrokerIdentity,
     ) -> Vec<RegisterBrokerResult> {
         let name_server_address_list = self.remoting_client.get_available_name_srv_list();
@@ -174,9 +197,17 @@ impl BrokerOuterAPI {
                 let cloned_header = request_header.clone();
                 let addr = namesrv_addr.clone();
                 let outer_api = self.clone();
+                let zone_name = zone_name.clone();
                 let join_handle = tokio::spawn(async move {
                     outer_api
-                        .register_broker(addr, oneway, timeout_mills, cloned_header, cloned_body)
+                        .register_broker(
+                            addr,
+                            oneway,
+                            timeout_mills,
+                            cloned_header,
+                            cloned_body,
+                            zone_name,
+                        )
                         .await
                 });
                 /*let handle =
@@ -210,6 +241,7 @@ impl BrokerOuterAPI {
         timeout_mills: u64,
         request_header: RegisterBrokerRequestHeader,
         body: Vec<u8>,
+        zone_name: Option<String>,
     ) -> Option<RegisterBrokerResult> {
         debug!(
             "Register broker to name remoting_server, namesrv_addr={},request_code={:?}, \
@@ -219,9 +251,12 @@ impl BrokerOuterAPI {
             request_header,
             body
         );
-        let request =
+        let mut request =
             RemotingCommand::create_request_command(RequestCode::RegisterBroker, request_header)
                 .set_body(Some(body.clone()));
+        if let Some(zone_name) = zone_name {
+            request.add_ext_field(mix_all::ZONE_NAME, zone_name);
+        }
         if oneway {
             self.remoting_client
                 .invoke_oneway(namesrv_addr, request, timeout_mills)
@@ -302,6 +337,198 @@ impl BrokerOuterAPI {
     }
 }
 
+/// Requests to the controller, see `enable_controller_mode`.
+impl BrokerOuterAPI {
+    async fn invoke_controller(
+        &self,
+        controller_address: &str,
+        request: RemotingCommand,
+        accepted_codes: &[ResponseCode],
+    ) -> BrokerResult<RemotingCommand> {
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(controller_address.to_string()),
+                request,
+                CONTROLLER_TIMEOUT_MILLIS,
+            )
+            .await?;
+        let response_code = ResponseCode::from(response.code());
+        if response_code == ResponseCode::Success || accepted_codes.contains(&response_code) {
+            Ok(response)
+        } else {
+            Err(BrokerError::ControllerResponseError(
+                controller_address.to_string(),
+                response.code(),
+                response.remark().cloned().unwrap_or_default(),
+            ))
+        }
+    }
+
+    fn decode_controller_header<T>(
+        controller_address: &str,
+        response: &RemotingCommand,
+    ) -> BrokerResult<T>
+    where
+        T: FromMap<Target = T>,
+    {
+        response.decode_command_custom_header::<T>().ok_or_else(|| {
+            BrokerError::ControllerResponseError(
+                controller_address.to_string(),
+                response.code(),
+                "missing response header".to_string(),
+            )
+        })
+    }
+
+    fn decode_sync_state_set(response: &RemotingCommand) -> Option<SyncStateSet> {
+        response
+            .body()
+            .as_ref()
+            .and_then(|body| SyncStateSet::decode(body.as_ref()).ok())
+    }
+
+    pub async fn get_controller_metadata(
+        &self,
+        controller_address: &str,
+    ) -> BrokerResult<GetMetaDataResponseHeader> {
+        let request = RemotingCommand::create_remoting_command(
+            ControllerRequestCode::ControllerGetMetadataInfo,
+        );
+        let response = self
+            .invoke_controller(controller_address, request, &[])
+            .await?;
+        Self::decode_controller_header(controller_address, &response)
+    }
+
+    pub async fn get_next_broker_id(
+        &self,
+        controller_address: &str,
+        cluster_name: &str,
+        broker_name: &str,
+    ) -> BrokerResult<GetNextBrokerIdResponseHeader> {
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerGetNextBrokerId,
+            GetNextBrokerIdRequestHeader {
+                cluster_name: cluster_name.to_string(),
+                broker_name: broker_name.to_string(),
+            },
+        );
+        let response = self
+            .invoke_controller(controller_address, request, &[])
+            .await?;
+        Self::decode_controller_header(controller_address, &response)
+    }
+
+    pub async fn apply_broker_id(
+        &self,
+        controller_address: &str,
+        cluster_name: &str,
+        broker_name: &str,
+        broker_id: i64,
+        register_check_code: &str,
+    ) -> BrokerResult<()> {
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerApplyBrokerId,
+            ApplyBrokerIdRequestHeader {
+                cluster_name: cluster_name.to_string(),
+                broker_name: broker_name.to_string(),
+                applied_broker_id: broker_id,
+                register_check_code: register_check_code.to_string(),
+            },
+        );
+        self.invoke_controller(controller_address, request, &[])
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn register_broker_to_controller(
+        &self,
+        controller_address: &str,
+        request_header: RegisterBrokerToControllerRequestHeader,
+    ) -> BrokerResult<(
+        RegisterBrokerToControllerResponseHeader,
+        Option<SyncStateSet>,
+    )> {
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerRegisterBroker,
+            request_header,
+        );
+        let response = self
+            .invoke_controller(controller_address, request, &[])
+            .await?;
+        Ok((
+            Self::decode_controller_header(controller_address, &response)?,
+            Self::decode_sync_state_set(&response),
+        ))
+    }
+
+    /// Asks the controller to elect a master, favoring this broker. Succeeds as well when the
+    /// broker set still has a master, the response then describes it.
+    pub async fn elect_master(
+        &self,
+        controller_address: &str,
+        cluster_name: &str,
+        broker_name: &str,
+        broker_id: i64,
+    ) -> BrokerResult<ElectMasterResponseHeader> {
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerElectMaster,
+            ElectMasterRequestHeader {
+                cluster_name: cluster_name.to_string(),
+                broker_name: broker_name.to_string(),
+                broker_id: Some(broker_id),
+                designate_elect: false,
+                invoke_time: get_current_millis() as i64,
+            },
+        );
+        let response = self
+            .invoke_controller(
+                controller_address,
+                request,
+                &[ResponseCode::ControllerMasterStillExist],
+            )
+            .await?;
+        Self::decode_controller_header(controller_address, &response)
+    }
+
+    pub async fn get_replica_info(
+        &self,
+        controller_address: &str,
+        broker_name: &str,
+    ) -> BrokerResult<(GetReplicaInfoResponseHeader, Option<SyncStateSet>)> {
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerGetReplicaInfo,
+            GetReplicaInfoRequestHeader {
+                broker_name: broker_name.to_string(),
+            },
+        );
+        let response = self
+            .invoke_controller(controller_address, request, &[])
+            .await?;
+        Ok((
+            Self::decode_controller_header(controller_address, &response)?,
+            Self::decode_sync_state_set(&response),
+        ))
+    }
+
+    pub async fn send_heartbeat_to_controller(
+        &self,
+        controller_address: &str,
+        request_header: BrokerHeartbeatRequestHeader,
+    ) {
+        let request =
+            RemotingCommand::create_request_command(RequestCode::BrokerHeartbeat, request_header);
+        self.remoting_client
+            .invoke_oneway(
+                controller_address.to_string(),
+                request,
+                CONTROLLER_TIMEOUT_MILLIS,
+            )
+            .await;
+    }
+}
+
 fn dns_lookup_address_by_domain(domain: &str) -> Vec<String> {
     let mut address_list = Vec::new();
     // Ensure logging is initialized
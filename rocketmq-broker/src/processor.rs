@@ -14,6 +14,9 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
@@ -24,6 +27,7 @@ use rocketmq_store::log_file::MessageStore;
 use tracing::info;
 
 use self::client_manage_processor::ClientManageProcessor;
+use crate::controller::replicas_manager::ReplicasManager;
 use crate::processor::ack_message_processor::AckMessageProcessor;
 use crate::processor::admin_broker_processor::AdminBrokerProcessor;
 use crate::processor::change_invisible_time_processor::ChangeInvisibleTimeProcessor;
@@ -77,6 +81,7 @@ where
     pub(crate) query_assignment_processor: QueryAssignmentProcessor,
     pub(crate) end_transaction_processor: EndTransactionProcessor,
     pub(crate) admin_broker_processor: AdminBrokerProcessor,
+    pub(crate) replicas_manager: Arc<ReplicasManager>,
 }
 impl<MS: Clone> Clone for BrokerRequestProcessor<MS> {
     fn clone(&self) -> Self {
@@ -96,6 +101,7 @@ impl<MS: Clone> Clone for BrokerRequestProcessor<MS> {
             query_assignment_processor: self.query_assignment_processor.clone(),
             query_message_processor: self.query_message_processor.clone(),
             end_transaction_processor: self.end_transaction_processor.clone(),
+            replicas_manager: self.replicas_manager.clone(),
         }
     }
 }
@@ -107,6 +113,12 @@ impl<MS: MessageStore + Send + Sync + 'static> RequestProcessor for BrokerReques
         ctx: ConnectionHandlerContext,
         request: RemotingCommand,
     ) -> Result<Option<RemotingCommand>> {
+        if request.code() == ControllerRequestCode::NotifyBrokerRoleChanged.to_i32() {
+            return Ok(Some(
+                self.replicas_manager
+                    .handle_notify_broker_role_changed(&request),
+            ));
+        }
         let request_code = RequestCode::from(request.code());
         info!("process_request: {:?}", request_code);
         let result = match request_code {
@@ -18,6 +18,8 @@ use std::sync::Arc;
 
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_remoting::acl::plain_permission_manager::PlainPermissionManager;
+use rocketmq_remoting::base::remoting_metrics::StatsRemotingMetricsSink;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
@@ -30,20 +32,26 @@ use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
 use tracing::warn;
 
 use crate::client::manager::consumer_manager::ConsumerManager;
+use crate::client::net::broker_to_client::Broker2Client;
 use crate::offset::manager::consumer_offset_manager::ConsumerOffsetManager;
 use crate::out_api::broker_outer_api::BrokerOuterAPI;
+use crate::processor::admin_broker_processor::acl_request_handler::AclRequestHandler;
 use crate::processor::admin_broker_processor::broker_config_request_handler::BrokerConfigRequestHandler;
 use crate::processor::admin_broker_processor::consumer_request_handler::ConsumerRequestHandler;
 use crate::processor::admin_broker_processor::offset_request_handler::OffsetRequestHandler;
+use crate::processor::admin_broker_processor::subscription_group_request_handler::SubscriptionGroupRequestHandler;
 use crate::processor::admin_broker_processor::topic_request_handler::TopicRequestHandler;
 use crate::processor::pop_inflight_message_counter::PopInflightMessageCounter;
 use crate::schedule::schedule_message_service::ScheduleMessageService;
+use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
 use crate::topic::manager::topic_config_manager::TopicConfigManager;
 use crate::topic::manager::topic_queue_mapping_manager::TopicQueueMappingManager;
 
+mod acl_request_handler;
 mod broker_config_request_handler;
 mod consumer_request_handler;
 mod offset_request_handler;
+mod subscription_group_request_handler;
 mod topic_request_handler;
 
 #[derive(Clone)]
@@ -52,6 +60,8 @@ pub struct AdminBrokerProcessor {
     broker_config_request_handler: BrokerConfigRequestHandler,
     consumer_request_handler: ConsumerRequestHandler,
     offset_request_handler: OffsetRequestHandler,
+    acl_request_handler: AclRequestHandler,
+    subscription_group_request_handler: SubscriptionGroupRequestHandler,
 }
 
 impl AdminBrokerProcessor {
@@ -61,6 +71,7 @@ impl AdminBrokerProcessor {
         message_store_config: Arc<MessageStoreConfig>,
         topic_config_manager: TopicConfigManager,
         consumer_offset_manager: ConsumerOffsetManager,
+        subscription_group_manager: Arc<SubscriptionGroupManager<DefaultMessageStore>>,
         topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
         default_message_store: DefaultMessageStore,
         schedule_message_service: ScheduleMessageService,
@@ -68,6 +79,8 @@ impl AdminBrokerProcessor {
         consume_manager: Arc<ConsumerManager>,
         broker_out_api: Arc<BrokerOuterAPI>,
         broker_stats_manager: Arc<BrokerStatsManager>,
+        plain_permission_manager: Option<Arc<PlainPermissionManager>>,
+        remoting_metrics_sink: Arc<StatsRemotingMetricsSink>,
     ) -> Self {
         let inner = Inner {
             broker_config,
@@ -75,6 +88,7 @@ impl AdminBrokerProcessor {
             message_store_config,
             topic_config_manager,
             consumer_offset_manager,
+            subscription_group_manager,
             topic_queue_mapping_manager,
             default_message_store,
             pop_inflight_message_counter: Arc::new(PopInflightMessageCounter),
@@ -83,16 +97,24 @@ impl AdminBrokerProcessor {
             consume_manager,
             broker_out_api,
             broker_stats_manager,
+            plain_permission_manager,
+            broker_to_client: Broker2Client,
+            remoting_metrics_sink,
         };
         let topic_request_handler = TopicRequestHandler::new(inner.clone());
         let broker_config_request_handler = BrokerConfigRequestHandler::new(inner.clone());
         let consumer_request_handler = ConsumerRequestHandler::new(inner.clone());
         let offset_request_handler = OffsetRequestHandler::new(inner.clone());
+        let acl_request_handler = AclRequestHandler::new(inner.clone());
+        let subscription_group_request_handler =
+            SubscriptionGroupRequestHandler::new(inner.clone());
         AdminBrokerProcessor {
             topic_request_handler,
             broker_config_request_handler,
             consumer_request_handler,
             offset_request_handler,
+            acl_request_handler,
+            subscription_group_request_handler,
         }
     }
 }
@@ -191,6 +213,86 @@ impl AdminBrokerProcessor {
                     .get_min_offset(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::UpdateAndCreateAclConfig => {
+                self.acl_request_handler
+                    .update_and_create_acl_config(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::DeleteAclConfig => {
+                self.acl_request_handler
+                    .delete_acl_config(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::UpdateGlobalWhiteAddrsConfig => {
+                self.acl_request_handler
+                    .update_global_white_addrs_config(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetBrokerClusterAclConfig => {
+                self.acl_request_handler
+                    .get_broker_cluster_acl_config(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::SearchOffsetByTimestamp => {
+                self.offset_request_handler
+                    .search_offset_by_timestamp(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetEarliestMsgStoreTime => {
+                self.offset_request_handler
+                    .get_earliest_msg_store_time(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::ResetConsumerOffsetInBroker => {
+                self.offset_request_handler
+                    .reset_consumer_offset_in_broker(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::InvokeBrokerToResetOffset => {
+                self.offset_request_handler
+                    .invoke_broker_to_reset_offset(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::CloneGroupOffset => {
+                self.offset_request_handler
+                    .clone_group_offset(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::QueryCorrectionOffset => {
+                self.offset_request_handler
+                    .query_correction_offset(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::QueryConsumeTimeSpan => {
+                self.consumer_request_handler
+                    .query_consume_time_span(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::UpdateAndCreateSubscriptionGroup => {
+                self.subscription_group_request_handler
+                    .update_and_create_subscription_group(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetAllSubscriptionGroupConfig => {
+                self.subscription_group_request_handler
+                    .get_all_subscription_group_config(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetSubscriptionGroupConfig => {
+                self.subscription_group_request_handler
+                    .get_subscription_group_config(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::DeleteSubscriptionGroup => {
+                self.subscription_group_request_handler
+                    .delete_subscription_group(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::UpdateAndGetGroupForbidden => {
+                self.subscription_group_request_handler
+                    .update_and_get_group_forbidden(channel, ctx, request_code, request)
+                    .await
+            }
 
             _ => Some(get_unknown_cmd_response(request_code)),
         }
@@ -209,6 +311,14 @@ fn get_unknown_cmd_response(request_code: RequestCode) -> RemotingCommand {
     )
 }
 
+/// Response to a request whose custom header is missing or malformed.
+fn invalid_request_header() -> RemotingCommand {
+    RemotingCommand::create_response_command_with_code_remark(
+        ResponseCode::SystemError,
+        "decode request header failed",
+    )
+}
+
 #[derive(Clone)]
 struct Inner {
     broker_config: Arc<BrokerConfig>,
@@ -216,6 +326,7 @@ struct Inner {
     message_store_config: Arc<MessageStoreConfig>,
     topic_config_manager: TopicConfigManager,
     consumer_offset_manager: ConsumerOffsetManager,
+    subscription_group_manager: Arc<SubscriptionGroupManager<DefaultMessageStore>>,
     topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
     default_message_store: DefaultMessageStore,
     pop_inflight_message_counter: Arc<PopInflightMessageCounter>,
@@ -224,4 +335,7 @@ struct Inner {
     consume_manager: Arc<ConsumerManager>,
     broker_out_api: Arc<BrokerOuterAPI>,
     broker_stats_manager: Arc<BrokerStatsManager>,
+    plain_permission_manager: Option<Arc<PlainPermissionManager>>,
+    broker_to_client: Broker2Client,
+    remoting_metrics_sink: Arc<StatsRemotingMetricsSink>,
 }
@@ -0,0 +1,184 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_remoting::acl::plain_access_config::PlainAccessConfig;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::create_access_config_request_header::CreateAccessConfigRequestHeader;
+use rocketmq_remoting::protocol::header::delete_access_config_request_header::DeleteAccessConfigRequestHeader;
+use rocketmq_remoting::protocol::header::get_broker_acl_config_response_header::GetBrokerAclConfigResponseHeader;
+use rocketmq_remoting::protocol::header::update_global_white_addrs_config_request_header::UpdateGlobalWhiteAddrsConfigRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use tracing::info;
+use tracing::warn;
+
+use crate::processor::admin_broker_processor::Inner;
+
+#[derive(Clone)]
+pub(super) struct AclRequestHandler {
+    inner: Inner,
+}
+
+impl AclRequestHandler {
+    pub fn new(inner: Inner) -> Self {
+        AclRequestHandler { inner }
+    }
+}
+
+impl AclRequestHandler {
+    pub async fn update_and_create_acl_config(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let Some(permission_manager) = self.inner.plain_permission_manager.clone() else {
+            return Some(acl_not_enabled());
+        };
+        let request_header =
+            match request.decode_command_custom_header::<CreateAccessConfigRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(system_error("decode request header failed".to_string())),
+            };
+        let access_config = PlainAccessConfig::from(request_header);
+        let access_key = access_config.access_key.clone();
+        info!(
+            "update acl config of accessKey={}, caller={}",
+            access_key,
+            channel.remote_address()
+        );
+        Some(
+            match permission_manager.update_access_config(access_config) {
+                Ok(_) => RemotingCommand::create_response_command(),
+                Err(err) => {
+                    warn!(
+                        "update acl config of accessKey={} failed: {}",
+                        access_key, err
+                    );
+                    system_error(err)
+                }
+            },
+        )
+    }
+
+    pub async fn delete_acl_config(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let Some(permission_manager) = self.inner.plain_permission_manager.clone() else {
+            return Some(acl_not_enabled());
+        };
+        let request_header =
+            match request.decode_command_custom_header::<DeleteAccessConfigRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(system_error("decode request header failed".to_string())),
+            };
+        let access_key = request_header.access_key.as_str();
+        info!(
+            "delete acl config of accessKey={}, caller={}",
+            access_key,
+            channel.remote_address()
+        );
+        Some(match permission_manager.delete_access_config(access_key) {
+            Ok(true) => RemotingCommand::create_response_command(),
+            Ok(false) => system_error(format!("No acl config for {}", access_key)),
+            Err(err) => {
+                warn!(
+                    "delete acl config of accessKey={} failed: {}",
+                    access_key, err
+                );
+                system_error(err)
+            }
+        })
+    }
+
+    pub async fn update_global_white_addrs_config(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let Some(permission_manager) = self.inner.plain_permission_manager.clone() else {
+            return Some(acl_not_enabled());
+        };
+        let request_header = match request
+            .decode_command_custom_header::<UpdateGlobalWhiteAddrsConfigRequestHeader>()
+        {
+            Some(request_header) => request_header,
+            None => return Some(system_error("decode request header failed".to_string())),
+        };
+        let addresses: Vec<String> = request_header
+            .global_white_addrs
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(str::to_string)
+            .collect();
+        info!(
+            "update global white addresses to {:?}, caller={}",
+            addresses,
+            channel.remote_address()
+        );
+        Some(
+            match permission_manager.update_global_white_addrs(addresses) {
+                Ok(_) => RemotingCommand::create_response_command(),
+                Err(err) => {
+                    warn!("update global white addresses failed: {}", err);
+                    system_error(err)
+                }
+            },
+        )
+    }
+
+    pub async fn get_broker_cluster_acl_config(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let Some(permission_manager) = self.inner.plain_permission_manager.clone() else {
+            return Some(acl_not_enabled());
+        };
+        let broker_config = &self.inner.broker_config;
+        let response_header = GetBrokerAclConfigResponseHeader {
+            version: permission_manager.data_version().to_string(),
+            broker_name: broker_config.broker_name.clone(),
+            broker_addr: broker_config.get_broker_addr(),
+            cluster_name: broker_config.broker_identity.broker_cluster_name.clone(),
+        };
+        Some(
+            RemotingCommand::create_response_command_with_header(response_header)
+                .set_body(Some(permission_manager.acl_config().encode())),
+        )
+    }
+}
+
+fn acl_not_enabled() -> RemotingCommand {
+    system_error("The broker does not enable acl".to_string())
+}
+
+fn system_error(remark: String) -> RemotingCommand {
+    RemotingCommand::create_response_command_with_code_remark(ResponseCode::SystemError, remark)
+}
@@ -192,6 +192,10 @@ impl BrokerConfigRequestHandler {
                 .get_start_accept_send_request_time_stamp()
                 .to_string(),
         );
+        // Per request code count, latency distribution and errors of both remoting servers
+        for stats in self.inner.remoting_metrics_sink.snapshot() {
+            runtime_info.insert(format!("rpcStats@{}", stats.stats_key()), stats.to_string());
+        }
         let is_timer_wheel_enable = self.inner.message_store_config.is_timer_wheel_enable();
         if is_timer_wheel_enable {
             runtime_info.insert(
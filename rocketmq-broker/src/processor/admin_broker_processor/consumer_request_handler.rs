@@ -19,6 +19,7 @@ use std::collections::HashSet;
 
 use rocketmq_common::common::config_manager::ConfigManager;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
@@ -26,14 +27,18 @@ use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
 use rocketmq_remoting::protocol::admin::offset_wrapper::OffsetWrapper;
 use rocketmq_remoting::protocol::body::connection::Connection;
 use rocketmq_remoting::protocol::body::consumer_connection::ConsumerConnection;
+use rocketmq_remoting::protocol::body::query_consume_time_span_body::QueryConsumeTimeSpanBody;
+use rocketmq_remoting::protocol::body::queue_time_span::QueueTimeSpan;
 use rocketmq_remoting::protocol::header::get_consume_stats_request_header::GetConsumeStatsRequestHeader;
 use rocketmq_remoting::protocol::header::get_consumer_connection_list_request_header::GetConsumerConnectionListRequestHeader;
+use rocketmq_remoting::protocol::header::query_consume_time_span_request_header::QueryConsumeTimeSpanRequestHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use rocketmq_store::log_file::MessageStore;
 use tracing::warn;
 
+use crate::processor::admin_broker_processor::invalid_request_header;
 use crate::processor::admin_broker_processor::Inner;
 
 #[derive(Clone)]
@@ -56,9 +61,12 @@ impl ConsumerRequestHandler {
         request: RemotingCommand,
     ) -> Option<RemotingCommand> {
         let mut response = RemotingCommand::create_response_command();
-        let request_header = request
+        let request_header = match request
             .decode_command_custom_header::<GetConsumerConnectionListRequestHeader>()
-            .unwrap();
+        {
+            Some(request_header) => request_header,
+            None => return Some(invalid_request_header()),
+        };
         let consumer_group_info = self
             .inner
             .consume_manager
@@ -106,9 +114,11 @@ impl ConsumerRequestHandler {
         request: RemotingCommand,
     ) -> Option<RemotingCommand> {
         let mut response = RemotingCommand::create_response_command();
-        let request_header = request
-            .decode_command_custom_header::<GetConsumeStatsRequestHeader>()
-            .unwrap();
+        let request_header =
+            match request.decode_command_custom_header::<GetConsumeStatsRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
         let mut consume_stats = ConsumeStats::new();
         let mut topics = HashSet::new();
         if request_header.get_topic().is_empty() {
@@ -218,6 +228,69 @@ impl ConsumerRequestHandler {
         Some(response)
     }
 
+    pub async fn query_consume_time_span(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<QueryConsumeTimeSpanRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
+        let topic = request_header.topic.as_str();
+        let group = request_header.group.as_str();
+        let Some(topic_config) = self.inner.topic_config_manager.select_topic_config(topic) else {
+            return Some(
+                response
+                    .set_code(ResponseCode::TopicNotExist)
+                    .set_remark(Some(format!("topic[{}] not exist", topic))),
+            );
+        };
+        let store = &self.inner.default_message_store;
+        let now = get_current_millis() as i64;
+        let mut body = QueryConsumeTimeSpanBody::default();
+        for queue_id in 0..topic_config.write_queue_nums as i32 {
+            let min_time_stamp = store.get_earliest_message_time_in_queue(topic, queue_id);
+            let max_offset = store.get_max_offset_in_queue(topic, queue_id);
+            let max_time_stamp = store.get_message_store_timestamp(topic, queue_id, max_offset - 1);
+            let consumer_offset = self
+                .inner
+                .consumer_offset_manager
+                .query_offset(group, topic, queue_id);
+            let consume_time_stamp = if consumer_offset > 0 {
+                store.get_message_store_timestamp(topic, queue_id, consumer_offset - 1)
+            } else {
+                min_time_stamp
+            };
+            let mut delay_time = 0;
+            if consumer_offset < max_offset {
+                let next_offset =
+                    consumer_offset.max(store.get_min_offset_in_queue(topic, queue_id));
+                let next_time_stamp =
+                    store.get_message_store_timestamp(topic, queue_id, next_offset);
+                if next_time_stamp > 0 {
+                    delay_time = now - next_time_stamp;
+                }
+            }
+            body.consume_time_span_set.push(QueueTimeSpan {
+                message_queue: MessageQueue::from_parts(
+                    topic,
+                    self.inner.broker_config.broker_name.as_str(),
+                    queue_id,
+                ),
+                min_time_stamp,
+                max_time_stamp,
+                consume_time_stamp,
+                delay_time,
+            });
+        }
+        Some(response.set_body(Some(body.encode())))
+    }
+
     pub async fn get_all_consumer_offset(
         &mut self,
         _channel: Channel,
@@ -14,22 +14,41 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::collections::HashSet;
+
+use rocketmq_common::common::boundary_type::BoundaryType;
+use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::query_correction_offset_body::QueryCorrectionOffsetBody;
+use rocketmq_remoting::protocol::body::reset_offset_body::ResetOffsetBody;
+use rocketmq_remoting::protocol::header::clone_group_offset_request_header::CloneGroupOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::get_earliest_msg_storetime_request_header::GetEarliestMsgStoretimeRequestHeader;
+use rocketmq_remoting::protocol::header::get_earliest_msg_storetime_response_header::GetEarliestMsgStoretimeResponseHeader;
 use rocketmq_remoting::protocol::header::get_max_offset_request_header::GetMaxOffsetRequestHeader;
 use rocketmq_remoting::protocol::header::get_max_offset_response_header::GetMaxOffsetResponseHeader;
 use rocketmq_remoting::protocol::header::get_min_offset_request_header::GetMinOffsetRequestHeader;
 use rocketmq_remoting::protocol::header::get_min_offset_response_header::GetMinOffsetResponseHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::TopicRequestHeaderTrait;
+use rocketmq_remoting::protocol::header::query_correction_offset_header::QueryCorrectionOffsetHeader;
+use rocketmq_remoting::protocol::header::reset_offset_request_header::ResetOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::search_offset_request_header::SearchOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::search_offset_response_header::SearchOffsetResponseHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_context::TopicQueueMappingContext;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_utils::TopicQueueMappingUtils;
+use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::rpc::rpc_client::RpcClient;
 use rocketmq_remoting::rpc::rpc_request::RpcRequest;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_store::config::broker_role::BrokerRole;
 use rocketmq_store::log_file::MessageStore;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
 
+use crate::processor::admin_broker_processor::invalid_request_header;
 use crate::processor::admin_broker_processor::Inner;
 
 #[derive(Clone)]
@@ -51,7 +70,11 @@ impl OffsetRequestHandler {
         _request_code: RequestCode,
         request: RemotingCommand,
     ) -> Option<RemotingCommand> {
-        let request_header = request.decode_command_custom_header::<GetMaxOffsetRequestHeader>()?;
+        let request_header =
+            match request.decode_command_custom_header::<GetMaxOffsetRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
         let mapping_context = self
             .inner
             .topic_queue_mapping_manager
@@ -82,7 +105,11 @@ impl OffsetRequestHandler {
         _request_code: RequestCode,
         request: RemotingCommand,
     ) -> Option<RemotingCommand> {
-        let request_header = request.decode_command_custom_header::<GetMinOffsetRequestHeader>()?;
+        let request_header =
+            match request.decode_command_custom_header::<GetMinOffsetRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
 
         let mapping_context = self
             .inner
@@ -106,6 +133,395 @@ impl OffsetRequestHandler {
             response_header,
         ))
     }
+    pub async fn search_offset_by_timestamp(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header =
+            match request.decode_command_custom_header::<SearchOffsetRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
+        let offset = self
+            .inner
+            .default_message_store
+            .get_offset_in_queue_by_time(
+                request_header.topic.as_str(),
+                request_header.queue_id,
+                request_header.timestamp,
+                request_header.boundary_type,
+            );
+        Some(RemotingCommand::create_response_command_with_header(
+            SearchOffsetResponseHeader { offset },
+        ))
+    }
+
+    pub async fn get_earliest_msg_store_time(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header =
+            match request.decode_command_custom_header::<GetEarliestMsgStoretimeRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
+        let timestamp = self
+            .inner
+            .default_message_store
+            .get_earliest_message_time_in_queue(
+                request_header.topic.as_str(),
+                request_header.queue_id,
+            );
+        Some(RemotingCommand::create_response_command_with_header(
+            GetEarliestMsgStoretimeResponseHeader { timestamp },
+        ))
+    }
+
+    pub async fn reset_consumer_offset_in_broker(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header =
+            match request.decode_command_custom_header::<ResetOffsetRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
+        info!(
+            "[reset-offset] reset offset in broker started by {}. topic={}, group={}, queueId={}, \
+             offset={:?}, timestamp={}",
+            channel.remote_address(),
+            request_header.topic,
+            request_header.group,
+            request_header.queue_id,
+            request_header.offset,
+            request_header.timestamp
+        );
+        Some(self.reset_offset_in_broker(&request_header))
+    }
+
+    pub async fn invoke_broker_to_reset_offset(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header =
+            match request.decode_command_custom_header::<ResetOffsetRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
+        info!(
+            "[reset-offset] reset offset started by {}. topic={}, group={}, timestamp={}, \
+             isForce={}",
+            channel.remote_address(),
+            request_header.topic,
+            request_header.group,
+            request_header.timestamp,
+            request_header.is_force
+        );
+        if self.inner.broker_config.use_server_side_reset_offset {
+            return Some(self.reset_offset_in_broker(&request_header));
+        }
+        Some(self.reset_offset_in_clients(&request_header).await)
+    }
+
+    pub async fn clone_group_offset(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header =
+            match request.decode_command_custom_header::<CloneGroupOffsetRequestHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
+        let src_group = request_header.src_group.as_str();
+        let topics = match request_header.topic.as_deref() {
+            Some(topic) if !topic.trim().is_empty() => HashSet::from([topic.to_string()]),
+            _ => self
+                .inner
+                .consumer_offset_manager
+                .which_topic_by_consumer(src_group),
+        };
+        for topic in topics.iter() {
+            if self
+                .inner
+                .topic_config_manager
+                .select_topic_config(topic)
+                .is_none()
+            {
+                warn!("[cloneGroupOffset], topic config not exist, {}", topic);
+                continue;
+            }
+            if !request_header.offline
+                && self
+                    .inner
+                    .consume_manager
+                    .find_subscription_data_count(src_group)
+                    > 0
+                && self
+                    .inner
+                    .consume_manager
+                    .find_subscription_data(src_group, topic)
+                    .is_none()
+            {
+                warn!(
+                    "[cloneGroupOffset], the consumer group[{}], topic[{}] not exist",
+                    src_group, topic
+                );
+                continue;
+            }
+            self.inner.consumer_offset_manager.clone_offset(
+                src_group,
+                request_header.dest_group.as_str(),
+                topic,
+            );
+        }
+        Some(RemotingCommand::create_response_command())
+    }
+
+    pub async fn query_correction_offset(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header =
+            match request.decode_command_custom_header::<QueryCorrectionOffsetHeader>() {
+                Some(request_header) => request_header,
+                None => return Some(invalid_request_header()),
+            };
+        let topic = request_header.topic.as_str();
+        let mut correction_offsets = self
+            .inner
+            .consumer_offset_manager
+            .query_min_offset_in_all_group(topic, request_header.filter_groups.as_deref());
+        let compare_offsets = self
+            .inner
+            .consumer_offset_manager
+            .query_offset_table(request_header.compare_group.as_str(), topic)
+            .unwrap_or_default();
+        for (queue_id, compare_offset) in compare_offsets {
+            if let Some(correction_offset) = correction_offsets.get_mut(&queue_id) {
+                if *correction_offset > compare_offset {
+                    *correction_offset = i64::MAX;
+                }
+            }
+        }
+        let body = QueryCorrectionOffsetBody { correction_offsets };
+        Some(RemotingCommand::create_response_command().set_body(Some(body.encode())))
+    }
+
+    /// Moves the offsets on the broker, the consumers pick them up with their next pull.
+    fn reset_offset_in_broker(&self, request_header: &ResetOffsetRequestHeader) -> RemotingCommand {
+        let response = RemotingCommand::create_response_command();
+        if self.inner.message_store_config.broker_role == BrokerRole::Slave {
+            return response
+                .set_code(ResponseCode::SystemError)
+                .set_remark(Some("Can not reset offset in slave broker".to_string()));
+        }
+        let topic = request_header.topic.as_str();
+        let group = request_header.group.as_str();
+        let Some(topic_config) = self.inner.topic_config_manager.select_topic_config(topic) else {
+            return response
+                .set_code(ResponseCode::TopicNotExist)
+                .set_remark(Some(format!("Topic {} does not exist", topic)));
+        };
+        if !self
+            .inner
+            .subscription_group_manager
+            .contains_subscription_group(group)
+        {
+            return response
+                .set_code(ResponseCode::SubscriptionGroupNotExist)
+                .set_remark(Some(format!("Group {} does not exist", group)));
+        }
+        let queue_ids = if request_header.queue_id >= 0 {
+            vec![request_header.queue_id]
+        } else {
+            (0..topic_config.read_queue_nums as i32).collect()
+        };
+        let store = &self.inner.default_message_store;
+        let mut queue_offsets = Vec::with_capacity(queue_ids.len());
+        for queue_id in queue_ids {
+            let offset = match request_header.offset {
+                Some(offset) if request_header.queue_id >= 0 && offset >= 0 => {
+                    let min_offset = store.get_min_offset_in_queue(topic, queue_id);
+                    let max_offset = store.get_max_offset_in_queue(topic, queue_id);
+                    if offset < min_offset || offset > max_offset {
+                        return response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(Some(format!(
+                                "Target offset {} not in consume queue range [{}-{}]",
+                                offset, min_offset, max_offset
+                            )));
+                    }
+                    offset
+                }
+                _ => self.search_offset_for_reset(topic, queue_id, request_header.timestamp),
+            };
+            queue_offsets.push((queue_id, offset));
+        }
+        if queue_offsets.is_empty() {
+            return response
+                .set_code(ResponseCode::SystemError)
+                .set_remark(Some("No queues to reset.".to_string()));
+        }
+
+        let mut body = ResetOffsetBody::default();
+        for (queue_id, offset) in queue_offsets {
+            self.inner
+                .consumer_offset_manager
+                .assign_reset_offset(topic, group, queue_id, offset);
+            body.offset_table.insert(
+                MessageQueue::from_parts(
+                    topic,
+                    self.inner.broker_config.broker_name.as_str(),
+                    queue_id,
+                ),
+                offset,
+            );
+        }
+        info!(
+            "Reset offset, topic={}, group={}, queues={:?}",
+            topic, group, body.offset_table
+        );
+        response.set_body(Some(body.encode()))
+    }
+
+    /// Computes the offsets and pushes them to the online consumers of the group with
+    /// `ResetConsumerClientOffset`.
+    async fn reset_offset_in_clients(
+        &self,
+        request_header: &ResetOffsetRequestHeader,
+    ) -> RemotingCommand {
+        let response = RemotingCommand::create_response_command();
+        let topic = request_header.topic.as_str();
+        let group = request_header.group.as_str();
+        let Some(topic_config) = self.inner.topic_config_manager.select_topic_config(topic) else {
+            error!(
+                "[reset-offset] reset offset failed, no topic in this broker. topic={}",
+                topic
+            );
+            return response
+                .set_code(ResponseCode::SystemError)
+                .set_remark(Some(format!(
+                    "[reset-offset] reset offset failed, no topic in this broker. topic={}",
+                    topic
+                )));
+        };
+        let mut body = ResetOffsetBody::default();
+        for queue_id in 0..topic_config.write_queue_nums as i32 {
+            let consumer_offset = self
+                .inner
+                .consumer_offset_manager
+                .query_offset(group, topic, queue_id);
+            if consumer_offset == -1 {
+                return response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(Some(format!("The consumer group <{}> not exist", group)));
+            }
+            let mut timestamp_offset =
+                self.search_offset_for_reset(topic, queue_id, request_header.timestamp);
+            if timestamp_offset < 0 {
+                warn!(
+                    "reset offset is invalid. topic={}, queueId={}, timeStampOffset={}",
+                    topic, queue_id, timestamp_offset
+                );
+                timestamp_offset = 0;
+            }
+            let offset = if request_header.is_force || timestamp_offset < consumer_offset {
+                timestamp_offset
+            } else {
+                consumer_offset
+            };
+            body.offset_table.insert(
+                MessageQueue::from_parts(
+                    topic,
+                    self.inner.broker_config.broker_name.as_str(),
+                    queue_id,
+                ),
+                offset,
+            );
+        }
+
+        let channels = self
+            .inner
+            .consume_manager
+            .get_consumer_group_info(group)
+            .map(|consumer_group_info| consumer_group_info.get_all_channels())
+            .unwrap_or_default();
+        if channels.is_empty() {
+            return response
+                .set_code(ResponseCode::ConsumerNotOnline)
+                .set_remark(Some(format!(
+                    "Consumer not online, so can not reset offset, Group: {} Topic: {} Timestamp: \
+                     {}",
+                    group, topic, request_header.timestamp
+                )));
+        }
+        let body = body.encode();
+        for channel in channels {
+            let notify_header = ResetOffsetRequestHeader {
+                topic: topic.to_string(),
+                group: group.to_string(),
+                timestamp: request_header.timestamp,
+                is_force: request_header.is_force,
+                ..Default::default()
+            };
+            let request = RemotingCommand::create_request_command(
+                RequestCode::ResetConsumerClientOffset,
+                notify_header,
+            )
+            .set_body(Some(body.clone()));
+            match self
+                .inner
+                .broker_to_client
+                .call_client_oneway(&channel, request)
+                .await
+            {
+                Ok(_) => info!(
+                    "[reset-offset] reset offset success. topic={}, group={}, client={}",
+                    topic,
+                    group,
+                    channel.remote_address()
+                ),
+                Err(err) => error!(
+                    "[reset-offset] reset offset exception. topic={}, group={}, client={}, {}",
+                    topic,
+                    group,
+                    channel.remote_address(),
+                    err
+                ),
+            }
+        }
+        response.set_body(Some(body))
+    }
+
+    /// Offset of the first message stored at or after `timestamp`, -1 stands for the end of
+    /// the queue.
+    fn search_offset_for_reset(&self, topic: &str, queue_id: i32, timestamp: i64) -> i64 {
+        let store = &self.inner.default_message_store;
+        if timestamp == -1 {
+            store.get_max_offset_in_queue(topic, queue_id)
+        } else {
+            store.get_offset_in_queue_by_time(topic, queue_id, timestamp, BoundaryType::Lower)
+        }
+    }
+
     /*
     async fn handle_get_min_offset(
         &mut self,
@@ -0,0 +1,243 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::group_forbidden::GroupForbidden;
+use rocketmq_remoting::protocol::header::delete_subscription_group_request_header::DeleteSubscriptionGroupRequestHeader;
+use rocketmq_remoting::protocol::header::get_subscription_group_config_request_header::GetSubscriptionGroupConfigRequestHeader;
+use rocketmq_remoting::protocol::header::update_group_forbidden_request_header::UpdateGroupForbiddenRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use tracing::info;
+
+use crate::processor::admin_broker_processor::Inner;
+use crate::subscription::manager::subscription_group_manager::CHARACTER_MAX_LENGTH;
+
+#[derive(Clone)]
+pub(super) struct SubscriptionGroupRequestHandler {
+    inner: Inner,
+}
+
+impl SubscriptionGroupRequestHandler {
+    pub fn new(inner: Inner) -> Self {
+        SubscriptionGroupRequestHandler { inner }
+    }
+}
+
+impl SubscriptionGroupRequestHandler {
+    pub async fn update_and_create_subscription_group(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let config = match request
+            .body()
+            .as_ref()
+            .map(|body| SubscriptionGroupConfig::decode(body.as_ref()))
+        {
+            Some(Ok(config)) => config,
+            _ => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark(Some("Invalid subscription group config body.".to_string())),
+                );
+            }
+        };
+        info!(
+            "AdminBrokerProcessor#updateAndCreateSubscriptionGroup called by {}, group: {}",
+            channel.remote_address(),
+            config.group_name()
+        );
+        let group = config.group_name();
+        if group.trim().is_empty()
+            || group.len() > CHARACTER_MAX_LENGTH
+            || TopicValidator::is_topic_or_group_illegal(group)
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(Some(format!(
+                        "The specified group[{}] is blank, too long or contains illegal \
+                         characters.",
+                        group
+                    ))),
+            );
+        }
+        let retry_topic = mix_all::get_retry_topic(group);
+        let retry_queue_nums = config.retry_queue_nums().max(0) as u32;
+        self.inner
+            .subscription_group_manager
+            .update_subscription_group_config(config);
+
+        if retry_queue_nums > 0
+            && self
+                .inner
+                .topic_config_manager
+                .select_topic_config(retry_topic.as_str())
+                .is_none()
+        {
+            let mut topic_config = TopicConfig::with_perm(
+                retry_topic,
+                retry_queue_nums,
+                retry_queue_nums,
+                PermName::PERM_READ | PermName::PERM_WRITE,
+            );
+            self.inner
+                .topic_config_manager
+                .update_topic_config(&mut topic_config);
+            if self.inner.broker_config.enable_single_topic_register {
+                self.inner
+                    .topic_config_manager
+                    .broker_runtime_inner()
+                    .register_single_topic_all(topic_config)
+                    .await;
+            } else {
+                self.inner
+                    .topic_config_manager
+                    .broker_runtime_inner()
+                    .register_increment_broker_data(
+                        vec![topic_config],
+                        self.inner
+                            .topic_config_manager
+                            .data_version()
+                            .as_ref()
+                            .clone(),
+                    )
+                    .await;
+            }
+        }
+        Some(response.set_code(ResponseCode::Success))
+    }
+
+    pub async fn get_all_subscription_group_config(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let wrapper = self
+            .inner
+            .subscription_group_manager
+            .subscription_group_wrapper();
+        Some(RemotingCommand::create_response_command().set_body(Some(wrapper.encode())))
+    }
+
+    pub async fn get_subscription_group_config(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header = request
+            .decode_command_custom_header::<GetSubscriptionGroupConfigRequestHeader>()
+            .unwrap();
+        let response = RemotingCommand::create_response_command();
+        match self
+            .inner
+            .subscription_group_manager
+            .find_subscription_group_config(request_header.group.as_str())
+        {
+            Some(config) => Some(response.set_body(Some(config.encode()))),
+            None => Some(
+                response
+                    .set_code(ResponseCode::SubscriptionGroupNotExist)
+                    .set_remark(Some(format!(
+                        "The subscription group[{}] not exist",
+                        request_header.group
+                    ))),
+            ),
+        }
+    }
+
+    pub async fn delete_subscription_group(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header = request
+            .decode_command_custom_header::<DeleteSubscriptionGroupRequestHeader>()
+            .unwrap();
+        info!(
+            "AdminBrokerProcessor#deleteSubscriptionGroup, caller={}",
+            channel.remote_address()
+        );
+        let group = request_header.group_name.as_str();
+        self.inner
+            .subscription_group_manager
+            .delete_subscription_group_config(group);
+        if request_header.clean_offset {
+            self.inner.consumer_offset_manager.remove_offset(group);
+        }
+        Some(RemotingCommand::create_response_command())
+    }
+
+    pub async fn update_and_get_group_forbidden(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header = request
+            .decode_command_custom_header::<UpdateGroupForbiddenRequestHeader>()
+            .unwrap();
+        let group = request_header.group.as_str();
+        let topic = request_header.topic.as_str();
+        info!(
+            "updateAndGetGroupForbidden called by {}, group: {}, topic: {}, readable: {:?}",
+            channel.remote_address(),
+            group,
+            topic,
+            request_header.readable
+        );
+        let subscription_group_manager = &self.inner.subscription_group_manager;
+        if let Some(readable) = request_header.readable {
+            subscription_group_manager.update_forbidden(
+                group,
+                topic,
+                PermName::INDEX_PERM_READ as i32,
+                !readable,
+            );
+        }
+        let group_forbidden = GroupForbidden {
+            topic: topic.to_string(),
+            group: group.to_string(),
+            readable: !subscription_group_manager.get_forbidden(
+                group,
+                topic,
+                PermName::INDEX_PERM_READ as i32,
+            ),
+        };
+        Some(RemotingCommand::create_response_command().set_body(Some(group_forbidden.encode())))
+    }
+}
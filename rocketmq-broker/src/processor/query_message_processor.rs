@@ -25,6 +25,9 @@ use rocketmq_remoting::protocol::header::query_message_response_header::QueryMes
 use rocketmq_remoting::protocol::header::view_message_request_header::ViewMessageRequestHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_store::base::index_query::IndexKeyType;
+use rocketmq_store::base::index_query::IndexQuery;
+use rocketmq_store::base::index_query::IndexQueryCursor;
 use rocketmq_store::config::message_store_config::MessageStoreConfig;
 use rocketmq_store::log_file::MessageStore;
 
@@ -74,20 +77,40 @@ where
             .decode_command_custom_header::<QueryMessageRequestHeader>()
             .unwrap();
         response.set_opaque_mut(request.opaque());
-        let is_unique_key = request.ext_fields().unwrap().get(UNIQUE_MSG_QUERY_FLAG);
-        if is_unique_key.is_some() && is_unique_key.unwrap() == "true" {
+        let is_unique_key = request
+            .ext_fields()
+            .and_then(|ext_fields| ext_fields.get(UNIQUE_MSG_QUERY_FLAG))
+            .is_some_and(|value| value == "true");
+        if is_unique_key {
             request_header.max_num = self.message_store_config.default_query_max_num as i32;
         }
-        let query_message_result = self
-            .message_store
-            .query_message(
-                request_header.topic.as_str(),
-                request_header.key.as_str(),
-                request_header.max_num,
-                request_header.begin_timestamp,
-                request_header.end_timestamp,
-            )
-            .await?;
+        let cursor = match request_header.continuation_token.as_deref() {
+            None => None,
+            Some(token) => match IndexQueryCursor::decode(token) {
+                Some(cursor) => Some(cursor),
+                None => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(Some(format!("invalid continuation token {}", token))),
+                    );
+                }
+            },
+        };
+        let query = IndexQuery {
+            topic: request_header.topic,
+            key: request_header.key,
+            key_type: if is_unique_key {
+                IndexKeyType::UniqKey
+            } else {
+                IndexKeyType::Key
+            },
+            max_num: request_header.max_num,
+            begin_timestamp: request_header.begin_timestamp,
+            end_timestamp: request_header.end_timestamp,
+            cursor,
+        };
+        let query_message_result = self.message_store.query_message_page(&query).await?;
 
         let response_header = response
             .read_custom_header_mut::<QueryMessageResponseHeader>()
@@ -96,11 +119,18 @@ where
             query_message_result.index_last_update_phyoffset;
         response_header.index_last_update_timestamp =
             query_message_result.index_last_update_timestamp;
+        response_header.continuation_token = query_message_result
+            .next_cursor
+            .map(|cursor| cursor.encode());
 
         if query_message_result.buffer_total_size > 0 {
             let message_data = query_message_result.get_message_data();
             return Some(response.set_body(message_data));
         }
+        // every message of this page was filtered out, but the next page may match
+        if query_message_result.next_cursor.is_some() {
+            return Some(response);
+        }
         Some(
             response
                 .set_code(ResponseCode::QueryNotFound)
@@ -655,6 +655,9 @@ impl<MS: MessageStore> SendMessageProcessor<MS> {
                   rocketmq_store::base::message_status_enum::PutMessageStatus::WheelTimerNotEnable => {
                       response.set_code_mut(RemotingSysResponseCode::SystemError).set_remark_mut(Some("accurate timer message is not enabled, timerWheelEnable is %s".to_string()));
                   },
+                  rocketmq_store::base::message_status_enum::PutMessageStatus::TopicStorageQuotaExceeded => {
+                      response.set_code_mut(RemotingSysResponseCode::SystemError).set_remark_mut(Some("[TOPIC_STORAGE_QUOTA_EXCEEDED]the topic has reached the max.storage.bytes attribute, wait for its messages to expire or raise the quota.".to_string()));
+                  },
                   _ => {
                       response.set_code_mut(RemotingSysResponseCode::SystemError).set_remark_mut(Some("UNKNOWN_ERROR DEFAULT".to_string()));
                   }
@@ -15,20 +15,18 @@
  * limitations under the License.
  */
 
-use std::collections::HashMap;
 use std::sync::Arc;
 
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::config_manager::ConfigManager;
 use rocketmq_common::common::mix_all::is_sys_consumer_group;
 use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_remoting::protocol::body::subscription_group_wrapper::SubscriptionGroupWrapper;
 use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
-use rocketmq_remoting::protocol::DataVersion;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_store::log_file::MessageStore;
-use serde::Deserialize;
-use serde::Serialize;
 use tracing::info;
+use tracing::warn;
 
 use crate::broker_path_config_helper::get_subscription_group_path;
 
@@ -134,15 +132,7 @@ where
                     subscription_group_config_new
                 );
             }
-            let state_machine_version = if let Some(ref store) = self.message_store {
-                store.get_state_machine_version()
-            } else {
-                0
-            };
-            self.subscription_group_wrapper
-                .lock()
-                .data_version
-                .next_version_with(state_machine_version);
+            self.update_data_version();
             self.persist();
             subscription_group_config = Some(subscription_group_config_new);
         }
@@ -160,6 +150,109 @@ where
             .cloned()
     }
 
+    /// Creates the group or replaces its config.
+    pub fn update_subscription_group_config(&self, config: SubscriptionGroupConfig) {
+        let old = self
+            .subscription_group_wrapper
+            .lock()
+            .subscription_group_table
+            .insert(config.group_name().to_string(), config.clone());
+        match old {
+            Some(old) => info!(
+                "update subscription group config, old: {:?} new: {:?}",
+                old, config
+            ),
+            None => info!("create new subscription group, {:?}", config),
+        }
+        self.update_data_version();
+        self.persist();
+    }
+
+    /// Removes the group and its forbidden bits, returning the removed config.
+    pub fn delete_subscription_group_config(&self, group: &str) -> Option<SubscriptionGroupConfig> {
+        let mut wrapper = self.subscription_group_wrapper.lock();
+        let old = wrapper.subscription_group_table.remove(group);
+        wrapper.forbidden_table.remove(group);
+        drop(wrapper);
+        match old {
+            Some(ref old) => {
+                info!("delete subscription group OK, subscription group:{:?}", old);
+                self.update_data_version();
+                self.persist();
+            }
+            None => warn!(
+                "delete subscription group failed, subscription groupName: {} not exist",
+                group
+            ),
+        }
+        old
+    }
+
+    pub fn subscription_group_wrapper(&self) -> SubscriptionGroupWrapper {
+        self.subscription_group_wrapper.lock().clone()
+    }
+
+    /// Sets or clears the `forbidden_index` bit of `group` on `topic`.
+    pub fn update_forbidden(
+        &self,
+        group: &str,
+        topic: &str,
+        forbidden_index: i32,
+        forbidden: bool,
+    ) {
+        let bit_forbidden = 1 << forbidden_index;
+        // the mask is read and written under the same lock so that concurrent updates of
+        // different bits do not overwrite each other
+        let mut wrapper = self.subscription_group_wrapper.lock();
+        let old = wrapper
+            .forbidden_table
+            .get(group)
+            .and_then(|topics| topics.get(topic))
+            .copied();
+        let topic_forbidden = old.unwrap_or_default().max(0);
+        let topic_forbidden = if forbidden {
+            topic_forbidden | bit_forbidden
+        } else {
+            topic_forbidden & !bit_forbidden
+        };
+        if topic_forbidden == 0 {
+            if let Some(topics) = wrapper.forbidden_table.get_mut(group) {
+                topics.remove(topic);
+                if topics.is_empty() {
+                    wrapper.forbidden_table.remove(group);
+                }
+            }
+        } else {
+            wrapper
+                .forbidden_table
+                .entry(group.to_string())
+                .or_default()
+                .insert(topic.to_string(), topic_forbidden);
+        }
+        drop(wrapper);
+        if old.unwrap_or_default() == topic_forbidden {
+            return;
+        }
+        info!(
+            "set group forbidden, {}@{} old: {:?} new: {}",
+            group, topic, old, topic_forbidden
+        );
+        self.update_data_version();
+        self.persist();
+    }
+
+    fn update_data_version(&self) {
+        let state_machine_version = if let Some(ref store) = self.message_store {
+            store.get_state_machine_version()
+        } else {
+            0
+        };
+        self.subscription_group_wrapper
+            .lock()
+            .data_version
+            .next_version_with(state_machine_version);
+    }
+
     pub fn get_forbidden(&self, group: &str, topic: &str, forbidden_index: i32) -> bool {
         let topic_forbidden = self.get_forbidden_internal(group, topic);
         let bit_forbidden = 1 << forbidden_index;
@@ -187,20 +280,104 @@ where
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct SubscriptionGroupWrapper {
-    subscription_group_table: HashMap<String, SubscriptionGroupConfig>,
-    forbidden_table: HashMap<String, HashMap<String, i32>>,
-    data_version: DataVersion,
-}
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::constant::PermName;
+    use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
+
+    use super::*;
 
-impl SubscriptionGroupWrapper {
-    pub fn subscription_group_table(&self) -> &HashMap<String, SubscriptionGroupConfig> {
-        &self.subscription_group_table
+    fn new_manager(name: &str) -> SubscriptionGroupManager<DefaultMessageStore> {
+        let store_path = std::env::temp_dir().join(format!(
+            "rocketmq-subscription-group-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let broker_config = BrokerConfig {
+            store_path_root_dir: store_path.to_string_lossy().to_string(),
+            ..BrokerConfig::default()
+        };
+        SubscriptionGroupManager::new(Arc::new(broker_config), None)
     }
 
-    pub fn forbidden_table(&self) -> &HashMap<String, HashMap<String, i32>> {
-        &self.forbidden_table
+    #[test]
+    fn update_and_delete_subscription_group() {
+        let manager = new_manager("crud");
+        let before = manager
+            .subscription_group_wrapper()
+            .data_version
+            .get_counter();
+
+        let mut config = SubscriptionGroupConfig::new("group_a");
+        config.set_retry_queue_nums(2);
+        manager.update_subscription_group_config(config);
+        let found = manager.find_subscription_group_config("group_a").unwrap();
+        assert_eq!(found.retry_queue_nums(), 2);
+        assert!(
+            manager
+                .subscription_group_wrapper()
+                .data_version
+                .get_counter()
+                > before
+        );
+
+        manager.update_forbidden("group_a", "topic_a", PermName::INDEX_PERM_READ as i32, true);
+        assert!(manager
+            .delete_subscription_group_config("group_a")
+            .is_some());
+        assert!(!manager
+            .subscription_group_wrapper()
+            .subscription_group_table()
+            .contains_key("group_a"));
+        assert!(!manager.get_forbidden("group_a", "topic_a", PermName::INDEX_PERM_READ as i32));
+        assert!(manager
+            .delete_subscription_group_config("group_a")
+            .is_none());
+    }
+
+    #[test]
+    fn update_forbidden_sets_and_clears_bits() {
+        let manager = new_manager("forbidden");
+        let read = PermName::INDEX_PERM_READ as i32;
+        let write = PermName::INDEX_PERM_WRITE as i32;
+
+        manager.update_forbidden("group_b", "topic_b", read, true);
+        manager.update_forbidden("group_b", "topic_b", write, true);
+        assert!(manager.get_forbidden("group_b", "topic_b", read));
+        assert!(manager.get_forbidden("group_b", "topic_b", write));
+
+        manager.update_forbidden("group_b", "topic_b", read, false);
+        assert!(!manager.get_forbidden("group_b", "topic_b", read));
+        assert!(manager.get_forbidden("group_b", "topic_b", write));
+
+        manager.update_forbidden("group_b", "topic_b", write, false);
+        assert!(manager
+            .subscription_group_wrapper()
+            .forbidden_table()
+            .is_empty());
+    }
+
+    #[test]
+    fn update_forbidden_concurrently_keeps_every_bit() {
+        let manager = Arc::new(new_manager("forbidden-concurrent"));
+        let read = PermName::INDEX_PERM_READ as i32;
+        let write = PermName::INDEX_PERM_WRITE as i32;
+
+        for _ in 0..50 {
+            let handles = [read, write].map(|forbidden_index| {
+                let manager = manager.clone();
+                std::thread::spawn(move || {
+                    manager.update_forbidden("group_c", "topic_c", forbidden_index, true);
+                })
+            });
+            for handle in handles {
+                handle.join().unwrap();
+            }
+            assert!(manager.get_forbidden("group_c", "topic_c", read));
+            assert!(manager.get_forbidden("group_c", "topic_c", write));
+
+            manager.update_forbidden("group_c", "topic_c", read, false);
+            manager.update_forbidden("group_c", "topic_c", write, false);
+        }
     }
 }
@@ -431,7 +431,7 @@ impl TopicConfigManager {
             .get(topic_config.topic_name.as_ref().unwrap().as_str())
             .is_none();
         let final_attributes =
-            alter_current_attributes(create, ALL.clone(), new_attributes, current_attributes);
+            alter_current_attributes(create, ALL.clone(), current_attributes, new_attributes);
         topic_config.attributes = final_attributes;
         match self.put_topic_config(topic_config.clone()) {
             None => {
@@ -18,6 +18,7 @@ use clap::Parser;
 use rocketmq_cli::command_line::Commands;
 use rocketmq_cli::command_line::RootCli;
 use rocketmq_cli::content_show::print_content;
+use rocketmq_cli::rebuild_logics::rebuild_logics;
 
 fn main() {
     let cli = RootCli::parse();
@@ -25,5 +26,12 @@ fn main() {
         Commands::ReadMessageLog { config, from, to } => {
             print_content(from, to, config);
         }
+        Commands::RebuildLogics {
+            store_path,
+            from,
+            mapped_file_size_commit_log,
+        } => {
+            rebuild_logics(store_path, from, mapped_file_size_commit_log);
+        }
     }
 }
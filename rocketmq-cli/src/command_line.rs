@@ -81,4 +81,38 @@ pub enum Commands {
         )]
         to: Option<u32>,
     },
+
+    #[command(
+        arg_required_else_help = true,
+        author = "mxsm",
+        version = "0.2.0",
+        about = "rebuild consume queue and index files from the commit log"
+    )]
+    RebuildLogics {
+        #[arg(
+            short = 's',
+            long,
+            value_name = "DIR",
+            help = "store root dir of the stopped broker"
+        )]
+        store_path: PathBuf,
+
+        #[arg(
+            short = 'f',
+            long,
+            value_name = "OFFSET",
+            default_value_t = 0,
+            help = "The commit log offset of the first message to replay, defaults to the min \
+                    offset of the commit log."
+        )]
+        from: i64,
+
+        #[arg(
+            short = 'm',
+            long,
+            value_name = "SIZE",
+            help = "commit log file size in bytes, defaults to the broker default"
+        )]
+        mapped_file_size_commit_log: Option<usize>,
+    },
 }
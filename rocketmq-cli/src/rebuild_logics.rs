@@ -0,0 +1,58 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use rocketmq_store::log_file::MessageStore;
+use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
+
+pub fn rebuild_logics(store_path: PathBuf, from: i64, mapped_file_size_commit_log: Option<usize>) {
+    let mut message_store_config = MessageStoreConfig {
+        store_path_root_dir: store_path.to_string_lossy().to_string(),
+        ..MessageStoreConfig::default()
+    };
+    if let Some(mapped_file_size_commit_log) = mapped_file_size_commit_log {
+        message_store_config.mapped_file_size_commit_log = mapped_file_size_commit_log;
+    }
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async move {
+        let mut message_store = DefaultMessageStore::new(
+            Arc::new(message_store_config),
+            Arc::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        if !message_store.load().await {
+            eprintln!("load message store failed");
+            std::process::exit(1);
+        }
+        match message_store.rebuild_logics(from, |progress| println!("{}", progress)) {
+            Ok(progress) => println!(
+                "rebuild finished, dispatched: {}, skipped: {}",
+                progress.dispatched_messages, progress.skipped_messages
+            ),
+            Err(err) => {
+                eprintln!("rebuild failed: {}", err);
+                std::process::exit(1);
+            }
+        }
+    });
+}
@@ -21,6 +21,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::utils::name_server_address_utils::NameServerAddressUtils;
 use rocketmq_common::utils::name_server_address_utils::NAMESRV_ENDPOINT_PATTERN;
 use rocketmq_common::utils::network_util::NetworkUtil;
@@ -29,6 +30,7 @@ use rocketmq_common::TimeUtils::get_current_nano;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
 use rocketmq_remoting::protocol::request_type::RequestType;
 use rocketmq_remoting::protocol::LanguageCode;
+use rocketmq_remoting::runtime::config::tls_system_config::TLS_ENABLE;
 
 use crate::base::access_channel::AccessChannel;
 
@@ -39,6 +41,8 @@ pub const DECODE_DECOMPRESS_BODY: &str = "com.rocketmq.decompress.body";
 pub const SEND_LATENCY_ENABLE: &str = "com.rocketmq.sendLatencyEnable";
 pub const START_DETECTOR_ENABLE: &str = "com.rocketmq.startDetectorEnable";
 pub const HEART_BEAT_V2: &str = "com.rocketmq.heartbeat.v2";
+/// Marks the zone segment appended to the client id when zone aware routing is enabled.
+pub const CLIENT_ID_ZONE_PREFIX: &str = "zone=";
 
 #[derive(Clone)]
 pub struct ClientConfig {
@@ -51,11 +55,19 @@ pub struct ClientConfig {
     pub namespace_v2: Option<String>,
     pub access_channel: AccessChannel,
     pub poll_name_server_interval: u32,
+    /// Subscribe to route changes pushed by the name server, polling every
+    /// `poll_name_server_interval` is kept as a fallback.
+    pub enable_topic_route_push: bool,
     pub heartbeat_broker_interval: u32,
     pub persist_consumer_offset_interval: u32,
     pub pull_time_delay_millis_when_exception: u32,
     pub unit_mode: bool,
     pub unit_name: Option<String>,
+    /// Availability zone the client runs in, read from `rocketmq.zone` or `ROCKETMQ_ZONE`.
+    pub zone_name: Option<String>,
+    /// Prefer brokers in `zone_name` when sending and allocating queues, falling back to
+    /// other zones when none is usable.
+    pub enable_zone_aware_routing: bool,
     pub decode_read_body: bool,
     pub decode_decompress_body: bool,
     pub vip_channel_enabled: bool,
@@ -93,11 +105,17 @@ impl ClientConfig {
             namespace_v2: None,
             access_channel: AccessChannel::Local,
             poll_name_server_interval: Duration::from_secs(30).as_millis() as u32,
+            enable_topic_route_push: false,
             heartbeat_broker_interval: Duration::from_secs(30).as_millis() as u32,
             persist_consumer_offset_interval: Duration::from_secs(5).as_millis() as u32,
             pull_time_delay_millis_when_exception: 1000,
             unit_mode: false,
             unit_name: None,
+            zone_name: env::var(mix_all::ROCKETMQ_ZONE_PROPERTY)
+                .or_else(|_| env::var(mix_all::ROCKETMQ_ZONE_ENV))
+                .ok()
+                .filter(|zone| !zone.is_empty()),
+            enable_zone_aware_routing: false,
             decode_read_body: env::var(DECODE_READ_BODY)
                 .unwrap_or_else(|_| "true".to_string())
                 .parse::<bool>()
@@ -114,7 +132,10 @@ impl ClientConfig {
                 .unwrap_or_else(|_| "false".to_string())
                 .parse::<bool>()
                 .unwrap_or(false),
-            use_tls: false,
+            use_tls: env::var(TLS_ENABLE)
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .unwrap_or(false),
             socks_proxy_config: env::var(SOCKS_PROXY_CONFIG).unwrap_or_else(|_| "{}".to_string()),
             mq_client_api_timeout: Duration::from_secs(3).as_millis() as u64,
             detect_timeout: 200,
@@ -192,6 +213,12 @@ impl ClientConfig {
             }
         }
 
+        if let Some(zone_name) = self.zone_aware_zone_name() {
+            sb.push('@');
+            sb.push_str(CLIENT_ID_ZONE_PREFIX);
+            sb.push_str(zone_name);
+        }
+
         if self.enable_stream_request_type {
             sb.push('@');
             sb.push_str(RequestType::Stream.to_string().as_str());
@@ -200,6 +227,15 @@ impl ClientConfig {
         sb
     }
 
+    /// The zone to route by, `None` unless zone aware routing is enabled and a zone is set.
+    pub fn zone_aware_zone_name(&self) -> Option<&str> {
+        if self.enable_zone_aware_routing {
+            self.zone_name.as_deref()
+        } else {
+            None
+        }
+    }
+
     pub fn get_namesrv_addr(&self) -> Option<String> {
         if StringUtils::is_not_empty_str(self.namesrv_addr.as_deref())
             && NAMESRV_ENDPOINT_PATTERN.is_match(self.namesrv_addr.as_ref().unwrap().as_str())
@@ -212,3 +248,29 @@ impl ClientConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_zone_to_client_id_when_zone_aware() {
+        let mut client_config = ClientConfig {
+            client_ip: Some("10.0.0.1".to_string()),
+            instance_name: "instance".to_string(),
+            unit_name: None,
+            zone_name: Some("az1".to_string()),
+            enable_stream_request_type: false,
+            ..ClientConfig::default()
+        };
+        assert_eq!(client_config.zone_aware_zone_name(), None);
+        assert_eq!(client_config.build_mq_client_id(), "10.0.0.1@instance");
+
+        client_config.enable_zone_aware_routing = true;
+        assert_eq!(client_config.zone_aware_zone_name(), Some("az1"));
+        assert_eq!(
+            client_config.build_mq_client_id(),
+            format!("10.0.0.1@instance@{}az1", CLIENT_ID_ZONE_PREFIX)
+        );
+    }
+}
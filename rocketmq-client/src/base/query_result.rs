@@ -22,6 +22,7 @@ use rocketmq_common::common::message::message_ext::MessageExt;
 pub struct QueryResult {
     index_last_update_timestamp: u64,
     message_list: Vec<MessageExt>,
+    continuation_token: Option<String>,
 }
 
 impl QueryResult {
@@ -30,6 +31,7 @@ impl QueryResult {
         QueryResult {
             index_last_update_timestamp,
             message_list,
+            continuation_token: None,
         }
     }
 
@@ -41,6 +43,15 @@ impl QueryResult {
     pub fn message_list(&self) -> &Vec<MessageExt> {
         &self.message_list
     }
+
+    /// Token to pass back to fetch the next page, `None` once every broker is exhausted.
+    pub fn continuation_token(&self) -> Option<&str> {
+        self.continuation_token.as_deref()
+    }
+
+    pub fn set_continuation_token(&mut self, continuation_token: Option<String>) {
+        self.continuation_token = continuation_token;
+    }
 }
 
 // Implementing the Display trait for pretty printing, similar to Java's toString method
@@ -48,8 +59,9 @@ impl fmt::Display for QueryResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "QueryResult [index_last_update_timestamp={}, message_list={:?}]",
-            self.index_last_update_timestamp, self.message_list
+            "QueryResult [index_last_update_timestamp={}, message_list={:?}, \
+             continuation_token={:?}]",
+            self.index_last_update_timestamp, self.message_list, self.continuation_token
         )
     }
 }
@@ -14,6 +14,8 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::collections::HashMap;
+
 use rocketmq_common::common::message::message_queue::MessageQueue;
 
 use crate::Result;
@@ -27,5 +29,18 @@ pub trait AllocateMessageQueueStrategy: Send + Sync {
         cid_all: &[String],
     ) -> Result<Vec<MessageQueue>>;
 
+    /// Same as `allocate`, with the zone of each broker taken from the topic route. Strategies
+    /// that don't care where brokers are deployed keep this default, which ignores the zones.
+    fn allocate_with_broker_zones(
+        &self,
+        consumer_group: &str,
+        current_cid: &str,
+        mq_all: &[MessageQueue],
+        cid_all: &[String],
+        _broker_zones: &HashMap<String /* broker name */, String /* zone */>,
+    ) -> Result<Vec<MessageQueue>> {
+        self.allocate(consumer_group, current_cid, mq_all, cid_all)
+    }
+
     fn get_name(&self) -> &'static str;
 }
@@ -23,6 +23,7 @@ use std::thread;
 
 use rocketmq_common::common::base::service_state::ServiceState;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mix_all::DEFAULT_CONSUMER_GROUP;
@@ -39,6 +40,7 @@ use tokio::runtime::Handle;
 use tracing::info;
 
 use crate::base::client_config::ClientConfig;
+use crate::base::query_result::QueryResult;
 use crate::base::validators::Validators;
 use crate::consumer::consumer_impl::consume_message_concurrently_service::ConsumeMessageConcurrentlyService;
 use crate::consumer::consumer_impl::consume_message_orderly_service::ConsumeMessageOrderlyService;
@@ -360,6 +362,62 @@ impl DefaultMQPushConsumerImpl {
         }
     }
 
+    fn make_sure_state_ok(&self) -> Result<()> {
+        if self.service_state != ServiceState::Running {
+            return Err(MQClientError::MQClientErr(
+                -1,
+                format!(
+                    "The consumer service state not OK, {:?} {}",
+                    self.service_state,
+                    FAQUrl::suggest_todo(FAQUrl::CLIENT_SERVICE_NOT_OK)
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn query_message(
+        &mut self,
+        topic: &str,
+        key: &str,
+        max_num: i32,
+        begin: i64,
+        end: i64,
+        continuation_token: Option<&str>,
+    ) -> Result<QueryResult> {
+        self.make_sure_state_ok()?;
+        let client_instance = self.client_instance.as_mut().unwrap();
+        let mq_client_api_impl = client_instance.mq_client_api_impl.clone();
+        client_instance
+            .mq_admin_impl
+            .query_message(
+                topic,
+                key,
+                max_num,
+                begin,
+                end,
+                false,
+                continuation_token,
+                mq_client_api_impl,
+                &mut self.client_config,
+            )
+            .await
+    }
+
+    pub async fn query_message_by_uniq_key(
+        &mut self,
+        topic: &str,
+        uniq_key: &str,
+    ) -> Result<Option<MessageExt>> {
+        self.make_sure_state_ok()?;
+        let client_instance = self.client_instance.as_mut().unwrap();
+        let mq_client_api_impl = client_instance.mq_client_api_impl.clone();
+        client_instance
+            .mq_admin_impl
+            .query_message_by_uniq_key(topic, uniq_key, mq_client_api_impl, &mut self.client_config)
+            .await
+    }
+
     fn check_config(&mut self) -> Result<()> {
         Validators::check_group(self.consumer_config.consumer_group.as_str())?;
         if self.consumer_config.consumer_group.is_empty() {
@@ -280,15 +280,22 @@ where
                     let mut ci_all = ci_all.unwrap();
                     ci_all.sort();
 
+                    let broker_zones = self
+                        .client_instance
+                        .as_ref()
+                        .unwrap()
+                        .find_broker_zone_table(topic)
+                        .await;
                     let allocate_result = match self
                         .allocate_message_queue_strategy
                         .as_ref()
                         .unwrap()
-                        .allocate(
+                        .allocate_with_broker_zones(
                             self.consumer_group.as_ref().unwrap(),
                             self.client_instance.as_ref().unwrap().client_id.as_ref(),
                             mq_all.as_slice(),
                             ci_all.as_slice(),
+                            &broker_zones,
                         ) {
                         Ok(value) => value,
                         Err(e) => {
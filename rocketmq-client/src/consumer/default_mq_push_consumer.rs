@@ -48,6 +48,7 @@ use crate::consumer::message_selector::MessageSelector;
 use crate::consumer::mq_consumer::MQConsumer;
 use crate::consumer::mq_push_consumer::MQPushConsumer;
 use crate::consumer::rebalance_strategy::allocate_message_queue_averagely::AllocateMessageQueueAveragely;
+use crate::error::MQClientError;
 use crate::trace::async_trace_dispatcher::AsyncTraceDispatcher;
 use crate::trace::hook::consume_message_trace_hook_impl::ConsumeMessageTraceHookImpl;
 use crate::trace::trace_dispatcher::TraceDispatcher;
@@ -447,7 +448,10 @@ impl MQAdmin for DefaultMQPushConsumer {
         queue_num: i32,
         attributes: HashMap<String, String>,
     ) -> crate::Result<()> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
+        Err(MQClientError::MQClientErr(
+            -1,
+            "create_topic is not supported by DefaultMQPushConsumer".to_string(),
+        ))
     }
 
     fn create_topic_with_flag(
@@ -458,23 +462,38 @@ impl MQAdmin for DefaultMQPushConsumer {
         topic_sys_flag: i32,
         attributes: HashMap<String, String>,
     ) -> crate::Result<()> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
+        Err(MQClientError::MQClientErr(
+            -1,
+            "create_topic_with_flag is not supported by DefaultMQPushConsumer".to_string(),
+        ))
     }
 
     fn search_offset(&self, mq: &MessageQueue, timestamp: u64) -> crate::Result<i64> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
+        Err(MQClientError::MQClientErr(
+            -1,
+            "search_offset is not supported by DefaultMQPushConsumer".to_string(),
+        ))
     }
 
     fn max_offset(&self, mq: &MessageQueue) -> crate::Result<i64> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
+        Err(MQClientError::MQClientErr(
+            -1,
+            "max_offset is not supported by DefaultMQPushConsumer".to_string(),
+        ))
     }
 
     fn min_offset(&self, mq: &MessageQueue) -> crate::Result<i64> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
+        Err(MQClientError::MQClientErr(
+            -1,
+            "min_offset is not supported by DefaultMQPushConsumer".to_string(),
+        ))
     }
 
     fn earliest_msg_store_time(&self, mq: &MessageQueue) -> crate::Result<u64> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
+        Err(MQClientError::MQClientErr(
+            -1,
+            "earliest_msg_store_time is not supported by DefaultMQPushConsumer".to_string(),
+        ))
     }
 
     fn query_message(
@@ -485,11 +504,21 @@ impl MQAdmin for DefaultMQPushConsumer {
         begin: u64,
         end: u64,
     ) -> crate::Result<QueryResult> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
+        Err(MQClientError::MQClientErr(
+            -1,
+            "query_message of DefaultMQPushConsumer is async, use \
+             DefaultMQPushConsumer::query_message"
+                .to_string(),
+        ))
     }
 
     fn view_message(&self, topic: &str, msg_id: &str) -> crate::Result<MessageExt> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
+        Err(MQClientError::MQClientErr(
+            -1,
+            "view_message of DefaultMQPushConsumer is async, use \
+             DefaultMQPushConsumer::query_message_by_uniq_key"
+                .to_string(),
+        ))
     }
 }
 
@@ -666,4 +695,37 @@ impl DefaultMQPushConsumer {
     pub fn set_consume_from_where(&mut self, consume_from_where: ConsumeFromWhere) {
         self.consumer_config.consume_from_where = consume_from_where;
     }
+
+    /// Queries messages whose keys contain `key` and whose store time falls in
+    /// `[begin, end]`. Pass the token of the previous result to fetch the next page.
+    pub async fn query_message(
+        &mut self,
+        topic: &str,
+        key: &str,
+        max_num: i32,
+        begin: i64,
+        end: i64,
+        continuation_token: Option<&str>,
+    ) -> crate::Result<QueryResult> {
+        let topic = self.client_config.with_namespace(topic);
+        self.default_mqpush_consumer_impl
+            .as_mut()
+            .unwrap()
+            .query_message(topic.as_str(), key, max_num, begin, end, continuation_token)
+            .await
+    }
+
+    /// Looks a message up by the client message id assigned when it was sent.
+    pub async fn query_message_by_uniq_key(
+        &mut self,
+        topic: &str,
+        uniq_key: &str,
+    ) -> crate::Result<Option<MessageExt>> {
+        let topic = self.client_config.with_namespace(topic);
+        self.default_mqpush_consumer_impl
+            .as_mut()
+            .unwrap()
+            .query_message_by_uniq_key(topic.as_str(), uniq_key)
+            .await
+    }
 }
@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 pub mod allocate_message_queue_averagely;
+pub mod allocate_message_queue_by_zone;
 
 use std::collections::HashSet;
 
@@ -0,0 +1,203 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rocketmq_common::common::message::message_queue::MessageQueue;
+
+use crate::base::client_config::CLIENT_ID_ZONE_PREFIX;
+use crate::consumer::allocate_message_queue_strategy::AllocateMessageQueueStrategy;
+use crate::consumer::rebalance_strategy::check;
+use crate::Result;
+
+/// Keeps the queues of a zone on the consumers of the same zone, the queues of zones without
+/// consumers (or of brokers without a zone) are shared by every consumer.
+///
+/// Each zone is split with the wrapped strategy. A consumer's zone is read from its client id,
+/// so consumers have to enable `enable_zone_aware_routing` and set `zone_name`.
+pub struct AllocateMessageQueueByZone {
+    allocate_message_queue_strategy: Arc<dyn AllocateMessageQueueStrategy>,
+}
+
+impl AllocateMessageQueueByZone {
+    pub fn new(allocate_message_queue_strategy: Arc<dyn AllocateMessageQueueStrategy>) -> Self {
+        Self {
+            allocate_message_queue_strategy,
+        }
+    }
+
+    fn consumer_zone(cid: &str) -> Option<&str> {
+        cid.split('@')
+            .find_map(|segment| segment.strip_prefix(CLIENT_ID_ZONE_PREFIX))
+    }
+}
+
+impl AllocateMessageQueueStrategy for AllocateMessageQueueByZone {
+    fn allocate(
+        &self,
+        consumer_group: &str,
+        current_cid: &str,
+        mq_all: &[MessageQueue],
+        cid_all: &[String],
+    ) -> Result<Vec<MessageQueue>> {
+        self.allocate_message_queue_strategy
+            .allocate(consumer_group, current_cid, mq_all, cid_all)
+    }
+
+    fn allocate_with_broker_zones(
+        &self,
+        consumer_group: &str,
+        current_cid: &str,
+        mq_all: &[MessageQueue],
+        cid_all: &[String],
+        broker_zones: &HashMap<String, String>,
+    ) -> Result<Vec<MessageQueue>> {
+        let mut result = Vec::new();
+        if !check(consumer_group, current_cid, mq_all, cid_all)? {
+            return Ok(result);
+        }
+
+        let mut mq_by_zone = BTreeMap::<&str, Vec<MessageQueue>>::new();
+        let mut mq_shared = Vec::new();
+        for mq in mq_all {
+            match broker_zones.get(mq.get_broker_name()) {
+                Some(zone) => mq_by_zone
+                    .entry(zone.as_str())
+                    .or_default()
+                    .push(mq.clone()),
+                None => mq_shared.push(mq.clone()),
+            }
+        }
+        let mut cid_by_zone = HashMap::<&str, Vec<String>>::new();
+        for cid in cid_all {
+            if let Some(zone) = Self::consumer_zone(cid) {
+                cid_by_zone.entry(zone).or_default().push(cid.clone());
+            }
+        }
+
+        let current_zone = Self::consumer_zone(current_cid);
+        for (zone, mqs) in mq_by_zone {
+            match cid_by_zone.get(zone) {
+                Some(cids) => {
+                    if current_zone == Some(zone) {
+                        result.extend(self.allocate_message_queue_strategy.allocate(
+                            consumer_group,
+                            current_cid,
+                            &mqs,
+                            cids,
+                        )?);
+                    }
+                }
+                None => mq_shared.extend(mqs),
+            }
+        }
+        if !mq_shared.is_empty() {
+            result.extend(self.allocate_message_queue_strategy.allocate(
+                consumer_group,
+                current_cid,
+                &mq_shared,
+                cid_all,
+            )?);
+        }
+        Ok(result)
+    }
+
+    fn get_name(&self) -> &'static str {
+        "ZONE_AWARE"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consumer::rebalance_strategy::allocate_message_queue_averagely::AllocateMessageQueueAveragely;
+
+    fn queues(broker_names: &[&str]) -> Vec<MessageQueue> {
+        broker_names
+            .iter()
+            .flat_map(|broker_name| {
+                (0..2).map(|queue_id| MessageQueue::from_parts("TopicTest", *broker_name, queue_id))
+            })
+            .collect()
+    }
+
+    fn allocate(
+        current_cid: &str,
+        mq_all: &[MessageQueue],
+        cid_all: &[String],
+        broker_zones: &[(&str, &str)],
+    ) -> Vec<MessageQueue> {
+        let broker_zones = broker_zones
+            .iter()
+            .map(|(broker_name, zone)| (broker_name.to_string(), zone.to_string()))
+            .collect();
+        AllocateMessageQueueByZone::new(Arc::new(AllocateMessageQueueAveragely))
+            .allocate_with_broker_zones("group", current_cid, mq_all, cid_all, &broker_zones)
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_consumer_zone_from_client_id() {
+        assert_eq!(
+            AllocateMessageQueueByZone::consumer_zone("10.0.0.1@1234@zone=az1"),
+            Some("az1")
+        );
+        assert_eq!(
+            AllocateMessageQueueByZone::consumer_zone("10.0.0.1@1234@unit@zone=az1@STREAM"),
+            Some("az1")
+        );
+        assert_eq!(
+            AllocateMessageQueueByZone::consumer_zone("10.0.0.1@1234@unit"),
+            None
+        );
+    }
+
+    #[test]
+    fn allocate_queues_of_own_zone() {
+        let mq_all = queues(&["broker-a", "broker-b"]);
+        let cid_all = vec!["c1@zone=az1".to_string(), "c2@zone=az2".to_string()];
+        let broker_zones = [("broker-a", "az1"), ("broker-b", "az2")];
+        assert_eq!(
+            allocate("c1@zone=az1", &mq_all, &cid_all, &broker_zones),
+            queues(&["broker-a"])
+        );
+        assert_eq!(
+            allocate("c2@zone=az2", &mq_all, &cid_all, &broker_zones),
+            queues(&["broker-b"])
+        );
+    }
+
+    #[test]
+    fn share_queues_of_zones_without_consumers() {
+        // broker-b is in a zone without consumers and broker-c has no zone
+        let mq_all = queues(&["broker-a", "broker-b", "broker-c"]);
+        let cid_all = vec!["c1@zone=az1".to_string(), "c2".to_string()];
+        let broker_zones = [("broker-a", "az1"), ("broker-b", "az2")];
+
+        let mut c1 = allocate("c1@zone=az1", &mq_all, &cid_all, &broker_zones);
+        let c2 = allocate("c2", &mq_all, &cid_all, &broker_zones);
+        assert!(c2.iter().all(|mq| mq.get_broker_name() != "broker-a"));
+        assert_eq!(c2.len(), 2);
+        c1.extend(c2);
+        c1.sort_by(|left, right| {
+            (left.get_broker_name(), left.get_queue_id())
+                .cmp(&(right.get_broker_name(), right.get_queue_id()))
+        });
+        assert_eq!(c1, mq_all);
+    }
+}
@@ -34,6 +34,7 @@ use rocketmq_remoting::base::connection_net_event::ConnectionNetEvent;
 use rocketmq_remoting::protocol::heartbeat::consumer_data::ConsumerData;
 use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
 use rocketmq_remoting::protocol::heartbeat::producer_data::ProducerData;
+use rocketmq_remoting::protocol::route::topic_route_change::TopicRouteChangeBody;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
 use rocketmq_remoting::rpc::client_metadata::ClientMetadata;
 use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
@@ -103,6 +104,9 @@ where
     broker_version_table:
         Arc<RwLock<HashMap<String /* Broker Name */, HashMap<String /* address */, i32>>>>,
     send_heartbeat_times_total: Arc<AtomicI64>,
+    /// Name server and version of the last route change pushed for each topic.
+    topic_route_push_version_table:
+        Arc<RwLock<HashMap<String /* Topic */, (String /* namesrv addr */, i64)>>>,
 }
 
 impl<C> MQClientInstance<C>
@@ -118,9 +122,19 @@ where
         let broker_addr_table = Arc::new(Default::default());
         let (tx, _) = tokio::sync::broadcast::channel::<ConnectionNetEvent>(16);
         let mut rx = tx.subscribe();
+        let (topic_route_change_tx, topic_route_change_rx) =
+            if client_config.enable_topic_route_push {
+                let (tx, rx) = tokio::sync::mpsc::channel::<(String, TopicRouteChangeBody)>(1024);
+                (Some(tx), Some(rx))
+            } else {
+                (None, None)
+            };
         let mq_client_api_impl = ArcRefCellWrapper::new(MQClientAPIImpl::new(
-            Arc::new(TokioClientConfig::default()),
-            ClientRemotingProcessor {},
+            Arc::new(TokioClientConfig {
+                use_tls: client_config.use_tls,
+                ..Default::default()
+            }),
+            ClientRemotingProcessor::new(topic_route_change_tx),
             rpc_hook,
             client_config.clone(),
             Some(tx),
@@ -166,7 +180,18 @@ where
             broker_addr_table,
             broker_version_table: Arc::new(Default::default()),
             send_heartbeat_times_total: Arc::new(AtomicI64::new(0)),
+            topic_route_push_version_table: Arc::new(Default::default()),
         };
+        if let Some(mut topic_route_change_rx) = topic_route_change_rx {
+            let mut instance_ = instance.clone();
+            tokio::spawn(async move {
+                while let Some((namesrv_addr, body)) = topic_route_change_rx.recv().await {
+                    instance_
+                        .on_topic_route_change(namesrv_addr.as_str(), body)
+                        .await;
+                }
+            });
+        }
         let instance_ = instance.clone();
         tokio::spawn(async move {
             while let Ok(value) = rx.recv().await {
@@ -357,12 +382,77 @@ where
             }
         }
 
+        if self.client_config.enable_topic_route_push {
+            // Resubscribing every poll picks up new topics and a switch to another name server
+            if let Err(err) = self
+                .mq_client_api_impl
+                .subscribe_topic_route_change(
+                    topic_list.clone(),
+                    self.client_config.mq_client_api_timeout,
+                )
+                .await
+            {
+                warn!("subscribeTopicRouteChange exception, err:{}", err);
+            }
+        }
+
         for topic in topic_list.iter() {
             self.update_topic_route_info_from_name_server_topic(topic)
                 .await;
         }
     }
 
+    /// Applies the routes pushed by `namesrv_addr`, skipping the ones older than the last push
+    /// received from it for the same topic.
+    pub async fn on_topic_route_change(&mut self, namesrv_addr: &str, body: TopicRouteChangeBody) {
+        for change in body.changes {
+            {
+                let mut version_table = self.topic_route_push_version_table.write().await;
+                if let Some((addr, version)) = version_table.get(&change.topic) {
+                    if addr == namesrv_addr && *version >= change.version {
+                        continue;
+                    }
+                }
+                version_table.insert(
+                    change.topic.clone(),
+                    (namesrv_addr.to_string(), change.version),
+                );
+            }
+            info!(
+                "the topic[{}] route info pushed by {}, version {}",
+                change.topic, namesrv_addr, change.version
+            );
+            let lock = self.lock_namesrv.lock().await;
+            if change.topic_route_data.queue_datas.is_empty() {
+                self.remove_topic_route_info(&change.topic).await;
+            } else {
+                self.update_topic_route_info(&change.topic, change.topic_route_data, true)
+                    .await;
+            }
+            drop(lock);
+        }
+    }
+
+    /// Forgets the route of a deleted topic, producers fall back to querying the name server on
+    /// their next send and consumers drop the queues of the topic on their next rebalance.
+    async fn remove_topic_route_info(&self, topic: &str) {
+        info!("the topic[{}] route info removed", topic);
+        self.topic_route_table.write().await.remove(topic);
+        self.topic_end_points_table.write().await.remove(topic);
+        let mut producer_table = self.producer_table.write().await;
+        for value in producer_table.values_mut() {
+            value.update_topic_publish_info(topic.to_string(), Some(TopicPublishInfo::new()));
+        }
+        drop(producer_table);
+        let mut consumer_table = self.consumer_table.write().await;
+        let subscribe_info = HashSet::new();
+        for value in consumer_table.values_mut() {
+            value
+                .update_topic_subscribe_info(topic, &subscribe_info)
+                .await;
+        }
+    }
+
     #[inline]
     pub async fn update_topic_route_info_from_name_server_topic(&mut self, topic: &str) -> bool {
         self.update_topic_route_info_from_name_server_default(topic, false, None)
@@ -414,6 +504,25 @@ where
         None
     }
 
+    /// Zone of each broker serving `topic`, brokers without a zone are left out.
+    pub async fn find_broker_zone_table(&self, topic: &str) -> HashMap<String, String> {
+        let topic_route_table = self.topic_route_table.read().await;
+        topic_route_table
+            .get(topic)
+            .map(|topic_route_data| {
+                topic_route_data
+                    .broker_datas
+                    .iter()
+                    .filter_map(|bd| {
+                        bd.zone_name()
+                            .clone()
+                            .map(|zone_name| (bd.broker_name().to_string(), zone_name))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub async fn update_topic_route_info_from_name_server_default(
         &mut self,
         topic: &str,
@@ -449,70 +558,11 @@ where
                 .await
                 .unwrap_or(None)
         };
-        if let Some(mut topic_route_data) = topic_route_data {
-            let mut topic_route_table = self.topic_route_table.write().await;
-            let old = topic_route_table.get(topic);
-            let mut changed = topic_route_data.topic_route_data_changed(old);
-            if !changed {
-                changed = self.is_need_update_topic_route_info(topic).await;
-            } else {
-                info!(
-                    "the topic[{}] route info changed, old[{:?}] ,new[{:?}]",
-                    topic, old, topic_route_data
-                )
-            }
-            if changed {
-                let mut broker_addr_table = self.broker_addr_table.write().await;
-                for bd in topic_route_data.broker_datas.iter() {
-                    broker_addr_table
-                        .insert(bd.broker_name().to_string(), bd.broker_addrs().clone());
-                }
-                drop(broker_addr_table);
-
-                // Update endpoint map
-                {
-                    let mq_end_points = ClientMetadata::topic_route_data2endpoints_for_static_topic(
-                        topic,
-                        &topic_route_data,
-                    );
-                    if let Some(mq_end_points) = mq_end_points {
-                        if !mq_end_points.is_empty() {
-                            let mut topic_end_points_table =
-                                self.topic_end_points_table.write().await;
-                            topic_end_points_table.insert(topic.to_string(), mq_end_points);
-                        }
-                    }
-                }
-
-                // Update Pub info
-                {
-                    let mut publish_info =
-                        topic_route_data2topic_publish_info(topic, &mut topic_route_data);
-                    publish_info.have_topic_router_info = true;
-                    let mut producer_table = self.producer_table.write().await;
-                    for (_, value) in producer_table.iter_mut() {
-                        value.update_topic_publish_info(
-                            topic.to_string(),
-                            Some(publish_info.clone()),
-                        );
-                    }
-                }
-
-                // Update sub info
-                {
-                    let mut consumer_table = self.consumer_table.write().await;
-                    if !consumer_table.is_empty() {
-                        let subscribe_info =
-                            topic_route_data2topic_subscribe_info(topic, &topic_route_data);
-                        for (_, value) in consumer_table.iter_mut() {
-                            value
-                                .update_topic_subscribe_info(topic, &subscribe_info)
-                                .await;
-                        }
-                    }
-                }
-                let clone_topic_route_data = TopicRouteData::from_existing(&topic_route_data);
-                topic_route_table.insert(topic.to_string(), clone_topic_route_data);
+        if let Some(topic_route_data) = topic_route_data {
+            if self
+                .update_topic_route_info(topic, topic_route_data, false)
+                .await
+            {
                 return true;
             }
         } else {
@@ -527,6 +577,77 @@ where
         false
     }
 
+    /// Applies `topic_route_data` to the route, publish and subscribe tables when it changed,
+    /// or unconditionally with `force`. Callers hold `lock_namesrv`.
+    async fn update_topic_route_info(
+        &self,
+        topic: &str,
+        mut topic_route_data: TopicRouteData,
+        force: bool,
+    ) -> bool {
+        let mut topic_route_table = self.topic_route_table.write().await;
+        let old = topic_route_table.get(topic);
+        let mut changed = force || topic_route_data.topic_route_data_changed(old);
+        if !changed {
+            changed = self.is_need_update_topic_route_info(topic).await;
+        } else {
+            info!(
+                "the topic[{}] route info changed, old[{:?}] ,new[{:?}]",
+                topic, old, topic_route_data
+            )
+        }
+        if changed {
+            let mut broker_addr_table = self.broker_addr_table.write().await;
+            for bd in topic_route_data.broker_datas.iter() {
+                broker_addr_table.insert(bd.broker_name().to_string(), bd.broker_addrs().clone());
+            }
+            drop(broker_addr_table);
+
+            // Update endpoint map
+            {
+                let mq_end_points = ClientMetadata::topic_route_data2endpoints_for_static_topic(
+                    topic,
+                    &topic_route_data,
+                );
+                if let Some(mq_end_points) = mq_end_points {
+                    if !mq_end_points.is_empty() {
+                        let mut topic_end_points_table = self.topic_end_points_table.write().await;
+                        topic_end_points_table.insert(topic.to_string(), mq_end_points);
+                    }
+                }
+            }
+
+            // Update Pub info
+            {
+                let mut publish_info =
+                    topic_route_data2topic_publish_info(topic, &mut topic_route_data);
+                publish_info.have_topic_router_info = true;
+                let mut producer_table = self.producer_table.write().await;
+                for (_, value) in producer_table.iter_mut() {
+                    value.update_topic_publish_info(topic.to_string(), Some(publish_info.clone()));
+                }
+            }
+
+            // Update sub info
+            {
+                let mut consumer_table = self.consumer_table.write().await;
+                if !consumer_table.is_empty() {
+                    let subscribe_info =
+                        topic_route_data2topic_subscribe_info(topic, &topic_route_data);
+                    for (_, value) in consumer_table.iter_mut() {
+                        value
+                            .update_topic_subscribe_info(topic, &subscribe_info)
+                            .await;
+                    }
+                }
+            }
+            let clone_topic_route_data = TopicRouteData::from_existing(&topic_route_data);
+            topic_route_table.insert(topic.to_string(), clone_topic_route_data);
+            return true;
+        }
+        false
+    }
+
     async fn is_need_update_topic_route_info(&self, topic: &str) -> bool {
         let mut result = false;
         let producer_table = self.producer_table.read().await;
@@ -1010,6 +1131,7 @@ pub fn topic_route_data2topic_publish_info(
             }
         }
     }
+    info.update_zone_message_queue_table();
     info
 }
 
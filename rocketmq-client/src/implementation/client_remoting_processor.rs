@@ -29,6 +29,9 @@ use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::header::reply_message_request_header::ReplyMessageRequestHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::remoting_command::DEFAULT_FRAME_MAX_LENGTH;
+use rocketmq_remoting::protocol::route::topic_route_change::TopicRouteChangeBody;
+use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use rocketmq_remoting::runtime::processor::RequestProcessor;
 use rocketmq_remoting::Result;
@@ -38,8 +41,14 @@ use tracing::warn;
 
 use crate::producer::request_future_holder::REQUEST_FUTURE_HOLDER;
 
+/// Route changes pushed by a name server, along with its address.
+pub type TopicRouteChangeSender = tokio::sync::mpsc::Sender<(String, TopicRouteChangeBody)>;
+
 #[derive(Clone)]
-pub struct ClientRemotingProcessor;
+pub struct ClientRemotingProcessor {
+    /// Set when `enable_topic_route_push` is on.
+    topic_route_change_tx: Option<TopicRouteChangeSender>,
+}
 
 impl RequestProcessor for ClientRemotingProcessor {
     async fn process_request(
@@ -52,6 +61,10 @@ impl RequestProcessor for ClientRemotingProcessor {
         info!("process_request: {:?}", request_code);
         match request_code {
             RequestCode::PushReplyMessageToClient => self.receive_reply_message(ctx, request).await,
+            RequestCode::NotifyTopicRouteChange => {
+                self.receive_topic_route_change(&channel, request);
+                Ok(None)
+            }
             _ => {
                 info!("Unknown request code: {:?}", request_code);
                 Ok(None)
@@ -61,6 +74,41 @@ impl RequestProcessor for ClientRemotingProcessor {
 }
 
 impl ClientRemotingProcessor {
+    pub fn new(topic_route_change_tx: Option<TopicRouteChangeSender>) -> Self {
+        ClientRemotingProcessor {
+            topic_route_change_tx,
+        }
+    }
+
+    /// Hands the pushed routes over to the client instance. Pushes that can not be handed over
+    /// are dropped, the routes are still picked up by the next poll.
+    fn receive_topic_route_change(&self, channel: &Channel, request: RemotingCommand) {
+        let Some(topic_route_change_tx) = self.topic_route_change_tx.as_ref() else {
+            return;
+        };
+        let body = match request
+            .body()
+            .as_ref()
+            .map(|body| TopicRouteChangeBody::decode(body))
+        {
+            Some(Ok(body)) => body,
+            Some(Err(err)) => {
+                warn!("decode topic route change failed: {}", err);
+                return;
+            }
+            None => return,
+        };
+        if let Err(err) =
+            topic_route_change_tx.try_send((channel.remote_address().to_string(), body))
+        {
+            warn!(
+                "drop topic route change from {}: {}",
+                channel.remote_address(),
+                err
+            );
+        }
+    }
+
     async fn receive_reply_message(
         &mut self,
         ctx: ConnectionHandlerContext,
@@ -108,7 +156,7 @@ impl ClientRemotingProcessor {
         if (sys_flag & MessageSysFlag::COMPRESSED_FLAG) == MessageSysFlag::COMPRESSED_FLAG {
             let de_result =
                 CompressorFactory::get_compressor(MessageSysFlag::get_compression_type(sys_flag))
-                    .decompress(body.unwrap())
+                    .decompress(body.unwrap(), DEFAULT_FRAME_MAX_LENGTH)
                     .map(Bytes::from);
             if let Ok(decompressed) = de_result {
                 msg.message.body = Some(decompressed);
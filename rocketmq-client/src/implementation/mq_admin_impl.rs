@@ -14,11 +14,20 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::ArcRefCellWrapper;
+use rocketmq_remoting::protocol::header::query_message_request_header::QueryMessageRequestHeader;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+use tracing::warn;
 
 use crate::base::client_config::ClientConfig;
+use crate::base::query_result::QueryResult;
 use crate::error::MQClientError::MQClientErr;
 use crate::factory::mq_client_instance;
 use crate::implementation::mq_client_api_impl::MQClientAPIImpl;
@@ -92,6 +101,136 @@ impl MQAdminImpl {
         ))
     }
 
+    /// Queries one page of messages by key (or by unique key) on the master of every broker
+    /// serving `topic`. The returned continuation token records the next page of each broker
+    /// that still has results; brokers missing from a token are exhausted.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn query_message(
+        &mut self,
+        topic: &str,
+        key: &str,
+        max_num: i32,
+        begin: i64,
+        end: i64,
+        is_unique_key: bool,
+        continuation_token: Option<&str>,
+        mut mq_client_api_impl: ArcRefCellWrapper<MQClientAPIImpl>,
+        client_config: &mut ClientConfig,
+    ) -> Result<QueryResult> {
+        let broker_tokens = match continuation_token {
+            Some(token) => Some(decode_continuation_token(token)?),
+            None => None,
+        };
+        let topic_route_data = mq_client_api_impl
+            .get_topic_route_info_from_name_server_detail(topic, self.timeout_millis, true)
+            .await?;
+        let Some(topic_route_data) = topic_route_data else {
+            return Err(MQClientErr(
+                -1,
+                format!("The topic[{}] not matched route info", topic),
+            ));
+        };
+
+        let mut index_last_update_timestamp = 0;
+        let mut message_list = Vec::new();
+        let mut next_tokens = HashMap::new();
+        for broker_data in topic_route_data.broker_datas.iter() {
+            let broker_name = broker_data.broker_name();
+            let broker_token = match broker_tokens.as_ref() {
+                Some(tokens) => match tokens.get(broker_name) {
+                    Some(token) => Some(token.clone()),
+                    None => continue,
+                },
+                None => None,
+            };
+            let Some(addr) = broker_data
+                .broker_addrs()
+                .get(&(mix_all::MASTER_ID as i64))
+                .cloned()
+                .or_else(|| broker_data.select_broker_addr())
+            else {
+                continue;
+            };
+            let request_header = QueryMessageRequestHeader {
+                topic: topic.to_string(),
+                key: key.to_string(),
+                max_num,
+                begin_timestamp: begin,
+                end_timestamp: end,
+                continuation_token: broker_token.clone().filter(|token| !token.is_empty()),
+                topic_request_header: None,
+            };
+            match mq_client_api_impl
+                .query_message(
+                    addr.as_str(),
+                    request_header,
+                    self.timeout_millis,
+                    is_unique_key,
+                )
+                .await
+            {
+                Ok((response_header, msg_list)) => {
+                    index_last_update_timestamp = index_last_update_timestamp
+                        .max(response_header.index_last_update_timestamp as u64);
+                    if let Some(token) = response_header.continuation_token {
+                        next_tokens.insert(broker_name.to_string(), token);
+                    }
+                    message_list.extend(msg_list);
+                }
+                Err(e) => {
+                    // Keep the broker in the token so the caller can retry this page.
+                    warn!("queryMessage from broker {} exception, {}", addr, e);
+                    next_tokens.insert(broker_name.to_string(), broker_token.unwrap_or_default());
+                }
+            }
+        }
+
+        let namespace = client_config.get_namespace().unwrap_or_default();
+        for msg in message_list.iter_mut() {
+            let user_topic = NamespaceUtil::without_namespace_with_namespace(
+                msg.get_topic(),
+                namespace.as_str(),
+            );
+            msg.set_topic(user_topic.as_str());
+        }
+        message_list.sort_by_key(|msg| Reverse(msg.store_timestamp));
+
+        let mut query_result = QueryResult::new(index_last_update_timestamp, message_list);
+        if !next_tokens.is_empty() {
+            query_result.set_continuation_token(Some(encode_continuation_token(&next_tokens)));
+        }
+        Ok(query_result)
+    }
+
+    /// Looks a message up by its unique key (client message id), returning the earliest stored
+    /// copy when the message was stored more than once.
+    pub async fn query_message_by_uniq_key(
+        &mut self,
+        topic: &str,
+        uniq_key: &str,
+        mq_client_api_impl: ArcRefCellWrapper<MQClientAPIImpl>,
+        client_config: &mut ClientConfig,
+    ) -> Result<Option<MessageExt>> {
+        let query_result = self
+            .query_message(
+                topic,
+                uniq_key,
+                32,
+                0,
+                i64::MAX,
+                true,
+                None,
+                mq_client_api_impl,
+                client_config,
+            )
+            .await?;
+        Ok(query_result
+            .message_list()
+            .iter()
+            .min_by_key(|msg| msg.store_timestamp)
+            .cloned())
+    }
+
     pub async fn max_offset(&mut self, mq: &MessageQueue) -> Result<i64> {
         unimplemented!("max_offset")
     }
@@ -99,3 +238,27 @@ impl MQAdminImpl {
         unimplemented!("max_offset")
     }
 }
+
+fn encode_continuation_token(broker_tokens: &HashMap<String, String>) -> String {
+    serde_json::to_string(broker_tokens).unwrap_or_default()
+}
+
+fn decode_continuation_token(token: &str) -> Result<HashMap<String, String>> {
+    serde_json::from_str(token)
+        .map_err(|_| MQClientErr(-1, format!("invalid continuation token {}", token)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuation_token_round_trip() {
+        let mut broker_tokens = HashMap::new();
+        broker_tokens.insert("broker-a".to_string(), "idx-1f".to_string());
+        broker_tokens.insert("broker-b".to_string(), String::new());
+        let token = encode_continuation_token(&broker_tokens);
+        assert_eq!(decode_continuation_token(&token).unwrap(), broker_tokens);
+        assert!(decode_continuation_token("idx-1f").is_err());
+    }
+}
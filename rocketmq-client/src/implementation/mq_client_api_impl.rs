@@ -14,14 +14,18 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::collections::HashSet;
 use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
 use std::time::Instant;
 
 use bytes::Bytes;
 use lazy_static::lazy_static;
+use rocketmq_common::common::compression::compression_type::CompressionType;
 use rocketmq_common::common::message::message_batch::MessageBatch;
 use rocketmq_common::common::message::message_client_id_setter::MessageClientIDSetter;
+use rocketmq_common::common::message::message_decoder;
+use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::message::MessageConst;
 use rocketmq_common::common::message::MessageTrait;
@@ -31,6 +35,8 @@ use rocketmq_common::common::namesrv::name_server_update_callback::NameServerUpd
 use rocketmq_common::common::namesrv::top_addressing::TopAddressing;
 use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_common::ArcRefCellWrapper;
+use rocketmq_remoting::acl::plain_access_config::AclConfig;
+use rocketmq_remoting::acl::plain_access_config::PlainAccessConfig;
 use rocketmq_remoting::base::connection_net_event::ConnectionNetEvent;
 use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
 use rocketmq_remoting::clients::RemotingClient;
@@ -39,16 +45,22 @@ use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::protocol::body::check_client_request_body::CheckClientRequestBody;
 use rocketmq_remoting::protocol::body::get_consumer_listby_group_response_body::GetConsumerListByGroupResponseBody;
 use rocketmq_remoting::protocol::header::client_request_header::GetRouteInfoRequestHeader;
+use rocketmq_remoting::protocol::header::create_access_config_request_header::CreateAccessConfigRequestHeader;
+use rocketmq_remoting::protocol::header::delete_access_config_request_header::DeleteAccessConfigRequestHeader;
 use rocketmq_remoting::protocol::header::get_consumer_listby_group_request_header::GetConsumerListByGroupRequestHeader;
 use rocketmq_remoting::protocol::header::heartbeat_request_header::HeartbeatRequestHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::SendMessageRequestHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header_v2::SendMessageRequestHeaderV2;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_response_header::SendMessageResponseHeader;
+use rocketmq_remoting::protocol::header::query_message_request_header::QueryMessageRequestHeader;
+use rocketmq_remoting::protocol::header::query_message_response_header::QueryMessageResponseHeader;
 use rocketmq_remoting::protocol::header::update_consumer_offset_header::UpdateConsumerOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::update_global_white_addrs_config_request_header::UpdateGlobalWhiteAddrsConfigRequestHeader;
 use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::topic_route_change::SubscribeTopicRouteChangeRequestBody;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
 use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::protocol::RemotingSerializable;
@@ -191,7 +203,8 @@ impl MQClientAPIImpl {
         let request = RemotingCommand::create_request_command(
             RequestCode::GetRouteinfoByTopic,
             request_header,
-        );
+        )
+        .accept_body_compression(CompressionType::Zstd);
         let response = self
             .remoting_client
             .invoke_async(None, request, timeout_millis)
@@ -235,6 +248,31 @@ impl MQClientAPIImpl {
         }
     }
 
+    /// Subscribes to route changes of `topics` on the connection to the chosen name server,
+    /// replacing the topics subscribed before.
+    pub async fn subscribe_topic_route_change(
+        &self,
+        topics: HashSet<String>,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request =
+            RemotingCommand::create_remoting_command(RequestCode::SubscribeTopicRouteChange)
+                .set_body(Some(Bytes::from(
+                    SubscribeTopicRouteChangeRequestBody { topics }.encode(),
+                )));
+        let response = self
+            .remoting_client
+            .invoke_async(None, request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) != ResponseCode::Success {
+            return Err(MQClientError::MQClientErr(
+                response.code(),
+                response.remark().map_or("".to_string(), |s| s.to_string()),
+            ));
+        }
+        Ok(())
+    }
+
     pub fn get_name_server_address_list(&self) -> &[String] {
         self.remoting_client.get_name_server_address_list()
     }
@@ -729,6 +767,48 @@ impl MQClientAPIImpl {
         ))
     }
 
+    /// Queries one page of messages from the index of the broker at `addr`. A `QueryNotFound`
+    /// response is an empty page rather than an error.
+    pub async fn query_message(
+        &mut self,
+        addr: &str,
+        request_header: QueryMessageRequestHeader,
+        timeout_millis: u64,
+        is_unique_key: bool,
+    ) -> Result<(QueryMessageResponseHeader, Vec<MessageExt>)> {
+        let mut request =
+            RemotingCommand::create_request_command(RequestCode::QueryMessage, request_header);
+        request.add_ext_field(mix_all::UNIQUE_MSG_QUERY_FLAG, is_unique_key.to_string());
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        match ResponseCode::from(response.code()) {
+            ResponseCode::Success | ResponseCode::QueryNotFound => {
+                let response_header = response
+                    .decode_command_custom_header::<QueryMessageResponseHeader>()
+                    .unwrap_or_default();
+                let msg_list = match response.body() {
+                    Some(body) => message_decoder::decodes(&mut body.clone(), true, true),
+                    None => Vec::new(),
+                };
+                Ok((response_header, msg_list))
+            }
+            _ => Err(MQClientError::MQBrokerError(
+                response.code(),
+                response.remark().map_or("".to_string(), |s| s.to_string()),
+                addr.to_string(),
+            )),
+        }
+    }
+
     pub async fn update_consumer_offset_oneway(
         &mut self,
         addr: &str,
@@ -769,4 +849,92 @@ impl MQClientAPIImpl {
             Ok(())
         }
     }
+    /// Creates or replaces the ACL account of `access_config.access_key` on the broker at
+    /// `addr`.
+    pub async fn create_plain_access_config(
+        &mut self,
+        addr: &str,
+        access_config: &PlainAccessConfig,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request = RemotingCommand::create_request_command(
+            RequestCode::UpdateAndCreateAclConfig,
+            CreateAccessConfigRequestHeader::from(access_config),
+        );
+        self.invoke_broker_acl_command(addr, request, timeout_millis)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn delete_access_config(
+        &mut self,
+        addr: &str,
+        access_key: &str,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request = RemotingCommand::create_request_command(
+            RequestCode::DeleteAclConfig,
+            DeleteAccessConfigRequestHeader {
+                access_key: access_key.to_string(),
+            },
+        );
+        self.invoke_broker_acl_command(addr, request, timeout_millis)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn update_global_white_addrs_config(
+        &mut self,
+        addr: &str,
+        global_white_addrs: &[String],
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request = RemotingCommand::create_request_command(
+            RequestCode::UpdateGlobalWhiteAddrsConfig,
+            UpdateGlobalWhiteAddrsConfigRequestHeader {
+                global_white_addrs: global_white_addrs.join(","),
+            },
+        );
+        self.invoke_broker_acl_command(addr, request, timeout_millis)
+            .await
+            .map(|_| ())
+    }
+
+    /// Fetches the ACL accounts and global white addresses of the broker at `addr`.
+    pub async fn get_broker_cluster_acl_config(
+        &mut self,
+        addr: &str,
+        timeout_millis: u64,
+    ) -> Result<AclConfig> {
+        let request =
+            RemotingCommand::create_remoting_command(RequestCode::GetBrokerClusterAclConfig);
+        let response = self
+            .invoke_broker_acl_command(addr, request, timeout_millis)
+            .await?;
+        match response.body() {
+            Some(body) => AclConfig::decode(body)
+                .map_err(|err| MQClientError::MQClientErr(-1, err.to_string())),
+            None => Ok(AclConfig::default()),
+        }
+    }
+
+    async fn invoke_broker_acl_command(
+        &mut self,
+        addr: &str,
+        request: RemotingCommand,
+        timeout_millis: u64,
+    ) -> Result<RemotingCommand> {
+        let response = self
+            .remoting_client
+            .invoke_async(Some(addr.to_string()), request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) != ResponseCode::Success {
+            return Err(MQClientError::MQBrokerError(
+                response.code(),
+                response.remark().map_or("".to_string(), |s| s.to_string()),
+                addr.to_string(),
+            ));
+        }
+        Ok(response)
+    }
 }
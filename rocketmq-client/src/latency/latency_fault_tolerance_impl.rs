@@ -78,7 +78,11 @@ impl LatencyFaultTolerance<String> for LatencyFaultToleranceImpl {
     }
 
     fn is_reachable(&self, name: &String) -> bool {
-        todo!()
+        let fault_item_table = self.fault_item_table.lock();
+        if let Some(fault_item) = fault_item_table.get(name) {
+            return fault_item.is_reachable();
+        }
+        true
     }
 
     fn remove(&mut self, name: &String) {
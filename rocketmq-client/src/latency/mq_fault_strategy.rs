@@ -42,6 +42,7 @@ pub struct MQFaultStrategy {
     not_available_duration: &'static [u64],
     reachable_filter: Box<dyn QueueFilter>,
     available_filter: Box<dyn QueueFilter>,
+    zone_name: Option<String>,
 }
 
 impl MQFaultStrategy {
@@ -61,6 +62,7 @@ impl MQFaultStrategy {
             available_filter: Box::new(AvailableFilter {
                 latency_fault_tolerance,
             }),
+            zone_name: client_config.zone_aware_zone_name().map(str::to_string),
         }
     }
 
@@ -89,29 +91,50 @@ impl MQFaultStrategy {
         THREAD_BROKER_FILTER.with(|filer| {
             filer.borrow_mut().last_broker_name = last_broker_name.map(|s| s.to_string());
         });
-        if self.send_latency_fault_enable.load(Ordering::Relaxed) {
-            if reset_index {
-                tp_info.reset_index();
-            }
-            let broker_filter = THREAD_BROKER_FILTER.with_borrow(|f| f.clone());
-            let filter = &[self.available_filter.as_ref(), &broker_filter];
-            let mut mq = tp_info.select_one_message_queue(filter);
+        if reset_index && self.send_latency_fault_enable.load(Ordering::Relaxed) {
+            tp_info.reset_index();
+        }
+        let broker_filter = THREAD_BROKER_FILTER.with_borrow(|f| f.clone());
+        if let Some(zone_name) = self.zone_name.as_deref() {
+            // Only fall back to the other zones once no queue of the local zone passes the
+            // filters, so a failing local broker does not keep receiving the retries.
+            let mq = self.select_with_filters(&broker_filter, false, |filters| {
+                tp_info.select_one_message_queue_in_zone(zone_name, filters)
+            });
             if mq.is_some() {
                 return mq;
             }
-            let filter = &[self.reachable_filter.as_ref(), &broker_filter];
-            mq = tp_info.select_one_message_queue(filter);
+        }
+        self.select_with_filters(&broker_filter, true, |filters| {
+            tp_info.select_one_message_queue(filters)
+        })
+    }
+
+    fn select_with_filters<F>(
+        &self,
+        broker_filter: &BrokerFilter,
+        unfiltered_fallback: bool,
+        select: F,
+    ) -> Option<MessageQueue>
+    where
+        F: Fn(&[&dyn QueueFilter]) -> Option<MessageQueue>,
+    {
+        if self.send_latency_fault_enable.load(Ordering::Relaxed) {
+            let mut mq = select(&[self.available_filter.as_ref(), broker_filter]);
             if mq.is_some() {
                 return mq;
             }
-            return tp_info.select_one_message_queue(&[]);
+            mq = select(&[self.reachable_filter.as_ref(), broker_filter]);
+            if mq.is_some() || !unfiltered_fallback {
+                return mq;
+            }
+            return select(&[]);
         }
-        let broker_filter = THREAD_BROKER_FILTER.with_borrow(|f| f.clone());
-        let mq = tp_info.select_one_message_queue(&[&broker_filter]);
-        if mq.is_some() {
+        let mq = select(&[broker_filter]);
+        if mq.is_some() || !unfiltered_fallback {
             return mq;
         }
-        tp_info.select_one_message_queue(&[])
+        select(&[])
     }
 
     pub fn get_latency_max(&self) -> &'static [u64] {
@@ -196,3 +219,61 @@ impl QueueFilter for AvailableFilter {
         tolerance.is_available(&message_queue.get_broker_name().to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::producer::producer_impl::topic_publish_info::tests::zoned_publish_info;
+
+    fn zone_aware_strategy(zone_name: &str) -> MQFaultStrategy {
+        MQFaultStrategy::new(&ClientConfig {
+            zone_name: Some(zone_name.to_string()),
+            enable_zone_aware_routing: true,
+            send_latency_enable: true,
+            ..ClientConfig::default()
+        })
+    }
+
+    fn selected_brokers(strategy: &MQFaultStrategy, tp_info: &TopicPublishInfo) -> Vec<String> {
+        (0..4)
+            .map(|_| {
+                strategy
+                    .select_one_message_queue(tp_info, None, false)
+                    .unwrap()
+                    .get_broker_name()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prefer_queues_in_own_zone() {
+        let tp_info = zoned_publish_info(&[("broker-a", Some("az1")), ("broker-b", Some("az2"))]);
+        let strategy = zone_aware_strategy("az2");
+        assert_eq!(selected_brokers(&strategy, &tp_info), vec!["broker-b"; 4]);
+    }
+
+    #[test]
+    fn fall_back_when_own_zone_has_no_queues() {
+        let tp_info = zoned_publish_info(&[("broker-a", Some("az1")), ("broker-b", None)]);
+        let strategy = zone_aware_strategy("az2");
+        let brokers = selected_brokers(&strategy, &tp_info);
+        assert!(brokers.iter().any(|broker| broker == "broker-a"));
+        assert!(brokers.iter().any(|broker| broker == "broker-b"));
+    }
+
+    #[test]
+    fn fall_back_when_own_zone_is_faulty() {
+        let tp_info = zoned_publish_info(&[
+            ("broker-a", Some("az1")),
+            ("broker-b", Some("az2")),
+            ("broker-c", Some("az2")),
+        ]);
+        let strategy = zone_aware_strategy("az2");
+        strategy.update_fault_item("broker-b", 0, true, false);
+        assert_eq!(selected_brokers(&strategy, &tp_info), vec!["broker-c"; 4]);
+
+        strategy.update_fault_item("broker-c", 0, true, false);
+        assert_eq!(selected_brokers(&strategy, &tp_info), vec!["broker-a"; 4]);
+    }
+}
@@ -23,6 +23,7 @@ use rocketmq_common::common::compression::compressor::Compressor;
 use rocketmq_common::common::compression::compressor_factory::CompressorFactory;
 use rocketmq_common::common::message::message_batch::MessageBatch;
 use rocketmq_common::common::message::message_client_id_setter::MessageClientIDSetter;
+use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::message::message_single::Message;
 use rocketmq_common::common::message::MessageConst;
@@ -37,6 +38,7 @@ use rocketmq_remoting::runtime::RPCHook;
 use tracing::error;
 
 use crate::base::client_config::ClientConfig;
+use crate::base::query_result::QueryResult;
 use crate::base::validators::Validators;
 use crate::error::MQClientError::MQClientErr;
 use crate::producer::default_mq_produce_builder::DefaultMQProducerBuilder;
@@ -615,6 +617,39 @@ impl DefaultMQProducer {
     pub fn with_namespace(&mut self, resource: &str) -> String {
         self.client_config.with_namespace(resource)
     }
+
+    /// Queries messages whose keys contain `key` and whose store time falls in
+    /// `[begin, end]`. Pass the token of the previous result to fetch the next page.
+    pub async fn query_message(
+        &mut self,
+        topic: &str,
+        key: &str,
+        max_num: i32,
+        begin: i64,
+        end: i64,
+        continuation_token: Option<&str>,
+    ) -> Result<QueryResult> {
+        let topic = self.with_namespace(topic);
+        self.default_mqproducer_impl
+            .as_mut()
+            .unwrap()
+            .query_message(topic.as_str(), key, max_num, begin, end, continuation_token)
+            .await
+    }
+
+    /// Looks a message up by the client message id assigned when it was sent.
+    pub async fn query_message_by_uniq_key(
+        &mut self,
+        topic: &str,
+        uniq_key: &str,
+    ) -> Result<Option<MessageExt>> {
+        let topic = self.with_namespace(topic);
+        self.default_mqproducer_impl
+            .as_mut()
+            .unwrap()
+            .query_message_by_uniq_key(topic.as_str(), uniq_key)
+            .await
+    }
 }
 
 impl MQProducer for DefaultMQProducer {
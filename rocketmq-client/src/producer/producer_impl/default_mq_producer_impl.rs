@@ -56,6 +56,7 @@ use tokio_util::bytes::Bytes;
 use tracing::warn;
 
 use crate::base::client_config::ClientConfig;
+use crate::base::query_result::QueryResult;
 use crate::base::validators::Validators;
 use crate::common::client_error_code::ClientErrorCode;
 use crate::error::MQClientError;
@@ -1397,6 +1398,48 @@ impl DefaultMQProducerImpl {
             .await
     }
 
+    pub async fn query_message(
+        &mut self,
+        topic: &str,
+        key: &str,
+        max_num: i32,
+        begin: i64,
+        end: i64,
+        continuation_token: Option<&str>,
+    ) -> Result<QueryResult> {
+        self.make_sure_state_ok()?;
+        let client_instance = self.client_instance.as_mut().unwrap();
+        let mq_client_api_impl = client_instance.mq_client_api_impl.clone();
+        client_instance
+            .mq_admin_impl
+            .query_message(
+                topic,
+                key,
+                max_num,
+                begin,
+                end,
+                false,
+                continuation_token,
+                mq_client_api_impl,
+                &mut self.client_config,
+            )
+            .await
+    }
+
+    pub async fn query_message_by_uniq_key(
+        &mut self,
+        topic: &str,
+        uniq_key: &str,
+    ) -> Result<Option<MessageExt>> {
+        self.make_sure_state_ok()?;
+        let client_instance = self.client_instance.as_mut().unwrap();
+        let mq_client_api_impl = client_instance.mq_client_api_impl.clone();
+        client_instance
+            .mq_admin_impl
+            .query_message_by_uniq_key(topic, uniq_key, mq_client_api_impl, &mut self.client_config)
+            .await
+    }
+
     pub async fn request_with_selector<M, S, T>(
         &mut self,
         mut msg: M,
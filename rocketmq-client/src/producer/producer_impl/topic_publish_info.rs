@@ -14,6 +14,8 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::collections::HashMap;
+
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
 
@@ -27,6 +29,8 @@ pub struct TopicPublishInfo {
     pub message_queue_list: Vec<MessageQueue>,
     pub send_which_queue: ThreadLocalIndex,
     pub topic_route_data: Option<TopicRouteData>,
+    /// Writable queues grouped by the zone of the broker hosting them.
+    pub zone_message_queue_table: HashMap<String /* zone */, Vec<MessageQueue>>,
 }
 
 impl TopicPublishInfo {
@@ -37,6 +41,7 @@ impl TopicPublishInfo {
             message_queue_list: vec![],
             send_which_queue: ThreadLocalIndex,
             topic_route_data: None,
+            zone_message_queue_table: HashMap::new(),
         }
     }
 
@@ -53,6 +58,44 @@ impl TopicPublishInfo {
         self.select_one_message_queue_with_filters(&self.message_queue_list, filters)
     }
 
+    /// Same as `select_one_message_queue`, restricted to the queues of brokers in `zone_name`.
+    #[inline]
+    pub fn select_one_message_queue_in_zone(
+        &self,
+        zone_name: &str,
+        filters: &[&dyn QueueFilter],
+    ) -> Option<MessageQueue> {
+        let message_queue_list = self.zone_message_queue_table.get(zone_name)?;
+        self.select_one_message_queue_with_filters(message_queue_list, filters)
+    }
+
+    /// Rebuilds `zone_message_queue_table` from `message_queue_list` and the zones of the brokers
+    /// in `topic_route_data`, queues of brokers without a zone are left out.
+    pub fn update_zone_message_queue_table(&mut self) {
+        self.zone_message_queue_table.clear();
+        let Some(topic_route_data) = self.topic_route_data.as_ref() else {
+            return;
+        };
+        let broker_zones = topic_route_data
+            .broker_datas
+            .iter()
+            .filter_map(|broker_data| {
+                broker_data
+                    .zone_name()
+                    .as_ref()
+                    .map(|zone_name| (broker_data.broker_name(), zone_name.as_str()))
+            })
+            .collect::<HashMap<&str, &str>>();
+        for mq in &self.message_queue_list {
+            if let Some(zone_name) = broker_zones.get(mq.get_broker_name()) {
+                self.zone_message_queue_table
+                    .entry(zone_name.to_string())
+                    .or_default()
+                    .push(mq.clone());
+            }
+        }
+    }
+
     fn select_one_message_queue_with_filters(
         &self,
         message_queue_list: &[MessageQueue],
@@ -83,3 +126,59 @@ impl TopicPublishInfo {
         Some(message_queue_list[index as usize].clone())
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use rocketmq_remoting::protocol::route::route_data_view::BrokerData;
+
+    use super::*;
+
+    pub(crate) fn zoned_publish_info(broker_zones: &[(&str, Option<&str>)]) -> TopicPublishInfo {
+        let mut topic_route_data = TopicRouteData::default();
+        let mut tp_info = TopicPublishInfo::new();
+        for (broker_name, zone_name) in broker_zones {
+            topic_route_data.broker_datas.push(BrokerData::new(
+                "DefaultCluster".to_string(),
+                broker_name.to_string(),
+                HashMap::new(),
+                zone_name.map(str::to_string),
+            ));
+            for queue_id in 0..2 {
+                tp_info.message_queue_list.push(MessageQueue::from_parts(
+                    "TopicTest",
+                    *broker_name,
+                    queue_id,
+                ));
+            }
+        }
+        tp_info.topic_route_data = Some(topic_route_data);
+        tp_info.update_zone_message_queue_table();
+        tp_info
+    }
+
+    #[test]
+    fn group_queues_by_broker_zone() {
+        let tp_info = zoned_publish_info(&[
+            ("broker-a", Some("az1")),
+            ("broker-b", Some("az2")),
+            ("broker-c", None),
+        ]);
+        assert_eq!(tp_info.zone_message_queue_table.len(), 2);
+        assert_eq!(
+            tp_info.zone_message_queue_table["az1"],
+            vec![
+                MessageQueue::from_parts("TopicTest", "broker-a", 0),
+                MessageQueue::from_parts("TopicTest", "broker-a", 1),
+            ]
+        );
+        for _ in 0..4 {
+            let mq = tp_info
+                .select_one_message_queue_in_zone("az2", &[])
+                .unwrap();
+            assert_eq!(mq.get_broker_name(), "broker-b");
+        }
+        assert!(tp_info
+            .select_one_message_queue_in_zone("az3", &[])
+            .is_none());
+    }
+}
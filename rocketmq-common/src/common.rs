@@ -35,6 +35,7 @@ pub mod config;
 pub mod config_manager;
 pub mod constant;
 pub mod consumer;
+pub mod controller;
 mod faq;
 pub mod filter;
 pub mod future;
@@ -19,6 +19,7 @@ pub mod attribute_parser;
 pub mod attribute_util;
 pub mod cleanup_policy;
 pub mod cq_type;
+pub mod long_range_attribute;
 pub mod topic_attributes;
 pub mod topic_message_type;
 
@@ -49,6 +50,20 @@ pub trait AttributeTrait {
     fn verify(&self, value: &str);
 }
 
+impl<T: AttributeTrait + ?Sized> AttributeTrait for std::sync::Arc<T> {
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    fn changeable(&self) -> bool {
+        (**self).changeable()
+    }
+
+    fn verify(&self, value: &str) {
+        (**self).verify(value)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Attribute {
     pub(crate) name: String,
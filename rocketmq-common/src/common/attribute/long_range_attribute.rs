@@ -0,0 +1,117 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use crate::common::attribute::Attribute;
+use crate::common::attribute::AttributeTrait;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LongRangeAttribute {
+    pub(crate) attribute: Attribute,
+    pub(crate) min: i64,
+    pub(crate) max: i64,
+    pub(crate) default_value: i64,
+}
+
+impl AttributeTrait for LongRangeAttribute {
+    fn name(&self) -> String {
+        self.attribute.name.clone()
+    }
+
+    fn changeable(&self) -> bool {
+        self.attribute.changeable
+    }
+
+    fn verify(&self, value: &str) {
+        let value = value
+            .parse::<i64>()
+            .unwrap_or_else(|_| panic!("value is not a number: {}", value));
+        if value < self.min || value > self.max {
+            panic!(
+                "value is not in range({}, {}), value: {}",
+                self.min, self.max, value
+            );
+        }
+    }
+}
+
+impl LongRangeAttribute {
+    pub fn get_name(&self) -> &str {
+        self.attribute.name.as_str()
+    }
+
+    pub fn get_default_value(&self) -> i64 {
+        self.default_value
+    }
+
+    pub fn get_min(&self) -> i64 {
+        self.min
+    }
+
+    pub fn get_max(&self) -> i64 {
+        self.max
+    }
+
+    /// Parses `value`, falling back to the default value when it is absent or out of range.
+    pub fn parse(&self, value: Option<&str>) -> i64 {
+        match value.and_then(|value| value.parse::<i64>().ok()) {
+            Some(value) if value >= self.min && value <= self.max => value,
+            _ => self.default_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute() -> LongRangeAttribute {
+        LongRangeAttribute {
+            attribute: Attribute {
+                name: String::from("test.long"),
+                changeable: true,
+            },
+            min: -1,
+            max: 100,
+            default_value: -1,
+        }
+    }
+
+    #[test]
+    fn verify_accepts_values_in_range() {
+        attribute().verify("100");
+        attribute().verify("-1");
+    }
+
+    #[test]
+    #[should_panic(expected = "value is not in range")]
+    fn verify_rejects_values_out_of_range() {
+        attribute().verify("101");
+    }
+
+    #[test]
+    #[should_panic(expected = "value is not a number")]
+    fn verify_rejects_non_numeric_values() {
+        attribute().verify("ten");
+    }
+
+    #[test]
+    fn parse_falls_back_to_default_value() {
+        assert_eq!(attribute().parse(Some("42")), 42);
+        assert_eq!(attribute().parse(Some("1000")), -1);
+        assert_eq!(attribute().parse(Some("ten")), -1);
+        assert_eq!(attribute().parse(None), -1);
+    }
+}
@@ -17,6 +17,7 @@
 
 use std::any::Any;
 use std::collections::HashMap;
+use std::env;
 
 use lazy_static::lazy_static;
 use serde::Deserialize;
@@ -102,7 +103,7 @@ impl BrokerIdentity {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct BrokerConfig {
     pub broker_identity: BrokerIdentity,
 
@@ -118,8 +119,22 @@ pub struct BrokerConfig {
     pub trace_topic_enable: bool,
     pub msg_trace_topic_name: String,
     pub enable_controller_mode: bool,
+    /// Controller addresses separated by `;`, used when `enable_controller_mode` is set.
+    pub controller_addr: Option<String>,
+    /// Interval, in milliseconds, between two fetches of the master of the broker set from
+    /// the controller.
+    pub sync_broker_metadata_period: u64,
+    pub broker_heartbeat_interval: u64,
+    /// How long the controller waits for a heartbeat before electing another master.
+    pub controller_heart_beat_timeout_mills: u64,
+    /// Lower values are preferred when the controller elects among equally up-to-date
+    /// replicas.
+    pub broker_election_priority: i32,
     pub broker_name: String,
     pub region_id: String,
+    /// Availability zone the broker is deployed in, reported to the name server so clients can
+    /// prefer brokers in their own zone.
+    pub zone_name: Option<String>,
     pub trace_on: bool,
     pub broker_permission: u32,
     pub async_send_enable: bool,
@@ -169,6 +184,25 @@ pub struct BrokerConfig {
     pub auto_delete_unused_stats: bool,
     pub forward_timeout: u64,
     pub store_reply_message_enable: bool,
+    pub send_message_thread_pool_nums: usize,
+    pub pull_message_thread_pool_nums: usize,
+    pub query_message_thread_pool_nums: usize,
+    pub admin_broker_thread_pool_nums: usize,
+    pub heartbeat_thread_pool_nums: usize,
+    pub consumer_manage_thread_pool_nums: usize,
+    pub send_thread_pool_queue_capacity: usize,
+    pub pull_thread_pool_queue_capacity: usize,
+    pub query_thread_pool_queue_capacity: usize,
+    pub admin_broker_thread_pool_queue_capacity: usize,
+    pub heartbeat_thread_pool_queue_capacity: usize,
+    pub consumer_manager_thread_pool_queue_capacity: usize,
+    /// Requests of one client connection processed at the same time, extra ones are
+    /// rejected with `SYSTEM_BUSY`.
+    pub max_in_flight_requests_per_connection: usize,
+    /// Checks the signature and the topic and group permissions of client requests against
+    /// the plain ACL file.
+    pub acl_enable: bool,
+    pub plain_acl_file_path: String,
 }
 
 impl Default for BrokerConfig {
@@ -190,8 +224,16 @@ impl Default for BrokerConfig {
             trace_topic_enable: false,
             msg_trace_topic_name: TopicValidator::RMQ_SYS_TRACE_TOPIC.to_string(),
             enable_controller_mode: false,
+            controller_addr: None,
+            sync_broker_metadata_period: 1000 * 5,
+            broker_heartbeat_interval: 1000,
+            controller_heart_beat_timeout_mills: 1000 * 10,
+            broker_election_priority: i32::MAX,
             broker_name: default_broker_name(),
             region_id: mix_all::DEFAULT_TRACE_REGION_ID.to_string(),
+            zone_name: env::var(mix_all::ROCKETMQ_ZONE_PROPERTY)
+                .or_else(|_| env::var(mix_all::ROCKETMQ_ZONE_ENV))
+                .ok(),
             trace_on: true,
             broker_permission: PermName::PERM_WRITE | PermName::PERM_READ,
             async_send_enable: false,
@@ -245,6 +287,21 @@ impl Default for BrokerConfig {
             enable_mixed_message_type: false,
             auto_delete_unused_stats: false,
             store_reply_message_enable: true,
+            send_message_thread_pool_nums: num_cpus::get().min(4),
+            pull_message_thread_pool_nums: 16 + num_cpus::get() * 2,
+            query_message_thread_pool_nums: 8 + num_cpus::get(),
+            admin_broker_thread_pool_nums: 16,
+            heartbeat_thread_pool_nums: num_cpus::get().min(32),
+            consumer_manage_thread_pool_nums: 32,
+            send_thread_pool_queue_capacity: 10000,
+            pull_thread_pool_queue_capacity: 100000,
+            query_thread_pool_queue_capacity: 20000,
+            admin_broker_thread_pool_queue_capacity: 10000,
+            heartbeat_thread_pool_queue_capacity: 50000,
+            consumer_manager_thread_pool_queue_capacity: 1000000,
+            max_in_flight_requests_per_connection: 4096,
+            acl_enable: false,
+            plain_acl_file_path: default_plain_acl_file_path(),
         }
     }
 }
@@ -339,7 +396,31 @@ impl BrokerConfig {
             "enableControllerMode".to_string(),
             self.enable_controller_mode.to_string(),
         );
+        properties.insert(
+            "controllerAddr".to_string(),
+            self.controller_addr.clone().unwrap_or_default(),
+        );
+        properties.insert(
+            "syncBrokerMetadataPeriod".to_string(),
+            self.sync_broker_metadata_period.to_string(),
+        );
+        properties.insert(
+            "brokerHeartbeatInterval".to_string(),
+            self.broker_heartbeat_interval.to_string(),
+        );
+        properties.insert(
+            "controllerHeartBeatTimeoutMills".to_string(),
+            self.controller_heart_beat_timeout_mills.to_string(),
+        );
+        properties.insert(
+            "brokerElectionPriority".to_string(),
+            self.broker_election_priority.to_string(),
+        );
         properties.insert("regionId".to_string(), self.region_id.clone());
+        properties.insert(
+            "zoneName".to_string(),
+            self.zone_name.clone().unwrap_or_default(),
+        );
         properties.insert("traceOn".to_string(), self.trace_on.to_string());
         properties.insert(
             "brokerPermission".to_string(),
@@ -526,10 +607,21 @@ impl BrokerConfig {
             "forwardTimeout".to_string(),
             self.forward_timeout.to_string(),
         );
+        properties.insert("aclEnable".to_string(), self.acl_enable.to_string());
+        properties.insert(
+            "plainAclFilePath".to_string(),
+            self.plain_acl_file_path.clone(),
+        );
         properties
     }
 }
 
+fn default_plain_acl_file_path() -> String {
+    let rocketmq_home = env::var(mix_all::ROCKETMQ_HOME_PROPERTY)
+        .unwrap_or_else(|_| env::var(mix_all::ROCKETMQ_HOME_ENV).unwrap_or_default());
+    format!("{}{}", rocketmq_home, mix_all::PLAIN_ACL_FILE)
+}
+
 pub fn default_broker_name() -> String {
     LOCAL_HOST_NAME
         .clone()
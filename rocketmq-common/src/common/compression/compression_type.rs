@@ -2,19 +2,16 @@ use std::io::Read;
 use std::io::Write;
 use std::io::{self};
 
-use bytes::buf::Reader;
-use bytes::buf::Writer;
 use bytes::Buf;
-use bytes::BufMut;
 use bytes::Bytes;
-use flate2::read::ZlibDecoder;
-use flate2::write::ZlibEncoder;
-use flate2::Compression;
-use lz4_flex::compress_prepend_size;
-use lz4_flex::decompress_size_prepended;
 
+use crate::common::compression::compressor_factory::CompressorFactory;
 use crate::common::sys_flag::message_sys_flag::MessageSysFlag;
 
+/// Level used when the caller does not pick one. Every compressor clamps the level into the
+/// range it supports, so the same value is valid for all of them.
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 5;
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum CompressionType {
     LZ4,
@@ -24,11 +21,25 @@ pub enum CompressionType {
 
 impl CompressionType {
     pub fn of(name: &str) -> Self {
+        Self::from_name(name).unwrap_or_else(|| panic!("Unsupported compress type name: {}", name))
+    }
+
+    /// Like [`CompressionType::of`], but returns `None` for an unknown name instead of
+    /// panicking, for names received from a peer.
+    pub fn from_name(name: &str) -> Option<Self> {
         match name.trim().to_uppercase().as_str() {
-            "LZ4" => Self::LZ4,
-            "ZSTD" => Self::Zstd,
-            "ZLIB" => Self::Zlib,
-            _ => panic!("Unsupported compress type name: {}", name),
+            "LZ4" => Some(Self::LZ4),
+            "ZSTD" => Some(Self::Zstd),
+            "ZLIB" => Some(Self::Zlib),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LZ4 => "LZ4",
+            Self::Zstd => "ZSTD",
+            Self::Zlib => "ZLIB",
         }
     }
 
@@ -50,41 +61,19 @@ impl CompressionType {
     }
 
     pub fn compression(&self, data: &Bytes) -> Bytes {
-        match self {
-            CompressionType::LZ4 => {
-                let compressed = compress_prepend_size(data.chunk());
-                Bytes::from(compressed)
-            }
-            CompressionType::Zstd => {
-                let compressed = zstd::encode_all(data.clone().reader(), 5).unwrap();
-                Bytes::from(compressed)
-            }
-            CompressionType::Zlib => {
-                let mut zlib_encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-                let _ = zlib_encoder.write_all(data.chunk());
-                let result = zlib_encoder.finish().unwrap();
-                Bytes::from(result)
-            }
-        }
+        let compressed = CompressorFactory::get_compressor(*self)
+            .compress(data.chunk(), DEFAULT_COMPRESSION_LEVEL)
+            .unwrap();
+        Bytes::from(compressed)
     }
 
+    /// Decompresses a message body read from the store, which is bounded by the store's own
+    /// message size limit.
     pub fn decompression(&self, data: &Bytes) -> Bytes {
-        match self {
-            CompressionType::LZ4 => {
-                let compressed = decompress_size_prepended(data.chunk()).unwrap();
-                Bytes::from(compressed)
-            }
-            CompressionType::Zstd => {
-                let compressed = zstd::decode_all(data.clone().reader()).unwrap();
-                Bytes::from(compressed)
-            }
-            CompressionType::Zlib => {
-                let mut zlib_encoder = ZlibDecoder::new(data.clone().reader());
-                let mut decompressed_data = Vec::new();
-                zlib_encoder.read_to_end(&mut decompressed_data).unwrap();
-                Bytes::from(decompressed_data)
-            }
-        }
+        let decompressed = CompressorFactory::get_compressor(*self)
+            .decompress(data.chunk(), usize::MAX)
+            .unwrap();
+        Bytes::from(decompressed)
     }
 }
 
@@ -131,3 +120,40 @@ impl Read for BytesReader {
         Ok(bytes_to_copy)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_round_trips_through_from_name() {
+        for compression_type in [
+            CompressionType::LZ4,
+            CompressionType::Zstd,
+            CompressionType::Zlib,
+        ] {
+            assert_eq!(
+                CompressionType::from_name(compression_type.name()),
+                Some(compression_type)
+            );
+        }
+        assert_eq!(
+            CompressionType::from_name(" zstd "),
+            Some(CompressionType::Zstd)
+        );
+        assert_eq!(CompressionType::from_name("snappy"), None);
+    }
+
+    #[test]
+    fn compression_round_trips_for_every_type() {
+        let data = Bytes::from("rocketmq".repeat(256));
+        for compression_type in [
+            CompressionType::LZ4,
+            CompressionType::Zstd,
+            CompressionType::Zlib,
+        ] {
+            let compressed = compression_type.compression(&data);
+            assert_eq!(compression_type.decompression(&compressed), data);
+        }
+    }
+}
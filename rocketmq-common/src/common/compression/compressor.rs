@@ -14,6 +14,10 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::io::Error;
+use std::io::ErrorKind;
+use std::io::Read;
+
 pub trait Compressor {
     /// Compress message by different compressor.
     ///
@@ -25,16 +29,42 @@ pub trait Compressor {
     /// # Returns
     ///
     /// Compressed byte data or an `std::io::Error`.
-    fn compress(&self, src: &[u8], level: i32) -> Result<Vec<u8>, std::io::Error>;
+    fn compress(&self, src: &[u8], level: i32) -> Result<Vec<u8>, Error>;
 
     /// Decompress message by different compressor.
     ///
     /// # Arguments
     ///
     /// * `src` - Bytes ready to decompress.
+    /// * `max_len` - Upper bound of the decompressed length. Decompression stops with an error as
+    ///   soon as the output would exceed it, so a small corrupt or hostile input can not make the
+    ///   caller allocate more than `max_len` bytes.
     ///
     /// # Returns
     ///
     /// Decompressed byte data or an `std::io::Error`.
-    fn decompress(&self, src: &[u8]) -> Result<Vec<u8>, std::io::Error>;
+    fn decompress(&self, src: &[u8], max_len: usize) -> Result<Vec<u8>, Error>;
+}
+
+/// Reads `reader` to the end, failing once more than `max_len` bytes come out of it.
+pub(crate) fn read_to_end_bounded(
+    reader: impl Read,
+    max_len: usize,
+    capacity_hint: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut decompressed = Vec::with_capacity(capacity_hint.min(max_len));
+    reader
+        .take((max_len as u64).saturating_add(1))
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() > max_len {
+        return Err(exceeds_max_len(max_len));
+    }
+    Ok(decompressed)
+}
+
+pub(crate) fn exceeds_max_len(max_len: usize) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("decompressed length exceeds the max {}", max_len),
+    )
 }
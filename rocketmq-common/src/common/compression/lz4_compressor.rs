@@ -14,18 +14,77 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+
 use std::io::Error;
+use std::io::ErrorKind;
+
+use lz4_flex::compress_prepend_size;
+use lz4_flex::decompress;
 
+use crate::common::compression::compressor::exceeds_max_len;
 use crate::common::compression::compressor::Compressor;
 
 pub struct Lz4Compressor;
 
 impl Compressor for Lz4Compressor {
-    fn compress(&self, src: &[u8], level: i32) -> Result<Vec<u8>, Error> {
-        todo!()
+    /// LZ4 only has a single fast mode, so `level` is accepted for symmetry with the other
+    /// compressors and ignored.
+    fn compress(&self, src: &[u8], _level: i32) -> Result<Vec<u8>, Error> {
+        Ok(compress_prepend_size(src))
+    }
+
+    /// The block format prepends the decompressed length as a little endian `u32`, it is
+    /// checked against `max_len` before the output is allocated.
+    fn decompress(&self, src: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+        let Some((size, block)) = src.split_first_chunk::<4>() else {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "lz4 block is missing its size prefix",
+            ));
+        };
+        let size = u32::from_le_bytes(*size) as usize;
+        if size > max_len {
+            return Err(exceeds_max_len(max_len));
+        }
+        decompress(block, size).map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_and_decompress_round_trip() {
+        let src = "rocketmq".repeat(1024).into_bytes();
+        let compressed = Lz4Compressor.compress(&src, 5).unwrap();
+        assert!(compressed.len() < src.len());
+        assert_eq!(
+            Lz4Compressor.decompress(&compressed, src.len()).unwrap(),
+            src
+        );
+    }
+
+    #[test]
+    fn decompress_rejects_garbage() {
+        assert!(Lz4Compressor
+            .decompress(&[16, 0, 0, 0, 0xf0], 1024)
+            .is_err());
+    }
+
+    #[test]
+    fn decompress_rejects_prepended_size_over_max_len() {
+        // claims 4 GiB of output, rejected before anything is allocated for it
+        let bomb = [0xff, 0xff, 0xff, 0xff, 0x10, 0x00];
+        let err = Lz4Compressor.decompress(&bomb, 1024 * 1024).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
 
-    fn decompress(&self, src: &[u8]) -> Result<Vec<u8>, Error> {
-        todo!()
+        let src = vec![0u8; 4096];
+        let compressed = Lz4Compressor.compress(&src, 0).unwrap();
+        assert!(Lz4Compressor
+            .decompress(&compressed, src.len() - 1)
+            .is_err());
+        assert!(Lz4Compressor.decompress(&compressed, 2).is_err());
+        assert!(Lz4Compressor.decompress(&[1, 0], 1024).is_err());
     }
 }
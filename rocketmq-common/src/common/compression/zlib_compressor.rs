@@ -14,18 +14,87 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+
 use std::io::Error;
+use std::io::Write;
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 
+use crate::common::compression::compressor::read_to_end_bounded;
 use crate::common::compression::compressor::Compressor;
 
 pub struct ZlibCompressor;
 
+impl ZlibCompressor {
+    /// Clamps `level` into zlib's `0..=9` range, so callers can pass the same level to every
+    /// compressor.
+    fn compression_level(level: i32) -> Compression {
+        Compression::new(level.clamp(0, 9) as u32)
+    }
+}
+
 impl Compressor for ZlibCompressor {
     fn compress(&self, src: &[u8], level: i32) -> Result<Vec<u8>, Error> {
-        todo!()
+        let mut encoder = ZlibEncoder::new(
+            Vec::with_capacity(src.len()),
+            Self::compression_level(level),
+        );
+        encoder.write_all(src)?;
+        encoder.finish()
+    }
+
+    fn decompress(&self, src: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+        read_to_end_bounded(ZlibDecoder::new(src), max_len, src.len() * 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_and_decompress_round_trip() {
+        let src = "rocketmq".repeat(1024).into_bytes();
+        let compressed = ZlibCompressor.compress(&src, 5).unwrap();
+        assert!(compressed.len() < src.len());
+        assert_eq!(
+            ZlibCompressor.decompress(&compressed, src.len()).unwrap(),
+            src
+        );
+    }
+
+    #[test]
+    fn out_of_range_level_is_clamped() {
+        let src = "rocketmq".repeat(64).into_bytes();
+        for level in [i32::MIN, -1, 0, 10, i32::MAX] {
+            let compressed = ZlibCompressor.compress(&src, level).unwrap();
+            assert_eq!(
+                ZlibCompressor.decompress(&compressed, src.len()).unwrap(),
+                src
+            );
+        }
+    }
+
+    #[test]
+    fn decompress_rejects_garbage() {
+        assert!(ZlibCompressor.decompress(b"not zlib", 1024).is_err());
     }
 
-    fn decompress(&self, src: &[u8]) -> Result<Vec<u8>, Error> {
-        todo!()
+    #[test]
+    fn decompress_stops_at_max_len() {
+        // 64 MiB of zeros deflate to about 64 KiB, written in chunks so the test itself never
+        // holds the inflated data
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        let chunk = vec![0u8; 1024 * 1024];
+        for _ in 0..64 {
+            encoder.write_all(&chunk).unwrap();
+        }
+        let bomb = encoder.finish().unwrap();
+        assert!(bomb.len() < 1024 * 1024);
+
+        let err = ZlibCompressor.decompress(&bomb, 1024 * 1024).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
     }
 }
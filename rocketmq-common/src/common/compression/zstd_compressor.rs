@@ -14,18 +14,78 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+
 use std::io::Error;
 
+use zstd::zstd_safe::CompressionLevel;
+
+use crate::common::compression::compressor::read_to_end_bounded;
 use crate::common::compression::compressor::Compressor;
 
 pub struct ZstdCompressor;
 
+impl ZstdCompressor {
+    /// Clamps `level` into the range supported by the linked zstd library, so callers can pass
+    /// the same level to every compressor.
+    fn compression_level(level: i32) -> CompressionLevel {
+        let range = zstd::compression_level_range();
+        level.clamp(*range.start(), *range.end())
+    }
+}
+
 impl Compressor for ZstdCompressor {
     fn compress(&self, src: &[u8], level: i32) -> Result<Vec<u8>, Error> {
-        todo!()
+        zstd::encode_all(src, Self::compression_level(level))
+    }
+
+    fn decompress(&self, src: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+        read_to_end_bounded(
+            zstd::stream::read::Decoder::new(src)?,
+            max_len,
+            src.len() * 2,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_and_decompress_round_trip() {
+        let src = "rocketmq".repeat(1024).into_bytes();
+        let compressed = ZstdCompressor.compress(&src, 5).unwrap();
+        assert!(compressed.len() < src.len());
+        assert_eq!(
+            ZstdCompressor.decompress(&compressed, src.len()).unwrap(),
+            src
+        );
+    }
+
+    #[test]
+    fn out_of_range_level_is_clamped() {
+        let src = "rocketmq".repeat(64).into_bytes();
+        for level in [i32::MIN, -1000, 0, 1000, i32::MAX] {
+            let compressed = ZstdCompressor.compress(&src, level).unwrap();
+            assert_eq!(
+                ZstdCompressor.decompress(&compressed, src.len()).unwrap(),
+                src
+            );
+        }
+    }
+
+    #[test]
+    fn decompress_rejects_garbage() {
+        assert!(ZstdCompressor.decompress(b"not zstd", 1024).is_err());
     }
 
-    fn decompress(&self, src: &[u8]) -> Result<Vec<u8>, Error> {
-        todo!()
+    #[test]
+    fn decompress_stops_at_max_len() {
+        let src = vec![0u8; 1024 * 1024];
+        let compressed = ZstdCompressor.compress(&src, 5).unwrap();
+        assert!(ZstdCompressor.decompress(&compressed, src.len()).is_ok());
+        assert!(ZstdCompressor
+            .decompress(&compressed, src.len() - 1)
+            .is_err());
     }
 }
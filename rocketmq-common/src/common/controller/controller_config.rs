@@ -0,0 +1,166 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::env;
+
+use serde::Deserialize;
+
+use crate::common::mix_all::ROCKETMQ_HOME_ENV;
+use crate::common::mix_all::ROCKETMQ_HOME_PROPERTY;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ControllerConfig {
+    #[serde(alias = "rocketmqHome")]
+    pub rocketmq_home: String,
+
+    #[serde(alias = "configStorePath")]
+    pub config_store_path: String,
+
+    #[serde(alias = "controllerStorePath")]
+    pub controller_store_path: String,
+
+    #[serde(alias = "controllerThreadPoolNums")]
+    pub controller_thread_pool_nums: i32,
+
+    #[serde(alias = "controllerRequestThreadPoolQueueCapacity")]
+    pub controller_request_thread_pool_queue_capacity: i32,
+
+    /// Interval, in milliseconds, between two scans for brokers whose heartbeat timed out.
+    #[serde(alias = "scanNotActiveBrokerInterval")]
+    pub scan_not_active_broker_interval: u64,
+
+    /// Whether a replica outside the sync state set may be elected when no in-sync replica is
+    /// alive. Doing so may lose messages that only the old master had.
+    #[serde(alias = "enableElectUncleanMaster")]
+    pub enable_elect_unclean_master: bool,
+
+    #[serde(alias = "notifyBrokerRoleChanged")]
+    pub notify_broker_role_changed: bool,
+
+    #[serde(alias = "electMasterMaxRetryCount")]
+    pub elect_master_max_retry_count: i32,
+
+    /// Name of the Raft group the controllers form.
+    #[serde(alias = "controllerRaftGroup", alias = "controllerDLegerGroup")]
+    pub controller_raft_group: String,
+
+    /// Members of the Raft group as `id-address` pairs separated by `;`, for example
+    /// `n0-127.0.0.1:9878;n1-127.0.0.1:9868;n2-127.0.0.1:9858`. Unset runs a single controller
+    /// keeping its metadata in memory only.
+    #[serde(alias = "controllerRaftPeers", alias = "controllerDLegerPeers")]
+    pub controller_raft_peers: Option<String>,
+
+    /// Id of this controller in `controller_raft_peers`.
+    #[serde(alias = "controllerRaftSelfId", alias = "controllerDLegerSelfId")]
+    pub controller_raft_self_id: String,
+
+    /// A follower that hears nothing from the leader for a random time between one and two
+    /// election timeouts starts an election.
+    #[serde(alias = "raftElectionTimeoutMills")]
+    pub raft_election_timeout_mills: u64,
+
+    #[serde(alias = "raftHeartbeatIntervalMills")]
+    pub raft_heartbeat_interval_mills: u64,
+
+    /// Number of applied log entries after which the log is compacted into a snapshot.
+    #[serde(alias = "raftSnapshotThreshold")]
+    pub raft_snapshot_threshold: u64,
+}
+
+impl Default for ControllerConfig {
+    fn default() -> Self {
+        let rocketmq_home = env::var(ROCKETMQ_HOME_PROPERTY)
+            .unwrap_or_else(|_| env::var(ROCKETMQ_HOME_ENV).unwrap_or_default());
+        let home_dir = dirs::home_dir()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let config_store_path = format!(
+            "{}{}{}{}{}",
+            home_dir,
+            std::path::MAIN_SEPARATOR,
+            "rocketmq-controller",
+            std::path::MAIN_SEPARATOR,
+            "controller.properties"
+        );
+        let controller_store_path = format!(
+            "{}{}{}{}{}",
+            home_dir,
+            std::path::MAIN_SEPARATOR,
+            "rocketmq-controller",
+            std::path::MAIN_SEPARATOR,
+            "store"
+        );
+
+        ControllerConfig {
+            rocketmq_home,
+            config_store_path,
+            controller_store_path,
+            controller_thread_pool_nums: 16,
+            controller_request_thread_pool_queue_capacity: 50000,
+            scan_not_active_broker_interval: 5 * 1000,
+            enable_elect_unclean_master: false,
+            notify_broker_role_changed: true,
+            elect_master_max_retry_count: 3,
+            controller_raft_group: "DefaultControllerGroup".to_string(),
+            controller_raft_peers: None,
+            controller_raft_self_id: "n0".to_string(),
+            raft_election_timeout_mills: 1000,
+            raft_heartbeat_interval_mills: 100,
+            raft_snapshot_threshold: 1000,
+        }
+    }
+}
+
+impl ControllerConfig {
+    pub fn new() -> ControllerConfig {
+        Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controller_config_defaults() {
+        let config = ControllerConfig::new();
+        assert_eq!(config.scan_not_active_broker_interval, 5 * 1000);
+        assert!(!config.enable_elect_unclean_master);
+        assert!(config.notify_broker_role_changed);
+        assert_eq!(config.elect_master_max_retry_count, 3);
+        assert!(config.controller_store_path.ends_with("store"));
+        assert!(config.controller_raft_peers.is_none());
+        assert_eq!(config.raft_election_timeout_mills, 1000);
+    }
+
+    #[test]
+    fn controller_config_accepts_java_style_keys() {
+        let config: ControllerConfig = serde_json::from_str(
+            r#"{"enableElectUncleanMaster":true,"scanNotActiveBrokerInterval":1000,
+                "controllerDLegerPeers":"n0-127.0.0.1:9878","controllerDLegerSelfId":"n0"}"#,
+        )
+        .unwrap();
+        assert!(config.enable_elect_unclean_master);
+        assert_eq!(config.scan_not_active_broker_interval, 1000);
+        assert_eq!(config.elect_master_max_retry_count, 3);
+        assert_eq!(
+            config.controller_raft_peers.as_deref(),
+            Some("n0-127.0.0.1:9878")
+        );
+        assert_eq!(config.controller_raft_self_id, "n0");
+    }
+}
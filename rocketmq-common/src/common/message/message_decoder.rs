@@ -284,6 +284,35 @@ pub fn decode(
     Some(msg_ext)
 }
 
+/// Decodes every message stored back to back in `byte_buffer`, stopping at the first
+/// truncated or undecodable entry.
+pub fn decodes(
+    byte_buffer: &mut Bytes,
+    read_body: bool,
+    de_compress_body: bool,
+) -> Vec<MessageExt> {
+    let mut msg_exts = Vec::new();
+    while byte_buffer.remaining() >= 4 {
+        let size = byte_buffer.slice(0..4).get_i32();
+        if size <= 0 || size as usize > byte_buffer.remaining() {
+            break;
+        }
+        let mut msg_bytes = byte_buffer.split_to(size as usize);
+        match decode(
+            &mut msg_bytes,
+            read_body,
+            de_compress_body,
+            false,
+            false,
+            false,
+        ) {
+            Some(msg_ext) => msg_exts.push(msg_ext),
+            None => break,
+        }
+    }
+    msg_exts
+}
+
 pub fn count_inner_msg_num(bytes: Option<Bytes>) -> u32 {
     match bytes {
         None => 0,
@@ -377,6 +406,14 @@ mod tests {
         assert_eq!(count_inner_msg_num(Some(bytes.freeze())), 1);
     }
 
+    #[test]
+    fn decodes_stops_at_truncated_message() {
+        let mut bytes = BytesMut::new();
+        bytes.put_i32(100);
+        bytes.put_slice(&[0, 0, 0, 0]);
+        assert!(decodes(&mut bytes.freeze(), true, false).is_empty());
+    }
+
     #[test]
     fn count_inner_msg_num_counts_zero_for_no_messages() {
         let bytes = BytesMut::new();
@@ -15,6 +15,8 @@
  * limitations under the License.
  */
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::env;
 
 use once_cell::sync::Lazy;
@@ -57,6 +59,7 @@ pub const DEFAULT_TRACE_REGION_ID: &str = "DefaultRegion";
 pub const CONSUME_CONTEXT_TYPE: &str = "ConsumeContextType";
 pub const CID_SYS_RMQ_TRANS: &str = "CID_RMQ_SYS_TRANS";
 pub const ACL_CONF_TOOLS_FILE: &str = "/conf/tools.yml";
+pub const PLAIN_ACL_FILE: &str = "/conf/plain_acl.yml";
 pub const REPLY_MESSAGE_FLAG: &str = "reply";
 pub const LMQ_PREFIX: &str = "%LMQ%";
 pub const LMQ_QUEUE_ID: u64 = 0;
@@ -158,6 +161,38 @@ pub fn human_readable_byte_count(bytes: i64, si: bool) -> String {
     format!("{:.1} {}B", bytes / unit.powi(exp), pre)
 }
 
+/// Parses `key=value` lines in the Java properties format, skipping blank lines and `#`/`!`
+/// comments. `:` is accepted as separator as well.
+pub fn string_to_properties(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| {
+            let split = line.find(['=', ':'])?;
+            Some((
+                line[..split].trim().to_string(),
+                line[split + 1..].trim().to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// The inverse of [`string_to_properties`], keys are sorted so the output is stable.
+pub fn properties_to_string(properties: &HashMap<String, String>) -> String {
+    properties
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .fold(String::new(), |mut content, (key, value)| {
+            content.push_str(key);
+            content.push('=');
+            content.push_str(value);
+            content.push('\n');
+            content
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,4 +275,18 @@ mod tests {
     fn returns_false_for_none_metadata() {
         assert!(!is_lmq(None));
     }
+
+    #[test]
+    fn properties_round_trip() {
+        let properties = string_to_properties(
+            "# comment\n\norderMessageEnable = true\nscanNotActiveBrokerInterval:3000\nbad line\n",
+        );
+        assert_eq!(properties.len(), 2);
+        assert_eq!(properties["orderMessageEnable"], "true");
+        assert_eq!(properties["scanNotActiveBrokerInterval"], "3000");
+        assert_eq!(
+            properties_to_string(&properties),
+            "orderMessageEnable=true\nscanNotActiveBrokerInterval=3000\n"
+        );
+    }
 }
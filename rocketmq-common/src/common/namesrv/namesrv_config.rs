@@ -15,7 +15,9 @@
  * limitations under the License.
  */
 
+use std::collections::HashMap;
 use std::env;
+use std::str::FromStr;
 
 use serde::Deserialize;
 
@@ -89,6 +91,28 @@ pub struct NamesrvConfig {
 
     #[serde(alias = "configBlackList")]
     pub config_black_list: String,
+
+    /// Address of the HTTP management API, e.g. `0.0.0.0:9880`. Requires the `http` feature of
+    /// `rocketmq-namesrv`, disabled when unset.
+    #[serde(alias = "httpListenAddress", default)]
+    pub http_listen_address: Option<String>,
+
+    /// File the route tables are snapshotted to every `route_snapshot_interval` millis, so a
+    /// restarted name server serves routes before the brokers re-register. Disabled when unset.
+    #[serde(alias = "routeSnapshotPath", default)]
+    pub route_snapshot_path: Option<String>,
+
+    #[serde(alias = "routeSnapshotInterval")]
+    pub route_snapshot_interval: u64,
+
+    /// Millis the brokers restored from the snapshot are kept without re-registering.
+    #[serde(alias = "routeSnapshotTtl")]
+    pub route_snapshot_ttl: u64,
+
+    /// Millis a broker is kept without heartbeat when its registration carries no
+    /// `heartbeatTimeoutMillis`.
+    #[serde(alias = "brokerChannelExpiredTime")]
+    pub broker_channel_expired_time: u64,
 }
 
 impl Default for NamesrvConfig {
@@ -136,6 +160,11 @@ impl Default for NamesrvConfig {
             wait_seconds_for_service: 45,
             delete_topic_with_broker_registration: false,
             config_black_list: "configBlackList;configStorePath;kvConfigPath".to_string(),
+            http_listen_address: None,
+            route_snapshot_path: None,
+            route_snapshot_interval: 30 * 1000,
+            route_snapshot_ttl: 2 * 60 * 1000,
+            broker_channel_expired_time: 2 * 60 * 1000,
         }
     }
 }
@@ -144,6 +173,191 @@ impl NamesrvConfig {
     pub fn new() -> NamesrvConfig {
         Self::default()
     }
+
+    pub fn get_properties(&self) -> HashMap<String, String> {
+        let mut properties: HashMap<String, String> = HashMap::new();
+        properties.insert("rocketmqHome".to_string(), self.rocketmq_home.clone());
+        properties.insert("kvConfigPath".to_string(), self.kv_config_path.clone());
+        properties.insert(
+            "configStorePath".to_string(),
+            self.config_store_path.clone(),
+        );
+        properties.insert("productEnvName".to_string(), self.product_env_name.clone());
+        properties.insert("clusterTest".to_string(), self.cluster_test.to_string());
+        properties.insert(
+            "orderMessageEnable".to_string(),
+            self.order_message_enable.to_string(),
+        );
+        properties.insert(
+            "returnOrderTopicConfigToBroker".to_string(),
+            self.return_order_topic_config_to_broker.to_string(),
+        );
+        properties.insert(
+            "clientRequestThreadPoolNums".to_string(),
+            self.client_request_thread_pool_nums.to_string(),
+        );
+        properties.insert(
+            "defaultThreadPoolNums".to_string(),
+            self.default_thread_pool_nums.to_string(),
+        );
+        properties.insert(
+            "clientRequestThreadPoolQueueCapacity".to_string(),
+            self.client_request_thread_pool_queue_capacity.to_string(),
+        );
+        properties.insert(
+            "defaultThreadPoolQueueCapacity".to_string(),
+            self.default_thread_pool_queue_capacity.to_string(),
+        );
+        properties.insert(
+            "scanNotActiveBrokerInterval".to_string(),
+            self.scan_not_active_broker_interval.to_string(),
+        );
+        properties.insert(
+            "unRegisterBrokerQueueCapacity".to_string(),
+            self.unregister_broker_queue_capacity.to_string(),
+        );
+        properties.insert(
+            "supportActingMaster".to_string(),
+            self.support_acting_master.to_string(),
+        );
+        properties.insert(
+            "enableAllTopicList".to_string(),
+            self.enable_all_topic_list.to_string(),
+        );
+        properties.insert(
+            "enableTopicList".to_string(),
+            self.enable_topic_list.to_string(),
+        );
+        properties.insert(
+            "notifyMinBrokerIdChanged".to_string(),
+            self.notify_min_broker_id_changed.to_string(),
+        );
+        properties.insert(
+            "enableControllerInNamesrv".to_string(),
+            self.enable_controller_in_namesrv.to_string(),
+        );
+        properties.insert(
+            "needWaitForService".to_string(),
+            self.need_wait_for_service.to_string(),
+        );
+        properties.insert(
+            "waitSecondsForService".to_string(),
+            self.wait_seconds_for_service.to_string(),
+        );
+        properties.insert(
+            "deleteTopicWithBrokerRegistration".to_string(),
+            self.delete_topic_with_broker_registration.to_string(),
+        );
+        properties.insert(
+            "configBlackList".to_string(),
+            self.config_black_list.clone(),
+        );
+        properties.insert(
+            "httpListenAddress".to_string(),
+            self.http_listen_address.clone().unwrap_or_default(),
+        );
+        properties.insert(
+            "routeSnapshotPath".to_string(),
+            self.route_snapshot_path.clone().unwrap_or_default(),
+        );
+        properties.insert(
+            "routeSnapshotInterval".to_string(),
+            self.route_snapshot_interval.to_string(),
+        );
+        properties.insert(
+            "routeSnapshotTtl".to_string(),
+            self.route_snapshot_ttl.to_string(),
+        );
+        properties.insert(
+            "brokerChannelExpiredTime".to_string(),
+            self.broker_channel_expired_time.to_string(),
+        );
+        properties
+    }
+
+    /// Whether `key` is listed in `config_black_list` and so must not be changed remotely.
+    pub fn is_in_config_black_list(&self, key: &str) -> bool {
+        self.config_black_list
+            .split(';')
+            .map(str::trim)
+            .any(|black| black == key)
+    }
+
+    /// Whether `key` can be changed on a running name server. Thread pools, paths and the
+    /// embedded controller are only read at startup.
+    pub fn is_updatable_property(key: &str) -> bool {
+        matches!(
+            key,
+            "productEnvName"
+                | "clusterTest"
+                | "orderMessageEnable"
+                | "returnOrderTopicConfigToBroker"
+                | "scanNotActiveBrokerInterval"
+                | "supportActingMaster"
+                | "enableAllTopicList"
+                | "enableTopicList"
+                | "notifyMinBrokerIdChanged"
+                | "needWaitForService"
+                | "waitSecondsForService"
+                | "deleteTopicWithBrokerRegistration"
+                | "brokerChannelExpiredTime"
+        )
+    }
+
+    /// Applies the updatable properties, keyed by their camelCase names. Nothing is changed
+    /// unless every property is updatable and its value parses.
+    pub fn update(&mut self, properties: &HashMap<String, String>) -> Result<(), String> {
+        let mut updated = self.clone();
+        for (key, value) in properties {
+            match key.as_str() {
+                "productEnvName" => updated.product_env_name.clone_from(value),
+                "clusterTest" => updated.cluster_test = parse_property(key, value)?,
+                "orderMessageEnable" => updated.order_message_enable = parse_property(key, value)?,
+                "returnOrderTopicConfigToBroker" => {
+                    updated.return_order_topic_config_to_broker = parse_property(key, value)?
+                }
+                "scanNotActiveBrokerInterval" => {
+                    let interval: u64 = parse_property(key, value)?;
+                    if interval == 0 {
+                        return Err(format!("{} must be greater than 0", key));
+                    }
+                    updated.scan_not_active_broker_interval = interval
+                }
+                "supportActingMaster" => {
+                    updated.support_acting_master = parse_property(key, value)?
+                }
+                "enableAllTopicList" => updated.enable_all_topic_list = parse_property(key, value)?,
+                "enableTopicList" => updated.enable_topic_list = parse_property(key, value)?,
+                "notifyMinBrokerIdChanged" => {
+                    updated.notify_min_broker_id_changed = parse_property(key, value)?
+                }
+                "needWaitForService" => updated.need_wait_for_service = parse_property(key, value)?,
+                "waitSecondsForService" => {
+                    updated.wait_seconds_for_service = parse_property(key, value)?
+                }
+                "deleteTopicWithBrokerRegistration" => {
+                    updated.delete_topic_with_broker_registration = parse_property(key, value)?
+                }
+                "brokerChannelExpiredTime" => {
+                    let timeout: u64 = parse_property(key, value)?;
+                    if timeout == 0 {
+                        return Err(format!("{} must be greater than 0", key));
+                    }
+                    updated.broker_channel_expired_time = timeout
+                }
+                _ => return Err(format!("{} can not be updated at runtime", key)),
+            }
+        }
+        *self = updated;
+        Ok(())
+    }
+}
+
+fn parse_property<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value of {}: {}", key, value))
 }
 
 #[cfg(test)]
@@ -203,5 +417,48 @@ mod tests {
             config.config_black_list,
             "configBlackList;configStorePath;kvConfigPath".to_string()
         );
+        assert_eq!(config.http_listen_address, None);
+        assert_eq!(config.route_snapshot_path, None);
+        assert_eq!(config.route_snapshot_interval, 30 * 1000);
+        assert_eq!(config.route_snapshot_ttl, 2 * 60 * 1000);
+        assert_eq!(config.broker_channel_expired_time, 2 * 60 * 1000);
+    }
+
+    #[test]
+    fn black_list_contains_store_paths() {
+        let config = NamesrvConfig::new();
+        assert!(config.is_in_config_black_list("configBlackList"));
+        assert!(config.is_in_config_black_list("kvConfigPath"));
+        assert!(!config.is_in_config_black_list("orderMessageEnable"));
+    }
+
+    #[test]
+    fn update_applies_all_or_nothing() {
+        let mut config = NamesrvConfig::new();
+        let mut properties = HashMap::new();
+        properties.insert("orderMessageEnable".to_string(), "true".to_string());
+        properties.insert(
+            "scanNotActiveBrokerInterval".to_string(),
+            "1000".to_string(),
+        );
+        config.update(&properties).unwrap();
+        assert!(config.order_message_enable);
+        assert_eq!(config.scan_not_active_broker_interval, 1000);
+        assert_eq!(
+            config.get_properties()["scanNotActiveBrokerInterval"],
+            "1000"
+        );
+
+        properties.insert("enableAllTopicList".to_string(), "false".to_string());
+        properties.insert("waitSecondsForService".to_string(), "soon".to_string());
+        assert!(config.update(&properties).is_err());
+        assert!(config.enable_all_topic_list);
+
+        let mut properties = HashMap::new();
+        properties.insert("defaultThreadPoolNums".to_string(), "4".to_string());
+        assert!(config.update(&properties).is_err());
+        assert!(!NamesrvConfig::is_updatable_property(
+            "defaultThreadPoolNums"
+        ));
     }
 }
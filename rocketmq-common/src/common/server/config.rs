@@ -17,11 +17,62 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+/// How a remoting server treats TLS on accepted connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsMode {
+    /// Plaintext connections only.
+    #[default]
+    Disabled,
+    /// Accept both TLS and plaintext connections.
+    Permissive,
+    /// TLS connections only.
+    Enforcing,
+}
+
+impl TlsMode {
+    pub fn parse(mode: &str) -> Option<TlsMode> {
+        match mode.to_ascii_lowercase().as_str() {
+            "disabled" => Some(TlsMode::Disabled),
+            "permissive" => Some(TlsMode::Permissive),
+            "enforcing" => Some(TlsMode::Enforcing),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TlsMode::Disabled => "disabled",
+            TlsMode::Permissive => "permissive",
+            TlsMode::Enforcing => "enforcing",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct ServerConfig {
     pub listen_port: u32,
     pub bind_address: String,
+    /// Also accept connections on the Unix domain socket at this path, for clients on the same
+    /// host. They reach it with a `unix://` address, e.g. `unix:///var/run/rocketmq/broker.sock`.
+    pub unix_socket_path: Option<String>,
+    pub tls_mode: TlsMode,
+    /// PEM certificate chain presented to clients.
+    pub tls_server_cert_path: Option<String>,
+    /// PEM private key of `tls_server_cert_path`.
+    pub tls_server_key_path: Option<String>,
+    /// PEM CA certificates used to verify client certificates.
+    pub tls_server_trust_cert_path: Option<String>,
+    /// Require clients to present a certificate signed by `tls_server_trust_cert_path`.
+    pub tls_server_need_client_auth: bool,
+    /// Drain connections on shutdown: answer new requests with GO_AWAY and wait for the ones
+    /// in flight, instead of dropping the connections right away.
+    pub enable_shutdown_gracefully: bool,
+    /// Max time to wait for the requests in flight when shutting down gracefully.
+    pub shutdown_wait_time_seconds: u64,
+    /// Log the requests taking longer than this to process, `0` disables the log.
+    pub slow_request_threshold_millis: u64,
 }
 
 impl Default for ServerConfig {
@@ -29,6 +80,15 @@ impl Default for ServerConfig {
         ServerConfig {
             listen_port: 10911,
             bind_address: "0.0.0.0".to_string(),
+            unix_socket_path: None,
+            tls_mode: TlsMode::Disabled,
+            tls_server_cert_path: None,
+            tls_server_key_path: None,
+            tls_server_trust_cert_path: None,
+            tls_server_need_client_auth: false,
+            enable_shutdown_gracefully: true,
+            shutdown_wait_time_seconds: 30,
+            slow_request_threshold_millis: 1000,
         }
     }
 }
@@ -41,4 +101,26 @@ impl ServerConfig {
     pub fn listen_port(&self) -> u32 {
         self.listen_port
     }
+
+    pub fn tls_mode(&self) -> TlsMode {
+        self.tls_mode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tls_mode_parse_and_deserialize() {
+        assert_eq!(TlsMode::parse("Enforcing"), Some(TlsMode::Enforcing));
+        assert_eq!(TlsMode::parse("plain"), None);
+
+        let config: ServerConfig =
+            serde_json::from_str(r#"{"listenPort":9876,"tlsMode":"permissive"}"#).unwrap();
+        assert_eq!(config.listen_port, 9876);
+        assert_eq!(config.bind_address, "0.0.0.0");
+        assert_eq!(config.tls_mode(), TlsMode::Permissive);
+        assert!(!config.tls_server_need_client_auth);
+    }
 }
@@ -31,8 +31,8 @@ use crate::common::stats::call_snapshot::CallSnapshot;
 use crate::common::stats::stats_snapshot::StatsSnapshot;
 
 pub struct StatsItem {
-    value: AtomicU64,
-    times: AtomicU64,
+    value: Arc<AtomicU64>,
+    times: Arc<AtomicU64>,
     cs_list_minute: Arc<Mutex<LinkedList<CallSnapshot>>>,
     cs_list_hour: Arc<Mutex<LinkedList<CallSnapshot>>>,
     cs_list_day: Arc<Mutex<LinkedList<CallSnapshot>>>,
@@ -43,8 +43,8 @@ pub struct StatsItem {
 impl StatsItem {
     pub fn new(stats_name: &str, stats_key: &str) -> Self {
         StatsItem {
-            value: AtomicU64::new(0),
-            times: AtomicU64::new(0),
+            value: Arc::new(AtomicU64::new(0)),
+            times: Arc::new(AtomicU64::new(0)),
             cs_list_minute: Arc::new(Mutex::new(LinkedList::new())),
             cs_list_hour: Arc::new(Mutex::new(LinkedList::new())),
             cs_list_day: Arc::new(Mutex::new(LinkedList::new())),
@@ -53,6 +53,27 @@ impl StatsItem {
         }
     }
 
+    pub fn get_stats_name(&self) -> &str {
+        &self.stats_name
+    }
+
+    pub fn get_stats_key(&self) -> &str {
+        &self.stats_key
+    }
+
+    pub fn get_value(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    pub fn get_times(&self) -> u64 {
+        self.times.load(Ordering::Relaxed)
+    }
+
+    pub fn add(&self, inc_value: u64, inc_times: u64) {
+        self.value.fetch_add(inc_value, Ordering::Relaxed);
+        self.times.fetch_add(inc_times, Ordering::Relaxed);
+    }
+
     pub fn compute_stats_data(cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>) -> StatsSnapshot {
         let mut stats_snapshot = StatsSnapshot::new();
         let cs_list = cs_list.lock();
@@ -97,19 +118,34 @@ impl StatsItem {
         let stats_name = self.stats_name.clone();
         let stats_key = self.stats_key.clone();
 
+        let (times, value) = (Arc::clone(&self.times), Arc::clone(&self.value));
         thread::spawn(move || loop {
             thread::sleep(Duration::from_secs(10));
-            Self::sampling_in_seconds(cs_list_minute.clone());
+            Self::sampling_in_seconds(
+                cs_list_minute.clone(),
+                times.load(Ordering::Relaxed),
+                value.load(Ordering::Relaxed),
+            );
         });
 
+        let (times, value) = (Arc::clone(&self.times), Arc::clone(&self.value));
         thread::spawn(move || loop {
             thread::sleep(Duration::from_secs(600));
-            Self::sampling_in_minutes(cs_list_hour.clone());
+            Self::sampling_in_minutes(
+                cs_list_hour.clone(),
+                times.load(Ordering::Relaxed),
+                value.load(Ordering::Relaxed),
+            );
         });
 
+        let (times, value) = (Arc::clone(&self.times), Arc::clone(&self.value));
         thread::spawn(move || loop {
             thread::sleep(Duration::from_secs(3600));
-            Self::sampling_in_hour(cs_list_day.clone());
+            Self::sampling_in_hour(
+                cs_list_day.clone(),
+                times.load(Ordering::Relaxed),
+                value.load(Ordering::Relaxed),
+            );
         });
 
         let stats_name_clone = stats_name.clone();
@@ -156,7 +192,11 @@ impl StatsItem {
         });
     }
 
-    pub fn sampling_in_seconds(cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>) {
+    pub fn sampling_in_seconds(
+        cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>,
+        times: u64,
+        value: u64,
+    ) {
         let mut cs_list = cs_list.lock();
         if cs_list.is_empty() {
             cs_list.push_back(CallSnapshot::new(
@@ -174,15 +214,19 @@ impl StatsItem {
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap()
                 .as_millis() as u64,
-            0,
-            0,
+            times,
+            value,
         ));
         if cs_list.len() > 7 {
             cs_list.pop_front();
         }
     }
 
-    pub fn sampling_in_minutes(cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>) {
+    pub fn sampling_in_minutes(
+        cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>,
+        times: u64,
+        value: u64,
+    ) {
         let mut cs_list = cs_list.lock();
         if cs_list.is_empty() {
             cs_list.push_back(CallSnapshot::new(
@@ -200,15 +244,15 @@ impl StatsItem {
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap()
                 .as_millis() as u64,
-            0,
-            0,
+            times,
+            value,
         ));
         if cs_list.len() > 7 {
             cs_list.pop_front();
         }
     }
 
-    pub fn sampling_in_hour(cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>) {
+    pub fn sampling_in_hour(cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>, times: u64, value: u64) {
         let mut cs_list = cs_list.lock();
         if cs_list.is_empty() {
             cs_list.push_back(CallSnapshot::new(
@@ -226,14 +270,49 @@ impl StatsItem {
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap()
                 .as_millis() as u64,
-            0,
-            0,
+            times,
+            value,
         ));
         if cs_list.len() > 25 {
             cs_list.pop_front();
         }
     }
 
+    /// Takes a sample of the counters for [`StatsItem::get_stats_data_in_minute`], every 10s.
+    pub fn sample_in_seconds(&self) {
+        Self::sampling_in_seconds(
+            Arc::clone(&self.cs_list_minute),
+            self.get_times(),
+            self.get_value(),
+        );
+    }
+
+    /// Takes a sample of the counters for [`StatsItem::get_stats_data_in_hour`], every 10min.
+    pub fn sample_in_minutes(&self) {
+        Self::sampling_in_minutes(
+            Arc::clone(&self.cs_list_hour),
+            self.get_times(),
+            self.get_value(),
+        );
+    }
+
+    /// Takes a sample of the counters for [`StatsItem::get_stats_data_in_day`], every hour.
+    pub fn sample_in_hour(&self) {
+        Self::sampling_in_hour(
+            Arc::clone(&self.cs_list_day),
+            self.get_times(),
+            self.get_value(),
+        );
+    }
+
+    pub fn print_stats_at_minutes(&self) {
+        Self::print_at_minutes(
+            &self.stats_name,
+            &self.stats_key,
+            Arc::clone(&self.cs_list_minute),
+        );
+    }
+
     pub fn print_at_minutes(
         stats_name: &str,
         stats_key: &str,
@@ -384,4 +463,19 @@ mod tests {
         assert_eq!(snapshot.get_times(), 0);
         assert_eq!(snapshot.get_avgpt(), 0.0);
     }
+
+    #[test]
+    fn sampling_records_added_values() {
+        let stats_item = StatsItem::new("TestName", "TestKey");
+        stats_item.sample_in_seconds();
+        stats_item.add(30, 2);
+        stats_item.add(10, 1);
+        stats_item.sample_in_seconds();
+        assert_eq!(stats_item.get_value(), 40);
+        assert_eq!(stats_item.get_times(), 3);
+        let snapshot = stats_item.get_stats_data_in_minute();
+        assert_eq!(snapshot.get_sum(), 40);
+        assert_eq!(snapshot.get_times(), 3);
+        assert!((snapshot.get_avgpt() - 40.0 / 3.0).abs() < f64::EPSILON);
+    }
 }
@@ -14,17 +14,183 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::common::stats::stats_item::StatsItem;
 use crate::common::stats::stats_snapshot::StatsSnapshot;
+use crate::TimeUtils::get_current_millis;
+use crate::UtilAll::compute_next_minutes_time_millis;
 
-#[derive(Debug)]
-pub struct StatsItemSet {}
+/// A named group of [`StatsItem`]s, one per stats key, e.g. one per topic for `TOPIC_PUT_NUMS`.
+#[derive(Debug, Clone)]
+pub struct StatsItemSet {
+    stats_item_table: Arc<DashMap<String, Arc<StatsItem>>>,
+    stats_name: String,
+    scheduled_tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
 
 impl StatsItemSet {
-    pub fn new(_stats_name: String) -> Self {
-        StatsItemSet {}
+    pub fn new(stats_name: String) -> Self {
+        StatsItemSet {
+            stats_item_table: Arc::new(DashMap::new()),
+            stats_name,
+            scheduled_tasks: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn get_stats_name(&self) -> &str {
+        &self.stats_name
+    }
+
+    /// Starts sampling the items for the minute, hour and day snapshots and printing the
+    /// minute snapshot. Must be called within a tokio runtime; stopped by
+    /// [`StatsItemSet::shutdown`].
+    pub fn init(&self) {
+        let mut scheduled_tasks = self.scheduled_tasks.lock();
+        if !scheduled_tasks.is_empty() {
+            return;
+        }
+        let sampling = [
+            (
+                Duration::from_secs(10),
+                StatsItem::sample_in_seconds as fn(&StatsItem),
+            ),
+            (Duration::from_secs(600), StatsItem::sample_in_minutes),
+            (Duration::from_secs(3600), StatsItem::sample_in_hour),
+        ];
+        for (period, sample) in sampling {
+            let stats_item_table = Arc::clone(&self.stats_item_table);
+            scheduled_tasks.push(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(period);
+                loop {
+                    interval.tick().await;
+                    for entry in stats_item_table.iter() {
+                        sample(entry.value());
+                    }
+                }
+            }));
+        }
+
+        let stats_item_table = Arc::clone(&self.stats_item_table);
+        scheduled_tasks.push(tokio::spawn(async move {
+            let initial_delay =
+                compute_next_minutes_time_millis().saturating_sub(get_current_millis());
+            tokio::time::sleep(Duration::from_millis(initial_delay)).await;
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                for entry in stats_item_table.iter() {
+                    entry.value().print_stats_at_minutes();
+                }
+            }
+        }));
+    }
+
+    pub fn shutdown(&self) {
+        for task in self.scheduled_tasks.lock().drain(..) {
+            task.abort();
+        }
+    }
+
+    pub fn add_value(&self, stats_key: &str, inc_value: u64, inc_times: u64) {
+        self.get_and_create_stats_item(stats_key)
+            .add(inc_value, inc_times);
+    }
+
+    pub fn del_value(&self, stats_key: &str) {
+        self.stats_item_table.remove(stats_key);
+    }
+
+    pub fn get_stats_item(&self, stats_key: &str) -> Option<Arc<StatsItem>> {
+        self.stats_item_table
+            .get(stats_key)
+            .map(|item| Arc::clone(item.value()))
+    }
+
+    pub fn get_and_create_stats_item(&self, stats_key: &str) -> Arc<StatsItem> {
+        if let Some(stats_item) = self.stats_item_table.get(stats_key) {
+            return Arc::clone(stats_item.value());
+        }
+        let stats_item = self
+            .stats_item_table
+            .entry(stats_key.to_string())
+            .or_insert_with(|| Arc::new(StatsItem::new(&self.stats_name, stats_key)));
+        Arc::clone(stats_item.value())
     }
 
     pub fn get_stats_data_in_minute(&self, stats_key: &str) -> StatsSnapshot {
-        StatsSnapshot::new()
+        self.get_stats_item(stats_key)
+            .map(|item| item.get_stats_data_in_minute())
+            .unwrap_or_default()
+    }
+
+    pub fn get_stats_data_in_hour(&self, stats_key: &str) -> StatsSnapshot {
+        self.get_stats_item(stats_key)
+            .map(|item| item.get_stats_data_in_hour())
+            .unwrap_or_default()
+    }
+
+    pub fn get_stats_data_in_day(&self, stats_key: &str) -> StatsSnapshot {
+        self.get_stats_item(stats_key)
+            .map(|item| item.get_stats_data_in_day())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_value_accumulates_per_key() {
+        let stats_set = StatsItemSet::new("TestName".to_string());
+        stats_set.add_value("TestKey", 10, 1);
+        stats_set.add_value("TestKey", 20, 1);
+        stats_set.add_value("OtherKey", 5, 1);
+
+        let stats_item = stats_set.get_stats_item("TestKey").unwrap();
+        assert_eq!(stats_item.get_stats_name(), "TestName");
+        assert_eq!(stats_item.get_value(), 30);
+        assert_eq!(stats_item.get_times(), 2);
+        assert_eq!(stats_set.get_stats_item("OtherKey").unwrap().get_value(), 5);
+    }
+
+    #[test]
+    fn unknown_key_has_empty_snapshot() {
+        let stats_set = StatsItemSet::new("TestName".to_string());
+        assert!(stats_set.get_stats_item("TestKey").is_none());
+        assert_eq!(stats_set.get_stats_data_in_minute("TestKey").get_times(), 0);
+    }
+
+    #[test]
+    fn del_value_removes_item() {
+        let stats_set = StatsItemSet::new("TestName".to_string());
+        stats_set.add_value("TestKey", 10, 1);
+        stats_set.del_value("TestKey");
+        assert!(stats_set.get_stats_item("TestKey").is_none());
+    }
+
+    #[tokio::test]
+    async fn init_samples_items_and_shutdown_stops_it() {
+        let stats_set = StatsItemSet::new("TestName".to_string());
+        stats_set.add_value("TestKey", 10, 1);
+        stats_set.init();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stats_set.add_value("TestKey", 30, 1);
+        stats_set
+            .get_stats_item("TestKey")
+            .unwrap()
+            .sample_in_seconds();
+
+        let snapshot = stats_set.get_stats_data_in_minute("TestKey");
+        assert_eq!(snapshot.get_sum(), 40);
+        assert_eq!(snapshot.get_times(), 2);
+        stats_set.shutdown();
     }
 }
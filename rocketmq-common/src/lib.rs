@@ -44,6 +44,7 @@ pub use crate::utils::file_utils as FileUtils;
 pub use crate::utils::message_utils as MessageUtils;
 pub use crate::utils::parse_config_file as ParseConfigFile;
 pub use crate::utils::time_utils as TimeUtils;
+pub use crate::utils::topic_retention_utils as TopicRetentionUtils;
 pub use crate::utils::util_all as UtilAll;
 
 pub mod common;
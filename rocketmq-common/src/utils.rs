@@ -28,4 +28,5 @@ pub mod queue_type_utils;
 pub mod serde_json_utils;
 pub mod string_utils;
 pub mod time_utils;
+pub mod topic_retention_utils;
 pub mod util_all;
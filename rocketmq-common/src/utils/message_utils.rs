@@ -18,6 +18,7 @@ use std::collections::hash_map::DefaultHasher;
 use std::collections::HashSet;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::net::IpAddr;
 use std::net::SocketAddr;
 
 use bytes::BufMut;
@@ -130,8 +131,26 @@ pub fn build_batch_message_id(
     message_id
 }
 
-pub fn parse_message_id(_msg_id: impl Into<String>) -> (SocketAddr, i64) {
-    unimplemented!()
+/// Splits an offset message id built by [`build_message_id`] back into the store host and the
+/// commit log offset, returning `None` when `msg_id` is not such an id.
+pub fn parse_message_id(msg_id: impl Into<String>) -> Option<(SocketAddr, i64)> {
+    let msg_id = msg_id.into();
+    if msg_id.len() % 2 != 0 || !msg_id.is_ascii() {
+        return None;
+    }
+    let bytes = (0..msg_id.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&msg_id[index..index + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    let ip: IpAddr = match bytes.len() {
+        16 => <[u8; 4]>::try_from(&bytes[..4]).ok()?.into(),
+        28 => <[u8; 16]>::try_from(&bytes[..16]).ok()?.into(),
+        _ => return None,
+    };
+    let port_start = bytes.len() - 12;
+    let port = i32::from_be_bytes(bytes[port_start..port_start + 4].try_into().ok()?);
+    let offset = i64::from_be_bytes(bytes[port_start + 4..].try_into().ok()?);
+    Some((SocketAddr::new(ip, u16::try_from(port).ok()?), offset))
 }
 
 #[cfg(test)]
@@ -200,6 +219,17 @@ mod tests {
         assert_eq!(result, "7F0000010000000C0000000000000001");
     }
 
+    #[test]
+    fn parse_message_id_reverses_build_message_id() {
+        for addr in ["127.0.0.1:10911", "[::1]:10911"] {
+            let socket_addr: SocketAddr = addr.parse().unwrap();
+            let msg_id = build_message_id(socket_addr, 4096);
+            assert_eq!(parse_message_id(msg_id), Some((socket_addr, 4096)));
+        }
+        assert_eq!(parse_message_id("7F0000010000000C"), None);
+        assert_eq!(parse_message_id("ZZ0000010000000C0000000000000001"), None);
+    }
+
     #[test]
     fn build_batch_message_id_creates_correct_id_for_single_position() {
         let socket_addr = SocketAddr::new(Ipv4Addr::new(127, 0, 0, 1).into(), 8080);
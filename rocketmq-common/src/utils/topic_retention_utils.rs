@@ -0,0 +1,73 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use crate::common::config::TopicConfig;
+use crate::TopicAttributes;
+
+/// Returns the message time to live of the topic in milliseconds, if one is set.
+pub fn get_message_ttl(topic_config: Option<&TopicConfig>) -> Option<i64> {
+    let attribute = &*TopicAttributes::MESSAGE_TTL_ATTRIBUTE;
+    let ttl = attribute.parse(
+        topic_config
+            .and_then(|config| config.attributes.get(attribute.get_name()))
+            .map(String::as_str),
+    );
+    (ttl > 0).then_some(ttl)
+}
+
+/// Returns the max bytes of messages the topic may keep in the store, if a quota is set.
+pub fn get_max_storage_bytes(topic_config: Option<&TopicConfig>) -> Option<i64> {
+    let attribute = &*TopicAttributes::MAX_STORAGE_BYTES_ATTRIBUTE;
+    let max_bytes = attribute.parse(
+        topic_config
+            .and_then(|config| config.attributes.get(attribute.get_name()))
+            .map(String::as_str),
+    );
+    (max_bytes >= 0).then_some(max_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retention_is_unset_by_default() {
+        let topic_config = TopicConfig::default();
+        assert_eq!(get_message_ttl(Some(&topic_config)), None);
+        assert_eq!(get_max_storage_bytes(Some(&topic_config)), None);
+        assert_eq!(get_message_ttl(None), None);
+        assert_eq!(get_max_storage_bytes(None), None);
+    }
+
+    #[test]
+    fn retention_is_read_from_topic_attributes() {
+        let mut topic_config = TopicConfig::default();
+        topic_config.attributes.insert(
+            TopicAttributes::MESSAGE_TTL_ATTRIBUTE
+                .get_name()
+                .to_string(),
+            "60000".to_string(),
+        );
+        topic_config.attributes.insert(
+            TopicAttributes::MAX_STORAGE_BYTES_ATTRIBUTE
+                .get_name()
+                .to_string(),
+            "1024".to_string(),
+        );
+        assert_eq!(get_message_ttl(Some(&topic_config)), Some(60000));
+        assert_eq!(get_max_storage_bytes(Some(&topic_config)), Some(1024));
+    }
+}
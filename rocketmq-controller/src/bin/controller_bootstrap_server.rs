@@ -0,0 +1,88 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::path::PathBuf;
+
+use clap::Parser;
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_common::EnvUtils::EnvUtils;
+use rocketmq_common::ParseConfigFile;
+use rocketmq_controller::bootstrap::Builder;
+use rocketmq_rust::rocketmq;
+use tracing::info;
+
+#[rocketmq::main]
+async fn main() -> anyhow::Result<()> {
+    rocketmq_common::log::init_logger();
+    let args = Args::parse();
+    let home = EnvUtils::get_rocketmq_home();
+
+    info!("Rocketmq(Rust) home: {}", home);
+    info!(
+        "Rocketmq controller(Rust) running on: {}:{}",
+        args.ip, args.port
+    );
+    let config_file = args
+        .config
+        .unwrap_or_else(|| PathBuf::from(home).join("conf").join("controller.toml"));
+    let controller_config =
+        ParseConfigFile::parse_config_file::<ControllerConfig>(config_file.clone())?;
+    let mut server_config = ParseConfigFile::parse_config_file::<ServerConfig>(config_file)?;
+    server_config.listen_port = args.port;
+    server_config.bind_address = args.ip;
+    Builder::new()
+        .set_controller_config(controller_config)
+        .set_server_config(server_config)
+        .build()
+        .boot()
+        .await;
+
+    Ok(())
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    author = "mxsm",
+    version = "0.1.0",
+    about = "RocketMQ Controller(Rust)"
+)]
+struct Args {
+    /// rocketmq controller port
+    #[arg(
+        short,
+        long,
+        value_name = "PORT",
+        default_missing_value = "9878",
+        default_value = "9878",
+        required = false
+    )]
+    port: u32,
+
+    /// rocketmq controller ip
+    #[arg(
+        short,
+        long,
+        value_name = "IP",
+        default_value = "0.0.0.0",
+        required = false
+    )]
+    ip: String,
+
+    /// rocketmq controller config file
+    #[arg(short, long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
@@ -0,0 +1,97 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::remoting_server::server::RocketMQServer;
+use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+use tracing::error;
+use tracing::info;
+
+use crate::controller_manager::ControllerManager;
+use crate::processor::ControllerRequestProcessor;
+
+/// Runs the controller as a standalone server.
+pub struct ControllerBootstrap {
+    controller_config: Arc<ControllerConfig>,
+    server_config: Arc<ServerConfig>,
+}
+
+pub struct Builder {
+    controller_config: Option<ControllerConfig>,
+    server_config: Option<ServerConfig>,
+}
+
+impl ControllerBootstrap {
+    pub async fn boot(self) {
+        let remoting_client = RocketmqDefaultClient::new(
+            Arc::new(TokioClientConfig::default()),
+            DefaultRemotingRequestProcessor,
+        );
+        let controller_manager = Arc::new(ControllerManager::new(
+            self.controller_config,
+            Arc::new(remoting_client),
+        ));
+        controller_manager.start();
+        let server = RocketMQServer::new(self.server_config);
+        // Returns on ctrl-c once the connections are drained
+        match server
+            .run(ControllerRequestProcessor::new(controller_manager.clone()))
+            .await
+        {
+            Ok(()) => info!("controller remoting server terminated, shutting down"),
+            Err(err) => error!("controller remoting server failed to start: {}", err),
+        }
+        // The remoting client owns a runtime, which can not be dropped on an async thread
+        let _ = tokio::task::spawn_blocking(move || drop(controller_manager)).await;
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Builder {
+            controller_config: None,
+            server_config: None,
+        }
+    }
+
+    pub fn set_controller_config(mut self, controller_config: ControllerConfig) -> Self {
+        self.controller_config = Some(controller_config);
+        self
+    }
+
+    pub fn set_server_config(mut self, server_config: ServerConfig) -> Self {
+        self.server_config = Some(server_config);
+        self
+    }
+
+    pub fn build(self) -> ControllerBootstrap {
+        ControllerBootstrap {
+            controller_config: Arc::new(self.controller_config.unwrap_or_default()),
+            server_config: Arc::new(self.server_config.unwrap_or_default()),
+        }
+    }
+}
@@ -0,0 +1,568 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetRequestHeader;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetResponseHeader;
+use rocketmq_remoting::protocol::header::controller::apply_broker_id_request_header::ApplyBrokerIdRequestHeader;
+use rocketmq_remoting::protocol::header::controller::apply_broker_id_request_header::ApplyBrokerIdResponseHeader;
+use rocketmq_remoting::protocol::header::controller::clean_controller_broker_data_request_header::CleanControllerBrokerDataRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_meta_data_response_header::GetMetaDataResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_next_broker_id_request_header::GetNextBrokerIdRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_next_broker_id_request_header::GetNextBrokerIdResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoResponseHeader;
+use rocketmq_remoting::protocol::header::controller::notify_broker_role_changed_request_header::NotifyBrokerRoleChangedRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerResponseHeader;
+use rocketmq_remoting::protocol::header::namesrv::broker_request::BrokerHeartbeatRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use tracing::info;
+use tracing::warn;
+
+use crate::controller_result::ControllerResult;
+use crate::controller_state_machine::ControllerStateMachine;
+use crate::elect_policy::DefaultElectPolicy;
+use crate::elect_policy::ElectPolicy;
+use crate::event::EventMessage;
+use crate::heartbeat_manager::BrokerHeartbeatManager;
+use crate::raft::message::RaftEnvelope;
+use crate::raft::node::RaftNode;
+use crate::raft::parse_peers;
+use crate::raft::transport::RaftTransport;
+use crate::raft::transport::RemotingRaftTransport;
+use crate::raft::NodeId;
+use crate::replicas_info_manager::ReplicasInfoManager;
+
+const NOTIFY_BROKER_ROLE_CHANGED_TIMEOUT_MILLIS: u64 = 3000;
+
+/// Keeps the replica metadata of the broker sets and elects their masters.
+///
+/// Embedded in the name server when `enable_controller_in_namesrv` is set, or run standalone
+/// with `rocketmq-controller-rust`. When `controller_raft_peers` is set the controllers form a
+/// Raft group: the leader serves the requests and replicates their events to the others.
+pub struct ControllerManager {
+    controller_config: Arc<ControllerConfig>,
+    replicas_info_manager: Arc<Mutex<ReplicasInfoManager>>,
+    heartbeat_manager: Arc<BrokerHeartbeatManager>,
+    elect_policy: Arc<dyn ElectPolicy>,
+    remoting_client: Arc<RocketmqDefaultClient>,
+    raft_node: Option<Arc<RaftNode<ControllerStateMachine>>>,
+    raft_peers: BTreeMap<NodeId, String>,
+    /// Serializes the requests while their events are replicated, so each request sees the
+    /// events of the previous ones.
+    request_lock: tokio::sync::Mutex<()>,
+}
+
+impl ControllerManager {
+    pub fn new(
+        controller_config: Arc<ControllerConfig>,
+        remoting_client: Arc<RocketmqDefaultClient>,
+    ) -> Self {
+        let raft_peers = controller_config
+            .controller_raft_peers
+            .as_deref()
+            .map(parse_peers)
+            .unwrap_or_default();
+        let raft_transport = Arc::new(RemotingRaftTransport::new(
+            remoting_client.clone(),
+            raft_peers,
+        ));
+        Self::new_with_raft_transport(controller_config, remoting_client, raft_transport)
+    }
+
+    /// Creates a manager sending the Raft messages through `raft_transport`, which is only
+    /// used when `controller_raft_peers` is set.
+    pub fn new_with_raft_transport(
+        controller_config: Arc<ControllerConfig>,
+        remoting_client: Arc<RocketmqDefaultClient>,
+        raft_transport: Arc<dyn RaftTransport>,
+    ) -> Self {
+        let replicas_info_manager = Arc::new(Mutex::new(ReplicasInfoManager::new()));
+        let raft_peers = controller_config
+            .controller_raft_peers
+            .as_deref()
+            .map(parse_peers)
+            .unwrap_or_default();
+        let raft_node = (!raft_peers.is_empty()).then(|| {
+            Arc::new(RaftNode::with_controller_config(
+                &controller_config,
+                &controller_config.controller_raft_group,
+                ControllerStateMachine::new(replicas_info_manager.clone()),
+                raft_transport,
+            ))
+        });
+        let heartbeat_manager = Arc::new(BrokerHeartbeatManager::new());
+        let elect_policy = Arc::new(DefaultElectPolicy::new(
+            heartbeat_manager.clone(),
+            controller_config.enable_elect_unclean_master,
+        ));
+        Self {
+            controller_config,
+            replicas_info_manager,
+            heartbeat_manager,
+            elect_policy,
+            remoting_client,
+            raft_node,
+            raft_peers,
+            request_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Starts electing new masters for broker sets whose master stopped sending heartbeats,
+    /// until the manager is dropped.
+    pub fn start(self: &Arc<Self>) {
+        if let Some(raft_node) = &self.raft_node {
+            raft_node.start();
+        }
+        let manager = Arc::downgrade(self);
+        let period = Duration::from_millis(
+            self.controller_config
+                .scan_not_active_broker_interval
+                .max(100),
+        );
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.scan_not_active_broker().await;
+            }
+        });
+        info!("controller manager started");
+    }
+
+    pub fn controller_config(&self) -> &ControllerConfig {
+        &self.controller_config
+    }
+
+    pub fn heartbeat_manager(&self) -> &Arc<BrokerHeartbeatManager> {
+        &self.heartbeat_manager
+    }
+
+    fn is_alive(&self) -> impl Fn(&str, &str, i64) -> bool + '_ {
+        |cluster_name, broker_name, broker_id| {
+            self.heartbeat_manager
+                .is_broker_active(cluster_name, broker_name, broker_id)
+        }
+    }
+
+    pub fn raft_node(&self) -> Option<&Arc<RaftNode<ControllerStateMachine>>> {
+        self.raft_node.as_ref()
+    }
+
+    /// Whether this controller serves the requests, that is it leads its Raft group.
+    pub fn is_leader(&self) -> bool {
+        self.raft_node
+            .as_ref()
+            .is_none_or(|raft_node| raft_node.is_leader_ready())
+    }
+
+    /// Runs a request against the replica metadata and applies the events it produced before
+    /// anyone else can read the metadata. In a Raft group, only the leader serves requests
+    /// and answers once their events are committed.
+    async fn handle_request<T>(
+        &self,
+        request: impl FnOnce(&ReplicasInfoManager) -> ControllerResult<T>,
+    ) -> ControllerResult<T> {
+        let Some(raft_node) = &self.raft_node else {
+            let mut replicas_info_manager = self.replicas_info_manager.lock();
+            let result = request(&replicas_info_manager);
+            for event in result.events() {
+                replicas_info_manager.apply_event(event);
+            }
+            return result;
+        };
+        let _request_guard = self.request_lock.lock().await;
+        if !raft_node.is_leader_ready() {
+            return ControllerResult::failure(
+                ResponseCode::ControllerNotLeader,
+                format!(
+                    "controller {} is not the leader, leader: {:?}",
+                    raft_node.id(),
+                    raft_node.leader_id()
+                ),
+            );
+        }
+        let result = request(&self.replicas_info_manager.lock());
+        if result.events().is_empty() {
+            return result;
+        }
+        match raft_node
+            .propose(ControllerStateMachine::encode_events(result.events()))
+            .await
+        {
+            Ok(()) => result,
+            Err(err) => {
+                ControllerResult::failure(ResponseCode::ControllerNotLeader, err.to_string())
+            }
+        }
+    }
+
+    /// Feeds a message of the Raft group to this controller.
+    pub fn on_raft_message(&self, envelope: RaftEnvelope) {
+        match &self.raft_node {
+            Some(raft_node) => raft_node.step(envelope),
+            None => warn!(
+                "drop raft message of group {} from {}, raft is not enabled",
+                envelope.group, envelope.from
+            ),
+        }
+    }
+
+    pub fn on_broker_heartbeat(&self, request: &BrokerHeartbeatRequestHeader) {
+        self.heartbeat_manager.on_broker_heartbeat(request);
+    }
+
+    pub async fn get_next_broker_id(
+        &self,
+        request: &GetNextBrokerIdRequestHeader,
+    ) -> ControllerResult<GetNextBrokerIdResponseHeader> {
+        self.handle_request(|manager| manager.get_next_broker_id(request))
+            .await
+    }
+
+    pub async fn apply_broker_id(
+        &self,
+        request: &ApplyBrokerIdRequestHeader,
+    ) -> ControllerResult<ApplyBrokerIdResponseHeader> {
+        self.handle_request(|manager| manager.apply_broker_id(request))
+            .await
+    }
+
+    pub async fn register_broker(
+        &self,
+        request: &RegisterBrokerToControllerRequestHeader,
+    ) -> ControllerResult<RegisterBrokerToControllerResponseHeader> {
+        self.handle_request(|manager| manager.register_broker(request, self.is_alive()))
+            .await
+    }
+
+    pub async fn elect_master(
+        &self,
+        request: &ElectMasterRequestHeader,
+    ) -> ControllerResult<ElectMasterResponseHeader> {
+        let result = self
+            .handle_request(|manager| manager.elect_master(request, self.elect_policy.as_ref()))
+            .await;
+        if result.events().iter().any(|event| {
+            matches!(
+                event,
+                EventMessage::ElectMaster {
+                    new_master_broker_id: Some(_),
+                    ..
+                }
+            )
+        }) {
+            self.notify_broker_role_changed(&request.broker_name);
+        }
+        result
+    }
+
+    pub async fn alter_sync_state_set(
+        &self,
+        request: &AlterSyncStateSetRequestHeader,
+        sync_state_set: &SyncStateSet,
+    ) -> ControllerResult<AlterSyncStateSetResponseHeader> {
+        self.handle_request(|manager| {
+            manager.alter_sync_state_set(request, sync_state_set, self.is_alive())
+        })
+        .await
+    }
+
+    pub async fn get_replica_info(
+        &self,
+        request: &GetReplicaInfoRequestHeader,
+    ) -> ControllerResult<GetReplicaInfoResponseHeader> {
+        self.handle_request(|manager| manager.get_replica_info(request))
+            .await
+    }
+
+    pub async fn get_sync_state_data(&self, broker_names: &[String]) -> ControllerResult<()> {
+        self.handle_request(|manager| manager.get_sync_state_data(broker_names, self.is_alive()))
+            .await
+    }
+
+    pub async fn clean_broker_data(
+        &self,
+        request: &CleanControllerBrokerDataRequestHeader,
+    ) -> ControllerResult<()> {
+        self.handle_request(|manager| manager.clean_broker_data(request, self.is_alive()))
+            .await
+    }
+
+    /// A standalone controller is always the leader of its own group.
+    pub fn get_controller_metadata(&self) -> GetMetaDataResponseHeader {
+        let Some(raft_node) = &self.raft_node else {
+            return GetMetaDataResponseHeader {
+                is_leader: true,
+                ..Default::default()
+            };
+        };
+        let controller_leader_id = raft_node.leader_id();
+        GetMetaDataResponseHeader {
+            group: Some(self.controller_config.controller_raft_group.clone()),
+            controller_leader_address: controller_leader_id
+                .as_ref()
+                .and_then(|leader_id| self.raft_peers.get(leader_id))
+                .cloned(),
+            controller_leader_id,
+            is_leader: raft_node.is_leader_ready(),
+            peers: Some(
+                self.raft_peers
+                    .values()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(";"),
+            ),
+        }
+    }
+
+    async fn scan_not_active_broker(&self) {
+        let inactive_brokers = self.heartbeat_manager.scan_not_active_broker();
+        // Followers track heartbeats too, to take over when they become leader
+        if !self.is_leader() {
+            return;
+        }
+        for identity in inactive_brokers {
+            let is_master = self
+                .replicas_info_manager
+                .lock()
+                .is_master(&identity.broker_name, identity.broker_id);
+            if !is_master {
+                continue;
+            }
+            warn!(
+                "master {} of broker set {} is inactive, electing a new master",
+                identity.broker_id, identity.broker_name
+            );
+            let result = self
+                .elect_master(&ElectMasterRequestHeader {
+                    cluster_name: identity.cluster_name.clone(),
+                    broker_name: identity.broker_name.clone(),
+                    broker_id: None,
+                    designate_elect: false,
+                    invoke_time: get_current_millis() as i64,
+                })
+                .await;
+            if !result.is_success() {
+                warn!(
+                    "failed to elect a new master of broker set {}: {:?}",
+                    identity.broker_name,
+                    result.remark()
+                );
+            }
+        }
+    }
+
+    /// Tells every alive replica of the broker set about its new master.
+    fn notify_broker_role_changed(&self, broker_name: &str) {
+        if !self.controller_config.notify_broker_role_changed {
+            return;
+        }
+        let (header, body, targets) = {
+            let replicas_info_manager = self.replicas_info_manager.lock();
+            let (Some(replica_info), Some(sync_state_info)) = (
+                replicas_info_manager.replica_info(broker_name),
+                replicas_info_manager.sync_state_info(broker_name),
+            ) else {
+                return;
+            };
+            let master_broker_id = sync_state_info.master_broker_id();
+            let header = NotifyBrokerRoleChangedRequestHeader {
+                master_address: master_broker_id
+                    .and_then(|broker_id| replica_info.broker_address(broker_id))
+                    .map(str::to_string),
+                master_epoch: sync_state_info.master_epoch(),
+                sync_state_set_epoch: sync_state_info.sync_state_set_epoch(),
+                master_broker_id,
+            };
+            let body = SyncStateSet::new(
+                sync_state_info.sync_state_set().clone(),
+                sync_state_info.sync_state_set_epoch(),
+            )
+            .encode();
+            let targets = replica_info
+                .broker_addresses()
+                .into_iter()
+                .filter(|(broker_id, _)| {
+                    self.heartbeat_manager.is_broker_active(
+                        replica_info.cluster_name(),
+                        broker_name,
+                        *broker_id,
+                    )
+                })
+                .map(|(_, address)| address)
+                .collect::<Vec<_>>();
+            (header, body, targets)
+        };
+        for broker_address in targets {
+            let request = RemotingCommand::create_request_command(
+                ControllerRequestCode::NotifyBrokerRoleChanged,
+                header.clone(),
+            )
+            .set_body(Some(body.clone()));
+            let remoting_client = self.remoting_client.clone();
+            tokio::spawn(async move {
+                remoting_client
+                    .invoke_oneway(
+                        broker_address,
+                        request,
+                        NOTIFY_BROKER_ROLE_CHANGED_TIMEOUT_MILLIS,
+                    )
+                    .await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+
+    use super::*;
+    use crate::raft::local_cluster::LocalRouter;
+
+    fn raft_group(
+        router: &Arc<LocalRouter>,
+        store_dir: &std::path::Path,
+    ) -> Vec<Arc<ControllerManager>> {
+        let remoting_client = Arc::new(RocketmqDefaultClient::new(
+            Arc::new(TokioClientConfig::default()),
+            DefaultRemotingRequestProcessor,
+        ));
+        ["n0", "n1", "n2"]
+            .into_iter()
+            .map(|self_id| {
+                let controller_config = ControllerConfig {
+                    controller_store_path: store_dir.to_string_lossy().into_owned(),
+                    controller_raft_peers: Some(
+                        "n0-127.0.0.1:9878;n1-127.0.0.1:9868;n2-127.0.0.1:9858".to_string(),
+                    ),
+                    controller_raft_self_id: self_id.to_string(),
+                    raft_election_timeout_mills: 100,
+                    raft_heartbeat_interval_mills: 10,
+                    ..ControllerConfig::default()
+                };
+                let manager = Arc::new(ControllerManager::new_with_raft_transport(
+                    Arc::new(controller_config),
+                    remoting_client.clone(),
+                    router.clone(),
+                ));
+                let endpoint = Arc::downgrade(&manager);
+                router.register(self_id, move |envelope| {
+                    if let Some(manager) = endpoint.upgrade() {
+                        manager.on_raft_message(envelope);
+                    }
+                });
+                manager.start();
+                manager
+            })
+            .collect()
+    }
+
+    async fn wait_for_leader(managers: &[Arc<ControllerManager>]) -> Arc<ControllerManager> {
+        for _ in 0..500 {
+            if let Some(leader) = managers.iter().find(|manager| manager.is_leader()) {
+                return leader.clone();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("no controller became leader");
+    }
+
+    fn apply_broker_id_request(broker_id: i64) -> ApplyBrokerIdRequestHeader {
+        ApplyBrokerIdRequestHeader {
+            cluster_name: "DefaultCluster".to_string(),
+            broker_name: "broker-a".to_string(),
+            applied_broker_id: broker_id,
+            register_check_code: format!("127.0.0.1:1091{};1", broker_id),
+        }
+    }
+
+    #[test]
+    fn raft_group_replicates_metadata_and_fails_over() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+        let store_dir = std::env::temp_dir().join("controller_manager_raft_group");
+        let _ = std::fs::remove_dir_all(&store_dir);
+        runtime.block_on(async {
+            let router = Arc::new(LocalRouter::new());
+            let managers = raft_group(&router, &store_dir);
+            let leader = wait_for_leader(&managers).await;
+            let follower = managers
+                .iter()
+                .find(|manager| !Arc::ptr_eq(manager, &leader))
+                .unwrap();
+            assert!(leader
+                .apply_broker_id(&apply_broker_id_request(1))
+                .await
+                .is_success());
+            let result = follower.apply_broker_id(&apply_broker_id_request(2)).await;
+            assert_eq!(result.response_code(), ResponseCode::ControllerNotLeader);
+            let metadata = follower.get_controller_metadata();
+            assert!(!metadata.is_leader);
+            assert_eq!(
+                metadata.controller_leader_id.as_deref(),
+                leader.raft_node().map(|node| node.id()).as_deref()
+            );
+
+            // The new leader continues from the metadata replicated by the old one
+            let old_leader_id = leader.raft_node().unwrap().id();
+            router.isolate(old_leader_id.clone());
+            let others = managers
+                .iter()
+                .filter(|manager| !Arc::ptr_eq(manager, &leader))
+                .cloned()
+                .collect::<Vec<_>>();
+            let new_leader = wait_for_leader(&others).await;
+            assert_ne!(new_leader.raft_node().unwrap().id(), old_leader_id);
+            let result = new_leader
+                .get_next_broker_id(&GetNextBrokerIdRequestHeader {
+                    cluster_name: "DefaultCluster".to_string(),
+                    broker_name: "broker-a".to_string(),
+                })
+                .await;
+            assert_eq!(result.response().unwrap().next_broker_id, Some(2));
+
+            drop((leader, new_leader, others));
+            tokio::task::spawn_blocking(move || drop(managers))
+                .await
+                .unwrap();
+        });
+        let _ = std::fs::remove_dir_all(store_dir);
+    }
+}
@@ -0,0 +1,122 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::command_custom_header::CommandCustomHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+
+use crate::event::EventMessage;
+
+/// The outcome of a controller request: the events to apply to the replica metadata, and the
+/// response to send back once they are applied.
+#[derive(Debug, Clone)]
+pub struct ControllerResult<T> {
+    events: Vec<EventMessage>,
+    response: Option<T>,
+    body: Option<Vec<u8>>,
+    response_code: ResponseCode,
+    remark: Option<String>,
+}
+
+impl<T> ControllerResult<T> {
+    pub fn new(response: Option<T>) -> Self {
+        Self {
+            events: Vec::new(),
+            response,
+            body: None,
+            response_code: ResponseCode::Success,
+            remark: None,
+        }
+    }
+
+    pub fn of(events: Vec<EventMessage>, response: Option<T>) -> Self {
+        Self {
+            events,
+            ..Self::new(response)
+        }
+    }
+
+    pub fn failure(response_code: ResponseCode, remark: impl Into<String>) -> Self {
+        let mut result = Self::new(None);
+        result.set_code_and_remark(response_code, remark);
+        result
+    }
+
+    pub fn events(&self) -> &[EventMessage] {
+        &self.events
+    }
+
+    pub fn add_event(&mut self, event: EventMessage) {
+        self.events.push(event);
+    }
+
+    pub fn response(&self) -> Option<&T> {
+        self.response.as_ref()
+    }
+
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    pub fn set_body(&mut self, body: Vec<u8>) {
+        self.body = Some(body);
+    }
+
+    pub fn response_code(&self) -> ResponseCode {
+        self.response_code
+    }
+
+    pub fn remark(&self) -> Option<&str> {
+        self.remark.as_deref()
+    }
+
+    pub fn set_code_and_remark(&mut self, response_code: ResponseCode, remark: impl Into<String>) {
+        self.response_code = response_code;
+        self.remark = Some(remark.into());
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.response_code == ResponseCode::Success
+    }
+}
+
+impl<T> ControllerResult<T> {
+    fn response_command(response_code: ResponseCode, remark: Option<String>) -> RemotingCommand {
+        RemotingCommand::create_response_command()
+            .set_code(response_code)
+            .set_remark(remark)
+    }
+}
+
+impl<T> ControllerResult<T>
+where
+    T: CommandCustomHeader + Send + Sync + 'static,
+{
+    pub fn into_response_command(self) -> RemotingCommand {
+        let mut response = Self::response_command(self.response_code, self.remark);
+        if let Some(header) = self.response {
+            response = response.set_command_custom_header(header);
+        }
+        response.set_body(self.body)
+    }
+}
+
+impl ControllerResult<()> {
+    /// Builds the response of requests answering with a body only.
+    pub fn into_body_response_command(self) -> RemotingCommand {
+        Self::response_command(self.response_code, self.remark).set_body(self.body)
+    }
+}
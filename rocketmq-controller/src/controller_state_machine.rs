@@ -0,0 +1,112 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tracing::error;
+
+use crate::event::EventMessage;
+use crate::raft::StateMachine;
+use crate::replicas_info_manager::ReplicasInfoManager;
+
+/// Replicates the replica metadata: each log entry holds the events of one controller request.
+pub struct ControllerStateMachine {
+    replicas_info_manager: Arc<Mutex<ReplicasInfoManager>>,
+}
+
+impl ControllerStateMachine {
+    pub fn new(replicas_info_manager: Arc<Mutex<ReplicasInfoManager>>) -> Self {
+        Self {
+            replicas_info_manager,
+        }
+    }
+
+    pub fn encode_events(events: &[EventMessage]) -> Vec<u8> {
+        serde_json::to_vec(events).expect("controller events are always serializable")
+    }
+}
+
+impl StateMachine for ControllerStateMachine {
+    fn apply(&mut self, index: u64, data: &[u8]) {
+        match serde_json::from_slice::<Vec<EventMessage>>(data) {
+            Ok(events) => {
+                let mut replicas_info_manager = self.replicas_info_manager.lock();
+                for event in &events {
+                    replicas_info_manager.apply_event(event);
+                }
+            }
+            Err(err) => error!(
+                "failed to decode controller events at index {}: {}",
+                index, err
+            ),
+        }
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        serde_json::to_vec(&*self.replicas_info_manager.lock())
+            .expect("replica metadata is always serializable")
+    }
+
+    fn restore(&mut self, data: &[u8]) {
+        match serde_json::from_slice::<ReplicasInfoManager>(data) {
+            Ok(replicas_info_manager) => *self.replicas_info_manager.lock() = replicas_info_manager,
+            Err(err) => error!("failed to restore replica metadata from snapshot: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn snapshot_restores_the_replica_metadata() {
+        let mut state_machine =
+            ControllerStateMachine::new(Arc::new(Mutex::new(ReplicasInfoManager::new())));
+        state_machine.apply(
+            1,
+            &ControllerStateMachine::encode_events(&[
+                EventMessage::ApplyBrokerId {
+                    cluster_name: "DefaultCluster".to_string(),
+                    broker_name: "broker-a".to_string(),
+                    broker_address: "127.0.0.1:10911".to_string(),
+                    new_broker_id: 1,
+                    register_check_code: "127.0.0.1:10911;1".to_string(),
+                },
+                EventMessage::ElectMaster {
+                    broker_name: "broker-a".to_string(),
+                    new_master_broker_id: Some(1),
+                },
+            ]),
+        );
+        let snapshot = state_machine.snapshot();
+
+        let restored = Arc::new(Mutex::new(ReplicasInfoManager::new()));
+        ControllerStateMachine::new(restored.clone()).restore(&snapshot);
+        let restored = restored.lock();
+        assert!(restored.is_master("broker-a", 1));
+        assert_eq!(
+            restored
+                .sync_state_info("broker-a")
+                .unwrap()
+                .sync_state_set(),
+            &HashSet::from([1])
+        );
+    }
+}
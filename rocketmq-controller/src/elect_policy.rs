@@ -0,0 +1,251 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::heartbeat_manager::BrokerHeartbeatManager;
+
+pub trait ElectPolicy: Send + Sync {
+    /// Picks the master of a broker set among its replicas.
+    ///
+    /// # Arguments
+    /// * `sync_state_brokers` - Replicas in sync with the old master.
+    /// * `all_replica_brokers` - Every replica of the broker set.
+    /// * `old_master` - The current master, if any.
+    /// * `prefer_broker_id` - A replica to favor among the candidates.
+    ///
+    /// # Returns
+    /// The broker id of the new master, or `None` when no replica can be elected.
+    fn elect(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        sync_state_brokers: &HashSet<i64>,
+        all_replica_brokers: &HashSet<i64>,
+        old_master: Option<i64>,
+        prefer_broker_id: Option<i64>,
+    ) -> Option<i64>;
+
+    /// Whether the replica is alive and allowed to become master.
+    fn is_valid_candidate(&self, cluster_name: &str, broker_name: &str, broker_id: i64) -> bool;
+}
+
+/// Keeps the old master while it is alive, otherwise elects the alive in-sync replica with the
+/// highest epoch, then the highest max offset, then the lowest election priority.
+///
+/// When `enable_elect_unclean_master` is set and no in-sync replica is alive, any alive replica
+/// may be elected.
+pub struct DefaultElectPolicy {
+    heartbeat_manager: Arc<BrokerHeartbeatManager>,
+    enable_elect_unclean_master: bool,
+}
+
+impl DefaultElectPolicy {
+    pub fn new(
+        heartbeat_manager: Arc<BrokerHeartbeatManager>,
+        enable_elect_unclean_master: bool,
+    ) -> Self {
+        Self {
+            heartbeat_manager,
+            enable_elect_unclean_master,
+        }
+    }
+
+    fn try_elect(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        brokers: &HashSet<i64>,
+        prefer_broker_id: Option<i64>,
+    ) -> Option<i64> {
+        let mut candidates = brokers
+            .iter()
+            .copied()
+            .filter(|broker_id| self.is_valid_candidate(cluster_name, broker_name, *broker_id))
+            .collect::<Vec<_>>();
+        if let Some(prefer_broker_id) = prefer_broker_id {
+            if candidates.contains(&prefer_broker_id) {
+                return Some(prefer_broker_id);
+            }
+        }
+        candidates.sort_by_key(|broker_id| {
+            let live_info =
+                self.heartbeat_manager
+                    .get_broker_live_info(cluster_name, broker_name, *broker_id);
+            (
+                Reverse(live_info.as_ref().map_or(i32::MIN, |info| info.epoch)),
+                Reverse(live_info.as_ref().map_or(i64::MIN, |info| info.max_offset)),
+                live_info.map_or(i32::MAX, |info| info.election_priority),
+                *broker_id,
+            )
+        });
+        candidates.first().copied()
+    }
+}
+
+impl ElectPolicy for DefaultElectPolicy {
+    fn elect(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        sync_state_brokers: &HashSet<i64>,
+        all_replica_brokers: &HashSet<i64>,
+        old_master: Option<i64>,
+        prefer_broker_id: Option<i64>,
+    ) -> Option<i64> {
+        if let Some(old_master) = old_master {
+            if self.is_valid_candidate(cluster_name, broker_name, old_master) {
+                return Some(old_master);
+            }
+        }
+        self.try_elect(
+            cluster_name,
+            broker_name,
+            sync_state_brokers,
+            prefer_broker_id,
+        )
+        .or_else(|| {
+            if self.enable_elect_unclean_master {
+                self.try_elect(
+                    cluster_name,
+                    broker_name,
+                    all_replica_brokers,
+                    prefer_broker_id,
+                )
+            } else {
+                None
+            }
+        })
+    }
+
+    fn is_valid_candidate(&self, cluster_name: &str, broker_name: &str, broker_id: i64) -> bool {
+        self.heartbeat_manager
+            .is_broker_active(cluster_name, broker_name, broker_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::header::namesrv::broker_request::BrokerHeartbeatRequestHeader;
+
+    use super::*;
+
+    fn heartbeat(
+        manager: &BrokerHeartbeatManager,
+        broker_id: i64,
+        epoch: i32,
+        max_offset: i64,
+        election_priority: i32,
+    ) {
+        manager.on_broker_heartbeat(&BrokerHeartbeatRequestHeader::new(
+            "DefaultCluster",
+            format!("127.0.0.1:1091{}", broker_id),
+            "broker-a",
+            Some(broker_id),
+            Some(epoch),
+            Some(max_offset),
+            None,
+            Some(60_000),
+            Some(election_priority),
+        ));
+    }
+
+    #[test]
+    fn keeps_alive_old_master() {
+        let manager = Arc::new(BrokerHeartbeatManager::new());
+        heartbeat(&manager, 1, 1, 10, 0);
+        heartbeat(&manager, 2, 2, 100, 0);
+        let policy = DefaultElectPolicy::new(manager, false);
+        let replicas = HashSet::from([1, 2]);
+        assert_eq!(
+            policy.elect(
+                "DefaultCluster",
+                "broker-a",
+                &replicas,
+                &replicas,
+                Some(1),
+                None
+            ),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn elects_by_epoch_then_offset_then_priority() {
+        let manager = Arc::new(BrokerHeartbeatManager::new());
+        heartbeat(&manager, 2, 1, 100, 0);
+        heartbeat(&manager, 3, 2, 50, 5);
+        heartbeat(&manager, 4, 2, 50, 1);
+        let policy = DefaultElectPolicy::new(manager, false);
+        let replicas = HashSet::from([1, 2, 3, 4]);
+        assert_eq!(
+            policy.elect(
+                "DefaultCluster",
+                "broker-a",
+                &replicas,
+                &replicas,
+                Some(1),
+                None
+            ),
+            Some(4)
+        );
+        assert_eq!(
+            policy.elect(
+                "DefaultCluster",
+                "broker-a",
+                &replicas,
+                &replicas,
+                Some(1),
+                Some(2)
+            ),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn elects_out_of_sync_replica_only_when_unclean_election_is_enabled() {
+        let manager = Arc::new(BrokerHeartbeatManager::new());
+        heartbeat(&manager, 2, 1, 100, 0);
+        let sync_state_set = HashSet::from([1]);
+        let replicas = HashSet::from([1, 2]);
+        let clean = DefaultElectPolicy::new(manager.clone(), false);
+        assert_eq!(
+            clean.elect(
+                "DefaultCluster",
+                "broker-a",
+                &sync_state_set,
+                &replicas,
+                Some(1),
+                None
+            ),
+            None
+        );
+        let unclean = DefaultElectPolicy::new(manager, true);
+        assert_eq!(
+            unclean.elect(
+                "DefaultCluster",
+                "broker-a",
+                &sync_state_set,
+                &replicas,
+                Some(1),
+                None
+            ),
+            Some(2)
+        );
+    }
+}
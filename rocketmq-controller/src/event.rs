@@ -0,0 +1,86 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A change of the replica metadata.
+///
+/// Requests never touch the metadata directly: they compute the events describing the change
+/// against the current state, and the events are then applied in order. This keeps the state
+/// machine deterministic so the events can be replicated to other controllers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventMessage {
+    ApplyBrokerId {
+        cluster_name: String,
+        broker_name: String,
+        broker_address: String,
+        new_broker_id: i64,
+        register_check_code: String,
+    },
+    UpdateBrokerAddress {
+        cluster_name: String,
+        broker_name: String,
+        broker_address: String,
+        broker_id: i64,
+    },
+    /// `new_master_broker_id` is `None` when no replica could be elected, the broker set is left
+    /// without a master until the next election.
+    ElectMaster {
+        broker_name: String,
+        new_master_broker_id: Option<i64>,
+    },
+    AlterSyncStateSet {
+        broker_name: String,
+        new_sync_state_set: HashSet<i64>,
+    },
+    /// Forgets the given replicas, or the whole broker set when `broker_ids_to_clean` is `None`.
+    CleanBrokerData {
+        broker_name: String,
+        broker_ids_to_clean: Option<HashSet<i64>>,
+    },
+}
+
+impl EventMessage {
+    pub fn broker_name(&self) -> &str {
+        match self {
+            EventMessage::ApplyBrokerId { broker_name, .. }
+            | EventMessage::UpdateBrokerAddress { broker_name, .. }
+            | EventMessage::ElectMaster { broker_name, .. }
+            | EventMessage::AlterSyncStateSet { broker_name, .. }
+            | EventMessage::CleanBrokerData { broker_name, .. } => broker_name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_message_round_trips_through_json() {
+        let event = EventMessage::ElectMaster {
+            broker_name: "broker-a".to_string(),
+            new_master_broker_id: Some(2),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<EventMessage>(&json).unwrap(), event);
+        assert_eq!(event.broker_name(), "broker-a");
+    }
+}
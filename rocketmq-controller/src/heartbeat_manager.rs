@@ -0,0 +1,220 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use parking_lot::RwLock;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::protocol::header::namesrv::broker_request::BrokerHeartbeatRequestHeader;
+use tracing::info;
+
+const DEFAULT_BROKER_CHANNEL_EXPIRED_TIME: u64 = 1000 * 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BrokerIdentityInfo {
+    pub cluster_name: String,
+    pub broker_name: String,
+    pub broker_id: i64,
+}
+
+impl BrokerIdentityInfo {
+    pub fn new(
+        cluster_name: impl Into<String>,
+        broker_name: impl Into<String>,
+        broker_id: i64,
+    ) -> Self {
+        Self {
+            cluster_name: cluster_name.into(),
+            broker_name: broker_name.into(),
+            broker_id,
+        }
+    }
+}
+
+/// What the controller knows about a replica from its last heartbeat.
+#[derive(Debug, Clone)]
+pub struct BrokerLiveInfo {
+    pub broker_name: String,
+    pub broker_addr: String,
+    pub broker_id: i64,
+    pub heartbeat_timeout_millis: u64,
+    pub last_update_timestamp: u64,
+    pub epoch: i32,
+    pub max_offset: i64,
+    pub confirm_offset: i64,
+    /// Lower values win among otherwise equal candidates.
+    pub election_priority: i32,
+}
+
+impl BrokerLiveInfo {
+    fn is_expired(&self, now: u64) -> bool {
+        self.last_update_timestamp + self.heartbeat_timeout_millis < now
+    }
+}
+
+#[derive(Default)]
+pub struct BrokerHeartbeatManager {
+    broker_live_table: RwLock<HashMap<BrokerIdentityInfo, BrokerLiveInfo>>,
+}
+
+impl BrokerHeartbeatManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Heartbeats without a broker id come from brokers that are not managed by a controller
+    /// and are ignored.
+    pub fn on_broker_heartbeat(&self, header: &BrokerHeartbeatRequestHeader) {
+        let Some(broker_id) = header.broker_id else {
+            return;
+        };
+        let identity = BrokerIdentityInfo::new(
+            header.cluster_name.as_str(),
+            header.broker_name.as_str(),
+            broker_id,
+        );
+        let heartbeat_timeout_millis = header
+            .heartbeat_timeout_mills
+            .filter(|timeout| *timeout > 0)
+            .map_or(DEFAULT_BROKER_CHANNEL_EXPIRED_TIME, |timeout| {
+                timeout as u64
+            });
+        let mut table = self.broker_live_table.write();
+        let previous = table.get(&identity);
+        let live_info = BrokerLiveInfo {
+            broker_name: header.broker_name.clone(),
+            broker_addr: header.broker_addr.clone(),
+            broker_id,
+            heartbeat_timeout_millis,
+            last_update_timestamp: get_current_millis(),
+            epoch: header
+                .epoch
+                .or(previous.map(|info| info.epoch))
+                .unwrap_or(-1),
+            max_offset: header
+                .max_offset
+                .or(previous.map(|info| info.max_offset))
+                .unwrap_or(-1),
+            confirm_offset: header
+                .confirm_offset
+                .or(previous.map(|info| info.confirm_offset))
+                .unwrap_or(-1),
+            election_priority: header
+                .election_priority
+                .or(previous.map(|info| info.election_priority))
+                .unwrap_or(i32::MAX),
+        };
+        if previous.is_none() {
+            info!(
+                "new broker registered to controller, {:?}, address: {}",
+                identity, live_info.broker_addr
+            );
+        }
+        table.insert(identity, live_info);
+    }
+
+    pub fn is_broker_active(&self, cluster_name: &str, broker_name: &str, broker_id: i64) -> bool {
+        let identity = BrokerIdentityInfo::new(cluster_name, broker_name, broker_id);
+        self.broker_live_table
+            .read()
+            .get(&identity)
+            .is_some_and(|info| !info.is_expired(get_current_millis()))
+    }
+
+    pub fn get_broker_live_info(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        broker_id: i64,
+    ) -> Option<BrokerLiveInfo> {
+        let identity = BrokerIdentityInfo::new(cluster_name, broker_name, broker_id);
+        self.broker_live_table.read().get(&identity).cloned()
+    }
+
+    pub fn remove_broker(&self, identity: &BrokerIdentityInfo) {
+        self.broker_live_table.write().remove(identity);
+    }
+
+    /// Removes and returns the brokers whose heartbeat timed out.
+    pub fn scan_not_active_broker(&self) -> Vec<BrokerIdentityInfo> {
+        let now = get_current_millis();
+        let mut inactive = Vec::new();
+        self.broker_live_table.write().retain(|identity, info| {
+            if info.is_expired(now) {
+                info!(
+                    "broker heartbeat expired, {:?}, address: {}, timeout: {}ms",
+                    identity, info.broker_addr, info.heartbeat_timeout_millis
+                );
+                inactive.push(identity.clone());
+                false
+            } else {
+                true
+            }
+        });
+        inactive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat(broker_id: Option<i64>, timeout: Option<i64>) -> BrokerHeartbeatRequestHeader {
+        BrokerHeartbeatRequestHeader::new(
+            "DefaultCluster",
+            "127.0.0.1:10911",
+            "broker-a",
+            broker_id,
+            Some(1),
+            Some(100),
+            None,
+            timeout,
+            None,
+        )
+    }
+
+    #[test]
+    fn heartbeat_keeps_broker_active_until_it_expires() {
+        let manager = BrokerHeartbeatManager::new();
+        manager.on_broker_heartbeat(&heartbeat(None, None));
+        assert!(!manager.is_broker_active("DefaultCluster", "broker-a", 0));
+
+        manager.on_broker_heartbeat(&heartbeat(Some(1), Some(60_000)));
+        assert!(manager.is_broker_active("DefaultCluster", "broker-a", 1));
+        let info = manager
+            .get_broker_live_info("DefaultCluster", "broker-a", 1)
+            .unwrap();
+        assert_eq!(info.max_offset, 100);
+        assert_eq!(info.election_priority, i32::MAX);
+        assert!(manager.scan_not_active_broker().is_empty());
+
+        manager
+            .broker_live_table
+            .write()
+            .values_mut()
+            .for_each(|info| {
+                info.last_update_timestamp -= 120_000;
+            });
+        assert!(!manager.is_broker_active("DefaultCluster", "broker-a", 1));
+        assert_eq!(
+            manager.scan_not_active_broker(),
+            vec![BrokerIdentityInfo::new("DefaultCluster", "broker-a", 1)]
+        );
+        assert!(manager
+            .get_broker_live_info("DefaultCluster", "broker-a", 1)
+            .is_none());
+    }
+}
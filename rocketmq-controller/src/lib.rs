@@ -0,0 +1,30 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The controller keeps the replica metadata of every broker set, hands out broker ids and
+//! elects a new master when the current one stops sending heartbeats.
+
+pub mod bootstrap;
+pub mod controller_manager;
+pub mod controller_result;
+pub mod controller_state_machine;
+pub mod elect_policy;
+pub mod event;
+pub mod heartbeat_manager;
+pub mod processor;
+pub mod raft;
+pub mod replicas_info_manager;
@@ -0,0 +1,355 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
+use rocketmq_remoting::protocol::command_custom_header::FromMap;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetRequestHeader;
+use rocketmq_remoting::protocol::header::controller::apply_broker_id_request_header::ApplyBrokerIdRequestHeader;
+use rocketmq_remoting::protocol::header::controller::clean_controller_broker_data_request_header::CleanControllerBrokerDataRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_next_broker_id_request_header::GetNextBrokerIdRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::broker_request::BrokerHeartbeatRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_remoting::runtime::processor::RequestProcessor;
+use rocketmq_remoting::Result;
+use tracing::debug;
+
+use crate::controller_manager::ControllerManager;
+use crate::raft::transport::decode_raft_envelope;
+
+/// Serves the `Controller*` request codes and the broker heartbeats feeding the election.
+#[derive(Clone)]
+pub struct ControllerRequestProcessor {
+    controller_manager: Arc<ControllerManager>,
+}
+
+impl ControllerRequestProcessor {
+    pub fn new(controller_manager: Arc<ControllerManager>) -> Self {
+        Self { controller_manager }
+    }
+
+    /// Whether the request is served by the controller.
+    pub fn is_controller_request(request_code: i32) -> bool {
+        ControllerRequestCode::value_of(request_code).is_some()
+            || request_code == RequestCode::BrokerHeartbeat.to_i32()
+    }
+
+    pub async fn handle_request(&self, request: &RemotingCommand) -> RemotingCommand {
+        if request.code() == RequestCode::BrokerHeartbeat.to_i32() {
+            return match decode_header::<BrokerHeartbeatRequestHeader>(request) {
+                Ok(header) => {
+                    self.controller_manager.on_broker_heartbeat(&header);
+                    RemotingCommand::create_response_command()
+                }
+                Err(response) => *response,
+            };
+        }
+        let Some(request_code) = ControllerRequestCode::value_of(request.code()) else {
+            return request_code_not_supported(request.code());
+        };
+        debug!("controller process request: {:?}", request_code);
+        self.process(request_code, request)
+            .await
+            .unwrap_or_else(|response| *response)
+    }
+
+    async fn process(
+        &self,
+        request_code: ControllerRequestCode,
+        request: &RemotingCommand,
+    ) -> std::result::Result<RemotingCommand, Box<RemotingCommand>> {
+        let manager = &self.controller_manager;
+        let response = match request_code {
+            ControllerRequestCode::ControllerAlterSyncStateSet => {
+                let header = decode_header::<AlterSyncStateSetRequestHeader>(request)?;
+                let sync_state_set = decode_body::<SyncStateSet>(request)?;
+                manager
+                    .alter_sync_state_set(&header, &sync_state_set)
+                    .await
+                    .into_response_command()
+            }
+            ControllerRequestCode::ControllerElectMaster => {
+                let header = decode_header::<ElectMasterRequestHeader>(request)?;
+                manager.elect_master(&header).await.into_response_command()
+            }
+            ControllerRequestCode::ControllerRegisterBroker => {
+                let header = decode_header::<RegisterBrokerToControllerRequestHeader>(request)?;
+                manager
+                    .register_broker(&header)
+                    .await
+                    .into_response_command()
+            }
+            ControllerRequestCode::ControllerGetReplicaInfo => {
+                let header = decode_header::<GetReplicaInfoRequestHeader>(request)?;
+                manager
+                    .get_replica_info(&header)
+                    .await
+                    .into_response_command()
+            }
+            ControllerRequestCode::ControllerGetMetadataInfo => {
+                RemotingCommand::create_response_command_with_header(
+                    manager.get_controller_metadata(),
+                )
+            }
+            ControllerRequestCode::ControllerGetSyncStateData => {
+                let broker_names = decode_body::<Vec<String>>(request)?;
+                manager
+                    .get_sync_state_data(&broker_names)
+                    .await
+                    .into_body_response_command()
+            }
+            ControllerRequestCode::CleanBrokerData => {
+                let header = decode_header::<CleanControllerBrokerDataRequestHeader>(request)?;
+                manager
+                    .clean_broker_data(&header)
+                    .await
+                    .into_body_response_command()
+            }
+            ControllerRequestCode::ControllerGetNextBrokerId => {
+                let header = decode_header::<GetNextBrokerIdRequestHeader>(request)?;
+                manager
+                    .get_next_broker_id(&header)
+                    .await
+                    .into_response_command()
+            }
+            ControllerRequestCode::ControllerApplyBrokerId => {
+                let header = decode_header::<ApplyBrokerIdRequestHeader>(request)?;
+                manager
+                    .apply_broker_id(&header)
+                    .await
+                    .into_response_command()
+            }
+            ControllerRequestCode::ControllerRaftMessage => {
+                let envelope = decode_raft_envelope(request).ok_or_else(|| {
+                    Box::new(RemotingCommand::create_response_command_with_code_remark(
+                        ResponseCode::ControllerInvalidRequest,
+                        "invalid raft message",
+                    ))
+                })?;
+                manager.on_raft_message(envelope);
+                RemotingCommand::create_response_command()
+            }
+            ControllerRequestCode::GetBrokerEpochCache
+            | ControllerRequestCode::NotifyBrokerRoleChanged
+            | ControllerRequestCode::UpdateControllerConfig
+            | ControllerRequestCode::GetControllerConfig => {
+                request_code_not_supported(request.code())
+            }
+        };
+        Ok(response)
+    }
+}
+
+fn decode_header<T>(request: &RemotingCommand) -> std::result::Result<T, Box<RemotingCommand>>
+where
+    T: FromMap<Target = T>,
+{
+    request.decode_command_custom_header::<T>().ok_or_else(|| {
+        Box::new(RemotingCommand::create_response_command_with_code_remark(
+            ResponseCode::ControllerInvalidRequest,
+            "invalid request header",
+        ))
+    })
+}
+
+fn decode_body<T>(request: &RemotingCommand) -> std::result::Result<T, Box<RemotingCommand>>
+where
+    T: RemotingDeserializable<Output = T>,
+{
+    request
+        .body()
+        .as_ref()
+        .and_then(|body| T::decode(body).ok())
+        .ok_or_else(|| {
+            Box::new(RemotingCommand::create_response_command_with_code_remark(
+                ResponseCode::ControllerInvalidRequest,
+                "invalid request body",
+            ))
+        })
+}
+
+fn request_code_not_supported(request_code: i32) -> RemotingCommand {
+    RemotingCommand::create_response_command_with_code_remark(
+        RemotingSysResponseCode::RequestCodeNotSupported,
+        format!(
+            "request code {} is not supported by the controller",
+            request_code
+        ),
+    )
+}
+
+impl RequestProcessor for ControllerRequestProcessor {
+    async fn process_request(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
+    ) -> Result<Option<RemotingCommand>> {
+        Ok(Some(self.handle_request(&request).await))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::controller::controller_config::ControllerConfig;
+    use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+    use rocketmq_remoting::protocol::command_custom_header::CommandCustomHeader;
+    use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterResponseHeader;
+    use rocketmq_remoting::protocol::header::controller::get_next_broker_id_request_header::GetNextBrokerIdResponseHeader;
+    use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoResponseHeader;
+    use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+
+    use super::*;
+
+    fn request<T>(code: impl Into<i32>, header: T) -> RemotingCommand
+    where
+        T: CommandCustomHeader + Sync + Send + 'static,
+    {
+        let mut request = RemotingCommand::create_request_command(code, header);
+        request.make_custom_header_to_net();
+        request
+    }
+
+    async fn serve_broker_lifecycle(processor: &ControllerRequestProcessor) {
+        let response = processor
+            .handle_request(&request(
+                ControllerRequestCode::ControllerGetNextBrokerId,
+                GetNextBrokerIdRequestHeader {
+                    cluster_name: "DefaultCluster".to_string(),
+                    broker_name: "broker-a".to_string(),
+                },
+            ))
+            .await;
+        let next_broker_id = response
+            .read_custom_header_ref::<GetNextBrokerIdResponseHeader>()
+            .unwrap()
+            .next_broker_id;
+        assert_eq!(next_broker_id, Some(1));
+
+        let response = processor
+            .handle_request(&request(
+                ControllerRequestCode::ControllerApplyBrokerId,
+                ApplyBrokerIdRequestHeader {
+                    cluster_name: "DefaultCluster".to_string(),
+                    broker_name: "broker-a".to_string(),
+                    applied_broker_id: 1,
+                    register_check_code: "127.0.0.1:10911;1".to_string(),
+                },
+            ))
+            .await;
+        assert_eq!(response.code(), i32::from(ResponseCode::Success));
+
+        let response = processor
+            .handle_request(&request(
+                RequestCode::BrokerHeartbeat,
+                BrokerHeartbeatRequestHeader::new(
+                    "DefaultCluster",
+                    "127.0.0.1:10911",
+                    "broker-a",
+                    Some(1),
+                    Some(0),
+                    Some(0),
+                    None,
+                    Some(60_000),
+                    None,
+                ),
+            ))
+            .await;
+        assert_eq!(response.code(), i32::from(ResponseCode::Success));
+
+        let response = processor
+            .handle_request(&request(
+                ControllerRequestCode::ControllerElectMaster,
+                ElectMasterRequestHeader {
+                    cluster_name: "DefaultCluster".to_string(),
+                    broker_name: "broker-a".to_string(),
+                    broker_id: Some(1),
+                    designate_elect: false,
+                    invoke_time: 0,
+                },
+            ))
+            .await;
+        assert_eq!(response.code(), i32::from(ResponseCode::Success));
+        let header = response
+            .read_custom_header_ref::<ElectMasterResponseHeader>()
+            .unwrap();
+        assert_eq!(header.master_broker_id, Some(1));
+        assert_eq!(header.master_epoch, 1);
+
+        let response = processor
+            .handle_request(&request(
+                ControllerRequestCode::ControllerGetReplicaInfo,
+                GetReplicaInfoRequestHeader {
+                    broker_name: "broker-a".to_string(),
+                },
+            ))
+            .await;
+        let header = response
+            .read_custom_header_ref::<GetReplicaInfoResponseHeader>()
+            .unwrap();
+        assert_eq!(header.master_address.as_deref(), Some("127.0.0.1:10911"));
+        let sync_state_set =
+            SyncStateSet::decode(response.body().as_ref().unwrap().as_ref()).unwrap();
+        assert_eq!(sync_state_set.sync_state_set_epoch, 1);
+
+        let response = processor
+            .handle_request(&request(
+                ControllerRequestCode::GetControllerConfig,
+                GetReplicaInfoRequestHeader::default(),
+            ))
+            .await;
+        assert_eq!(
+            response.code(),
+            i32::from(RemotingSysResponseCode::RequestCodeNotSupported)
+        );
+    }
+
+    #[test]
+    fn serves_broker_id_assignment_and_election() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let remoting_client = RocketmqDefaultClient::new(
+                Arc::new(TokioClientConfig::default()),
+                DefaultRemotingRequestProcessor,
+            );
+            let processor = ControllerRequestProcessor::new(Arc::new(ControllerManager::new(
+                Arc::new(ControllerConfig::default()),
+                Arc::new(remoting_client),
+            )));
+            serve_broker_lifecycle(&processor).await;
+            // The remoting client owns a runtime, which can not be dropped on an async thread
+            tokio::task::spawn_blocking(move || drop(processor))
+                .await
+                .unwrap();
+        });
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A small Raft implementation replicating the metadata of the controllers.
+//!
+//! [`core::RaftCore`] is a deterministic state machine: it is driven by ticks, incoming
+//! messages and proposals, and leaves the messages to send in an outbox. [`node::RaftNode`]
+//! drives it on tokio, persists it with [`storage::RaftStorage`] and ships the messages through
+//! a [`transport::RaftTransport`]. [`local_cluster::LocalCluster`] runs several cores in
+//! process, delivering their messages by hand.
+
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+pub mod core;
+pub mod local_cluster;
+pub mod log;
+pub mod message;
+pub mod node;
+pub mod storage;
+pub mod transport;
+
+/// Id of a member of a Raft group, such as `n0`.
+pub type NodeId = String;
+
+/// The replicated state, fed with the committed entries of the log in order.
+pub trait StateMachine: Send + 'static {
+    fn apply(&mut self, index: u64, data: &[u8]);
+
+    fn snapshot(&self) -> Vec<u8>;
+
+    /// Replaces the whole state with a snapshot taken by [`Self::snapshot`].
+    fn restore(&mut self, data: &[u8]);
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum RaftError {
+    #[error("not the raft leader, leader: {0:?}")]
+    NotLeader(Option<NodeId>),
+
+    #[error("proposal was overwritten by another leader before being committed")]
+    ProposalDropped,
+
+    #[error("the raft state could not be saved, the member stopped")]
+    StorageFailed,
+}
+
+/// Parses members given as `id-address` pairs separated by `;`.
+pub fn parse_peers(peers: &str) -> BTreeMap<NodeId, String> {
+    peers
+        .split(';')
+        .map(str::trim)
+        .filter_map(|peer| peer.split_once('-'))
+        .map(|(id, address)| (id.trim().to_string(), address.trim().to_string()))
+        .filter(|(id, address)| !id.is_empty() && !address.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_peers_skips_malformed_members() {
+        let peers = parse_peers("n0-127.0.0.1:9878; n1-127.0.0.1:9868;;n2;-127.0.0.1:1");
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers["n0"], "127.0.0.1:9878");
+        assert_eq!(peers["n1"], "127.0.0.1:9868");
+    }
+}
@@ -0,0 +1,673 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::info;
+
+use crate::raft::log::RaftLog;
+use crate::raft::message::LogEntry;
+use crate::raft::message::RaftEnvelope;
+use crate::raft::message::RaftMessage;
+use crate::raft::message::Snapshot;
+use crate::raft::NodeId;
+use crate::raft::RaftError;
+use crate::raft::StateMachine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaftRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+/// Timing and sizing of a Raft member, in ticks of the driver.
+#[derive(Debug, Clone)]
+pub struct RaftOptions {
+    /// The election timeout is chosen at random between one and two times this.
+    pub election_ticks: u64,
+    pub heartbeat_ticks: u64,
+    pub snapshot_threshold: u64,
+    pub max_entries_per_message: usize,
+    /// Seeds the randomized election timeouts.
+    pub random_seed: u64,
+}
+
+impl Default for RaftOptions {
+    fn default() -> Self {
+        Self {
+            election_ticks: 10,
+            heartbeat_ticks: 1,
+            snapshot_threshold: 1000,
+            max_entries_per_message: 64,
+            random_seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+}
+
+/// What a member must find again after a restart.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistentState {
+    pub current_term: u64,
+    pub voted_for: Option<NodeId>,
+    pub log: RaftLog,
+}
+
+/// A member of a Raft group, without any I/O.
+///
+/// Messages to send pile up in an outbox drained with [`Self::take_messages`]; the persistent
+/// state must be saved with [`Self::take_persistent_state`] before sending them.
+pub struct RaftCore<S> {
+    group: String,
+    id: NodeId,
+    peers: Vec<NodeId>,
+    options: RaftOptions,
+    role: RaftRole,
+    current_term: u64,
+    voted_for: Option<NodeId>,
+    leader_id: Option<NodeId>,
+    log: RaftLog,
+    commit_index: u64,
+    last_applied: u64,
+    /// Index of the entry the leader appended on election, it serves once it is applied.
+    leader_ready_index: u64,
+    election_elapsed: u64,
+    election_timeout: u64,
+    heartbeat_elapsed: u64,
+    votes: HashSet<NodeId>,
+    /// Peers heard from during the current election timeout, while leading.
+    recent_active: HashSet<NodeId>,
+    next_index: BTreeMap<NodeId, u64>,
+    match_index: BTreeMap<NodeId, u64>,
+    state_machine: S,
+    random_state: u64,
+    outbox: Vec<RaftEnvelope>,
+    applied: Vec<(u64, u64)>,
+    persistent_state_changed: bool,
+}
+
+impl<S: StateMachine> RaftCore<S> {
+    /// Creates a member of the group formed by `members`, which includes `id`, resuming from
+    /// its persistent state after a restart.
+    pub fn new(
+        group: impl Into<String>,
+        id: impl Into<NodeId>,
+        members: impl IntoIterator<Item = NodeId>,
+        options: RaftOptions,
+        mut state_machine: S,
+        persistent_state: Option<PersistentState>,
+    ) -> Self {
+        let id = id.into();
+        let mut peers = members
+            .into_iter()
+            .filter(|member| *member != id)
+            .collect::<Vec<_>>();
+        peers.sort();
+        peers.dedup();
+        let persistent_state = persistent_state.unwrap_or_default();
+        let snapshot = persistent_state.log.snapshot();
+        if snapshot.last_included_index > 0 {
+            state_machine.restore(&snapshot.data);
+        }
+        let applied_index = snapshot.last_included_index;
+        let random_state = options
+            .random_seed
+            .wrapping_add(id.bytes().fold(0u64, |hash, byte| {
+                hash.wrapping_mul(31).wrapping_add(byte as u64)
+            }));
+        let mut core = Self {
+            group: group.into(),
+            id,
+            peers,
+            options,
+            role: RaftRole::Follower,
+            current_term: persistent_state.current_term,
+            voted_for: persistent_state.voted_for,
+            leader_id: None,
+            log: persistent_state.log,
+            commit_index: applied_index,
+            last_applied: applied_index,
+            leader_ready_index: u64::MAX,
+            election_elapsed: 0,
+            election_timeout: 0,
+            heartbeat_elapsed: 0,
+            votes: HashSet::new(),
+            recent_active: HashSet::new(),
+            next_index: BTreeMap::new(),
+            match_index: BTreeMap::new(),
+            state_machine,
+            random_state,
+            outbox: Vec::new(),
+            applied: Vec::new(),
+            persistent_state_changed: false,
+        };
+        core.reset_election_timeout();
+        core
+    }
+
+    pub fn id(&self) -> &NodeId {
+        &self.id
+    }
+
+    pub fn role(&self) -> RaftRole {
+        self.role
+    }
+
+    pub fn current_term(&self) -> u64 {
+        self.current_term
+    }
+
+    pub fn leader_id(&self) -> Option<&NodeId> {
+        self.leader_id.as_ref()
+    }
+
+    pub fn commit_index(&self) -> u64 {
+        self.commit_index
+    }
+
+    pub fn last_applied(&self) -> u64 {
+        self.last_applied
+    }
+
+    pub fn log(&self) -> &RaftLog {
+        &self.log
+    }
+
+    pub fn state_machine(&self) -> &S {
+        &self.state_machine
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.role == RaftRole::Leader
+    }
+
+    /// Whether this member leads and applied every entry committed by previous leaders, so
+    /// its state machine is up to date.
+    pub fn is_leader_ready(&self) -> bool {
+        self.is_leader() && self.last_applied >= self.leader_ready_index
+    }
+
+    fn quorum(&self) -> usize {
+        let members = self.peers.len() + 1;
+        members / 2 + 1
+    }
+
+    fn next_random(&mut self) -> u64 {
+        // splitmix64, members with close seeds still get unrelated timeouts
+        self.random_state = self.random_state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.random_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn reset_election_timeout(&mut self) {
+        let election_ticks = self.options.election_ticks.max(1);
+        self.election_elapsed = 0;
+        self.election_timeout = election_ticks + self.next_random() % election_ticks;
+    }
+
+    pub fn tick(&mut self) {
+        if self.is_leader() {
+            self.election_elapsed += 1;
+            if self.election_elapsed >= self.options.election_ticks.max(1) {
+                self.election_elapsed = 0;
+                // Step down when a majority is out of reach, so clients find the new leader
+                if self.recent_active.len() + 1 < self.quorum() {
+                    info!(
+                        "raft {} {} lost contact with a majority, stepping down",
+                        self.group, self.id
+                    );
+                    self.become_follower(self.current_term, None);
+                    return;
+                }
+                self.recent_active.clear();
+            }
+            self.heartbeat_elapsed += 1;
+            if self.heartbeat_elapsed >= self.options.heartbeat_ticks.max(1) {
+                self.heartbeat_elapsed = 0;
+                self.broadcast_append();
+            }
+            return;
+        }
+        self.election_elapsed += 1;
+        if self.election_elapsed >= self.election_timeout {
+            self.campaign();
+        }
+    }
+
+    /// Appends `data` to the log of the leader, returning the index and term of the entry.
+    pub fn propose(&mut self, data: Vec<u8>) -> Result<(u64, u64), RaftError> {
+        if !self.is_leader() {
+            return Err(RaftError::NotLeader(self.leader_id.clone()));
+        }
+        let index = self.append_entry(data);
+        self.broadcast_append();
+        self.maybe_commit();
+        Ok((index, self.current_term))
+    }
+
+    pub fn step(&mut self, envelope: RaftEnvelope) {
+        if envelope.group != self.group || envelope.to != self.id {
+            return;
+        }
+        let from = envelope.from;
+        let message = envelope.message;
+        let term = message.term();
+        if term > self.current_term {
+            let leader_id = match message {
+                RaftMessage::AppendEntries { .. } | RaftMessage::InstallSnapshot { .. } => {
+                    Some(from.clone())
+                }
+                _ => None,
+            };
+            self.become_follower(term, leader_id);
+        }
+        if term < self.current_term {
+            // Tell a stale leader or candidate about the newer term
+            match message {
+                RaftMessage::RequestVote { .. } => self.send(
+                    from,
+                    RaftMessage::RequestVoteResponse {
+                        term: self.current_term,
+                        vote_granted: false,
+                    },
+                ),
+                RaftMessage::AppendEntries { .. } | RaftMessage::InstallSnapshot { .. } => self
+                    .send(
+                        from,
+                        RaftMessage::AppendEntriesResponse {
+                            term: self.current_term,
+                            success: false,
+                            match_index: 0,
+                        },
+                    ),
+                _ => {}
+            }
+            return;
+        }
+        match message {
+            RaftMessage::RequestVote {
+                last_log_index,
+                last_log_term,
+                ..
+            } => self.handle_request_vote(from, last_log_index, last_log_term),
+            RaftMessage::RequestVoteResponse { vote_granted, .. } => {
+                self.handle_request_vote_response(from, vote_granted)
+            }
+            RaftMessage::AppendEntries {
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit,
+                ..
+            } => self.handle_append_entries(
+                from,
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit,
+            ),
+            RaftMessage::AppendEntriesResponse {
+                success,
+                match_index,
+                ..
+            } => self.handle_append_entries_response(from, success, match_index),
+            RaftMessage::InstallSnapshot { snapshot, .. } => {
+                self.handle_install_snapshot(from, snapshot)
+            }
+            RaftMessage::InstallSnapshotResponse {
+                last_included_index,
+                ..
+            } => self.handle_append_entries_response(from, true, last_included_index),
+        }
+    }
+
+    /// Messages to send, in order.
+    pub fn take_messages(&mut self) -> Vec<RaftEnvelope> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Index and term of the entries applied since the last call.
+    pub fn take_applied(&mut self) -> Vec<(u64, u64)> {
+        std::mem::take(&mut self.applied)
+    }
+
+    /// The persistent state, if it changed since the last call.
+    pub fn take_persistent_state(&mut self) -> Option<PersistentState> {
+        if !std::mem::take(&mut self.persistent_state_changed) {
+            return None;
+        }
+        Some(PersistentState {
+            current_term: self.current_term,
+            voted_for: self.voted_for.clone(),
+            log: self.log.clone(),
+        })
+    }
+
+    fn send(&mut self, to: NodeId, message: RaftMessage) {
+        self.outbox.push(RaftEnvelope {
+            group: self.group.clone(),
+            from: self.id.clone(),
+            to,
+            message,
+        });
+    }
+
+    fn become_follower(&mut self, term: u64, leader_id: Option<NodeId>) {
+        if term != self.current_term {
+            self.current_term = term;
+            self.voted_for = None;
+            self.persistent_state_changed = true;
+        }
+        self.role = RaftRole::Follower;
+        self.leader_id = leader_id;
+        self.leader_ready_index = u64::MAX;
+        self.reset_election_timeout();
+    }
+
+    fn campaign(&mut self) {
+        self.role = RaftRole::Candidate;
+        self.current_term += 1;
+        self.voted_for = Some(self.id.clone());
+        self.leader_id = None;
+        self.persistent_state_changed = true;
+        self.votes = HashSet::from([self.id.clone()]);
+        self.reset_election_timeout();
+        info!(
+            "raft {} {} starts an election for term {}",
+            self.group, self.id, self.current_term
+        );
+        if self.votes.len() >= self.quorum() {
+            self.become_leader();
+            return;
+        }
+        let (last_log_index, last_log_term) = (self.log.last_index(), self.log.last_term());
+        for peer in self.peers.clone() {
+            self.send(
+                peer,
+                RaftMessage::RequestVote {
+                    term: self.current_term,
+                    last_log_index,
+                    last_log_term,
+                },
+            );
+        }
+    }
+
+    fn become_leader(&mut self) {
+        info!(
+            "raft {} {} becomes leader of term {}",
+            self.group, self.id, self.current_term
+        );
+        self.role = RaftRole::Leader;
+        self.leader_id = Some(self.id.clone());
+        self.heartbeat_elapsed = 0;
+        self.election_elapsed = 0;
+        self.recent_active.clear();
+        let next_index = self.log.last_index() + 1;
+        for peer in &self.peers {
+            self.next_index.insert(peer.clone(), next_index);
+            self.match_index.insert(peer.clone(), 0);
+        }
+        // Entries of previous terms only commit along with an entry of the current term
+        self.leader_ready_index = self.append_entry(Vec::new());
+        self.broadcast_append();
+        self.maybe_commit();
+    }
+
+    fn append_entry(&mut self, data: Vec<u8>) -> u64 {
+        let index = self.log.last_index() + 1;
+        self.log.append(LogEntry {
+            index,
+            term: self.current_term,
+            data,
+        });
+        self.persistent_state_changed = true;
+        index
+    }
+
+    fn handle_request_vote(&mut self, candidate: NodeId, last_log_index: u64, last_log_term: u64) {
+        let log_up_to_date = last_log_term > self.log.last_term()
+            || (last_log_term == self.log.last_term() && last_log_index >= self.log.last_index());
+        let can_vote = self.voted_for.is_none() || self.voted_for.as_ref() == Some(&candidate);
+        let vote_granted = can_vote && log_up_to_date && !self.is_leader();
+        if vote_granted {
+            self.voted_for = Some(candidate.clone());
+            self.persistent_state_changed = true;
+            self.reset_election_timeout();
+        }
+        self.send(
+            candidate,
+            RaftMessage::RequestVoteResponse {
+                term: self.current_term,
+                vote_granted,
+            },
+        );
+    }
+
+    fn handle_request_vote_response(&mut self, from: NodeId, vote_granted: bool) {
+        if self.role != RaftRole::Candidate || !vote_granted {
+            return;
+        }
+        self.votes.insert(from);
+        if self.votes.len() >= self.quorum() {
+            self.become_leader();
+        }
+    }
+
+    fn handle_append_entries(
+        &mut self,
+        leader: NodeId,
+        prev_log_index: u64,
+        prev_log_term: u64,
+        entries: Vec<LogEntry>,
+        leader_commit: u64,
+    ) {
+        if self.role != RaftRole::Follower || self.leader_id.as_ref() != Some(&leader) {
+            self.become_follower(self.current_term, Some(leader.clone()));
+        } else {
+            self.election_elapsed = 0;
+        }
+        // Entries up to the snapshot are committed, so they match the leader
+        let matches = prev_log_index < self.log.snapshot().last_included_index
+            || self.log.term_at(prev_log_index) == Some(prev_log_term);
+        if !matches {
+            let hint = prev_log_index
+                .saturating_sub(1)
+                .min(self.log.last_index())
+                .max(self.commit_index);
+            self.send(
+                leader,
+                RaftMessage::AppendEntriesResponse {
+                    term: self.current_term,
+                    success: false,
+                    match_index: hint,
+                },
+            );
+            return;
+        }
+        let mut match_index = prev_log_index;
+        for entry in entries {
+            match_index = entry.index;
+            if entry.index < self.log.first_index() {
+                continue;
+            }
+            match self.log.term_at(entry.index) {
+                Some(term) if term == entry.term => continue,
+                Some(_) => self.log.truncate_from(entry.index),
+                None => {}
+            }
+            self.log.append(entry);
+            self.persistent_state_changed = true;
+        }
+        let match_index = match_index.max(self.log.snapshot().last_included_index);
+        let commit_index = leader_commit.min(match_index);
+        if commit_index > self.commit_index {
+            self.commit_index = commit_index;
+            self.apply_committed();
+        }
+        self.send(
+            leader,
+            RaftMessage::AppendEntriesResponse {
+                term: self.current_term,
+                success: true,
+                match_index,
+            },
+        );
+    }
+
+    fn handle_append_entries_response(&mut self, from: NodeId, success: bool, match_index: u64) {
+        if !self.is_leader() || !self.next_index.contains_key(&from) {
+            return;
+        }
+        self.recent_active.insert(from.clone());
+        if success {
+            let matched = self.match_index.entry(from.clone()).or_default();
+            *matched = (*matched).max(match_index);
+            let next_index = *matched + 1;
+            self.next_index.insert(from.clone(), next_index);
+            self.maybe_commit();
+            if next_index <= self.log.last_index() {
+                self.send_append(from);
+            }
+        } else {
+            let next_index = self.next_index.get(&from).copied().unwrap_or(1);
+            let next_index = next_index.saturating_sub(1).min(match_index + 1).max(1);
+            self.next_index.insert(from.clone(), next_index);
+            self.send_append(from);
+        }
+    }
+
+    fn handle_install_snapshot(&mut self, leader: NodeId, snapshot: Snapshot) {
+        if self.role != RaftRole::Follower || self.leader_id.as_ref() != Some(&leader) {
+            self.become_follower(self.current_term, Some(leader.clone()));
+        } else {
+            self.election_elapsed = 0;
+        }
+        let last_included_index = snapshot.last_included_index;
+        if last_included_index > self.commit_index {
+            info!(
+                "raft {} {} installs snapshot at index {} from {}",
+                self.group, self.id, last_included_index, leader
+            );
+            self.state_machine.restore(&snapshot.data);
+            self.log.compact(snapshot);
+            self.commit_index = last_included_index;
+            self.last_applied = last_included_index;
+            self.persistent_state_changed = true;
+        }
+        self.send(
+            leader,
+            RaftMessage::InstallSnapshotResponse {
+                term: self.current_term,
+                last_included_index: self.commit_index,
+            },
+        );
+    }
+
+    fn broadcast_append(&mut self) {
+        for peer in self.peers.clone() {
+            self.send_append(peer);
+        }
+    }
+
+    fn send_append(&mut self, peer: NodeId) {
+        let next_index = self
+            .next_index
+            .get(&peer)
+            .copied()
+            .unwrap_or(self.log.last_index() + 1);
+        if next_index < self.log.first_index() {
+            let snapshot = self.log.snapshot().clone();
+            self.send(
+                peer,
+                RaftMessage::InstallSnapshot {
+                    term: self.current_term,
+                    snapshot,
+                },
+            );
+            return;
+        }
+        let prev_log_index = next_index - 1;
+        let message = RaftMessage::AppendEntries {
+            term: self.current_term,
+            prev_log_index,
+            prev_log_term: self.log.term_at(prev_log_index).unwrap_or_default(),
+            entries: self
+                .log
+                .entries_from(next_index, self.options.max_entries_per_message),
+            leader_commit: self.commit_index,
+        };
+        self.send(peer, message);
+    }
+
+    /// Commits the latest entry of the current term a majority has.
+    fn maybe_commit(&mut self) {
+        let mut index = self.log.last_index();
+        while index > self.commit_index {
+            if self.log.term_at(index) != Some(self.current_term) {
+                break;
+            }
+            let replicas = 1 + self
+                .match_index
+                .values()
+                .filter(|match_index| **match_index >= index)
+                .count();
+            if replicas >= self.quorum() {
+                self.commit_index = index;
+                self.apply_committed();
+                return;
+            }
+            index -= 1;
+        }
+    }
+
+    fn apply_committed(&mut self) {
+        while self.last_applied < self.commit_index {
+            let index = self.last_applied + 1;
+            let Some(entry) = self.log.entry(index) else {
+                break;
+            };
+            if !entry.data.is_empty() {
+                self.state_machine.apply(index, &entry.data);
+            }
+            self.applied.push((index, entry.term));
+            self.last_applied = index;
+        }
+        self.maybe_snapshot();
+    }
+
+    fn maybe_snapshot(&mut self) {
+        let snapshot_index = self.log.snapshot().last_included_index;
+        if self.last_applied - snapshot_index < self.options.snapshot_threshold.max(1) {
+            return;
+        }
+        let Some(last_included_term) = self.log.term_at(self.last_applied) else {
+            return;
+        };
+        self.log.compact(Snapshot {
+            last_included_index: self.last_applied,
+            last_included_term,
+            data: self.state_machine.snapshot(),
+        });
+        self.persistent_state_changed = true;
+    }
+}
@@ -0,0 +1,394 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::raft::core::PersistentState;
+use crate::raft::core::RaftCore;
+use crate::raft::core::RaftOptions;
+use crate::raft::message::RaftEnvelope;
+use crate::raft::transport::RaftTransport;
+use crate::raft::NodeId;
+use crate::raft::RaftError;
+use crate::raft::StateMachine;
+
+/// Runs the members of a Raft group in process and delivers their messages by hand, so tests
+/// control time, crashes and network partitions.
+pub struct LocalCluster<S> {
+    group: String,
+    members: Vec<NodeId>,
+    options: RaftOptions,
+    new_state_machine: Box<dyn Fn() -> S>,
+    nodes: BTreeMap<NodeId, RaftCore<S>>,
+    persistent_states: BTreeMap<NodeId, PersistentState>,
+    in_flight: VecDeque<RaftEnvelope>,
+    isolated: HashSet<NodeId>,
+}
+
+impl<S: StateMachine> LocalCluster<S> {
+    /// Starts a group of `size` members named `n0`, `n1`...
+    pub fn new(
+        size: usize,
+        options: RaftOptions,
+        new_state_machine: impl Fn() -> S + 'static,
+    ) -> Self {
+        let members = (0..size)
+            .map(|index| format!("n{}", index))
+            .collect::<Vec<_>>();
+        let mut cluster = Self {
+            group: "LocalCluster".to_string(),
+            members: members.clone(),
+            options,
+            new_state_machine: Box::new(new_state_machine),
+            nodes: BTreeMap::new(),
+            persistent_states: BTreeMap::new(),
+            in_flight: VecDeque::new(),
+            isolated: HashSet::new(),
+        };
+        for id in members {
+            cluster.restart(&id);
+        }
+        cluster
+    }
+
+    pub fn members(&self) -> &[NodeId] {
+        &self.members
+    }
+
+    /// The running member, `None` once stopped.
+    pub fn node(&self, id: &str) -> Option<&RaftCore<S>> {
+        self.nodes.get(id)
+    }
+
+    /// The leader of the highest term among the running members that are not isolated.
+    pub fn leader(&self) -> Option<NodeId> {
+        self.nodes
+            .values()
+            .filter(|node| node.is_leader() && !self.isolated.contains(node.id()))
+            .max_by_key(|node| node.current_term())
+            .map(|node| node.id().clone())
+    }
+
+    /// Ticks every running member once, then delivers the messages until none is left.
+    pub fn tick(&mut self) {
+        for node in self.nodes.values_mut() {
+            node.tick();
+        }
+        self.deliver();
+    }
+
+    /// Ticks until a member leads and applied the entries of the previous leaders.
+    pub fn tick_until_leader(&mut self, max_ticks: usize) -> Option<NodeId> {
+        for _ in 0..max_ticks {
+            self.tick();
+            if let Some(leader) = self.leader() {
+                if self.nodes[&leader].is_leader_ready() {
+                    return Some(leader);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn propose(&mut self, id: &str, data: Vec<u8>) -> Result<(u64, u64), RaftError> {
+        let result = self
+            .nodes
+            .get_mut(id)
+            .ok_or(RaftError::NotLeader(None))?
+            .propose(data);
+        self.deliver();
+        result
+    }
+
+    pub fn deliver(&mut self) {
+        self.collect();
+        while let Some(envelope) = self.in_flight.pop_front() {
+            if self.isolated.contains(&envelope.from) || self.isolated.contains(&envelope.to) {
+                continue;
+            }
+            let Some(node) = self.nodes.get_mut(&envelope.to) else {
+                continue;
+            };
+            node.step(envelope);
+            self.collect();
+        }
+    }
+
+    fn collect(&mut self) {
+        for (id, node) in self.nodes.iter_mut() {
+            if let Some(state) = node.take_persistent_state() {
+                self.persistent_states.insert(id.clone(), state);
+            }
+            self.in_flight.extend(node.take_messages());
+            node.take_applied();
+        }
+    }
+
+    /// Drops every message from and to the member until [`Self::heal`].
+    pub fn isolate(&mut self, id: &str) {
+        self.isolated.insert(id.to_string());
+    }
+
+    pub fn heal(&mut self) {
+        self.isolated.clear();
+    }
+
+    /// Crashes the member, keeping only what it persisted.
+    pub fn stop(&mut self, id: &str) {
+        self.nodes.remove(id);
+    }
+
+    pub fn restart(&mut self, id: &str) {
+        let options = RaftOptions {
+            random_seed: self
+                .options
+                .random_seed
+                .wrapping_add(self.nodes.len() as u64),
+            ..self.options.clone()
+        };
+        let node = RaftCore::new(
+            self.group.clone(),
+            id,
+            self.members.clone(),
+            options,
+            (self.new_state_machine)(),
+            self.persistent_states.get(id).cloned(),
+        );
+        self.nodes.insert(id.to_string(), node);
+    }
+}
+
+type Endpoint = Arc<dyn Fn(RaftEnvelope) + Send + Sync>;
+
+/// A [`RaftTransport`] delivering messages to members running in the same process.
+#[derive(Default)]
+pub struct LocalRouter {
+    endpoints: RwLock<HashMap<NodeId, Endpoint>>,
+    isolated: RwLock<HashSet<NodeId>>,
+}
+
+impl LocalRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delivers the messages to `id` by calling `endpoint`, typically `RaftNode::step`.
+    pub fn register(
+        &self,
+        id: impl Into<NodeId>,
+        endpoint: impl Fn(RaftEnvelope) + Send + Sync + 'static,
+    ) {
+        self.endpoints.write().insert(id.into(), Arc::new(endpoint));
+    }
+
+    pub fn isolate(&self, id: impl Into<NodeId>) {
+        self.isolated.write().insert(id.into());
+    }
+
+    pub fn heal(&self) {
+        self.isolated.write().clear();
+    }
+}
+
+impl RaftTransport for LocalRouter {
+    fn send(&self, envelope: RaftEnvelope) {
+        {
+            let isolated = self.isolated.read();
+            if isolated.contains(&envelope.from) || isolated.contains(&envelope.to) {
+                return;
+            }
+        }
+        let Some(endpoint) = self.endpoints.read().get(&envelope.to).cloned() else {
+            return;
+        };
+        // Never step the receiver on the stack of the sender, which holds its own lock
+        tokio::spawn(async move { endpoint(envelope) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raft::core::RaftRole;
+
+    #[derive(Default)]
+    struct RecordingStateMachine {
+        values: Vec<String>,
+    }
+
+    impl StateMachine for RecordingStateMachine {
+        fn apply(&mut self, _index: u64, data: &[u8]) {
+            self.values.push(String::from_utf8_lossy(data).into_owned());
+        }
+
+        fn snapshot(&self) -> Vec<u8> {
+            serde_json::to_vec(&self.values).unwrap()
+        }
+
+        fn restore(&mut self, data: &[u8]) {
+            self.values = serde_json::from_slice(data).unwrap();
+        }
+    }
+
+    fn cluster(size: usize, snapshot_threshold: u64) -> LocalCluster<RecordingStateMachine> {
+        LocalCluster::new(
+            size,
+            RaftOptions {
+                snapshot_threshold,
+                ..RaftOptions::default()
+            },
+            RecordingStateMachine::default,
+        )
+    }
+
+    fn values(cluster: &LocalCluster<RecordingStateMachine>, id: &str) -> Vec<String> {
+        cluster.node(id).unwrap().state_machine().values.clone()
+    }
+
+    fn propose_all(
+        cluster: &mut LocalCluster<RecordingStateMachine>,
+        leader: &str,
+        values: &[&str],
+    ) {
+        for value in values {
+            cluster.propose(leader, value.as_bytes().to_vec()).unwrap();
+        }
+        // Followers learn the commit index with the next heartbeat
+        cluster.tick();
+    }
+
+    #[test]
+    fn single_member_commits_alone() {
+        let mut cluster = cluster(1, 1000);
+        let leader = cluster.tick_until_leader(100).unwrap();
+        propose_all(&mut cluster, &leader, &["a", "b"]);
+        assert_eq!(values(&cluster, &leader), ["a", "b"]);
+    }
+
+    #[test]
+    fn elects_one_leader_and_replicates_to_every_member() {
+        let mut cluster = cluster(3, 1000);
+        let leader = cluster.tick_until_leader(100).unwrap();
+        let leaders = cluster
+            .members()
+            .iter()
+            .filter(|id| cluster.node(id).unwrap().role() == RaftRole::Leader)
+            .count();
+        assert_eq!(leaders, 1);
+
+        propose_all(&mut cluster, &leader, &["a", "b", "c"]);
+        for id in cluster.members().to_vec() {
+            assert_eq!(values(&cluster, &id), ["a", "b", "c"]);
+            assert_eq!(cluster.node(&id).unwrap().leader_id(), Some(&leader));
+        }
+        let follower = cluster
+            .members()
+            .iter()
+            .find(|id| **id != leader)
+            .unwrap()
+            .clone();
+        assert_eq!(
+            cluster.propose(&follower, b"d".to_vec()),
+            Err(RaftError::NotLeader(Some(leader)))
+        );
+    }
+
+    #[test]
+    fn isolated_leader_is_replaced_and_its_uncommitted_entries_dropped() {
+        let mut cluster = cluster(3, 1000);
+        let old_leader = cluster.tick_until_leader(100).unwrap();
+        propose_all(&mut cluster, &old_leader, &["a"]);
+        let old_term = cluster.node(&old_leader).unwrap().current_term();
+
+        cluster.isolate(&old_leader);
+        // Accepted by the old leader, but it cannot reach a majority any more
+        cluster.propose(&old_leader, b"lost".to_vec()).unwrap();
+        let new_leader = cluster.tick_until_leader(100).unwrap();
+        assert_ne!(new_leader, old_leader);
+        assert!(cluster.node(&new_leader).unwrap().current_term() > old_term);
+        propose_all(&mut cluster, &new_leader, &["b"]);
+
+        cluster.heal();
+        for _ in 0..30 {
+            cluster.tick();
+        }
+        assert_eq!(cluster.leader(), Some(new_leader.clone()));
+        for id in cluster.members().to_vec() {
+            assert_eq!(values(&cluster, &id), ["a", "b"]);
+        }
+    }
+
+    #[test]
+    fn lagging_member_catches_up_from_a_snapshot() {
+        let mut cluster = cluster(3, 5);
+        let leader = cluster.tick_until_leader(100).unwrap();
+        let lagging = cluster
+            .members()
+            .iter()
+            .find(|id| **id != leader)
+            .unwrap()
+            .clone();
+        cluster.stop(&lagging);
+
+        let proposed = (0..20).map(|value| value.to_string()).collect::<Vec<_>>();
+        let proposed = proposed.iter().map(String::as_str).collect::<Vec<_>>();
+        propose_all(&mut cluster, &leader, &proposed);
+        assert!(cluster.node(&leader).unwrap().log().first_index() > 1);
+
+        cluster.restart(&lagging);
+        for _ in 0..5 {
+            cluster.tick();
+        }
+        assert_eq!(values(&cluster, &lagging), proposed);
+        assert!(
+            cluster
+                .node(&lagging)
+                .unwrap()
+                .log()
+                .snapshot()
+                .last_included_index
+                > 0
+        );
+    }
+
+    #[test]
+    fn restarted_group_recovers_its_state() {
+        let mut cluster = cluster(3, 4);
+        let leader = cluster.tick_until_leader(100).unwrap();
+        propose_all(&mut cluster, &leader, &["a", "b", "c", "d", "e", "f"]);
+        for id in cluster.members().to_vec() {
+            cluster.stop(&id);
+        }
+        for id in cluster.members().to_vec() {
+            cluster.restart(&id);
+        }
+        let leader = cluster.tick_until_leader(100).unwrap();
+        cluster.tick();
+        for id in cluster.members().to_vec() {
+            assert_eq!(values(&cluster, &id), ["a", "b", "c", "d", "e", "f"]);
+        }
+        assert_eq!(
+            cluster.node(&leader).unwrap().commit_index(),
+            cluster.node(&leader).unwrap().log().last_index()
+        );
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::raft::message::LogEntry;
+use crate::raft::message::Snapshot;
+
+/// The entries of the log following its latest snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaftLog {
+    snapshot: Snapshot,
+    entries: Vec<LogEntry>,
+}
+
+impl RaftLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    pub fn first_index(&self) -> u64 {
+        self.snapshot.last_included_index + 1
+    }
+
+    pub fn last_index(&self) -> u64 {
+        self.entries
+            .last()
+            .map_or(self.snapshot.last_included_index, |entry| entry.index)
+    }
+
+    pub fn last_term(&self) -> u64 {
+        self.entries
+            .last()
+            .map_or(self.snapshot.last_included_term, |entry| entry.term)
+    }
+
+    /// Term of the entry at `index`, `None` when it is compacted or not in the log yet.
+    pub fn term_at(&self, index: u64) -> Option<u64> {
+        if index == self.snapshot.last_included_index {
+            return Some(self.snapshot.last_included_term);
+        }
+        self.entry(index).map(|entry| entry.term)
+    }
+
+    pub fn entry(&self, index: u64) -> Option<&LogEntry> {
+        if index < self.first_index() {
+            return None;
+        }
+        self.entries.get((index - self.first_index()) as usize)
+    }
+
+    /// At most `max_entries` entries starting at `index`.
+    pub fn entries_from(&self, index: u64, max_entries: usize) -> Vec<LogEntry> {
+        if index < self.first_index() {
+            return Vec::new();
+        }
+        self.entries
+            .iter()
+            .skip((index - self.first_index()) as usize)
+            .take(max_entries)
+            .cloned()
+            .collect()
+    }
+
+    pub fn append(&mut self, entry: LogEntry) {
+        debug_assert_eq!(entry.index, self.last_index() + 1);
+        self.entries.push(entry);
+    }
+
+    /// Removes the entry at `index` and all the entries following it.
+    pub fn truncate_from(&mut self, index: u64) {
+        if index < self.first_index() {
+            self.entries.clear();
+            return;
+        }
+        self.entries.truncate((index - self.first_index()) as usize);
+    }
+
+    /// Replaces the entries up to the snapshot with it, keeping the entries following it.
+    pub fn compact(&mut self, snapshot: Snapshot) {
+        let keeps_following_entries =
+            self.term_at(snapshot.last_included_index) == Some(snapshot.last_included_term);
+        if keeps_following_entries {
+            let compacted = (snapshot.last_included_index + 1 - self.first_index()) as usize;
+            self.entries.drain(..compacted.min(self.entries.len()));
+        } else {
+            self.entries.clear();
+        }
+        self.snapshot = snapshot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(index: u64, term: u64) -> LogEntry {
+        LogEntry {
+            index,
+            term,
+            data: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn compaction_keeps_entries_following_the_snapshot() {
+        let mut log = RaftLog::new();
+        for index in 1..=5 {
+            log.append(entry(index, 1 + index / 3));
+        }
+        assert_eq!(log.term_at(3), Some(2));
+        log.compact(Snapshot {
+            last_included_index: 3,
+            last_included_term: 2,
+            data: Vec::new(),
+        });
+        assert_eq!(log.first_index(), 4);
+        assert_eq!(log.last_index(), 5);
+        assert_eq!(log.term_at(3), Some(2));
+        assert_eq!(log.term_at(2), None);
+        assert_eq!(log.entries_from(4, 10).len(), 2);
+
+        log.truncate_from(5);
+        assert_eq!(log.last_index(), 4);
+
+        log.compact(Snapshot {
+            last_included_index: 8,
+            last_included_term: 3,
+            data: Vec::new(),
+        });
+        assert_eq!(log.last_index(), 8);
+        assert_eq!(log.last_term(), 3);
+        assert!(log.entries_from(9, 10).is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::raft::NodeId;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub index: u64,
+    pub term: u64,
+    /// Empty for the entry a new leader appends to commit the entries of older terms.
+    pub data: Vec<u8>,
+}
+
+/// The state machine as of `last_included_index`, replacing the log up to that entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub last_included_index: u64,
+    pub last_included_term: u64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RaftMessage {
+    #[serde(rename_all = "camelCase")]
+    RequestVote {
+        term: u64,
+        last_log_index: u64,
+        last_log_term: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    RequestVoteResponse { term: u64, vote_granted: bool },
+    #[serde(rename_all = "camelCase")]
+    AppendEntries {
+        term: u64,
+        prev_log_index: u64,
+        prev_log_term: u64,
+        entries: Vec<LogEntry>,
+        leader_commit: u64,
+    },
+    /// On success `match_index` is the last entry matching the leader, otherwise a hint of
+    /// where the leader should retry from.
+    #[serde(rename_all = "camelCase")]
+    AppendEntriesResponse {
+        term: u64,
+        success: bool,
+        match_index: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    InstallSnapshot { term: u64, snapshot: Snapshot },
+    #[serde(rename_all = "camelCase")]
+    InstallSnapshotResponse { term: u64, last_included_index: u64 },
+}
+
+impl RaftMessage {
+    pub fn term(&self) -> u64 {
+        match self {
+            RaftMessage::RequestVote { term, .. }
+            | RaftMessage::RequestVoteResponse { term, .. }
+            | RaftMessage::AppendEntries { term, .. }
+            | RaftMessage::AppendEntriesResponse { term, .. }
+            | RaftMessage::InstallSnapshot { term, .. }
+            | RaftMessage::InstallSnapshotResponse { term, .. } => *term,
+        }
+    }
+}
+
+/// A message addressed to a member of a Raft group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaftEnvelope {
+    pub group: String,
+    pub from: NodeId,
+    pub to: NodeId,
+    pub message: RaftMessage,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raft_envelope_round_trips_through_json() {
+        let envelope = RaftEnvelope {
+            group: "DefaultControllerGroup".to_string(),
+            from: "n0".to_string(),
+            to: "n1".to_string(),
+            message: RaftMessage::AppendEntries {
+                term: 2,
+                prev_log_index: 1,
+                prev_log_term: 1,
+                entries: vec![LogEntry {
+                    index: 2,
+                    term: 2,
+                    data: b"event".to_vec(),
+                }],
+                leader_commit: 1,
+            },
+        };
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert!(json.contains(r#""type":"appendEntries""#));
+        assert_eq!(
+            serde_json::from_str::<RaftEnvelope>(&json).unwrap(),
+            envelope
+        );
+    }
+}
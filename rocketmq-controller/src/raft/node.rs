@@ -0,0 +1,417 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
+use rocketmq_common::TimeUtils::get_current_millis;
+use tokio::sync::oneshot;
+use tracing::error;
+use tracing::warn;
+
+use crate::raft::core::PersistentState;
+use crate::raft::core::RaftCore;
+use crate::raft::core::RaftOptions;
+use crate::raft::message::RaftEnvelope;
+use crate::raft::parse_peers;
+use crate::raft::storage::RaftStorage;
+use crate::raft::transport::RaftTransport;
+use crate::raft::NodeId;
+use crate::raft::RaftError;
+use crate::raft::StateMachine;
+
+type ProposalSender = oneshot::Sender<Result<(), RaftError>>;
+
+/// Drives a [`RaftCore`] on tokio: ticks it, persists it before sending its messages and
+/// tells proposers once their entry is applied.
+///
+/// The state is saved on a dedicated thread, in the order the core produced it, and the
+/// messages and applied entries of a batch are only released once its state is on disk. A
+/// member whose state can not be saved stops: what it would tell the others might not survive
+/// a restart.
+pub struct RaftNode<S> {
+    core: Mutex<RaftCore<S>>,
+    outbox: Arc<Outbox>,
+    /// Batches waiting for their state to be saved, `None` without storage.
+    persister: Option<mpsc::Sender<Ready>>,
+    tick_interval: Duration,
+}
+
+/// What the core produced since the last flush.
+struct Ready {
+    state: Option<PersistentState>,
+    messages: Vec<RaftEnvelope>,
+    applied: Vec<(u64, u64)>,
+    /// Term and known leader when this member does not lead, the proposals made up to that
+    /// term fail.
+    not_leader: Option<(u64, Option<NodeId>)>,
+}
+
+struct Outbox {
+    transport: Arc<dyn RaftTransport>,
+    /// Term of the proposed entry and its proposer, by index.
+    proposals: Mutex<HashMap<u64, (u64, ProposalSender)>>,
+    failed: AtomicBool,
+}
+
+impl Outbox {
+    /// Sends the messages of `ready` and answers the proposers, unless the member failed.
+    fn release(&self, ready: Ready) {
+        let mut proposals = self.proposals.lock();
+        if self.failed.load(Ordering::Acquire) {
+            for (_, (_, sender)) in proposals.drain() {
+                let _ = sender.send(Err(RaftError::StorageFailed));
+            }
+            return;
+        }
+        for envelope in ready.messages {
+            self.transport.send(envelope);
+        }
+        for (index, term) in ready.applied {
+            if let Some((proposed_term, sender)) = proposals.remove(&index) {
+                let result = if proposed_term == term {
+                    Ok(())
+                } else {
+                    Err(RaftError::ProposalDropped)
+                };
+                let _ = sender.send(result);
+            }
+        }
+        if let Some((term, leader_id)) = ready.not_leader {
+            // Proposals of a later term were made after this batch, while leading again
+            let dropped = proposals
+                .iter()
+                .filter(|(_, (proposed_term, _))| *proposed_term <= term)
+                .map(|(index, _)| *index)
+                .collect::<Vec<_>>();
+            for index in dropped {
+                if let Some((_, sender)) = proposals.remove(&index) {
+                    let _ = sender.send(Err(RaftError::NotLeader(leader_id.clone())));
+                }
+            }
+        }
+    }
+
+    /// Saves the state of every batch before releasing it, until the node is dropped.
+    fn persist(&self, storage: &RaftStorage, batches: mpsc::Receiver<Ready>) {
+        for mut ready in batches {
+            if let Some(state) = ready.state.take() {
+                if !self.failed.load(Ordering::Acquire) {
+                    if let Err(err) = storage.save(&state) {
+                        error!(
+                            "failed to save raft state {}, the member stops: {}",
+                            storage.file_name(),
+                            err
+                        );
+                        self.failed.store(true, Ordering::Release);
+                    }
+                }
+            }
+            self.release(ready);
+        }
+    }
+}
+
+impl<S: StateMachine> RaftNode<S> {
+    pub fn new(
+        core: RaftCore<S>,
+        transport: Arc<dyn RaftTransport>,
+        storage: Option<RaftStorage>,
+        tick_interval: Duration,
+    ) -> Self {
+        let outbox = Arc::new(Outbox {
+            transport,
+            proposals: Mutex::new(HashMap::new()),
+            failed: AtomicBool::new(false),
+        });
+        let persister = storage.map(|storage| {
+            let (sender, receiver) = mpsc::channel();
+            let outbox = outbox.clone();
+            // Transports send on the runtime the node was created on
+            let runtime = tokio::runtime::Handle::try_current().ok();
+            thread::Builder::new()
+                .name(format!("RaftPersister-{}", core.id()))
+                .spawn(move || {
+                    let _guard = runtime.as_ref().map(|runtime| runtime.enter());
+                    outbox.persist(&storage, receiver)
+                })
+                .expect("failed to spawn the raft persister thread");
+            sender
+        });
+        Self {
+            core: Mutex::new(core),
+            outbox,
+            persister,
+            tick_interval,
+        }
+    }
+
+    /// Creates the member `controller_raft_self_id` of the group `group` formed by
+    /// `controller_raft_peers`, resuming from the state it saved under `controller_store_path`.
+    ///
+    /// # Panics
+    ///
+    /// When the saved state can not be read: starting over could vote twice in a term or lose
+    /// committed entries.
+    pub fn with_controller_config(
+        controller_config: &ControllerConfig,
+        group: &str,
+        state_machine: S,
+        transport: Arc<dyn RaftTransport>,
+    ) -> Self {
+        let peers = controller_config
+            .controller_raft_peers
+            .as_deref()
+            .map(parse_peers)
+            .unwrap_or_default();
+        let self_id = controller_config.controller_raft_self_id.as_str();
+        if !peers.contains_key(self_id) {
+            warn!(
+                "raft member {} of group {} is not one of the peers {:?}",
+                self_id, group, controller_config.controller_raft_peers
+            );
+        }
+        let storage = RaftStorage::new(
+            PathBuf::from(&controller_config.controller_store_path)
+                .join(format!("{}-{}.json", group, self_id))
+                .to_string_lossy(),
+        );
+        let persistent_state = storage.load().unwrap_or_else(|err| {
+            panic!("failed to load raft state {}: {}", storage.file_name(), err)
+        });
+        let heartbeat_interval = controller_config.raft_heartbeat_interval_mills.max(1);
+        let options = RaftOptions {
+            election_ticks: (controller_config.raft_election_timeout_mills / heartbeat_interval)
+                .max(2),
+            heartbeat_ticks: 1,
+            snapshot_threshold: controller_config.raft_snapshot_threshold,
+            random_seed: get_current_millis(),
+            ..RaftOptions::default()
+        };
+        let core = RaftCore::new(
+            group,
+            self_id,
+            peers.into_keys(),
+            options,
+            state_machine,
+            persistent_state,
+        );
+        Self::new(
+            core,
+            transport,
+            Some(storage),
+            Duration::from_millis(heartbeat_interval),
+        )
+    }
+
+    /// Ticks the member until the node is dropped.
+    pub fn start(self: &Arc<Self>) {
+        let node = Arc::downgrade(self);
+        let tick_interval = self.tick_interval;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tick_interval);
+            loop {
+                interval.tick().await;
+                let Some(node) = node.upgrade() else {
+                    break;
+                };
+                node.tick();
+            }
+        });
+    }
+
+    pub fn tick(&self) {
+        if self.is_failed() {
+            return;
+        }
+        let mut core = self.core.lock();
+        core.tick();
+        self.flush(&mut core);
+    }
+
+    pub fn step(&self, envelope: RaftEnvelope) {
+        if self.is_failed() {
+            return;
+        }
+        let mut core = self.core.lock();
+        core.step(envelope);
+        self.flush(&mut core);
+    }
+
+    /// Replicates `data` and waits until it is applied to the state machine of this member.
+    pub async fn propose(&self, data: Vec<u8>) -> Result<(), RaftError> {
+        if self.is_failed() {
+            return Err(RaftError::StorageFailed);
+        }
+        let receiver = {
+            let mut core = self.core.lock();
+            let (index, term) = core.propose(data)?;
+            let (sender, receiver) = oneshot::channel();
+            self.outbox.proposals.lock().insert(index, (term, sender));
+            self.flush(&mut core);
+            receiver
+        };
+        receiver.await.unwrap_or(Err(RaftError::ProposalDropped))
+    }
+
+    /// Whether the member stopped because its state could not be saved.
+    pub fn is_failed(&self) -> bool {
+        self.outbox.failed.load(Ordering::Acquire)
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.core.lock().id().clone()
+    }
+
+    pub fn is_leader_ready(&self) -> bool {
+        self.core.lock().is_leader_ready()
+    }
+
+    pub fn leader_id(&self) -> Option<NodeId> {
+        self.core.lock().leader_id().cloned()
+    }
+
+    pub fn current_term(&self) -> u64 {
+        self.core.lock().current_term()
+    }
+
+    /// Reads the state machine of this member, which lags behind the leader on followers.
+    pub fn read_state_machine<R>(&self, read: impl FnOnce(&S) -> R) -> R {
+        read(self.core.lock().state_machine())
+    }
+
+    fn flush(&self, core: &mut RaftCore<S>) {
+        let ready = Ready {
+            state: core.take_persistent_state(),
+            messages: core.take_messages(),
+            applied: core.take_applied(),
+            not_leader: (!core.is_leader())
+                .then(|| (core.current_term(), core.leader_id().cloned())),
+        };
+        match &self.persister {
+            Some(persister) => {
+                // The persister only stops once the node is dropped
+                let _ = persister.send(ready);
+            }
+            None => self.outbox.release(ready),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::raft::core::RaftRole;
+
+    struct NoopStateMachine;
+
+    impl StateMachine for NoopStateMachine {
+        fn apply(&mut self, _index: u64, _data: &[u8]) {}
+
+        fn snapshot(&self) -> Vec<u8> {
+            Vec::new()
+        }
+
+        fn restore(&mut self, _data: &[u8]) {}
+    }
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Mutex<Vec<RaftEnvelope>>,
+    }
+
+    impl RaftTransport for RecordingTransport {
+        fn send(&self, envelope: RaftEnvelope) {
+            self.sent.lock().push(envelope);
+        }
+    }
+
+    fn node(
+        members: &[&str],
+        file_name: &Path,
+        transport: Arc<RecordingTransport>,
+    ) -> RaftNode<NoopStateMachine> {
+        let core = RaftCore::new(
+            "group",
+            "n0",
+            members.iter().map(|member| member.to_string()),
+            RaftOptions::default(),
+            NoopStateMachine,
+            None,
+        );
+        RaftNode::new(
+            core,
+            transport,
+            Some(RaftStorage::new(file_name.to_string_lossy())),
+            Duration::from_millis(10),
+        )
+    }
+
+    #[tokio::test]
+    async fn applies_proposals_once_saved() {
+        let dir = std::env::temp_dir().join("raft_node_applies_proposals_once_saved");
+        let _ = std::fs::remove_dir_all(&dir);
+        let node = node(&["n0"], &dir.join("raft.json"), Arc::default());
+        for _ in 0..100 {
+            node.tick();
+            if node.core.lock().role() == RaftRole::Leader {
+                break;
+            }
+        }
+        node.propose(b"event".to_vec()).await.unwrap();
+        let state = RaftStorage::new(dir.join("raft.json").to_string_lossy())
+            .load()
+            .unwrap()
+            .unwrap();
+        assert!(state.log.last_index() >= 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn stops_without_sending_when_state_can_not_be_saved() {
+        // The state can not be saved under a regular file
+        let file = std::env::temp_dir().join("raft_node_stops_when_state_can_not_be_saved");
+        std::fs::write(&file, b"").unwrap();
+        let transport = Arc::new(RecordingTransport::default());
+        let node = node(&["n0", "n1"], &file.join("raft.json"), transport.clone());
+
+        // Starting an election votes for itself, which has to be saved before asking for votes
+        for _ in 0..100 {
+            node.tick();
+            if node.is_failed() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert!(node.is_failed());
+        assert!(transport.sent.lock().is_empty());
+        assert_eq!(
+            node.propose(b"event".to_vec()).await,
+            Err(RaftError::StorageFailed)
+        );
+        let _ = std::fs::remove_file(file);
+    }
+}
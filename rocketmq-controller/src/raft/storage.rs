@@ -0,0 +1,124 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+use rocketmq_common::FileUtils;
+use tracing::warn;
+
+use crate::raft::core::PersistentState;
+
+/// Keeps the persistent state of a Raft member in a JSON file, replaced atomically on every
+/// save.
+pub struct RaftStorage {
+    file_name: String,
+}
+
+impl RaftStorage {
+    pub fn new(file_name: impl Into<String>) -> Self {
+        Self {
+            file_name: file_name.into(),
+        }
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// The state saved last, `None` on the first start.
+    pub fn load(&self) -> io::Result<Option<PersistentState>> {
+        match Self::load_file(&self.file_name) {
+            Ok(state) => Ok(state),
+            Err(err) => {
+                // States saved in place, before saves were atomic, kept a backup next to them
+                warn!(
+                    "failed to load raft state {}, trying its backup: {}",
+                    self.file_name, err
+                );
+                Self::load_file(&format!("{}.bak", self.file_name))
+            }
+        }
+    }
+
+    fn load_file(file_name: &str) -> io::Result<Option<PersistentState>> {
+        let content = FileUtils::file_to_string(file_name)?;
+        if content.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Writes the state to a temporary file synced to disk, then renames it over the previous
+    /// state, so a crash leaves either the previous or the new state behind.
+    pub fn save(&self, state: &PersistentState) -> io::Result<()> {
+        let content = serde_json::to_vec(state)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let path = Path::new(&self.file_name);
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+        let tmp_file_name = format!("{}.tmp", self.file_name);
+        let mut file = File::create(&tmp_file_name)?;
+        file.write_all(&content)?;
+        file.sync_all()?;
+        fs::rename(&tmp_file_name, path)?;
+        // The rename is only durable once the directory is synced
+        #[cfg(unix)]
+        File::open(dir)?.sync_all()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raft::message::LogEntry;
+
+    #[test]
+    fn saved_state_is_loaded_back() {
+        let dir = std::env::temp_dir().join("raft_storage_saved_state_is_loaded_back");
+        let _ = std::fs::remove_dir_all(&dir);
+        let storage = RaftStorage::new(dir.join("raft.json").to_string_lossy());
+        assert_eq!(storage.load().unwrap(), None);
+
+        let mut state = PersistentState {
+            current_term: 3,
+            voted_for: Some("n1".to_string()),
+            ..Default::default()
+        };
+        state.log.append(LogEntry {
+            index: 1,
+            term: 3,
+            data: b"event".to_vec(),
+        });
+        storage.save(&state).unwrap();
+        assert_eq!(storage.load().unwrap(), Some(state.clone()));
+
+        state.current_term = 4;
+        storage.save(&state).unwrap();
+        assert_eq!(storage.load().unwrap(), Some(state));
+        assert!(!dir.join("raft.json.tmp").exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,90 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use tracing::warn;
+
+use crate::raft::message::RaftEnvelope;
+use crate::raft::NodeId;
+
+const RAFT_MESSAGE_TIMEOUT_MILLIS: u64 = 3000;
+
+/// Delivers Raft messages to the other members of the group. Raft tolerates lost, reordered
+/// and duplicated messages, so sending is fire and forget.
+pub trait RaftTransport: Send + Sync + 'static {
+    fn send(&self, envelope: RaftEnvelope);
+}
+
+/// Sends Raft messages as oneway `ControllerRaftMessage` requests carrying the envelope as
+/// JSON body.
+pub struct RemotingRaftTransport {
+    remoting_client: Arc<RocketmqDefaultClient>,
+    peers: BTreeMap<NodeId, String>,
+}
+
+impl RemotingRaftTransport {
+    pub fn new(
+        remoting_client: Arc<RocketmqDefaultClient>,
+        peers: BTreeMap<NodeId, String>,
+    ) -> Self {
+        Self {
+            remoting_client,
+            peers,
+        }
+    }
+}
+
+impl RaftTransport for RemotingRaftTransport {
+    fn send(&self, envelope: RaftEnvelope) {
+        let Some(address) = self.peers.get(&envelope.to).cloned() else {
+            warn!(
+                "unknown raft member {} of group {}",
+                envelope.to, envelope.group
+            );
+            return;
+        };
+        let body = match serde_json::to_vec(&envelope) {
+            Ok(body) => body,
+            Err(err) => {
+                warn!("failed to encode raft message: {}", err);
+                return;
+            }
+        };
+        let request =
+            RemotingCommand::create_remoting_command(ControllerRequestCode::ControllerRaftMessage)
+                .set_body(Some(body));
+        let remoting_client = self.remoting_client.clone();
+        tokio::spawn(async move {
+            remoting_client
+                .invoke_oneway(address, request, RAFT_MESSAGE_TIMEOUT_MILLIS)
+                .await;
+        });
+    }
+}
+
+/// Decodes the envelope of a `ControllerRaftMessage` request.
+pub fn decode_raft_envelope(request: &RemotingCommand) -> Option<RaftEnvelope> {
+    request
+        .body()
+        .as_ref()
+        .and_then(|body| serde_json::from_slice(body).ok())
+}
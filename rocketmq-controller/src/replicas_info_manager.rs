@@ -0,0 +1,1044 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use rocketmq_common::common::mix_all::FIRST_BROKER_CONTROLLER_ID;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::body::broker_body::broker_member_group::BrokerMemberGroup;
+use rocketmq_remoting::protocol::body::broker_replicas_info::BrokerReplicasInfo;
+use rocketmq_remoting::protocol::body::broker_replicas_info::ReplicaIdentity;
+use rocketmq_remoting::protocol::body::broker_replicas_info::ReplicasInfo;
+use rocketmq_remoting::protocol::body::elect_master_response_body::ElectMasterResponseBody;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetRequestHeader;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetResponseHeader;
+use rocketmq_remoting::protocol::header::controller::apply_broker_id_request_header::ApplyBrokerIdRequestHeader;
+use rocketmq_remoting::protocol::header::controller::apply_broker_id_request_header::ApplyBrokerIdResponseHeader;
+use rocketmq_remoting::protocol::header::controller::clean_controller_broker_data_request_header::CleanControllerBrokerDataRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_next_broker_id_request_header::GetNextBrokerIdRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_next_broker_id_request_header::GetNextBrokerIdResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoResponseHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerResponseHeader;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::info;
+
+use crate::controller_result::ControllerResult;
+use crate::elect_policy::ElectPolicy;
+use crate::event::EventMessage;
+
+/// Ids and addresses of the replicas of one broker set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerReplicaInfo {
+    cluster_name: String,
+    broker_name: String,
+    next_assign_broker_id: i64,
+    /// broker id -> (broker address, register check code)
+    broker_id_info: HashMap<i64, (String, String)>,
+}
+
+impl BrokerReplicaInfo {
+    fn new(cluster_name: impl Into<String>, broker_name: impl Into<String>) -> Self {
+        Self {
+            cluster_name: cluster_name.into(),
+            broker_name: broker_name.into(),
+            next_assign_broker_id: FIRST_BROKER_CONTROLLER_ID as i64,
+            broker_id_info: HashMap::new(),
+        }
+    }
+
+    pub fn cluster_name(&self) -> &str {
+        &self.cluster_name
+    }
+
+    pub fn broker_name(&self) -> &str {
+        &self.broker_name
+    }
+
+    pub fn next_assign_broker_id(&self) -> i64 {
+        self.next_assign_broker_id
+    }
+
+    pub fn broker_address(&self, broker_id: i64) -> Option<&str> {
+        self.broker_id_info
+            .get(&broker_id)
+            .map(|(address, _)| address.as_str())
+    }
+
+    pub fn all_brokers(&self) -> HashSet<i64> {
+        self.broker_id_info.keys().copied().collect()
+    }
+
+    pub fn broker_addresses(&self) -> HashMap<i64, String> {
+        self.broker_id_info
+            .iter()
+            .map(|(broker_id, (address, _))| (*broker_id, address.clone()))
+            .collect()
+    }
+}
+
+/// The master and the SyncStateSet of one broker set, with the epochs fencing them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStateInfo {
+    cluster_name: String,
+    broker_name: String,
+    master_broker_id: Option<i64>,
+    master_epoch: i32,
+    sync_state_set: HashSet<i64>,
+    sync_state_set_epoch: i32,
+}
+
+impl SyncStateInfo {
+    fn new(cluster_name: impl Into<String>, broker_name: impl Into<String>) -> Self {
+        Self {
+            cluster_name: cluster_name.into(),
+            broker_name: broker_name.into(),
+            master_broker_id: None,
+            master_epoch: 0,
+            sync_state_set: HashSet::new(),
+            sync_state_set_epoch: 0,
+        }
+    }
+
+    pub fn master_broker_id(&self) -> Option<i64> {
+        self.master_broker_id
+    }
+
+    pub fn master_epoch(&self) -> i32 {
+        self.master_epoch
+    }
+
+    pub fn sync_state_set(&self) -> &HashSet<i64> {
+        &self.sync_state_set
+    }
+
+    pub fn sync_state_set_epoch(&self) -> i32 {
+        self.sync_state_set_epoch
+    }
+
+    /// Whether the broker set never had a master.
+    pub fn is_first_time_for_elect(&self) -> bool {
+        self.master_epoch == 0
+    }
+
+    fn update_master(&mut self, master_broker_id: Option<i64>) {
+        self.master_broker_id = master_broker_id;
+        self.master_epoch += 1;
+        if let Some(master_broker_id) = master_broker_id {
+            self.update_sync_state_set(HashSet::from([master_broker_id]));
+        }
+    }
+
+    fn update_sync_state_set(&mut self, sync_state_set: HashSet<i64>) {
+        self.sync_state_set = sync_state_set;
+        self.sync_state_set_epoch += 1;
+    }
+
+    fn to_sync_state_set(&self) -> SyncStateSet {
+        SyncStateSet::new(self.sync_state_set.clone(), self.sync_state_set_epoch)
+    }
+}
+
+/// The replica metadata of every broker set managed by the controller.
+///
+/// Request handlers only read the metadata and return the events to apply, see
+/// [`EventMessage`]. `is_alive` arguments tell whether the replica `(cluster name, broker
+/// name, broker id)` is alive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicasInfoManager {
+    replica_info_table: HashMap<String /* broker name */, BrokerReplicaInfo>,
+    sync_state_set_info_table: HashMap<String /* broker name */, SyncStateInfo>,
+}
+
+impl ReplicasInfoManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_next_broker_id(
+        &self,
+        request: &GetNextBrokerIdRequestHeader,
+    ) -> ControllerResult<GetNextBrokerIdResponseHeader> {
+        let next_broker_id = self
+            .replica_info_table
+            .get(&request.broker_name)
+            .map_or(FIRST_BROKER_CONTROLLER_ID as i64, |info| {
+                info.next_assign_broker_id
+            });
+        ControllerResult::new(Some(GetNextBrokerIdResponseHeader {
+            cluster_name: request.cluster_name.clone(),
+            broker_name: request.broker_name.clone(),
+            next_broker_id: Some(next_broker_id),
+        }))
+    }
+
+    /// The register check code is `address;timestamp`, applying again with the same check code
+    /// succeeds so a broker can retry after losing the response.
+    pub fn apply_broker_id(
+        &self,
+        request: &ApplyBrokerIdRequestHeader,
+    ) -> ControllerResult<ApplyBrokerIdResponseHeader> {
+        let broker_id = request.applied_broker_id;
+        let expected_broker_id = match self.replica_info_table.get(&request.broker_name) {
+            None => FIRST_BROKER_CONTROLLER_ID as i64,
+            Some(info) => {
+                if let Some((_, register_check_code)) = info.broker_id_info.get(&broker_id) {
+                    if *register_check_code == request.register_check_code {
+                        return ControllerResult::new(Some(ApplyBrokerIdResponseHeader {
+                            cluster_name: request.cluster_name.clone(),
+                            broker_name: request.broker_name.clone(),
+                        }));
+                    }
+                    return ControllerResult::failure(
+                        ResponseCode::ControllerBrokerIdInvalid,
+                        format!(
+                            "broker id {} of broker set {} is already taken by another broker",
+                            broker_id, request.broker_name
+                        ),
+                    );
+                }
+                info.next_assign_broker_id
+            }
+        };
+        if broker_id != expected_broker_id {
+            return ControllerResult::failure(
+                ResponseCode::ControllerBrokerIdInvalid,
+                format!(
+                    "broker id {} of broker set {} is not the next assignable id {}",
+                    broker_id, request.broker_name, expected_broker_id
+                ),
+            );
+        }
+        let broker_address = request
+            .register_check_code
+            .split(';')
+            .next()
+            .unwrap_or_default();
+        ControllerResult::of(
+            vec![EventMessage::ApplyBrokerId {
+                cluster_name: request.cluster_name.clone(),
+                broker_name: request.broker_name.clone(),
+                broker_address: broker_address.to_string(),
+                new_broker_id: broker_id,
+                register_check_code: request.register_check_code.clone(),
+            }],
+            Some(ApplyBrokerIdResponseHeader {
+                cluster_name: request.cluster_name.clone(),
+                broker_name: request.broker_name.clone(),
+            }),
+        )
+    }
+
+    /// Answers the current master, if it is alive, and the SyncStateSet as body.
+    pub fn register_broker(
+        &self,
+        request: &RegisterBrokerToControllerRequestHeader,
+        is_alive: impl Fn(&str, &str, i64) -> bool,
+    ) -> ControllerResult<RegisterBrokerToControllerResponseHeader> {
+        let broker_name = request.broker_name.as_str();
+        let (Some(replica_info), Some(sync_state_info)) = (
+            self.replica_info_table.get(broker_name),
+            self.sync_state_set_info_table.get(broker_name),
+        ) else {
+            return ControllerResult::failure(
+                ResponseCode::ControllerBrokerMetadataNotExist,
+                format!("broker set {} has not applied any broker id", broker_name),
+            );
+        };
+        let Some(broker_address) = replica_info.broker_address(request.broker_id) else {
+            return ControllerResult::failure(
+                ResponseCode::ControllerBrokerMetadataNotExist,
+                format!(
+                    "broker id {} has not been applied in broker set {}",
+                    request.broker_id, broker_name
+                ),
+            );
+        };
+        let mut events = Vec::new();
+        if broker_address != request.broker_address {
+            events.push(EventMessage::UpdateBrokerAddress {
+                cluster_name: request.cluster_name.clone(),
+                broker_name: request.broker_name.clone(),
+                broker_address: request.broker_address.clone(),
+                broker_id: request.broker_id,
+            });
+        }
+        let mut response = RegisterBrokerToControllerResponseHeader {
+            cluster_name: request.cluster_name.clone(),
+            broker_name: request.broker_name.clone(),
+            ..Default::default()
+        };
+        if let Some(master_broker_id) = sync_state_info.master_broker_id {
+            if is_alive(&replica_info.cluster_name, broker_name, master_broker_id) {
+                response.master_broker_id = Some(master_broker_id);
+                response.master_address = if master_broker_id == request.broker_id {
+                    Some(request.broker_address.clone())
+                } else {
+                    replica_info
+                        .broker_address(master_broker_id)
+                        .map(str::to_string)
+                };
+                response.master_epoch = sync_state_info.master_epoch;
+                response.sync_state_set_epoch = sync_state_info.sync_state_set_epoch;
+            }
+        }
+        let mut result = ControllerResult::of(events, Some(response));
+        result.set_body(sync_state_info.to_sync_state_set().encode());
+        result
+    }
+
+    pub fn elect_master(
+        &self,
+        request: &ElectMasterRequestHeader,
+        elect_policy: &dyn ElectPolicy,
+    ) -> ControllerResult<ElectMasterResponseHeader> {
+        let broker_name = request.broker_name.as_str();
+        let (Some(replica_info), Some(sync_state_info)) = (
+            self.replica_info_table.get(broker_name),
+            self.sync_state_set_info_table.get(broker_name),
+        ) else {
+            return ControllerResult::failure(
+                ResponseCode::ControllerBrokerNeedToBeRegistered,
+                format!("broker set {} has not been registered", broker_name),
+            );
+        };
+        let cluster_name = replica_info.cluster_name.as_str();
+        let old_master = sync_state_info.master_broker_id;
+        let new_master = if request.designate_elect {
+            match request.broker_id {
+                Some(broker_id)
+                    if replica_info.broker_id_info.contains_key(&broker_id)
+                        && elect_policy.is_valid_candidate(
+                            cluster_name,
+                            broker_name,
+                            broker_id,
+                        ) =>
+                {
+                    Some(broker_id)
+                }
+                _ => {
+                    return ControllerResult::failure(
+                        ResponseCode::ControllerElectMasterFailed,
+                        format!(
+                            "designated broker {:?} of broker set {} is not an alive replica",
+                            request.broker_id, broker_name
+                        ),
+                    );
+                }
+            }
+        } else {
+            let all_replica_brokers = replica_info.all_brokers();
+            // The first master of a broker set may be any replica, none of them has data yet
+            let sync_state_brokers = if sync_state_info.is_first_time_for_elect() {
+                &all_replica_brokers
+            } else {
+                &sync_state_info.sync_state_set
+            };
+            elect_policy.elect(
+                cluster_name,
+                broker_name,
+                sync_state_brokers,
+                &all_replica_brokers,
+                old_master,
+                request.broker_id,
+            )
+        };
+
+        let broker_member_group = BrokerMemberGroup::new(
+            Some(cluster_name.to_string()),
+            Some(broker_name.to_string()),
+            Some(replica_info.broker_addresses()),
+        );
+        match new_master {
+            Some(new_master) if Some(new_master) == old_master => {
+                let mut result = ControllerResult::new(Some(ElectMasterResponseHeader {
+                    master_broker_id: Some(new_master),
+                    master_address: replica_info.broker_address(new_master).map(str::to_string),
+                    master_epoch: sync_state_info.master_epoch,
+                    sync_state_set_epoch: sync_state_info.sync_state_set_epoch,
+                }));
+                result.set_body(
+                    ElectMasterResponseBody {
+                        broker_member_group: Some(broker_member_group),
+                        sync_state_set: sync_state_info.sync_state_set.clone(),
+                    }
+                    .encode(),
+                );
+                result.set_code_and_remark(
+                    ResponseCode::ControllerMasterStillExist,
+                    format!("the master of broker set {} is still alive", broker_name),
+                );
+                result
+            }
+            Some(new_master) => {
+                let mut result = ControllerResult::of(
+                    vec![EventMessage::ElectMaster {
+                        broker_name: broker_name.to_string(),
+                        new_master_broker_id: Some(new_master),
+                    }],
+                    Some(ElectMasterResponseHeader {
+                        master_broker_id: Some(new_master),
+                        master_address: replica_info.broker_address(new_master).map(str::to_string),
+                        master_epoch: sync_state_info.master_epoch + 1,
+                        sync_state_set_epoch: sync_state_info.sync_state_set_epoch + 1,
+                    }),
+                );
+                result.set_body(
+                    ElectMasterResponseBody {
+                        broker_member_group: Some(broker_member_group),
+                        sync_state_set: HashSet::from([new_master]),
+                    }
+                    .encode(),
+                );
+                result
+            }
+            None => {
+                let mut result = ControllerResult::new(None);
+                // Drop the dead master so brokers stop following it, once
+                if old_master.is_some() {
+                    result.add_event(EventMessage::ElectMaster {
+                        broker_name: broker_name.to_string(),
+                        new_master_broker_id: None,
+                    });
+                }
+                result.set_code_and_remark(
+                    ResponseCode::ControllerMasterNotAvailable,
+                    format!("no replica of broker set {} can be elected", broker_name),
+                );
+                result
+            }
+        }
+    }
+
+    /// Replaces the SyncStateSet, requested by the master that knows the current epochs.
+    pub fn alter_sync_state_set(
+        &self,
+        request: &AlterSyncStateSetRequestHeader,
+        sync_state_set: &SyncStateSet,
+        is_alive: impl Fn(&str, &str, i64) -> bool,
+    ) -> ControllerResult<AlterSyncStateSetResponseHeader> {
+        let broker_name = request.broker_name.as_str();
+        let (Some(replica_info), Some(sync_state_info)) = (
+            self.replica_info_table.get(broker_name),
+            self.sync_state_set_info_table.get(broker_name),
+        ) else {
+            return ControllerResult::failure(
+                ResponseCode::ControllerAlterSyncStateSetFailed,
+                format!("broker set {} has not been registered", broker_name),
+            );
+        };
+        if sync_state_info.master_broker_id != Some(request.master_broker_id) {
+            return ControllerResult::failure(
+                ResponseCode::ControllerInvalidMaster,
+                format!(
+                    "broker {} is not the master of broker set {}",
+                    request.master_broker_id, broker_name
+                ),
+            );
+        }
+        if request.master_epoch != sync_state_info.master_epoch {
+            return ControllerResult::failure(
+                ResponseCode::ControllerFencedMasterEpoch,
+                format!(
+                    "master epoch {} is fenced, current master epoch is {}",
+                    request.master_epoch, sync_state_info.master_epoch
+                ),
+            );
+        }
+        if sync_state_set.sync_state_set_epoch != sync_state_info.sync_state_set_epoch {
+            return ControllerResult::failure(
+                ResponseCode::ControllerFencedSyncStateSetEpoch,
+                format!(
+                    "sync state set epoch {} is fenced, current sync state set epoch is {}",
+                    sync_state_set.sync_state_set_epoch, sync_state_info.sync_state_set_epoch
+                ),
+            );
+        }
+        for broker_id in &sync_state_set.sync_state_set {
+            if !replica_info.broker_id_info.contains_key(broker_id) {
+                return ControllerResult::failure(
+                    ResponseCode::ControllerInvalidReplicas,
+                    format!(
+                        "broker {} is not a replica of broker set {}",
+                        broker_id, broker_name
+                    ),
+                );
+            }
+            if !is_alive(&replica_info.cluster_name, broker_name, *broker_id) {
+                return ControllerResult::failure(
+                    ResponseCode::ControllerBrokerNotAlive,
+                    format!(
+                        "replica {} of broker set {} is not alive",
+                        broker_id, broker_name
+                    ),
+                );
+            }
+        }
+        if !sync_state_set
+            .sync_state_set
+            .contains(&request.master_broker_id)
+        {
+            return ControllerResult::failure(
+                ResponseCode::ControllerAlterSyncStateSetFailed,
+                "the new sync state set does not contain the master",
+            );
+        }
+        if sync_state_set.sync_state_set == sync_state_info.sync_state_set {
+            return ControllerResult::failure(
+                ResponseCode::ControllerAlterSyncStateSetFailed,
+                "the new sync state set is the same as the current one",
+            );
+        }
+        let new_sync_state_set_epoch = sync_state_info.sync_state_set_epoch + 1;
+        let mut result = ControllerResult::of(
+            vec![EventMessage::AlterSyncStateSet {
+                broker_name: broker_name.to_string(),
+                new_sync_state_set: sync_state_set.sync_state_set.clone(),
+            }],
+            Some(AlterSyncStateSetResponseHeader {
+                new_sync_state_set_epoch,
+            }),
+        );
+        result.set_body(
+            SyncStateSet::new(
+                sync_state_set.sync_state_set.clone(),
+                new_sync_state_set_epoch,
+            )
+            .encode(),
+        );
+        result
+    }
+
+    pub fn get_replica_info(
+        &self,
+        request: &GetReplicaInfoRequestHeader,
+    ) -> ControllerResult<GetReplicaInfoResponseHeader> {
+        let broker_name = request.broker_name.as_str();
+        let (Some(replica_info), Some(sync_state_info)) = (
+            self.replica_info_table.get(broker_name),
+            self.sync_state_set_info_table.get(broker_name),
+        ) else {
+            return ControllerResult::failure(
+                ResponseCode::ControllerBrokerMetadataNotExist,
+                format!("broker set {} has not been registered", broker_name),
+            );
+        };
+        let master_broker_id = sync_state_info.master_broker_id;
+        let mut result = ControllerResult::new(Some(GetReplicaInfoResponseHeader {
+            master_broker_id,
+            master_address: master_broker_id
+                .and_then(|broker_id| replica_info.broker_address(broker_id))
+                .map(str::to_string),
+            master_epoch: sync_state_info.master_epoch,
+        }));
+        result.set_body(sync_state_info.to_sync_state_set().encode());
+        result
+    }
+
+    /// Answers a [`BrokerReplicasInfo`] body covering the given broker sets.
+    pub fn get_sync_state_data(
+        &self,
+        broker_names: &[String],
+        is_alive: impl Fn(&str, &str, i64) -> bool,
+    ) -> ControllerResult<()> {
+        let mut replicas_info_table = HashMap::new();
+        for broker_name in broker_names {
+            let (Some(replica_info), Some(sync_state_info)) = (
+                self.replica_info_table.get(broker_name),
+                self.sync_state_set_info_table.get(broker_name),
+            ) else {
+                continue;
+            };
+            let mut in_sync_replicas = Vec::new();
+            let mut not_in_sync_replicas = Vec::new();
+            let mut broker_ids = replica_info.all_brokers().into_iter().collect::<Vec<_>>();
+            broker_ids.sort_unstable();
+            for broker_id in broker_ids {
+                let identity = ReplicaIdentity {
+                    broker_name: broker_name.clone(),
+                    broker_id,
+                    broker_address: replica_info
+                        .broker_address(broker_id)
+                        .unwrap_or_default()
+                        .to_string(),
+                    alive: is_alive(&replica_info.cluster_name, broker_name, broker_id),
+                };
+                if sync_state_info.sync_state_set.contains(&broker_id) {
+                    in_sync_replicas.push(identity);
+                } else {
+                    not_in_sync_replicas.push(identity);
+                }
+            }
+            let master_broker_id = sync_state_info.master_broker_id;
+            replicas_info_table.insert(
+                broker_name.clone(),
+                ReplicasInfo {
+                    master_broker_id,
+                    master_address: master_broker_id
+                        .and_then(|broker_id| replica_info.broker_address(broker_id))
+                        .map(str::to_string),
+                    master_epoch: sync_state_info.master_epoch,
+                    sync_state_set_epoch: sync_state_info.sync_state_set_epoch,
+                    in_sync_replicas,
+                    not_in_sync_replicas,
+                },
+            );
+        }
+        let mut result = ControllerResult::new(None);
+        result.set_body(
+            BrokerReplicasInfo {
+                replicas_info_table,
+            }
+            .encode(),
+        );
+        result
+    }
+
+    /// Forgets replicas, refusing to forget alive ones unless `is_clean_living_broker` is set.
+    pub fn clean_broker_data(
+        &self,
+        request: &CleanControllerBrokerDataRequestHeader,
+        is_alive: impl Fn(&str, &str, i64) -> bool,
+    ) -> ControllerResult<()> {
+        let broker_name = request.broker_name.as_str();
+        let Some(replica_info) = self.replica_info_table.get(broker_name) else {
+            return ControllerResult::failure(
+                ResponseCode::ControllerInvalidCleanBrokerMetadata,
+                format!("broker set {} has not been registered", broker_name),
+            );
+        };
+        let broker_ids_to_clean = match request
+            .broker_controller_ids_to_clean
+            .as_deref()
+            .filter(|ids| !ids.trim().is_empty())
+        {
+            None => None,
+            Some(ids) => {
+                let parsed = ids
+                    .split(';')
+                    .filter(|id| !id.trim().is_empty())
+                    .map(|id| id.trim().parse::<i64>())
+                    .collect::<Result<HashSet<_>, _>>();
+                match parsed {
+                    Ok(parsed) => Some(parsed),
+                    Err(_) => {
+                        return ControllerResult::failure(
+                            ResponseCode::ControllerInvalidCleanBrokerMetadata,
+                            format!("invalid broker ids to clean: {}", ids),
+                        );
+                    }
+                }
+            }
+        };
+        if !request.is_clean_living_broker {
+            let targets = broker_ids_to_clean
+                .clone()
+                .unwrap_or_else(|| replica_info.all_brokers());
+            if let Some(alive) = targets
+                .iter()
+                .find(|broker_id| is_alive(&replica_info.cluster_name, broker_name, **broker_id))
+            {
+                return ControllerResult::failure(
+                    ResponseCode::ControllerInvalidCleanBrokerMetadata,
+                    format!(
+                        "replica {} of broker set {} is still alive",
+                        alive, broker_name
+                    ),
+                );
+            }
+        }
+        ControllerResult::of(
+            vec![EventMessage::CleanBrokerData {
+                broker_name: broker_name.to_string(),
+                broker_ids_to_clean,
+            }],
+            None,
+        )
+    }
+
+    pub fn apply_event(&mut self, event: &EventMessage) {
+        match event {
+            EventMessage::ApplyBrokerId {
+                cluster_name,
+                broker_name,
+                broker_address,
+                new_broker_id,
+                register_check_code,
+            } => {
+                let replica_info = self
+                    .replica_info_table
+                    .entry(broker_name.clone())
+                    .or_insert_with(|| BrokerReplicaInfo::new(cluster_name, broker_name));
+                replica_info.broker_id_info.insert(
+                    *new_broker_id,
+                    (broker_address.clone(), register_check_code.clone()),
+                );
+                replica_info.next_assign_broker_id =
+                    replica_info.next_assign_broker_id.max(*new_broker_id + 1);
+                self.sync_state_set_info_table
+                    .entry(broker_name.clone())
+                    .or_insert_with(|| SyncStateInfo::new(cluster_name, broker_name));
+            }
+            EventMessage::UpdateBrokerAddress {
+                broker_name,
+                broker_address,
+                broker_id,
+                ..
+            } => {
+                if let Some((address, _)) = self
+                    .replica_info_table
+                    .get_mut(broker_name)
+                    .and_then(|info| info.broker_id_info.get_mut(broker_id))
+                {
+                    *address = broker_address.clone();
+                }
+            }
+            EventMessage::ElectMaster {
+                broker_name,
+                new_master_broker_id,
+            } => {
+                if let Some(sync_state_info) = self.sync_state_set_info_table.get_mut(broker_name) {
+                    sync_state_info.update_master(*new_master_broker_id);
+                    info!(
+                        "broker set {} elected master {:?}, master epoch {}",
+                        broker_name, new_master_broker_id, sync_state_info.master_epoch
+                    );
+                }
+            }
+            EventMessage::AlterSyncStateSet {
+                broker_name,
+                new_sync_state_set,
+            } => {
+                if let Some(sync_state_info) = self.sync_state_set_info_table.get_mut(broker_name) {
+                    sync_state_info.update_sync_state_set(new_sync_state_set.clone());
+                }
+            }
+            EventMessage::CleanBrokerData {
+                broker_name,
+                broker_ids_to_clean,
+            } => {
+                let Some(broker_ids_to_clean) = broker_ids_to_clean else {
+                    self.replica_info_table.remove(broker_name);
+                    self.sync_state_set_info_table.remove(broker_name);
+                    return;
+                };
+                let now_empty = match self.replica_info_table.get_mut(broker_name) {
+                    Some(replica_info) => {
+                        replica_info
+                            .broker_id_info
+                            .retain(|broker_id, _| !broker_ids_to_clean.contains(broker_id));
+                        replica_info.broker_id_info.is_empty()
+                    }
+                    None => true,
+                };
+                if now_empty {
+                    self.replica_info_table.remove(broker_name);
+                    self.sync_state_set_info_table.remove(broker_name);
+                } else if let Some(sync_state_info) =
+                    self.sync_state_set_info_table.get_mut(broker_name)
+                {
+                    sync_state_info
+                        .sync_state_set
+                        .retain(|broker_id| !broker_ids_to_clean.contains(broker_id));
+                    if sync_state_info
+                        .master_broker_id
+                        .is_some_and(|master| broker_ids_to_clean.contains(&master))
+                    {
+                        sync_state_info.master_broker_id = None;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn replica_info(&self, broker_name: &str) -> Option<&BrokerReplicaInfo> {
+        self.replica_info_table.get(broker_name)
+    }
+
+    pub fn sync_state_info(&self, broker_name: &str) -> Option<&SyncStateInfo> {
+        self.sync_state_set_info_table.get(broker_name)
+    }
+
+    pub fn broker_names(&self) -> Vec<String> {
+        self.replica_info_table.keys().cloned().collect()
+    }
+
+    pub fn is_master(&self, broker_name: &str, broker_id: i64) -> bool {
+        self.sync_state_set_info_table
+            .get(broker_name)
+            .is_some_and(|info| info.master_broker_id == Some(broker_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rocketmq_remoting::protocol::header::namesrv::broker_request::BrokerHeartbeatRequestHeader;
+
+    use super::*;
+    use crate::elect_policy::DefaultElectPolicy;
+    use crate::heartbeat_manager::BrokerHeartbeatManager;
+
+    const CLUSTER: &str = "DefaultCluster";
+    const BROKER: &str = "broker-a";
+
+    fn apply_all<T>(manager: &mut ReplicasInfoManager, result: &ControllerResult<T>) {
+        for event in result.events() {
+            manager.apply_event(event);
+        }
+    }
+
+    fn register_replica(manager: &mut ReplicasInfoManager, address: &str) -> i64 {
+        let next = manager
+            .get_next_broker_id(&GetNextBrokerIdRequestHeader {
+                cluster_name: CLUSTER.to_string(),
+                broker_name: BROKER.to_string(),
+            })
+            .response()
+            .unwrap()
+            .next_broker_id
+            .unwrap();
+        let result = manager.apply_broker_id(&ApplyBrokerIdRequestHeader {
+            cluster_name: CLUSTER.to_string(),
+            broker_name: BROKER.to_string(),
+            applied_broker_id: next,
+            register_check_code: format!("{};1", address),
+        });
+        assert!(result.is_success());
+        apply_all(manager, &result);
+        next
+    }
+
+    fn heartbeat(manager: &BrokerHeartbeatManager, broker_id: i64, max_offset: i64) {
+        manager.on_broker_heartbeat(&BrokerHeartbeatRequestHeader::new(
+            CLUSTER,
+            format!("127.0.0.1:1091{}", broker_id),
+            BROKER,
+            Some(broker_id),
+            Some(1),
+            Some(max_offset),
+            None,
+            Some(60_000),
+            None,
+        ));
+    }
+
+    fn elect_request(broker_id: Option<i64>) -> ElectMasterRequestHeader {
+        ElectMasterRequestHeader {
+            cluster_name: CLUSTER.to_string(),
+            broker_name: BROKER.to_string(),
+            broker_id,
+            designate_elect: false,
+            invoke_time: 0,
+        }
+    }
+
+    #[test]
+    fn applies_broker_ids_in_order() {
+        let mut manager = ReplicasInfoManager::new();
+        assert_eq!(register_replica(&mut manager, "127.0.0.1:10911"), 1);
+        assert_eq!(register_replica(&mut manager, "127.0.0.1:10912"), 2);
+
+        let taken = manager.apply_broker_id(&ApplyBrokerIdRequestHeader {
+            cluster_name: CLUSTER.to_string(),
+            broker_name: BROKER.to_string(),
+            applied_broker_id: 2,
+            register_check_code: "127.0.0.1:10913;1".to_string(),
+        });
+        assert_eq!(
+            taken.response_code(),
+            ResponseCode::ControllerBrokerIdInvalid
+        );
+
+        let retried = manager.apply_broker_id(&ApplyBrokerIdRequestHeader {
+            cluster_name: CLUSTER.to_string(),
+            broker_name: BROKER.to_string(),
+            applied_broker_id: 2,
+            register_check_code: "127.0.0.1:10912;1".to_string(),
+        });
+        assert!(retried.is_success());
+        assert!(retried.events().is_empty());
+        assert_eq!(
+            manager
+                .replica_info(BROKER)
+                .unwrap()
+                .next_assign_broker_id(),
+            3
+        );
+    }
+
+    #[test]
+    fn elects_new_master_when_old_master_dies() {
+        let mut manager = ReplicasInfoManager::new();
+        let heartbeat_manager = Arc::new(BrokerHeartbeatManager::new());
+        let policy = DefaultElectPolicy::new(heartbeat_manager.clone(), true);
+        let first = register_replica(&mut manager, "127.0.0.1:10911");
+        let second = register_replica(&mut manager, "127.0.0.1:10912");
+        heartbeat(&heartbeat_manager, first, 100);
+        heartbeat(&heartbeat_manager, second, 50);
+
+        let result = manager.elect_master(&elect_request(Some(first)), &policy);
+        assert!(result.is_success());
+        apply_all(&mut manager, &result);
+        assert!(manager.is_master(BROKER, first));
+        assert_eq!(manager.sync_state_info(BROKER).unwrap().master_epoch(), 1);
+
+        let still_exist = manager.elect_master(&elect_request(Some(second)), &policy);
+        assert_eq!(
+            still_exist.response_code(),
+            ResponseCode::ControllerMasterStillExist
+        );
+        assert_eq!(
+            still_exist.response().unwrap().master_broker_id,
+            Some(first)
+        );
+
+        heartbeat_manager.remove_broker(&crate::heartbeat_manager::BrokerIdentityInfo::new(
+            CLUSTER, BROKER, first,
+        ));
+        let result = manager.elect_master(&elect_request(None), &policy);
+        assert!(result.is_success());
+        apply_all(&mut manager, &result);
+        let sync_state_info = manager.sync_state_info(BROKER).unwrap();
+        assert_eq!(sync_state_info.master_broker_id(), Some(second));
+        assert_eq!(sync_state_info.master_epoch(), 2);
+        assert_eq!(sync_state_info.sync_state_set(), &HashSet::from([second]));
+        assert_eq!(
+            result.response().unwrap().master_address.as_deref(),
+            Some("127.0.0.1:10912")
+        );
+    }
+
+    #[test]
+    fn master_is_dropped_when_no_replica_can_be_elected() {
+        let mut manager = ReplicasInfoManager::new();
+        let heartbeat_manager = Arc::new(BrokerHeartbeatManager::new());
+        let policy = DefaultElectPolicy::new(heartbeat_manager.clone(), false);
+        let first = register_replica(&mut manager, "127.0.0.1:10911");
+        register_replica(&mut manager, "127.0.0.1:10912");
+        heartbeat(&heartbeat_manager, first, 100);
+        let result = manager.elect_master(&elect_request(Some(first)), &policy);
+        apply_all(&mut manager, &result);
+
+        heartbeat_manager.remove_broker(&crate::heartbeat_manager::BrokerIdentityInfo::new(
+            CLUSTER, BROKER, first,
+        ));
+        let result = manager.elect_master(&elect_request(None), &policy);
+        assert_eq!(
+            result.response_code(),
+            ResponseCode::ControllerMasterNotAvailable
+        );
+        apply_all(&mut manager, &result);
+        assert_eq!(
+            manager.sync_state_info(BROKER).unwrap().master_broker_id(),
+            None
+        );
+
+        let again = manager.elect_master(&elect_request(None), &policy);
+        assert!(again.events().is_empty());
+    }
+
+    #[test]
+    fn alter_sync_state_set_is_fenced_by_epochs() {
+        let mut manager = ReplicasInfoManager::new();
+        let heartbeat_manager = Arc::new(BrokerHeartbeatManager::new());
+        let policy = DefaultElectPolicy::new(heartbeat_manager.clone(), false);
+        let first = register_replica(&mut manager, "127.0.0.1:10911");
+        let second = register_replica(&mut manager, "127.0.0.1:10912");
+        heartbeat(&heartbeat_manager, first, 100);
+        heartbeat(&heartbeat_manager, second, 100);
+        let result = manager.elect_master(&elect_request(Some(first)), &policy);
+        apply_all(&mut manager, &result);
+        let is_alive = |cluster_name: &str, broker_name: &str, broker_id: i64| {
+            heartbeat_manager.is_broker_active(cluster_name, broker_name, broker_id)
+        };
+
+        let request = AlterSyncStateSetRequestHeader {
+            broker_name: BROKER.to_string(),
+            master_broker_id: first,
+            master_epoch: 1,
+            invoke_time: 0,
+        };
+        let stale = manager.alter_sync_state_set(
+            &request,
+            &SyncStateSet::new(HashSet::from([first, second]), 0),
+            is_alive,
+        );
+        assert_eq!(
+            stale.response_code(),
+            ResponseCode::ControllerFencedSyncStateSetEpoch
+        );
+
+        let result = manager.alter_sync_state_set(
+            &request,
+            &SyncStateSet::new(HashSet::from([first, second]), 1),
+            is_alive,
+        );
+        assert!(result.is_success());
+        apply_all(&mut manager, &result);
+        let sync_state_info = manager.sync_state_info(BROKER).unwrap();
+        assert_eq!(sync_state_info.sync_state_set_epoch(), 2);
+        assert_eq!(
+            sync_state_info.sync_state_set(),
+            &HashSet::from([first, second])
+        );
+    }
+
+    #[test]
+    fn clean_broker_data_refuses_alive_replicas() {
+        let mut manager = ReplicasInfoManager::new();
+        let heartbeat_manager = BrokerHeartbeatManager::new();
+        let first = register_replica(&mut manager, "127.0.0.1:10911");
+        let second = register_replica(&mut manager, "127.0.0.1:10912");
+        heartbeat(&heartbeat_manager, first, 100);
+        let is_alive = |cluster_name: &str, broker_name: &str, broker_id: i64| {
+            heartbeat_manager.is_broker_active(cluster_name, broker_name, broker_id)
+        };
+        let mut request = CleanControllerBrokerDataRequestHeader {
+            cluster_name: CLUSTER.to_string(),
+            broker_name: BROKER.to_string(),
+            broker_controller_ids_to_clean: Some(format!("{};{}", first, second)),
+            is_clean_living_broker: false,
+            invoke_time: 0,
+        };
+        assert_eq!(
+            manager
+                .clean_broker_data(&request, is_alive)
+                .response_code(),
+            ResponseCode::ControllerInvalidCleanBrokerMetadata
+        );
+
+        request.broker_controller_ids_to_clean = Some(second.to_string());
+        let result = manager.clean_broker_data(&request, is_alive);
+        assert!(result.is_success());
+        apply_all(&mut manager, &result);
+        assert_eq!(
+            manager.replica_info(BROKER).unwrap().all_brokers(),
+            HashSet::from([first])
+        );
+    }
+}
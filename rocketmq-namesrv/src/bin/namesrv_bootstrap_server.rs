@@ -18,6 +18,7 @@
 use std::path::PathBuf;
 
 use clap::Parser;
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
 use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
 use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_common::EnvUtils::EnvUtils;
@@ -39,12 +40,17 @@ async fn main() -> anyhow::Result<()> {
     );
     let config_file = PathBuf::from(home).join("conf").join("namesrv.toml");
     let namesrv_config = ParseConfigFile::parse_config_file::<NamesrvConfig>(config_file.clone())?;
+    // Read when the controller is embedded, see `enableControllerInNamesrv`
+    let controller_config =
+        ParseConfigFile::parse_config_file::<ControllerConfig>(config_file.clone())?;
+    // TLS settings come from namesrv.toml, the listen address from the command line
+    let mut server_config = ParseConfigFile::parse_config_file::<ServerConfig>(config_file)?;
+    server_config.listen_port = args.port;
+    server_config.bind_address = args.ip;
     Builder::new()
         .set_name_server_config(namesrv_config)
-        .set_server_config(ServerConfig {
-            listen_port: args.port,
-            bind_address: args.ip,
-        })
+        .set_server_config(server_config)
+        .set_controller_config(controller_config)
         .build()
         .boot()
         .await;
@@ -16,21 +16,37 @@
  */
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::Duration;
 
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
 use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
 use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_controller::controller_manager::ControllerManager;
+use rocketmq_controller::processor::ControllerRequestProcessor;
 use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::code::request_code::RequestCode;
+#[cfg(feature = "http")]
+use rocketmq_remoting::http::HttpServer;
+use rocketmq_remoting::remoting_server::request_dispatcher::ExecutorPoolConfig;
+use rocketmq_remoting::remoting_server::request_dispatcher::RequestDispatchConfig;
+use rocketmq_remoting::remoting_server::request_dispatcher::RequestDispatcher;
 use rocketmq_remoting::remoting_server::server::RocketMQServer;
 use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
 use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
 use rocketmq_runtime::RocketMQRuntime;
 use tokio::select;
 use tokio::sync::broadcast;
+use tracing::error;
+use tracing::info;
+#[cfg(not(feature = "http"))]
+use tracing::warn;
 
+#[cfg(feature = "http")]
+use crate::http::NamesrvHttpRequestHandler;
+use crate::kvconfig::kvconfig_replicator::KVConfigReplicator;
 use crate::processor::ClientRequestProcessor;
 use crate::processor::NameServerRequestProcessor;
 use crate::KVConfigManager;
+use crate::NamesrvConfigManager;
 use crate::RouteInfoManager;
 
 pub struct NameServerBootstrap {
@@ -40,12 +56,15 @@ pub struct NameServerBootstrap {
 pub struct Builder {
     name_server_config: Option<NamesrvConfig>,
     server_config: Option<ServerConfig>,
+    controller_config: Option<ControllerConfig>,
 }
 
 struct NameServerRuntime {
-    name_server_config: Arc<NamesrvConfig>,
+    name_server_config: Arc<parking_lot::RwLock<NamesrvConfig>>,
+    namesrv_config_manager: Arc<NamesrvConfigManager>,
     tokio_client_config: Arc<TokioClientConfig>,
     server_config: Arc<ServerConfig>,
+    controller_config: Arc<ControllerConfig>,
     route_info_manager: Arc<parking_lot::RwLock<RouteInfoManager>>,
     kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
     name_server_runtime: Option<RocketMQRuntime>,
@@ -67,8 +86,33 @@ impl NameServerRuntime {
         let (notify_conn_disconnect, _) = broadcast::channel::<SocketAddr>(100);
         let receiver = notify_conn_disconnect.subscribe();
         let request_processor = self.init_processors(receiver);
-        let server = RocketMQServer::new(self.server_config.clone());
-        server.run(request_processor).await;
+        self.start_http_server(&request_processor).await;
+        let server = RocketMQServer::new(self.server_config.clone())
+            .set_request_dispatcher(RequestDispatcher::new(&self.request_dispatch_config()));
+        // Returns on ctrl-c once the connections are drained, see `enable_shutdown_gracefully`
+        match server.run(request_processor).await {
+            Ok(()) => info!("name server remoting server terminated, shutting down"),
+            Err(err) => error!("name server remoting server failed to start: {}", err),
+        }
+    }
+
+    /// Route queries from clients get their own pool so broker registrations and admin
+    /// requests can not starve them, and the other way around.
+    fn request_dispatch_config(&self) -> RequestDispatchConfig {
+        let config = self.name_server_config.read();
+        RequestDispatchConfig::new(ExecutorPoolConfig::new(
+            "default",
+            config.default_thread_pool_nums.max(1) as usize,
+            config.default_thread_pool_queue_capacity.max(0) as usize,
+        ))
+        .register_pool(
+            ExecutorPoolConfig::new(
+                "client-request",
+                config.client_request_thread_pool_nums.max(1) as usize,
+                config.client_request_thread_pool_queue_capacity.max(0) as usize,
+            ),
+            [RequestCode::GetRouteinfoByTopic],
+        )
     }
 
     fn init_processors(
@@ -76,6 +120,12 @@ impl NameServerRuntime {
         receiver: broadcast::Receiver<SocketAddr>,
     ) -> NameServerRequestProcessor {
         RouteInfoManager::start(self.route_info_manager.clone(), receiver);
+        RouteInfoManager::start_topic_route_notifier(self.route_info_manager.clone());
+        RouteInfoManager::start_route_snapshot(self.route_info_manager.clone());
+        RouteInfoManager::start_scan_not_active_broker(
+            self.route_info_manager.clone(),
+            self.namesrv_config_manager.clone(),
+        );
 
         let client_request_processor = ClientRequestProcessor::new(
             self.route_info_manager.clone(),
@@ -86,24 +136,68 @@ impl NameServerRuntime {
             crate::processor::default_request_processor::DefaultRequestProcessor::new(
                 self.route_info_manager.clone(),
                 self.kvconfig_manager.clone(),
+                self.namesrv_config_manager.clone(),
             );
-
-        let route_info_manager_arc = self.route_info_manager.clone();
-        self.name_server_runtime
-            .as_ref()
-            .unwrap()
-            .schedule_at_fixed_rate(
-                move || {
-                    route_info_manager_arc.write().scan_not_active_broker();
-                },
-                Some(Duration::from_secs(5)),
-                Duration::from_secs(5),
-            );
+        let controller_request_processor = self.init_controller();
         NameServerRequestProcessor {
             client_request_processor: Arc::new(client_request_processor),
             default_request_processor: Arc::new(default_request_processor),
+            kvconfig_replicator: controller_request_processor
+                .as_ref()
+                .and_then(|_| self.init_kvconfig_replicator()),
+            controller_request_processor,
         }
     }
+
+    /// Serves the HTTP management API on `http_listen_address`.
+    #[cfg(feature = "http")]
+    async fn start_http_server(&self, request_processor: &NameServerRequestProcessor) {
+        let Some(address) = self.name_server_config.read().http_listen_address.clone() else {
+            return;
+        };
+        match HttpServer::bind(&address).await {
+            Ok(server) => {
+                tokio::spawn(server.run(Arc::new(NamesrvHttpRequestHandler::new(
+                    self.route_info_manager.clone(),
+                    self.kvconfig_manager.clone(),
+                    request_processor.client_request_processor.clone(),
+                    request_processor.kvconfig_replicator.clone(),
+                ))));
+            }
+            Err(err) => error!("start http server on {} failed: {}", address, err),
+        }
+    }
+
+    #[cfg(not(feature = "http"))]
+    async fn start_http_server(&self, _request_processor: &NameServerRequestProcessor) {
+        if self.name_server_config.read().http_listen_address.is_some() {
+            warn!("http_listen_address is ignored, the name server is built without `http`");
+        }
+    }
+
+    fn init_controller(&self) -> Option<ControllerRequestProcessor> {
+        if !self.name_server_config.read().enable_controller_in_namesrv {
+            return None;
+        }
+        let controller_manager = Arc::new(ControllerManager::new(
+            self.controller_config.clone(),
+            Arc::new(self.remoting_client.clone()),
+        ));
+        controller_manager.start();
+        info!("controller embedded in name server started");
+        Some(ControllerRequestProcessor::new(controller_manager))
+    }
+
+    /// The embedded controllers forming a Raft group also replicate the KV config.
+    fn init_kvconfig_replicator(&self) -> Option<Arc<KVConfigReplicator>> {
+        let kvconfig_replicator = KVConfigReplicator::new(
+            &self.controller_config,
+            self.kvconfig_manager.clone(),
+            Arc::new(self.remoting_client.clone()),
+        )?;
+        kvconfig_replicator.start();
+        Some(Arc::new(kvconfig_replicator))
+    }
 }
 
 impl Drop for NameServerRuntime {
@@ -125,6 +219,7 @@ impl Builder {
         Builder {
             name_server_config: None,
             server_config: None,
+            controller_config: None,
         }
     }
 
@@ -138,24 +233,38 @@ impl Builder {
         self
     }
 
+    /// Used when `enable_controller_in_namesrv` is set.
+    pub fn set_controller_config(mut self, controller_config: ControllerConfig) -> Self {
+        self.controller_config = Some(controller_config);
+        self
+    }
+
     pub fn build(self) -> NameServerBootstrap {
-        let name_server_config = Arc::new(self.name_server_config.unwrap());
+        let name_server_config =
+            Arc::new(parking_lot::RwLock::new(self.name_server_config.unwrap()));
+        let namesrv_config_manager =
+            Arc::new(NamesrvConfigManager::new(name_server_config.clone()));
+        namesrv_config_manager.load();
         let runtime = RocketMQRuntime::new_multi(10, "namesrv-thread");
         let tokio_client_config = Arc::new(TokioClientConfig::default());
         let remoting_client = RocketmqDefaultClient::new(
             tokio_client_config.clone(),
             DefaultRemotingRequestProcessor,
         );
+        let mut route_info_manager = RouteInfoManager::new(
+            name_server_config.clone(),
+            Arc::new(remoting_client.clone()),
+        );
+        route_info_manager.load_route_snapshot();
 
         NameServerBootstrap {
             name_server_runtime: NameServerRuntime {
                 name_server_config: name_server_config.clone(),
+                namesrv_config_manager,
                 tokio_client_config,
                 server_config: Arc::new(self.server_config.unwrap()),
-                route_info_manager: Arc::new(parking_lot::RwLock::new(RouteInfoManager::new(
-                    name_server_config.clone(),
-                    Arc::new(remoting_client.clone()),
-                ))),
+                controller_config: Arc::new(self.controller_config.unwrap_or_default()),
+                route_info_manager: Arc::new(parking_lot::RwLock::new(route_info_manager)),
                 kvconfig_manager: Arc::new(parking_lot::RwLock::new(KVConfigManager::new(
                     name_server_config,
                 ))),
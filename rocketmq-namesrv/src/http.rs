@@ -0,0 +1,481 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! HTTP/JSON management API of the name server, enabled by the `http` feature and served on
+//! `http_listen_address`.
+//!
+//! | Method | Path                          | Response                           |
+//! |--------|-------------------------------|------------------------------------|
+//! | GET    | `/health`                     | `{"status":"UP"}`                  |
+//! | GET    | `/ready`                      | 503 until routes are served        |
+//! | GET    | `/cluster`                    | cluster info                       |
+//! | GET    | `/brokers`                    | broker liveness                    |
+//! | GET    | `/brokers/events?since={seq}` | broker liveness events after `seq` |
+//! | GET    | `/topics/{topic}/route`       | topic route data                   |
+//! | GET    | `/kv/{namespace}`             | all KV configs of the namespace    |
+//! | GET    | `/kv/{namespace}/{key}`       | `{"value":"..."}`                  |
+//! | PUT    | `/kv/{namespace}/{key}`       | body `{"value":"..."}`             |
+//! | DELETE | `/kv/{namespace}/{key}`       |                                    |
+
+use std::sync::Arc;
+
+use rocketmq_common::TimeUtils;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::http::HttpRequest;
+use rocketmq_remoting::http::HttpRequestHandler;
+use rocketmq_remoting::http::HttpResponse;
+use rocketmq_remoting::http::Method;
+use rocketmq_remoting::http::StatusCode;
+use rocketmq_remoting::protocol::header::namesrv::kv_config_header::DeleteKVConfigRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::kv_config_header::PutKVConfigRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::DataVersion;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::kvconfig::kvconfig_replicator::KVConfigReplicator;
+use crate::processor::ClientRequestProcessor;
+use crate::KVConfigManager;
+use crate::RouteInfoManager;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BrokerLiveness {
+    cluster_name: String,
+    broker_addr: String,
+    last_update_timestamp: i64,
+    heartbeat_timeout_millis: i64,
+    /// Expired brokers are removed by the next `scan_not_active_broker`.
+    expired: bool,
+    /// Restored from the route snapshot and not registered since.
+    stale: bool,
+    ha_server_addr: String,
+    data_version: DataVersion,
+}
+
+#[derive(Debug, Deserialize)]
+struct KVConfigValue {
+    value: String,
+}
+
+pub(crate) struct NamesrvHttpRequestHandler {
+    route_info_manager: Arc<parking_lot::RwLock<RouteInfoManager>>,
+    kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
+    client_request_processor: Arc<ClientRequestProcessor>,
+    /// KV config changes go through the Raft group when set, like the remoting requests.
+    kvconfig_replicator: Option<Arc<KVConfigReplicator>>,
+}
+
+impl NamesrvHttpRequestHandler {
+    pub(crate) fn new(
+        route_info_manager: Arc<parking_lot::RwLock<RouteInfoManager>>,
+        kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
+        client_request_processor: Arc<ClientRequestProcessor>,
+        kvconfig_replicator: Option<Arc<KVConfigReplicator>>,
+    ) -> Self {
+        NamesrvHttpRequestHandler {
+            route_info_manager,
+            kvconfig_manager,
+            client_request_processor,
+            kvconfig_replicator,
+        }
+    }
+
+    fn ready(&self) -> HttpResponse {
+        let ready = self.client_request_processor.is_namesrv_ready();
+        let status = if ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        HttpResponse::json_with_status(status, &json!({ "ready": ready }))
+    }
+
+    fn brokers(&self) -> HttpResponse {
+        let now = TimeUtils::get_current_millis() as i64;
+        let route_info_manager = self.route_info_manager.read();
+        let mut brokers = route_info_manager
+            .broker_live_table
+            .iter()
+            .map(|(broker_addr_info, broker_live_info)| BrokerLiveness {
+                cluster_name: broker_addr_info.cluster_name.clone(),
+                broker_addr: broker_addr_info.broker_addr.clone(),
+                last_update_timestamp: broker_live_info.last_update_timestamp(),
+                heartbeat_timeout_millis: broker_live_info.heartbeat_timeout_millis(),
+                expired: broker_live_info.last_update_timestamp()
+                    + broker_live_info.heartbeat_timeout_millis()
+                    < now,
+                stale: route_info_manager.stale_brokers.contains(broker_addr_info),
+                ha_server_addr: broker_live_info.ha_server_addr().to_string(),
+                data_version: broker_live_info.data_version().clone(),
+            })
+            .collect::<Vec<_>>();
+        drop(route_info_manager);
+        brokers.sort_by(|a, b| {
+            (&a.cluster_name, &a.broker_addr).cmp(&(&b.cluster_name, &b.broker_addr))
+        });
+        HttpResponse::json(&brokers)
+    }
+
+    fn broker_liveness_events(&self, request: &HttpRequest) -> HttpResponse {
+        let since = match request.query("since").map(str::parse::<u64>) {
+            None => 0,
+            Some(Ok(since)) => since,
+            Some(Err(_)) => {
+                return HttpResponse::error(StatusCode::BAD_REQUEST, "since must be a number")
+            }
+        };
+        let broker_liveness_events = self
+            .route_info_manager
+            .read()
+            .broker_liveness_events
+            .clone();
+        HttpResponse::json(&broker_liveness_events.events_since(since))
+    }
+
+    fn topic_route(&self, topic: &str) -> HttpResponse {
+        match self
+            .route_info_manager
+            .read()
+            .pickup_topic_route_data(topic)
+        {
+            Some(topic_route_data) => HttpResponse::json(&topic_route_data),
+            None => HttpResponse::error(
+                StatusCode::NOT_FOUND,
+                format!("no route info of topic {}", topic),
+            ),
+        }
+    }
+
+    fn kv_configs(&self, namespace: &str) -> HttpResponse {
+        match self
+            .kvconfig_manager
+            .read()
+            .get_config_table()
+            .get(namespace)
+        {
+            Some(kv_table) => HttpResponse::json(kv_table),
+            None => HttpResponse::error(
+                StatusCode::NOT_FOUND,
+                format!("no config item, namespace: {}", namespace),
+            ),
+        }
+    }
+
+    fn kv_config(&self, namespace: &str, key: &str) -> HttpResponse {
+        match self.kvconfig_manager.read().get_kvconfig(namespace, key) {
+            Some(value) => HttpResponse::json(&json!({ "value": value })),
+            None => HttpResponse::error(
+                StatusCode::NOT_FOUND,
+                format!("no config item, namespace: {} key: {}", namespace, key),
+            ),
+        }
+    }
+
+    async fn put_kv_config(
+        &self,
+        namespace: &str,
+        key: &str,
+        request: &HttpRequest,
+    ) -> HttpResponse {
+        let value = match request.json::<KVConfigValue>() {
+            Ok(body) => body.value,
+            Err(response) => return response,
+        };
+        match &self.kvconfig_replicator {
+            Some(kvconfig_replicator) => {
+                let mut request = RemotingCommand::create_request_command(
+                    RequestCode::PutKvConfig,
+                    PutKVConfigRequestHeader::new(namespace, key, value),
+                );
+                request.make_custom_header_to_net();
+                Self::replicated(kvconfig_replicator.replicate(request).await)
+            }
+            None => {
+                self.kvconfig_manager
+                    .write()
+                    .put_kv_config(namespace, key, value);
+                HttpResponse::json(&json!({}))
+            }
+        }
+    }
+
+    async fn delete_kv_config(&self, namespace: &str, key: &str) -> HttpResponse {
+        match &self.kvconfig_replicator {
+            Some(kvconfig_replicator) => {
+                let mut request = RemotingCommand::create_request_command(
+                    RequestCode::DeleteKvConfig,
+                    DeleteKVConfigRequestHeader::new(namespace, key),
+                );
+                request.make_custom_header_to_net();
+                Self::replicated(kvconfig_replicator.replicate(request).await)
+            }
+            None => {
+                self.kvconfig_manager
+                    .write()
+                    .delete_kv_config(namespace, key);
+                HttpResponse::json(&json!({}))
+            }
+        }
+    }
+
+    fn replicated(response: RemotingCommand) -> HttpResponse {
+        if response.code() == i32::from(RemotingSysResponseCode::Success) {
+            return HttpResponse::json(&json!({}));
+        }
+        HttpResponse::error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            response.remark().cloned().unwrap_or_default(),
+        )
+    }
+}
+
+impl HttpRequestHandler for NamesrvHttpRequestHandler {
+    async fn handle(&self, request: HttpRequest) -> HttpResponse {
+        let segments = request.path_segments();
+        let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+        match (request.method(), segments.as_slice()) {
+            (&Method::GET, ["health"]) => HttpResponse::json(&json!({ "status": "UP" })),
+            (&Method::GET, ["ready"]) => self.ready(),
+            (&Method::GET, ["cluster"]) => {
+                HttpResponse::json(&self.route_info_manager.read().get_all_cluster_info())
+            }
+            (&Method::GET, ["brokers"]) => self.brokers(),
+            (&Method::GET, ["brokers", "events"]) => self.broker_liveness_events(&request),
+            (&Method::GET, ["topics", topic, "route"]) => self.topic_route(topic),
+            (&Method::GET, ["kv", namespace]) => self.kv_configs(namespace),
+            (&Method::GET, ["kv", namespace, key]) => self.kv_config(namespace, key),
+            (&Method::PUT, ["kv", namespace, key]) => {
+                self.put_kv_config(namespace, key, &request).await
+            }
+            (&Method::DELETE, ["kv", namespace, key]) => {
+                self.delete_kv_config(namespace, key).await
+            }
+            (
+                _,
+                ["health"]
+                | ["ready"]
+                | ["cluster"]
+                | ["brokers"]
+                | ["brokers", "events"]
+                | ["topics", _, "route"]
+                | ["kv", _]
+                | ["kv", _, _],
+            ) => HttpResponse::method_not_allowed(),
+            _ => HttpResponse::not_found(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use rocketmq_common::common::config::TopicConfig;
+    use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+    use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+    use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
+    use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+    use serde_json::Value;
+
+    use super::*;
+
+    fn handler(namesrv_config: NamesrvConfig) -> NamesrvHttpRequestHandler {
+        let namesrv_config = Arc::new(parking_lot::RwLock::new(namesrv_config));
+        let route_info_manager = Arc::new(parking_lot::RwLock::new(RouteInfoManager::new(
+            namesrv_config.clone(),
+            Arc::new(RocketmqDefaultClient::new(
+                Arc::new(TokioClientConfig::default()),
+                DefaultRemotingRequestProcessor,
+            )),
+        )));
+        let kvconfig_manager = Arc::new(parking_lot::RwLock::new(KVConfigManager::new(
+            namesrv_config.clone(),
+        )));
+        let client_request_processor = Arc::new(ClientRequestProcessor::new(
+            route_info_manager.clone(),
+            namesrv_config,
+            kvconfig_manager.clone(),
+        ));
+        NamesrvHttpRequestHandler::new(
+            route_info_manager,
+            kvconfig_manager,
+            client_request_processor,
+            None,
+        )
+    }
+
+    fn handle(
+        runtime: &tokio::runtime::Runtime,
+        handler: &NamesrvHttpRequestHandler,
+        method: Method,
+        path: &str,
+        body: &str,
+    ) -> (StatusCode, Value) {
+        let response =
+            runtime.block_on(handler.handle(HttpRequest::new(method, path, body.to_string())));
+        (
+            response.status(),
+            serde_json::from_slice(response.body()).unwrap(),
+        )
+    }
+
+    // Dropping the remoting client inside the runtime panics, so the handler lives outside it
+    #[test]
+    fn serves_route_info_and_kv_config() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let dir = std::env::temp_dir().join("namesrv_http_serves_route_info_and_kv_config");
+        let _ = std::fs::remove_dir_all(&dir);
+        let handler = handler(NamesrvConfig {
+            kv_config_path: dir.join("kvConfig.json").to_string_lossy().into_owned(),
+            ..NamesrvConfig::default()
+        });
+
+        let mut topic_config_wrapper = TopicConfigAndMappingSerializeWrapper::default();
+        for topic in ["TopicTest", "TopicOther"] {
+            topic_config_wrapper
+                .topic_config_serialize_wrapper
+                .topic_config_table
+                .insert(topic.to_string(), TopicConfig::new(topic));
+        }
+        handler.route_info_manager.write().register_broker(
+            "DefaultCluster".to_string(),
+            "127.0.0.1:10911".to_string(),
+            "broker-a".to_string(),
+            0,
+            "127.0.0.1:10912".to_string(),
+            None,
+            None,
+            None,
+            topic_config_wrapper,
+            Vec::new(),
+            "127.0.0.1:50000".parse::<SocketAddr>().unwrap(),
+        );
+
+        let (status, body) = handle(&runtime, &handler, Method::GET, "/health", "");
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "UP");
+        let (status, _) = handle(&runtime, &handler, Method::GET, "/ready", "");
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, body) = handle(&runtime, &handler, Method::GET, "/cluster", "");
+        assert_eq!(body["clusterAddrTable"]["DefaultCluster"][0], "broker-a");
+        let (_, body) = handle(&runtime, &handler, Method::GET, "/brokers", "");
+        assert_eq!(body[0]["brokerAddr"], "127.0.0.1:10911");
+        assert_eq!(body[0]["expired"], false);
+        assert_eq!(body[0]["stale"], false);
+        assert!(body[0]["lastUpdateTimestamp"].as_i64().unwrap() > 0);
+        let (_, body) = handle(&runtime, &handler, Method::GET, "/brokers/events", "");
+        assert_eq!(body[0]["type"], "online");
+        assert_eq!(body[0]["brokerAddr"], "127.0.0.1:10911");
+        let (_, body) = handle(
+            &runtime,
+            &handler,
+            Method::GET,
+            "/brokers/events?since=1",
+            "",
+        );
+        assert_eq!(body.as_array().unwrap().len(), 0);
+
+        let (status, body) = handle(
+            &runtime,
+            &handler,
+            Method::GET,
+            "/topics/TopicTest/route",
+            "",
+        );
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["brokerDatas"][0]["brokerName"], "broker-a");
+        let (status, _) = handle(&runtime, &handler, Method::GET, "/topics/Unknown/route", "");
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = handle(
+            &runtime,
+            &handler,
+            Method::PUT,
+            "/kv/ORDER_TOPIC_CONFIG/TopicTest",
+            r#"{"value":"broker-a:4"}"#,
+        );
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = handle(
+            &runtime,
+            &handler,
+            Method::GET,
+            "/kv/ORDER_TOPIC_CONFIG/TopicTest",
+            "",
+        );
+        assert_eq!(body["value"], "broker-a:4");
+        let (_, body) = handle(
+            &runtime,
+            &handler,
+            Method::GET,
+            "/kv/ORDER_TOPIC_CONFIG",
+            "",
+        );
+        assert_eq!(body["TopicTest"], "broker-a:4");
+        let (status, _) = handle(
+            &runtime,
+            &handler,
+            Method::PUT,
+            "/kv/ORDER_TOPIC_CONFIG/TopicTest",
+            "broker-a:4",
+        );
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        handle(
+            &runtime,
+            &handler,
+            Method::DELETE,
+            "/kv/ORDER_TOPIC_CONFIG/TopicTest",
+            "",
+        );
+        let (status, _) = handle(
+            &runtime,
+            &handler,
+            Method::GET,
+            "/kv/ORDER_TOPIC_CONFIG/TopicTest",
+            "",
+        );
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = handle(&runtime, &handler, Method::POST, "/cluster", "");
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        let (status, _) = handle(&runtime, &handler, Method::GET, "/unknown", "");
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn not_ready_while_waiting_for_service() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let handler = handler(NamesrvConfig {
+            need_wait_for_service: true,
+            wait_seconds_for_service: 60,
+            ..NamesrvConfig::default()
+        });
+        let (status, body) = handle(&runtime, &handler, Method::GET, "/ready", "");
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["ready"], false);
+    }
+}
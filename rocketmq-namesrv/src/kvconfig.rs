@@ -21,6 +21,7 @@ use serde::Deserialize;
 use serde::Serialize;
 
 pub mod kvconfig_mananger;
+pub mod kvconfig_replicator;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct KVConfigSerializeWrapper {
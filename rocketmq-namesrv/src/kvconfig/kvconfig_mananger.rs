@@ -32,7 +32,7 @@ pub struct KVConfigManager {
     pub(crate) config_table:
         HashMap<String /* Namespace */, HashMap<String /* Key */, String /* Value */>>,
 
-    pub(crate) namesrv_config: Arc<NamesrvConfig>,
+    pub(crate) namesrv_config: Arc<parking_lot::RwLock<NamesrvConfig>>,
 }
 
 impl KVConfigManager {
@@ -45,7 +45,7 @@ impl KVConfigManager {
     /// # Returns
     ///
     /// A new `KVConfigManager` instance.
-    pub fn new(namesrv_config: Arc<NamesrvConfig>) -> KVConfigManager {
+    pub fn new(namesrv_config: Arc<parking_lot::RwLock<NamesrvConfig>>) -> KVConfigManager {
         KVConfigManager {
             config_table: HashMap::new(),
             namesrv_config,
@@ -66,7 +66,7 @@ impl KVConfigManager {
     /// # Returns
     ///
     /// A reference to the Namesrv configuration.
-    pub fn get_namesrv_config(&self) -> &NamesrvConfig {
+    pub fn get_namesrv_config(&self) -> &Arc<parking_lot::RwLock<NamesrvConfig>> {
         &self.namesrv_config
     }
 }
@@ -74,7 +74,7 @@ impl KVConfigManager {
 impl KVConfigManager {
     /// Loads key-value configurations from a file.
     pub fn load(&mut self) {
-        let result = FileUtils::file_to_string(self.namesrv_config.kv_config_path.as_str());
+        let result = FileUtils::file_to_string(self.namesrv_config.read().kv_config_path.as_str());
         if let Ok(content) = result {
             let wrapper =
                 SerdeJsonUtils::decode::<KVConfigSerializeWrapper>(content.as_bytes()).unwrap();
@@ -94,7 +94,7 @@ impl KVConfigManager {
 
         let result = FileUtils::string_to_file(
             content.as_str(),
-            self.namesrv_config.kv_config_path.as_str(),
+            self.namesrv_config.read().kv_config_path.as_str(),
         );
         if let Err(err) = result {
             error!("persist KV config failed: {}", err);
@@ -0,0 +1,384 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
+use rocketmq_controller::raft::message::RaftEnvelope;
+use rocketmq_controller::raft::node::RaftNode;
+use rocketmq_controller::raft::parse_peers;
+use rocketmq_controller::raft::transport::RaftTransport;
+use rocketmq_controller::raft::transport::RemotingRaftTransport;
+use rocketmq_controller::raft::NodeId;
+use rocketmq_controller::raft::RaftError;
+use rocketmq_controller::raft::StateMachine;
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::protocol::header::namesrv::kv_config_header::DeleteKVConfigRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::kv_config_header::PutKVConfigRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::error;
+use tracing::info;
+
+use crate::KVConfigManager;
+
+const FORWARD_TO_LEADER_TIMEOUT_MILLIS: u64 = 3000;
+
+/// A change of the KV config, as replicated in the Raft log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum KVConfigCommand {
+    Put {
+        namespace: String,
+        key: String,
+        value: String,
+    },
+    Delete {
+        namespace: String,
+        key: String,
+    },
+}
+
+/// Applies the replicated changes to the local [`KVConfigManager`], which still persists them
+/// to `kv_config_path`.
+pub struct KVConfigStateMachine {
+    kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
+}
+
+impl KVConfigStateMachine {
+    pub fn new(kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>) -> Self {
+        Self { kvconfig_manager }
+    }
+}
+
+impl StateMachine for KVConfigStateMachine {
+    fn apply(&mut self, index: u64, data: &[u8]) {
+        match serde_json::from_slice::<KVConfigCommand>(data) {
+            Ok(KVConfigCommand::Put {
+                namespace,
+                key,
+                value,
+            }) => self
+                .kvconfig_manager
+                .write()
+                .put_kv_config(namespace, key, value),
+            Ok(KVConfigCommand::Delete { namespace, key }) => self
+                .kvconfig_manager
+                .write()
+                .delete_kv_config(namespace, key),
+            Err(err) => error!(
+                "failed to decode KV config command at index {}: {}",
+                index, err
+            ),
+        }
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        serde_json::to_vec(self.kvconfig_manager.read().get_config_table())
+            .expect("KV config is always serializable")
+    }
+
+    fn restore(&mut self, data: &[u8]) {
+        match serde_json::from_slice::<HashMap<String, HashMap<String, String>>>(data) {
+            Ok(config_table) => {
+                let mut kvconfig_manager = self.kvconfig_manager.write();
+                kvconfig_manager.config_table = config_table;
+                kvconfig_manager.persist();
+            }
+            Err(err) => error!("failed to restore KV config from snapshot: {}", err),
+        }
+    }
+}
+
+/// Replicates the KV config of the name servers through a Raft group of their own, formed by
+/// the members of the embedded controller group, see `controller_raft_peers`.
+///
+/// Changes go through the leader: the other members forward `PutKvConfig` and
+/// `DeleteKvConfig` to it. Reads are served from the local copy.
+pub struct KVConfigReplicator {
+    raft_group: String,
+    raft_node: Arc<RaftNode<KVConfigStateMachine>>,
+    raft_peers: BTreeMap<NodeId, String>,
+    remoting_client: Arc<RocketmqDefaultClient>,
+}
+
+impl KVConfigReplicator {
+    /// `None` when `controller_raft_peers` is unset.
+    pub fn new(
+        controller_config: &ControllerConfig,
+        kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
+        remoting_client: Arc<RocketmqDefaultClient>,
+    ) -> Option<Self> {
+        let raft_peers = parse_peers(controller_config.controller_raft_peers.as_deref()?);
+        let raft_transport = Arc::new(RemotingRaftTransport::new(
+            remoting_client.clone(),
+            raft_peers.clone(),
+        ));
+        Some(Self::new_with_raft_transport(
+            controller_config,
+            kvconfig_manager,
+            remoting_client,
+            raft_transport,
+        ))
+    }
+
+    pub fn new_with_raft_transport(
+        controller_config: &ControllerConfig,
+        kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
+        remoting_client: Arc<RocketmqDefaultClient>,
+        raft_transport: Arc<dyn RaftTransport>,
+    ) -> Self {
+        let raft_group = format!("{}-KVConfig", controller_config.controller_raft_group);
+        let raft_node = RaftNode::with_controller_config(
+            controller_config,
+            &raft_group,
+            KVConfigStateMachine::new(kvconfig_manager),
+            raft_transport,
+        );
+        Self {
+            raft_group,
+            raft_node: Arc::new(raft_node),
+            raft_peers: controller_config
+                .controller_raft_peers
+                .as_deref()
+                .map(parse_peers)
+                .unwrap_or_default(),
+            remoting_client,
+        }
+    }
+
+    pub fn raft_group(&self) -> &str {
+        &self.raft_group
+    }
+
+    pub fn raft_node(&self) -> &Arc<RaftNode<KVConfigStateMachine>> {
+        &self.raft_node
+    }
+
+    pub fn start(&self) {
+        self.raft_node.start();
+        info!("KV config replication started");
+    }
+
+    /// Feeds a message of the KV config group, returning it back when it belongs to another
+    /// group.
+    pub fn on_raft_message(&self, envelope: RaftEnvelope) -> Option<RaftEnvelope> {
+        if envelope.group != self.raft_group {
+            return Some(envelope);
+        }
+        self.raft_node.step(envelope);
+        None
+    }
+
+    /// Serves a `PutKvConfig` or `DeleteKvConfig` request once the change is committed.
+    pub async fn replicate(&self, request: RemotingCommand) -> RemotingCommand {
+        let Some(command) = Self::decode_command(&request) else {
+            return RemotingCommand::create_response_command_with_code_remark(
+                RemotingSysResponseCode::SystemError,
+                "namespace or key is empty",
+            );
+        };
+        let data = serde_json::to_vec(&command).expect("KV config is always serializable");
+        match self.raft_node.propose(data).await {
+            Ok(()) => RemotingCommand::create_response_command(),
+            Err(RaftError::NotLeader(Some(leader_id))) => {
+                self.forward_to_leader(&leader_id, request).await
+            }
+            Err(err) => RemotingCommand::create_response_command_with_code_remark(
+                RemotingSysResponseCode::SystemError,
+                format!("failed to replicate KV config: {}", err),
+            ),
+        }
+    }
+
+    fn decode_command(request: &RemotingCommand) -> Option<KVConfigCommand> {
+        let command = if request.code() == RequestCode::PutKvConfig.to_i32() {
+            let header = request.decode_command_custom_header::<PutKVConfigRequestHeader>()?;
+            KVConfigCommand::Put {
+                namespace: header.namespace,
+                key: header.key,
+                value: header.value,
+            }
+        } else {
+            let header = request.decode_command_custom_header::<DeleteKVConfigRequestHeader>()?;
+            KVConfigCommand::Delete {
+                namespace: header.namespace,
+                key: header.key,
+            }
+        };
+        let (KVConfigCommand::Put { namespace, key, .. }
+        | KVConfigCommand::Delete { namespace, key }) = &command;
+        (!namespace.is_empty() && !key.is_empty()).then_some(command)
+    }
+
+    async fn forward_to_leader(
+        &self,
+        leader_id: &NodeId,
+        request: RemotingCommand,
+    ) -> RemotingCommand {
+        let Some(leader_address) = self.raft_peers.get(leader_id) else {
+            return RemotingCommand::create_response_command_with_code_remark(
+                RemotingSysResponseCode::SystemError,
+                format!("unknown KV config leader {}", leader_id),
+            );
+        };
+        let request = RemotingCommand::create_remoting_command(request.code())
+            .set_ext_fields(request.ext_fields().cloned().unwrap_or_default());
+        match self
+            .remoting_client
+            .invoke_async(
+                Some(leader_address.clone()),
+                request,
+                FORWARD_TO_LEADER_TIMEOUT_MILLIS,
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => RemotingCommand::create_response_command_with_code_remark(
+                RemotingSysResponseCode::SystemError,
+                format!(
+                    "failed to forward KV config change to leader {}: {}",
+                    leader_address, err
+                ),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+    use rocketmq_controller::raft::core::RaftOptions;
+    use rocketmq_controller::raft::local_cluster::LocalCluster;
+
+    use super::*;
+
+    fn kvconfig_manager(kv_config_path: String) -> Arc<parking_lot::RwLock<KVConfigManager>> {
+        let namesrv_config = NamesrvConfig {
+            kv_config_path,
+            ..NamesrvConfig::default()
+        };
+        Arc::new(parking_lot::RwLock::new(KVConfigManager::new(Arc::new(
+            parking_lot::RwLock::new(namesrv_config),
+        ))))
+    }
+
+    fn encode(command: KVConfigCommand) -> Vec<u8> {
+        serde_json::to_vec(&command).unwrap()
+    }
+
+    #[test]
+    fn kv_config_changes_reach_every_name_server() {
+        let dir = std::env::temp_dir().join("kvconfig_replicator_changes_reach_every_member");
+        let _ = std::fs::remove_dir_all(&dir);
+        let managers = (0..3)
+            .map(|index| {
+                kvconfig_manager(
+                    dir.join(format!("kvConfig-{}.json", index))
+                        .to_string_lossy()
+                        .into_owned(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let next_manager = std::cell::Cell::new(0);
+        let state_machines = managers.clone();
+        let mut cluster = LocalCluster::new(
+            3,
+            RaftOptions {
+                snapshot_threshold: 2,
+                ..RaftOptions::default()
+            },
+            move || {
+                // Restarted members get a fresh copy, as after losing their local file
+                let index = next_manager.get();
+                next_manager.set(index + 1);
+                KVConfigStateMachine::new(
+                    state_machines
+                        .get(index)
+                        .cloned()
+                        .unwrap_or_else(|| kvconfig_manager(String::new())),
+                )
+            },
+        );
+        let leader = cluster.tick_until_leader(100).unwrap();
+        cluster
+            .propose(
+                &leader,
+                encode(KVConfigCommand::Put {
+                    namespace: "ORDER_TOPIC_CONFIG".to_string(),
+                    key: "TopicTest".to_string(),
+                    value: "broker-a:4".to_string(),
+                }),
+            )
+            .unwrap();
+        cluster
+            .propose(
+                &leader,
+                encode(KVConfigCommand::Put {
+                    namespace: "ORDER_TOPIC_CONFIG".to_string(),
+                    key: "TopicOther".to_string(),
+                    value: "broker-b:4".to_string(),
+                }),
+            )
+            .unwrap();
+        cluster
+            .propose(
+                &leader,
+                encode(KVConfigCommand::Delete {
+                    namespace: "ORDER_TOPIC_CONFIG".to_string(),
+                    key: "TopicOther".to_string(),
+                }),
+            )
+            .unwrap();
+        cluster.tick();
+        for manager in &managers {
+            let manager = manager.read();
+            assert_eq!(
+                manager.get_kvconfig("ORDER_TOPIC_CONFIG", "TopicTest"),
+                Some("broker-a:4".to_string())
+            );
+            assert_eq!(
+                manager.get_kvconfig("ORDER_TOPIC_CONFIG", "TopicOther"),
+                None
+            );
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn snapshot_replaces_the_whole_kv_config() {
+        let dir = std::env::temp_dir().join("kvconfig_replicator_snapshot");
+        let source = kvconfig_manager(dir.join("source.json").to_string_lossy().into_owned());
+        source.write().put_kv_config("namespace", "key", "value");
+        let snapshot = KVConfigStateMachine::new(source).snapshot();
+
+        let target = kvconfig_manager(dir.join("target.json").to_string_lossy().into_owned());
+        target.write().put_kv_config("namespace", "stale", "value");
+        KVConfigStateMachine::new(target.clone()).restore(&snapshot);
+        assert_eq!(
+            target.read().get_kvconfig("namespace", "key"),
+            Some("value".to_string())
+        );
+        assert_eq!(target.read().get_kvconfig("namespace", "stale"), None);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -18,11 +18,15 @@
 #![allow(dead_code)]
 
 pub use self::kvconfig::kvconfig_mananger::KVConfigManager;
+pub use self::namesrv_config_manager::NamesrvConfigManager;
 pub use self::namesrv_config_parse::parse_command_and_config_file;
 pub use self::route::route_info_manager::RouteInfoManager;
 
 pub mod bootstrap;
+#[cfg(feature = "http")]
+mod http;
 mod kvconfig;
+mod namesrv_config_manager;
 mod namesrv_config_parse;
 pub mod processor;
 mod route;
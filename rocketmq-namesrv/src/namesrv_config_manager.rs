@@ -0,0 +1,182 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+use rocketmq_common::FileUtils;
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+use tracing::info;
+use tracing::warn;
+
+/// Owns the name server configuration shared by the running components and applies the
+/// updates sent with `UpdateNamesrvConfig`.
+///
+/// Updated properties are persisted to `config_store_path` and applied again by [`load`] on
+/// the next start, so they take precedence over the configuration file.
+///
+/// [`load`]: NamesrvConfigManager::load
+pub struct NamesrvConfigManager {
+    namesrv_config: Arc<parking_lot::RwLock<NamesrvConfig>>,
+    config_changed: Notify,
+}
+
+impl NamesrvConfigManager {
+    pub fn new(namesrv_config: Arc<parking_lot::RwLock<NamesrvConfig>>) -> Self {
+        NamesrvConfigManager {
+            namesrv_config,
+            config_changed: Notify::new(),
+        }
+    }
+
+    pub fn namesrv_config(&self) -> &Arc<parking_lot::RwLock<NamesrvConfig>> {
+        &self.namesrv_config
+    }
+
+    /// Applies the properties persisted by earlier updates.
+    pub fn load(&self) {
+        let properties = self.persisted_properties();
+        if properties.is_empty() {
+            return;
+        }
+        let properties = properties
+            .into_iter()
+            .filter(|(key, _)| {
+                let updatable = NamesrvConfig::is_updatable_property(key);
+                if !updatable {
+                    warn!("ignore persisted name server config {}", key);
+                }
+                updatable
+            })
+            .collect::<HashMap<_, _>>();
+        match self.namesrv_config.write().update(&properties) {
+            Ok(()) => info!("load persisted name server config: {:?}", properties),
+            Err(err) => warn!("load persisted name server config failed: {}", err),
+        }
+    }
+
+    /// Updates the running configuration and persists the changed properties. Keys in the
+    /// config black list are expected to be rejected by the caller.
+    pub fn update(&self, properties: &HashMap<String, String>) -> Result<(), String> {
+        let config_store_path = {
+            let mut namesrv_config = self.namesrv_config.write();
+            namesrv_config.update(properties)?;
+            namesrv_config.config_store_path.clone()
+        };
+        info!("name server config updated: {:?}", properties);
+        self.config_changed.notify_waiters();
+
+        let mut persisted = self.persisted_properties();
+        persisted.extend(properties.clone());
+        FileUtils::string_to_file(
+            mix_all::properties_to_string(&persisted).as_str(),
+            config_store_path.as_str(),
+        )
+        .map_err(|err| format!("persist name server config failed: {}", err))
+    }
+
+    /// All properties in the `key=value` format returned by `GetNamesrvConfig`.
+    pub fn get_all_configs_format_string(&self) -> String {
+        mix_all::properties_to_string(&self.namesrv_config.read().get_properties())
+    }
+
+    /// Completes on the next successful [`update`](NamesrvConfigManager::update).
+    pub fn config_changed(&self) -> Notified<'_> {
+        self.config_changed.notified()
+    }
+
+    fn persisted_properties(&self) -> HashMap<String, String> {
+        let config_store_path = self.namesrv_config.read().config_store_path.clone();
+        match FileUtils::file_to_string(config_store_path.as_str()) {
+            Ok(content) => mix_all::string_to_properties(content.as_str()),
+            Err(err) => {
+                warn!(
+                    "read name server config {} failed: {}",
+                    config_store_path, err
+                );
+                HashMap::new()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn namesrv_config_manager(name: &str) -> NamesrvConfigManager {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        let namesrv_config = NamesrvConfig {
+            config_store_path: dir
+                .join("rocketmq-namesrv.properties")
+                .to_string_lossy()
+                .into_owned(),
+            ..NamesrvConfig::default()
+        };
+        NamesrvConfigManager::new(Arc::new(parking_lot::RwLock::new(namesrv_config)))
+    }
+
+    #[test]
+    fn updated_properties_survive_restart() {
+        let manager = namesrv_config_manager("namesrv_config_manager_survive_restart");
+        let mut properties = HashMap::new();
+        properties.insert("orderMessageEnable".to_string(), "true".to_string());
+        properties.insert("waitSecondsForService".to_string(), "10".to_string());
+        manager.update(&properties).unwrap();
+        assert!(manager.namesrv_config().read().order_message_enable);
+        assert!(manager
+            .get_all_configs_format_string()
+            .contains("waitSecondsForService=10\n"));
+
+        let config_store_path = manager.namesrv_config().read().config_store_path.clone();
+        let restarted =
+            NamesrvConfigManager::new(Arc::new(parking_lot::RwLock::new(NamesrvConfig {
+                config_store_path,
+                ..NamesrvConfig::default()
+            })));
+        restarted.load();
+        let namesrv_config = restarted.namesrv_config().read();
+        assert!(namesrv_config.order_message_enable);
+        assert_eq!(namesrv_config.wait_seconds_for_service, 10);
+    }
+
+    #[tokio::test]
+    async fn update_wakes_up_waiters() {
+        let manager = namesrv_config_manager("namesrv_config_manager_wakes_up_waiters");
+        let config_changed = manager.config_changed();
+        tokio::pin!(config_changed);
+        config_changed.as_mut().enable();
+
+        let mut properties = HashMap::new();
+        properties.insert(
+            "scanNotActiveBrokerInterval".to_string(),
+            "1000".to_string(),
+        );
+        manager.update(&properties).unwrap();
+        config_changed.await;
+        assert_eq!(
+            manager
+                .namesrv_config()
+                .read()
+                .scan_not_active_broker_interval,
+            1000
+        );
+    }
+}
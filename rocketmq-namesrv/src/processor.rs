@@ -17,6 +17,9 @@
 
 use std::sync::Arc;
 
+use rocketmq_controller::processor::ControllerRequestProcessor;
+use rocketmq_controller::raft::transport::decode_raft_envelope;
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
@@ -26,6 +29,7 @@ use rocketmq_remoting::Result;
 use tracing::info;
 
 pub use self::client_request_processor::ClientRequestProcessor;
+use crate::kvconfig::kvconfig_replicator::KVConfigReplicator;
 use crate::processor::default_request_processor::DefaultRequestProcessor;
 
 mod client_request_processor;
@@ -34,6 +38,10 @@ pub mod default_request_processor;
 pub struct NameServerRequestProcessor {
     pub(crate) client_request_processor: Arc<ClientRequestProcessor>,
     pub(crate) default_request_processor: Arc<DefaultRequestProcessor>,
+    /// Set when the controller is embedded, see `enable_controller_in_namesrv`.
+    pub(crate) controller_request_processor: Option<ControllerRequestProcessor>,
+    /// Set when the embedded controllers form a Raft group, see `controller_raft_peers`.
+    pub(crate) kvconfig_replicator: Option<Arc<KVConfigReplicator>>,
 }
 
 impl Clone for NameServerRequestProcessor {
@@ -41,6 +49,8 @@ impl Clone for NameServerRequestProcessor {
         Self {
             client_request_processor: self.client_request_processor.clone(),
             default_request_processor: self.default_request_processor.clone(),
+            controller_request_processor: self.controller_request_processor.clone(),
+            kvconfig_replicator: self.kvconfig_replicator.clone(),
         }
     }
 }
@@ -54,6 +64,32 @@ impl RequestProcessor for NameServerRequestProcessor {
     ) -> Result<Option<RemotingCommand>> {
         let request_code = RequestCode::from(request.code());
         info!("process_request: {:?}", request_code);
+        if let Some(kvconfig_replicator) = &self.kvconfig_replicator {
+            if request_code == RequestCode::PutKvConfig
+                || request_code == RequestCode::DeleteKvConfig
+            {
+                return Ok(Some(kvconfig_replicator.replicate(request).await));
+            }
+            if request.code() == ControllerRequestCode::ControllerRaftMessage.to_i32() {
+                let is_kvconfig_message = decode_raft_envelope(&request)
+                    .and_then(|envelope| kvconfig_replicator.on_raft_message(envelope))
+                    .is_none();
+                if is_kvconfig_message {
+                    return Ok(Some(RemotingCommand::create_response_command()));
+                }
+            }
+        }
+        if let Some(controller_request_processor) = &self.controller_request_processor {
+            if ControllerRequestCode::value_of(request.code()).is_some() {
+                return Ok(Some(
+                    controller_request_processor.handle_request(&request).await,
+                ));
+            }
+            // Heartbeats keep the broker alive for both the route info and the election
+            if request_code == RequestCode::BrokerHeartbeat {
+                controller_request_processor.handle_request(&request).await;
+            }
+        }
         let result = match request_code {
             RequestCode::GetRouteinfoByTopic => self
                 .client_request_processor
@@ -38,7 +38,7 @@ use crate::route::route_info_manager::RouteInfoManager;
 
 pub struct ClientRequestProcessor {
     route_info_manager: Arc<parking_lot::RwLock<RouteInfoManager>>,
-    namesrv_config: Arc<NamesrvConfig>,
+    namesrv_config: Arc<parking_lot::RwLock<NamesrvConfig>>,
     need_check_namesrv_ready: AtomicBool,
     startup_time_millis: u64,
     kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
@@ -47,7 +47,7 @@ pub struct ClientRequestProcessor {
 impl ClientRequestProcessor {
     pub fn new(
         route_info_manager: Arc<parking_lot::RwLock<RouteInfoManager>>,
-        namesrv_config: Arc<NamesrvConfig>,
+        namesrv_config: Arc<parking_lot::RwLock<NamesrvConfig>>,
         kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
     ) -> Self {
         Self {
@@ -59,15 +59,22 @@ impl ClientRequestProcessor {
         }
     }
 
+    /// Whether routes are served, see `need_wait_for_service`. The name server is ready once
+    /// `wait_seconds_for_service` passed since startup or a route was served.
+    pub(crate) fn is_namesrv_ready(&self) -> bool {
+        let namesrv_config = self.namesrv_config.read();
+        !namesrv_config.need_wait_for_service
+            || !self.need_check_namesrv_ready.load(Ordering::Relaxed)
+            || TimeUtils::get_current_millis() - self.startup_time_millis
+                >= Duration::from_secs(namesrv_config.wait_seconds_for_service as u64).as_millis()
+                    as u64
+    }
+
     fn get_route_info_by_topic(&self, request: RemotingCommand) -> RemotingCommand {
         let request_header = request
             .decode_command_custom_header::<GetRouteInfoRequestHeader>()
             .unwrap();
-        let namesrv_ready = self.need_check_namesrv_ready.load(Ordering::Relaxed)
-            && TimeUtils::get_current_millis() - self.startup_time_millis
-                >= Duration::from_secs(self.namesrv_config.wait_seconds_for_service as u64)
-                    .as_millis() as u64;
-        if self.namesrv_config.need_wait_for_service && !namesrv_ready {
+        if !self.is_namesrv_ready() {
             warn!(
                 "name remoting_server not ready. request code {} ",
                 request.code()
@@ -92,7 +99,7 @@ impl ClientRequestProcessor {
                 if self.need_check_namesrv_ready.load(Ordering::Relaxed) {
                     self.need_check_namesrv_ready.store(false, Ordering::SeqCst);
                 }
-                if self.namesrv_config.order_message_enable {
+                if self.namesrv_config.read().order_message_enable {
                     //get kv config
                     let order_topic_config = self
                         .kvconfig_manager
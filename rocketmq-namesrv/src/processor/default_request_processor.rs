@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 
+use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
@@ -50,8 +51,10 @@ use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::Delete
 use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::GetTopicsByClusterRequestHeader;
 use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::RegisterTopicRequestHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::topic_route_change::SubscribeTopicRouteChangeRequestBody;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
 use rocketmq_remoting::protocol::DataVersion;
+use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use tracing::info;
@@ -59,11 +62,13 @@ use tracing::warn;
 
 use crate::route::route_info_manager::RouteInfoManager;
 use crate::KVConfigManager;
+use crate::NamesrvConfigManager;
 
 #[derive(Clone)]
 pub struct DefaultRequestProcessor {
     route_info_manager: Arc<parking_lot::RwLock<RouteInfoManager>>,
     kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
+    namesrv_config_manager: Arc<NamesrvConfigManager>,
 }
 
 impl DefaultRequestProcessor {
@@ -111,6 +116,11 @@ impl DefaultRequestProcessor {
             Some(RequestCode::GetHasUnitSubUnunitTopicList) => {
                 self.get_has_unit_sub_un_unit_topic_list(request)
             }
+            Some(RequestCode::UpdateNamesrvConfig) => self.update_config(request),
+            Some(RequestCode::GetNamesrvConfig) => self.get_config(request),
+            Some(RequestCode::SubscribeTopicRouteChange) => {
+                self.subscribe_topic_route_change(channel, request)
+            }
             _ => RemotingCommand::create_response_command_with_code(
                 RemotingSysResponseCode::SystemError,
             ),
@@ -214,10 +224,12 @@ impl DefaultRequestProcessor {
     pub fn new(
         route_info_manager: Arc<parking_lot::RwLock<RouteInfoManager>>,
         kvconfig_manager: Arc<parking_lot::RwLock<KVConfigManager>>,
+        namesrv_config_manager: Arc<NamesrvConfigManager>,
     ) -> Self {
         Self {
             route_info_manager,
             kvconfig_manager,
+            namesrv_config_manager,
         }
     }
 }
@@ -281,6 +293,7 @@ impl DefaultRequestProcessor {
             .kvconfig_manager
             .read()
             .namesrv_config
+            .read()
             .return_order_topic_config_to_broker
         {
             if let Some(value) = self
@@ -316,12 +329,11 @@ impl DefaultRequestProcessor {
         let request_header = request
             .decode_command_custom_header::<BrokerHeartbeatRequestHeader>()
             .unwrap();
-        self.route_info_manager
-            .write()
-            .update_broker_info_update_timestamp(
-                request_header.cluster_name.as_str(),
-                request_header.broker_addr.as_str(),
-            );
+        self.route_info_manager.write().process_broker_heartbeat(
+            request_header.cluster_name.as_str(),
+            request_header.broker_addr.as_str(),
+            request_header.heartbeat_timeout_mills,
+        );
         RemotingCommand::create_response_command()
     }
 
@@ -376,7 +388,7 @@ impl DefaultRequestProcessor {
 
     fn get_all_topic_list_from_nameserver(&self, _request: RemotingCommand) -> RemotingCommand {
         let rd_lock = self.route_info_manager.read();
-        if rd_lock.namesrv_config.enable_all_topic_list {
+        if rd_lock.namesrv_config.read().enable_all_topic_list {
             let topics = rd_lock.get_all_topic_list();
             drop(rd_lock); //release lock
             return RemotingCommand::create_response_command()
@@ -436,6 +448,7 @@ impl DefaultRequestProcessor {
             .route_info_manager
             .read()
             .namesrv_config
+            .read()
             .enable_topic_list
         {
             return RemotingCommand::create_response_command_with_code(
@@ -464,6 +477,7 @@ impl DefaultRequestProcessor {
             .route_info_manager
             .read()
             .namesrv_config
+            .read()
             .enable_topic_list
         {
             let topic_list = self.route_info_manager.read().get_unit_topics();
@@ -478,6 +492,7 @@ impl DefaultRequestProcessor {
             .route_info_manager
             .read()
             .namesrv_config
+            .read()
             .enable_topic_list
         {
             let topic_list = self.route_info_manager.read().get_has_unit_sub_topic_list();
@@ -492,6 +507,7 @@ impl DefaultRequestProcessor {
             .route_info_manager
             .read()
             .namesrv_config
+            .read()
             .enable_topic_list
         {
             let topic_list = self
@@ -503,6 +519,70 @@ impl DefaultRequestProcessor {
         RemotingCommand::create_response_command_with_code(RemotingSysResponseCode::SystemError)
             .set_remark(Some(String::from("disable")))
     }
+
+    fn update_config(&self, request: RemotingCommand) -> RemotingCommand {
+        let properties = match request.body() {
+            Some(body) => match std::str::from_utf8(body) {
+                Ok(content) => mix_all::string_to_properties(content),
+                Err(_) => {
+                    return RemotingCommand::create_response_command_with_code(
+                        RemotingSysResponseCode::SystemError,
+                    )
+                    .set_remark(Some(String::from("string2Properties error")));
+                }
+            },
+            None => return RemotingCommand::create_response_command(),
+        };
+        let black_listed = {
+            let namesrv_config = self.namesrv_config_manager.namesrv_config().read();
+            properties
+                .keys()
+                .any(|key| namesrv_config.is_in_config_black_list(key))
+        };
+        if black_listed {
+            return RemotingCommand::create_response_command_with_code(ResponseCode::NoPermission)
+                .set_remark(Some(String::from("Can not update config in black list.")));
+        }
+        match self.namesrv_config_manager.update(&properties) {
+            Ok(()) => RemotingCommand::create_response_command(),
+            Err(err) => {
+                warn!("update name server config failed: {}", err);
+                RemotingCommand::create_response_command_with_code(
+                    RemotingSysResponseCode::SystemError,
+                )
+                .set_remark(Some(err))
+            }
+        }
+    }
+
+    fn get_config(&self, _request: RemotingCommand) -> RemotingCommand {
+        let content = self.namesrv_config_manager.get_all_configs_format_string();
+        RemotingCommand::create_response_command().set_body(Some(Bytes::from(content)))
+    }
+
+    fn subscribe_topic_route_change(
+        &self,
+        channel: Channel,
+        request: RemotingCommand,
+    ) -> RemotingCommand {
+        let topics = match request.body() {
+            Some(body) => match SubscribeTopicRouteChangeRequestBody::decode(body) {
+                Ok(body) => body.topics,
+                Err(err) => {
+                    return RemotingCommand::create_response_command_with_code(
+                        RemotingSysResponseCode::SystemError,
+                    )
+                    .set_remark(Some(err.to_string()));
+                }
+            },
+            None => HashSet::new(),
+        };
+        self.route_info_manager
+            .read()
+            .topic_route_notifier
+            .subscribe(channel, topics);
+        RemotingCommand::create_response_command()
+    }
 }
 
 fn extract_register_topic_config_from_request(
@@ -15,4 +15,7 @@
  * limitations under the License.
  */
 
+pub mod broker_liveness;
 pub mod route_info_manager;
+pub(crate) mod route_snapshot;
+pub(crate) mod topic_route_notifier;
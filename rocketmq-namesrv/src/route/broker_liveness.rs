@@ -0,0 +1,259 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::VecDeque;
+
+use rocketmq_common::TimeUtils;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Events kept for the readers polling with `events_since`.
+const BROKER_LIVENESS_EVENT_HISTORY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BrokerOfflineReason {
+    /// The broker sent `UnregisterBroker`.
+    Unregistered,
+    /// No heartbeat within the broker's heartbeat timeout.
+    HeartbeatTimeout,
+    /// The broker's connection to the name server was closed.
+    ConnectionClosed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum BrokerLivenessEventKind {
+    Online,
+    #[serde(rename_all = "camelCase")]
+    Offline {
+        reason: BrokerOfflineReason,
+    },
+    /// The broker registered again under another broker id, e.g. a slave switched to master.
+    #[serde(rename_all = "camelCase")]
+    RoleChanged {
+        previous_broker_id: i64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerLivenessEvent {
+    /// Increases by one with every event published by this name server.
+    pub seq: u64,
+    pub timestamp: i64,
+    pub cluster_name: String,
+    pub broker_name: String,
+    pub broker_addr: String,
+    pub broker_id: i64,
+    #[serde(flatten)]
+    pub kind: BrokerLivenessEventKind,
+}
+
+/// Publishes the broker online/offline/role change transitions seen by `RouteInfoManager`.
+///
+/// Name server components subscribe to the broadcast channel, the HTTP API reads the last
+/// events with `events_since`.
+pub(crate) struct BrokerLivenessEvents {
+    sender: broadcast::Sender<BrokerLivenessEvent>,
+    history: parking_lot::Mutex<VecDeque<BrokerLivenessEvent>>,
+    next_seq: parking_lot::Mutex<u64>,
+}
+
+impl BrokerLivenessEvents {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = broadcast::channel(BROKER_LIVENESS_EVENT_HISTORY);
+        BrokerLivenessEvents {
+            sender,
+            history: parking_lot::Mutex::new(VecDeque::with_capacity(
+                BROKER_LIVENESS_EVENT_HISTORY,
+            )),
+            next_seq: parking_lot::Mutex::new(1),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<BrokerLivenessEvent> {
+        self.sender.subscribe()
+    }
+
+    pub(crate) fn publish(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        broker_addr: &str,
+        broker_id: i64,
+        kind: BrokerLivenessEventKind,
+    ) {
+        let mut next_seq = self.next_seq.lock();
+        let event = BrokerLivenessEvent {
+            seq: *next_seq,
+            timestamp: TimeUtils::get_current_millis() as i64,
+            cluster_name: cluster_name.to_string(),
+            broker_name: broker_name.to_string(),
+            broker_addr: broker_addr.to_string(),
+            broker_id,
+            kind,
+        };
+        *next_seq += 1;
+        let mut history = self.history.lock();
+        if history.len() == BROKER_LIVENESS_EVENT_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(event.clone());
+        drop(history);
+        // Nobody listening is fine, the event stays in the history
+        let _ = self.sender.send(event);
+    }
+
+    /// The retained events with a `seq` greater than `seq`, oldest first.
+    pub(crate) fn events_since(&self, seq: u64) -> Vec<BrokerLivenessEvent> {
+        self.history
+            .lock()
+            .iter()
+            .filter(|event| event.seq > seq)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+
+    use super::*;
+    use crate::route::route_info_manager::test_util;
+    use crate::route_info::broker_addr_info::BrokerAddrInfo;
+    use crate::RouteInfoManager;
+
+    fn register_broker(
+        route_info_manager: &mut RouteInfoManager,
+        broker_id: i64,
+        heartbeat_timeout_millis: Option<i64>,
+    ) {
+        test_util::register_broker(
+            route_info_manager,
+            broker_id,
+            heartbeat_timeout_millis,
+            test_util::topic_config_wrapper(&["TopicTest", "TopicOther"]),
+        );
+    }
+
+    fn new_route_info_manager() -> RouteInfoManager {
+        test_util::new_route_info_manager(NamesrvConfig::default())
+    }
+
+    #[test]
+    fn publishes_broker_transitions() {
+        let mut route_info_manager = new_route_info_manager();
+        let mut events = route_info_manager.subscribe_broker_liveness();
+
+        register_broker(&mut route_info_manager, 0, None);
+        let broker_addr_info = BrokerAddrInfo::new("DefaultCluster", "127.0.0.1:10911");
+        assert_eq!(
+            route_info_manager.broker_live_table[&broker_addr_info].heartbeat_timeout_millis(),
+            2 * 60 * 1000
+        );
+        // Registering again under the same id is not a transition
+        register_broker(&mut route_info_manager, 0, None);
+        register_broker(&mut route_info_manager, 1, Some(1));
+        assert_eq!(
+            route_info_manager.broker_live_table[&broker_addr_info].heartbeat_timeout_millis(),
+            1
+        );
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        route_info_manager.scan_not_active_broker();
+
+        let online = events.try_recv().unwrap();
+        assert_eq!(online.seq, 1);
+        assert_eq!(online.broker_id, 0);
+        assert_eq!(online.kind, BrokerLivenessEventKind::Online);
+        let role_changed = events.try_recv().unwrap();
+        assert_eq!(role_changed.broker_id, 1);
+        assert_eq!(
+            role_changed.kind,
+            BrokerLivenessEventKind::RoleChanged {
+                previous_broker_id: 0
+            }
+        );
+        let offline = events.try_recv().unwrap();
+        assert_eq!(offline.broker_addr, "127.0.0.1:10911");
+        assert_eq!(
+            offline.kind,
+            BrokerLivenessEventKind::Offline {
+                reason: BrokerOfflineReason::HeartbeatTimeout
+            }
+        );
+        assert!(events.try_recv().is_err());
+
+        let history = route_info_manager.broker_liveness_events.events_since(1);
+        assert_eq!(history, vec![role_changed, offline]);
+    }
+
+    #[test]
+    fn keeps_broker_heartbeating_within_its_timeout() {
+        let mut route_info_manager = new_route_info_manager();
+        register_broker(&mut route_info_manager, 0, Some(300));
+        let mut events = route_info_manager.subscribe_broker_liveness();
+
+        // the heartbeats span more than the timeout, each one arrives within it
+        for _ in 0..5 {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            route_info_manager.process_broker_heartbeat(
+                "DefaultCluster",
+                "127.0.0.1:10911",
+                Some(300),
+            );
+            route_info_manager.scan_not_active_broker();
+        }
+
+        let broker_addr_info = BrokerAddrInfo::new("DefaultCluster", "127.0.0.1:10911");
+        assert!(route_info_manager
+            .broker_live_table
+            .contains_key(&broker_addr_info));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn expires_broker_once_past_its_timeout() {
+        let mut route_info_manager = new_route_info_manager();
+        register_broker(&mut route_info_manager, 0, Some(1));
+        let mut events = route_info_manager.subscribe_broker_liveness();
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        route_info_manager.scan_not_active_broker();
+        route_info_manager.scan_not_active_broker();
+
+        let broker_addr_info = BrokerAddrInfo::new("DefaultCluster", "127.0.0.1:10911");
+        assert!(!route_info_manager
+            .broker_live_table
+            .contains_key(&broker_addr_info));
+        let offline = events.try_recv().unwrap();
+        assert_eq!(offline.broker_name, "broker-a");
+        assert_eq!(
+            offline.kind,
+            BrokerLivenessEventKind::Offline {
+                reason: BrokerOfflineReason::HeartbeatTimeout
+            }
+        );
+        assert!(events.try_recv().is_err());
+
+        register_broker(&mut route_info_manager, 0, None);
+        let online = events.try_recv().unwrap();
+        assert_eq!(online.broker_addr, "127.0.0.1:10911");
+        assert_eq!(online.kind, BrokerLivenessEventKind::Online);
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use rocketmq_common::common::config::TopicConfig;
@@ -44,21 +45,28 @@ use rocketmq_remoting::protocol::route::route_data_view::QueueData;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
 use rocketmq_remoting::protocol::static_topic::topic_queue_info::TopicQueueMappingInfo;
 use rocketmq_remoting::protocol::DataVersion;
+use tokio::select;
 use tokio::sync::broadcast;
+use tokio::time::Instant;
 use tracing::debug;
 use tracing::info;
 use tracing::warn;
 
+use crate::route::broker_liveness::BrokerLivenessEvent;
+use crate::route::broker_liveness::BrokerLivenessEventKind;
+use crate::route::broker_liveness::BrokerLivenessEvents;
+use crate::route::broker_liveness::BrokerOfflineReason;
+use crate::route::topic_route_notifier::TopicRouteNotifier;
 use crate::route_info::broker_addr_info::BrokerAddrInfo;
 use crate::route_info::broker_addr_info::BrokerLiveInfo;
 use crate::route_info::broker_addr_info::BrokerStatusChangeInfo;
+use crate::NamesrvConfigManager;
 
-const DEFAULT_BROKER_CHANNEL_EXPIRED_TIME: i64 = 1000 * 60 * 2;
-
-type TopicQueueTable =
+pub(crate) type TopicQueueTable =
     HashMap<String /* topic */, HashMap<String /* broker name */, QueueData>>;
-type BrokerAddrTable = HashMap<String /* brokerName */, BrokerData>;
-type ClusterAddrTable = HashMap<String /* clusterName */, HashSet<String /* brokerName */>>;
+pub(crate) type BrokerAddrTable = HashMap<String /* brokerName */, BrokerData>;
+pub(crate) type ClusterAddrTable =
+    HashMap<String /* clusterName */, HashSet<String /* brokerName */>>;
 type BrokerLiveTable = HashMap<BrokerAddrInfo /* brokerAddr */, BrokerLiveInfo>;
 type FilterServerTable =
     HashMap<BrokerAddrInfo /* brokerAddr */, Vec<String> /* Filter Server */>;
@@ -72,14 +80,18 @@ pub struct RouteInfoManager {
     pub(crate) broker_live_table: BrokerLiveTable,
     pub(crate) filter_server_table: FilterServerTable,
     pub(crate) topic_queue_mapping_info_table: TopicQueueMappingInfoTable,
-    pub(crate) namesrv_config: Arc<NamesrvConfig>,
+    pub(crate) namesrv_config: Arc<parking_lot::RwLock<NamesrvConfig>>,
     pub(crate) remoting_client: Arc<RocketmqDefaultClient>,
+    pub(crate) topic_route_notifier: Arc<TopicRouteNotifier>,
+    /// Brokers restored from the route snapshot that have not registered since.
+    pub(crate) stale_brokers: HashSet<BrokerAddrInfo>,
+    pub(crate) broker_liveness_events: Arc<BrokerLivenessEvents>,
 }
 
 #[allow(private_interfaces)]
 impl RouteInfoManager {
     pub fn new(
-        namesrv_config: Arc<NamesrvConfig>,
+        namesrv_config: Arc<parking_lot::RwLock<NamesrvConfig>>,
         remoting_client: Arc<RocketmqDefaultClient>,
     ) -> Self {
         RouteInfoManager {
@@ -91,8 +103,16 @@ impl RouteInfoManager {
             topic_queue_mapping_info_table: HashMap::new(),
             namesrv_config,
             remoting_client,
+            topic_route_notifier: Arc::new(TopicRouteNotifier::new()),
+            stale_brokers: HashSet::new(),
+            broker_liveness_events: Arc::new(BrokerLivenessEvents::new()),
         }
     }
+
+    /// Broker online/offline/role change events published from now on.
+    pub fn subscribe_broker_liveness(&self) -> broadcast::Receiver<BrokerLivenessEvent> {
+        self.broker_liveness_events.subscribe()
+    }
 }
 
 //impl register broker
@@ -105,7 +125,7 @@ impl RouteInfoManager {
         broker_id: i64,
         ha_server_addr: String,
         zone_name: Option<String>,
-        _timeout_millis: Option<i64>,
+        timeout_millis: Option<i64>,
         enable_acting_master: Option<bool>,
         topic_config_serialize_wrapper: TopicConfigAndMappingSerializeWrapper,
         filter_server_list: Vec<String>,
@@ -140,15 +160,16 @@ impl RouteInfoManager {
                     .insert(broker_name.clone(), broker_data);
                 true
             };
+        let broker_addr_info = BrokerAddrInfo::new(cluster_name.clone(), broker_addr.clone());
+        let was_live = self.broker_live_table.contains_key(&broker_addr_info)
+            && !self.stale_brokers.contains(&broker_addr_info);
         let broker_data = self.broker_addr_table.get_mut(&broker_name).unwrap();
-        let mut prev_min_broker_id = 0i64;
-        if !broker_data.broker_addrs().is_empty() {
-            prev_min_broker_id = broker_data.broker_addrs().keys().min().copied().unwrap();
-        }
-        let mut is_min_broker_id_changed = false;
-        if broker_id < prev_min_broker_id {
-            is_min_broker_id_changed = true;
-        }
+        let prev_min_broker_id = broker_data.broker_addrs().keys().min().copied();
+        let previous_broker_id = broker_data
+            .broker_addrs()
+            .iter()
+            .find(|(id, addr)| **id != broker_id && **addr == broker_addr)
+            .map(|(id, _)| *id);
 
         //Switch slave to master: first remove <1, IP:PORT> in rocketmq-namesrv, then add <0,
         // IP:PORT> The same IP:PORT must only have one record in brokerAddrTable
@@ -207,12 +228,27 @@ impl RouteInfoManager {
             .insert(broker_id, broker_addr.clone());
 
         register_first |= old_addr.is_none();
+        // Also covers a master registering again as slave, which raises the min broker id
+        let is_min_broker_id_changed = prev_min_broker_id.is_some()
+            && prev_min_broker_id != broker_data.broker_addrs().keys().min().copied();
         let is_master = mix_all::MASTER_ID == broker_id as u64;
 
         let is_prime_slave = enable_acting_master.is_some()
             && !is_master
             && broker_id == broker_data.broker_addrs().keys().min().copied().unwrap();
         let broker_data = broker_data.clone();
+        let route_changed = register_first
+            || self.is_broker_topic_config_changed(
+                &cluster_name,
+                &broker_addr,
+                topic_config_serialize_wrapper
+                    .topic_config_serialize_wrapper
+                    .data_version(),
+            );
+        let was_stale = self.stale_brokers.remove(&broker_addr_info);
+        // Queue data is refreshed even when the data version did not change, e.g. restoring the
+        // write perm wiped by an operator, so those topics are pushed as well
+        let mut changed_topics = HashSet::new();
         //handle master or prime slave topic config update
         if is_master || is_prime_slave {
             let tc_table = topic_config_serialize_wrapper
@@ -223,7 +259,10 @@ impl RouteInfoManager {
 
             // Delete the topics that don't exist in tcTable from the current broker
             // Static topic is not supported currently
-            if self.namesrv_config.delete_topic_with_broker_registration
+            if self
+                .namesrv_config
+                .read()
+                .delete_topic_with_broker_registration
                 && topic_queue_mapping_info_map.is_empty()
             {
                 let old_topic_set = self.topic_set_of_broker_name(&broker_name);
@@ -251,6 +290,15 @@ impl RouteInfoManager {
                     }
                 }
             }
+            if was_stale {
+                // Topics deleted while the name server was down are only missing from tcTable
+                self.topic_queue_table.retain(|topic, queue_data_map| {
+                    if !tc_table.contains_key(topic) {
+                        queue_data_map.remove(&broker_name);
+                    }
+                    !queue_data_map.is_empty()
+                });
+            }
             let data_version = topic_config_serialize_wrapper
                 .topic_config_serialize_wrapper
                 .data_version();
@@ -269,7 +317,10 @@ impl RouteInfoManager {
                 {
                     config.perm &= !PermName::PERM_WRITE;
                 }
-                self.create_and_update_queue_data(&broker_name, config);
+                let topic = config.topic_name.clone();
+                if self.create_and_update_queue_data(&broker_name, config) {
+                    changed_topics.extend(topic);
+                }
             }
             if self.is_broker_topic_config_changed(&cluster_name, &broker_addr, data_version)
                 || register_first
@@ -290,8 +341,9 @@ impl RouteInfoManager {
             }
         }
 
-        let broker_addr_info = BrokerAddrInfo::new(cluster_name.clone(), broker_addr.clone());
-
+        let heartbeat_timeout_millis = timeout_millis
+            .filter(|timeout| *timeout > 0)
+            .unwrap_or(self.namesrv_config.read().broker_channel_expired_time as i64);
         self.broker_live_table.insert(
             broker_addr_info.clone(),
             BrokerLiveInfo::new(
@@ -299,7 +351,7 @@ impl RouteInfoManager {
                     .duration_since(SystemTime::UNIX_EPOCH)
                     .expect("Time went backwards")
                     .as_millis() as i64,
-                DEFAULT_BROKER_CHANNEL_EXPIRED_TIME,
+                heartbeat_timeout_millis,
                 topic_config_serialize_wrapper
                     .topic_config_serialize_wrapper
                     .data_version()
@@ -327,19 +379,38 @@ impl RouteInfoManager {
                 }
             }
         }
-        if is_min_broker_id_changed && self.namesrv_config.notify_min_broker_id_changed {
-            self.notify_min_broker_id_changed(
-                broker_data.broker_addrs(),
-                None,
-                Some(
-                    self.broker_live_table
-                        .get(&broker_addr_info)
-                        .unwrap()
-                        .ha_server_addr()
-                        .to_string(),
-                ),
-            )
+        if is_min_broker_id_changed && self.namesrv_config.read().notify_min_broker_id_changed {
+            let min_broker_addr_info = broker_data
+                .broker_addrs()
+                .iter()
+                .min_by_key(|(id, _)| **id)
+                .map(|(_, addr)| BrokerAddrInfo::new(cluster_name.clone(), addr.clone()));
+            let ha_broker_addr = min_broker_addr_info
+                .and_then(|addr_info| self.broker_live_table.get(&addr_info))
+                .map(|live_info| live_info.ha_server_addr().to_string());
+            self.notify_min_broker_id_changed(broker_data.broker_addrs(), None, ha_broker_addr)
+        }
+        if !was_live {
+            self.broker_liveness_events.publish(
+                &cluster_name,
+                &broker_name,
+                &broker_addr,
+                broker_id,
+                BrokerLivenessEventKind::Online,
+            );
+        } else if let Some(previous_broker_id) = previous_broker_id {
+            self.broker_liveness_events.publish(
+                &cluster_name,
+                &broker_name,
+                &broker_addr,
+                broker_id,
+                BrokerLivenessEventKind::RoleChanged { previous_broker_id },
+            );
+        }
+        if route_changed {
+            changed_topics.extend(self.topic_set_of_broker_name(&broker_name));
         }
+        self.topic_route_notifier.route_changed(changed_topics);
         Some(result)
     }
 }
@@ -405,7 +476,7 @@ impl RouteInfoManager {
                     .unwrap_or_default(),
             );
 
-            if !self.namesrv_config.support_acting_master {
+            if !self.namesrv_config.read().support_acting_master {
                 return Some(topic_route_data);
             }
 
@@ -467,7 +538,7 @@ impl RouteInfoManager {
 }
 
 impl RouteInfoManager {
-    fn topic_set_of_broker_name(&mut self, broker_name: &str) -> HashSet<String> {
+    fn topic_set_of_broker_name(&self, broker_name: &str) -> HashSet<String> {
         let mut topic_of_broker = HashSet::new();
         for (key, value) in self.topic_queue_table.iter() {
             if value.contains_key(broker_name) {
@@ -529,7 +600,12 @@ impl RouteInfoManager {
         None
     }
 
-    fn create_and_update_queue_data(&mut self, broker_name: &str, topic_config: TopicConfig) {
+    /// Returns whether the queue data of `broker_name` on the topic was added or changed.
+    fn create_and_update_queue_data(
+        &mut self,
+        broker_name: &str,
+        topic_config: TopicConfig,
+    ) -> bool {
         let queue_data = QueueData::new(
             broker_name.to_string(),
             topic_config.write_queue_nums,
@@ -547,15 +623,16 @@ impl RouteInfoManager {
                 queue_data_map_inner.insert(broker_name.to_string(), queue_data);
             } else {
                 let unwrap = existed_qd.unwrap();
-                if unwrap != &queue_data {
-                    info!(
-                        "topic changed, {} OLD: {:?} NEW: {:?}",
-                        topic_config.topic_name.as_ref().unwrap(),
-                        unwrap,
-                        queue_data
-                    );
-                    queue_data_map_inner.insert(broker_name.to_string(), queue_data);
+                if unwrap == &queue_data {
+                    return false;
                 }
+                info!(
+                    "topic changed, {} OLD: {:?} NEW: {:?}",
+                    topic_config.topic_name.as_ref().unwrap(),
+                    unwrap,
+                    queue_data
+                );
+                queue_data_map_inner.insert(broker_name.to_string(), queue_data);
             }
         } else {
             let mut queue_data_map_inner = HashMap::new();
@@ -570,6 +647,7 @@ impl RouteInfoManager {
                 queue_data_map_inner,
             );
         }
+        true
     }
 
     fn notify_min_broker_id_changed(
@@ -642,6 +720,25 @@ impl RouteInfoManager {
         }
     }
 
+    /// Refreshes the broker like `update_broker_info_update_timestamp`, also taking the
+    /// heartbeat timeout the broker sent, if any.
+    pub(crate) fn process_broker_heartbeat(
+        &mut self,
+        cluster_name: impl Into<String>,
+        broker_addr: impl Into<String>,
+        heartbeat_timeout_millis: Option<i64>,
+    ) {
+        let broker_addr_info = BrokerAddrInfo::new(cluster_name, broker_addr);
+        if let Some(value) = self.broker_live_table.get_mut(&broker_addr_info) {
+            value.last_update_timestamp = TimeUtils::get_current_millis() as i64;
+            if let Some(heartbeat_timeout_millis) =
+                heartbeat_timeout_millis.filter(|timeout| *timeout > 0)
+            {
+                value.heartbeat_timeout_millis = heartbeat_timeout_millis;
+            }
+        }
+    }
+
     pub(crate) fn get_broker_member_group(
         &mut self,
         cluster_name: &str,
@@ -672,8 +769,11 @@ impl RouteInfoManager {
         request_code: RequestCode,
     ) -> i32 {
         let mut topic_cnt = 0;
-        for (_topic, qd_map) in self.topic_queue_table.iter_mut() {
-            let qd = qd_map.get_mut(broker_name).unwrap();
+        let mut changed_topics = Vec::new();
+        for (topic, qd_map) in self.topic_queue_table.iter_mut() {
+            let Some(qd) = qd_map.get_mut(broker_name) else {
+                continue;
+            };
             let mut perm = qd.perm;
             match request_code {
                 RequestCode::WipeWritePermOfBroker => {
@@ -684,9 +784,13 @@ impl RouteInfoManager {
                 }
                 _ => {}
             }
+            if qd.perm != perm {
+                changed_topics.push(topic.as_str());
+            }
             qd.perm = perm;
             topic_cnt += 1;
         }
+        self.topic_route_notifier.route_changed(changed_topics);
         topic_cnt
     }
 
@@ -728,6 +832,7 @@ impl RouteInfoManager {
         } else {
             self.topic_queue_table.remove(topic_inner.as_str());
         }
+        self.topic_route_notifier.route_changed([topic_inner]);
     }
 
     pub(crate) fn register_topic(
@@ -746,6 +851,7 @@ impl RouteInfoManager {
         }
         let queue_data_map = self.topic_queue_table.get_mut(&topic_inner).unwrap();
         let vec_length = queue_data_vec.len();
+        let mut changed = false;
         for queue_data in queue_data_vec {
             if !self
                 .broker_addr_table
@@ -757,7 +863,9 @@ impl RouteInfoManager {
                 );
                 return;
             }
-            queue_data_map.insert(queue_data.broker_name().to_string(), queue_data);
+            let broker_name = queue_data.broker_name().to_string();
+            changed |= queue_data_map.get(&broker_name) != Some(&queue_data);
+            queue_data_map.insert(broker_name, queue_data);
         }
 
         if queue_data_map.len() > vec_length {
@@ -771,6 +879,9 @@ impl RouteInfoManager {
                 &topic_inner, queue_data_map
             )
         }
+        if changed {
+            self.topic_route_notifier.route_changed([topic_inner]);
+        }
     }
 
     pub(crate) fn get_topics_by_cluster(&self, cluster: &str) -> TopicList {
@@ -863,17 +974,24 @@ impl RouteInfoManager {
             if broker_live_info.heartbeat_timeout_millis + broker_live_info.last_update_timestamp
                 < TimeUtils::get_current_millis() as i64
             {
-                self.on_connection_disconnected(broker_addr_info);
+                self.on_connection_disconnected(
+                    broker_addr_info,
+                    BrokerOfflineReason::HeartbeatTimeout,
+                );
             }
         }
     }
 
-    fn on_connection_disconnected(&mut self, broker_addr_info: &BrokerAddrInfo) {
+    fn on_connection_disconnected(
+        &mut self,
+        broker_addr_info: &BrokerAddrInfo,
+        reason: BrokerOfflineReason,
+    ) {
         let mut request_header = UnRegisterBrokerRequestHeader::default();
         let need_un_register =
             self.setup_un_register_request(&mut request_header, broker_addr_info);
         if need_un_register {
-            self.un_register_broker(vec![request_header]);
+            self.un_register_broker_with_reason(vec![request_header], reason);
         }
     }
 
@@ -907,6 +1025,17 @@ impl RouteInfoManager {
     pub(crate) fn un_register_broker(
         &mut self,
         un_register_requests: Vec<UnRegisterBrokerRequestHeader>,
+    ) {
+        self.un_register_broker_with_reason(
+            un_register_requests,
+            BrokerOfflineReason::Unregistered,
+        );
+    }
+
+    fn un_register_broker_with_reason(
+        &mut self,
+        un_register_requests: Vec<UnRegisterBrokerRequestHeader>,
+        reason: BrokerOfflineReason,
     ) {
         let mut remove_broker = HashSet::<String>::new();
         let mut reduced_broker = HashSet::<String>::new();
@@ -926,6 +1055,16 @@ impl RouteInfoManager {
             );
 
             self.filter_server_table.remove(&broker_addr_info);
+            self.stale_brokers.remove(&broker_addr_info);
+            if pre.is_some() {
+                self.broker_liveness_events.publish(
+                    cluster_name,
+                    broker_name,
+                    broker_addr,
+                    un_register_request.broker_id as i64,
+                    BrokerLivenessEventKind::Offline { reason },
+                );
+            }
 
             let mut remove_broker_name = false;
             let mut is_min_broker_id_changed = false;
@@ -949,7 +1088,7 @@ impl RouteInfoManager {
                         remove_broker_id_set.insert(*broker_id);
                     }
                 }
-                let _removed = if remove_broker_id_set.is_empty() {
+                let _removed = if !remove_broker_id_set.is_empty() {
                     for broker_id in remove_broker_id_set {
                         broker_data.broker_addrs_mut().remove(&broker_id);
                     }
@@ -986,8 +1125,16 @@ impl RouteInfoManager {
                 reduced_broker.insert(broker_name.clone());
             }
         }
+        let changed_topics = remove_broker
+            .iter()
+            .chain(reduced_broker.iter())
+            .flat_map(|broker_name| self.topic_set_of_broker_name(broker_name))
+            .collect::<HashSet<_>>();
         self.clean_topic_by_un_register_requests(remove_broker, reduced_broker);
-        if !need_notify_broker_map.is_empty() && self.namesrv_config.notify_min_broker_id_changed {
+        self.topic_route_notifier.route_changed(changed_topics);
+        if !need_notify_broker_map.is_empty()
+            && self.namesrv_config.read().notify_min_broker_id_changed
+        {
             for (broker_name, broker_status_change_info) in need_notify_broker_map {
                 let broker_data = self.broker_addr_table.get(&broker_name);
                 if let Some(broker_data) = broker_data {
@@ -1092,6 +1239,7 @@ impl RouteInfoManager {
     }
 
     pub fn connection_disconnected(&mut self, socket_addr: SocketAddr) {
+        self.topic_route_notifier.unsubscribe(&socket_addr);
         let mut broker_addr_info = None;
         for (bai, bli) in &self.broker_live_table {
             if bli.remote_addr == socket_addr {
@@ -1100,11 +1248,7 @@ impl RouteInfoManager {
             }
         }
         if let Some(bai) = broker_addr_info {
-            let mut request_header = UnRegisterBrokerRequestHeader::default();
-            let need_un_register = self.setup_un_register_request(&mut request_header, &bai);
-            if need_un_register {
-                self.un_register_broker(vec![request_header]);
-            }
+            self.on_connection_disconnected(&bai, BrokerOfflineReason::ConnectionClosed);
         }
     }
 }
@@ -1125,4 +1269,87 @@ impl RouteInfoManager {
             }
         });
     }
+
+    /// Pushes route changes to the clients subscribed with `SubscribeTopicRouteChange`.
+    pub fn start_topic_route_notifier(route_info_manager: Arc<parking_lot::RwLock<Self>>) {
+        let topic_route_notifier = route_info_manager.read().topic_route_notifier.clone();
+        TopicRouteNotifier::start(topic_route_notifier, route_info_manager);
+    }
+
+    /// Scans for expired brokers every `scan_not_active_broker_interval`, picking up interval
+    /// changes made through `UpdateNamesrvConfig` without waiting for the pending scan.
+    pub fn start_scan_not_active_broker(
+        route_info_manager: Arc<parking_lot::RwLock<Self>>,
+        namesrv_config_manager: Arc<NamesrvConfigManager>,
+    ) {
+        tokio::spawn(async move {
+            let mut last_scan = Instant::now();
+            loop {
+                let interval = Duration::from_millis(
+                    namesrv_config_manager
+                        .namesrv_config()
+                        .read()
+                        .scan_not_active_broker_interval,
+                );
+                select! {
+                    _ = tokio::time::sleep_until(last_scan + interval) => {
+                        route_info_manager.write().scan_not_active_broker();
+                        last_scan = Instant::now();
+                    }
+                    _ = namesrv_config_manager.config_changed() => {}
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+
+    use super::*;
+
+    pub(crate) fn new_route_info_manager(namesrv_config: NamesrvConfig) -> RouteInfoManager {
+        RouteInfoManager::new(
+            Arc::new(parking_lot::RwLock::new(namesrv_config)),
+            Arc::new(RocketmqDefaultClient::new(
+                Arc::new(TokioClientConfig::default()),
+                DefaultRemotingRequestProcessor,
+            )),
+        )
+    }
+
+    pub(crate) fn topic_config_wrapper(topics: &[&str]) -> TopicConfigAndMappingSerializeWrapper {
+        let mut topic_config_wrapper = TopicConfigAndMappingSerializeWrapper::default();
+        for topic in topics {
+            topic_config_wrapper
+                .topic_config_serialize_wrapper
+                .topic_config_table
+                .insert(topic.to_string(), TopicConfig::new(*topic));
+        }
+        topic_config_wrapper
+    }
+
+    /// Registers `broker-a` of `DefaultCluster` at `127.0.0.1:10911`.
+    pub(crate) fn register_broker(
+        route_info_manager: &mut RouteInfoManager,
+        broker_id: i64,
+        heartbeat_timeout_millis: Option<i64>,
+        topic_config_wrapper: TopicConfigAndMappingSerializeWrapper,
+    ) {
+        route_info_manager.register_broker(
+            "DefaultCluster".to_string(),
+            "127.0.0.1:10911".to_string(),
+            "broker-a".to_string(),
+            broker_id,
+            "127.0.0.1:10912".to_string(),
+            None,
+            heartbeat_timeout_millis,
+            None,
+            topic_config_wrapper,
+            Vec::new(),
+            "127.0.0.1:50000".parse::<SocketAddr>().unwrap(),
+        );
+    }
 }
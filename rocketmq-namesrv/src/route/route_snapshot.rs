@@ -0,0 +1,284 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
+use rocketmq_common::FileUtils;
+use rocketmq_common::TimeUtils;
+use rocketmq_remoting::protocol::DataVersion;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::route::route_info_manager::BrokerAddrTable;
+use crate::route::route_info_manager::ClusterAddrTable;
+use crate::route::route_info_manager::TopicQueueTable;
+use crate::route_info::broker_addr_info::BrokerAddrInfo;
+use crate::route_info::broker_addr_info::BrokerLiveInfo;
+use crate::RouteInfoManager;
+
+/// The route tables persisted to `route_snapshot_path`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RouteSnapshot {
+    pub(crate) timestamp: i64,
+    pub(crate) topic_queue_table: TopicQueueTable,
+    pub(crate) broker_addr_table: BrokerAddrTable,
+    pub(crate) cluster_addr_table: ClusterAddrTable,
+    pub(crate) ha_server_addr_table: HashMap<String /* brokerAddr */, String>,
+}
+
+impl RouteInfoManager {
+    pub(crate) fn route_snapshot(&self) -> RouteSnapshot {
+        RouteSnapshot {
+            timestamp: TimeUtils::get_current_millis() as i64,
+            topic_queue_table: self.topic_queue_table.clone(),
+            broker_addr_table: self.broker_addr_table.clone(),
+            cluster_addr_table: self.cluster_addr_table.clone(),
+            ha_server_addr_table: self
+                .broker_live_table
+                .iter()
+                .map(|(broker_addr_info, broker_live_info)| {
+                    (
+                        broker_addr_info.broker_addr.clone(),
+                        broker_live_info.ha_server_addr().to_string(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Restores the route tables of `snapshot`. Its brokers are stale until they register
+    /// again and are unregistered by `scan_not_active_broker` unless they do so within
+    /// `ttl_millis`.
+    pub(crate) fn restore_route_snapshot(&mut self, snapshot: RouteSnapshot, ttl_millis: i64) {
+        let now = TimeUtils::get_current_millis() as i64;
+        for broker_data in snapshot.broker_addr_table.values() {
+            for broker_addr in broker_data.broker_addrs().values() {
+                let broker_addr_info =
+                    BrokerAddrInfo::new(broker_data.cluster(), broker_addr.clone());
+                if self.broker_live_table.contains_key(&broker_addr_info) {
+                    continue;
+                }
+                // A data version no broker reports makes the broker register its topics again
+                self.broker_live_table.insert(
+                    broker_addr_info.clone(),
+                    BrokerLiveInfo::new(
+                        now,
+                        ttl_millis,
+                        DataVersion::default(),
+                        snapshot
+                            .ha_server_addr_table
+                            .get(broker_addr)
+                            .cloned()
+                            .unwrap_or_default(),
+                        SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+                    ),
+                );
+                self.stale_brokers.insert(broker_addr_info);
+            }
+        }
+        for (broker_name, broker_data) in snapshot.broker_addr_table {
+            self.broker_addr_table
+                .entry(broker_name)
+                .or_insert(broker_data);
+        }
+        for (cluster_name, broker_names) in snapshot.cluster_addr_table {
+            self.cluster_addr_table
+                .entry(cluster_name)
+                .or_default()
+                .extend(broker_names);
+        }
+        for (topic, queue_data_map) in snapshot.topic_queue_table {
+            let table = self.topic_queue_table.entry(topic).or_default();
+            for (broker_name, queue_data) in queue_data_map {
+                table.entry(broker_name).or_insert(queue_data);
+            }
+        }
+    }
+
+    /// Restores the snapshot at `route_snapshot_path`, if any.
+    pub fn load_route_snapshot(&mut self) {
+        let (path, ttl) = {
+            let namesrv_config = self.namesrv_config.read();
+            match namesrv_config.route_snapshot_path.clone() {
+                Some(path) => (path, namesrv_config.route_snapshot_ttl as i64),
+                None => return,
+            }
+        };
+        let content = match FileUtils::file_to_string(&path) {
+            Ok(content) if !content.is_empty() => content,
+            Ok(_) => return,
+            Err(err) => {
+                warn!("read route snapshot {} failed: {}", path, err);
+                return;
+            }
+        };
+        match SerdeJsonUtils::decode::<RouteSnapshot>(content.as_bytes()) {
+            Ok(snapshot) => {
+                info!(
+                    "load route snapshot taken at {}, {} brokers, {} topics",
+                    snapshot.timestamp,
+                    snapshot.broker_addr_table.len(),
+                    snapshot.topic_queue_table.len()
+                );
+                self.restore_route_snapshot(snapshot, ttl);
+            }
+            Err(err) => warn!("decode route snapshot {} failed: {}", path, err),
+        }
+    }
+
+    /// Writes the route tables to `route_snapshot_path`.
+    pub fn persist_route_snapshot(&self) {
+        if let Some((path, snapshot)) = self.take_route_snapshot() {
+            write_route_snapshot(&path, &snapshot);
+        }
+    }
+
+    /// Skipped while restored brokers are stale, so restarting again before they expire can
+    /// not keep dead brokers around.
+    fn take_route_snapshot(&self) -> Option<(String, RouteSnapshot)> {
+        let path = self.namesrv_config.read().route_snapshot_path.clone()?;
+        if !self.stale_brokers.is_empty() {
+            return None;
+        }
+        Some((path, self.route_snapshot()))
+    }
+
+    /// Persists the route tables every `route_snapshot_interval` when `route_snapshot_path` is
+    /// set.
+    pub fn start_route_snapshot(route_info_manager: Arc<parking_lot::RwLock<Self>>) {
+        let interval = {
+            let route_info_manager = route_info_manager.read();
+            let namesrv_config = route_info_manager.namesrv_config.read();
+            if namesrv_config.route_snapshot_path.is_none() {
+                return;
+            }
+            Duration::from_millis(namesrv_config.route_snapshot_interval.max(1000))
+        };
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some((path, snapshot)) = route_info_manager.read().take_route_snapshot() else {
+                    continue;
+                };
+                let _ = tokio::task::spawn_blocking(move || write_route_snapshot(&path, &snapshot))
+                    .await;
+            }
+        });
+    }
+}
+
+fn write_route_snapshot(path: &str, snapshot: &RouteSnapshot) {
+    let content = match SerdeJsonUtils::to_json(snapshot) {
+        Ok(content) => content,
+        Err(err) => {
+            error!("encode route snapshot failed: {}", err);
+            return;
+        }
+    };
+    if let Err(err) = FileUtils::string_to_file(&content, path) {
+        error!("persist route snapshot {} failed: {}", path, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+
+    use super::*;
+    use crate::route::route_info_manager::test_util;
+
+    fn route_info_manager(route_snapshot_path: &str) -> RouteInfoManager {
+        test_util::new_route_info_manager(NamesrvConfig {
+            route_snapshot_path: Some(route_snapshot_path.to_string()),
+            ..NamesrvConfig::default()
+        })
+    }
+
+    fn register_broker(route_info_manager: &mut RouteInfoManager, topics: &[&str]) {
+        test_util::register_broker(
+            route_info_manager,
+            0,
+            None,
+            test_util::topic_config_wrapper(topics),
+        );
+    }
+
+    #[test]
+    fn restored_routes_are_stale_until_the_broker_registers() {
+        let dir = std::env::temp_dir().join("namesrv_restored_routes_are_stale");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir
+            .join("routeSnapshot.json")
+            .to_string_lossy()
+            .into_owned();
+        let mut route_info_manager_before = route_info_manager(&path);
+        register_broker(&mut route_info_manager_before, &["TopicTest", "TopicOther"]);
+        route_info_manager_before.persist_route_snapshot();
+
+        let mut restarted = route_info_manager(&path);
+        restarted.load_route_snapshot();
+        let broker_addr_info = BrokerAddrInfo::new("DefaultCluster", "127.0.0.1:10911");
+        assert!(restarted.stale_brokers.contains(&broker_addr_info));
+        assert_eq!(
+            restarted.broker_live_table[&broker_addr_info].ha_server_addr(),
+            "127.0.0.1:10912"
+        );
+        let route = restarted.pickup_topic_route_data("TopicOther").unwrap();
+        assert_eq!(route.broker_datas[0].broker_name(), "broker-a");
+
+        // The broker dropped TopicOther while the name server was down
+        register_broker(&mut restarted, &["TopicTest", "TopicNew"]);
+        assert!(restarted.stale_brokers.is_empty());
+        assert!(restarted.pickup_topic_route_data("TopicOther").is_none());
+        assert!(restarted.pickup_topic_route_data("TopicNew").is_some());
+        assert!(restarted.pickup_topic_route_data("TopicTest").is_some());
+    }
+
+    #[test]
+    fn stale_brokers_expire_after_ttl() {
+        let dir = std::env::temp_dir().join("namesrv_stale_brokers_expire_after_ttl");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir
+            .join("routeSnapshot.json")
+            .to_string_lossy()
+            .into_owned();
+        let mut route_info_manager_before = route_info_manager(&path);
+        register_broker(&mut route_info_manager_before, &["TopicTest", "TopicOther"]);
+
+        let mut restarted = route_info_manager(&path);
+        restarted.restore_route_snapshot(route_info_manager_before.route_snapshot(), -1);
+        assert!(restarted.pickup_topic_route_data("TopicTest").is_some());
+        // Not persisted while stale
+        restarted.persist_route_snapshot();
+        assert!(!std::path::Path::new(&path).exists());
+
+        restarted.scan_not_active_broker();
+        assert!(restarted.stale_brokers.is_empty());
+        assert!(restarted.broker_live_table.is_empty());
+        assert!(restarted.pickup_topic_route_data("TopicTest").is_none());
+    }
+}
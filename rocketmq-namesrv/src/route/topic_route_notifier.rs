@@ -0,0 +1,458 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use rocketmq_common::TimeUtils;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::topic_route_change::TopicRouteChange;
+use rocketmq_remoting::protocol::route::topic_route_change::TopicRouteChangeBody;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use tokio::sync::Notify;
+use tracing::info;
+use tracing::warn;
+
+use crate::RouteInfoManager;
+
+struct Subscription {
+    channel: Channel,
+    topics: HashSet<String>,
+}
+
+/// Pushes the routes of changed topics to the clients subscribed to them, so they do not have
+/// to wait for the next `poll_name_server_interval` to pick up a broker failure.
+///
+/// `RouteInfoManager` only records which topics changed, the routes are picked up and pushed by
+/// the task started with `start`, outside the route lock.
+pub(crate) struct TopicRouteNotifier {
+    subscriptions: parking_lot::Mutex<HashMap<SocketAddr, Subscription>>,
+    changed_topics: parking_lot::Mutex<HashSet<String>>,
+    changed: Notify,
+    /// Seeded with the boot time so a restarted name server keeps pushing increasing versions.
+    version: AtomicI64,
+}
+
+impl TopicRouteNotifier {
+    pub(crate) fn new() -> Self {
+        TopicRouteNotifier {
+            subscriptions: parking_lot::Mutex::new(HashMap::new()),
+            changed_topics: parking_lot::Mutex::new(HashSet::new()),
+            changed: Notify::new(),
+            version: AtomicI64::new(TimeUtils::get_current_millis() as i64),
+        }
+    }
+
+    /// Replaces the topics subscribed on `channel`, an empty set removes the subscription.
+    pub(crate) fn subscribe(&self, channel: Channel, topics: HashSet<String>) {
+        let remote_address = channel.remote_address();
+        let mut subscriptions = self.subscriptions.lock();
+        if topics.is_empty() {
+            subscriptions.remove(&remote_address);
+        } else {
+            subscriptions.insert(remote_address, Subscription { channel, topics });
+        }
+    }
+
+    pub(crate) fn unsubscribe(&self, remote_address: &SocketAddr) {
+        if self.subscriptions.lock().remove(remote_address).is_some() {
+            info!("topic route subscription of {} removed", remote_address);
+        }
+    }
+
+    /// Records that the routes of `topics` changed, topics nobody subscribed to are ignored.
+    pub(crate) fn route_changed<I>(&self, topics: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let subscriptions = self.subscriptions.lock();
+        if subscriptions.is_empty() {
+            return;
+        }
+        let mut changed_topics = self.changed_topics.lock();
+        let len = changed_topics.len();
+        for topic in topics {
+            let topic = topic.as_ref();
+            if subscriptions
+                .values()
+                .any(|subscription| subscription.topics.contains(topic))
+            {
+                changed_topics.insert(topic.to_string());
+            }
+        }
+        if changed_topics.len() > len {
+            self.changed.notify_one();
+        }
+    }
+
+    fn take_changed_topics(&self) -> HashSet<String> {
+        std::mem::take(&mut *self.changed_topics.lock())
+    }
+
+    /// Versions the changed routes and sends every subscriber the ones it subscribed to.
+    /// Subscriptions whose connection is gone are dropped.
+    pub(crate) async fn push(&self, routes: HashMap<String, TopicRouteData>) {
+        if routes.is_empty() {
+            return;
+        }
+        let changes = routes
+            .into_iter()
+            .map(|(topic, topic_route_data)| TopicRouteChange {
+                topic,
+                version: self.version.fetch_add(1, Ordering::AcqRel) + 1,
+                topic_route_data,
+            })
+            .collect::<Vec<_>>();
+        let targets = self
+            .subscriptions
+            .lock()
+            .values()
+            .filter_map(|subscription| {
+                let changes = changes
+                    .iter()
+                    .filter(|change| subscription.topics.contains(&change.topic))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                (!changes.is_empty()).then(|| (subscription.channel.clone(), changes))
+            })
+            .collect::<Vec<_>>();
+        for (channel, changes) in targets {
+            let request =
+                RemotingCommand::create_remoting_command(RequestCode::NotifyTopicRouteChange)
+                    .set_body(Some(Bytes::from(TopicRouteChangeBody { changes }.encode())))
+                    .mark_oneway_rpc();
+            if let Err(err) = channel.write_and_flush(request).await {
+                warn!(
+                    "push topic route change to {} failed: {}",
+                    channel.remote_address(),
+                    err
+                );
+                self.unsubscribe(&channel.remote_address());
+            }
+        }
+    }
+
+    /// Pushes the routes of the topics recorded by `route_changed` until the runtime shuts down,
+    /// topics whose route is gone are pushed with an empty route.
+    pub(crate) fn start(
+        topic_route_notifier: Arc<Self>,
+        route_info_manager: Arc<parking_lot::RwLock<RouteInfoManager>>,
+    ) {
+        tokio::spawn(async move {
+            loop {
+                topic_route_notifier.changed.notified().await;
+                let changed_topics = topic_route_notifier.take_changed_topics();
+                let routes = {
+                    let route_info_manager = route_info_manager.read();
+                    changed_topics
+                        .into_iter()
+                        .map(|topic| {
+                            // a topic without a route was deleted, the empty route tells the
+                            // subscribers to drop it
+                            let topic_route_data = route_info_manager
+                                .pickup_topic_route_data(&topic)
+                                .unwrap_or_default();
+                            (topic, topic_route_data)
+                        })
+                        .collect::<HashMap<_, _>>()
+                };
+                topic_route_notifier.push(routes).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use rocketmq_common::common::constant::PermName;
+    use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+    use rocketmq_common::ArcRefCellWrapper;
+    use rocketmq_remoting::codec::remoting_command_codec::RemotingCommandCodec;
+    use rocketmq_remoting::connection::Connection;
+    use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
+    use rocketmq_remoting::protocol::RemotingDeserializable;
+    use tokio::net::TcpListener;
+    use tokio::net::TcpStream;
+    use tokio_util::codec::FramedRead;
+
+    use super::*;
+    use crate::route::route_info_manager::test_util;
+
+    fn route_info_manager() -> Arc<parking_lot::RwLock<RouteInfoManager>> {
+        let route_info_manager = Arc::new(parking_lot::RwLock::new(
+            test_util::new_route_info_manager(NamesrvConfig::default()),
+        ));
+        register_broker(&route_info_manager, topic_config_wrapper());
+        route_info_manager
+    }
+
+    fn topic_config_wrapper() -> TopicConfigAndMappingSerializeWrapper {
+        test_util::topic_config_wrapper(&["TopicTest", "TopicOther"])
+    }
+
+    fn register_broker(
+        route_info_manager: &parking_lot::RwLock<RouteInfoManager>,
+        topic_config_wrapper: TopicConfigAndMappingSerializeWrapper,
+    ) {
+        test_util::register_broker(
+            &mut route_info_manager.write(),
+            0,
+            None,
+            topic_config_wrapper,
+        );
+    }
+
+    /// Connects a client to `listener`, returning the client side and the name server side.
+    async fn connect(
+        listener: &TcpListener,
+    ) -> (FramedRead<TcpStream, RemotingCommandCodec>, Channel) {
+        let local_address = listener.local_addr().unwrap();
+        let client = FramedRead::new(
+            TcpStream::connect(local_address).await.unwrap(),
+            RemotingCommandCodec::new(),
+        );
+        let (stream, remote_address) = listener.accept().await.unwrap();
+        let channel = Channel::new(
+            local_address,
+            remote_address,
+            Connection::new(stream),
+            ArcRefCellWrapper::new(HashMap::new()),
+        );
+        (client, channel)
+    }
+
+    fn write_perm(change: &TopicRouteChange) -> u32 {
+        change.topic_route_data.queue_datas[0].perm & PermName::PERM_WRITE
+    }
+
+    async fn next_change(
+        client: &mut FramedRead<TcpStream, RemotingCommandCodec>,
+    ) -> TopicRouteChangeBody {
+        let command = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(command.code(), RequestCode::NotifyTopicRouteChange.to_i32());
+        assert!(command.is_oneway_rpc());
+        TopicRouteChangeBody::decode(command.body().as_ref().unwrap()).unwrap()
+    }
+
+    // Dropping the remoting client inside the runtime panics, so the manager lives outside it
+    #[test]
+    fn pushes_changed_routes_to_subscribers() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let route_info_manager = route_info_manager();
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut client, channel) = connect(&listener).await;
+            let topic_route_notifier = route_info_manager.read().topic_route_notifier.clone();
+            topic_route_notifier.subscribe(channel, HashSet::from(["TopicTest".to_string()]));
+            RouteInfoManager::start_topic_route_notifier(route_info_manager.clone());
+
+            route_info_manager
+                .write()
+                .wipe_write_perm_of_broker_by_lock("broker-a");
+            let body = next_change(&mut client).await;
+            assert_eq!(body.changes.len(), 1);
+            let wiped = &body.changes[0];
+            assert_eq!(wiped.topic, "TopicTest");
+            assert_eq!(write_perm(wiped), 0);
+
+            route_info_manager
+                .write()
+                .add_write_perm_of_broker_by_lock("broker-a");
+            let body = next_change(&mut client).await;
+            assert!(body.changes[0].version > wiped.version);
+            assert_ne!(write_perm(&body.changes[0]), 0);
+        });
+    }
+
+    #[test]
+    fn skips_push_when_route_is_unchanged() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let route_info_manager = route_info_manager();
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut client, channel) = connect(&listener).await;
+            let topic_route_notifier = route_info_manager.read().topic_route_notifier.clone();
+            topic_route_notifier.subscribe(channel, HashSet::from(["TopicTest".to_string()]));
+            RouteInfoManager::start_topic_route_notifier(route_info_manager.clone());
+
+            // a registration with the same topic configs and data version, and registering the
+            // same queue data again
+            let mut topic_config_wrapper = topic_config_wrapper();
+            topic_config_wrapper
+                .topic_config_serialize_wrapper
+                .data_version = route_info_manager
+                .read()
+                .query_broker_topic_config("DefaultCluster", "127.0.0.1:10911")
+                .unwrap()
+                .clone();
+            register_broker(&route_info_manager, topic_config_wrapper);
+            let queue_datas = route_info_manager
+                .read()
+                .pickup_topic_route_data("TopicTest")
+                .unwrap()
+                .queue_datas;
+            route_info_manager
+                .write()
+                .register_topic("TopicTest", queue_datas);
+            assert!(topic_route_notifier.take_changed_topics().is_empty());
+
+            route_info_manager
+                .write()
+                .wipe_write_perm_of_broker_by_lock("broker-a");
+            let wiped = next_change(&mut client).await.changes.remove(0);
+            assert_eq!(write_perm(&wiped), 0);
+            route_info_manager
+                .write()
+                .wipe_write_perm_of_broker_by_lock("broker-a");
+            assert!(topic_route_notifier.take_changed_topics().is_empty());
+
+            // the next push is the next change, nothing was pushed in between
+            route_info_manager
+                .write()
+                .add_write_perm_of_broker_by_lock("broker-a");
+            let added = next_change(&mut client).await.changes.remove(0);
+            assert_ne!(write_perm(&added), 0);
+            assert_eq!(added.version, wiped.version + 1);
+        });
+    }
+
+    #[test]
+    fn pushes_restored_queue_data_of_unchanged_registration() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let route_info_manager = route_info_manager();
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut client, channel) = connect(&listener).await;
+            let topic_route_notifier = route_info_manager.read().topic_route_notifier.clone();
+            topic_route_notifier.subscribe(channel, HashSet::from(["TopicTest".to_string()]));
+            RouteInfoManager::start_topic_route_notifier(route_info_manager.clone());
+
+            route_info_manager
+                .write()
+                .wipe_write_perm_of_broker_by_lock("broker-a");
+            let wiped = next_change(&mut client).await.changes.remove(0);
+            assert_eq!(write_perm(&wiped), 0);
+
+            // the broker registers its topic configs again, which restores the write perm
+            let mut topic_config_wrapper = topic_config_wrapper();
+            topic_config_wrapper
+                .topic_config_serialize_wrapper
+                .data_version = route_info_manager
+                .read()
+                .query_broker_topic_config("DefaultCluster", "127.0.0.1:10911")
+                .unwrap()
+                .clone();
+            register_broker(&route_info_manager, topic_config_wrapper);
+            let restored = next_change(&mut client).await.changes.remove(0);
+            assert_ne!(write_perm(&restored), 0);
+        });
+    }
+
+    #[test]
+    fn pushes_only_to_subscribers_of_changed_topic() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let route_info_manager = route_info_manager();
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut test_client, test_channel) = connect(&listener).await;
+            let (mut other_client, other_channel) = connect(&listener).await;
+            let topic_route_notifier = route_info_manager.read().topic_route_notifier.clone();
+            topic_route_notifier.subscribe(test_channel, HashSet::from(["TopicTest".to_string()]));
+            topic_route_notifier
+                .subscribe(other_channel, HashSet::from(["TopicOther".to_string()]));
+            RouteInfoManager::start_topic_route_notifier(route_info_manager.clone());
+
+            let mut queue_data = route_info_manager
+                .read()
+                .pickup_topic_route_data("TopicOther")
+                .unwrap()
+                .queue_datas
+                .remove(0);
+            queue_data.write_queue_nums += 1;
+            route_info_manager
+                .write()
+                .register_topic("TopicOther", vec![queue_data.clone()]);
+            let body = next_change(&mut other_client).await;
+            assert_eq!(body.changes.len(), 1);
+            assert_eq!(body.changes[0].topic, "TopicOther");
+            assert_eq!(
+                body.changes[0].topic_route_data.queue_datas,
+                vec![queue_data]
+            );
+
+            // the subscriber of TopicTest only gets the later change of its own topic
+            route_info_manager
+                .write()
+                .wipe_write_perm_of_broker_by_lock("broker-a");
+            let body = next_change(&mut test_client).await;
+            assert_eq!(body.changes.len(), 1);
+            assert_eq!(body.changes[0].topic, "TopicTest");
+            assert_eq!(write_perm(&body.changes[0]), 0);
+        });
+    }
+
+    #[test]
+    fn pushes_empty_route_of_deleted_topic() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let route_info_manager = route_info_manager();
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut client, channel) = connect(&listener).await;
+            let topic_route_notifier = route_info_manager.read().topic_route_notifier.clone();
+            topic_route_notifier.subscribe(channel, HashSet::from(["TopicTest".to_string()]));
+            RouteInfoManager::start_topic_route_notifier(route_info_manager.clone());
+
+            route_info_manager
+                .write()
+                .delete_topic("TopicTest", None::<String>);
+            let body = next_change(&mut client).await;
+            assert_eq!(body.changes.len(), 1);
+            assert_eq!(body.changes[0].topic, "TopicTest");
+            assert_eq!(body.changes[0].topic_route_data, TopicRouteData::default());
+        });
+    }
+}
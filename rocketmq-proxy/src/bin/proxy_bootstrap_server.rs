@@ -0,0 +1,69 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::path::PathBuf;
+
+use clap::Parser;
+use rocketmq_common::EnvUtils::EnvUtils;
+use rocketmq_common::ParseConfigFile;
+use rocketmq_proxy::bootstrap::Builder;
+use rocketmq_proxy::config::ProxyConfig;
+use rocketmq_rust::rocketmq;
+use tracing::info;
+
+#[rocketmq::main]
+async fn main() -> anyhow::Result<()> {
+    rocketmq_common::log::init_logger();
+    let args = Args::parse();
+    let home = EnvUtils::get_rocketmq_home();
+
+    info!("Rocketmq(Rust) home: {}", home);
+    let config_file = args
+        .config
+        .unwrap_or_else(|| PathBuf::from(home).join("conf").join("proxy.toml"));
+    let mut proxy_config = ParseConfigFile::parse_config_file::<ProxyConfig>(config_file)?;
+    if let Some(port) = args.port {
+        proxy_config.grpc_server_port = port;
+    }
+    if let Some(namesrv_addr) = args.namesrv_addr {
+        proxy_config.namesrv_addr = namesrv_addr;
+    }
+    info!(
+        "Rocketmq proxy(Rust) running on: {}:{}, name servers: {}",
+        proxy_config.bind_address, proxy_config.grpc_server_port, proxy_config.namesrv_addr
+    );
+    Builder::new()
+        .set_proxy_config(proxy_config)
+        .build()
+        .boot()
+        .await
+}
+
+#[derive(Parser, Debug)]
+#[command(author = "mxsm", version = "0.1.0", about = "RocketMQ Proxy(Rust)")]
+struct Args {
+    /// gRPC port of the proxy, overrides the config file
+    #[arg(short, long, value_name = "PORT", required = false)]
+    port: Option<u16>,
+
+    /// name server addresses separated by `;`, overrides the config file
+    #[arg(short, long, value_name = "NAMESRV_ADDR", required = false)]
+    namesrv_addr: Option<String>,
+
+    /// rocketmq proxy config file
+    #[arg(short, long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
@@ -0,0 +1,84 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tonic::transport::Server;
+use tracing::info;
+
+use crate::config::ProxyConfig;
+use crate::grpc::messaging_service::GrpcMessagingService;
+use crate::proto::v2::messaging_service_server::MessagingServiceServer;
+use crate::service::cluster_service::ClusterService;
+
+pub struct ProxyBootstrap {
+    proxy_config: Arc<ProxyConfig>,
+}
+
+pub struct Builder {
+    proxy_config: Option<ProxyConfig>,
+}
+
+impl ProxyBootstrap {
+    /// Serves the gRPC messaging service until ctrl-c.
+    pub async fn boot(self) -> anyhow::Result<()> {
+        let addr: SocketAddr = format!(
+            "{}:{}",
+            self.proxy_config.bind_address, self.proxy_config.grpc_server_port
+        )
+        .parse()?;
+        let cluster_service = Arc::new(ClusterService::new(self.proxy_config.clone()));
+        cluster_service.start().await;
+        let messaging_service =
+            GrpcMessagingService::new(self.proxy_config.clone(), cluster_service.clone());
+
+        info!("rocketmq proxy gRPC server listening on {}", addr);
+        Server::builder()
+            .add_service(MessagingServiceServer::new(messaging_service))
+            .serve_with_shutdown(addr, async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await?;
+        info!("rocketmq proxy gRPC server terminated, shutting down");
+        // The remoting client owns a runtime, which must not be dropped on an async thread
+        tokio::task::spawn_blocking(move || drop(cluster_service)).await?;
+        Ok(())
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Builder { proxy_config: None }
+    }
+
+    pub fn set_proxy_config(mut self, proxy_config: ProxyConfig) -> Self {
+        self.proxy_config = Some(proxy_config);
+        self
+    }
+
+    pub fn build(self) -> ProxyBootstrap {
+        ProxyBootstrap {
+            proxy_config: Arc::new(self.proxy_config.unwrap_or_default()),
+        }
+    }
+}
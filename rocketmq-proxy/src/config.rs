@@ -0,0 +1,108 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxyConfig {
+    pub bind_address: String,
+    /// Port of the `apache.rocketmq.v2.MessagingService` gRPC endpoint.
+    pub grpc_server_port: u16,
+    /// Name server addresses separated by `;`.
+    pub namesrv_addr: String,
+    /// Timeout of the remoting requests sent to brokers and name servers.
+    pub remoting_timeout_millis: u64,
+    /// How long a topic route fetched from the name servers is reused.
+    pub route_cache_expire_millis: u64,
+    /// Invisible duration of received messages when the request does not carry one.
+    pub default_invisible_duration_millis: u64,
+    pub min_invisible_duration_millis: u64,
+    pub max_invisible_duration_millis: u64,
+    /// Upper bound of the long polling timeout a `ReceiveMessage` request may ask for.
+    pub max_long_polling_timeout_millis: u64,
+    /// How often a long polling `ReceiveMessage` request pulls the brokers again while no
+    /// message is available.
+    pub receive_poll_interval_millis: u64,
+    pub max_receive_batch_size: u32,
+    /// Messages delivered this many times without being acked go to the dead letter queue of
+    /// their consumer group.
+    pub max_delivery_attempts: i32,
+    /// Most messages of a consumer group kept in flight on one queue. Once reached, nothing
+    /// more is pulled from that queue until acks free up room.
+    pub max_in_flight_messages_per_queue: usize,
+    /// Largest message body accepted from producers, advertised in the client settings.
+    pub max_message_body_size: usize,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        ProxyConfig {
+            bind_address: "0.0.0.0".to_string(),
+            grpc_server_port: 8081,
+            namesrv_addr: "127.0.0.1:9876".to_string(),
+            remoting_timeout_millis: 3000,
+            route_cache_expire_millis: 20_000,
+            default_invisible_duration_millis: 30_000,
+            min_invisible_duration_millis: 1000,
+            max_invisible_duration_millis: 12 * 60 * 60 * 1000,
+            max_long_polling_timeout_millis: 20_000,
+            receive_poll_interval_millis: 200,
+            max_receive_batch_size: 32,
+            max_delivery_attempts: 16,
+            max_in_flight_messages_per_queue: 1024,
+            max_message_body_size: 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl ProxyConfig {
+    pub fn namesrv_addr_list(&self) -> Vec<String> {
+        self.namesrv_addr
+            .split(';')
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_namesrv_addr_list() {
+        let config = ProxyConfig {
+            namesrv_addr: "127.0.0.1:9876; 127.0.0.2:9876;".to_string(),
+            ..ProxyConfig::default()
+        };
+        assert_eq!(
+            config.namesrv_addr_list(),
+            vec!["127.0.0.1:9876".to_string(), "127.0.0.2:9876".to_string()]
+        );
+    }
+
+    #[test]
+    fn deserializes_camel_case_with_defaults() {
+        let config: ProxyConfig =
+            serde_json::from_str(r#"{"grpcServerPort": 18081, "maxDeliveryAttempts": 3}"#).unwrap();
+        assert_eq!(config.grpc_server_port, 18081);
+        assert_eq!(config.max_delivery_attempts, 3);
+        assert_eq!(config.remoting_timeout_millis, 3000);
+    }
+}
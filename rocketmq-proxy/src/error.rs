@@ -0,0 +1,114 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::error::Error as RemotingError;
+use thiserror::Error;
+
+use crate::proto::v2::Code;
+use crate::proto::v2::Status;
+
+#[derive(Debug, Error)]
+pub enum ProxyError {
+    /// Rejected by the proxy itself, e.g. an invalid argument or an unknown receipt handle.
+    #[error("{1}")]
+    ProxyErr(Code, String),
+
+    /// A broker or name server answered with a response code other than `Success`.
+    #[error("Broker exception occurred: CODE:{0}, broker address:{1}, Message:{2}")]
+    MQBrokerError(i32, String, String),
+
+    #[error("{0}")]
+    RemotingError(#[from] RemotingError),
+}
+
+impl ProxyError {
+    pub fn new(code: Code, message: impl Into<String>) -> Self {
+        ProxyError::ProxyErr(code, message.into())
+    }
+
+    pub fn code(&self) -> Code {
+        match self {
+            ProxyError::ProxyErr(code, _) => *code,
+            ProxyError::MQBrokerError(code, _, _) => response_code_to_code(*code),
+            ProxyError::RemotingError(_) => Code::InternalServerError,
+        }
+    }
+
+    pub fn to_status(&self) -> Status {
+        let message = match self {
+            ProxyError::ProxyErr(_, message) => message.clone(),
+            ProxyError::MQBrokerError(_, _, remark) => remark.clone(),
+            ProxyError::RemotingError(err) => err.to_string(),
+        };
+        Status {
+            code: self.code() as i32,
+            message,
+        }
+    }
+}
+
+/// Maps the response code of a broker or name server to the code reported to gRPC clients.
+pub fn response_code_to_code(code: i32) -> Code {
+    match ResponseCode::from(code) {
+        ResponseCode::Success => Code::Ok,
+        ResponseCode::SystemBusy | ResponseCode::FlowControl => Code::TooManyRequests,
+        ResponseCode::RequestCodeNotSupported => Code::NotImplemented,
+        ResponseCode::FlushDiskTimeout => Code::MasterPersistenceTimeout,
+        ResponseCode::FlushSlaveTimeout => Code::SlavePersistenceTimeout,
+        ResponseCode::SlaveNotAvailable => Code::HaNotAvailable,
+        ResponseCode::MessageIllegal => Code::BadRequest,
+        ResponseCode::NoPermission => Code::Forbidden,
+        ResponseCode::TopicNotExist => Code::TopicNotFound,
+        ResponseCode::SubscriptionGroupNotExist => Code::ConsumerGroupNotFound,
+        ResponseCode::SubscriptionParseFailed => Code::IllegalFilterExpression,
+        ResponseCode::VersionNotSupported => Code::VersionUnsupported,
+        ResponseCode::RpcTimeOut => Code::ProxyTimeout,
+        _ => Code::InternalServerError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_broker_response_codes() {
+        let err = ProxyError::MQBrokerError(
+            ResponseCode::TopicNotExist as i32,
+            "127.0.0.1:10911".to_string(),
+            "topic not exist".to_string(),
+        );
+        let status = err.to_status();
+        assert_eq!(status.code, Code::TopicNotFound as i32);
+        assert_eq!(status.message, "topic not exist");
+        assert_eq!(
+            response_code_to_code(ResponseCode::SystemBusy as i32),
+            Code::TooManyRequests
+        );
+        assert_eq!(
+            response_code_to_code(ResponseCode::ServiceNotAvailable as i32),
+            Code::InternalServerError
+        );
+    }
+
+    #[test]
+    fn proxy_errors_keep_their_code() {
+        let status = ProxyError::new(Code::InvalidReceiptHandle, "expired").to_status();
+        assert_eq!(status.code, Code::InvalidReceiptHandle as i32);
+        assert_eq!(status.message, "expired");
+    }
+}
@@ -0,0 +1,295 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::time::Duration;
+
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::STRING_HASH_SET;
+
+use crate::error::ProxyError;
+use crate::processor::consumer_processor::ReceivedMessage;
+use crate::proto::v2;
+use crate::proto::v2::Code;
+use crate::Result;
+
+/// Turns a message published by a gRPC producer into a remoting message.
+pub fn to_message(message: &v2::Message) -> Result<Message> {
+    let topic = message
+        .topic
+        .as_ref()
+        .map(|topic| topic.name.as_str())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| ProxyError::new(Code::IllegalTopic, "topic is required"))?;
+    let mut properties = HashMap::new();
+    for (key, value) in &message.user_properties {
+        if STRING_HASH_SET.contains(key.as_str()) {
+            return Err(ProxyError::new(
+                Code::IllegalMessagePropertyKey,
+                format!("property {} is reserved by the system", key),
+            ));
+        }
+        properties.insert(key.clone(), value.clone());
+    }
+
+    let system_properties = message.system_properties.clone().unwrap_or_default();
+    if let Some(tag) = system_properties.tag.as_ref() {
+        if tag.trim().is_empty() || tag.contains('|') {
+            return Err(ProxyError::new(
+                Code::IllegalMessageTag,
+                format!("illegal tag {}", tag),
+            ));
+        }
+        properties.insert(MessageConst::PROPERTY_TAGS.to_string(), tag.clone());
+    }
+    if !system_properties.keys.is_empty() {
+        properties.insert(
+            MessageConst::PROPERTY_KEYS.to_string(),
+            system_properties.keys.join(MessageConst::KEY_SEPARATOR),
+        );
+    }
+    if !system_properties.message_id.is_empty() {
+        properties.insert(
+            MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX.to_string(),
+            system_properties.message_id.clone(),
+        );
+    }
+    if let Some(message_group) = system_properties.message_group.as_ref() {
+        properties.insert(
+            MessageConst::PROPERTY_SHARDING_KEY.to_string(),
+            message_group.clone(),
+        );
+    }
+    if let Some(delivery_timestamp) = system_properties.delivery_timestamp.as_ref() {
+        properties.insert(
+            MessageConst::PROPERTY_TIMER_DELIVER_MS.to_string(),
+            timestamp_to_millis(delivery_timestamp).to_string(),
+        );
+    }
+    if system_properties.message_type == v2::MessageType::Transaction as i32 {
+        properties.insert(
+            MessageConst::PROPERTY_TRANSACTION_PREPARED.to_string(),
+            "true".to_string(),
+        );
+    }
+    if let Some(trace_context) = system_properties.trace_context.as_ref() {
+        properties.insert(
+            MessageConst::PROPERTY_TRACE_CONTEXT.to_string(),
+            trace_context.clone(),
+        );
+    }
+
+    let mut result = Message::new(topic, &message.body);
+    result.properties.extend(properties);
+    Ok(result)
+}
+
+/// Turns a message received for a gRPC consumer into its protobuf form.
+pub fn to_proto_message(received: &ReceivedMessage) -> v2::Message {
+    let message = &received.message;
+    let properties = message.properties();
+    let body = message.body().unwrap_or_default();
+    let user_properties = properties
+        .iter()
+        .filter(|(key, _)| !STRING_HASH_SET.contains(key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    let system_properties = v2::SystemProperties {
+        tag: message.get_tags(),
+        keys: properties
+            .get(MessageConst::PROPERTY_KEYS)
+            .map(|keys| {
+                keys.split(MessageConst::KEY_SEPARATOR)
+                    .filter(|key| !key.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        message_id: properties
+            .get(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX)
+            .cloned()
+            .unwrap_or_else(|| message.msg_id().to_string()),
+        body_digest: Some(v2::Digest {
+            r#type: v2::DigestType::Crc32 as i32,
+            checksum: format!("{:08X}", crc32fast::hash(&body)),
+        }),
+        body_encoding: v2::Encoding::Identity as i32,
+        message_type: message_type(message) as i32,
+        born_timestamp: Some(millis_to_timestamp(message.born_timestamp())),
+        born_host: message.born_host().to_string(),
+        store_timestamp: Some(millis_to_timestamp(message.store_timestamp())),
+        store_host: message.store_host().to_string(),
+        delivery_timestamp: properties
+            .get(MessageConst::PROPERTY_TIMER_DELIVER_MS)
+            .and_then(|millis| millis.parse().ok())
+            .map(millis_to_timestamp),
+        receipt_handle: Some(received.receipt_handle.clone()),
+        queue_id: message.queue_id(),
+        queue_offset: Some(message.queue_offset()),
+        invisible_duration: None,
+        delivery_attempt: Some(received.delivery_attempt),
+        message_group: properties.get(MessageConst::PROPERTY_SHARDING_KEY).cloned(),
+        trace_context: properties
+            .get(MessageConst::PROPERTY_TRACE_CONTEXT)
+            .cloned(),
+        orphaned_transaction_recovery_duration: None,
+        dead_letter_queue: None,
+    };
+    v2::Message {
+        topic: Some(v2::Resource {
+            resource_namespace: String::new(),
+            name: message.topic().to_string(),
+        }),
+        user_properties,
+        system_properties: Some(system_properties),
+        body: body.to_vec(),
+    }
+}
+
+fn message_type(message: &MessageExt) -> v2::MessageType {
+    let properties = message.properties();
+    if properties
+        .get(MessageConst::PROPERTY_TRANSACTION_PREPARED)
+        .is_some_and(|prepared| prepared == "true")
+    {
+        v2::MessageType::Transaction
+    } else if properties.contains_key(MessageConst::PROPERTY_SHARDING_KEY) {
+        v2::MessageType::Fifo
+    } else if properties.contains_key(MessageConst::PROPERTY_TIMER_DELIVER_MS) {
+        v2::MessageType::Delay
+    } else {
+        v2::MessageType::Normal
+    }
+}
+
+pub fn millis_to_timestamp(millis: i64) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: millis.div_euclid(1000),
+        nanos: (millis.rem_euclid(1000) * 1_000_000) as i32,
+    }
+}
+
+pub fn timestamp_to_millis(timestamp: &prost_types::Timestamp) -> i64 {
+    timestamp.seconds * 1000 + i64::from(timestamp.nanos) / 1_000_000
+}
+
+/// Converts a protobuf duration, treating negative durations as invalid.
+pub fn to_duration(duration: &prost_types::Duration) -> Option<Duration> {
+    Duration::try_from(*duration).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn to_message_maps_system_properties() {
+        let message = v2::Message {
+            topic: Some(v2::Resource {
+                resource_namespace: String::new(),
+                name: "TopicTest".to_string(),
+            }),
+            user_properties: HashMap::from([("color".to_string(), "red".to_string())]),
+            system_properties: Some(v2::SystemProperties {
+                tag: Some("TagA".to_string()),
+                keys: vec!["k1".to_string(), "k2".to_string()],
+                message_id: "01ABCDEF".to_string(),
+                message_group: Some("order-1".to_string()),
+                message_type: v2::MessageType::Fifo as i32,
+                ..Default::default()
+            }),
+            body: b"hello".to_vec(),
+        };
+        let result = to_message(&message).unwrap();
+        assert_eq!(result.topic(), "TopicTest");
+        assert_eq!(result.body().unwrap().as_ref(), b"hello");
+        assert_eq!(result.get_tags().as_deref(), Some("TagA"));
+        assert_eq!(
+            result.get_property(MessageConst::PROPERTY_KEYS).as_deref(),
+            Some("k1 k2")
+        );
+        assert_eq!(
+            result
+                .get_property(MessageConst::PROPERTY_SHARDING_KEY)
+                .as_deref(),
+            Some("order-1")
+        );
+        assert_eq!(result.get_property("color").as_deref(), Some("red"));
+    }
+
+    #[test]
+    fn to_message_rejects_reserved_property_keys() {
+        let message = v2::Message {
+            topic: Some(v2::Resource {
+                resource_namespace: String::new(),
+                name: "TopicTest".to_string(),
+            }),
+            user_properties: HashMap::from([(
+                MessageConst::PROPERTY_TAGS.to_string(),
+                "TagA".to_string(),
+            )]),
+            ..Default::default()
+        };
+        let err = to_message(&message).unwrap_err();
+        assert_eq!(err.code(), Code::IllegalMessagePropertyKey);
+    }
+
+    #[test]
+    fn to_proto_message_reports_digest_and_receipt_handle() {
+        let mut message = MessageExt::default();
+        let mut inner = Message::with_tags("TopicTest", "TagA", b"hello");
+        inner.properties.insert(
+            MessageConst::PROPERTY_TIMER_DELIVER_MS.to_string(),
+            "1500".to_string(),
+        );
+        message.set_message_inner(inner);
+        message.set_queue_offset(7);
+        let received = ReceivedMessage {
+            message: Arc::new(message),
+            receipt_handle: "7 0 1000 broker-a".to_string(),
+            delivery_attempt: 2,
+        };
+        let result = to_proto_message(&received);
+        let system_properties = result.system_properties.unwrap();
+        assert_eq!(system_properties.tag.as_deref(), Some("TagA"));
+        assert_eq!(
+            system_properties.body_digest.unwrap().checksum,
+            format!("{:08X}", crc32fast::hash(b"hello"))
+        );
+        assert_eq!(
+            system_properties.message_type,
+            v2::MessageType::Delay as i32
+        );
+        assert_eq!(
+            system_properties.delivery_timestamp,
+            Some(prost_types::Timestamp {
+                seconds: 1,
+                nanos: 500_000_000,
+            })
+        );
+        assert_eq!(system_properties.queue_offset, Some(7));
+        assert_eq!(system_properties.delivery_attempt, Some(2));
+        assert_eq!(
+            system_properties.receipt_handle.as_deref(),
+            Some("7 0 1000 broker-a")
+        );
+        assert!(result.user_properties.is_empty());
+    }
+}
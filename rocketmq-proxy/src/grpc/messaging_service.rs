@@ -0,0 +1,788 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+use std::time::Duration;
+
+use rocketmq_common::common::filter::expression_type::ExpressionType;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::TimeUtils::get_current_millis;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::Request;
+use tonic::Response;
+use tonic::Streaming;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::config::ProxyConfig;
+use crate::error::ProxyError;
+use crate::grpc::converter;
+use crate::processor::client_processor::ClientProcessor;
+use crate::processor::client_processor::ClientSettings;
+use crate::processor::client_processor::SubscriptionEntry;
+use crate::processor::consumer_processor::ConsumerProcessor;
+use crate::processor::consumer_processor::ReceiveOptions;
+use crate::processor::producer_processor::ProducerProcessor;
+use crate::processor::route_processor::QueueView;
+use crate::processor::route_processor::RouteProcessor;
+use crate::processor::transaction_processor::TransactionProcessor;
+use crate::proto::v2;
+use crate::proto::v2::messaging_service_server::MessagingService;
+use crate::proto::v2::receive_message_response;
+use crate::proto::v2::settings::PubSub;
+use crate::proto::v2::telemetry_command::Command;
+use crate::proto::v2::Code;
+use crate::service::cluster_service::ClusterService;
+use crate::Result;
+
+/// Metadata key carrying the id of the client sending a request.
+pub const CLIENT_ID_KEY: &str = "x-mq-client-id";
+
+type GrpcResult<T> = std::result::Result<Response<T>, tonic::Status>;
+
+/// Serves `apache.rocketmq.v2.MessagingService` by translating its calls into remoting
+/// requests to the name servers and brokers.
+pub struct GrpcMessagingService {
+    config: Arc<ProxyConfig>,
+    route_processor: RouteProcessor,
+    producer_processor: Arc<ProducerProcessor>,
+    consumer_processor: Arc<ConsumerProcessor>,
+    transaction_processor: Arc<TransactionProcessor>,
+    client_processor: Arc<ClientProcessor>,
+}
+
+impl GrpcMessagingService {
+    pub fn new(config: Arc<ProxyConfig>, cluster_service: Arc<ClusterService>) -> Self {
+        let transaction_processor = Arc::new(TransactionProcessor::new(cluster_service.clone()));
+        let producer_processor = Arc::new(ProducerProcessor::new(
+            cluster_service.clone(),
+            transaction_processor.clone(),
+        ));
+        GrpcMessagingService {
+            config,
+            route_processor: RouteProcessor::new(cluster_service.clone()),
+            consumer_processor: Arc::new(ConsumerProcessor::new(
+                cluster_service.clone(),
+                producer_processor.clone(),
+            )),
+            producer_processor,
+            transaction_processor,
+            client_processor: Arc::new(ClientProcessor::new(cluster_service)),
+        }
+    }
+
+    /// Endpoints clients reach the brokers through, which is this proxy.
+    fn endpoints(&self, requested: Option<&v2::Endpoints>) -> v2::Endpoints {
+        requested.cloned().unwrap_or_else(|| v2::Endpoints {
+            scheme: v2::AddressScheme::IPv4 as i32,
+            addresses: vec![v2::Address {
+                host: self.config.bind_address.clone(),
+                port: i32::from(self.config.grpc_server_port),
+            }],
+        })
+    }
+
+    fn invisible_duration(&self, duration: Option<&prost_types::Duration>) -> Result<Duration> {
+        let Some(duration) = duration else {
+            return Ok(Duration::from_millis(
+                self.config.default_invisible_duration_millis,
+            ));
+        };
+        let min = Duration::from_millis(self.config.min_invisible_duration_millis);
+        let max = Duration::from_millis(self.config.max_invisible_duration_millis);
+        converter::to_duration(duration)
+            .filter(|duration| (min..=max).contains(duration))
+            .ok_or_else(|| {
+                ProxyError::new(
+                    Code::IllegalInvisibleTime,
+                    format!("invisible duration must be between {:?} and {:?}", min, max),
+                )
+            })
+    }
+
+    fn receive_options(&self, request: &v2::ReceiveMessageRequest) -> Result<OwnedReceiveOptions> {
+        let group = resource_name(request.group.as_ref(), Code::IllegalConsumerGroup)?;
+        let message_queue = request.message_queue.as_ref();
+        let topic = resource_name(
+            message_queue.and_then(|queue| queue.topic.as_ref()),
+            Code::IllegalTopic,
+        )?;
+        let broker_name = message_queue
+            .and_then(|queue| queue.broker.as_ref())
+            .map(|broker| broker.name.clone())
+            .filter(|name| !name.is_empty());
+        let queue_id = message_queue
+            .map(|queue| queue.id)
+            .filter(|queue_id| *queue_id >= 0);
+        let (expression_type, expression) = match request.filter_expression.as_ref() {
+            Some(filter) if filter.r#type == v2::FilterType::Sql as i32 => {
+                (ExpressionType::SQL92, filter.expression.clone())
+            }
+            Some(filter) => (ExpressionType::TAG, filter.expression.clone()),
+            None => (ExpressionType::TAG, "*".to_string()),
+        };
+        if request.batch_size <= 0 {
+            return Err(ProxyError::new(
+                Code::BadRequest,
+                format!("illegal batch size {}", request.batch_size),
+            ));
+        }
+        let long_polling_timeout = match request.long_polling_timeout.as_ref() {
+            Some(timeout) => converter::to_duration(timeout).ok_or_else(|| {
+                ProxyError::new(Code::IllegalPollingTime, "long polling timeout is negative")
+            })?,
+            None => Duration::ZERO,
+        };
+        Ok(OwnedReceiveOptions {
+            group,
+            topic,
+            broker_name,
+            queue_id,
+            expression_type: expression_type.to_string(),
+            expression,
+            batch_size: (request.batch_size as u32).min(self.config.max_receive_batch_size)
+                as usize,
+            invisible_duration: self.invisible_duration(request.invisible_duration.as_ref())?,
+            long_polling_timeout: long_polling_timeout.min(Duration::from_millis(
+                self.config.max_long_polling_timeout_millis,
+            )),
+        })
+    }
+
+    fn message_queue(
+        &self,
+        topic: &str,
+        queue_id: i32,
+        permission: v2::Permission,
+        broker_name: &str,
+        endpoints: &v2::Endpoints,
+    ) -> v2::MessageQueue {
+        v2::MessageQueue {
+            topic: Some(resource(topic)),
+            id: queue_id,
+            permission: permission as i32,
+            broker: Some(v2::Broker {
+                name: broker_name.to_string(),
+                id: mix_all::MASTER_ID as i32,
+                endpoints: Some(endpoints.clone()),
+            }),
+            accept_message_types: vec![
+                v2::MessageType::Normal as i32,
+                v2::MessageType::Fifo as i32,
+                v2::MessageType::Delay as i32,
+                v2::MessageType::Transaction as i32,
+            ],
+        }
+    }
+
+    async fn send_one(&self, message: &v2::Message) -> Result<v2::SendResultEntry> {
+        if message.body.len() > self.config.max_message_body_size {
+            return Err(ProxyError::new(
+                Code::MessageBodyTooLarge,
+                format!(
+                    "message body is larger than {} bytes",
+                    self.config.max_message_body_size
+                ),
+            ));
+        }
+        let message = converter::to_message(message)?;
+        let receipt = self
+            .producer_processor
+            .send_message(mix_all::DEFAULT_PRODUCER_GROUP, message)
+            .await?;
+        Ok(v2::SendResultEntry {
+            status: Some(ok_status()),
+            message_id: receipt.message_id,
+            transaction_id: receipt.transaction_id.unwrap_or_default(),
+            offset: receipt.queue_offset,
+        })
+    }
+
+    async fn end_transaction_inner(&self, request: &v2::EndTransactionRequest) -> Result<()> {
+        let topic = resource_name(request.topic.as_ref(), Code::IllegalTopic)?;
+        if request.transaction_id.is_empty() {
+            return Err(ProxyError::new(
+                Code::InvalidTransactionId,
+                "transaction id is required",
+            ));
+        }
+        let commit = match v2::TransactionResolution::try_from(request.resolution) {
+            Ok(v2::TransactionResolution::Commit) => true,
+            Ok(v2::TransactionResolution::Rollback) => false,
+            _ => {
+                return Err(ProxyError::new(
+                    Code::BadRequest,
+                    "transaction resolution must be COMMIT or ROLLBACK",
+                ))
+            }
+        };
+        let from_transaction_check =
+            request.source == v2::TransactionSource::SourceServerCheck as i32;
+        self.transaction_processor
+            .end_transaction(
+                &topic,
+                &request.transaction_id,
+                commit,
+                from_transaction_check,
+            )
+            .await
+    }
+
+    async fn change_invisible_duration_inner(
+        &self,
+        request: &v2::ChangeInvisibleDurationRequest,
+    ) -> Result<String> {
+        let group = resource_name(request.group.as_ref(), Code::IllegalConsumerGroup)?;
+        let topic = resource_name(request.topic.as_ref(), Code::IllegalTopic)?;
+        let invisible_duration =
+            self.invisible_duration(Some(request.invisible_duration.as_ref().ok_or_else(
+                || ProxyError::new(Code::IllegalInvisibleTime, "invisible duration is required"),
+            )?))?;
+        self.consumer_processor
+            .change_invisible_duration(&group, &topic, &request.receipt_handle, invisible_duration)
+            .await
+    }
+}
+
+#[tonic::async_trait]
+impl MessagingService for GrpcMessagingService {
+    async fn query_route(
+        &self,
+        request: Request<v2::QueryRouteRequest>,
+    ) -> GrpcResult<v2::QueryRouteResponse> {
+        let request = request.into_inner();
+        let endpoints = self.endpoints(request.endpoints.as_ref());
+        let result = match resource_name(request.topic.as_ref(), Code::IllegalTopic) {
+            Ok(topic) => self
+                .route_processor
+                .query_route(&topic)
+                .await
+                .map(|views| (topic, views)),
+            Err(err) => Err(err),
+        };
+        let response = match result {
+            Ok((topic, views)) => v2::QueryRouteResponse {
+                status: Some(ok_status()),
+                message_queues: views
+                    .iter()
+                    .map(|view| {
+                        self.message_queue(
+                            &topic,
+                            view.queue_id,
+                            permission(view),
+                            &view.broker_name,
+                            &endpoints,
+                        )
+                    })
+                    .collect(),
+            },
+            Err(err) => v2::QueryRouteResponse {
+                status: Some(err.to_status()),
+                message_queues: Vec::new(),
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn heartbeat(
+        &self,
+        request: Request<v2::HeartbeatRequest>,
+    ) -> GrpcResult<v2::HeartbeatResponse> {
+        let result = match client_id(&request) {
+            Ok(client_id) => self.client_processor.heartbeat(&client_id).await,
+            Err(err) => Err(err),
+        };
+        Ok(Response::new(v2::HeartbeatResponse {
+            status: Some(to_status(&result)),
+        }))
+    }
+
+    async fn send_message(
+        &self,
+        request: Request<v2::SendMessageRequest>,
+    ) -> GrpcResult<v2::SendMessageResponse> {
+        let request = request.into_inner();
+        if request.messages.is_empty() {
+            return Ok(Response::new(v2::SendMessageResponse {
+                status: Some(ProxyError::new(Code::BadRequest, "no message to send").to_status()),
+                entries: Vec::new(),
+            }));
+        }
+        let mut entries = Vec::with_capacity(request.messages.len());
+        for message in &request.messages {
+            let entry = match self.send_one(message).await {
+                Ok(entry) => entry,
+                Err(err) => v2::SendResultEntry {
+                    status: Some(err.to_status()),
+                    ..Default::default()
+                },
+            };
+            entries.push(entry);
+        }
+        let status = merged_status(entries.iter().map(|entry| entry.status.as_ref()));
+        Ok(Response::new(v2::SendMessageResponse {
+            status: Some(status),
+            entries,
+        }))
+    }
+
+    async fn query_assignment(
+        &self,
+        request: Request<v2::QueryAssignmentRequest>,
+    ) -> GrpcResult<v2::QueryAssignmentResponse> {
+        let request = request.into_inner();
+        let endpoints = self.endpoints(request.endpoints.as_ref());
+        let result = async {
+            resource_name(request.group.as_ref(), Code::IllegalConsumerGroup)?;
+            let topic = resource_name(request.topic.as_ref(), Code::IllegalTopic)?;
+            let broker_names = self.route_processor.query_assignment(&topic).await?;
+            Ok::<_, ProxyError>((topic, broker_names))
+        }
+        .await;
+        let response = match result {
+            Ok((topic, broker_names)) => v2::QueryAssignmentResponse {
+                status: Some(ok_status()),
+                assignments: broker_names
+                    .iter()
+                    .map(|broker_name| v2::Assignment {
+                        message_queue: Some(self.message_queue(
+                            &topic,
+                            -1,
+                            v2::Permission::Read,
+                            broker_name,
+                            &endpoints,
+                        )),
+                    })
+                    .collect(),
+            },
+            Err(err) => v2::QueryAssignmentResponse {
+                status: Some(err.to_status()),
+                assignments: Vec::new(),
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    type ReceiveMessageStream =
+        ReceiverStream<std::result::Result<v2::ReceiveMessageResponse, tonic::Status>>;
+
+    async fn receive_message(
+        &self,
+        request: Request<v2::ReceiveMessageRequest>,
+    ) -> GrpcResult<Self::ReceiveMessageStream> {
+        let request = request.into_inner();
+        let (tx, rx) = mpsc::channel(self.config.max_receive_batch_size as usize + 2);
+        let options = match self.receive_options(&request) {
+            Ok(options) => options,
+            Err(err) => {
+                let _ = tx.try_send(Ok(status_response(err.to_status())));
+                return Ok(Response::new(ReceiverStream::new(rx)));
+            }
+        };
+        let consumer_processor = self.consumer_processor.clone();
+        tokio::spawn(async move {
+            let result = consumer_processor
+                .receive_message(&options.as_receive_options())
+                .await;
+            let messages = match result {
+                Ok(messages) if messages.is_empty() => {
+                    let status = ProxyError::new(Code::MessageNotFound, "no new message");
+                    let _ = tx.send(Ok(status_response(status.to_status()))).await;
+                    return;
+                }
+                Ok(messages) => messages,
+                Err(err) => {
+                    let _ = tx.send(Ok(status_response(err.to_status()))).await;
+                    return;
+                }
+            };
+            if tx.send(Ok(status_response(ok_status()))).await.is_err() {
+                return;
+            }
+            for message in &messages {
+                let response = v2::ReceiveMessageResponse {
+                    content: Some(receive_message_response::Content::Message(
+                        converter::to_proto_message(message),
+                    )),
+                };
+                if tx.send(Ok(response)).await.is_err() {
+                    // The messages left become visible again once their invisible duration
+                    // elapses.
+                    return;
+                }
+            }
+            let _ = tx
+                .send(Ok(v2::ReceiveMessageResponse {
+                    content: Some(receive_message_response::Content::DeliveryTimestamp(
+                        converter::millis_to_timestamp(get_current_millis() as i64),
+                    )),
+                }))
+                .await;
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn ack_message(
+        &self,
+        request: Request<v2::AckMessageRequest>,
+    ) -> GrpcResult<v2::AckMessageResponse> {
+        let request = request.into_inner();
+        let names =
+            resource_name(request.group.as_ref(), Code::IllegalConsumerGroup).and_then(|group| {
+                Ok((
+                    group,
+                    resource_name(request.topic.as_ref(), Code::IllegalTopic)?,
+                ))
+            });
+        let (group, topic) = match names {
+            Ok(names) => names,
+            Err(err) => {
+                return Ok(Response::new(v2::AckMessageResponse {
+                    status: Some(err.to_status()),
+                    entries: Vec::new(),
+                }))
+            }
+        };
+        let mut entries = Vec::with_capacity(request.entries.len());
+        for entry in &request.entries {
+            let result = self
+                .consumer_processor
+                .ack_message(&group, &topic, &entry.receipt_handle)
+                .await;
+            entries.push(v2::AckMessageResultEntry {
+                message_id: entry.message_id.clone(),
+                receipt_handle: entry.receipt_handle.clone(),
+                status: Some(to_status(&result)),
+            });
+        }
+        let status = merged_status(entries.iter().map(|entry| entry.status.as_ref()));
+        Ok(Response::new(v2::AckMessageResponse {
+            status: Some(status),
+            entries,
+        }))
+    }
+
+    async fn forward_message_to_dead_letter_queue(
+        &self,
+        request: Request<v2::ForwardMessageToDeadLetterQueueRequest>,
+    ) -> GrpcResult<v2::ForwardMessageToDeadLetterQueueResponse> {
+        let request = request.into_inner();
+        let result = async {
+            let group = resource_name(request.group.as_ref(), Code::IllegalConsumerGroup)?;
+            let topic = resource_name(request.topic.as_ref(), Code::IllegalTopic)?;
+            self.consumer_processor
+                .forward_to_dead_letter_queue(&group, &topic, &request.receipt_handle)
+                .await
+        }
+        .await;
+        Ok(Response::new(v2::ForwardMessageToDeadLetterQueueResponse {
+            status: Some(to_status(&result)),
+        }))
+    }
+
+    async fn end_transaction(
+        &self,
+        request: Request<v2::EndTransactionRequest>,
+    ) -> GrpcResult<v2::EndTransactionResponse> {
+        let result = self.end_transaction_inner(request.get_ref()).await;
+        Ok(Response::new(v2::EndTransactionResponse {
+            status: Some(to_status(&result)),
+        }))
+    }
+
+    type TelemetryStream = ReceiverStream<std::result::Result<v2::TelemetryCommand, tonic::Status>>;
+
+    async fn telemetry(
+        &self,
+        request: Request<Streaming<v2::TelemetryCommand>>,
+    ) -> GrpcResult<Self::TelemetryStream> {
+        let client_id =
+            client_id(&request).map_err(|err| tonic::Status::invalid_argument(err.to_string()))?;
+        let mut inbound = request.into_inner();
+        let (tx, rx) = mpsc::channel(16);
+        let client_processor = self.client_processor.clone();
+        let max_body_size = self.config.max_message_body_size as i32;
+        tokio::spawn(async move {
+            while let Some(command) = inbound.next().await {
+                let command = match command {
+                    Ok(command) => command,
+                    Err(status) => {
+                        debug!(
+                            "telemetry stream of client {} failed: {}",
+                            client_id, status
+                        );
+                        break;
+                    }
+                };
+                let Some(Command::Settings(mut settings)) = command.command else {
+                    debug!("ignore telemetry command of client {}", client_id);
+                    continue;
+                };
+                client_processor.update_settings(&client_id, to_client_settings(&settings));
+                if let Err(err) = client_processor.heartbeat(&client_id).await {
+                    warn!("register client {} on brokers failed: {}", client_id, err);
+                }
+                if let Some(PubSub::Publishing(publishing)) = settings.pub_sub.as_mut() {
+                    publishing.max_body_size = max_body_size;
+                }
+                let reply = v2::TelemetryCommand {
+                    status: Some(ok_status()),
+                    command: Some(Command::Settings(settings)),
+                };
+                if tx.send(Ok(reply)).await.is_err() {
+                    break;
+                }
+            }
+            info!("telemetry stream of client {} closed", client_id);
+            client_processor.unregister(&client_id).await;
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn notify_client_termination(
+        &self,
+        request: Request<v2::NotifyClientTerminationRequest>,
+    ) -> GrpcResult<v2::NotifyClientTerminationResponse> {
+        let result = client_id(&request);
+        if let Ok(client_id) = result.as_ref() {
+            self.client_processor.unregister(client_id).await;
+        }
+        Ok(Response::new(v2::NotifyClientTerminationResponse {
+            status: Some(to_status(&result)),
+        }))
+    }
+
+    async fn change_invisible_duration(
+        &self,
+        request: Request<v2::ChangeInvisibleDurationRequest>,
+    ) -> GrpcResult<v2::ChangeInvisibleDurationResponse> {
+        let response = match self
+            .change_invisible_duration_inner(request.get_ref())
+            .await
+        {
+            Ok(receipt_handle) => v2::ChangeInvisibleDurationResponse {
+                status: Some(ok_status()),
+                receipt_handle,
+            },
+            Err(err) => v2::ChangeInvisibleDurationResponse {
+                status: Some(err.to_status()),
+                receipt_handle: String::new(),
+            },
+        };
+        Ok(Response::new(response))
+    }
+}
+
+/// [`ReceiveOptions`] owning its strings, so that it can move into a spawned task.
+struct OwnedReceiveOptions {
+    group: String,
+    topic: String,
+    broker_name: Option<String>,
+    queue_id: Option<i32>,
+    expression_type: String,
+    expression: String,
+    batch_size: usize,
+    invisible_duration: Duration,
+    long_polling_timeout: Duration,
+}
+
+impl OwnedReceiveOptions {
+    fn as_receive_options(&self) -> ReceiveOptions<'_> {
+        ReceiveOptions {
+            group: &self.group,
+            topic: &self.topic,
+            broker_name: self.broker_name.as_deref(),
+            queue_id: self.queue_id,
+            expression_type: &self.expression_type,
+            expression: &self.expression,
+            batch_size: self.batch_size,
+            invisible_duration: self.invisible_duration,
+            long_polling_timeout: self.long_polling_timeout,
+        }
+    }
+}
+
+fn client_id<T>(request: &Request<T>) -> Result<String> {
+    request
+        .metadata()
+        .get(CLIENT_ID_KEY)
+        .and_then(|value| value.to_str().ok())
+        .filter(|client_id| !client_id.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| ProxyError::new(Code::ClientIdRequired, "client id is required"))
+}
+
+fn resource(name: &str) -> v2::Resource {
+    v2::Resource {
+        resource_namespace: String::new(),
+        name: name.to_string(),
+    }
+}
+
+fn resource_name(resource: Option<&v2::Resource>, code: Code) -> Result<String> {
+    resource
+        .map(|resource| resource.name.trim())
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| ProxyError::new(code, "resource name is required"))
+}
+
+fn permission(view: &QueueView) -> v2::Permission {
+    match (view.readable, view.writeable) {
+        (true, true) => v2::Permission::ReadWrite,
+        (true, false) => v2::Permission::Read,
+        (false, true) => v2::Permission::Write,
+        (false, false) => v2::Permission::None,
+    }
+}
+
+fn to_client_settings(settings: &v2::Settings) -> ClientSettings {
+    match settings.pub_sub.as_ref() {
+        Some(PubSub::Publishing(publishing)) => ClientSettings {
+            topics: publishing
+                .topics
+                .iter()
+                .map(|topic| topic.name.clone())
+                .collect(),
+            ..Default::default()
+        },
+        Some(PubSub::Subscription(subscription)) => ClientSettings {
+            topics: Vec::new(),
+            consumer_group: subscription.group.as_ref().map(|group| group.name.clone()),
+            subscriptions: subscription
+                .subscriptions
+                .iter()
+                .filter_map(|entry| {
+                    let topic = entry.topic.as_ref()?.name.clone();
+                    let (expression_type, expression) = match entry.expression.as_ref() {
+                        Some(filter) if filter.r#type == v2::FilterType::Sql as i32 => {
+                            (ExpressionType::SQL92, filter.expression.clone())
+                        }
+                        Some(filter) => (ExpressionType::TAG, filter.expression.clone()),
+                        None => (ExpressionType::TAG, "*".to_string()),
+                    };
+                    Some(SubscriptionEntry {
+                        topic,
+                        expression_type: expression_type.to_string(),
+                        expression,
+                    })
+                })
+                .collect(),
+        },
+        None => ClientSettings::default(),
+    }
+}
+
+fn ok_status() -> v2::Status {
+    v2::Status {
+        code: Code::Ok as i32,
+        message: "OK".to_string(),
+    }
+}
+
+fn to_status<T>(result: &Result<T>) -> v2::Status {
+    match result {
+        Ok(_) => ok_status(),
+        Err(err) => err.to_status(),
+    }
+}
+
+fn status_response(status: v2::Status) -> v2::ReceiveMessageResponse {
+    v2::ReceiveMessageResponse {
+        content: Some(receive_message_response::Content::Status(status)),
+    }
+}
+
+/// Status of a batch request: the status of its only entry, `OK` when all entries succeeded,
+/// otherwise `MULTIPLE_RESULTS`.
+fn merged_status<'a>(
+    statuses: impl ExactSizeIterator<Item = Option<&'a v2::Status>>,
+) -> v2::Status {
+    let statuses = statuses.flatten().collect::<Vec<_>>();
+    if statuses.len() == 1 {
+        return statuses[0].clone();
+    }
+    if statuses.iter().all(|status| status.code == Code::Ok as i32) {
+        return ok_status();
+    }
+    v2::Status {
+        code: Code::MultipleResults as i32,
+        message: "some entries failed".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_status_reports_single_and_mixed_results() {
+        let failed = ProxyError::new(Code::TopicNotFound, "no route").to_status();
+        let ok = ok_status();
+        assert_eq!(merged_status([Some(&failed)].into_iter()), failed);
+        assert_eq!(merged_status([Some(&ok), Some(&ok)].into_iter()), ok);
+        assert_eq!(
+            merged_status([Some(&ok), Some(&failed)].into_iter()).code,
+            Code::MultipleResults as i32
+        );
+    }
+
+    #[test]
+    fn client_id_is_read_from_metadata() {
+        let mut request = Request::new(());
+        assert_eq!(
+            client_id(&request).unwrap_err().code(),
+            Code::ClientIdRequired
+        );
+        request
+            .metadata_mut()
+            .insert(CLIENT_ID_KEY, "client-1".parse().unwrap());
+        assert_eq!(client_id(&request).unwrap(), "client-1");
+    }
+
+    #[test]
+    fn subscription_settings_become_client_settings() {
+        let settings = v2::Settings {
+            pub_sub: Some(PubSub::Subscription(v2::Subscription {
+                group: Some(resource("GroupA")),
+                subscriptions: vec![v2::SubscriptionEntry {
+                    topic: Some(resource("TopicTest")),
+                    expression: Some(v2::FilterExpression {
+                        r#type: v2::FilterType::Sql as i32,
+                        expression: "a > 1".to_string(),
+                    }),
+                }],
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_client_settings(&settings),
+            ClientSettings {
+                topics: Vec::new(),
+                consumer_group: Some("GroupA".to_string()),
+                subscriptions: vec![SubscriptionEntry {
+                    topic: "TopicTest".to_string(),
+                    expression_type: ExpressionType::SQL92.to_string(),
+                    expression: "a > 1".to_string(),
+                }],
+            }
+        );
+    }
+}
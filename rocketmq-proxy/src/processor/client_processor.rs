@@ -0,0 +1,219 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use parking_lot::RwLock;
+use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::filter::expression_type::ExpressionType;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::protocol::header::heartbeat_request_header::HeartbeatRequestHeader;
+use rocketmq_remoting::protocol::header::unregister_client_request_header::UnregisterClientRequestHeader;
+use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
+use rocketmq_remoting::protocol::heartbeat::consumer_data::ConsumerData;
+use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
+use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use tracing::warn;
+
+use crate::service::cluster_service::ClusterService;
+use crate::Result;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionEntry {
+    pub topic: String,
+    pub expression_type: String,
+    pub expression: String,
+}
+
+/// What a client announced about itself in the settings of its telemetry stream.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientSettings {
+    /// Topics a producer publishes to.
+    pub topics: Vec<String>,
+    /// Group of a consumer.
+    pub consumer_group: Option<String>,
+    pub subscriptions: Vec<SubscriptionEntry>,
+}
+
+/// Tracks the clients connected to the proxy and keeps them registered on the brokers.
+pub struct ClientProcessor {
+    cluster_service: Arc<ClusterService>,
+    clients: RwLock<HashMap<String /* client id */, ClientSettings>>,
+}
+
+impl ClientProcessor {
+    pub fn new(cluster_service: Arc<ClusterService>) -> Self {
+        ClientProcessor {
+            cluster_service,
+            clients: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn update_settings(&self, client_id: &str, settings: ClientSettings) {
+        self.clients.write().insert(client_id.to_string(), settings);
+    }
+
+    pub fn get_settings(&self, client_id: &str) -> Option<ClientSettings> {
+        self.clients.read().get(client_id).cloned()
+    }
+
+    /// Registers the consumer `client_id` on the masters of the brokers serving its
+    /// subscriptions. Producers need no registration.
+    pub async fn heartbeat(&self, client_id: &str) -> Result<()> {
+        let Some(settings) = self.get_settings(client_id) else {
+            return Ok(());
+        };
+        let Some(consumer_group) = settings.consumer_group.as_ref() else {
+            return Ok(());
+        };
+        let consumer_data = ConsumerData {
+            group_name: consumer_group.clone(),
+            consume_type: ConsumeType::ConsumePassively,
+            message_model: MessageModel::Clustering,
+            consume_from_where: ConsumeFromWhere::ConsumeFromLastOffset,
+            subscription_data_set: settings
+                .subscriptions
+                .iter()
+                .map(build_subscription_data)
+                .collect(),
+            unit_mode: false,
+        };
+        let heartbeat_data = HeartbeatData {
+            client_id: client_id.to_string(),
+            consumer_data_set: HashSet::from([consumer_data]),
+            ..Default::default()
+        };
+        let body = Bytes::from(heartbeat_data.encode());
+        for addr in self.broker_addrs(&settings).await? {
+            let request = RemotingCommand::create_request_command(
+                RequestCode::HeartBeat,
+                HeartbeatRequestHeader::default(),
+            )
+            .set_body(Some(body.clone()));
+            let response = self.cluster_service.invoke(Some(&addr), request).await?;
+            ClusterService::check_response(Some(&addr), response)?;
+        }
+        Ok(())
+    }
+
+    /// Forgets `client_id` and unregisters it from the brokers it was registered on.
+    pub async fn unregister(&self, client_id: &str) {
+        let Some(settings) = self.clients.write().remove(client_id) else {
+            return;
+        };
+        let Some(consumer_group) = settings.consumer_group.clone() else {
+            return;
+        };
+        let addrs = match self.broker_addrs(&settings).await {
+            Ok(addrs) => addrs,
+            Err(err) => {
+                warn!("unregister client {} failed: {}", client_id, err);
+                return;
+            }
+        };
+        for addr in addrs {
+            let request_header = UnregisterClientRequestHeader {
+                client_id: client_id.to_string(),
+                producer_group: None,
+                consumer_group: Some(consumer_group.clone()),
+                ..Default::default()
+            };
+            let request = RemotingCommand::create_request_command(
+                RequestCode::UnregisterClient,
+                request_header,
+            );
+            self.cluster_service.invoke_oneway(&addr, request).await;
+        }
+    }
+
+    /// Masters of the brokers serving the topics `settings` subscribes to.
+    async fn broker_addrs(&self, settings: &ClientSettings) -> Result<HashSet<String>> {
+        let mut addrs = HashSet::new();
+        for subscription in &settings.subscriptions {
+            let route = self
+                .cluster_service
+                .get_topic_route(&subscription.topic)
+                .await?;
+            for queue_data in &route.queue_datas {
+                if let Ok(addr) = ClusterService::find_master_addr(&route, queue_data.broker_name())
+                {
+                    addrs.insert(addr);
+                }
+            }
+        }
+        Ok(addrs)
+    }
+}
+
+fn build_subscription_data(entry: &SubscriptionEntry) -> SubscriptionData {
+    let sub_string = if entry.expression.trim().is_empty() {
+        SubscriptionData::SUB_ALL.to_string()
+    } else {
+        entry.expression.trim().to_string()
+    };
+    let tags_set = if entry.expression_type == ExpressionType::TAG
+        && sub_string != SubscriptionData::SUB_ALL
+    {
+        sub_string
+            .split("||")
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    SubscriptionData {
+        topic: entry.topic.clone(),
+        sub_string,
+        tags_set,
+        expression_type: entry.expression_type.clone(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_tag_subscription_data() {
+        let data = build_subscription_data(&SubscriptionEntry {
+            topic: "TopicTest".to_string(),
+            expression_type: ExpressionType::TAG.to_string(),
+            expression: "TagA || TagB".to_string(),
+        });
+        assert_eq!(data.sub_string, "TagA || TagB");
+        assert_eq!(
+            data.tags_set,
+            HashSet::from(["TagA".to_string(), "TagB".to_string()])
+        );
+
+        let data = build_subscription_data(&SubscriptionEntry {
+            topic: "TopicTest".to_string(),
+            expression_type: ExpressionType::TAG.to_string(),
+            expression: String::new(),
+        });
+        assert_eq!(data.sub_string, SubscriptionData::SUB_ALL);
+        assert!(data.tags_set.is_empty());
+    }
+}
@@ -0,0 +1,692 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::filter::expression_type::ExpressionType;
+use rocketmq_common::common::message::message_client_id_setter::MessageClientIDSetter;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::sys_flag::pull_sys_flag::PullSysFlag;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::MessageDecoder;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::header::get_max_offset_request_header::GetMaxOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::get_max_offset_response_header::GetMaxOffsetResponseHeader;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::SendMessageRequestHeader;
+use rocketmq_remoting::protocol::header::pull_message_request_header::PullMessageRequestHeader;
+use rocketmq_remoting::protocol::header::pull_message_response_header::PullMessageResponseHeader;
+use rocketmq_remoting::protocol::header::query_consumer_offset_request_header::QueryConsumerOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::query_consumer_offset_response_header::QueryConsumerOffsetResponseHeader;
+use rocketmq_remoting::protocol::header::update_consumer_offset_header::UpdateConsumerOffsetRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use tokio::sync::Mutex;
+use tokio::sync::OwnedMutexGuard;
+use tokio::time;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::error::ProxyError;
+use crate::processor::producer_processor::ProducerProcessor;
+use crate::processor::receipt_handle::ReceiptHandle;
+use crate::proto::v2::Code;
+use crate::service::cluster_service::ClusterService;
+use crate::Result;
+
+/// What a `ReceiveMessage` request asks for.
+#[derive(Debug, Clone)]
+pub struct ReceiveOptions<'a> {
+    pub group: &'a str,
+    pub topic: &'a str,
+    /// Only receive from this broker, from any broker when `None`.
+    pub broker_name: Option<&'a str>,
+    /// Only receive from this queue, from any queue when `None`.
+    pub queue_id: Option<i32>,
+    pub expression_type: &'a str,
+    pub expression: &'a str,
+    pub batch_size: usize,
+    pub invisible_duration: Duration,
+    pub long_polling_timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReceivedMessage {
+    pub message: Arc<MessageExt>,
+    pub receipt_handle: String,
+    pub delivery_attempt: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QueueKey {
+    group: String,
+    topic: String,
+    broker_name: String,
+    queue_id: i32,
+}
+
+#[derive(Debug)]
+struct InFlightMessage {
+    message: Arc<MessageExt>,
+    invisible_until: u64,
+    delivery_attempt: i32,
+}
+
+/// Consume progress of a consumer group on one queue.
+#[derive(Debug, Default)]
+struct QueueState {
+    /// Offset of the next message to pull, `None` until read from the broker.
+    next_offset: Option<i64>,
+    committed_offset: Option<i64>,
+    in_flight: BTreeMap<i64 /* queue offset */, InFlightMessage>,
+}
+
+impl QueueState {
+    /// Offset before which every message has been acked.
+    fn consumed_offset(&self) -> Option<i64> {
+        self.in_flight.keys().next().copied().or(self.next_offset)
+    }
+
+    /// How many new messages may be pulled while keeping at most `max_in_flight` in flight.
+    fn pull_capacity(&self, max_in_flight: usize) -> usize {
+        max_in_flight.saturating_sub(self.in_flight.len())
+    }
+}
+
+struct PullResult {
+    next_begin_offset: i64,
+    messages: Vec<MessageExt>,
+}
+
+/// Serves simple consumers on top of pull: the proxy keeps the messages it delivered in flight
+/// until they are acked, delivers them again once their invisible duration elapses and only
+/// commits the consumer offset up to the oldest message still in flight.
+pub struct ConsumerProcessor {
+    cluster_service: Arc<ClusterService>,
+    producer_processor: Arc<ProducerProcessor>,
+    queues: parking_lot::Mutex<HashMap<QueueKey, Arc<Mutex<QueueState>>>>,
+    next_queue_index: AtomicUsize,
+}
+
+impl ConsumerProcessor {
+    pub fn new(
+        cluster_service: Arc<ClusterService>,
+        producer_processor: Arc<ProducerProcessor>,
+    ) -> Self {
+        ConsumerProcessor {
+            cluster_service,
+            producer_processor,
+            queues: parking_lot::Mutex::new(HashMap::new()),
+            next_queue_index: AtomicUsize::new(0),
+        }
+    }
+
+    /// Receives up to `batch_size` messages, waiting at most `long_polling_timeout` for the
+    /// first one. Returns an empty list when none arrived in time.
+    pub async fn receive_message(
+        &self,
+        options: &ReceiveOptions<'_>,
+    ) -> Result<Vec<ReceivedMessage>> {
+        let deadline = Instant::now() + options.long_polling_timeout;
+        let poll_interval =
+            Duration::from_millis(self.cluster_service.config().receive_poll_interval_millis);
+        loop {
+            let route = self.cluster_service.get_topic_route(options.topic).await?;
+            let queues = readable_queues(&route, options.broker_name, options.queue_id);
+            if queues.is_empty() {
+                return Err(ProxyError::new(
+                    Code::Forbidden,
+                    format!("topic {} has no readable queue", options.topic),
+                ));
+            }
+            let start = self.next_queue_index.fetch_add(1, Ordering::Relaxed);
+            let mut received = Vec::new();
+            let mut last_error = None;
+            for index in 0..queues.len() {
+                if received.len() >= options.batch_size {
+                    break;
+                }
+                let (broker_name, queue_id) = &queues[(start + index) % queues.len()];
+                let key = QueueKey {
+                    group: options.group.to_string(),
+                    topic: options.topic.to_string(),
+                    broker_name: broker_name.clone(),
+                    queue_id: *queue_id,
+                };
+                if let Err(err) = self
+                    .receive_from_queue(options, &route, key, &mut received)
+                    .await
+                {
+                    warn!(
+                        "receive message of group {} from {}@{} failed: {}",
+                        options.group, queue_id, broker_name, err
+                    );
+                    last_error = Some(err);
+                }
+            }
+            if !received.is_empty() {
+                return Ok(received);
+            }
+            if let Some(err) = last_error {
+                return Err(err);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(received);
+            }
+            time::sleep(poll_interval.min(deadline - now)).await;
+        }
+    }
+
+    async fn receive_from_queue(
+        &self,
+        options: &ReceiveOptions<'_>,
+        route: &TopicRouteData,
+        key: QueueKey,
+        received: &mut Vec<ReceivedMessage>,
+    ) -> Result<()> {
+        let addr = ClusterService::find_master_addr(route, &key.broker_name)?;
+        let mut state = self.queue_state(&key).lock_owned().await;
+        let now = get_current_millis();
+        let invisible_until = now + options.invisible_duration.as_millis() as u64;
+        let max_delivery_attempts = self.cluster_service.config().max_delivery_attempts;
+        let max_in_flight = self
+            .cluster_service
+            .config()
+            .max_in_flight_messages_per_queue;
+
+        let expired = state
+            .in_flight
+            .iter()
+            .filter(|(_, in_flight)| in_flight.invisible_until <= now)
+            .map(|(offset, _)| *offset)
+            .take(options.batch_size - received.len())
+            .collect::<Vec<_>>();
+        for offset in expired {
+            let Some(in_flight) = state.in_flight.get_mut(&offset) else {
+                continue;
+            };
+            in_flight.delivery_attempt += 1;
+            in_flight.invisible_until = invisible_until;
+            if in_flight.delivery_attempt > max_delivery_attempts {
+                let message = in_flight.message.clone();
+                let delivery_attempt = in_flight.delivery_attempt;
+                match self
+                    .send_to_dead_letter_queue(&addr, &key.group, &message, delivery_attempt)
+                    .await
+                {
+                    Ok(()) => {
+                        state.in_flight.remove(&offset);
+                    }
+                    Err(err) => warn!(
+                        "send message {} of group {} to dead letter queue failed: {}",
+                        message.msg_id(),
+                        key.group,
+                        err
+                    ),
+                }
+                continue;
+            }
+            received.push(Self::received_message(&key, offset, in_flight));
+        }
+
+        // Only redeliveries are served from a queue with too many messages in flight.
+        let remaining =
+            (options.batch_size - received.len()).min(state.pull_capacity(max_in_flight));
+        if remaining > 0 {
+            let next_offset = match state.next_offset {
+                Some(offset) => offset,
+                None => self.query_init_offset(&addr, &key).await?,
+            };
+            let pull_result = self
+                .pull(&addr, &key, next_offset, remaining, options)
+                .await?;
+            state.next_offset = Some(pull_result.next_begin_offset);
+            for message in pull_result.messages {
+                if !tag_matches(options.expression_type, options.expression, &message) {
+                    continue;
+                }
+                let offset = message.queue_offset();
+                let in_flight = InFlightMessage {
+                    delivery_attempt: message.reconsume_times() + 1,
+                    message: Arc::new(message),
+                    invisible_until,
+                };
+                received.push(Self::received_message(&key, offset, &in_flight));
+                state.in_flight.insert(offset, in_flight);
+            }
+        }
+        self.commit_offset(&addr, &key, &mut state).await;
+        Ok(())
+    }
+
+    /// Acks the message `receipt_handle` was issued for, so it is not delivered again.
+    pub async fn ack_message(&self, group: &str, topic: &str, receipt_handle: &str) -> Result<()> {
+        let (handle, key, mut state) = self.lock_in_flight(group, topic, receipt_handle).await?;
+        state.in_flight.remove(&handle.queue_offset);
+        let route = self.cluster_service.get_topic_route(topic).await?;
+        let addr = ClusterService::find_master_addr(&route, &key.broker_name)?;
+        self.commit_offset(&addr, &key, &mut state).await;
+        Ok(())
+    }
+
+    /// Makes the message `receipt_handle` was issued for invisible for `invisible_duration`
+    /// from now on, returning the receipt handle replacing `receipt_handle`.
+    pub async fn change_invisible_duration(
+        &self,
+        group: &str,
+        topic: &str,
+        receipt_handle: &str,
+        invisible_duration: Duration,
+    ) -> Result<String> {
+        let (mut handle, _, mut state) = self.lock_in_flight(group, topic, receipt_handle).await?;
+        let in_flight = state
+            .in_flight
+            .get_mut(&handle.queue_offset)
+            .expect("checked by lock_in_flight");
+        in_flight.invisible_until = get_current_millis() + invisible_duration.as_millis() as u64;
+        handle.invisible_until = in_flight.invisible_until;
+        Ok(handle.encode())
+    }
+
+    /// Moves the message `receipt_handle` was issued for to the dead letter queue of `group`.
+    pub async fn forward_to_dead_letter_queue(
+        &self,
+        group: &str,
+        topic: &str,
+        receipt_handle: &str,
+    ) -> Result<()> {
+        let (handle, key, mut state) = self.lock_in_flight(group, topic, receipt_handle).await?;
+        let route = self.cluster_service.get_topic_route(topic).await?;
+        let addr = ClusterService::find_master_addr(&route, &key.broker_name)?;
+        let in_flight = &state.in_flight[&handle.queue_offset];
+        let (message, delivery_attempt) = (in_flight.message.clone(), in_flight.delivery_attempt);
+        self.send_to_dead_letter_queue(&addr, group, &message, delivery_attempt)
+            .await?;
+        state.in_flight.remove(&handle.queue_offset);
+        self.commit_offset(&addr, &key, &mut state).await;
+        Ok(())
+    }
+
+    fn received_message(
+        key: &QueueKey,
+        offset: i64,
+        in_flight: &InFlightMessage,
+    ) -> ReceivedMessage {
+        let receipt_handle = ReceiptHandle {
+            queue_offset: offset,
+            queue_id: key.queue_id,
+            invisible_until: in_flight.invisible_until,
+            broker_name: key.broker_name.clone(),
+        };
+        ReceivedMessage {
+            message: in_flight.message.clone(),
+            receipt_handle: receipt_handle.encode(),
+            delivery_attempt: in_flight.delivery_attempt,
+        }
+    }
+
+    fn queue_state(&self, key: &QueueKey) -> Arc<Mutex<QueueState>> {
+        self.queues.lock().entry(key.clone()).or_default().clone()
+    }
+
+    /// Locks the queue `receipt_handle` was issued for, failing unless the handle is the
+    /// current one of a message in flight.
+    async fn lock_in_flight(
+        &self,
+        group: &str,
+        topic: &str,
+        receipt_handle: &str,
+    ) -> Result<(ReceiptHandle, QueueKey, OwnedMutexGuard<QueueState>)> {
+        let invalid = || {
+            ProxyError::new(
+                Code::InvalidReceiptHandle,
+                format!("receipt handle {} is invalid or expired", receipt_handle),
+            )
+        };
+        let handle = ReceiptHandle::decode(receipt_handle).ok_or_else(invalid)?;
+        let key = QueueKey {
+            group: group.to_string(),
+            topic: topic.to_string(),
+            broker_name: handle.broker_name.clone(),
+            queue_id: handle.queue_id,
+        };
+        let state = self.queues.lock().get(&key).cloned().ok_or_else(invalid)?;
+        let state = state.lock_owned().await;
+        match state.in_flight.get(&handle.queue_offset) {
+            Some(in_flight) if in_flight.invisible_until == handle.invisible_until => {
+                Ok((handle, key, state))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Offset a consumer group starts from on a queue it never consumed: the first message
+    /// when the queue still holds all of its messages, otherwise the end of the queue.
+    async fn query_init_offset(&self, addr: &str, key: &QueueKey) -> Result<i64> {
+        let request_header = QueryConsumerOffsetRequestHeader {
+            consumer_group: key.group.clone(),
+            topic: key.topic.clone(),
+            queue_id: key.queue_id,
+            set_zero_if_not_found: None,
+            topic_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::QueryConsumerOffset,
+            request_header,
+        );
+        let response = self.cluster_service.invoke(Some(addr), request).await?;
+        if ResponseCode::from(response.code()) != ResponseCode::QueryNotFound {
+            let response = ClusterService::check_response(Some(addr), response)?;
+            return Ok(response
+                .decode_command_custom_header_fast::<QueryConsumerOffsetResponseHeader>()
+                .and_then(|header| header.offset)
+                .unwrap_or_default());
+        }
+
+        let request_header = GetMaxOffsetRequestHeader {
+            topic: key.topic.clone(),
+            queue_id: key.queue_id,
+            committed: true,
+            topic_request_header: None,
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::GetMaxOffset, request_header);
+        let response = self.cluster_service.invoke(Some(addr), request).await?;
+        let response = ClusterService::check_response(Some(addr), response)?;
+        Ok(response
+            .decode_command_custom_header_fast::<GetMaxOffsetResponseHeader>()
+            .map(|header| header.offset)
+            .unwrap_or_default())
+    }
+
+    async fn pull(
+        &self,
+        addr: &str,
+        key: &QueueKey,
+        offset: i64,
+        max_msg_nums: usize,
+        options: &ReceiveOptions<'_>,
+    ) -> Result<PullResult> {
+        let expression = if options.expression.trim().is_empty() {
+            "*"
+        } else {
+            options.expression
+        };
+        let request_header = PullMessageRequestHeader {
+            consumer_group: key.group.clone(),
+            topic: key.topic.clone(),
+            queue_id: Some(key.queue_id),
+            queue_offset: offset,
+            max_msg_nums: max_msg_nums as i32,
+            sys_flag: PullSysFlag::build_sys_flag(false, false, true, false) as i32,
+            commit_offset: 0,
+            suspend_timeout_millis: 0,
+            subscription: Some(expression.to_string()),
+            sub_version: 0,
+            expression_type: Some(options.expression_type.to_string()),
+            max_msg_bytes: None,
+            request_source: None,
+            proxy_forward_client_id: None,
+            topic_request: None,
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::PullMessage, request_header);
+        let response = self.cluster_service.invoke(Some(addr), request).await?;
+        let next_begin_offset = response
+            .decode_command_custom_header_fast::<PullMessageResponseHeader>()
+            .and_then(|header| header.next_begin_offset)
+            .unwrap_or(offset);
+        match ResponseCode::from(response.code()) {
+            ResponseCode::Success => {
+                let messages = response
+                    .get_body()
+                    .map(|body| MessageDecoder::decodes(&mut body.clone(), true, true))
+                    .unwrap_or_default();
+                Ok(PullResult {
+                    next_begin_offset,
+                    messages,
+                })
+            }
+            ResponseCode::PullNotFound
+            | ResponseCode::PullRetryImmediately
+            | ResponseCode::PullOffsetMoved => Ok(PullResult {
+                next_begin_offset,
+                messages: Vec::new(),
+            }),
+            _ => Err(ProxyError::MQBrokerError(
+                response.code(),
+                addr.to_string(),
+                response.remark().cloned().unwrap_or_default(),
+            )),
+        }
+    }
+
+    async fn commit_offset(&self, addr: &str, key: &QueueKey, state: &mut QueueState) {
+        let Some(offset) = state.consumed_offset() else {
+            return;
+        };
+        if state.committed_offset == Some(offset) {
+            return;
+        }
+        let request_header = UpdateConsumerOffsetRequestHeader {
+            consumer_group: key.group.clone(),
+            topic: key.topic.clone(),
+            queue_id: Some(key.queue_id),
+            commit_offset: Some(offset),
+            topic_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::UpdateConsumerOffset,
+            request_header,
+        );
+        self.cluster_service.invoke_oneway(addr, request).await;
+        state.committed_offset = Some(offset);
+    }
+
+    /// Stores a copy of `message` in the dead letter queue of `group` on the broker at `addr`.
+    async fn send_to_dead_letter_queue(
+        &self,
+        addr: &str,
+        group: &str,
+        message: &MessageExt,
+        delivery_attempt: i32,
+    ) -> Result<()> {
+        let mut properties = message.properties().clone();
+        let origin_message_id = MessageClientIDSetter::get_uniq_id(message.message_inner())
+            .unwrap_or_else(|| message.msg_id().to_string());
+        properties.insert(
+            MessageConst::PROPERTY_ORIGIN_MESSAGE_ID.to_string(),
+            origin_message_id,
+        );
+        properties.insert(
+            MessageConst::PROPERTY_RETRY_TOPIC.to_string(),
+            message.topic().to_string(),
+        );
+        let dlq_message = Message {
+            topic: mix_all::get_dlq_topic(group),
+            flag: message.flag(),
+            properties,
+            body: message.body(),
+            ..Default::default()
+        };
+        let request_header = SendMessageRequestHeader {
+            producer_group: group.to_string(),
+            topic: dlq_message.topic.clone(),
+            default_topic: TopicValidator::AUTO_CREATE_TOPIC_KEY_TOPIC.to_string(),
+            default_topic_queue_nums: 1,
+            queue_id: Some(0),
+            sys_flag: 0,
+            born_timestamp: message.born_timestamp(),
+            flag: dlq_message.flag,
+            properties: Some(MessageDecoder::message_properties_to_string(
+                &dlq_message.properties,
+            )),
+            reconsume_times: Some(delivery_attempt - 1),
+            unit_mode: Some(false),
+            batch: Some(false),
+            max_reconsume_times: None,
+            topic_request_header: None,
+        };
+        self.producer_processor
+            .send_to_broker(addr, request_header, &dlq_message)
+            .await
+            .map(|_| ())
+    }
+}
+
+/// `(broker name, queue id)` of the readable queues of `route`, narrowed to `broker_name` and
+/// `queue_id` when given.
+fn readable_queues(
+    route: &TopicRouteData,
+    broker_name: Option<&str>,
+    queue_id: Option<i32>,
+) -> Vec<(String, i32)> {
+    let mut queues = Vec::new();
+    for queue_data in &route.queue_datas {
+        if !PermName::is_readable(queue_data.perm())
+            || broker_name.is_some_and(|name| name != queue_data.broker_name())
+        {
+            continue;
+        }
+        for id in 0..queue_data.read_queue_nums() as i32 {
+            if queue_id.is_none_or(|queue_id| queue_id == id) {
+                queues.push((queue_data.broker_name().to_string(), id));
+            }
+        }
+    }
+    queues
+}
+
+/// Brokers only filter tags by hash code, so tag expressions are checked again here.
+fn tag_matches(expression_type: &str, expression: &str, message: &MessageExt) -> bool {
+    if expression_type != ExpressionType::TAG {
+        return true;
+    }
+    let expression = expression.trim();
+    if expression.is_empty() || expression == "*" {
+        return true;
+    }
+    message.get_tags().is_some_and(|tag| {
+        expression
+            .split("||")
+            .any(|expected| expected.trim() == tag.as_str())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::route::route_data_view::QueueData;
+
+    use super::*;
+
+    fn message_with_tag(tag: Option<&str>) -> MessageExt {
+        let mut message = MessageExt::default();
+        if let Some(tag) = tag {
+            message.message.set_tags(tag.to_string());
+        }
+        message
+    }
+
+    #[test]
+    fn tag_expressions_match_message_tags() {
+        let tag_a = message_with_tag(Some("TagA"));
+        let untagged = message_with_tag(None);
+        assert!(tag_matches(ExpressionType::TAG, "*", &untagged));
+        assert!(tag_matches(ExpressionType::TAG, "", &untagged));
+        assert!(tag_matches(ExpressionType::TAG, "TagA", &tag_a));
+        assert!(tag_matches(ExpressionType::TAG, "TagB || TagA", &tag_a));
+        assert!(!tag_matches(ExpressionType::TAG, "TagB", &tag_a));
+        assert!(!tag_matches(ExpressionType::TAG, "TagA", &untagged));
+        assert!(tag_matches(ExpressionType::SQL92, "a > 1", &untagged));
+    }
+
+    #[test]
+    fn readable_queues_skip_unreadable_brokers() {
+        let mut route = TopicRouteData::new();
+        route.queue_datas = vec![
+            QueueData::new(
+                "broker-a".to_string(),
+                2,
+                2,
+                PermName::PERM_READ | PermName::PERM_WRITE,
+                0,
+            ),
+            QueueData::new("broker-b".to_string(), 2, 2, PermName::PERM_WRITE, 0),
+        ];
+        assert_eq!(
+            readable_queues(&route, None, None),
+            vec![("broker-a".to_string(), 0), ("broker-a".to_string(), 1)]
+        );
+        assert_eq!(
+            readable_queues(&route, Some("broker-a"), Some(1)),
+            vec![("broker-a".to_string(), 1)]
+        );
+        assert!(readable_queues(&route, Some("broker-b"), None).is_empty());
+    }
+
+    #[test]
+    fn consumed_offset_stops_at_oldest_message_in_flight() {
+        let mut state = QueueState::default();
+        assert_eq!(state.consumed_offset(), None);
+        state.next_offset = Some(10);
+        assert_eq!(state.consumed_offset(), Some(10));
+        for offset in [7, 8] {
+            state.in_flight.insert(
+                offset,
+                InFlightMessage {
+                    message: Arc::new(MessageExt::default()),
+                    invisible_until: 0,
+                    delivery_attempt: 1,
+                },
+            );
+        }
+        assert_eq!(state.consumed_offset(), Some(7));
+        state.in_flight.remove(&7);
+        assert_eq!(state.consumed_offset(), Some(8));
+    }
+
+    #[test]
+    fn pull_capacity_is_bounded_by_messages_in_flight() {
+        let mut state = QueueState::default();
+        assert_eq!(state.pull_capacity(2), 2);
+        for offset in 0..3 {
+            state.in_flight.insert(
+                offset,
+                InFlightMessage {
+                    message: Arc::new(MessageExt::default()),
+                    invisible_until: 0,
+                    delivery_attempt: 1,
+                },
+            );
+        }
+        assert_eq!(state.pull_capacity(2), 0);
+        assert_eq!(state.pull_capacity(4), 1);
+        state.in_flight.remove(&0);
+        state.in_flight.remove(&1);
+        assert_eq!(state.pull_capacity(2), 1);
+    }
+}
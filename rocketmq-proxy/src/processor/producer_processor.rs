@@ -0,0 +1,181 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::message::message_client_id_setter::MessageClientIDSetter;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::MessageDecoder::message_properties_to_string;
+use rocketmq_common::MessageUtils::get_sharding_key_index;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::SendMessageRequestHeader;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header_v2::SendMessageRequestHeaderV2;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_response_header::SendMessageResponseHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+
+use crate::error::ProxyError;
+use crate::processor::transaction_processor::TransactionProcessor;
+use crate::proto::v2::Code;
+use crate::service::cluster_service::ClusterService;
+use crate::Result;
+
+/// Outcome of a message stored by a broker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendReceipt {
+    pub message_id: String,
+    pub transaction_id: Option<String>,
+    pub queue_offset: i64,
+}
+
+pub struct ProducerProcessor {
+    cluster_service: Arc<ClusterService>,
+    transaction_processor: Arc<TransactionProcessor>,
+    send_which_queue: AtomicUsize,
+}
+
+impl ProducerProcessor {
+    pub fn new(
+        cluster_service: Arc<ClusterService>,
+        transaction_processor: Arc<TransactionProcessor>,
+    ) -> Self {
+        ProducerProcessor {
+            cluster_service,
+            transaction_processor,
+            send_which_queue: AtomicUsize::new(0),
+        }
+    }
+
+    /// Stores `message` on one of the writable queues of its topic. Messages of the same
+    /// message group always go to the same queue, the others are spread round robin.
+    pub async fn send_message(
+        &self,
+        producer_group: &str,
+        mut message: Message,
+    ) -> Result<SendReceipt> {
+        if MessageClientIDSetter::get_uniq_id(&message).is_none() {
+            MessageClientIDSetter::set_uniq_id(&mut message);
+        }
+        let route = self
+            .cluster_service
+            .get_topic_route(message.topic())
+            .await?;
+        let queues = writable_queues(&route);
+        if queues.is_empty() {
+            return Err(ProxyError::new(
+                Code::Forbidden,
+                format!("topic {} has no writable queue", message.topic()),
+            ));
+        }
+        let index = match message.get_property(MessageConst::PROPERTY_SHARDING_KEY) {
+            Some(message_group) => get_sharding_key_index(&message_group, queues.len()),
+            None => self.send_which_queue.fetch_add(1, Ordering::Relaxed) % queues.len(),
+        };
+        let (broker_name, queue_id) = &queues[index];
+        let addr = ClusterService::find_master_addr(&route, broker_name)?;
+
+        let transactional = message
+            .get_property(MessageConst::PROPERTY_TRANSACTION_PREPARED)
+            .is_some_and(|prepared| prepared == "true");
+        let sys_flag = if transactional {
+            MessageSysFlag::TRANSACTION_PREPARED_TYPE
+        } else {
+            MessageSysFlag::TRANSACTION_NOT_TYPE
+        };
+        let request_header = SendMessageRequestHeader {
+            producer_group: producer_group.to_string(),
+            topic: message.topic().to_string(),
+            default_topic: TopicValidator::AUTO_CREATE_TOPIC_KEY_TOPIC.to_string(),
+            default_topic_queue_nums: 4,
+            queue_id: Some(*queue_id),
+            sys_flag,
+            born_timestamp: get_current_millis() as i64,
+            flag: message.flag(),
+            properties: Some(message_properties_to_string(message.properties())),
+            reconsume_times: Some(0),
+            unit_mode: Some(false),
+            batch: Some(false),
+            max_reconsume_times: None,
+            topic_request_header: None,
+        };
+        let response = self.send_to_broker(&addr, request_header, &message).await?;
+
+        let message_id = MessageClientIDSetter::get_uniq_id(&message).unwrap_or_default();
+        if transactional {
+            self.transaction_processor.add_transaction(
+                producer_group,
+                message.topic(),
+                &addr,
+                &message_id,
+                &response,
+            );
+        }
+        let transaction_id =
+            transactional.then(|| response.transaction_id().unwrap_or(&message_id).to_string());
+        Ok(SendReceipt {
+            message_id,
+            transaction_id,
+            queue_offset: response.queue_offset(),
+        })
+    }
+
+    /// Sends `message` with `request_header` to the broker at `addr`.
+    pub(crate) async fn send_to_broker(
+        &self,
+        addr: &str,
+        request_header: SendMessageRequestHeader,
+        message: &Message,
+    ) -> Result<SendMessageResponseHeader> {
+        let request_header =
+            SendMessageRequestHeaderV2::create_send_message_request_header_v2(&request_header);
+        let request =
+            RemotingCommand::create_request_command(RequestCode::SendMessageV2, request_header)
+                .set_body(message.body());
+        let response = self.cluster_service.invoke(Some(addr), request).await?;
+        let response = ClusterService::check_response(Some(addr), response)?;
+        response
+            .decode_command_custom_header_fast::<SendMessageResponseHeader>()
+            .ok_or_else(|| {
+                ProxyError::new(
+                    Code::InternalServerError,
+                    format!("illegal send message response from {}", addr),
+                )
+            })
+    }
+}
+
+/// `(broker name, queue id)` of every writable queue of a broker with a known master.
+fn writable_queues(route: &TopicRouteData) -> Vec<(String, i32)> {
+    let mut queues = Vec::new();
+    for queue_data in &route.queue_datas {
+        if !PermName::is_writeable(queue_data.perm())
+            || ClusterService::find_master_addr(route, queue_data.broker_name()).is_err()
+        {
+            continue;
+        }
+        for queue_id in 0..queue_data.write_queue_nums() {
+            queues.push((queue_data.broker_name().to_string(), queue_id as i32));
+        }
+    }
+    queues
+}
@@ -0,0 +1,79 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+const SEPARATOR: char = ' ';
+
+/// Identifies one delivery of a message received through the proxy.
+///
+/// Renewing the invisible duration of a message issues a new handle, so the deadline also
+/// tells stale handles apart from the current one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptHandle {
+    pub queue_offset: i64,
+    pub queue_id: i32,
+    /// When the message becomes visible again, in milliseconds since the epoch.
+    pub invisible_until: u64,
+    pub broker_name: String,
+}
+
+impl ReceiptHandle {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}{SEPARATOR}{}{SEPARATOR}{}{SEPARATOR}{}",
+            self.queue_offset, self.queue_id, self.invisible_until, self.broker_name
+        )
+    }
+
+    pub fn decode(receipt_handle: &str) -> Option<ReceiptHandle> {
+        let mut fields = receipt_handle.splitn(4, SEPARATOR);
+        let queue_offset = fields.next()?.parse().ok()?;
+        let queue_id = fields.next()?.parse().ok()?;
+        let invisible_until = fields.next()?.parse().ok()?;
+        let broker_name = fields.next().filter(|name| !name.is_empty())?;
+        Some(ReceiptHandle {
+            queue_offset,
+            queue_id,
+            invisible_until,
+            broker_name: broker_name.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_and_decodes() {
+        let handle = ReceiptHandle {
+            queue_offset: 42,
+            queue_id: 3,
+            invisible_until: 1_700_000_000_000,
+            broker_name: "broker-a".to_string(),
+        };
+        let encoded = handle.encode();
+        assert_eq!(encoded, "42 3 1700000000000 broker-a");
+        assert_eq!(ReceiptHandle::decode(&encoded), Some(handle));
+    }
+
+    #[test]
+    fn rejects_malformed_handles() {
+        assert_eq!(ReceiptHandle::decode(""), None);
+        assert_eq!(ReceiptHandle::decode("42 3 1700000000000"), None);
+        assert_eq!(ReceiptHandle::decode("42 3 1700000000000 "), None);
+        assert_eq!(ReceiptHandle::decode("x 3 1700000000000 broker-a"), None);
+    }
+}
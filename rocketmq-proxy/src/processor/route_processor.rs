@@ -0,0 +1,139 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use rocketmq_common::common::constant::PermName;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+
+use crate::service::cluster_service::ClusterService;
+use crate::Result;
+
+/// A queue of a topic as exposed to gRPC clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueView {
+    pub broker_name: String,
+    pub queue_id: i32,
+    pub readable: bool,
+    pub writeable: bool,
+}
+
+pub struct RouteProcessor {
+    cluster_service: Arc<ClusterService>,
+}
+
+impl RouteProcessor {
+    pub fn new(cluster_service: Arc<ClusterService>) -> Self {
+        RouteProcessor { cluster_service }
+    }
+
+    pub async fn query_route(&self, topic: &str) -> Result<Vec<QueueView>> {
+        let route = self.cluster_service.get_topic_route(topic).await?;
+        Ok(queue_views(&route))
+    }
+
+    /// Brokers a consumer of `topic` receives from. Each assignment covers all the queues of
+    /// its broker.
+    pub async fn query_assignment(&self, topic: &str) -> Result<Vec<String>> {
+        let route = self.cluster_service.get_topic_route(topic).await?;
+        let mut broker_names = route
+            .queue_datas
+            .iter()
+            .filter(|queue_data| {
+                PermName::is_readable(queue_data.perm())
+                    && queue_data.read_queue_nums() > 0
+                    && ClusterService::find_master_addr(&route, queue_data.broker_name()).is_ok()
+            })
+            .map(|queue_data| queue_data.broker_name().to_string())
+            .collect::<Vec<_>>();
+        broker_names.sort();
+        broker_names.dedup();
+        Ok(broker_names)
+    }
+}
+
+fn queue_views(route: &TopicRouteData) -> Vec<QueueView> {
+    let mut views = Vec::new();
+    for queue_data in &route.queue_datas {
+        if ClusterService::find_master_addr(route, queue_data.broker_name()).is_err() {
+            continue;
+        }
+        let read_queue_nums = queue_data.read_queue_nums();
+        let write_queue_nums = queue_data.write_queue_nums();
+        for queue_id in 0..read_queue_nums.max(write_queue_nums) {
+            let readable = queue_id < read_queue_nums && PermName::is_readable(queue_data.perm());
+            let writeable =
+                queue_id < write_queue_nums && PermName::is_writeable(queue_data.perm());
+            if readable || writeable {
+                views.push(QueueView {
+                    broker_name: queue_data.broker_name().to_string(),
+                    queue_id: queue_id as i32,
+                    readable,
+                    writeable,
+                });
+            }
+        }
+    }
+    views
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rocketmq_remoting::protocol::route::route_data_view::BrokerData;
+    use rocketmq_remoting::protocol::route::route_data_view::QueueData;
+
+    use super::*;
+
+    #[test]
+    fn queue_views_follow_queue_nums_and_permissions() {
+        let mut route = TopicRouteData::new();
+        route.queue_datas = vec![
+            QueueData::new(
+                "broker-a".to_string(),
+                1,
+                2,
+                PermName::PERM_READ | PermName::PERM_WRITE,
+                0,
+            ),
+            QueueData::new("broker-b".to_string(), 1, 1, PermName::PERM_READ, 0),
+        ];
+        route.broker_datas = vec![BrokerData::new(
+            "DefaultCluster".to_string(),
+            "broker-a".to_string(),
+            HashMap::from([(0, "127.0.0.1:10911".to_string())]),
+            None,
+        )];
+        assert_eq!(
+            queue_views(&route),
+            vec![
+                QueueView {
+                    broker_name: "broker-a".to_string(),
+                    queue_id: 0,
+                    readable: true,
+                    writeable: true,
+                },
+                QueueView {
+                    broker_name: "broker-a".to_string(),
+                    queue_id: 1,
+                    readable: false,
+                    writeable: true,
+                },
+            ]
+        );
+    }
+}
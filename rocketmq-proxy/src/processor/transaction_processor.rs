@@ -0,0 +1,137 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::MessageUtils::parse_message_id;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::protocol::header::end_transaction_request_header::EndTransactionRequestHeader;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_response_header::SendMessageResponseHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+
+use crate::error::ProxyError;
+use crate::proto::v2::Code;
+use crate::service::cluster_service::ClusterService;
+use crate::Result;
+
+/// Transactions neither committed nor rolled back within this time are forgotten, the broker
+/// resolves them through its transaction check.
+const TRANSACTION_DATA_EXPIRE: Duration = Duration::from_secs(60 * 60);
+
+/// Where the half message of a transaction was stored.
+#[derive(Debug, Clone)]
+struct TransactionData {
+    producer_group: String,
+    topic: String,
+    broker_addr: String,
+    message_id: String,
+    tran_state_table_offset: i64,
+    commit_log_offset: i64,
+    created_at: Instant,
+}
+
+pub struct TransactionProcessor {
+    cluster_service: Arc<ClusterService>,
+    transactions: Mutex<HashMap<String /* transaction id */, TransactionData>>,
+}
+
+impl TransactionProcessor {
+    pub fn new(cluster_service: Arc<ClusterService>) -> Self {
+        TransactionProcessor {
+            cluster_service,
+            transactions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remembers the half message a broker stored for `message_id`, so that the transaction
+    /// can be ended later with only its id.
+    pub fn add_transaction(
+        &self,
+        producer_group: &str,
+        topic: &str,
+        broker_addr: &str,
+        message_id: &str,
+        response: &SendMessageResponseHeader,
+    ) {
+        let transaction_id = response.transaction_id().unwrap_or(message_id).to_string();
+        let commit_log_offset = parse_message_id(response.msg_id())
+            .map(|(_, offset)| offset)
+            .unwrap_or_default();
+        let mut transactions = self.transactions.lock();
+        transactions.retain(|_, data| data.created_at.elapsed() < TRANSACTION_DATA_EXPIRE);
+        transactions.insert(
+            transaction_id,
+            TransactionData {
+                producer_group: producer_group.to_string(),
+                topic: topic.to_string(),
+                broker_addr: broker_addr.to_string(),
+                message_id: message_id.to_string(),
+                tran_state_table_offset: response.queue_offset(),
+                commit_log_offset,
+                created_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Commits or rolls back the transaction whose half message was sent through this proxy.
+    pub async fn end_transaction(
+        &self,
+        topic: &str,
+        transaction_id: &str,
+        commit: bool,
+        from_transaction_check: bool,
+    ) -> Result<()> {
+        let data = {
+            let mut transactions = self.transactions.lock();
+            match transactions.get(transaction_id) {
+                Some(data) if data.topic == topic => transactions.remove(transaction_id),
+                _ => None,
+            }
+        }
+        .ok_or_else(|| {
+            ProxyError::new(
+                Code::InvalidTransactionId,
+                format!("unknown transaction {} of topic {}", transaction_id, topic),
+            )
+        })?;
+        let request_header = EndTransactionRequestHeader {
+            topic: data.topic,
+            producer_group: data.producer_group,
+            tran_state_table_offset: data.tran_state_table_offset,
+            commit_log_offset: data.commit_log_offset,
+            commit_or_rollback: if commit {
+                MessageSysFlag::TRANSACTION_COMMIT_TYPE
+            } else {
+                MessageSysFlag::TRANSACTION_ROLLBACK_TYPE
+            },
+            from_transaction_check,
+            msg_id: data.message_id,
+            transaction_id: Some(transaction_id.to_string()),
+            rpc_request_header: None,
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::EndTransaction, request_header);
+        self.cluster_service
+            .invoke_oneway(&data.broker_addr, request)
+            .await;
+        Ok(())
+    }
+}
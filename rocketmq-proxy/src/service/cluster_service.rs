@@ -0,0 +1,174 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use parking_lot::RwLock;
+use rocketmq_common::common::mix_all;
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::header::client_request_header::GetRouteInfoRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::remoting::RemotingService;
+use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+
+use crate::config::ProxyConfig;
+use crate::error::ProxyError;
+use crate::proto::v2::Code;
+use crate::Result;
+
+/// Reaches the brokers and name servers of the cluster behind the proxy over remoting.
+pub struct ClusterService {
+    config: Arc<ProxyConfig>,
+    remoting_client: RocketmqDefaultClient<DefaultRemotingRequestProcessor>,
+    route_cache: RwLock<HashMap<String /* topic */, (Instant, Arc<TopicRouteData>)>>,
+}
+
+impl ClusterService {
+    pub fn new(config: Arc<ProxyConfig>) -> Self {
+        let remoting_client = RocketmqDefaultClient::new(
+            Arc::new(TokioClientConfig::default()),
+            DefaultRemotingRequestProcessor,
+        );
+        ClusterService {
+            config,
+            remoting_client,
+            route_cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn start(&self) {
+        self.remoting_client
+            .update_name_server_address_list(self.config.namesrv_addr_list())
+            .await;
+        self.remoting_client.start().await;
+    }
+
+    pub fn config(&self) -> &ProxyConfig {
+        &self.config
+    }
+
+    /// Returns the route of `topic`, asking the name servers when the cached one is missing
+    /// or older than `route_cache_expire_millis`.
+    pub async fn get_topic_route(&self, topic: &str) -> Result<Arc<TopicRouteData>> {
+        let expire = Duration::from_millis(self.config.route_cache_expire_millis);
+        if let Some((fetched_at, route)) = self.route_cache.read().get(topic) {
+            if fetched_at.elapsed() < expire {
+                return Ok(route.clone());
+            }
+        }
+        let request_header = GetRouteInfoRequestHeader {
+            topic: topic.to_string(),
+            accept_standard_json_only: None,
+            topic_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::GetRouteinfoByTopic,
+            request_header,
+        );
+        let response = self.invoke(None, request).await?;
+        if ResponseCode::from(response.code()) == ResponseCode::TopicNotExist {
+            self.route_cache.write().remove(topic);
+            return Err(ProxyError::new(
+                Code::TopicNotFound,
+                format!("topic {} not found", topic),
+            ));
+        }
+        let route = Self::check_response(None, response)?
+            .get_body()
+            .and_then(|body| TopicRouteData::decode(body.as_ref()).ok())
+            .ok_or_else(|| {
+                ProxyError::new(
+                    Code::InternalServerError,
+                    format!("illegal route of topic {} from name server", topic),
+                )
+            })?;
+        let route = Arc::new(route);
+        self.route_cache
+            .write()
+            .insert(topic.to_string(), (Instant::now(), route.clone()));
+        Ok(route)
+    }
+
+    /// Address of the master of `broker_name` in `route`.
+    pub fn find_master_addr(route: &TopicRouteData, broker_name: &str) -> Result<String> {
+        route
+            .broker_datas
+            .iter()
+            .find(|broker_data| broker_data.broker_name() == broker_name)
+            .and_then(|broker_data| {
+                broker_data
+                    .broker_addrs()
+                    .get(&(mix_all::MASTER_ID as i64))
+                    .cloned()
+            })
+            .ok_or_else(|| {
+                ProxyError::new(
+                    Code::NotFound,
+                    format!("master of broker {} not found", broker_name),
+                )
+            })
+    }
+
+    /// Sends `request` to `addr`, or to a name server when `addr` is `None`.
+    pub async fn invoke(
+        &self,
+        addr: Option<&str>,
+        request: RemotingCommand,
+    ) -> Result<RemotingCommand> {
+        Ok(self
+            .remoting_client
+            .invoke_async(
+                addr.map(str::to_string),
+                request,
+                self.config.remoting_timeout_millis,
+            )
+            .await?)
+    }
+
+    pub async fn invoke_oneway(&self, addr: &str, request: RemotingCommand) {
+        self.remoting_client
+            .invoke_oneway(
+                addr.to_string(),
+                request,
+                self.config.remoting_timeout_millis,
+            )
+            .await
+    }
+
+    /// Passes `response` through when it succeeded, otherwise turns it into an error.
+    pub fn check_response(
+        addr: Option<&str>,
+        response: RemotingCommand,
+    ) -> Result<RemotingCommand> {
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(response);
+        }
+        Err(ProxyError::MQBrokerError(
+            response.code(),
+            addr.unwrap_or_default().to_string(),
+            response.remark().cloned().unwrap_or_default(),
+        ))
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+
+use crate::acl::acl_utils;
+use crate::acl::session_credentials::SessionCredentials;
+use crate::acl::session_credentials::ACCESS_KEY;
+use crate::acl::session_credentials::SECURITY_TOKEN;
+use crate::acl::session_credentials::SIGNATURE;
+use crate::protocol::remoting_command::RemotingCommand;
+use crate::runtime::RPCHook;
+use crate::Result;
+
+/// Signs outgoing requests with the access key and secret key of the client.
+pub struct AclClientRPCHook {
+    session_credentials: SessionCredentials,
+}
+
+impl AclClientRPCHook {
+    pub fn new(session_credentials: SessionCredentials) -> Self {
+        Self {
+            session_credentials,
+        }
+    }
+
+    pub fn session_credentials(&self) -> &SessionCredentials {
+        &self.session_credentials
+    }
+}
+
+impl RPCHook for AclClientRPCHook {
+    fn do_before_request(
+        &self,
+        _remote_addr: SocketAddr,
+        request: &mut RemotingCommand,
+    ) -> Result<()> {
+        // the custom header is signed as the ext fields it is sent as
+        request.make_custom_header_to_net();
+        request.add_ext_field(ACCESS_KEY, self.session_credentials.access_key());
+        if let Some(security_token) = self.session_credentials.security_token() {
+            request.add_ext_field(SECURITY_TOKEN, security_token);
+        }
+        let fields: BTreeMap<String, String> = request
+            .ext_fields()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let content = acl_utils::combine_request_content(
+            &fields,
+            request.body().as_ref().map(|body| body.as_ref()),
+        );
+        let signature =
+            acl_utils::calculate_signature(&content, self.session_credentials.secret_key());
+        request.add_ext_field(SIGNATURE, signature);
+        Ok(())
+    }
+
+    fn do_after_response(
+        &self,
+        _remote_addr: SocketAddr,
+        _response: &mut RemotingCommand,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use hmac::Hmac;
+use hmac::Mac;
+use sha1::Sha1;
+
+use crate::acl::session_credentials::SIGNATURE;
+
+/// Signs `data` with HMAC-SHA1 and returns the base64 encoded digest.
+pub fn calculate_signature(data: &[u8], secret_key: &str) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret_key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(data);
+    STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// Builds the signed content of a request: the values of `fields` ordered by key, leaving out
+/// the signature itself, followed by the body.
+pub fn combine_request_content(fields: &BTreeMap<String, String>, body: Option<&[u8]>) -> Vec<u8> {
+    let mut content = Vec::new();
+    for (key, value) in fields {
+        if key != SIGNATURE {
+            content.extend_from_slice(value.as_bytes());
+        }
+    }
+    if let Some(body) = body {
+        content.extend_from_slice(body);
+    }
+    content
+}
+
+/// Compares two signatures in constant time.
+pub fn signature_matches(expected: &str, actual: &str) -> bool {
+    let (expected, actual) = (expected.as_bytes(), actual.as_bytes());
+    expected.len() == actual.len()
+        && expected
+            .iter()
+            .zip(actual)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Whether `pattern` is a valid remote address pattern, see [`remote_address_matches`].
+pub fn is_valid_remote_address(pattern: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern == "*" || pattern.parse::<IpAddr>().is_ok() {
+        return true;
+    }
+    let segments: Vec<&str> = pattern.split('.').collect();
+    segments.len() == 4
+        && segments
+            .iter()
+            .all(|segment| parse_segment(segment).is_some())
+}
+
+/// Matches `ip` against a remote address pattern. Besides `*` and plain addresses, IPv4
+/// patterns accept per segment a wildcard (`192.168.*.*`), a range (`192.168.1.1-100`) or a
+/// set (`192.168.1.{1,2}`).
+pub fn remote_address_matches(pattern: &str, ip: &IpAddr) -> bool {
+    let pattern = pattern.trim();
+    if pattern == "*" {
+        return true;
+    }
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let segments: Vec<&str> = pattern.split('.').collect();
+            segments.len() == 4
+                && segments.iter().zip(ip.octets()).all(|(segment, octet)| {
+                    match parse_segment(segment) {
+                        Some(SegmentPattern::Any) => true,
+                        Some(SegmentPattern::Range(low, high)) => low <= octet && octet <= high,
+                        Some(SegmentPattern::Set(values)) => values.contains(&octet),
+                        None => false,
+                    }
+                })
+        }
+        ip @ IpAddr::V6(_) => pattern.parse::<IpAddr>().is_ok_and(|pattern| pattern == ip),
+    }
+}
+
+enum SegmentPattern {
+    Any,
+    Range(u8, u8),
+    Set(Vec<u8>),
+}
+
+fn parse_segment(segment: &str) -> Option<SegmentPattern> {
+    let segment = segment.trim();
+    if segment == "*" {
+        return Some(SegmentPattern::Any);
+    }
+    if let Some(values) = segment
+        .strip_prefix('{')
+        .and_then(|values| values.strip_suffix('}'))
+    {
+        return values
+            .split(',')
+            .map(|value| value.trim().parse::<u8>().ok())
+            .collect::<Option<Vec<u8>>>()
+            .map(SegmentPattern::Set);
+    }
+    if let Some((low, high)) = segment.split_once('-') {
+        let (low, high) = (low.parse::<u8>().ok()?, high.parse::<u8>().ok()?);
+        return (low <= high).then_some(SegmentPattern::Range(low, high));
+    }
+    segment
+        .parse::<u8>()
+        .ok()
+        .map(|value| SegmentPattern::Range(value, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_hmac_sha1_in_base64() {
+        // RFC 2202 test case 2
+        assert_eq!(
+            calculate_signature(b"what do ya want for nothing?", "Jefe"),
+            "7/zfauXrL6LSdBbV8YTfnCWafHk="
+        );
+    }
+
+    #[test]
+    fn request_content_is_ordered_by_key() {
+        let mut fields = BTreeMap::new();
+        fields.insert("b".to_string(), "2".to_string());
+        fields.insert("a".to_string(), "1".to_string());
+        fields.insert(SIGNATURE.to_string(), "ignored".to_string());
+        assert_eq!(
+            combine_request_content(&fields, Some(b"body")),
+            b"12body".to_vec()
+        );
+        assert!(signature_matches("abc", "abc"));
+        assert!(!signature_matches("abc", "abd"));
+        assert!(!signature_matches("abc", "ab"));
+    }
+
+    #[test]
+    fn matches_remote_address_patterns() {
+        let ip: IpAddr = "192.168.1.20".parse().unwrap();
+        for pattern in [
+            "*",
+            "192.168.1.20",
+            "192.168.*.*",
+            "192.168.1.1-100",
+            "192.168.1.{10,20}",
+        ] {
+            assert!(is_valid_remote_address(pattern), "{}", pattern);
+            assert!(remote_address_matches(pattern, &ip), "{}", pattern);
+        }
+        for pattern in [
+            "192.168.1.21",
+            "10.*.*.*",
+            "192.168.1.21-100",
+            "192.168.1.{1,2}",
+        ] {
+            assert!(!remote_address_matches(pattern, &ip), "{}", pattern);
+        }
+        let mapped: IpAddr = "::ffff:192.168.1.20".parse().unwrap();
+        assert!(remote_address_matches("192.168.1.*", &mapped));
+        assert!(!is_valid_remote_address("192.168.1"));
+        assert!(!is_valid_remote_address("192.168.1.300"));
+    }
+}
@@ -0,0 +1,144 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+/// Permissions of an account on topics and groups.
+pub struct Permission;
+
+impl Permission {
+    pub const DENY: u8 = 1;
+    pub const ANY: u8 = 1 << 1;
+    pub const PUB: u8 = 1 << 2;
+    pub const SUB: u8 = 1 << 3;
+
+    /// Parses `DENY`, `PUB`, `SUB` or `PUB|SUB`, `None` for anything else.
+    pub fn parse_perm(perm: &str) -> Option<u8> {
+        match perm.trim() {
+            "DENY" => Some(Self::DENY),
+            "PUB" => Some(Self::PUB),
+            "SUB" => Some(Self::SUB),
+            "PUB|SUB" | "SUB|PUB" => Some(Self::PUB | Self::SUB),
+            _ => None,
+        }
+    }
+
+    /// Parses a configured permission, denying when it is missing or invalid.
+    pub fn parse_perm_from_string(perm: Option<&str>) -> u8 {
+        perm.and_then(Self::parse_perm).unwrap_or(Self::DENY)
+    }
+
+    pub fn perm_to_string(perm: u8) -> &'static str {
+        if perm & Self::DENY != 0 {
+            return "DENY";
+        }
+        match (perm & Self::PUB != 0, perm & Self::SUB != 0) {
+            (true, true) => "PUB|SUB",
+            (true, false) => "PUB",
+            (false, true) => "SUB",
+            (false, false) => "DENY",
+        }
+    }
+
+    /// Parses `resource=PERM` entries such as `topicA=PUB|SUB`.
+    pub fn parse_resource_perms(entries: &[String]) -> Result<HashMap<String, u8>, String> {
+        let mut resource_perms = HashMap::with_capacity(entries.len());
+        for entry in entries {
+            let parsed = entry.split_once('=').and_then(|(resource, perm)| {
+                let resource = resource.trim();
+                if resource.is_empty() {
+                    return None;
+                }
+                Self::parse_perm(perm).map(|perm| (resource.to_string(), perm))
+            });
+            match parsed {
+                Some((resource, perm)) => {
+                    resource_perms.insert(resource, perm);
+                }
+                None => return Err(format!("invalid resource permission: {}", entry)),
+            }
+        }
+        Ok(resource_perms)
+    }
+
+    /// Whether `owned_perm` grants `needed_perm`. `DENY` wins over everything else and `ANY`
+    /// is granted by either `PUB` or `SUB`.
+    pub fn check_permission(needed_perm: u8, owned_perm: u8) -> bool {
+        if owned_perm & Self::DENY != 0 {
+            return false;
+        }
+        if needed_perm & Self::ANY != 0 {
+            return owned_perm & (Self::PUB | Self::SUB) != 0;
+        }
+        needed_perm & owned_perm == needed_perm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_permissions() {
+        assert_eq!(
+            Permission::parse_perm_from_string(Some("PUB|SUB")),
+            Permission::PUB | Permission::SUB
+        );
+        assert_eq!(
+            Permission::parse_perm_from_string(Some("ALL")),
+            Permission::DENY
+        );
+        assert_eq!(Permission::parse_perm_from_string(None), Permission::DENY);
+        assert_eq!(
+            Permission::perm_to_string(Permission::SUB | Permission::PUB),
+            "PUB|SUB"
+        );
+
+        let perms = Permission::parse_resource_perms(&[
+            "topicA=DENY".to_string(),
+            "topicB= PUB|SUB".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(perms["topicA"], Permission::DENY);
+        assert_eq!(perms["topicB"], Permission::PUB | Permission::SUB);
+        assert!(Permission::parse_resource_perms(&["topicC".to_string()]).is_err());
+        assert!(Permission::parse_resource_perms(&["=PUB".to_string()]).is_err());
+    }
+
+    #[test]
+    fn checks_permissions() {
+        assert!(Permission::check_permission(
+            Permission::PUB,
+            Permission::PUB | Permission::SUB
+        ));
+        assert!(!Permission::check_permission(
+            Permission::SUB,
+            Permission::PUB
+        ));
+        assert!(!Permission::check_permission(
+            Permission::PUB,
+            Permission::PUB | Permission::DENY
+        ));
+        assert!(Permission::check_permission(
+            Permission::ANY,
+            Permission::SUB
+        ));
+        assert!(!Permission::check_permission(
+            Permission::ANY,
+            Permission::DENY
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+
+use crate::acl::acl_utils;
+use crate::acl::permission::Permission;
+
+const MIN_KEY_LENGTH: usize = 6;
+
+/// One account of the plain ACL file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlainAccessConfig {
+    #[serde(deserialize_with = "deserialize_key")]
+    pub access_key: String,
+    #[serde(deserialize_with = "deserialize_key")]
+    pub secret_key: String,
+    /// Requests from a matching address skip signature and permission checks.
+    pub white_remote_address: Option<String>,
+    pub admin: bool,
+    pub default_topic_perm: Option<String>,
+    pub default_group_perm: Option<String>,
+    /// `topic=PERM` entries.
+    pub topic_perms: Vec<String>,
+    /// `group=PERM` entries.
+    pub group_perms: Vec<String>,
+}
+
+impl PlainAccessConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.access_key.len() < MIN_KEY_LENGTH {
+            return Err(format!(
+                "accessKey {} is shorter than {} characters",
+                self.access_key, MIN_KEY_LENGTH
+            ));
+        }
+        if self.secret_key.len() < MIN_KEY_LENGTH {
+            return Err(format!(
+                "secretKey of accessKey {} is shorter than {} characters",
+                self.access_key, MIN_KEY_LENGTH
+            ));
+        }
+        if let Some(white_remote_address) = self.white_remote_address.as_deref() {
+            if !white_remote_address.is_empty()
+                && !acl_utils::is_valid_remote_address(white_remote_address)
+            {
+                return Err(format!(
+                    "invalid whiteRemoteAddress {} of accessKey {}",
+                    white_remote_address, self.access_key
+                ));
+            }
+        }
+        for perm in [&self.default_topic_perm, &self.default_group_perm]
+            .into_iter()
+            .flatten()
+        {
+            if Permission::parse_perm(perm).is_none() {
+                return Err(format!(
+                    "invalid default permission {} of accessKey {}",
+                    perm, self.access_key
+                ));
+            }
+        }
+        Permission::parse_resource_perms(&self.topic_perms)?;
+        Permission::parse_resource_perms(&self.group_perms)?;
+        Ok(())
+    }
+}
+
+/// Keys written without quotes in YAML may be read as numbers.
+fn deserialize_key<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Key {
+        String(String),
+        Integer(i64),
+    }
+    Ok(match Key::deserialize(deserializer)? {
+        Key::String(key) => key,
+        Key::Integer(key) => key.to_string(),
+    })
+}
+
+/// Content of the plain ACL file, also returned by `GetBrokerClusterAclConfig`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AclConfig {
+    /// Requests from a matching address are accepted without any check.
+    pub global_white_remote_addresses: Vec<String>,
+    pub accounts: Vec<PlainAccessConfig>,
+}
+
+impl AclConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for address in &self.global_white_remote_addresses {
+            if !acl_utils::is_valid_remote_address(address) {
+                return Err(format!("invalid global white remote address {}", address));
+            }
+        }
+        let mut access_keys = std::collections::HashSet::new();
+        for account in &self.accounts {
+            account.validate()?;
+            if !access_keys.insert(account.access_key.as_str()) {
+                return Err(format!("duplicated accessKey {}", account.access_key));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_acl_yaml() {
+        let yaml = r#"
+globalWhiteRemoteAddresses:
+  - 10.10.103.*
+accounts:
+  - accessKey: RocketMQ
+    secretKey: 12345678
+    whiteRemoteAddress:
+    admin: false
+    defaultTopicPerm: DENY
+    defaultGroupPerm: SUB
+    topicPerms:
+      - topicA=DENY
+      - topicB=PUB|SUB
+    groupPerms:
+      - groupA=DENY
+  - accessKey: rocketmq2
+    secretKey: 12345678
+    admin: true
+"#;
+        let config: AclConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.global_white_remote_addresses, vec!["10.10.103.*"]);
+        assert_eq!(config.accounts.len(), 2);
+        assert_eq!(config.accounts[0].secret_key, "12345678");
+        assert_eq!(config.accounts[0].topic_perms[1], "topicB=PUB|SUB");
+        assert!(config.accounts[1].admin);
+        assert!(config.accounts[1].topic_perms.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_accounts() {
+        let mut account = PlainAccessConfig {
+            access_key: "RocketMQ".to_string(),
+            secret_key: "12345678".to_string(),
+            ..Default::default()
+        };
+        assert!(account.validate().is_ok());
+        account.topic_perms = vec!["topicA=ALL".to_string()];
+        assert!(account.validate().is_err());
+        account.topic_perms.clear();
+        account.secret_key = "123".to_string();
+        assert!(account.validate().is_err());
+
+        account.secret_key = "12345678".to_string();
+        let config = AclConfig {
+            global_white_remote_addresses: vec![],
+            accounts: vec![account.clone(), account],
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,249 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+
+use rocketmq_common::common::mix_all;
+
+use crate::acl::acl_utils;
+use crate::acl::permission::Permission;
+use crate::acl::session_credentials::ACCESS_KEY;
+use crate::acl::session_credentials::SECURITY_TOKEN;
+use crate::acl::session_credentials::SIGNATURE;
+use crate::code::request_code::RequestCode;
+use crate::protocol::heartbeat::heartbeat_data::HeartbeatData;
+use crate::protocol::remoting_command::RemotingCommand;
+use crate::protocol::RemotingDeserializable;
+
+const TOPIC: &str = "topic";
+const TOPIC_V2: &str = "b";
+const GROUP: &str = "group";
+const CONSUMER_GROUP: &str = "consumerGroup";
+
+/// A topic or a consumer group a request touches.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AccessResource {
+    Topic(String),
+    Group(String),
+}
+
+/// What a request asks for: who signed it and which resources it needs which permission on.
+#[derive(Debug, Clone)]
+pub struct PlainAccessResource {
+    pub access_key: Option<String>,
+    pub signature: Option<String>,
+    pub security_token: Option<String>,
+    pub remote_addr: IpAddr,
+    pub request_code: i32,
+    /// The content the signature was calculated over.
+    pub content: Vec<u8>,
+    pub resource_perms: HashMap<AccessResource, u8>,
+}
+
+impl PlainAccessResource {
+    pub fn parse(remote_addr: SocketAddr, request: &RemotingCommand) -> Result<Self, String> {
+        let fields: BTreeMap<String, String> = request
+            .ext_fields()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut resource = PlainAccessResource {
+            access_key: fields.get(ACCESS_KEY).cloned(),
+            signature: fields.get(SIGNATURE).cloned(),
+            security_token: fields.get(SECURITY_TOKEN).cloned(),
+            remote_addr: remote_addr.ip(),
+            request_code: request.code(),
+            content: acl_utils::combine_request_content(
+                &fields,
+                request.body().as_ref().map(|body| body.as_ref()),
+            ),
+            resource_perms: HashMap::new(),
+        };
+
+        let field = |key: &str| fields.get(key).map(String::as_str).unwrap_or_default();
+        match RequestCode::from(request.code()) {
+            RequestCode::SendMessage => resource.add_topic(field(TOPIC), Permission::PUB),
+            RequestCode::SendMessageV2
+            | RequestCode::SendBatchMessage
+            | RequestCode::SendReplyMessage
+            | RequestCode::SendReplyMessageV2 => {
+                let topic = fields
+                    .get(TOPIC_V2)
+                    .or_else(|| fields.get(TOPIC))
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                resource.add_topic(topic, Permission::PUB);
+            }
+            RequestCode::ConsumerSendMsgBack => resource.add_group(field(GROUP), Permission::SUB),
+            RequestCode::PullMessage
+            | RequestCode::LitePullMessage
+            | RequestCode::UpdateConsumerOffset
+            | RequestCode::QueryConsumerOffset => {
+                resource.add_topic(field(TOPIC), Permission::SUB);
+                resource.add_group(field(CONSUMER_GROUP), Permission::SUB);
+            }
+            RequestCode::QueryMessage => resource.add_topic(field(TOPIC), Permission::SUB),
+            RequestCode::UnregisterClient | RequestCode::GetConsumerListByGroup => {
+                resource.add_group(field(CONSUMER_GROUP), Permission::SUB)
+            }
+            RequestCode::HeartBeat => {
+                if let Some(body) = request.body() {
+                    let heartbeat_data = HeartbeatData::decode(body)
+                        .map_err(|err| format!("invalid heartbeat data: {}", err))?;
+                    for consumer_data in &heartbeat_data.consumer_data_set {
+                        resource.add_group(consumer_data.group_name.as_str(), Permission::SUB);
+                        for subscription_data in &consumer_data.subscription_data_set {
+                            resource.add_topic(subscription_data.topic.as_str(), Permission::SUB);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(resource)
+    }
+
+    /// Retry topics are owned by their consumer group.
+    fn add_topic(&mut self, topic: &str, perm: u8) {
+        match topic.strip_prefix(mix_all::RETRY_GROUP_TOPIC_PREFIX) {
+            Some(group) => self.add_group(group, Permission::SUB),
+            None if !topic.is_empty() => {
+                *self
+                    .resource_perms
+                    .entry(AccessResource::Topic(topic.to_string()))
+                    .or_default() |= perm;
+            }
+            None => {}
+        }
+    }
+
+    fn add_group(&mut self, group: &str, perm: u8) {
+        if !group.is_empty() {
+            *self
+                .resource_perms
+                .entry(AccessResource::Group(group.to_string()))
+                .or_default() |= perm;
+        }
+    }
+
+    /// Request codes only admin accounts may send.
+    pub fn is_admin_request(request_code: i32) -> bool {
+        matches!(
+            RequestCode::from(request_code),
+            RequestCode::UpdateAndCreateTopic
+                | RequestCode::UpdateAndCreateTopicList
+                | RequestCode::DeleteTopicInBroker
+                | RequestCode::UpdateBrokerConfig
+                | RequestCode::UpdateAndCreateSubscriptionGroup
+                | RequestCode::DeleteSubscriptionGroup
+                | RequestCode::UpdateAndCreateAclConfig
+                | RequestCode::DeleteAclConfig
+                | RequestCode::UpdateGlobalWhiteAddrsConfig
+                | RequestCode::GetBrokerClusterAclConfig
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::protocol::heartbeat::consumer_data::ConsumerData;
+    use crate::protocol::heartbeat::subscription_data::SubscriptionData;
+    use crate::protocol::RemotingSerializable;
+
+    fn remote_addr() -> SocketAddr {
+        "127.0.0.1:10911".parse().unwrap()
+    }
+
+    #[test]
+    fn parses_pull_message_resources() {
+        let mut request = RemotingCommand::create_remoting_command(RequestCode::PullMessage);
+        request.add_ext_field(TOPIC, "topicA");
+        request.add_ext_field(CONSUMER_GROUP, "groupA");
+        request.add_ext_field(ACCESS_KEY, "RocketMQ");
+        request.add_ext_field(SIGNATURE, "signature");
+        let resource = PlainAccessResource::parse(remote_addr(), &request).unwrap();
+        assert_eq!(resource.access_key.as_deref(), Some("RocketMQ"));
+        assert_eq!(resource.signature.as_deref(), Some("signature"));
+        assert_eq!(
+            resource.resource_perms[&AccessResource::Topic("topicA".to_string())],
+            Permission::SUB
+        );
+        assert_eq!(
+            resource.resource_perms[&AccessResource::Group("groupA".to_string())],
+            Permission::SUB
+        );
+        // ordered by key: AccessKey, consumerGroup, topic
+        assert_eq!(resource.content, b"RocketMQgroupAtopicA".to_vec());
+    }
+
+    #[test]
+    fn retry_topic_is_checked_as_group() {
+        let mut request = RemotingCommand::create_remoting_command(RequestCode::SendMessageV2);
+        request.add_ext_field(TOPIC_V2, "%RETRY%groupA");
+        let resource = PlainAccessResource::parse(remote_addr(), &request).unwrap();
+        assert_eq!(resource.resource_perms.len(), 1);
+        assert_eq!(
+            resource.resource_perms[&AccessResource::Group("groupA".to_string())],
+            Permission::SUB
+        );
+    }
+
+    #[test]
+    fn parses_heartbeat_subscriptions() {
+        let subscription_data = SubscriptionData {
+            class_filter_mode: false,
+            topic: "topicA".to_string(),
+            sub_string: "*".to_string(),
+            tags_set: HashSet::new(),
+            code_set: HashSet::new(),
+            sub_version: 0,
+            expression_type: "TAG".to_string(),
+            filter_class_source: String::new(),
+        };
+        let consumer_data = ConsumerData {
+            group_name: "groupA".to_string(),
+            subscription_data_set: HashSet::from([subscription_data]),
+            ..Default::default()
+        };
+        let heartbeat_data = HeartbeatData {
+            consumer_data_set: HashSet::from([consumer_data]),
+            ..Default::default()
+        };
+        let request = RemotingCommand::create_remoting_command(RequestCode::HeartBeat)
+            .set_body(Some(heartbeat_data.encode()));
+        let resource = PlainAccessResource::parse(remote_addr(), &request).unwrap();
+        assert_eq!(resource.resource_perms.len(), 2);
+        assert!(resource
+            .resource_perms
+            .contains_key(&AccessResource::Topic("topicA".to_string())));
+        assert!(PlainAccessResource::is_admin_request(
+            RequestCode::UpdateAndCreateAclConfig.to_i32()
+        ));
+        assert!(!PlainAccessResource::is_admin_request(
+            RequestCode::HeartBeat.to_i32()
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tracing::warn;
+
+use crate::acl::plain_access_resource::PlainAccessResource;
+use crate::acl::plain_permission_manager::PlainPermissionManager;
+use crate::code::response_code::ResponseCode;
+use crate::error::Error::AbortProcessException;
+use crate::protocol::remoting_command::RemotingCommand;
+use crate::runtime::RPCHook;
+use crate::Result;
+
+/// Server side hook rejecting requests that are not signed by a known account or that touch
+/// topics and groups the account has no permission on.
+#[derive(Clone)]
+pub struct PlainAccessValidator {
+    permission_manager: Arc<PlainPermissionManager>,
+}
+
+impl PlainAccessValidator {
+    pub fn new(permission_manager: Arc<PlainPermissionManager>) -> Self {
+        Self { permission_manager }
+    }
+
+    pub fn permission_manager(&self) -> &Arc<PlainPermissionManager> {
+        &self.permission_manager
+    }
+}
+
+impl RPCHook for PlainAccessValidator {
+    fn do_before_request(
+        &self,
+        remote_addr: SocketAddr,
+        request: &mut RemotingCommand,
+    ) -> Result<()> {
+        PlainAccessResource::parse(remote_addr, request)
+            .and_then(|resource| self.permission_manager.validate(&resource))
+            .map_err(|err| {
+                warn!(
+                    "reject request code {} from {}: {}",
+                    request.code(),
+                    remote_addr,
+                    err
+                );
+                AbortProcessException(ResponseCode::NoPermission.into(), err)
+            })
+    }
+
+    fn do_after_response(
+        &self,
+        _remote_addr: SocketAddr,
+        _response: &mut RemotingCommand,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::acl::acl_client_rpc_hook::AclClientRPCHook;
+    use crate::acl::session_credentials::SessionCredentials;
+    use crate::code::request_code::RequestCode;
+    use crate::protocol::header::pull_message_request_header::PullMessageRequestHeader;
+
+    #[test]
+    fn accepts_requests_signed_by_client_hook() {
+        let dir = std::env::temp_dir().join(format!("rocketmq-acl-hook-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plain_acl.yml");
+        fs::write(
+            &path,
+            "accounts:\n  - accessKey: teamA-app\n    secretKey: teamA-secret\n    \
+             defaultGroupPerm: SUB\n    topicPerms:\n      - teamA-topic=SUB\n",
+        )
+        .unwrap();
+        let manager = Arc::new(PlainPermissionManager::new(path));
+        manager.load().unwrap();
+        let validator = PlainAccessValidator::new(manager);
+        let remote_addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+
+        let pull = |topic: &str, secret_key: &str| {
+            let header = PullMessageRequestHeader {
+                consumer_group: "teamA-group".to_string(),
+                topic: topic.to_string(),
+                ..Default::default()
+            };
+            let mut request =
+                RemotingCommand::create_request_command(RequestCode::PullMessage, header);
+            AclClientRPCHook::new(SessionCredentials::new("teamA-app", secret_key))
+                .do_before_request(remote_addr, &mut request)
+                .unwrap();
+            request
+        };
+
+        assert!(validator
+            .do_before_request(remote_addr, &mut pull("teamA-topic", "teamA-secret"))
+            .is_ok());
+        match validator.do_before_request(remote_addr, &mut pull("teamB-topic", "teamA-secret")) {
+            Err(AbortProcessException(code, _)) => {
+                assert_eq!(code, i32::from(ResponseCode::NoPermission))
+            }
+            other => panic!("unexpected result {:?}", other.err()),
+        }
+        assert!(validator
+            .do_before_request(remote_addr, &mut pull("teamA-topic", "wrong-secret"))
+            .is_err());
+    }
+}
@@ -0,0 +1,525 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use parking_lot::RwLock;
+use tracing::error;
+use tracing::info;
+
+use crate::acl::acl_utils;
+use crate::acl::permission::Permission;
+use crate::acl::plain_access_config::AclConfig;
+use crate::acl::plain_access_config::PlainAccessConfig;
+use crate::acl::plain_access_resource::AccessResource;
+use crate::acl::plain_access_resource::PlainAccessResource;
+
+/// Interval the ACL file is checked for changes at.
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Holds the accounts of the plain ACL file, reloads the file when it changes and writes back
+/// the changes made through the admin commands.
+pub struct PlainPermissionManager {
+    file_path: PathBuf,
+    state: RwLock<AclState>,
+}
+
+#[derive(Default)]
+struct AclState {
+    config: AclConfig,
+    accounts: HashMap<String, PlainAccessAccount>,
+    /// Modification time and length of the file when it was last loaded or written.
+    file_stamp: Option<(SystemTime, u64)>,
+    data_version: u64,
+}
+
+struct PlainAccessAccount {
+    secret_key: String,
+    white_remote_address: Option<String>,
+    admin: bool,
+    default_topic_perm: u8,
+    default_group_perm: u8,
+    topic_perms: HashMap<String, u8>,
+    group_perms: HashMap<String, u8>,
+}
+
+impl PlainAccessAccount {
+    fn new(config: &PlainAccessConfig) -> Result<Self, String> {
+        Ok(PlainAccessAccount {
+            secret_key: config.secret_key.clone(),
+            white_remote_address: config
+                .white_remote_address
+                .clone()
+                .filter(|address| !address.trim().is_empty()),
+            admin: config.admin,
+            default_topic_perm: Permission::parse_perm_from_string(
+                config.default_topic_perm.as_deref(),
+            ),
+            default_group_perm: Permission::parse_perm_from_string(
+                config.default_group_perm.as_deref(),
+            ),
+            topic_perms: Permission::parse_resource_perms(&config.topic_perms)?,
+            group_perms: Permission::parse_resource_perms(&config.group_perms)?,
+        })
+    }
+
+    fn owned_perm(&self, resource: &AccessResource) -> u8 {
+        match resource {
+            AccessResource::Topic(topic) => self
+                .topic_perms
+                .get(topic)
+                .copied()
+                .unwrap_or(self.default_topic_perm),
+            AccessResource::Group(group) => self
+                .group_perms
+                .get(group)
+                .copied()
+                .unwrap_or(self.default_group_perm),
+        }
+    }
+}
+
+impl PlainPermissionManager {
+    pub fn new(file_path: impl Into<PathBuf>) -> Self {
+        Self {
+            file_path: file_path.into(),
+            state: RwLock::new(AclState::default()),
+        }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        self.file_path.as_path()
+    }
+
+    /// Loads the ACL file, keeping the current accounts when it can not be read or is invalid.
+    pub fn load(&self) -> Result<(), String> {
+        let mut state = self.state.write();
+        let file_stamp = file_stamp(&self.file_path);
+        let content = fs::read_to_string(&self.file_path)
+            .map_err(|err| format!("read acl file {} failed: {}", self.file_path.display(), err))?;
+        let config: AclConfig = if content.trim().is_empty() {
+            AclConfig::default()
+        } else {
+            serde_yaml::from_str(&content).map_err(|err| {
+                format!(
+                    "parse acl file {} failed: {}",
+                    self.file_path.display(),
+                    err
+                )
+            })?
+        };
+        Self::apply(&mut state, config)?;
+        state.file_stamp = file_stamp;
+        info!(
+            "load acl file {}, {} accounts, data version {}",
+            self.file_path.display(),
+            state.accounts.len(),
+            state.data_version
+        );
+        Ok(())
+    }
+
+    /// Reloads the ACL file when it changed since it was last loaded or written.
+    pub fn reload_if_changed(&self) -> bool {
+        let file_stamp = file_stamp(&self.file_path);
+        if file_stamp.is_none() || file_stamp == self.state.read().file_stamp {
+            return false;
+        }
+        match self.load() {
+            Ok(_) => true,
+            Err(err) => {
+                error!("reload acl file failed, keep the previous config: {}", err);
+                // do not retry a broken file until it changes again
+                self.state.write().file_stamp = file_stamp;
+                false
+            }
+        }
+    }
+
+    /// Checks the ACL file for changes every `interval` until the manager is dropped.
+    pub fn start_watcher(self: &Arc<Self>, interval: Duration) {
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match manager.upgrade() {
+                    Some(manager) => {
+                        manager.reload_if_changed();
+                    }
+                    None => break,
+                }
+            }
+        });
+    }
+
+    /// Checks the signature and the permissions of a request.
+    pub fn validate(&self, resource: &PlainAccessResource) -> Result<(), String> {
+        let state = self.state.read();
+        if state
+            .config
+            .global_white_remote_addresses
+            .iter()
+            .any(|pattern| acl_utils::remote_address_matches(pattern, &resource.remote_addr))
+        {
+            return Ok(());
+        }
+        let access_key = resource
+            .access_key
+            .as_deref()
+            .ok_or_else(|| "No accessKey is configured".to_string())?;
+        let account = state
+            .accounts
+            .get(access_key)
+            .ok_or_else(|| format!("No acl config for {}", access_key))?;
+        if account
+            .white_remote_address
+            .as_deref()
+            .is_some_and(|pattern| {
+                acl_utils::remote_address_matches(pattern, &resource.remote_addr)
+            })
+        {
+            return Ok(());
+        }
+
+        let expected = acl_utils::calculate_signature(&resource.content, &account.secret_key);
+        if !resource
+            .signature
+            .as_deref()
+            .is_some_and(|signature| acl_utils::signature_matches(&expected, signature))
+        {
+            return Err(format!(
+                "Check signature failed for accessKey={}",
+                access_key
+            ));
+        }
+
+        if !account.admin && PlainAccessResource::is_admin_request(resource.request_code) {
+            return Err(format!(
+                "Need admin permission for request code={}, but accessKey={} is not",
+                resource.request_code, access_key
+            ));
+        }
+        for (access_resource, needed_perm) in &resource.resource_perms {
+            let owned_perm = account.owned_perm(access_resource);
+            if !Permission::check_permission(*needed_perm, owned_perm) {
+                let (kind, name) = match access_resource {
+                    AccessResource::Topic(topic) => ("topic", topic),
+                    AccessResource::Group(group) => ("group", group),
+                };
+                return Err(format!(
+                    "No {} permission on {} {} for accessKey={}, owned {}",
+                    Permission::perm_to_string(*needed_perm),
+                    kind,
+                    name,
+                    access_key,
+                    Permission::perm_to_string(owned_perm)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates or replaces the account of `access_config.access_key` and writes back the file.
+    pub fn update_access_config(&self, access_config: PlainAccessConfig) -> Result<(), String> {
+        access_config.validate()?;
+        self.update(|config| {
+            match config
+                .accounts
+                .iter_mut()
+                .find(|account| account.access_key == access_config.access_key)
+            {
+                Some(account) => *account = access_config,
+                None => config.accounts.push(access_config),
+            }
+            true
+        })
+        .map(|_| ())
+    }
+
+    /// Deletes the account of `access_key`, returns `false` when there is none.
+    pub fn delete_access_config(&self, access_key: &str) -> Result<bool, String> {
+        self.update(|config| {
+            let accounts = config.accounts.len();
+            config
+                .accounts
+                .retain(|account| account.access_key != access_key);
+            config.accounts.len() != accounts
+        })
+    }
+
+    pub fn update_global_white_addrs(&self, addresses: Vec<String>) -> Result<(), String> {
+        for address in &addresses {
+            if !acl_utils::is_valid_remote_address(address) {
+                return Err(format!("invalid global white remote address {}", address));
+            }
+        }
+        self.update(|config| {
+            config.global_white_remote_addresses = addresses;
+            true
+        })
+        .map(|_| ())
+    }
+
+    pub fn acl_config(&self) -> AclConfig {
+        self.state.read().config.clone()
+    }
+
+    /// Increases every time the accounts are loaded or changed.
+    pub fn data_version(&self) -> u64 {
+        self.state.read().data_version
+    }
+
+    /// Applies `change` to a copy of the current config and, if it reports a change, writes it
+    /// to the file before making it current.
+    fn update(&self, change: impl FnOnce(&mut AclConfig) -> bool) -> Result<bool, String> {
+        let mut state = self.state.write();
+        let mut config = state.config.clone();
+        if !change(&mut config) {
+            return Ok(false);
+        }
+        config.validate()?;
+        self.persist(&config)?;
+        Self::apply(&mut state, config)?;
+        state.file_stamp = file_stamp(&self.file_path);
+        Ok(true)
+    }
+
+    fn persist(&self, config: &AclConfig) -> Result<(), String> {
+        let content = serde_yaml::to_string(config)
+            .map_err(|err| format!("serialize acl config failed: {}", err))?;
+        if let Some(parent) = self.file_path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                format!("create acl directory {} failed: {}", parent.display(), err)
+            })?;
+        }
+        let mut tmp_path = self.file_path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, content)
+            .and_then(|_| fs::rename(&tmp_path, &self.file_path))
+            .map_err(|err| {
+                format!(
+                    "write acl file {} failed: {}",
+                    self.file_path.display(),
+                    err
+                )
+            })
+    }
+
+    fn apply(state: &mut AclState, config: AclConfig) -> Result<(), String> {
+        config.validate()?;
+        let mut accounts = HashMap::with_capacity(config.accounts.len());
+        for account in &config.accounts {
+            accounts.insert(
+                account.access_key.clone(),
+                PlainAccessAccount::new(account)?,
+            );
+        }
+        state.accounts = accounts;
+        state.config = config;
+        state.data_version += 1;
+        Ok(())
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crate::acl::session_credentials::ACCESS_KEY;
+    use crate::acl::session_credentials::SIGNATURE;
+    use crate::code::request_code::RequestCode;
+    use crate::protocol::remoting_command::RemotingCommand;
+
+    const ACL_YAML: &str = r#"
+globalWhiteRemoteAddresses:
+  - 10.10.*.*
+accounts:
+  - accessKey: RocketMQ
+    secretKey: "12345678"
+    defaultTopicPerm: DENY
+    defaultGroupPerm: SUB
+    topicPerms:
+      - topicA=PUB|SUB
+      - topicB=DENY
+  - accessKey: rocketmq2
+    secretKey: "87654321"
+    whiteRemoteAddress: 192.168.0.*
+    admin: true
+"#;
+
+    fn acl_file(name: &str, content: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rocketmq-acl-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plain_acl.yml");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn signed_request(
+        code: RequestCode,
+        topic: &str,
+        access_key: &str,
+        secret_key: &str,
+    ) -> RemotingCommand {
+        let mut request = RemotingCommand::create_remoting_command(code);
+        request.add_ext_field("topic", topic);
+        request.add_ext_field(ACCESS_KEY, access_key);
+        let fields = request
+            .ext_fields()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let signature = acl_utils::calculate_signature(
+            &acl_utils::combine_request_content(&fields, None),
+            secret_key,
+        );
+        request.add_ext_field(SIGNATURE, signature);
+        request
+    }
+
+    fn validate(
+        manager: &PlainPermissionManager,
+        remote_addr: &str,
+        request: &RemotingCommand,
+    ) -> Result<(), String> {
+        let remote_addr: SocketAddr = remote_addr.parse().unwrap();
+        manager.validate(&PlainAccessResource::parse(remote_addr, request).unwrap())
+    }
+
+    #[test]
+    fn validates_signature_and_permissions() {
+        let manager = PlainPermissionManager::new(acl_file("validate", ACL_YAML));
+        manager.load().unwrap();
+        let client = "127.0.0.1:5000";
+
+        let request = signed_request(RequestCode::SendMessage, "topicA", "RocketMQ", "12345678");
+        assert!(validate(&manager, client, &request).is_ok());
+
+        let request = signed_request(RequestCode::SendMessage, "topicA", "RocketMQ", "wrong-key");
+        let err = validate(&manager, client, &request).unwrap_err();
+        assert!(err.contains("Check signature failed"), "{}", err);
+
+        let request = signed_request(RequestCode::SendMessage, "topicB", "RocketMQ", "12345678");
+        assert!(validate(&manager, client, &request).is_err());
+        // falls back to defaultTopicPerm
+        let request = signed_request(RequestCode::SendMessage, "topicC", "RocketMQ", "12345678");
+        assert!(validate(&manager, client, &request).is_err());
+
+        let request = signed_request(RequestCode::SendMessage, "topicA", "unknown", "12345678");
+        assert!(validate(&manager, client, &request).is_err());
+
+        let request = signed_request(
+            RequestCode::UpdateAndCreateTopic,
+            "topicA",
+            "RocketMQ",
+            "12345678",
+        );
+        let err = validate(&manager, client, &request).unwrap_err();
+        assert!(err.contains("Need admin permission"), "{}", err);
+    }
+
+    #[test]
+    fn white_remote_addresses_skip_checks() {
+        let manager = PlainPermissionManager::new(acl_file("white", ACL_YAML));
+        manager.load().unwrap();
+        let unsigned = RemotingCommand::create_remoting_command(RequestCode::SendMessage);
+        assert!(validate(&manager, "10.10.1.1:5000", &unsigned).is_ok());
+        assert!(validate(&manager, "127.0.0.1:5000", &unsigned).is_err());
+
+        let request = signed_request(RequestCode::SendMessage, "topicB", "rocketmq2", "wrong-key");
+        assert!(validate(&manager, "192.168.0.3:5000", &request).is_ok());
+        assert!(validate(&manager, "192.168.1.3:5000", &request).is_err());
+    }
+
+    #[test]
+    fn admin_updates_are_written_back() {
+        let path = acl_file("update", ACL_YAML);
+        let manager = PlainPermissionManager::new(path.clone());
+        manager.load().unwrap();
+        let version = manager.data_version();
+
+        manager
+            .update_access_config(PlainAccessConfig {
+                access_key: "teamB-app".to_string(),
+                secret_key: "teamB-secret".to_string(),
+                default_topic_perm: Some("SUB".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(manager.delete_access_config("rocketmq2").unwrap());
+        assert!(!manager.delete_access_config("rocketmq2").unwrap());
+        manager
+            .update_global_white_addrs(vec!["172.16.0.*".to_string()])
+            .unwrap();
+        assert!(manager
+            .update_global_white_addrs(vec!["not an address".to_string()])
+            .is_err());
+        assert!(manager
+            .update_access_config(PlainAccessConfig {
+                access_key: "short".to_string(),
+                ..Default::default()
+            })
+            .is_err());
+        assert_eq!(manager.data_version(), version + 3);
+
+        let reloaded = PlainPermissionManager::new(path);
+        reloaded.load().unwrap();
+        let config = reloaded.acl_config();
+        assert_eq!(config, manager.acl_config());
+        assert_eq!(config.global_white_remote_addresses, vec!["172.16.0.*"]);
+        let access_keys: Vec<&str> = config
+            .accounts
+            .iter()
+            .map(|account| account.access_key.as_str())
+            .collect();
+        assert_eq!(access_keys, vec!["RocketMQ", "teamB-app"]);
+    }
+
+    #[test]
+    fn reloads_changed_file_and_keeps_config_on_error() {
+        let path = acl_file("reload", ACL_YAML);
+        let manager = PlainPermissionManager::new(path.clone());
+        manager.load().unwrap();
+        assert!(!manager.reload_if_changed());
+
+        fs::write(
+            &path,
+            "accounts:\n  - accessKey: teamC-app\n    secretKey: teamC-secret\n",
+        )
+        .unwrap();
+        assert!(manager.reload_if_changed());
+        assert_eq!(manager.acl_config().accounts[0].access_key, "teamC-app");
+
+        fs::write(&path, "accounts: [").unwrap();
+        assert!(!manager.reload_if_changed());
+        assert_eq!(manager.acl_config().accounts[0].access_key, "teamC-app");
+    }
+}
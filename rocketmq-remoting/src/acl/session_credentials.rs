@@ -0,0 +1,62 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+pub const ACCESS_KEY: &str = "AccessKey";
+pub const SECRET_KEY: &str = "SecretKey";
+pub const SIGNATURE: &str = "Signature";
+pub const SECURITY_TOKEN: &str = "SecurityToken";
+
+/// Credentials a client signs its requests with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionCredentials {
+    access_key: String,
+    secret_key: String,
+    security_token: Option<String>,
+}
+
+impl SessionCredentials {
+    pub fn new(access_key: impl Into<String>, secret_key: impl Into<String>) -> Self {
+        Self {
+            access_key: access_key.into(),
+            secret_key: secret_key.into(),
+            security_token: None,
+        }
+    }
+
+    pub fn with_security_token(
+        access_key: impl Into<String>,
+        secret_key: impl Into<String>,
+        security_token: impl Into<String>,
+    ) -> Self {
+        Self {
+            access_key: access_key.into(),
+            secret_key: secret_key.into(),
+            security_token: Some(security_token.into()),
+        }
+    }
+
+    pub fn access_key(&self) -> &str {
+        self.access_key.as_str()
+    }
+
+    pub fn secret_key(&self) -> &str {
+        self.secret_key.as_str()
+    }
+
+    pub fn security_token(&self) -> Option<&str> {
+        self.security_token.as_deref()
+    }
+}
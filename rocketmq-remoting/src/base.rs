@@ -14,6 +14,8 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+pub mod channel_event_listener;
 pub mod connection_net_event;
 pub mod remoting_fn;
+pub mod remoting_metrics;
 pub mod response_future;
@@ -0,0 +1,36 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use crate::net::channel::Channel;
+
+/// Callbacks fired by a remoting client as the state of its channels changes.
+///
+/// Implementors must be `Send + Sync` since callbacks are invoked from the client's background
+/// tasks. Callbacks should return quickly; long-running work belongs on a separate task.
+pub trait ChannelEventListener: Send + Sync + 'static {
+    /// A new channel to `remote_addr` has been established.
+    fn on_channel_connect(&self, remote_addr: &str, channel: &Channel);
+
+    /// The channel to `remote_addr` has been closed, either by the peer or locally.
+    fn on_channel_close(&self, remote_addr: &str, channel: &Channel);
+
+    /// The channel to `remote_addr` failed with an error and has been closed.
+    fn on_channel_exception(&self, remote_addr: &str, channel: &Channel);
+
+    /// The channel to `remote_addr` has been idle for longer than the configured max idle time
+    /// and has been closed.
+    fn on_channel_idle(&self, remote_addr: &str, channel: &Channel);
+}
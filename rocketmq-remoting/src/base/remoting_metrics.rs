@@ -0,0 +1,488 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use rocketmq_common::common::stats::stats_item_set::StatsItemSet;
+use tracing::warn;
+
+use crate::code::response_code::RemotingSysResponseCode;
+
+pub const RPC_LATENCY: &str = "RPC_LATENCY";
+pub const RPC_ERRORS: &str = "RPC_ERRORS";
+pub const RPC_REQUEST_SIZE: &str = "RPC_REQUEST_SIZE";
+pub const RPC_RESPONSE_SIZE: &str = "RPC_RESPONSE_SIZE";
+pub const RPC_LATENCY_DISTRIBUTION: &str = "RPC_LATENCY_DISTRIBUTION";
+
+/// Latency buckets of [`RPC_LATENCY_DISTRIBUTION`]: the inclusive upper bound in milliseconds and
+/// the name of each bucket, the last one is unbounded.
+pub const LATENCY_BUCKETS: [(u64, &str); 13] = [
+    (0, "[<=0ms]"),
+    (10, "[0~10ms]"),
+    (50, "[10~50ms]"),
+    (100, "[50~100ms]"),
+    (200, "[100~200ms]"),
+    (500, "[200~500ms]"),
+    (1000, "[500ms~1s]"),
+    (2000, "[1~2s]"),
+    (3000, "[2~3s]"),
+    (4000, "[3~4s]"),
+    (5000, "[4~5s]"),
+    (10000, "[5~10s]"),
+    (u64::MAX, "[10s~]"),
+];
+
+/// The end of the connection a request was recorded on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemotingRole {
+    /// The request was received and processed.
+    Server,
+    /// The request was sent and its response awaited.
+    Client,
+}
+
+impl RemotingRole {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RemotingRole::Server => "SERVER",
+            RemotingRole::Client => "CLIENT",
+        }
+    }
+}
+
+/// A completed request, as seen by the server that processed it or the client that sent it.
+#[derive(Debug, Clone)]
+pub struct RequestMetrics<'a> {
+    pub role: RemotingRole,
+    pub code: i32,
+    pub remote_addr: &'a str,
+    pub opaque: i32,
+    /// Code of the response, `None` for oneway requests, requests answered later by the
+    /// processor, and requests that failed before a response was received.
+    pub response_code: Option<i32>,
+    /// The request failed: it was rejected, its processing or sending failed, it timed out, or it
+    /// was answered with a system error.
+    pub error: bool,
+    pub request_body_bytes: usize,
+    pub response_body_bytes: usize,
+    /// Time the request waited for a server executor, always zero on clients.
+    pub queue_time: Duration,
+    /// Time from receiving the request to writing its response on servers, and from sending the
+    /// request to receiving its response on clients.
+    pub cost: Duration,
+}
+
+impl RequestMetrics<'_> {
+    /// Whether `response_code` is a failure of the remote end rather than a business result.
+    pub fn is_system_error(response_code: i32) -> bool {
+        response_code == RemotingSysResponseCode::SystemError as i32
+            || response_code == RemotingSysResponseCode::SystemBusy as i32
+            || response_code == RemotingSysResponseCode::RequestCodeNotSupported as i32
+    }
+}
+
+/// Receives the metrics of every request a server or client handles.
+///
+/// Callbacks are invoked on the request path and must return quickly.
+pub trait RemotingMetricsSink: Send + Sync + 'static {
+    /// A request with `code` started; it is in flight until the matching `on_request_end`.
+    fn on_request_begin(&self, role: RemotingRole, code: i32);
+
+    /// A request started with `on_request_begin` completed, successfully or not.
+    fn on_request_end(&self, metrics: &RequestMetrics<'_>);
+}
+
+/// Forwards request metrics to an optional [`RemotingMetricsSink`] and logs the requests slower
+/// than a threshold.
+#[derive(Clone)]
+pub struct RemotingMetrics {
+    role: RemotingRole,
+    sink: Option<Arc<dyn RemotingMetricsSink>>,
+    slow_threshold: Option<Duration>,
+}
+
+impl RemotingMetrics {
+    /// Records nothing and logs no slow requests until configured.
+    pub fn new(role: RemotingRole) -> Self {
+        Self {
+            role,
+            sink: None,
+            slow_threshold: None,
+        }
+    }
+
+    pub fn set_sink(mut self, sink: Option<Arc<dyn RemotingMetricsSink>>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Logs the requests taking longer than `slow_threshold_millis`, `0` disables the log.
+    pub fn set_slow_threshold_millis(mut self, slow_threshold_millis: u64) -> Self {
+        self.slow_threshold =
+            (slow_threshold_millis > 0).then(|| Duration::from_millis(slow_threshold_millis));
+        self
+    }
+
+    pub fn role(&self) -> RemotingRole {
+        self.role
+    }
+
+    pub(crate) fn begin(&self, code: i32) {
+        if let Some(sink) = &self.sink {
+            sink.on_request_begin(self.role, code);
+        }
+    }
+
+    pub(crate) fn end(&self, metrics: &RequestMetrics<'_>) {
+        if let Some(sink) = &self.sink {
+            sink.on_request_end(metrics);
+        }
+        if let Some(slow_threshold) = self.slow_threshold {
+            if metrics.cost >= slow_threshold {
+                warn!(
+                    "slow remoting request, role={}, code={}, remote={}, opaque={}, \
+                     responseCode={:?}, queue={}ms, cost={}ms",
+                    metrics.role.name(),
+                    metrics.code,
+                    metrics.remote_addr,
+                    metrics.opaque,
+                    metrics.response_code,
+                    metrics.queue_time.as_millis(),
+                    metrics.cost.as_millis()
+                );
+            }
+        }
+    }
+}
+
+/// A [`RemotingMetricsSink`] keeping per request code stats in [`StatsItemSet`]s keyed by
+/// `ROLE@code`, e.g. `SERVER@10`:
+///
+/// * [`RPC_LATENCY`]: the cost in milliseconds per request, so the snapshot average is the mean
+///   latency and its times the request count.
+/// * [`RPC_ERRORS`]: one per failed request.
+/// * [`RPC_REQUEST_SIZE`] and [`RPC_RESPONSE_SIZE`]: body bytes per request.
+/// * [`RPC_LATENCY_DISTRIBUTION`]: one per request, keyed `ROLE@code@bucket` with the bucket of
+///   [`LATENCY_BUCKETS`] the request cost falls in, e.g. `SERVER@10@[10~50ms]`.
+///
+/// Call [`StatsRemotingMetricsSink::init`] within a tokio runtime to sample the minute, hour and
+/// day snapshots.
+#[derive(Debug, Clone)]
+pub struct StatsRemotingMetricsSink {
+    latency: StatsItemSet,
+    errors: StatsItemSet,
+    request_size: StatsItemSet,
+    response_size: StatsItemSet,
+    latency_distribution: StatsItemSet,
+    in_flight: Arc<RwLock<HashMap<String, Arc<AtomicI64>>>>,
+}
+
+/// Totals of the requests with one code recorded by a [`StatsRemotingMetricsSink`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemotingCodeStats {
+    pub role: RemotingRole,
+    pub code: i32,
+    pub times: u64,
+    pub latency_millis: u64,
+    pub errors: u64,
+    pub in_flight: i64,
+    /// Requests per bucket of [`LATENCY_BUCKETS`].
+    pub latency_distribution: [u64; LATENCY_BUCKETS.len()],
+}
+
+impl RemotingCodeStats {
+    pub fn stats_key(&self) -> String {
+        StatsRemotingMetricsSink::stats_key(self.role, self.code)
+    }
+}
+
+impl std::fmt::Display for RemotingCodeStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "times: {}, avgLatency: {}ms, errors: {}, inFlight: {}, latency:",
+            self.times,
+            self.latency_millis / self.times.max(1),
+            self.errors,
+            self.in_flight
+        )?;
+        for ((_, bucket), times) in LATENCY_BUCKETS.iter().zip(self.latency_distribution) {
+            write!(f, " {}:{}", bucket, times)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for StatsRemotingMetricsSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatsRemotingMetricsSink {
+    pub fn new() -> Self {
+        Self {
+            latency: StatsItemSet::new(RPC_LATENCY.to_string()),
+            errors: StatsItemSet::new(RPC_ERRORS.to_string()),
+            request_size: StatsItemSet::new(RPC_REQUEST_SIZE.to_string()),
+            response_size: StatsItemSet::new(RPC_RESPONSE_SIZE.to_string()),
+            latency_distribution: StatsItemSet::new(RPC_LATENCY_DISTRIBUTION.to_string()),
+            in_flight: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn init(&self) {
+        self.latency.init();
+        self.errors.init();
+        self.request_size.init();
+        self.response_size.init();
+        self.latency_distribution.init();
+    }
+
+    pub fn shutdown(&self) {
+        self.latency.shutdown();
+        self.errors.shutdown();
+        self.request_size.shutdown();
+        self.response_size.shutdown();
+        self.latency_distribution.shutdown();
+    }
+
+    pub fn stats_key(role: RemotingRole, code: i32) -> String {
+        format!("{}@{}", role.name(), code)
+    }
+
+    pub fn latency(&self) -> &StatsItemSet {
+        &self.latency
+    }
+
+    pub fn errors(&self) -> &StatsItemSet {
+        &self.errors
+    }
+
+    pub fn request_size(&self) -> &StatsItemSet {
+        &self.request_size
+    }
+
+    pub fn response_size(&self) -> &StatsItemSet {
+        &self.response_size
+    }
+
+    pub fn latency_distribution(&self) -> &StatsItemSet {
+        &self.latency_distribution
+    }
+
+    /// Name of the bucket of [`LATENCY_BUCKETS`] a request costing `cost_millis` falls in.
+    pub fn latency_bucket(cost_millis: u64) -> &'static str {
+        LATENCY_BUCKETS
+            .iter()
+            .find(|(upper_millis, _)| cost_millis <= *upper_millis)
+            .map_or(
+                LATENCY_BUCKETS[LATENCY_BUCKETS.len() - 1].1,
+                |(_, bucket)| bucket,
+            )
+    }
+
+    /// Totals of every request code recorded so far, ordered by role and code.
+    pub fn snapshot(&self) -> Vec<RemotingCodeStats> {
+        let in_flight = self
+            .in_flight
+            .read()
+            .iter()
+            .map(|(stats_key, in_flight)| (stats_key.clone(), in_flight.load(Ordering::Relaxed)))
+            .collect::<Vec<_>>();
+        let mut snapshot = in_flight
+            .into_iter()
+            .filter_map(|(stats_key, in_flight)| {
+                let (role, code) = stats_key.split_once('@')?;
+                let role = match role {
+                    "SERVER" => RemotingRole::Server,
+                    "CLIENT" => RemotingRole::Client,
+                    _ => return None,
+                };
+                let (times, latency_millis) = self
+                    .latency
+                    .get_stats_item(&stats_key)
+                    .map_or((0, 0), |latency| (latency.get_times(), latency.get_value()));
+                let mut latency_distribution = [0; LATENCY_BUCKETS.len()];
+                for (times, (_, bucket)) in latency_distribution.iter_mut().zip(LATENCY_BUCKETS) {
+                    *times = self
+                        .latency_distribution
+                        .get_stats_item(&format!("{}@{}", stats_key, bucket))
+                        .map_or(0, |item| item.get_times());
+                }
+                Some(RemotingCodeStats {
+                    role,
+                    code: code.parse().ok()?,
+                    times,
+                    latency_millis,
+                    errors: self
+                        .errors
+                        .get_stats_item(&stats_key)
+                        .map_or(0, |errors| errors.get_times()),
+                    in_flight,
+                    latency_distribution,
+                })
+            })
+            .collect::<Vec<_>>();
+        snapshot.sort_by_key(|stats| (stats.role.name(), stats.code));
+        snapshot
+    }
+
+    /// Requests with `code` currently in flight.
+    pub fn in_flight(&self, role: RemotingRole, code: i32) -> i64 {
+        self.in_flight
+            .read()
+            .get(&Self::stats_key(role, code))
+            .map_or(0, |in_flight| in_flight.load(Ordering::Relaxed))
+    }
+
+    fn in_flight_counter(&self, stats_key: String) -> Arc<AtomicI64> {
+        if let Some(in_flight) = self.in_flight.read().get(&stats_key) {
+            return Arc::clone(in_flight);
+        }
+        Arc::clone(self.in_flight.write().entry(stats_key).or_default())
+    }
+}
+
+impl RemotingMetricsSink for StatsRemotingMetricsSink {
+    fn on_request_begin(&self, role: RemotingRole, code: i32) {
+        self.in_flight_counter(Self::stats_key(role, code))
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_request_end(&self, metrics: &RequestMetrics<'_>) {
+        let stats_key = Self::stats_key(metrics.role, metrics.code);
+        self.in_flight_counter(stats_key.clone())
+            .fetch_sub(1, Ordering::Relaxed);
+        let cost_millis = metrics.cost.as_millis() as u64;
+        self.latency.add_value(&stats_key, cost_millis, 1);
+        self.latency_distribution.add_value(
+            &format!("{}@{}", stats_key, Self::latency_bucket(cost_millis)),
+            1,
+            1,
+        );
+        if metrics.error {
+            self.errors.add_value(&stats_key, 1, 1);
+        }
+        self.request_size
+            .add_value(&stats_key, metrics.request_body_bytes as u64, 1);
+        self.response_size
+            .add_value(&stats_key, metrics.response_body_bytes as u64, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_metrics(error: bool, cost_millis: u64) -> RequestMetrics<'static> {
+        RequestMetrics {
+            role: RemotingRole::Server,
+            code: 10,
+            remote_addr: "127.0.0.1:10911",
+            opaque: 1,
+            response_code: Some(0),
+            error,
+            request_body_bytes: 100,
+            response_body_bytes: 1000,
+            queue_time: Duration::ZERO,
+            cost: Duration::from_millis(cost_millis),
+        }
+    }
+
+    #[test]
+    fn stats_sink_records_per_code_stats() {
+        let sink = StatsRemotingMetricsSink::new();
+        sink.on_request_begin(RemotingRole::Server, 10);
+        sink.on_request_begin(RemotingRole::Server, 10);
+        assert_eq!(sink.in_flight(RemotingRole::Server, 10), 2);
+        assert_eq!(sink.in_flight(RemotingRole::Client, 10), 0);
+
+        sink.on_request_end(&request_metrics(false, 20));
+        sink.on_request_end(&request_metrics(true, 40));
+        assert_eq!(sink.in_flight(RemotingRole::Server, 10), 0);
+
+        let stats_key = StatsRemotingMetricsSink::stats_key(RemotingRole::Server, 10);
+        assert_eq!(stats_key, "SERVER@10");
+        let latency = sink.latency().get_stats_item(&stats_key).unwrap();
+        assert_eq!((latency.get_value(), latency.get_times()), (60, 2));
+        let errors = sink.errors().get_stats_item(&stats_key).unwrap();
+        assert_eq!(errors.get_times(), 1);
+        let request_size = sink.request_size().get_stats_item(&stats_key).unwrap();
+        assert_eq!(request_size.get_value(), 200);
+        let response_size = sink.response_size().get_stats_item(&stats_key).unwrap();
+        assert_eq!(response_size.get_value(), 2000);
+    }
+
+    #[test]
+    fn stats_sink_records_latency_distribution() {
+        assert_eq!(StatsRemotingMetricsSink::latency_bucket(0), "[<=0ms]");
+        assert_eq!(StatsRemotingMetricsSink::latency_bucket(10), "[0~10ms]");
+        assert_eq!(StatsRemotingMetricsSink::latency_bucket(11), "[10~50ms]");
+        assert_eq!(StatsRemotingMetricsSink::latency_bucket(60_000), "[10s~]");
+
+        let sink = StatsRemotingMetricsSink::new();
+        for cost_millis in [5, 8, 20, 1500] {
+            sink.on_request_begin(RemotingRole::Server, 10);
+            sink.on_request_end(&request_metrics(cost_millis == 1500, cost_millis));
+        }
+        sink.on_request_begin(RemotingRole::Server, 11);
+        assert_eq!(
+            sink.latency_distribution()
+                .get_stats_item("SERVER@10@[0~10ms]")
+                .unwrap()
+                .get_times(),
+            2
+        );
+
+        let snapshot = sink.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        let stats = &snapshot[0];
+        assert_eq!((stats.code, stats.times, stats.errors), (10, 4, 1));
+        assert_eq!(stats.latency_distribution[1], 2);
+        assert_eq!(stats.latency_distribution[2], 1);
+        assert_eq!(stats.latency_distribution[7], 1);
+        assert_eq!(stats.latency_distribution.iter().sum::<u64>(), 4);
+        assert!(stats
+            .to_string()
+            .starts_with("times: 4, avgLatency: 383ms, errors: 1"));
+        assert_eq!((snapshot[1].code, snapshot[1].in_flight), (11, 1));
+    }
+
+    #[test]
+    fn metrics_without_sink_is_a_no_op() {
+        let metrics = RemotingMetrics::new(RemotingRole::Client).set_slow_threshold_millis(1);
+        metrics.begin(10);
+        metrics.end(&request_metrics(false, 5));
+        assert_eq!(metrics.role(), RemotingRole::Client);
+    }
+
+    #[test]
+    fn system_error_codes() {
+        assert!(RequestMetrics::is_system_error(
+            RemotingSysResponseCode::SystemBusy as i32
+        ));
+        assert!(!RequestMetrics::is_system_error(
+            RemotingSysResponseCode::Success as i32
+        ));
+    }
+}
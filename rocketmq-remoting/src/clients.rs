@@ -81,17 +81,17 @@ pub trait RemotingClient: RemotingService {
     /// * `timeout_millis` - The timeout for the operation in milliseconds.
     async fn invoke_oneway(&self, addr: String, request: RemotingCommand, timeout_millis: u64);
 
-    /// Checks if a specified address is reachable.
+    /// Checks if a specified address is reachable, connecting to it if necessary.
     ///
     /// # Arguments
     /// * `addr` - The address to check for reachability.
-    fn is_address_reachable(&mut self, addr: String);
+    async fn is_address_reachable(&self, addr: &str) -> bool;
 
     /// Closes clients connected to the specified addresses.
     ///
     /// # Arguments
     /// * `addrs` - A list of addresses whose clients should be closed.
-    fn close_clients(&mut self, addrs: Vec<String>);
+    async fn close_clients(&self, addrs: Vec<String>);
 
     fn register_processor(&mut self, processor: impl RequestProcessor + Sync);
 }
@@ -15,10 +15,15 @@
  * limitations under the License.
  */
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 use futures_util::SinkExt;
 use futures_util::StreamExt;
 use rocketmq_common::ArcRefCellWrapper;
+use rocketmq_common::TimeUtils::get_current_millis;
 use tokio::sync::mpsc::Receiver;
 use tracing::error;
 use tracing::info;
@@ -28,10 +33,14 @@ use crate::base::connection_net_event::ConnectionNetEvent;
 use crate::base::response_future::ResponseFuture;
 use crate::code::response_code::ResponseCode;
 use crate::connection::Connection;
+use crate::connection::ConnectionStream;
 use crate::error::Error::ConnectionInvalid;
 use crate::error::Error::Io;
 use crate::error::Error::RemoteException;
 use crate::net::channel::Channel;
+use crate::net::tls::TlsClientContext;
+use crate::net::unix;
+use crate::net::unix::UNIX_CHANNEL_ADDRESS;
 use crate::protocol::remoting_command::RemotingCommand;
 use crate::protocol::RemotingCommandType;
 use crate::runtime::connection_handler_context::ConnectionHandlerContextWrapper;
@@ -57,6 +66,10 @@ struct ClientInner {
     channel: Channel,
     ctx: ArcRefCellWrapper<ConnectionHandlerContextWrapper>,
     tx: tokio::sync::mpsc::Sender<SendMessage>,
+    /// Last time a frame was read from or written to the connection.
+    last_active_millis: AtomicU64,
+    /// Whether the connection was torn down by an I/O error rather than a clean close.
+    exception: AtomicBool,
 }
 
 type SendMessage = (
@@ -74,8 +87,10 @@ async fn run_send(mut client: ArcRefCellWrapper<ClientInner>, mut rx: Receiver<S
 async fn run_recv<PR: RequestProcessor>(
     mut client: ArcRefCellWrapper<ClientInner>,
     mut processor: PR,
+    event_tx: Option<tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
 ) {
     while let Some(response) = client.ctx.channel.connection.reader.next().await {
+        client.touch();
         match response {
             Ok(msg) => match msg.get_type() {
                 // handle request
@@ -128,9 +143,12 @@ async fn run_recv<PR: RequestProcessor>(
             },
             Err(error) => match error {
                 Io(value) => {
-                    client.ctx.channel.connection.ok = false;
                     error!("error: {:?}", value);
-                    return;
+                    client.exception.store(true, Ordering::Release);
+                    if let Some(event_tx) = event_tx.as_ref() {
+                        let _ = event_tx.send(ConnectionNetEvent::EXCEPTION);
+                    }
+                    break;
                 }
                 _ => {
                     error!("error: {:?}", error);
@@ -138,6 +156,11 @@ async fn run_recv<PR: RequestProcessor>(
             },
         }
     }
+    client.ctx.channel.connection.ok = false;
+    client.fail_pending_requests();
+    if let Some(event_tx) = event_tx.as_ref() {
+        let _ = event_tx.send(ConnectionNetEvent::DISCONNECTED);
+    }
 }
 
 impl ClientInner {
@@ -160,7 +183,63 @@ impl ClientInner {
         let stream = tcp_stream?;
         let local_addr = stream.local_addr()?;
         let remote_address = stream.peer_addr()?;
-        let connection = Connection::new(stream);
+        Self::start(stream.into(), local_addr, remote_address, processor, tx)
+    }
+
+    pub async fn connect_with_tls<PR>(
+        addr: &str,
+        tls_context: &TlsClientContext,
+        processor: PR,
+        tx: Option<&tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
+    ) -> Result<(
+        tokio::sync::mpsc::Sender<SendMessage>,
+        ArcRefCellWrapper<ClientInner>,
+    )>
+    where
+        PR: RequestProcessor + 'static,
+    {
+        let stream = tokio::net::TcpStream::connect(addr).await?;
+        let local_addr = stream.local_addr()?;
+        let remote_address = stream.peer_addr()?;
+        let stream = tls_context.connect(addr, stream).await?;
+        Self::start(stream, local_addr, remote_address, processor, tx)
+    }
+
+    pub async fn connect_unix<PR>(
+        path: &str,
+        processor: PR,
+        tx: Option<&tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
+    ) -> Result<(
+        tokio::sync::mpsc::Sender<SendMessage>,
+        ArcRefCellWrapper<ClientInner>,
+    )>
+    where
+        PR: RequestProcessor + 'static,
+    {
+        let stream = unix::connect(path).await?;
+        Self::start(
+            stream,
+            UNIX_CHANNEL_ADDRESS,
+            unix::next_unix_channel_address(),
+            processor,
+            tx,
+        )
+    }
+
+    fn start<PR>(
+        stream: ConnectionStream,
+        local_addr: SocketAddr,
+        remote_address: SocketAddr,
+        processor: PR,
+        tx: Option<&tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
+    ) -> Result<(
+        tokio::sync::mpsc::Sender<SendMessage>,
+        ArcRefCellWrapper<ClientInner>,
+    )>
+    where
+        PR: RequestProcessor + 'static,
+    {
+        let connection = Connection::with_stream(stream);
         let response_table = ArcRefCellWrapper::new(HashMap::with_capacity(128));
         let channel = Channel::new(
             local_addr,
@@ -178,10 +257,12 @@ impl ClientInner {
             response_table,
             channel,
             tx: tx_.clone(),
+            last_active_millis: AtomicU64::new(get_current_millis()),
+            exception: AtomicBool::new(false),
         };
         let client = ArcRefCellWrapper::new(client);
 
-        tokio::spawn(run_recv(client.clone(), processor));
+        tokio::spawn(run_recv(client.clone(), processor, tx.cloned()));
         tokio::spawn(run_send(client.clone(), rx));
         if let Some(tx) = tx {
             let _ = tx.send(ConnectionNetEvent::CONNECTED(
@@ -198,6 +279,7 @@ impl ClientInner {
         tx: Option<tokio::sync::oneshot::Sender<Result<RemotingCommand>>>,
         timeout_millis: Option<u64>,
     ) -> Result<()> {
+        self.touch();
         let opaque = request.opaque();
         if let Some(tx) = tx {
             self.response_table.insert(
@@ -220,6 +302,23 @@ impl ClientInner {
             },
         }
     }
+
+    fn touch(&self) {
+        self.last_active_millis
+            .store(get_current_millis(), Ordering::Release);
+    }
+
+    /// Completes every in-flight request with an error so callers do not wait for their
+    /// timeout once the connection is gone.
+    fn fail_pending_requests(&mut self) {
+        let remote_address = self.channel.remote_address();
+        for (_, response_future) in self.response_table.drain() {
+            let _ = response_future.tx.send(Err(ConnectionInvalid(format!(
+                "connection to {} closed",
+                remote_address
+            ))));
+        }
+    }
 }
 
 impl Client {
@@ -256,6 +355,34 @@ impl Client {
         })
     }
 
+    /// Creates a new `Client` instance and connects to `addr` over TLS. The server certificate
+    /// is verified against the host part of `addr`.
+    pub async fn connect_with_tls<PR>(
+        addr: &str,
+        tls_context: &TlsClientContext,
+        processor: PR,
+        tx: Option<&tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
+    ) -> Result<Client>
+    where
+        PR: RequestProcessor + 'static,
+    {
+        let (tx, inner) = ClientInner::connect_with_tls(addr, tls_context, processor, tx).await?;
+        Ok(Client { inner, tx })
+    }
+
+    /// Creates a new `Client` instance connected to the Unix domain socket at `path`.
+    pub async fn connect_unix<PR>(
+        path: &str,
+        processor: PR,
+        tx: Option<&tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
+    ) -> Result<Client>
+    where
+        PR: RequestProcessor + 'static,
+    {
+        let (tx, inner) = ClientInner::connect_unix(path, processor, tx).await?;
+        Ok(Client { inner, tx })
+    }
+
     /// Invokes a remote operation with the given `RemotingCommand`.
     ///
     /// # Arguments
@@ -360,6 +487,35 @@ impl Client {
         self.inner.ctx.channel.connection_ref()
     }
 
+    pub fn channel(&self) -> &Channel {
+        &self.inner.channel
+    }
+
+    /// Returns whether the connection was closed because of an I/O error.
+    pub fn is_exception(&self) -> bool {
+        self.inner.exception.load(Ordering::Acquire)
+    }
+
+    /// Returns how long no frame has been read from or written to the connection.
+    pub fn idle_millis(&self) -> u64 {
+        get_current_millis().saturating_sub(self.inner.last_active_millis.load(Ordering::Acquire))
+    }
+
+    /// Returns whether `other` shares the same underlying connection.
+    pub fn same_connection(&self, other: &Client) -> bool {
+        std::ptr::eq(self.connection(), other.connection())
+    }
+
+    /// Marks the connection as unusable and shuts down its write half, which makes the peer
+    /// close the connection as well.
+    pub async fn close(&mut self) {
+        let connection = self.connection_mut();
+        connection.ok = false;
+        if let Err(error) = connection.writer.close().await {
+            warn!("close connection failed: {}", error);
+        }
+    }
+
     pub fn connection_mut(&mut self) -> &mut Connection {
         self.inner.ctx.channel.connection_mut()
     }
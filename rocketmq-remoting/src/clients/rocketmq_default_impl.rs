@@ -16,12 +16,16 @@
  */
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::atomic::AtomicI32;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
+use parking_lot::Mutex as SyncMutex;
 use rand::Rng;
 use rocketmq_common::ArcRefCellWrapper;
+use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_runtime::RocketMQRuntime;
 use tokio::sync::Mutex;
 use tokio::time;
@@ -30,10 +34,18 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::base::channel_event_listener::ChannelEventListener;
 use crate::base::connection_net_event::ConnectionNetEvent;
+use crate::base::remoting_metrics::RemotingMetrics;
+use crate::base::remoting_metrics::RemotingMetricsSink;
+use crate::base::remoting_metrics::RemotingRole;
+use crate::base::remoting_metrics::RequestMetrics;
 use crate::clients::Client;
 use crate::clients::RemotingClient;
+use crate::code::response_code::ResponseCode;
 use crate::error::Error;
+use crate::net::tls::TlsClientContext;
+use crate::net::unix;
 use crate::protocol::remoting_command::RemotingCommand;
 use crate::remoting::RemotingService;
 use crate::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
@@ -43,6 +55,44 @@ use crate::runtime::RPCHook;
 use crate::Result;
 
 const LOCK_TIMEOUT_MILLIS: u64 = 3000;
+const CHANNEL_SCAN_INTERVAL_MILLIS: u64 = 1000;
+const INITIAL_RECONNECT_INTERVAL_MILLIS: u64 = 100;
+
+/// Failed connection attempts to one address and when the next attempt is allowed.
+#[derive(Debug, Clone, Copy, Default)]
+struct ReconnectBackoff {
+    failures: u32,
+    next_attempt_millis: u64,
+}
+
+impl ReconnectBackoff {
+    fn can_attempt(&self, now_millis: u64) -> bool {
+        now_millis >= self.next_attempt_millis
+    }
+
+    /// Records a failed attempt and returns how long to wait before the next one.
+    fn record_failure(&mut self, now_millis: u64, max_interval_millis: u64) -> u64 {
+        self.failures = self.failures.saturating_add(1);
+        let interval = reconnect_interval_millis(self.failures, max_interval_millis);
+        self.next_attempt_millis = now_millis.saturating_add(interval);
+        interval
+    }
+}
+
+/// Doubles the reconnect interval on every consecutive failure, capped at
+/// `max_interval_millis`.
+fn reconnect_interval_millis(failures: u32, max_interval_millis: u64) -> u64 {
+    let shift = failures.saturating_sub(1).min(32);
+    INITIAL_RECONNECT_INTERVAL_MILLIS
+        .saturating_mul(1u64 << shift)
+        .min(max_interval_millis.max(INITIAL_RECONNECT_INTERVAL_MILLIS))
+}
+
+enum ChannelEviction {
+    Closed,
+    Exception,
+    Idle,
+}
 
 pub type ArcSyncClient = Arc<Mutex<Client>>;
 
@@ -58,6 +108,11 @@ pub struct RocketmqDefaultClient<PR = DefaultRemotingRequestProcessor> {
     client_runtime: Arc<RocketMQRuntime>,
     processor: PR,
     tx: Option<tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
+    tls_context: Option<TlsClientContext>,
+    rpc_hooks: Vec<Arc<Box<dyn RPCHook>>>,
+    channel_event_listener: Option<Arc<dyn ChannelEventListener>>,
+    reconnect_backoff: Arc<SyncMutex<HashMap<String /* ip:port */, ReconnectBackoff>>>,
+    metrics: RemotingMetrics,
 }
 impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
     pub fn new(tokio_client_config: Arc<TokioClientConfig>, processor: PR) -> Self {
@@ -69,6 +124,17 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
         processor: PR,
         tx: Option<tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
     ) -> Self {
+        let tls_context =
+            TlsClientContext::from_config(&tokio_client_config).unwrap_or_else(|err| {
+                warn!(
+                    "failed to initialize the TLS context of the remoting client, trusting the \
+                     platform CA certificates instead: {}",
+                    err
+                );
+                Some(TlsClientContext::with_platform_roots())
+            });
+        let metrics = RemotingMetrics::new(RemotingRole::Client)
+            .set_slow_threshold_millis(tokio_client_config.slow_request_threshold_millis);
         Self {
             tokio_client_config,
             connection_tables: Arc::new(Mutex::new(Default::default())),
@@ -79,11 +145,45 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
             client_runtime: Arc::new(RocketMQRuntime::new_multi(10, "client-thread")),
             processor,
             tx,
+            tls_context,
+            rpc_hooks: Vec::new(),
+            channel_event_listener: None,
+            reconnect_backoff: Arc::new(SyncMutex::new(HashMap::new())),
+            metrics,
         }
     }
+
+    pub fn set_channel_event_listener(
+        mut self,
+        channel_event_listener: Arc<dyn ChannelEventListener>,
+    ) -> Self {
+        self.channel_event_listener = Some(channel_event_listener);
+        self
+    }
+
+    /// Sets the sink receiving the metrics of every request the client sends.
+    pub fn set_metrics_sink(mut self, metrics_sink: Arc<dyn RemotingMetricsSink>) -> Self {
+        self.metrics = self.metrics.set_sink(Some(metrics_sink));
+        self
+    }
 }
 
 impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
+    /// Runs the registered hooks on an outgoing request, e.g. to sign it. Requests to the name
+    /// server are passed an unspecified address.
+    fn do_before_rpc_hooks(&self, addr: Option<&str>, request: &mut RemotingCommand) -> Result<()> {
+        if self.rpc_hooks.is_empty() {
+            return Ok(());
+        }
+        let remote_addr = addr
+            .and_then(|addr| addr.parse::<SocketAddr>().ok())
+            .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 0)));
+        for hook in &self.rpc_hooks {
+            hook.do_before_request(remote_addr, request)?;
+        }
+        Ok(())
+    }
+
     async fn get_and_create_nameserver_client(&self) -> Option<Client> {
         let mut addr = self.namesrv_addr_choosed.as_ref().clone();
         if let Some(ref addr) = addr {
@@ -160,50 +260,234 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
 
     async fn create_client(&self, addr: &str, duration: Duration) -> Option<Client> {
         let mut connection_tables = self.connection_tables.lock().await;
-        let cw = connection_tables.get(addr);
-        if let Some(cw) = cw {
+        if let Some(cw) = connection_tables.get(addr) {
             // if cw.lock().await.connection().ok {
             if cw.connection().ok {
                 return Some(cw.clone());
             }
         }
+        if let Some(stale) = connection_tables.remove(addr) {
+            self.notify_channel_evicted(addr, &stale, ChannelEviction::Closed);
+        }
 
-        let cw = connection_tables.get(addr);
-        if let Some(cw) = cw {
-            if cw.connection().ok {
-                // if cw.lock().await.connection().ok {
-                return Some(cw.clone());
+        let now = get_current_millis();
+        if let Some(backoff) = self.reconnect_backoff.lock().get(addr) {
+            if !backoff.can_attempt(now) {
+                debug!(
+                    "skip connecting to {}, {} consecutive failures, next attempt in {}ms",
+                    addr,
+                    backoff.failures,
+                    backoff.next_attempt_millis - now
+                );
+                return None;
             }
-        } else {
-            let _ = connection_tables.remove(addr);
         }
 
         let addr_inner = addr.to_string();
 
-        match time::timeout(duration, async {
-            Client::connect(addr_inner, self.processor.clone(), self.tx.as_ref()).await
+        let result = match time::timeout(duration, async {
+            if let Some(path) = unix::unix_socket_path(addr) {
+                // The socket never leaves the host, so it is not wrapped in TLS
+                return Client::connect_unix(path, self.processor.clone(), self.tx.as_ref()).await;
+            }
+            match self.tls_context.as_ref() {
+                Some(tls_context) => {
+                    Client::connect_with_tls(
+                        addr_inner.as_str(),
+                        tls_context,
+                        self.processor.clone(),
+                        self.tx.as_ref(),
+                    )
+                    .await
+                }
+                None => Client::connect(addr_inner, self.processor.clone(), self.tx.as_ref()).await,
+            }
         })
         .await
         {
-            Ok(client_inner) => match client_inner {
-                Ok(client_r) => {
-                    //let client = Arc::new(Mutex::new(client_r));
-                    let client = client_r;
-                    connection_tables.insert(addr.to_string(), client.clone());
-                    Some(client)
-                }
-                Err(_) => {
-                    error!("getAndCreateClient connect to {} failed", addr);
-                    None
+            Ok(client_inner) => client_inner,
+            Err(_) => Err(Error::RemoteException(format!(
+                "connect timeout after {}ms",
+                duration.as_millis()
+            ))),
+        };
+        match result {
+            Ok(client) => {
+                //let client = Arc::new(Mutex::new(client_r));
+                connection_tables.insert(addr.to_string(), client.clone());
+                self.reconnect_backoff.lock().remove(addr);
+                if let Some(listener) = self.channel_event_listener.as_ref() {
+                    listener.on_channel_connect(addr, client.channel());
                 }
-            },
-            Err(_) => {
-                error!("getAndCreateClient connect to {} failed", addr);
+                Some(client)
+            }
+            Err(e) => {
+                let max_interval_millis = self
+                    .tokio_client_config
+                    .max_reconnect_interval_time_seconds
+                    .max(0) as u64
+                    * 1000;
+                let interval = self
+                    .reconnect_backoff
+                    .lock()
+                    .entry(addr.to_string())
+                    .or_default()
+                    .record_failure(get_current_millis(), max_interval_millis);
+                error!(
+                    "getAndCreateClient connect to {} failed, {}, retry after {}ms",
+                    addr, e, interval
+                );
                 None
             }
         }
     }
 
+    fn notify_channel_evicted(&self, addr: &str, client: &Client, eviction: ChannelEviction) {
+        if let Some(listener) = self.channel_event_listener.as_ref() {
+            match eviction {
+                ChannelEviction::Closed => listener.on_channel_close(addr, client.channel()),
+                ChannelEviction::Exception => listener.on_channel_exception(addr, client.channel()),
+                ChannelEviction::Idle => listener.on_channel_idle(addr, client.channel()),
+            }
+        }
+    }
+
+    /// Evicts broken channels and closes channels idle for longer than
+    /// `client_channel_max_idle_time_seconds`.
+    async fn scan_channels(&self) {
+        let max_idle_millis = self
+            .tokio_client_config
+            .client_channel_max_idle_time_seconds
+            .max(0) as u64
+            * 1000;
+        let mut evicted = Vec::new();
+        {
+            let mut connection_tables = self.connection_tables.lock().await;
+            let addrs = connection_tables
+                .iter()
+                .filter_map(|(addr, client)| {
+                    let eviction = if client.is_exception() {
+                        ChannelEviction::Exception
+                    } else if !client.connection().ok {
+                        ChannelEviction::Closed
+                    } else if max_idle_millis > 0 && client.idle_millis() >= max_idle_millis {
+                        ChannelEviction::Idle
+                    } else {
+                        return None;
+                    };
+                    Some((addr.clone(), eviction))
+                })
+                .collect::<Vec<_>>();
+            for (addr, eviction) in addrs {
+                if let Some(client) = connection_tables.remove(&addr) {
+                    evicted.push((addr, client, eviction));
+                }
+            }
+        }
+        for (addr, mut client, eviction) in evicted {
+            match eviction {
+                ChannelEviction::Idle => {
+                    info!(
+                        "close idle channel {}, idle {}ms",
+                        addr,
+                        client.idle_millis()
+                    );
+                    client.close().await;
+                }
+                _ => warn!("evict broken channel {}", addr),
+            }
+            self.notify_channel_evicted(&addr, &client, eviction);
+        }
+    }
+
+    /// Removes the channel of `addr` from the connection table if it is still `client`'s, so
+    /// that the next request opens a new connection. The old connection is left open for
+    /// responses still in flight; the peer closes it once it is drained.
+    async fn evict_for_go_away(&self, addr: &str, client: &Client) {
+        let mut connection_tables = self.connection_tables.lock().await;
+        if connection_tables
+            .get(addr)
+            .is_some_and(|current| current.same_connection(client))
+        {
+            connection_tables.remove(addr);
+            info!("receive go away from {}, reconnect for new requests", addr);
+        }
+    }
+
+    async fn invoke_once(
+        &self,
+        addr: Option<&str>,
+        request: RemotingCommand,
+        timeout_millis: u64,
+    ) -> Result<(Client, RemotingCommand)> {
+        let client = self.get_and_create_client(addr).await;
+        match client {
+            None => Err(Error::RemoteException("get client failed".to_string())),
+            Some(client) => {
+                let mut client_inner = client.clone();
+                match self
+                    .client_runtime
+                    .get_handle()
+                    .spawn(async move {
+                        time::timeout(Duration::from_millis(timeout_millis), async move {
+                            client_inner.send_read(request, timeout_millis).await
+                        })
+                        .await
+                    })
+                    .await
+                {
+                    Ok(result) => match result {
+                        Ok(response) => match response {
+                            Ok(value) => Ok((client, value)),
+                            Err(e) => Err(Error::RemoteException(e.to_string())),
+                        },
+                        Err(err) => Err(Error::RemoteException(err.to_string())),
+                    },
+                    Err(err) => Err(Error::RemoteException(err.to_string())),
+                }
+            }
+        }
+    }
+
+    /// Sends `request`, evicting the channel when the server answers with GO_AWAY and retrying
+    /// once on a new channel within what remains of `timeout_millis` since `begin`.
+    async fn invoke_with_go_away_retry(
+        &self,
+        addr: Option<&str>,
+        request: RemotingCommand,
+        timeout_millis: u64,
+        begin: Instant,
+    ) -> Result<RemotingCommand> {
+        let reconnect_for_go_away = self.tokio_client_config.enable_reconnect_for_go_away;
+        let retry_request = (reconnect_for_go_away
+            && self.tokio_client_config.enable_transparent_retry)
+            .then(|| request.clone());
+
+        let (client, response) = self.invoke_once(addr, request, timeout_millis).await?;
+        if !reconnect_for_go_away || ResponseCode::from(response.code()) != ResponseCode::GoAway {
+            return Ok(response);
+        }
+        let target = match addr {
+            Some(addr) if !addr.is_empty() => Some(addr.to_string()),
+            _ => self.namesrv_addr_choosed.as_ref().clone(),
+        };
+        if let Some(target) = target.as_deref() {
+            self.evict_for_go_away(target, &client).await;
+        }
+
+        let remaining_millis = timeout_millis.saturating_sub(begin.elapsed().as_millis() as u64);
+        match retry_request {
+            Some(retry_request) if remaining_millis > 0 => {
+                let retry_request =
+                    retry_request.set_opaque(RemotingCommand::create_new_request_id());
+                self.invoke_once(addr, retry_request, remaining_millis)
+                    .await
+                    .map(|(_, response)| response)
+            }
+            _ => Ok(response),
+        }
+    }
+
     async fn scan_available_name_srv(&self) {
         if self.namesrv_addr_list.as_ref().is_empty() {
             debug!("scanAvailableNameSrv addresses of name remoting_server is null!");
@@ -255,6 +539,13 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingService for Rocketmq
                 client.scan_available_name_srv().await;
             }
         });
+        let client = self.clone();
+        self.client_runtime.get_handle().spawn(async move {
+            loop {
+                time::sleep(Duration::from_millis(CHANNEL_SCAN_INTERVAL_MILLIS)).await;
+                client.scan_channels().await;
+            }
+        });
     }
 
     fn shutdown(&mut self) {
@@ -262,11 +553,11 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingService for Rocketmq
     }
 
     fn register_rpc_hook(&mut self, hook: Arc<Box<dyn RPCHook>>) {
-        todo!()
+        self.rpc_hooks.push(hook);
     }
 
     fn clear_rpc_hook(&mut self) {
-        todo!()
+        self.rpc_hooks.clear();
     }
 }
 
@@ -333,51 +624,79 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
     async fn invoke_async(
         &self,
         addr: Option<String>,
-        request: RemotingCommand,
+        mut request: RemotingCommand,
         timeout_millis: u64,
     ) -> Result<RemotingCommand> {
-        let client = self.get_and_create_client(addr.as_deref()).await;
-        match client {
-            None => Err(Error::RemoteException("get client failed".to_string())),
-            Some(mut client) => {
-                match self
-                    .client_runtime
-                    .get_handle()
-                    .spawn(async move {
-                        time::timeout(Duration::from_millis(timeout_millis), async move {
-                            client.send_read(request, timeout_millis).await
-                        })
-                        .await
-                    })
-                    .await
-                {
-                    Ok(result) => match result {
-                        Ok(response) => match response {
-                            Ok(value) => Ok(value),
-                            Err(e) => Err(Error::RemoteException(e.to_string())),
-                        },
-                        Err(err) => Err(Error::RemoteException(err.to_string())),
-                    },
-                    Err(err) => Err(Error::RemoteException(err.to_string())),
-                }
-            }
-        }
+        self.do_before_rpc_hooks(addr.as_deref(), &mut request)?;
+        let begin = Instant::now();
+        let (code, opaque) = (request.code(), request.opaque());
+        let request_body_bytes = request.get_body().map_or(0, |body| body.len());
+        self.metrics.begin(code);
+        let result = self
+            .invoke_with_go_away_retry(addr.as_deref(), request, timeout_millis, begin)
+            .await;
+        let remote_addr = match addr {
+            Some(addr) if !addr.is_empty() => addr,
+            _ => self
+                .namesrv_addr_choosed
+                .as_ref()
+                .clone()
+                .unwrap_or_default(),
+        };
+        let response = result.as_ref().ok();
+        let response_code = response.map(|response| response.code());
+        self.metrics.end(&RequestMetrics {
+            role: self.metrics.role(),
+            code,
+            remote_addr: &remote_addr,
+            opaque,
+            response_code,
+            error: response_code.is_none_or(RequestMetrics::is_system_error),
+            request_body_bytes,
+            response_body_bytes: response
+                .and_then(|response| response.get_body())
+                .map_or(0, |body| body.len()),
+            queue_time: Duration::ZERO,
+            cost: begin.elapsed(),
+        });
+        result
     }
 
-    async fn invoke_oneway(&self, addr: String, request: RemotingCommand, timeout_millis: u64) {
+    async fn invoke_oneway(&self, addr: String, mut request: RemotingCommand, timeout_millis: u64) {
+        if let Err(err) = self.do_before_rpc_hooks(Some(addr.as_str()), &mut request) {
+            error!("rpc hook rejected oneway request to {}: {}", addr, err);
+            return;
+        }
         let client = self.get_and_create_client(Some(addr.as_str())).await;
         match client {
             None => {
                 error!("get client failed");
             }
             Some(mut client) => {
+                let metrics = self.metrics.clone();
                 self.client_runtime.get_handle().spawn(async move {
-                    match time::timeout(Duration::from_millis(timeout_millis), async move {
+                    let begin = Instant::now();
+                    let (code, opaque) = (request.code(), request.opaque());
+                    let request_body_bytes = request.get_body().map_or(0, |body| body.len());
+                    metrics.begin(code);
+                    let result = time::timeout(Duration::from_millis(timeout_millis), async {
                         //client.lock().await.send(request).await
                         client.send(request).await
                     })
-                    .await
-                    {
+                    .await;
+                    metrics.end(&RequestMetrics {
+                        role: metrics.role(),
+                        code,
+                        remote_addr: &addr,
+                        opaque,
+                        response_code: None,
+                        error: !matches!(result, Ok(Ok(_))),
+                        request_body_bytes,
+                        response_body_bytes: 0,
+                        queue_time: Duration::ZERO,
+                        cost: begin.elapsed(),
+                    });
+                    match result {
                         Ok(_) => Ok(()),
                         Err(err) => Err(Error::RemoteException(err.to_string())),
                     }
@@ -386,12 +705,19 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
         }
     }
 
-    fn is_address_reachable(&mut self, addr: String) {
-        todo!()
+    async fn is_address_reachable(&self, addr: &str) -> bool {
+        self.get_and_create_client(Some(addr)).await.is_some()
     }
 
-    fn close_clients(&mut self, addrs: Vec<String>) {
-        todo!()
+    async fn close_clients(&self, addrs: Vec<String>) {
+        for addr in addrs {
+            let client = self.connection_tables.lock().await.remove(&addr);
+            if let Some(mut client) = client {
+                info!("close channel {}", addr);
+                client.close().await;
+                self.notify_channel_evicted(&addr, &client, ChannelEviction::Closed);
+            }
+        }
     }
 
     fn register_processor(&mut self, processor: impl RequestProcessor + Sync) {
@@ -403,3 +729,323 @@ fn init_value_index() -> i32 {
     let mut rng = rand::thread_rng();
     rng.gen_range(0..999)
 }
+
+#[cfg(test)]
+mod tests {
+    use futures_util::SinkExt;
+    use futures_util::StreamExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::base::remoting_metrics::StatsRemotingMetricsSink;
+    use crate::connection::Connection;
+    use crate::net::channel::Channel;
+
+    const REQUEST_CODE: i32 = 10;
+
+    #[derive(Default)]
+    struct RecordingListener {
+        events: SyncMutex<Vec<String>>,
+    }
+
+    impl RecordingListener {
+        fn events(&self) -> Vec<String> {
+            self.events.lock().clone()
+        }
+    }
+
+    impl ChannelEventListener for RecordingListener {
+        fn on_channel_connect(&self, remote_addr: &str, _channel: &Channel) {
+            self.events.lock().push(format!("connect {}", remote_addr));
+        }
+
+        fn on_channel_close(&self, remote_addr: &str, _channel: &Channel) {
+            self.events.lock().push(format!("close {}", remote_addr));
+        }
+
+        fn on_channel_exception(&self, remote_addr: &str, _channel: &Channel) {
+            self.events
+                .lock()
+                .push(format!("exception {}", remote_addr));
+        }
+
+        fn on_channel_idle(&self, remote_addr: &str, _channel: &Channel) {
+            self.events.lock().push(format!("idle {}", remote_addr));
+        }
+    }
+
+    /// Answers requests on the n-th accepted connection with `respond(n, request)`, closing the
+    /// connection when it returns `None`.
+    async fn start_server(
+        respond: fn(usize, &RemotingCommand) -> Option<RemotingCommand>,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut index = 0;
+            while let Ok((stream, _)) = listener.accept().await {
+                let connection_index = index;
+                index += 1;
+                tokio::spawn(async move {
+                    let mut connection = Connection::new(stream);
+                    while let Some(Ok(request)) = connection.reader.next().await {
+                        let Some(response) = respond(connection_index, &request) else {
+                            return;
+                        };
+                        let response = response.set_opaque(request.opaque());
+                        if connection.writer.send(response).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    fn go_away_on_first_connection(index: usize, _: &RemotingCommand) -> Option<RemotingCommand> {
+        let code = if index == 0 {
+            ResponseCode::GoAway
+        } else {
+            ResponseCode::Success
+        };
+        Some(RemotingCommand::create_response_command_with_code(code))
+    }
+
+    fn new_client(
+        config: TokioClientConfig,
+    ) -> (
+        RocketmqDefaultClient,
+        Arc<RecordingListener>,
+        tokio::runtime::Runtime,
+    ) {
+        let listener = Arc::new(RecordingListener::default());
+        let client = RocketmqDefaultClient::new(Arc::new(config), DefaultRemotingRequestProcessor)
+            .set_channel_event_listener(listener.clone());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        (client, listener, runtime)
+    }
+
+    #[test]
+    fn reconnect_interval_doubles_up_to_max() {
+        assert_eq!(reconnect_interval_millis(1, 60_000), 100);
+        assert_eq!(reconnect_interval_millis(2, 60_000), 200);
+        assert_eq!(reconnect_interval_millis(5, 60_000), 1600);
+        assert_eq!(reconnect_interval_millis(20, 60_000), 60_000);
+        assert_eq!(reconnect_interval_millis(u32::MAX, 60_000), 60_000);
+        assert_eq!(reconnect_interval_millis(3, 0), 100);
+    }
+
+    #[test]
+    fn backoff_blocks_attempts_until_interval_elapsed() {
+        let mut backoff = ReconnectBackoff::default();
+        assert!(backoff.can_attempt(0));
+        assert_eq!(backoff.record_failure(1000, 60_000), 100);
+        assert!(!backoff.can_attempt(1099));
+        assert!(backoff.can_attempt(1100));
+        assert_eq!(backoff.record_failure(1100, 60_000), 200);
+        assert_eq!(backoff.failures, 2);
+    }
+
+    #[test]
+    fn go_away_reconnects_and_retries_transparently() {
+        let (client, listener, runtime) = new_client(TokioClientConfig::default());
+        runtime.block_on(async {
+            let addr = start_server(go_away_on_first_connection).await;
+            let response = client
+                .invoke_async(
+                    Some(addr.clone()),
+                    RemotingCommand::create_remoting_command(REQUEST_CODE),
+                    3000,
+                )
+                .await
+                .unwrap();
+            assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+            assert_eq!(
+                listener.events(),
+                vec![format!("connect {}", addr), format!("connect {}", addr)]
+            );
+        });
+    }
+
+    #[test]
+    fn records_request_metrics() {
+        let metrics_sink = Arc::new(StatsRemotingMetricsSink::new());
+        let (client, _listener, runtime) = new_client(TokioClientConfig::default());
+        let client = client.set_metrics_sink(metrics_sink.clone());
+        runtime.block_on(async {
+            let addr = start_server(|_, _| {
+                Some(RemotingCommand::create_response_command().set_body(Some("route")))
+            })
+            .await;
+            client
+                .invoke_async(
+                    Some(addr.clone()),
+                    RemotingCommand::create_remoting_command(REQUEST_CODE).set_body(Some("query")),
+                    3000,
+                )
+                .await
+                .unwrap();
+            assert!(client
+                .invoke_async(
+                    Some("127.0.0.1:1".to_string()),
+                    RemotingCommand::create_remoting_command(REQUEST_CODE),
+                    3000,
+                )
+                .await
+                .is_err());
+        });
+        drop(client);
+
+        let stats_key = StatsRemotingMetricsSink::stats_key(RemotingRole::Client, REQUEST_CODE);
+        let latency = metrics_sink.latency().get_stats_item(&stats_key).unwrap();
+        assert_eq!(latency.get_times(), 2);
+        let errors = metrics_sink.errors().get_stats_item(&stats_key).unwrap();
+        assert_eq!(errors.get_times(), 1);
+        let request_size = metrics_sink
+            .request_size()
+            .get_stats_item(&stats_key)
+            .unwrap();
+        assert_eq!(request_size.get_value(), 5);
+        let response_size = metrics_sink
+            .response_size()
+            .get_stats_item(&stats_key)
+            .unwrap();
+        assert_eq!(response_size.get_value(), 5);
+        assert_eq!(
+            metrics_sink.in_flight(RemotingRole::Client, REQUEST_CODE),
+            0
+        );
+    }
+
+    #[test]
+    fn go_away_is_returned_without_transparent_retry() {
+        let config = TokioClientConfig {
+            enable_transparent_retry: false,
+            ..TokioClientConfig::default()
+        };
+        let (client, _listener, runtime) = new_client(config);
+        runtime.block_on(async {
+            let addr = start_server(go_away_on_first_connection).await;
+            let request = RemotingCommand::create_remoting_command(REQUEST_CODE);
+            let response = client
+                .invoke_async(Some(addr.clone()), request.clone(), 3000)
+                .await
+                .unwrap();
+            assert_eq!(ResponseCode::from(response.code()), ResponseCode::GoAway);
+
+            let request = request.set_opaque(RemotingCommand::create_new_request_id());
+            let response = client
+                .invoke_async(Some(addr), request, 3000)
+                .await
+                .unwrap();
+            assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+        });
+    }
+
+    #[test]
+    fn closed_channel_fails_in_flight_requests_and_is_evicted() {
+        let (client, listener, runtime) = new_client(TokioClientConfig::default());
+        runtime.block_on(async {
+            let addr = start_server(|_, _| None).await;
+            let begin = Instant::now();
+            let result = client
+                .invoke_async(
+                    Some(addr.clone()),
+                    RemotingCommand::create_remoting_command(REQUEST_CODE),
+                    10_000,
+                )
+                .await;
+            assert!(result.is_err());
+            assert!(begin.elapsed() < Duration::from_secs(5));
+
+            client.scan_channels().await;
+            assert!(client.connection_tables.lock().await.is_empty());
+            assert_eq!(
+                listener.events(),
+                vec![format!("connect {}", addr), format!("close {}", addr)]
+            );
+        });
+    }
+
+    #[test]
+    fn idle_channel_is_closed() {
+        let config = TokioClientConfig {
+            client_channel_max_idle_time_seconds: 1,
+            ..TokioClientConfig::default()
+        };
+        let (client, listener, runtime) = new_client(config);
+        runtime.block_on(async {
+            let addr = start_server(go_away_on_first_connection).await;
+            assert!(client.is_address_reachable(&addr).await);
+            client.scan_channels().await;
+            assert_eq!(client.connection_tables.lock().await.len(), 1);
+
+            time::sleep(Duration::from_millis(1100)).await;
+            client.scan_channels().await;
+            assert!(client.connection_tables.lock().await.is_empty());
+            assert_eq!(
+                listener.events(),
+                vec![format!("connect {}", addr), format!("idle {}", addr)]
+            );
+        });
+    }
+
+    #[test]
+    fn unreachable_address_backs_off() {
+        let (client, listener, runtime) = new_client(TokioClientConfig::default());
+        runtime.block_on(async {
+            let addr = {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                listener.local_addr().unwrap().to_string()
+            };
+            assert!(!client.is_address_reachable(&addr).await);
+            assert!(!client.is_address_reachable(&addr).await);
+            assert_eq!(client.reconnect_backoff.lock()[&addr].failures, 1);
+
+            time::sleep(Duration::from_millis(
+                INITIAL_RECONNECT_INTERVAL_MILLIS + 20,
+            ))
+            .await;
+            assert!(!client.is_address_reachable(&addr).await);
+            assert_eq!(client.reconnect_backoff.lock()[&addr].failures, 2);
+            assert!(listener.events().is_empty());
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn invokes_unix_socket_address() {
+        let dir = std::env::temp_dir().join(format!("rocketmq-client-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("namesrv.sock").to_str().unwrap().to_string();
+        let addr = format!("{}{}", crate::net::unix::UNIX_ADDRESS_PREFIX, path);
+        let (client, listener, runtime) = new_client(TokioClientConfig::default());
+        runtime.block_on(async {
+            let unix_listener = crate::net::unix::bind(&path).unwrap();
+            tokio::spawn(async move {
+                let (stream, _) = unix_listener.accept().await.unwrap();
+                let mut connection = Connection::with_stream(stream.into());
+                while let Some(Ok(request)) = connection.reader.next().await {
+                    let response =
+                        RemotingCommand::create_response_command().set_opaque(request.opaque());
+                    if connection.writer.send(response).await.is_err() {
+                        return;
+                    }
+                }
+            });
+            let response = client
+                .invoke_async(
+                    Some(addr.clone()),
+                    RemotingCommand::create_remoting_command(REQUEST_CODE),
+                    3000,
+                )
+                .await
+                .unwrap();
+            assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+            assert_eq!(listener.events(), vec![format!("connect {}", addr)]);
+        });
+        drop(client);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -141,6 +141,10 @@ pub enum RequestCode {
     ExchangeBrokerHaInfo = 906,
     GetBrokerHaStatus = 907,
     ResetMasterFlushOffset = 908,
+    /// Client subscribes to route changes of its topics on the connection carrying the request.
+    SubscribeTopicRouteChange = 920,
+    /// Name server pushes changed topic routes to subscribed clients.
+    NotifyTopicRouteChange = 921,
     GetAllProducerInfo = 328,
     DeleteExpiredCommitlog = 329,
 
@@ -284,6 +288,8 @@ impl From<i32> for RequestCode {
             906 => RequestCode::ExchangeBrokerHaInfo,
             907 => RequestCode::GetBrokerHaStatus,
             908 => RequestCode::ResetMasterFlushOffset,
+            920 => RequestCode::SubscribeTopicRouteChange,
+            921 => RequestCode::NotifyTopicRouteChange,
             328 => RequestCode::GetAllProducerInfo,
             329 => RequestCode::DeleteExpiredCommitlog,
             2001 => RequestCode::UpdateColdDataFlowCtrConfig,
@@ -320,4 +326,58 @@ pub enum ControllerRequestCode {
     CleanBrokerData = 1011,
     ControllerGetNextBrokerId = 1012,
     ControllerApplyBrokerId = 1013,
+    /// Raft message exchanged between the controllers of a group, see `controller_raft_peers`.
+    ControllerRaftMessage = 1050,
+}
+
+impl From<ControllerRequestCode> for i32 {
+    fn from(value: ControllerRequestCode) -> Self {
+        value as i32
+    }
+}
+
+impl ControllerRequestCode {
+    pub fn to_i32(self) -> i32 {
+        self.into()
+    }
+
+    pub fn value_of(code: i32) -> Option<Self> {
+        match code {
+            1001 => Some(ControllerRequestCode::ControllerAlterSyncStateSet),
+            1002 => Some(ControllerRequestCode::ControllerElectMaster),
+            1003 => Some(ControllerRequestCode::ControllerRegisterBroker),
+            1004 => Some(ControllerRequestCode::ControllerGetReplicaInfo),
+            1005 => Some(ControllerRequestCode::ControllerGetMetadataInfo),
+            1006 => Some(ControllerRequestCode::ControllerGetSyncStateData),
+            1007 => Some(ControllerRequestCode::GetBrokerEpochCache),
+            1008 => Some(ControllerRequestCode::NotifyBrokerRoleChanged),
+            1009 => Some(ControllerRequestCode::UpdateControllerConfig),
+            1010 => Some(ControllerRequestCode::GetControllerConfig),
+            1011 => Some(ControllerRequestCode::CleanBrokerData),
+            1012 => Some(ControllerRequestCode::ControllerGetNextBrokerId),
+            1013 => Some(ControllerRequestCode::ControllerApplyBrokerId),
+            1050 => Some(ControllerRequestCode::ControllerRaftMessage),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controller_request_code_round_trips() {
+        for code in (1001..=1013).chain([1050]) {
+            assert_eq!(
+                ControllerRequestCode::value_of(code).unwrap().to_i32(),
+                code
+            );
+        }
+        assert_eq!(ControllerRequestCode::value_of(1000), None);
+        assert_eq!(
+            ControllerRequestCode::value_of(RequestCode::BrokerHeartbeat.to_i32()),
+            None
+        );
+    }
 }
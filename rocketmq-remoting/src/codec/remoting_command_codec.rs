@@ -22,6 +22,7 @@ use tokio_util::codec::Encoder;
 
 use crate::error::Error;
 use crate::protocol::remoting_command::RemotingCommand;
+use crate::protocol::remoting_command::FRAME_MAX_LENGTH;
 
 /// Encodes a `RemotingCommand` into a `BytesMut` buffer.
 ///
@@ -44,7 +45,9 @@ use crate::protocol::remoting_command::RemotingCommand;
 ///
 /// This function will return an error if the encoding process fails.
 #[derive(Debug, Clone)]
-pub struct RemotingCommandCodec;
+pub struct RemotingCommandCodec {
+    max_frame_size: usize,
+}
 
 impl Default for RemotingCommandCodec {
     fn default() -> Self {
@@ -53,8 +56,17 @@ impl Default for RemotingCommandCodec {
 }
 
 impl RemotingCommandCodec {
+    /// Creates a codec limited to the process-wide max frame length, see [`FRAME_MAX_LENGTH`].
     pub fn new() -> Self {
-        Self {}
+        Self::with_max_frame_size(*FRAME_MAX_LENGTH)
+    }
+
+    pub fn with_max_frame_size(max_frame_size: usize) -> Self {
+        Self { max_frame_size }
+    }
+
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
     }
 }
 
@@ -67,13 +79,15 @@ impl Decoder for RemotingCommandCodec {
     /// This method takes a mutable reference to a `BytesMut` buffer as a parameter.
     /// It first checks if there are at least 4 bytes in the buffer, if not, it returns `Ok(None)`.
     /// Then it reads the total size of the incoming data as a big-endian i32 from the first 4
-    /// bytes. If the available data is less than the total size, it returns `Ok(None)`.
+    /// bytes. A total size below 4 or above the max frame size is rejected with an error. If the
+    /// available data is less than the total size, it returns `Ok(None)`.
     /// It then splits the `BytesMut` buffer to get the command data including the total size and
     /// discards the first i32 (total size). It reads the header length as a big-endian i32 and
     /// checks if the header length is greater than the total size minus 4. If it is, it returns
     /// an error. It then splits the buffer again to get the header data and deserializes it
     /// into a `RemotingCommand`. If the total size minus 4 is greater than the header length,
-    /// it sets the body of the `RemotingCommand`.
+    /// it sets the body of the `RemotingCommand`. A body the peer compressed is restored before
+    /// the command is returned, see [`RemotingCommand::decompress_body`].
     ///
     /// # Arguments
     ///
@@ -89,7 +103,12 @@ impl Decoder for RemotingCommandCodec {
     ///
     /// This function will return an error if the decoding process fails.
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        RemotingCommand::decode(src)
+        let Some(mut cmd) = RemotingCommand::decode_with_max_frame_size(src, self.max_frame_size)?
+        else {
+            return Ok(None);
+        };
+        cmd.decompress_body(self.max_frame_size)?;
+        Ok(Some(cmd))
         /* let read_to = src.len();
         if read_to < 4 {
             // Wait for more data when there are less than 4 bytes.
@@ -172,6 +191,7 @@ impl Encoder<RemotingCommand> for RemotingCommandCodec {
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
+    use rocketmq_common::common::compression::compression_type::CompressionType;
 
     use super::*;
     use crate::protocol::header::client_request_header::GetRouteInfoRequestHeader;
@@ -180,15 +200,86 @@ mod tests {
     #[tokio::test]
     async fn decode_handles_insufficient_data() {
         let mut decoder = RemotingCommandCodec::new();
-        let mut src = BytesMut::from(&[0, 0, 0, 1][..]);
+        let mut src = BytesMut::from(&[0, 0, 0, 16][..]);
+        assert!(matches!(decoder.decode(&mut src), Ok(None)));
+        let mut src = BytesMut::from(&[0, 0, 0][..]);
         assert!(matches!(decoder.decode(&mut src), Ok(None)));
     }
 
     #[tokio::test]
-    async fn decode_handles_sufficient_data() {
+    async fn decode_rejects_frame_shorter_than_header_length() {
         let mut decoder = RemotingCommandCodec::new();
         let mut src = BytesMut::from(&[0, 0, 0, 1, 0, 0, 0, 0][..]);
+        assert!(decoder.decode(&mut src).is_err());
+    }
+
+    #[tokio::test]
+    async fn decode_rejects_negative_frame_length() {
+        let mut decoder = RemotingCommandCodec::new();
+        let mut src = BytesMut::from(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0][..]);
+        assert!(decoder.decode(&mut src).is_err());
+    }
+
+    #[tokio::test]
+    async fn decode_rejects_oversized_frame_before_buffering_it() {
+        let mut decoder = RemotingCommandCodec::with_max_frame_size(1024);
+        let mut src = BytesMut::from(&[0, 0, 0x04, 0x01][..]);
+        assert!(decoder.decode(&mut src).is_err());
+
+        let mut encoder = RemotingCommandCodec::new();
+        let mut dst = BytesMut::new();
+        let command =
+            RemotingCommand::create_remoting_command(1).set_body(Some(Bytes::from(vec![0; 2048])));
+        encoder.encode(command, &mut dst).unwrap();
+        assert!(decoder.decode(&mut dst).is_err());
+    }
+
+    #[tokio::test]
+    async fn decode_reserves_a_chunk_of_a_large_pending_frame() {
+        let mut decoder = RemotingCommandCodec::new();
+        let frame_size = 8 * 1024 * 1024u32;
+        let mut src = BytesMut::from(&frame_size.to_be_bytes()[..]);
         assert!(matches!(decoder.decode(&mut src), Ok(None)));
+        assert!(src.capacity() < 1024 * 1024, "{}", src.capacity());
+    }
+
+    #[tokio::test]
+    async fn decode_rejects_header_longer_than_frame() {
+        let mut decoder = RemotingCommandCodec::new();
+        let mut src = BytesMut::from(&[0, 0, 0, 8, 0, 0, 0, 16, 0, 0, 0, 0][..]);
+        assert!(decoder.decode(&mut src).is_err());
+    }
+
+    #[tokio::test]
+    async fn decode_restores_compressed_body() {
+        let body = Bytes::from("consumer-offset".repeat(1024));
+        let mut command = RemotingCommand::create_response_command().set_body(Some(body.clone()));
+        command.compress_body(CompressionType::Zstd, 5).unwrap();
+        let compressed_len = command.get_body().unwrap().len();
+
+        let mut codec = RemotingCommandCodec::new();
+        let mut dst = BytesMut::new();
+        codec.encode(command, &mut dst).unwrap();
+        assert!(dst.len() < body.len());
+        assert!(dst.len() > compressed_len);
+
+        let decoded = codec.decode(&mut dst).unwrap().unwrap();
+        assert_eq!(decoded.body_compression(), None);
+        assert_eq!(decoded.get_body(), Some(&body));
+    }
+
+    #[tokio::test]
+    async fn decode_rejects_body_inflating_beyond_max_frame_size() {
+        let mut command = RemotingCommand::create_response_command()
+            .set_body(Some(Bytes::from(vec![0u8; 64 * 1024])));
+        command.compress_body(CompressionType::Zlib, 5).unwrap();
+        let mut dst = BytesMut::new();
+        RemotingCommandCodec::new()
+            .encode(command, &mut dst)
+            .unwrap();
+        assert!(RemotingCommandCodec::with_max_frame_size(4096)
+            .decode(&mut dst)
+            .is_err());
     }
 
     #[tokio::test]
@@ -219,4 +310,144 @@ mod tests {
             .set_remark(Some("remark".to_string()));
         assert!(encoder.encode(command, &mut dst).is_ok());
     }
+
+    mod fuzz {
+        use std::collections::HashMap;
+
+        use proptest::collection::hash_map;
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+
+        use super::*;
+        use crate::protocol::SerializeType;
+
+        const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+        fn decode_all(src: &mut BytesMut) {
+            let mut decoder = RemotingCommandCodec::with_max_frame_size(MAX_FRAME_SIZE);
+            while let Ok(Some(_)) = decoder.decode(src) {}
+        }
+
+        fn serialize_type() -> impl Strategy<Value = SerializeType> {
+            prop_oneof![Just(SerializeType::JSON), Just(SerializeType::ROCKETMQ)]
+        }
+
+        #[derive(Debug, Clone)]
+        struct CommandParts {
+            serialize_type: SerializeType,
+            code: i32,
+            opaque: i32,
+            flag: i32,
+            remark: Option<String>,
+            ext_fields: HashMap<String, String>,
+            body: Option<Vec<u8>>,
+        }
+
+        impl CommandParts {
+            fn build(&self) -> RemotingCommand {
+                RemotingCommand::create_remoting_command(self.code)
+                    .set_serialize_type(self.serialize_type)
+                    .set_opaque(self.opaque)
+                    .set_flag(self.flag)
+                    .set_remark(self.remark.clone())
+                    .set_ext_fields(self.ext_fields.clone())
+                    .set_body(self.body.clone().map(Bytes::from))
+            }
+        }
+
+        fn command() -> impl Strategy<Value = CommandParts> {
+            (
+                serialize_type(),
+                0..i16::MAX as i32,
+                any::<i32>(),
+                0..2i32,
+                proptest::option::of("\\PC{1,32}"),
+                hash_map("[a-zA-Z]{1,12}", "\\PC{1,24}", 0..6),
+                proptest::option::of(vec(any::<u8>(), 1..256)),
+            )
+                .prop_map(
+                    |(serialize_type, code, opaque, flag, remark, ext_fields, body)| CommandParts {
+                        serialize_type,
+                        code,
+                        opaque,
+                        flag,
+                        remark,
+                        ext_fields,
+                        body,
+                    },
+                )
+        }
+
+        fn encode(command: RemotingCommand) -> BytesMut {
+            let mut dst = BytesMut::new();
+            RemotingCommandCodec::new()
+                .encode(command, &mut dst)
+                .unwrap();
+            dst
+        }
+
+        proptest! {
+            #[test]
+            fn decode_never_panics_on_arbitrary_bytes(bytes in vec(any::<u8>(), 0..1024)) {
+                decode_all(&mut BytesMut::from(&bytes[..]));
+            }
+
+            #[test]
+            fn decode_never_panics_on_arbitrary_headers(
+                serialize_type in serialize_type(),
+                header in vec(any::<u8>(), 0..512),
+                body in vec(any::<u8>(), 0..64),
+            ) {
+                let mut src = BytesMut::new();
+                src.put_i32((4 + header.len() + body.len()) as i32);
+                src.put_i32(RemotingCommand::mark_serialize_type(
+                    header.len() as i32,
+                    serialize_type,
+                ));
+                src.put_slice(&header);
+                src.put_slice(&body);
+                decode_all(&mut src);
+            }
+
+            #[test]
+            fn encoded_command_round_trips(parts in command()) {
+                let mut src = encode(parts.build());
+                let decoded = RemotingCommandCodec::new().decode(&mut src).unwrap().unwrap();
+
+                prop_assert!(src.is_empty());
+                prop_assert_eq!(decoded.code(), parts.code);
+                prop_assert_eq!(decoded.opaque(), parts.opaque);
+                prop_assert_eq!(decoded.flag(), parts.flag);
+                prop_assert_eq!(decoded.remark(), parts.remark.as_ref());
+                prop_assert_eq!(decoded.body().as_deref(), parts.body.as_deref());
+                prop_assert_eq!(
+                    decoded.ext_fields().cloned().unwrap_or_default(),
+                    parts.ext_fields
+                );
+            }
+
+            #[test]
+            fn truncated_frame_waits_for_more_data(
+                parts in command(),
+                cut in any::<prop::sample::Index>(),
+            ) {
+                let frame = encode(parts.build());
+                let mut src = BytesMut::from(&frame[..cut.index(frame.len())]);
+                let mut decoder = RemotingCommandCodec::new();
+                prop_assert!(matches!(decoder.decode(&mut src), Ok(None)));
+            }
+
+            #[test]
+            fn corrupted_frame_never_panics(
+                parts in command(),
+                position in any::<prop::sample::Index>(),
+                value in any::<u8>(),
+            ) {
+                let mut src = encode(parts.build());
+                let position = position.index(src.len());
+                src[position] = value;
+                decode_all(&mut src);
+            }
+        }
+    }
 }
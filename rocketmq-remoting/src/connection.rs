@@ -16,16 +16,119 @@
  */
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
 
 use futures_util::stream::SplitSink;
 use futures_util::stream::SplitStream;
 use futures_util::StreamExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
 use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+use tokio_rustls::client;
+use tokio_rustls::server;
 use tokio_util::codec::Framed;
 
 use crate::codec::remoting_command_codec::RemotingCommandCodec;
 use crate::protocol::remoting_command::RemotingCommand;
 
+/// The byte stream underneath a `Connection`: plaintext or TLS over TCP, or a local Unix
+/// domain socket.
+pub enum ConnectionStream {
+    Tcp(TcpStream),
+    ServerTls(Box<server::TlsStream<TcpStream>>),
+    ClientTls(Box<client::TlsStream<TcpStream>>),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl ConnectionStream {
+    pub fn is_tls(&self) -> bool {
+        matches!(
+            self,
+            ConnectionStream::ServerTls(_) | ConnectionStream::ClientTls(_)
+        )
+    }
+
+    pub fn is_unix(&self) -> bool {
+        #[cfg(unix)]
+        if let ConnectionStream::Unix(_) = self {
+            return true;
+        }
+        false
+    }
+}
+
+impl From<TcpStream> for ConnectionStream {
+    fn from(tcp_stream: TcpStream) -> Self {
+        ConnectionStream::Tcp(tcp_stream)
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixStream> for ConnectionStream {
+    fn from(unix_stream: UnixStream) -> Self {
+        ConnectionStream::Unix(unix_stream)
+    }
+}
+
+impl AsyncRead for ConnectionStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ConnectionStream::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            ConnectionStream::ServerTls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            ConnectionStream::ClientTls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            #[cfg(unix)]
+            ConnectionStream::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for ConnectionStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            ConnectionStream::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            ConnectionStream::ServerTls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            ConnectionStream::ClientTls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            #[cfg(unix)]
+            ConnectionStream::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ConnectionStream::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            ConnectionStream::ServerTls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            ConnectionStream::ClientTls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            #[cfg(unix)]
+            ConnectionStream::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ConnectionStream::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            ConnectionStream::ServerTls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            ConnectionStream::ClientTls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            #[cfg(unix)]
+            ConnectionStream::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
 /// Send and receive `Frame` values from a remote peer.
 ///
 /// When implementing networking protocols, a message on that protocol is
@@ -41,9 +144,9 @@ use crate::protocol::remoting_command::RemotingCommand;
 pub struct Connection {
     /// The `Framed` instance used for reading from and writing to the TCP stream.
     /// It leverages the `RemotingCommandCodec` for encoding and decoding frames.
-    //pub(crate) framed: Framed<TcpStream, RemotingCommandCodec>,
-    pub(crate) writer: SplitSink<Framed<TcpStream, RemotingCommandCodec>, RemotingCommand>,
-    pub(crate) reader: SplitStream<Framed<TcpStream, RemotingCommandCodec>>,
+    //pub(crate) framed: Framed<ConnectionStream, RemotingCommandCodec>,
+    pub(crate) writer: SplitSink<Framed<ConnectionStream, RemotingCommandCodec>, RemotingCommand>,
+    pub(crate) reader: SplitStream<Framed<ConnectionStream, RemotingCommandCodec>>,
 
     /// A boolean flag indicating the current state of the connection.
     /// `true` means the connection is in a good state, while `false` indicates
@@ -59,12 +162,12 @@ impl Hash for Connection {
         // Use the addr: *const _ess of writer and reader to hash them (they serve as a unique
         // identifier for these components)
         let writer_addr: *const SplitSink<
-            Framed<TcpStream, RemotingCommandCodec>,
+            Framed<ConnectionStream, RemotingCommandCodec>,
             RemotingCommand,
         > = &self.writer
-            as *const SplitSink<Framed<TcpStream, RemotingCommandCodec>, RemotingCommand>;
-        let reader_addr: *const SplitStream<Framed<TcpStream, RemotingCommandCodec>> =
-            &self.reader as *const SplitStream<Framed<TcpStream, RemotingCommandCodec>>;
+            as *const SplitSink<Framed<ConnectionStream, RemotingCommandCodec>, RemotingCommand>;
+        let reader_addr: *const SplitStream<Framed<ConnectionStream, RemotingCommandCodec>> =
+            &self.reader as *const SplitStream<Framed<ConnectionStream, RemotingCommandCodec>>;
 
         writer_addr.hash(state);
         reader_addr.hash(state);
@@ -95,7 +198,17 @@ impl Connection {
     ///
     /// A new `Connection` instance.
     pub fn new(tcp_stream: TcpStream) -> Connection {
-        let framed = Framed::with_capacity(tcp_stream, RemotingCommandCodec::new(), 1024 * 4);
+        Self::with_stream(ConnectionStream::Tcp(tcp_stream))
+    }
+
+    /// Creates a new `Connection` over an already established, possibly TLS, stream.
+    pub fn with_stream(stream: ConnectionStream) -> Connection {
+        Self::with_codec(stream, RemotingCommandCodec::new())
+    }
+
+    /// Creates a new `Connection` framed by `codec`, e.g. one with a custom max frame size.
+    pub fn with_codec(stream: ConnectionStream, codec: RemotingCommandCodec) -> Connection {
+        let framed = Framed::with_capacity(stream, codec, 1024 * 4);
         let (writer, reader) = framed.split();
         Self {
             writer,
@@ -106,14 +219,16 @@ impl Connection {
 }
 
 impl Connection {
-    /*pub fn framed(&self) -> &Framed<TcpStream, RemotingCommandCodec> {
+    /*pub fn framed(&self) -> &Framed<ConnectionStream, RemotingCommandCodec> {
         &self.framed
     }*/
-    pub fn reader(&self) -> &SplitStream<Framed<TcpStream, RemotingCommandCodec>> {
+    pub fn reader(&self) -> &SplitStream<Framed<ConnectionStream, RemotingCommandCodec>> {
         &self.reader
     }
 
-    pub fn writer(&self) -> &SplitSink<Framed<TcpStream, RemotingCommandCodec>, RemotingCommand> {
+    pub fn writer(
+        &self,
+    ) -> &SplitSink<Framed<ConnectionStream, RemotingCommandCodec>, RemotingCommand> {
         &self.writer
     }
 }
@@ -64,6 +64,9 @@ pub enum Error {
 
     #[error("Channel recv Request failed: {0}")]
     ChannelRecvRequestFailed(String),
+
+    #[error("TlsError: {0}")]
+    TlsError(String),
 }
 
 #[cfg(test)]
@@ -142,4 +145,10 @@ mod error_tests {
             "RemotingCommandEncoderError:Encoder error"
         );
     }
+
+    #[test]
+    fn tls_error_contains_correct_message() {
+        let error = Error::TlsError("no private key".into());
+        assert_eq!(error.to_string(), "TlsError: no private key");
+    }
 }
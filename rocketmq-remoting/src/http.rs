@@ -0,0 +1,298 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! A small HTTP/JSON listener for management endpoints, enabled by the `http` feature.
+//!
+//! Servers implement [`HttpRequestHandler`] and hand it to [`HttpServer::run`], the routing is
+//! left to the handler so the name server and the broker can expose what they own.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use http_body_util::Full;
+use http_body_util::Limited;
+use hyper::body::Incoming;
+use hyper::header::CONTENT_TYPE;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+pub use hyper::Method;
+use hyper::Request;
+use hyper::Response;
+pub use hyper::StatusCode;
+use hyper_util::rt::TokioIo;
+use percent_encoding::percent_decode_str;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use tokio::net::TcpListener;
+use tracing::info;
+use tracing::warn;
+
+/// Request bodies larger than this are answered with `413 Payload Too Large`.
+const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    method: Method,
+    path: String,
+    query: HashMap<String, String>,
+    body: Bytes,
+}
+
+impl HttpRequest {
+    /// `path_and_query` is the request target, e.g. `/topics/TopicTest/route?x=1`.
+    pub fn new(method: Method, path_and_query: &str, body: impl Into<Bytes>) -> Self {
+        let (path, query) = path_and_query
+            .split_once('?')
+            .unwrap_or((path_and_query, ""));
+        HttpRequest {
+            method,
+            path: path.to_string(),
+            query: form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect(),
+            body: body.into(),
+        }
+    }
+
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The percent-decoded, non-empty path segments.
+    pub fn path_segments(&self) -> Vec<String> {
+        self.path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+            .collect()
+    }
+
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(String::as_str)
+    }
+
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// Decodes the JSON body, answering `400 Bad Request` when it is malformed.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, HttpResponse> {
+        serde_json::from_slice(&self.body).map_err(|err| {
+            HttpResponse::error(StatusCode::BAD_REQUEST, format!("invalid body: {}", err))
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    status: StatusCode,
+    body: Bytes,
+}
+
+impl HttpResponse {
+    /// A `200 OK` response with `value` as JSON body.
+    pub fn json<T: Serialize + ?Sized>(value: &T) -> Self {
+        Self::json_with_status(StatusCode::OK, value)
+    }
+
+    pub fn json_with_status<T: Serialize + ?Sized>(status: StatusCode, value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(body) => HttpResponse {
+                status,
+                body: Bytes::from(body),
+            },
+            Err(err) => Self::error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("serialize response failed: {}", err),
+            ),
+        }
+    }
+
+    /// `{"error": message}` with the given status.
+    pub fn error(status: StatusCode, message: impl Into<String>) -> Self {
+        HttpResponse {
+            status,
+            body: Bytes::from(json!({ "error": message.into() }).to_string()),
+        }
+    }
+
+    pub fn not_found() -> Self {
+        Self::error(StatusCode::NOT_FOUND, "not found")
+    }
+
+    pub fn method_not_allowed() -> Self {
+        Self::error(StatusCode::METHOD_NOT_ALLOWED, "method not allowed")
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+}
+
+/// Handles the requests of an [`HttpServer`].
+#[trait_variant::make(HttpRequestHandler: Send)]
+pub trait LocalHttpRequestHandler {
+    async fn handle(&self, request: HttpRequest) -> HttpResponse;
+}
+
+pub struct HttpServer {
+    listener: TcpListener,
+}
+
+impl HttpServer {
+    pub async fn bind(address: &str) -> crate::Result<Self> {
+        Ok(HttpServer {
+            listener: TcpListener::bind(address).await?,
+        })
+    }
+
+    pub fn local_addr(&self) -> crate::Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves HTTP/1.1 connections until the task is dropped.
+    pub async fn run<H>(self, handler: Arc<H>)
+    where
+        H: HttpRequestHandler + Sync + 'static,
+    {
+        if let Ok(local_addr) = self.listener.local_addr() {
+            info!("http server listening on {}", local_addr);
+        }
+        loop {
+            let (stream, remote_addr) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    warn!("http server accept failed: {}", err);
+                    continue;
+                }
+            };
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |request| {
+                    let handler = handler.clone();
+                    async move { Ok::<_, Infallible>(dispatch(handler.as_ref(), request).await) }
+                });
+                if let Err(err) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    warn!("http connection from {} failed: {}", remote_addr, err);
+                }
+            });
+        }
+    }
+}
+
+async fn dispatch<H>(handler: &H, request: Request<Incoming>) -> Response<Full<Bytes>>
+where
+    H: HttpRequestHandler + Sync,
+{
+    let (parts, body) = request.into_parts();
+    let response = match Limited::new(body, MAX_BODY_SIZE).collect().await {
+        Ok(body) => {
+            let path_and_query = parts.uri.path_and_query().map_or_else(
+                || parts.uri.path(),
+                |path_and_query| path_and_query.as_str(),
+            );
+            handler
+                .handle(HttpRequest::new(
+                    parts.method,
+                    path_and_query,
+                    body.to_bytes(),
+                ))
+                .await
+        }
+        Err(err) => HttpResponse::error(StatusCode::PAYLOAD_TOO_LARGE, err.to_string()),
+    };
+    Response::builder()
+        .status(response.status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Full::new(response.body))
+        .expect("status and content type are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpStream;
+
+    use super::*;
+
+    struct EchoHandler;
+
+    impl HttpRequestHandler for EchoHandler {
+        async fn handle(&self, request: HttpRequest) -> HttpResponse {
+            HttpResponse::json(&json!({
+                "method": request.method().as_str(),
+                "segments": request.path_segments(),
+                "name": request.query("name"),
+                "body": String::from_utf8_lossy(request.body()),
+            }))
+        }
+    }
+
+    #[test]
+    fn parses_path_and_query() {
+        let request = HttpRequest::new(Method::GET, "/kv/ns%201/a+b?name=x%3Dy&flag", "");
+        assert_eq!(request.path(), "/kv/ns%201/a+b");
+        assert_eq!(request.path_segments(), vec!["kv", "ns 1", "a+b"]);
+        assert_eq!(request.query("name"), Some("x=y"));
+        assert_eq!(request.query("flag"), Some(""));
+        assert!(request.json::<serde_json::Value>().is_err());
+    }
+
+    #[tokio::test]
+    async fn serves_handler_over_http() {
+        let server = HttpServer::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap();
+        let serving = tokio::spawn(server.run(Arc::new(EchoHandler)));
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                b"PUT /kv/ns/key?name=n HTTP/1.1\r\nHost: localhost\r\nContent-Length: \
+                  5\r\nConnection: close\r\n\r\nvalue",
+            )
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        serving.abort();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("content-type: application/json"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["method"], "PUT");
+        assert_eq!(body["segments"], json!(["kv", "ns", "key"]));
+        assert_eq!(body["name"], "n");
+        assert_eq!(body["body"], "value");
+    }
+}
@@ -24,12 +24,15 @@ pub mod code;
 pub mod codec;
 pub mod connection;
 pub mod error;
+#[cfg(feature = "http")]
+pub mod http;
 pub mod net;
 pub mod protocol;
 
 use crate::error::Error;
 pub use crate::protocol::rocketmq_serializable;
 
+pub mod acl;
 pub mod base;
 pub mod remoting;
 pub mod remoting_server;
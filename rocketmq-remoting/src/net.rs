@@ -16,3 +16,5 @@
  */
 
 pub mod channel;
+pub mod tls;
+pub mod unix;
@@ -48,18 +48,30 @@ pub struct Channel {
     pub(crate) response_table: ArcRefCellWrapper<HashMap<i32, ResponseFuture>>,
 }
 
-type ChannelMessage = (
-    RemotingCommand,
-    Option<tokio::sync::oneshot::Sender<Result<RemotingCommand>>>,
-    Option<u64>,
-);
+pub(crate) enum ChannelMessage {
+    Command(
+        RemotingCommand,
+        Option<tokio::sync::oneshot::Sender<Result<RemotingCommand>>>,
+        Option<u64>,
+    ),
+    /// Closes the connection once every command queued before it has been written.
+    Close(tokio::sync::oneshot::Sender<Result<()>>),
+}
 
 pub(crate) async fn run_send(
     mut connection: ArcRefCellWrapper<Connection>,
     mut rx: Receiver<ChannelMessage>,
     mut response_table: ArcRefCellWrapper<HashMap<i32, ResponseFuture>>,
 ) {
-    while let Some((request, tx, timeout_millis)) = rx.recv().await {
+    while let Some(message) = rx.recv().await {
+        let (request, tx, timeout_millis) = match message {
+            ChannelMessage::Command(request, tx, timeout_millis) => (request, tx, timeout_millis),
+            ChannelMessage::Close(tx) => {
+                connection.ok = false;
+                let _ = tx.send(connection.writer.close().await);
+                return;
+            }
+        };
         let opaque = request.opaque();
         if let Some(tx) = tx {
             response_table.insert(
@@ -188,6 +200,27 @@ impl Channel {
         self.connection.mut_from_ref()
     }
 
+    /// Queues `command` on the connection's writer task without waiting for a response.
+    /// Writes from concurrent tasks are serialized by the writer task.
+    pub async fn write_and_flush(&self, command: RemotingCommand) -> Result<()> {
+        self.tx
+            .send(ChannelMessage::Command(command, None, None))
+            .await
+            .map_err(|err| ChannelSendRequestFailed(err.to_string()))
+    }
+
+    /// Writes every command queued so far, then closes the connection. Commands queued
+    /// afterwards are rejected.
+    pub async fn close(&self) -> Result<()> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(ChannelMessage::Close(tx))
+            .await
+            .map_err(|err| ChannelSendRequestFailed(err.to_string()))?;
+        rx.await
+            .map_err(|err| Error::ChannelRecvRequestFailed(err.to_string()))?
+    }
+
     pub async fn send_wait_response(
         &mut self,
         request: RemotingCommand,
@@ -197,7 +230,11 @@ impl Channel {
         let opaque = request.opaque();
         if let Err(err) = self
             .tx
-            .send((request, Some(tx), Some(timeout_millis)))
+            .send(ChannelMessage::Command(
+                request,
+                Some(tx),
+                Some(timeout_millis),
+            ))
             .await
         {
             return Err(ChannelSendRequestFailed(err.to_string()));
@@ -0,0 +1,484 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_common::common::server::config::TlsMode;
+use tokio::net::TcpStream;
+use tokio::time;
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::client::danger::HandshakeSignatureValid;
+use tokio_rustls::rustls::client::danger::ServerCertVerified;
+use tokio_rustls::rustls::client::danger::ServerCertVerifier;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::crypto::CryptoProvider;
+use tokio_rustls::rustls::pki_types::CertificateDer;
+use tokio_rustls::rustls::pki_types::PrivateKeyDer;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::pki_types::UnixTime;
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::DigitallySignedStruct;
+use tokio_rustls::rustls::RootCertStore;
+use tokio_rustls::rustls::SignatureScheme;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::TlsConnector;
+
+use crate::connection::ConnectionStream;
+use crate::error::Error::TlsError;
+use crate::runtime::config::client_config::TokioClientConfig;
+use crate::Result;
+
+/// First byte of a TLS handshake record, used to tell TLS from plaintext in permissive mode.
+const TLS_HANDSHAKE_RECORD_TYPE: u8 = 0x16;
+
+/// Upper bound of a TLS handshake, so a silent peer can not hold a connection slot forever.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Usual locations of the CA bundle of the platform, tried after `SSL_CERT_FILE`.
+const PLATFORM_CA_BUNDLES: &[&str] = &[
+    "/etc/ssl/certs/ca-certificates.crt",
+    "/etc/pki/tls/certs/ca-bundle.crt",
+    "/etc/ssl/ca-bundle.pem",
+    "/etc/ssl/cert.pem",
+];
+
+/// Server side TLS state built from a `ServerConfig`.
+#[derive(Clone)]
+pub struct TlsServerContext {
+    mode: TlsMode,
+    acceptor: TlsAcceptor,
+}
+
+impl TlsServerContext {
+    /// Builds the server TLS state, `None` when TLS is disabled.
+    pub fn from_config(config: &ServerConfig) -> Result<Option<TlsServerContext>> {
+        if config.tls_mode == TlsMode::Disabled {
+            return Ok(None);
+        }
+        let cert_path = config
+            .tls_server_cert_path
+            .as_deref()
+            .ok_or_else(|| TlsError("tlsServerCertPath is not set".to_string()))?;
+        let key_path = config
+            .tls_server_key_path
+            .as_deref()
+            .ok_or_else(|| TlsError("tlsServerKeyPath is not set".to_string()))?;
+        let provider = Arc::new(ring::default_provider());
+        let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| TlsError(e.to_string()))?;
+        let builder = if config.tls_server_need_client_auth {
+            let trust_cert_path =
+                config
+                    .tls_server_trust_cert_path
+                    .as_deref()
+                    .ok_or_else(|| {
+                        TlsError(
+                            "tlsServerTrustCertPath is required to authenticate clients"
+                                .to_string(),
+                        )
+                    })?;
+            let verifier = WebPkiClientVerifier::builder_with_provider(
+                Arc::new(load_root_store(trust_cert_path)?),
+                provider,
+            )
+            .build()
+            .map_err(|e| TlsError(e.to_string()))?;
+            builder.with_client_cert_verifier(verifier)
+        } else {
+            builder.with_no_client_auth()
+        };
+        let server_config = builder
+            .with_single_cert(load_certs(cert_path)?, load_private_key(key_path)?)
+            .map_err(|e| TlsError(e.to_string()))?;
+        Ok(Some(TlsServerContext {
+            mode: config.tls_mode,
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+        }))
+    }
+
+    pub fn mode(&self) -> TlsMode {
+        self.mode
+    }
+
+    /// Performs the server handshake on an accepted socket. In permissive mode a socket whose
+    /// first byte is not a TLS handshake record is kept as plaintext.
+    pub async fn accept(&self, socket: TcpStream) -> Result<ConnectionStream> {
+        if self.mode == TlsMode::Permissive {
+            let mut first_byte = [0u8; 1];
+            let read = socket.peek(&mut first_byte).await?;
+            if read == 0 || first_byte[0] != TLS_HANDSHAKE_RECORD_TYPE {
+                return Ok(ConnectionStream::Tcp(socket));
+            }
+        }
+        match time::timeout(TLS_HANDSHAKE_TIMEOUT, self.acceptor.accept(socket)).await {
+            Ok(Ok(stream)) => Ok(ConnectionStream::ServerTls(Box::new(stream))),
+            Ok(Err(e)) => Err(TlsError(format!("server handshake failed: {}", e))),
+            Err(_) => Err(TlsError("server handshake timed out".to_string())),
+        }
+    }
+}
+
+/// Client side TLS state built from a `TokioClientConfig`.
+#[derive(Clone)]
+pub struct TlsClientContext {
+    connector: TlsConnector,
+}
+
+impl TlsClientContext {
+    /// Builds the client TLS state, `None` when `use_tls` is off.
+    pub fn from_config(config: &TokioClientConfig) -> Result<Option<TlsClientContext>> {
+        if !config.use_tls {
+            return Ok(None);
+        }
+        let provider = Arc::new(ring::default_provider());
+        let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| TlsError(e.to_string()))?;
+        let builder = if config.tls_test_mode_enable {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(InsecureServerCertVerifier(provider)))
+        } else {
+            let trust_cert_path =
+                config
+                    .tls_client_trust_cert_path
+                    .as_deref()
+                    .ok_or_else(|| {
+                        TlsError(
+                            "tls.client.trustCertPath is required unless tls.test.mode.enable is \
+                             set"
+                            .to_string(),
+                        )
+                    })?;
+            builder.with_root_certificates(load_root_store(trust_cert_path)?)
+        };
+        let client_config = match (
+            config.tls_client_cert_path.as_deref(),
+            config.tls_client_key_path.as_deref(),
+        ) {
+            (Some(cert_path), Some(key_path)) => builder
+                .with_client_auth_cert(load_certs(cert_path)?, load_private_key(key_path)?)
+                .map_err(|e| TlsError(e.to_string()))?,
+            _ => builder.with_no_client_auth(),
+        };
+        Ok(Some(TlsClientContext {
+            connector: TlsConnector::from(Arc::new(client_config)),
+        }))
+    }
+
+    /// Client TLS state trusting the CA bundle of the platform and presenting no certificate.
+    /// Servers can not be verified, and so connections fail, when no bundle is found.
+    pub fn with_platform_roots() -> TlsClientContext {
+        let client_config =
+            rustls::ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .expect("ring supports the default protocol versions")
+                .with_root_certificates(load_platform_root_store())
+                .with_no_client_auth();
+        TlsClientContext {
+            connector: TlsConnector::from(Arc::new(client_config)),
+        }
+    }
+
+    /// Performs the client handshake, verifying the server against the host part of `addr`.
+    pub async fn connect(&self, addr: &str, socket: TcpStream) -> Result<ConnectionStream> {
+        let host = addr
+            .rsplit_once(':')
+            .map_or(addr, |(host, _)| host)
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let server_name = ServerName::try_from(host.to_string())
+            .map_err(|e| TlsError(format!("invalid server name {}: {}", host, e)))?;
+        match time::timeout(
+            TLS_HANDSHAKE_TIMEOUT,
+            self.connector.connect(server_name, socket),
+        )
+        .await
+        {
+            Ok(Ok(stream)) => Ok(ConnectionStream::ClientTls(Box::new(stream))),
+            Ok(Err(e)) => Err(TlsError(format!("client handshake failed: {}", e))),
+            Err(_) => Err(TlsError("client handshake timed out".to_string())),
+        }
+    }
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<std::io::Result<Vec<_>>>()?;
+    if certs.is_empty() {
+        return Err(TlsError(format!("no certificate found in {}", path)));
+    }
+    Ok(certs)
+}
+
+fn load_private_key(path: &str) -> Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| TlsError(format!("no private key found in {}", path)))
+}
+
+fn load_root_store(path: &str) -> Result<RootCertStore> {
+    let mut root_store = RootCertStore::empty();
+    for cert in load_certs(path)? {
+        root_store
+            .add(cert)
+            .map_err(|e| TlsError(format!("invalid CA certificate in {}: {}", path, e)))?;
+    }
+    Ok(root_store)
+}
+
+fn load_platform_root_store() -> RootCertStore {
+    let mut root_store = RootCertStore::empty();
+    let bundles = std::env::var("SSL_CERT_FILE")
+        .into_iter()
+        .chain(PLATFORM_CA_BUNDLES.iter().map(|path| path.to_string()));
+    for path in bundles {
+        if let Ok(certs) = load_certs(&path) {
+            root_store.add_parsable_certificates(certs);
+            if !root_store.is_empty() {
+                break;
+            }
+        }
+    }
+    root_store
+}
+
+/// Accepts any server certificate while still checking handshake signatures.
+#[derive(Debug)]
+struct InsecureServerCertVerifier(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for InsecureServerCertVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::SinkExt;
+    use futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::connection::Connection;
+    use crate::protocol::remoting_command::RemotingCommand;
+
+    fn cert_path(name: &str) -> Option<String> {
+        Some(format!(
+            "{}/testdata/tls/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        ))
+    }
+
+    fn server_context(mode: TlsMode, need_client_auth: bool) -> TlsServerContext {
+        let config = ServerConfig {
+            tls_mode: mode,
+            tls_server_cert_path: cert_path("server.pem"),
+            tls_server_key_path: cert_path("server.key"),
+            tls_server_trust_cert_path: cert_path("ca.pem"),
+            tls_server_need_client_auth: need_client_auth,
+            ..Default::default()
+        };
+        TlsServerContext::from_config(&config).unwrap().unwrap()
+    }
+
+    fn client_context(with_client_cert: bool) -> TlsClientContext {
+        let mut config = TokioClientConfig {
+            use_tls: true,
+            tls_test_mode_enable: false,
+            tls_client_trust_cert_path: cert_path("ca.pem"),
+            tls_client_cert_path: None,
+            tls_client_key_path: None,
+            ..Default::default()
+        };
+        if with_client_cert {
+            config.tls_client_cert_path = cert_path("client.pem");
+            config.tls_client_key_path = cert_path("client.key");
+        }
+        TlsClientContext::from_config(&config).unwrap().unwrap()
+    }
+
+    /// Accepts one connection, echoes back the first command received on it and reports
+    /// whether the connection was TLS.
+    async fn spawn_echo_server(
+        tls_context: TlsServerContext,
+    ) -> (String, tokio::task::JoinHandle<Result<bool>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let handle = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await?;
+            let stream = tls_context.accept(socket).await?;
+            let is_tls = stream.is_tls();
+            let mut connection = Connection::with_stream(stream);
+            if let Some(Ok(command)) = connection.reader.next().await {
+                connection.writer.send(command).await?;
+            }
+            Ok(is_tls)
+        });
+        (addr, handle)
+    }
+
+    async fn round_trip(stream: ConnectionStream) {
+        let mut connection = Connection::with_stream(stream);
+        connection
+            .writer
+            .send(RemotingCommand::create_remoting_command(105))
+            .await
+            .unwrap();
+        let response = connection.reader.next().await.unwrap().unwrap();
+        assert_eq!(response.code(), 105);
+    }
+
+    #[tokio::test]
+    async fn enforcing_server_with_mutual_auth() {
+        let (addr, server) = spawn_echo_server(server_context(TlsMode::Enforcing, true)).await;
+        let socket = TcpStream::connect(addr.as_str()).await.unwrap();
+        let stream = client_context(true)
+            .connect(addr.as_str(), socket)
+            .await
+            .unwrap();
+        assert!(stream.is_tls());
+        round_trip(stream).await;
+        assert!(server.await.unwrap().unwrap());
+    }
+
+    #[tokio::test]
+    async fn mutual_auth_rejects_client_without_certificate() {
+        let (addr, server) = spawn_echo_server(server_context(TlsMode::Enforcing, true)).await;
+        let socket = TcpStream::connect(addr.as_str()).await.unwrap();
+        // With TLS 1.3 the client may finish its side before the server rejects it
+        let _ = client_context(false).connect(addr.as_str(), socket).await;
+        assert!(matches!(server.await.unwrap(), Err(TlsError(_))));
+    }
+
+    #[tokio::test]
+    async fn enforcing_server_rejects_plaintext() {
+        let (addr, server) = spawn_echo_server(server_context(TlsMode::Enforcing, false)).await;
+        let mut socket = TcpStream::connect(addr.as_str()).await.unwrap();
+        socket.write_all(&[0, 0, 0, 8, 0, 0, 0, 0]).await.unwrap();
+        assert!(matches!(server.await.unwrap(), Err(TlsError(_))));
+    }
+
+    #[tokio::test]
+    async fn permissive_server_accepts_plaintext_and_tls() {
+        let tls_context = server_context(TlsMode::Permissive, false);
+
+        let (addr, server) = spawn_echo_server(tls_context.clone()).await;
+        round_trip(TcpStream::connect(addr.as_str()).await.unwrap().into()).await;
+        assert!(!server.await.unwrap().unwrap());
+
+        let (addr, server) = spawn_echo_server(tls_context).await;
+        let socket = TcpStream::connect(addr.as_str()).await.unwrap();
+        round_trip(
+            client_context(false)
+                .connect(addr.as_str(), socket)
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert!(server.await.unwrap().unwrap());
+    }
+
+    #[test]
+    fn context_from_config() {
+        assert!(TlsServerContext::from_config(&ServerConfig::default())
+            .unwrap()
+            .is_none());
+        let config = ServerConfig {
+            tls_mode: TlsMode::Enforcing,
+            ..Default::default()
+        };
+        assert!(TlsServerContext::from_config(&config).is_err());
+
+        let config = TokioClientConfig {
+            use_tls: false,
+            ..Default::default()
+        };
+        assert!(TlsClientContext::from_config(&config).unwrap().is_none());
+        let config = TokioClientConfig {
+            use_tls: true,
+            tls_test_mode_enable: false,
+            tls_client_trust_cert_path: None,
+            ..Default::default()
+        };
+        assert!(TlsClientContext::from_config(&config).is_err());
+        let config = TokioClientConfig {
+            use_tls: true,
+            tls_test_mode_enable: true,
+            ..Default::default()
+        };
+        assert!(TlsClientContext::from_config(&config).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn platform_roots_do_not_trust_private_ca() {
+        let (addr, server) = spawn_echo_server(server_context(TlsMode::Enforcing, false)).await;
+        let socket = TcpStream::connect(addr.as_str()).await.unwrap();
+        assert!(matches!(
+            TlsClientContext::with_platform_roots()
+                .connect(addr.as_str(), socket)
+                .await,
+            Err(TlsError(_))
+        ));
+        assert!(matches!(server.await.unwrap(), Err(TlsError(_))));
+    }
+}
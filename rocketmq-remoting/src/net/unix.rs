@@ -0,0 +1,159 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Unix domain socket transport, for clients co-located with a broker or name server.
+//!
+//! A Unix socket is addressed as `unix://` followed by the socket path, e.g.
+//! `unix:///var/run/rocketmq/broker.sock`, wherever a `host:port` address is accepted.
+
+use std::io;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::net::SocketAddrV4;
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+#[cfg(unix)]
+use tokio::net::UnixListener;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+
+use crate::connection::ConnectionStream;
+
+pub const UNIX_ADDRESS_PREFIX: &str = "unix://";
+
+/// The address reported for the local end of a channel over a Unix socket, which has no IP
+/// address.
+pub const UNIX_CHANNEL_ADDRESS: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0));
+
+static NEXT_UNIX_PEER: AtomicU64 = AtomicU64::new(1);
+
+/// A synthetic address for the remote end of a new channel over a Unix socket.
+///
+/// Peers are keyed by their remote address, e.g. by the name server on disconnect, so every
+/// channel gets its own address in `127.0.0.0/8`: a counter split into the low 24 bits of the
+/// IP and the port.
+pub fn next_unix_channel_address() -> SocketAddr {
+    let peer = NEXT_UNIX_PEER.fetch_add(1, Ordering::Relaxed);
+    let ip = Ipv4Addr::from(0x7f00_0000 | ((peer >> 16) as u32 & 0x00ff_ffff));
+    SocketAddr::V4(SocketAddrV4::new(ip, peer as u16))
+}
+
+/// Returns the socket path of a `unix://` address, `None` for any other address.
+pub fn unix_socket_path(addr: &str) -> Option<&str> {
+    addr.strip_prefix(UNIX_ADDRESS_PREFIX)
+        .filter(|path| !path.is_empty())
+}
+
+/// Binds a listener on `path`, replacing the socket file a previous process left behind.
+#[cfg(unix)]
+pub fn bind(path: &str) -> io::Result<UnixListener> {
+    remove_stale_socket(Path::new(path))?;
+    UnixListener::bind(path)
+}
+
+#[cfg(unix)]
+pub async fn connect(path: &str) -> io::Result<ConnectionStream> {
+    UnixStream::connect(path).await.map(ConnectionStream::Unix)
+}
+
+#[cfg(not(unix))]
+pub async fn connect(path: &str) -> io::Result<ConnectionStream> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "cannot connect to {}, unix domain sockets are not supported on this platform",
+            path
+        ),
+    ))
+}
+
+/// Removes the socket file at `path`, refusing to remove anything that is not a socket.
+#[cfg(unix)]
+pub fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a unix socket", path.display()),
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_unix_addresses_only() {
+        assert_eq!(
+            unix_socket_path("unix:///tmp/broker.sock"),
+            Some("/tmp/broker.sock")
+        );
+        assert_eq!(unix_socket_path("unix://"), None);
+        assert_eq!(unix_socket_path("127.0.0.1:10911"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn accepted_peers_get_distinct_addresses() {
+        use crate::remoting_server::server_listener::ServerListener;
+
+        let dir = std::env::temp_dir().join(format!("rocketmq-unix-peers-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket_path = dir.join("peers.sock").to_string_lossy().to_string();
+        let listener = ServerListener::bind_unix(&socket_path).unwrap();
+        let mut remote_addrs = Vec::new();
+        for _ in 0..2 {
+            let (client, accepted) = tokio::join!(connect(&socket_path), listener.accept());
+            client.unwrap();
+            let accepted = accepted.unwrap();
+            assert_eq!(accepted.local_addr, UNIX_CHANNEL_ADDRESS);
+            remote_addrs.push(accepted.remote_addr);
+        }
+        assert_ne!(remote_addrs[0], remote_addrs[1]);
+        assert!(remote_addrs.iter().all(|addr| addr.ip().is_loopback()));
+        drop(listener);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bind_replaces_stale_socket_but_not_other_files() {
+        let dir = std::env::temp_dir().join(format!("rocketmq-unix-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("stale.sock");
+        let socket_path = socket.to_str().unwrap();
+        drop(bind(socket_path).unwrap());
+        assert!(socket.exists());
+        let listener = bind(socket_path).unwrap();
+        let (client, server) = tokio::join!(connect(socket_path), listener.accept());
+        assert!(client.is_ok() && server.is_ok());
+
+        let file = dir.join("regular");
+        std::fs::write(&file, b"data").unwrap();
+        assert!(bind(file.to_str().unwrap()).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
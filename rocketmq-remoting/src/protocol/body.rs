@@ -16,8 +16,10 @@
  */
 
 pub mod broker_body;
+pub mod broker_replicas_info;
 pub mod consumer_running_info;
 pub mod create_topic_list_request_body;
+pub mod elect_master_response_body;
 pub mod get_consumer_listby_group_response_body;
 
 pub mod consumer_connection;
@@ -26,9 +28,16 @@ pub mod check_client_request_body;
 pub mod cm_result;
 pub mod connection;
 pub mod consume_message_directly_result;
+pub mod group_forbidden;
 pub mod group_list;
 pub mod kv_table;
 pub mod pop_process_queue_info;
 pub mod process_queue_info;
+pub mod query_consume_time_span_body;
+pub mod query_correction_offset_body;
+pub mod queue_time_span;
+pub mod reset_offset_body;
+pub mod subscription_group_wrapper;
+pub mod sync_state_set;
 pub mod topic;
 pub mod topic_info_wrapper;
@@ -0,0 +1,47 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Replica metadata of broker sets, answered to `ControllerGetSyncStateData`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerReplicasInfo {
+    pub replicas_info_table: HashMap<String /* broker name */, ReplicasInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicasInfo {
+    pub master_broker_id: Option<i64>,
+    pub master_address: Option<String>,
+    pub master_epoch: i32,
+    pub sync_state_set_epoch: i32,
+    pub in_sync_replicas: Vec<ReplicaIdentity>,
+    pub not_in_sync_replicas: Vec<ReplicaIdentity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicaIdentity {
+    pub broker_name: String,
+    pub broker_id: i64,
+    pub broker_address: String,
+    pub alive: bool,
+}
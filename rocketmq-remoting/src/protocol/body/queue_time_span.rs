@@ -0,0 +1,34 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Store times of the messages of one queue, as seen by a consumer group.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueTimeSpan {
+    pub message_queue: MessageQueue,
+    /// Store time of the earliest message, -1 if the queue is empty.
+    pub min_time_stamp: i64,
+    /// Store time of the latest message, -1 if the queue is empty.
+    pub max_time_stamp: i64,
+    /// Store time of the last consumed message.
+    pub consume_time_stamp: i64,
+    /// Milliseconds since the first unconsumed message was stored, 0 when caught up.
+    pub delay_time: i64,
+}
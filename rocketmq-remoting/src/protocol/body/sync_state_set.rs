@@ -0,0 +1,37 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The replicas of a broker set that are in sync with its master, master included.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStateSet {
+    pub sync_state_set: HashSet<i64>,
+    pub sync_state_set_epoch: i32,
+}
+
+impl SyncStateSet {
+    pub fn new(sync_state_set: HashSet<i64>, sync_state_set_epoch: i32) -> Self {
+        Self {
+            sync_state_set,
+            sync_state_set_epoch,
+        }
+    }
+}
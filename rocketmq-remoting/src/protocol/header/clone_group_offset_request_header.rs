@@ -0,0 +1,83 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::command_custom_header::CommandCustomHeader;
+use crate::protocol::command_custom_header::FromMap;
+use crate::rpc::rpc_request_header::RpcRequestHeader;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneGroupOffsetRequestHeader {
+    pub src_group: String,
+
+    pub dest_group: String,
+
+    /// Clones every topic of `src_group` when absent.
+    pub topic: Option<String>,
+
+    /// Also clones topics the online consumers of `src_group` no longer subscribe to.
+    pub offline: bool,
+
+    #[serde(flatten)]
+    pub rpc_request_header: Option<RpcRequestHeader>,
+}
+
+impl CloneGroupOffsetRequestHeader {
+    pub const SRC_GROUP: &'static str = "srcGroup";
+    pub const DEST_GROUP: &'static str = "destGroup";
+    pub const TOPIC: &'static str = "topic";
+    pub const OFFLINE: &'static str = "offline";
+}
+
+impl CommandCustomHeader for CloneGroupOffsetRequestHeader {
+    fn to_map(&self) -> Option<HashMap<String, String>> {
+        let mut map = HashMap::new();
+        map.insert(Self::SRC_GROUP.to_string(), self.src_group.clone());
+        map.insert(Self::DEST_GROUP.to_string(), self.dest_group.clone());
+        if let Some(ref topic) = self.topic {
+            map.insert(Self::TOPIC.to_string(), topic.clone());
+        }
+        map.insert(Self::OFFLINE.to_string(), self.offline.to_string());
+        if let Some(value) = self.rpc_request_header.as_ref() {
+            if let Some(value) = value.to_map() {
+                map.extend(value);
+            }
+        }
+        Some(map)
+    }
+}
+
+impl FromMap for CloneGroupOffsetRequestHeader {
+    type Target = Self;
+
+    fn from(map: &HashMap<String, String>) -> Option<Self::Target> {
+        Some(CloneGroupOffsetRequestHeader {
+            src_group: map.get(Self::SRC_GROUP).cloned().unwrap_or_default(),
+            dest_group: map.get(Self::DEST_GROUP).cloned().unwrap_or_default(),
+            topic: map.get(Self::TOPIC).cloned(),
+            offline: map
+                .get(Self::OFFLINE)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            rpc_request_header: <RpcRequestHeader as FromMap>::from(map),
+        })
+    }
+}
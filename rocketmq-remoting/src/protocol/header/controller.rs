@@ -0,0 +1,25 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+pub mod alter_sync_state_set_request_header;
+pub mod apply_broker_id_request_header;
+pub mod clean_controller_broker_data_request_header;
+pub mod elect_master_request_header;
+pub mod get_meta_data_response_header;
+pub mod get_next_broker_id_request_header;
+pub mod get_replica_info_request_header;
+pub mod notify_broker_role_changed_request_header;
+pub mod register_broker_to_controller_request_header;
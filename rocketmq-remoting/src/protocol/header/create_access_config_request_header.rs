@@ -0,0 +1,102 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::acl::plain_access_config::PlainAccessConfig;
+
+/// Creates or replaces an ACL account. Topic and group permissions are comma separated
+/// `resource=PERM` entries.
+#[derive(Debug, Clone, Serialize, Deserialize, RequestHeaderCodec, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAccessConfigRequestHeader {
+    pub access_key: String,
+    pub secret_key: String,
+    pub white_remote_address: Option<String>,
+    pub default_topic_perm: Option<String>,
+    pub default_group_perm: Option<String>,
+    pub admin: bool,
+    pub topic_perms: Option<String>,
+    pub group_perms: Option<String>,
+}
+
+impl From<&PlainAccessConfig> for CreateAccessConfigRequestHeader {
+    fn from(config: &PlainAccessConfig) -> Self {
+        Self {
+            access_key: config.access_key.clone(),
+            secret_key: config.secret_key.clone(),
+            white_remote_address: config.white_remote_address.clone(),
+            default_topic_perm: config.default_topic_perm.clone(),
+            default_group_perm: config.default_group_perm.clone(),
+            admin: config.admin,
+            topic_perms: Some(config.topic_perms.join(",")),
+            group_perms: Some(config.group_perms.join(",")),
+        }
+    }
+}
+
+impl From<CreateAccessConfigRequestHeader> for PlainAccessConfig {
+    fn from(header: CreateAccessConfigRequestHeader) -> Self {
+        let split = |perms: Option<String>| -> Vec<String> {
+            perms
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|perm| !perm.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        Self {
+            access_key: header.access_key,
+            secret_key: header.secret_key,
+            white_remote_address: header.white_remote_address,
+            admin: header.admin,
+            default_topic_perm: header.default_topic_perm,
+            default_group_perm: header.default_group_perm,
+            topic_perms: split(header.topic_perms),
+            group_perms: split(header.group_perms),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::command_custom_header::CommandCustomHeader;
+    use crate::protocol::command_custom_header::FromMap;
+
+    #[test]
+    fn round_trips_access_config() {
+        let config = PlainAccessConfig {
+            access_key: "teamA-app".to_string(),
+            secret_key: "teamA-secret".to_string(),
+            white_remote_address: Some("192.168.0.*".to_string()),
+            admin: false,
+            default_topic_perm: Some("DENY".to_string()),
+            default_group_perm: Some("SUB".to_string()),
+            topic_perms: vec!["topicA=PUB|SUB".to_string(), "topicB=SUB".to_string()],
+            group_perms: vec![],
+        };
+        let header: CreateAccessConfigRequestHeader = (&config).into();
+        let map = header.to_map().unwrap();
+        assert_eq!(map["accessKey"], "teamA-app");
+        assert_eq!(map["topicPerms"], "topicA=PUB|SUB,topicB=SUB");
+        let header = <CreateAccessConfigRequestHeader as FromMap>::from(&map).unwrap();
+        assert_eq!(PlainAccessConfig::from(header), config);
+    }
+}
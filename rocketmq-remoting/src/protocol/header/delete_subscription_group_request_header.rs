@@ -0,0 +1,73 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::command_custom_header::CommandCustomHeader;
+use crate::protocol::command_custom_header::FromMap;
+use crate::rpc::rpc_request_header::RpcRequestHeader;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteSubscriptionGroupRequestHeader {
+    pub group_name: String,
+
+    /// Also removes the consumer offsets of the group.
+    pub clean_offset: bool,
+
+    #[serde(flatten)]
+    pub rpc_request_header: Option<RpcRequestHeader>,
+}
+
+impl DeleteSubscriptionGroupRequestHeader {
+    pub const GROUP_NAME: &'static str = "groupName";
+    pub const CLEAN_OFFSET: &'static str = "cleanOffset";
+}
+
+impl CommandCustomHeader for DeleteSubscriptionGroupRequestHeader {
+    fn to_map(&self) -> Option<HashMap<String, String>> {
+        let mut map = HashMap::new();
+        map.insert(Self::GROUP_NAME.to_string(), self.group_name.clone());
+        map.insert(
+            Self::CLEAN_OFFSET.to_string(),
+            self.clean_offset.to_string(),
+        );
+        if let Some(value) = self.rpc_request_header.as_ref() {
+            if let Some(value) = value.to_map() {
+                map.extend(value);
+            }
+        }
+        Some(map)
+    }
+}
+
+impl FromMap for DeleteSubscriptionGroupRequestHeader {
+    type Target = Self;
+
+    fn from(map: &HashMap<String, String>) -> Option<Self::Target> {
+        Some(DeleteSubscriptionGroupRequestHeader {
+            group_name: map.get(Self::GROUP_NAME).cloned().unwrap_or_default(),
+            clean_offset: map
+                .get(Self::CLEAN_OFFSET)
+                .and_then(|value| value.parse::<bool>().ok())
+                .unwrap_or_default(),
+            rpc_request_header: <RpcRequestHeader as FromMap>::from(map),
+        })
+    }
+}
@@ -0,0 +1,74 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::command_custom_header::CommandCustomHeader;
+use crate::protocol::command_custom_header::FromMap;
+use crate::rpc::rpc_request_header::RpcRequestHeader;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCorrectionOffsetHeader {
+    /// Comma separated groups left out of the minimum offset.
+    pub filter_groups: Option<String>,
+
+    pub compare_group: String,
+
+    pub topic: String,
+
+    #[serde(flatten)]
+    pub rpc_request_header: Option<RpcRequestHeader>,
+}
+
+impl QueryCorrectionOffsetHeader {
+    pub const FILTER_GROUPS: &'static str = "filterGroups";
+    pub const COMPARE_GROUP: &'static str = "compareGroup";
+    pub const TOPIC: &'static str = "topic";
+}
+
+impl CommandCustomHeader for QueryCorrectionOffsetHeader {
+    fn to_map(&self) -> Option<HashMap<String, String>> {
+        let mut map = HashMap::new();
+        if let Some(ref filter_groups) = self.filter_groups {
+            map.insert(Self::FILTER_GROUPS.to_string(), filter_groups.clone());
+        }
+        map.insert(Self::COMPARE_GROUP.to_string(), self.compare_group.clone());
+        map.insert(Self::TOPIC.to_string(), self.topic.clone());
+        if let Some(value) = self.rpc_request_header.as_ref() {
+            if let Some(value) = value.to_map() {
+                map.extend(value);
+            }
+        }
+        Some(map)
+    }
+}
+
+impl FromMap for QueryCorrectionOffsetHeader {
+    type Target = Self;
+
+    fn from(map: &HashMap<String, String>) -> Option<Self::Target> {
+        Some(QueryCorrectionOffsetHeader {
+            filter_groups: map.get(Self::FILTER_GROUPS).cloned(),
+            compare_group: map.get(Self::COMPARE_GROUP).cloned().unwrap_or_default(),
+            topic: map.get(Self::TOPIC).cloned().unwrap_or_default(),
+            rpc_request_header: <RpcRequestHeader as FromMap>::from(map),
+        })
+    }
+}
@@ -34,6 +34,9 @@ pub struct QueryMessageRequestHeader {
 
     pub end_timestamp: i64,
 
+    /// Token returned by the previous page, absent for the first page.
+    pub continuation_token: Option<String>,
+
     #[serde(flatten)]
     pub topic_request_header: Option<TopicRequestHeader>,
 }
@@ -44,6 +47,7 @@ impl QueryMessageRequestHeader {
     pub const MAX_NUM: &'static str = "maxNum";
     pub const BEGIN_TIMESTAMP: &'static str = "beginTimestamp";
     pub const END_TIMESTAMP: &'static str = "endTimestamp";
+    pub const CONTINUATION_TOKEN: &'static str = "continuationToken";
 }
 
 /*impl From<&QueryMessageRequestHeader> for String {
@@ -66,6 +70,9 @@ impl CommandCustomHeader for QueryMessageRequestHeader {
             Self::END_TIMESTAMP.to_string(),
             self.end_timestamp.to_string(),
         );
+        if let Some(value) = self.continuation_token.as_ref() {
+            map.insert(Self::CONTINUATION_TOKEN.to_string(), value.clone());
+        }
         if let Some(value) = self.topic_request_header.as_ref() {
             if let Some(val) = value.to_map() {
                 map.extend(val);
@@ -85,6 +92,7 @@ impl FromMap for QueryMessageRequestHeader {
             max_num: map.get(Self::MAX_NUM)?.parse().ok()?,
             begin_timestamp: map.get(Self::BEGIN_TIMESTAMP)?.parse().ok()?,
             end_timestamp: map.get(Self::END_TIMESTAMP)?.parse().ok()?,
+            continuation_token: map.get(Self::CONTINUATION_TOKEN).cloned(),
             topic_request_header: <TopicRequestHeader as FromMap>::from(map),
         })
     }
@@ -150,6 +158,7 @@ mod query_message_request_header_tests {
             max_num: 10,
             begin_timestamp: 1000,
             end_timestamp: 2000,
+            continuation_token: Some("idx-10".to_string()),
             topic_request_header: None,
         };
 
@@ -160,6 +169,9 @@ mod query_message_request_header_tests {
         assert_eq!(map.get("maxNum").unwrap(), "10");
         assert_eq!(map.get("beginTimestamp").unwrap(), "1000");
         assert_eq!(map.get("endTimestamp").unwrap(), "2000");
+        assert_eq!(map.get("continuationToken").unwrap(), "idx-10");
+        let header = <QueryMessageRequestHeader as FromMap>::from(&map).unwrap();
+        assert_eq!(header.continuation_token.as_deref(), Some("idx-10"));
     }
 
     #[test]
@@ -171,6 +183,7 @@ mod query_message_request_header_tests {
             max_num: 10,
             begin_timestamp: 1000,
             end_timestamp: 2000,
+            continuation_token: None,
             topic_request_header: Some(topic_request_header),
         };
 
@@ -23,4 +23,6 @@ use serde::Serialize;
 pub struct QueryMessageResponseHeader {
     pub index_last_update_timestamp: i64,
     pub index_last_update_phyoffset: i64,
+    /// Token of the next page, absent when the query is exhausted.
+    pub continuation_token: Option<String>,
 }
@@ -0,0 +1,117 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::command_custom_header::CommandCustomHeader;
+use crate::protocol::command_custom_header::FromMap;
+use crate::rpc::topic_request_header::TopicRequestHeader;
+
+/// Used by `ResetConsumerOffsetInBroker`, `InvokeBrokerToResetOffset` and the
+/// `ResetConsumerClientOffset` notification sent to consumers.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetOffsetRequestHeader {
+    pub topic: String,
+
+    pub group: String,
+
+    /// Resets every queue of the topic when negative.
+    pub queue_id: i32,
+
+    /// Target offset of `queue_id`, takes precedence over `timestamp`.
+    pub offset: Option<i64>,
+
+    /// Store timestamp to seek to, -1 seeks to the end of the queues.
+    pub timestamp: i64,
+
+    /// Also moves offsets forward, otherwise offsets are only rewound.
+    pub is_force: bool,
+
+    #[serde(flatten)]
+    pub topic_request_header: Option<TopicRequestHeader>,
+}
+
+impl Default for ResetOffsetRequestHeader {
+    fn default() -> Self {
+        ResetOffsetRequestHeader {
+            topic: Default::default(),
+            group: Default::default(),
+            queue_id: -1,
+            offset: None,
+            timestamp: Default::default(),
+            is_force: Default::default(),
+            topic_request_header: None,
+        }
+    }
+}
+
+impl ResetOffsetRequestHeader {
+    pub const TOPIC: &'static str = "topic";
+    pub const GROUP: &'static str = "group";
+    pub const QUEUE_ID: &'static str = "queueId";
+    pub const OFFSET: &'static str = "offset";
+    pub const TIMESTAMP: &'static str = "timestamp";
+    pub const IS_FORCE: &'static str = "isForce";
+}
+
+impl CommandCustomHeader for ResetOffsetRequestHeader {
+    fn to_map(&self) -> Option<HashMap<String, String>> {
+        let mut map = HashMap::new();
+        map.insert(Self::TOPIC.to_string(), self.topic.clone());
+        map.insert(Self::GROUP.to_string(), self.group.clone());
+        map.insert(Self::QUEUE_ID.to_string(), self.queue_id.to_string());
+        if let Some(offset) = self.offset {
+            map.insert(Self::OFFSET.to_string(), offset.to_string());
+        }
+        map.insert(Self::TIMESTAMP.to_string(), self.timestamp.to_string());
+        map.insert(Self::IS_FORCE.to_string(), self.is_force.to_string());
+        if let Some(value) = self.topic_request_header.as_ref() {
+            if let Some(value) = value.to_map() {
+                map.extend(value);
+            }
+        }
+        Some(map)
+    }
+}
+
+impl FromMap for ResetOffsetRequestHeader {
+    type Target = Self;
+
+    fn from(map: &HashMap<String, String>) -> Option<Self::Target> {
+        Some(ResetOffsetRequestHeader {
+            topic: map.get(Self::TOPIC).cloned().unwrap_or_default(),
+            group: map.get(Self::GROUP).cloned().unwrap_or_default(),
+            queue_id: map
+                .get(Self::QUEUE_ID)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(-1),
+            offset: map.get(Self::OFFSET).and_then(|value| value.parse().ok()),
+            timestamp: map
+                .get(Self::TIMESTAMP)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            is_force: map
+                .get(Self::IS_FORCE)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            topic_request_header: <TopicRequestHeader as FromMap>::from(map),
+        })
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use rocketmq_common::common::boundary_type::BoundaryType;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::command_custom_header::CommandCustomHeader;
+use crate::protocol::command_custom_header::FromMap;
+use crate::rpc::topic_request_header::TopicRequestHeader;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchOffsetRequestHeader {
+    pub topic: String,
+
+    pub queue_id: i32,
+
+    pub timestamp: i64,
+
+    pub boundary_type: BoundaryType,
+
+    #[serde(flatten)]
+    pub topic_request_header: Option<TopicRequestHeader>,
+}
+
+impl SearchOffsetRequestHeader {
+    pub const TOPIC: &'static str = "topic";
+    pub const QUEUE_ID: &'static str = "queueId";
+    pub const TIMESTAMP: &'static str = "timestamp";
+    pub const BOUNDARY_TYPE: &'static str = "boundaryType";
+}
+
+impl CommandCustomHeader for SearchOffsetRequestHeader {
+    fn to_map(&self) -> Option<HashMap<String, String>> {
+        let mut map = HashMap::new();
+        map.insert(Self::TOPIC.to_string(), self.topic.clone());
+        map.insert(Self::QUEUE_ID.to_string(), self.queue_id.to_string());
+        map.insert(Self::TIMESTAMP.to_string(), self.timestamp.to_string());
+        map.insert(
+            Self::BOUNDARY_TYPE.to_string(),
+            self.boundary_type.get_name().to_uppercase(),
+        );
+        if let Some(value) = self.topic_request_header.as_ref() {
+            if let Some(value) = value.to_map() {
+                map.extend(value);
+            }
+        }
+        Some(map)
+    }
+}
+
+impl FromMap for SearchOffsetRequestHeader {
+    type Target = Self;
+
+    fn from(map: &HashMap<String, String>) -> Option<Self::Target> {
+        Some(SearchOffsetRequestHeader {
+            topic: map.get(Self::TOPIC).cloned().unwrap_or_default(),
+            queue_id: map
+                .get(Self::QUEUE_ID)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            timestamp: map
+                .get(Self::TIMESTAMP)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            boundary_type: map
+                .get(Self::BOUNDARY_TYPE)
+                .and_then(|value| BoundaryType::get_type(value))
+                .unwrap_or_default(),
+            topic_request_header: <TopicRequestHeader as FromMap>::from(map),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_offset_request_header_round_trip() {
+        let header = SearchOffsetRequestHeader {
+            topic: "TopicTest".to_string(),
+            queue_id: 3,
+            timestamp: 1_700_000_000_000,
+            boundary_type: BoundaryType::Upper,
+            topic_request_header: None,
+        };
+        let map = header.to_map().unwrap();
+        assert_eq!(map.get("boundaryType").unwrap(), "UPPER");
+
+        let decoded = <SearchOffsetRequestHeader as FromMap>::from(&map).unwrap();
+        assert_eq!(decoded.topic, "TopicTest");
+        assert_eq!(decoded.queue_id, 3);
+        assert_eq!(decoded.timestamp, 1_700_000_000_000);
+        assert_eq!(decoded.boundary_type, BoundaryType::Upper);
+    }
+}
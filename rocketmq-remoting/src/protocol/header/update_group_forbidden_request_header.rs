@@ -0,0 +1,75 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::command_custom_header::CommandCustomHeader;
+use crate::protocol::command_custom_header::FromMap;
+use crate::rpc::topic_request_header::TopicRequestHeader;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateGroupForbiddenRequestHeader {
+    pub group: String,
+    pub topic: String,
+    /// Forbids (`false`) or allows (`true`) the group to read the topic, left unchanged when
+    /// absent so the request only reads the current state.
+    pub readable: Option<bool>,
+
+    #[serde(flatten)]
+    pub topic_request_header: Option<TopicRequestHeader>,
+}
+
+impl UpdateGroupForbiddenRequestHeader {
+    pub const GROUP: &'static str = "group";
+    pub const TOPIC: &'static str = "topic";
+    pub const READABLE: &'static str = "readable";
+}
+
+impl CommandCustomHeader for UpdateGroupForbiddenRequestHeader {
+    fn to_map(&self) -> Option<HashMap<String, String>> {
+        let mut map = HashMap::new();
+        map.insert(Self::GROUP.to_string(), self.group.clone());
+        map.insert(Self::TOPIC.to_string(), self.topic.clone());
+        if let Some(readable) = self.readable {
+            map.insert(Self::READABLE.to_string(), readable.to_string());
+        }
+        if let Some(value) = self.topic_request_header.as_ref() {
+            if let Some(value) = value.to_map() {
+                map.extend(value);
+            }
+        }
+        Some(map)
+    }
+}
+
+impl FromMap for UpdateGroupForbiddenRequestHeader {
+    type Target = Self;
+
+    fn from(map: &HashMap<String, String>) -> Option<Self::Target> {
+        Some(UpdateGroupForbiddenRequestHeader {
+            group: map.get(Self::GROUP).cloned().unwrap_or_default(),
+            topic: map.get(Self::TOPIC).cloned().unwrap_or_default(),
+            readable: map
+                .get(Self::READABLE)
+                .and_then(|value| value.parse::<bool>().ok()),
+            topic_request_header: <TopicRequestHeader as FromMap>::from(map),
+        })
+    }
+}
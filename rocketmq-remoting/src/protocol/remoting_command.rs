@@ -29,6 +29,8 @@ use bytes::BufMut;
 use bytes::Bytes;
 use bytes::BytesMut;
 use lazy_static::lazy_static;
+use rocketmq_common::common::compression::compression_type::CompressionType;
+use rocketmq_common::common::compression::compressor_factory::CompressorFactory;
 use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
 use rocketmq_common::ArcRefCellWrapper;
@@ -48,6 +50,19 @@ use crate::rocketmq_serializable::RocketMQSerializable;
 pub const SERIALIZE_TYPE_PROPERTY: &str = "rocketmq.serialize.type";
 pub const SERIALIZE_TYPE_ENV: &str = "ROCKETMQ_SERIALIZE_TYPE";
 pub const REMOTING_VERSION_KEY: &str = "rocketmq.remoting.version";
+pub const FRAME_MAX_LENGTH_PROPERTY: &str = "com.rocketmq.remoting.frameMaxLength";
+pub const FRAME_MAX_LENGTH_ENV: &str = "ROCKETMQ_REMOTING_FRAME_MAX_LENGTH";
+pub const DEFAULT_FRAME_MAX_LENGTH: usize = 16 * 1024 * 1024;
+/// The most the decoder reserves ahead of a partially received frame.
+const FRAME_RESERVE_CHUNK: usize = 64 * 1024;
+/// Ext field a requester sets to the name of a [`CompressionType`] it can decompress.
+pub const ACCEPT_BODY_COMPRESSION_KEY: &str = "acceptBodyCompression";
+/// Ext field naming the [`CompressionType`] the body of a command was compressed with.
+pub const BODY_COMPRESSION_KEY: &str = "bodyCompression";
+pub const BODY_COMPRESSION_THRESHOLD_PROPERTY: &str =
+    "com.rocketmq.remoting.bodyCompressionThreshold";
+pub const BODY_COMPRESSION_THRESHOLD_ENV: &str = "ROCKETMQ_REMOTING_BODY_COMPRESSION_THRESHOLD";
+pub const DEFAULT_BODY_COMPRESSION_THRESHOLD: usize = 4 * 1024;
 
 lazy_static! {
     static ref requestId: Arc<AtomicI32> = Arc::new(AtomicI32::new(0));
@@ -63,6 +78,19 @@ lazy_static! {
             _ => SerializeType::JSON,
         }
     };
+    pub static ref FRAME_MAX_LENGTH: usize = std::env::var(FRAME_MAX_LENGTH_PROPERTY)
+        .or_else(|_| std::env::var(FRAME_MAX_LENGTH_ENV))
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_FRAME_MAX_LENGTH);
+    /// Smallest response body compressed for a requester that accepts body compression.
+    pub static ref BODY_COMPRESSION_THRESHOLD: usize =
+        std::env::var(BODY_COMPRESSION_THRESHOLD_PROPERTY)
+            .or_else(|_| std::env::var(BODY_COMPRESSION_THRESHOLD_ENV))
+            .ok()
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_BODY_COMPRESSION_THRESHOLD);
 }
 
 fn set_cmd_version(cmd: &mut RemotingCommand) {
@@ -356,6 +384,87 @@ impl RemotingCommand {
         self
     }
 
+    /// Advertises that the response to this request may carry a body compressed with
+    /// `compression_type`.
+    pub fn accept_body_compression(mut self, compression_type: CompressionType) -> Self {
+        self.add_ext_field(ACCEPT_BODY_COMPRESSION_KEY, compression_type.name());
+        self
+    }
+
+    /// The compression the requester advertised, if any it supports.
+    pub fn accepted_body_compression(&self) -> Option<CompressionType> {
+        self.ext_fields
+            .as_ref()
+            .and_then(|ext| ext.get(ACCEPT_BODY_COMPRESSION_KEY))
+            .and_then(|name| CompressionType::from_name(name))
+    }
+
+    /// The compression the body is currently encoded with.
+    pub fn body_compression(&self) -> Option<CompressionType> {
+        self.ext_fields
+            .as_ref()
+            .and_then(|ext| ext.get(BODY_COMPRESSION_KEY))
+            .and_then(|name| CompressionType::from_name(name))
+    }
+
+    /// Compresses the body in place and records `compression_type` in the ext fields. Does
+    /// nothing when there is no body or it is already compressed.
+    pub fn compress_body(
+        &mut self,
+        compression_type: CompressionType,
+        level: i32,
+    ) -> crate::Result<()> {
+        if self.body_compression().is_some() {
+            return Ok(());
+        }
+        let Some(body) = self.body.as_ref() else {
+            return Ok(());
+        };
+        let compressed = CompressorFactory::get_compressor(compression_type)
+            .compress(body, level)
+            .map_err(|err| {
+                Error::RemotingCommandEncoderError(format!(
+                    "compress body with {} failed: {}",
+                    compression_type.name(),
+                    err
+                ))
+            })?;
+        self.body = Some(Bytes::from(compressed));
+        self.add_ext_field(BODY_COMPRESSION_KEY, compression_type.name());
+        Ok(())
+    }
+
+    /// Restores a body compressed by the peer and removes the compression ext field, so
+    /// processors always see the plain body. Fails when the compression is unknown, the body is
+    /// corrupt or it inflates beyond `max_body_size`.
+    pub fn decompress_body(&mut self, max_body_size: usize) -> crate::Result<()> {
+        let Some(name) = self
+            .ext_fields
+            .as_mut()
+            .and_then(|ext| ext.remove(BODY_COMPRESSION_KEY))
+        else {
+            return Ok(());
+        };
+        let compression_type = CompressionType::from_name(&name).ok_or_else(|| {
+            Error::RemotingCommandDecoderError(format!("unsupported body compression: {}", name))
+        })?;
+        let Some(body) = self.body.as_ref() else {
+            return Ok(());
+        };
+        // the decompressor stops at `max_body_size`, so a small frame can not inflate into an
+        // arbitrarily large allocation
+        let decompressed = CompressorFactory::get_compressor(compression_type)
+            .decompress(body, max_body_size)
+            .map_err(|err| {
+                Error::RemotingCommandDecoderError(format!(
+                    "decompress body with {} failed: {}",
+                    name, err
+                ))
+            })?;
+        self.body = Some(Bytes::from(decompressed));
+        Ok(())
+    }
+
     pub fn get_serialize_type(&self) -> SerializeType {
         self.serialize_type
     }
@@ -428,7 +537,7 @@ impl RemotingCommand {
                     SerializeType::ROCKETMQ,
                 );
                 dst[begin_index..begin_index + 4]
-                    .copy_from_slice(&(header_size as i32 + body_length).to_be_bytes());
+                    .copy_from_slice(&(4 + header_size as i32 + body_length).to_be_bytes());
                 dst[begin_index + 4..begin_index + 8]
                     .copy_from_slice(&serialize_type.to_be_bytes());
             }
@@ -436,25 +545,52 @@ impl RemotingCommand {
     }
 
     pub fn decode(src: &mut BytesMut) -> crate::Result<Option<RemotingCommand>> {
+        Self::decode_with_max_frame_size(src, *FRAME_MAX_LENGTH)
+    }
+
+    /// Decodes one frame from `src`, rejecting frames whose declared length exceeds
+    /// `max_frame_size`.
+    ///
+    /// The length prefix is validated before any payload is buffered, so a peer cannot make the
+    /// decoder wait for (and allocate) an arbitrarily large frame. Malformed frames are reported
+    /// as errors instead of panicking; the caller is expected to close the connection since the
+    /// stream can no longer be resynchronized.
+    pub fn decode_with_max_frame_size(
+        src: &mut BytesMut,
+        max_frame_size: usize,
+    ) -> crate::Result<Option<RemotingCommand>> {
         let read_to = src.len();
         if read_to < 4 {
             // Wait for more data when there are less than 4 bytes.
             return Ok(None);
         }
         //Read the total size as a big-endian i32 from the first 4 bytes.
-        let total_size = i32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
+        let total_size = i32::from_be_bytes([src[0], src[1], src[2], src[3]]);
+        if total_size < 4 {
+            return Err(Error::RemotingCommandDecoderError(format!(
+                "Invalid frame length {}, must be at least 4",
+                total_size
+            )));
+        }
+        let total_size = total_size as usize;
+        if total_size > max_frame_size {
+            return Err(Error::RemotingCommandDecoderError(format!(
+                "Frame length {} exceeds the max frame length {}",
+                total_size, max_frame_size
+            )));
+        }
 
         if read_to < total_size + 4 {
-            // Wait for more data when the available data is less than the total size.
+            // Wait for more data when the available data is less than the total size. Only a
+            // chunk is reserved, the buffer grows as the rest of the frame arrives, so a peer
+            // sending length prefixes alone can not pin a whole frame per connection.
+            src.reserve((total_size + 4 - read_to).min(FRAME_RESERVE_CHUNK));
             return Ok(None);
         }
         // Split the BytesMut to get the command data including the total size.
         let mut cmd_data = src.split_to(total_size + 4);
         // Discard the first i32 (total size).
         cmd_data.advance(4);
-        if cmd_data.remaining() < 4 {
-            return Ok(None);
-        }
         // Read the header length as a big-endian i32.
         let ori_header_length = cmd_data.get_i32();
         let header_length = parse_header_length(ori_header_length);
@@ -609,9 +745,9 @@ impl RemotingCommand {
     }
 
     pub fn add_ext_field(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
-        if let Some(ref mut ext) = self.ext_fields {
-            ext.insert(key.into(), value.into());
-        }
+        self.ext_fields
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value.into());
         self
     }
 
@@ -756,4 +892,60 @@ mod tests {
         println!("i={}", RemotingCommand::default().opaque);
         println!("i={}", RemotingCommand::default().opaque);
     }
+
+    #[test]
+    fn compress_and_decompress_body_round_trip() {
+        let body = Bytes::from("topic-config".repeat(1024));
+        let mut command = RemotingCommand::create_response_command().set_body(Some(body.clone()));
+        command.compress_body(CompressionType::Zstd, 5).unwrap();
+        assert_eq!(command.body_compression(), Some(CompressionType::Zstd));
+        assert!(command.get_body().unwrap().len() < body.len());
+
+        command.decompress_body(DEFAULT_FRAME_MAX_LENGTH).unwrap();
+        assert_eq!(command.body_compression(), None);
+        assert_eq!(command.get_body(), Some(&body));
+    }
+
+    #[test]
+    fn accept_body_compression_is_read_back_from_ext_fields() {
+        let command = RemotingCommand::create_remoting_command(1);
+        assert_eq!(command.accepted_body_compression(), None);
+        let command = command.accept_body_compression(CompressionType::LZ4);
+        assert_eq!(
+            command.accepted_body_compression(),
+            Some(CompressionType::LZ4)
+        );
+    }
+
+    #[test]
+    fn decompress_body_rejects_unknown_compression() {
+        let mut command = RemotingCommand::create_response_command().set_body(Some("body"));
+        command.add_ext_field(BODY_COMPRESSION_KEY, "snappy");
+        assert!(command.decompress_body(DEFAULT_FRAME_MAX_LENGTH).is_err());
+    }
+
+    #[test]
+    fn decompress_body_rejects_oversized_body() {
+        for compression_type in [
+            CompressionType::LZ4,
+            CompressionType::Zstd,
+            CompressionType::Zlib,
+        ] {
+            let mut command = RemotingCommand::create_response_command()
+                .set_body(Some(Bytes::from(vec![0u8; 64 * 1024])));
+            command.compress_body(compression_type, 5).unwrap();
+            assert!(command.decompress_body(1024).is_err());
+        }
+    }
+
+    #[test]
+    fn decompress_body_rejects_lz4_bomb_before_inflating() {
+        // a six byte body whose size prefix claims 4 GiB
+        let mut command =
+            RemotingCommand::create_response_command().set_body(Some(Bytes::from_static(&[
+                0xff, 0xff, 0xff, 0xff, 0x10, 0x00,
+            ])));
+        command.add_ext_field(BODY_COMPRESSION_KEY, CompressionType::LZ4.name());
+        assert!(command.decompress_body(DEFAULT_FRAME_MAX_LENGTH).is_err());
+    }
 }
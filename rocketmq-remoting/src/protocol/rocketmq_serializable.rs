@@ -52,8 +52,10 @@ impl RocketMQSerializable {
         limit: usize,
     ) -> Result<Option<String>> {
         let len = if use_short_length {
+            Self::ensure_remaining(buf, 2, "string length")?;
             buf.get_u16() as usize
         } else {
+            Self::ensure_remaining(buf, 4, "string length")?;
             buf.get_u32() as usize
         };
 
@@ -64,6 +66,7 @@ impl RocketMQSerializable {
         if len > limit {
             return Err(Error::DecodingError(len, limit));
         }
+        Self::ensure_remaining(buf, len, "string content")?;
 
         let bytes = buf.split_to(len).freeze(); // Convert BytesMut to Bytes
         str::from_utf8(&bytes)
@@ -79,15 +82,16 @@ impl RocketMQSerializable {
         buf.put_i32(cmd.opaque());
         buf.put_i32(cmd.flag());
         if let Some(remark) = cmd.remark() {
-            Self::write_str(buf, true, remark.as_str());
+            Self::write_str(buf, false, remark.as_str());
         } else {
             buf.put_i32(0);
         }
         let map_len_index = buf.len();
         buf.put_i32(0);
-        let header = cmd.command_custom_header_mut().unwrap();
-        if header.support_fast_codec() {
-            header.encode_fast(buf);
+        if let Some(header) = cmd.command_custom_header_mut() {
+            if header.support_fast_codec() {
+                header.encode_fast(buf);
+            }
         }
         if let Some(ext_fields) = cmd.ext_fields() {
             ext_fields.iter().for_each(|(k, v)| {
@@ -95,7 +99,7 @@ impl RocketMQSerializable {
                     return;
                 }
                 Self::write_str(buf, true, k.as_str());
-                Self::write_str(buf, true, v.as_str());
+                Self::write_str(buf, false, v.as_str());
             });
         }
         let current_length = buf.len();
@@ -208,9 +212,16 @@ impl RocketMQSerializable {
         header_buffer: &mut BytesMut,
         header_len: usize,
     ) -> Result<RemotingCommand> {
+        // code(2) + language(1) + version(2) + opaque(4) + flag(4)
+        Self::ensure_remaining(header_buffer, 13, "fixed header fields")?;
+        let code = header_buffer.get_i16();
+        let language_code = header_buffer.get_u8();
+        let language = LanguageCode::value_of(language_code).ok_or_else(|| {
+            Error::RemotingCommandDecoderError(format!("Unknown language code {}", language_code))
+        })?;
         let cmd = RemotingCommand::default()
-            .set_code(header_buffer.get_i16())
-            .set_language(LanguageCode::value_of(header_buffer.get_u8()).unwrap())
+            .set_code(code)
+            .set_language(language)
             .set_version(header_buffer.get_i16() as i32)
             .set_opaque(header_buffer.get_i32())
             .set_flag(header_buffer.get_i32());
@@ -218,12 +229,19 @@ impl RocketMQSerializable {
         let remark = Self::read_str(header_buffer, false, header_len)?;
 
         // HashMap<String, String> extFields
-        let ext_fields_length = header_buffer.get_i32() as usize;
+        Self::ensure_remaining(header_buffer, 4, "ext fields length")?;
+        let ext_fields_length = header_buffer.get_i32();
         let ext = if ext_fields_length > 0 {
+            let ext_fields_length = ext_fields_length as usize;
             if ext_fields_length > header_len {
                 return Err(Error::DecodingError(ext_fields_length, header_len));
             }
             Self::map_deserialize(header_buffer, ext_fields_length)?
+        } else if ext_fields_length < 0 {
+            return Err(Error::RemotingCommandDecoderError(format!(
+                "Invalid ext fields length {}",
+                ext_fields_length
+            )));
         } else {
             HashMap::new()
         };
@@ -232,17 +250,30 @@ impl RocketMQSerializable {
     }
 
     pub fn map_deserialize(buffer: &mut BytesMut, len: usize) -> Result<HashMap<String, String>> {
+        Self::ensure_remaining(buffer, len, "ext fields")?;
+        let mut content = buffer.split_to(len);
         let mut map = HashMap::new();
-        let end_index = buffer.len() - len;
 
-        while buffer.remaining() > end_index {
-            let key = Self::read_str(buffer, true, len)?.unwrap();
-            let value = Self::read_str(buffer, false, len)?.unwrap();
+        while content.has_remaining() {
+            let key = Self::read_str(&mut content, true, len)?.unwrap_or_default();
+            let value = Self::read_str(&mut content, false, len)?.unwrap_or_default();
             map.insert(key, value);
         }
 
         Ok(map)
     }
+
+    fn ensure_remaining(buf: &BytesMut, needed: usize, what: &str) -> Result<()> {
+        if buf.remaining() < needed {
+            return Err(Error::RemotingCommandDecoderError(format!(
+                "Truncated {}: need {} bytes but only {} remaining",
+                what,
+                needed,
+                buf.remaining()
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -15,4 +15,5 @@
  * limitations under the License.
  */
 pub mod route_data_view;
+pub mod topic_route_change;
 pub mod topic_route_data;
@@ -0,0 +1,48 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::route::topic_route_data::TopicRouteData;
+
+/// Body of `SubscribeTopicRouteChange`, replacing the topics previously subscribed on the
+/// connection.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribeTopicRouteChangeRequestBody {
+    pub topics: HashSet<String>,
+}
+
+/// Body of `NotifyTopicRouteChange`, carrying the current route of every changed topic. A
+/// topic that was deleted carries an empty route.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicRouteChangeBody {
+    pub changes: Vec<TopicRouteChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicRouteChange {
+    pub topic: String,
+    /// Increases with every change pushed by a name server, stale pushes can be dropped by
+    /// comparing it with the last one received from the same name server.
+    pub version: i64,
+    pub topic_route_data: TopicRouteData,
+}
@@ -17,7 +17,9 @@
 
 use crate::remoting::RemotingService;
 
+pub mod request_dispatcher;
 pub mod server;
+pub mod server_listener;
 
 pub trait RemotingServer: RemotingService {
     /*fn register_processor(
@@ -0,0 +1,275 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+
+/// Default limit of requests being processed at the same time for one connection.
+pub const DEFAULT_MAX_IN_FLIGHT_PER_CONNECTION: usize = 4096;
+
+/// Name of the pool serving request codes that are not bound to any other pool.
+pub const DEFAULT_POOL_NAME: &str = "default";
+
+/// Limits of one executor pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutorPoolConfig {
+    pub name: String,
+    /// Number of requests of the pool processed at the same time.
+    pub pool_size: usize,
+    /// Number of requests allowed to wait for a free worker before new ones are rejected.
+    pub queue_capacity: usize,
+}
+
+impl ExecutorPoolConfig {
+    pub fn new(name: impl Into<String>, pool_size: usize, queue_capacity: usize) -> Self {
+        Self {
+            name: name.into(),
+            pool_size: pool_size.max(1),
+            queue_capacity,
+        }
+    }
+}
+
+/// Binds request codes to executor pools, like the separate thread pools a broker registers
+/// its processors with.
+#[derive(Debug, Clone)]
+pub struct RequestDispatchConfig {
+    default_pool: ExecutorPoolConfig,
+    pools: Vec<(ExecutorPoolConfig, Vec<i32>)>,
+    max_in_flight_per_connection: usize,
+}
+
+impl Default for RequestDispatchConfig {
+    fn default() -> Self {
+        Self::new(ExecutorPoolConfig::new(
+            DEFAULT_POOL_NAME,
+            num_cpus::get() * 2,
+            10000,
+        ))
+    }
+}
+
+impl RequestDispatchConfig {
+    pub fn new(default_pool: ExecutorPoolConfig) -> Self {
+        Self {
+            default_pool,
+            pools: Vec::new(),
+            max_in_flight_per_connection: DEFAULT_MAX_IN_FLIGHT_PER_CONNECTION,
+        }
+    }
+
+    /// Serves `request_codes` with their own pool. A code registered twice stays with the
+    /// latest pool.
+    pub fn register_pool(
+        mut self,
+        pool: ExecutorPoolConfig,
+        request_codes: impl IntoIterator<Item = impl Into<i32>>,
+    ) -> Self {
+        let request_codes = request_codes.into_iter().map(Into::into).collect();
+        self.pools.push((pool, request_codes));
+        self
+    }
+
+    pub fn set_max_in_flight_per_connection(mut self, max_in_flight_per_connection: usize) -> Self {
+        self.max_in_flight_per_connection = max_in_flight_per_connection.max(1);
+        self
+    }
+
+    pub fn max_in_flight_per_connection(&self) -> usize {
+        self.max_in_flight_per_connection
+    }
+}
+
+/// One executor pool: a bounded number of workers and a bounded wait queue.
+pub(crate) struct ExecutorPool {
+    name: String,
+    capacity: usize,
+    workers: Arc<Semaphore>,
+    /// Covers both running and waiting requests, so a failed `try_acquire` means the queue
+    /// is full.
+    slots: Arc<Semaphore>,
+}
+
+impl ExecutorPool {
+    fn new(config: &ExecutorPoolConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            capacity: config.pool_size + config.queue_capacity,
+            workers: Arc::new(Semaphore::new(config.pool_size)),
+            slots: Arc::new(Semaphore::new(config.pool_size + config.queue_capacity)),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Takes a queue slot for a new request, `None` when the pool is saturated.
+    pub(crate) fn try_enqueue(&self) -> Option<QueuedRequest> {
+        let slot = self.slots.clone().try_acquire_owned().ok()?;
+        Some(QueuedRequest {
+            _slot: slot,
+            workers: self.workers.clone(),
+        })
+    }
+
+    /// Number of requests queued or running in this pool.
+    pub(crate) fn pending(&self) -> usize {
+        self.capacity - self.slots.available_permits()
+    }
+}
+
+/// A request admitted into a pool, waiting for or holding a worker.
+pub(crate) struct QueuedRequest {
+    _slot: OwnedSemaphorePermit,
+    workers: Arc<Semaphore>,
+}
+
+impl QueuedRequest {
+    /// Waits for a free worker of the pool. The returned permit must be held while the request
+    /// is processed.
+    pub(crate) async fn acquire_worker(&self) -> OwnedSemaphorePermit {
+        self.workers
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("executor pool semaphore is never closed")
+    }
+}
+
+/// Routes requests to executor pools by request code.
+#[derive(Clone)]
+pub struct RequestDispatcher {
+    inner: Arc<RequestDispatcherInner>,
+}
+
+struct RequestDispatcherInner {
+    pools: Vec<ExecutorPool>,
+    code_table: HashMap<i32, usize>,
+    max_in_flight_per_connection: usize,
+}
+
+impl Default for RequestDispatcher {
+    fn default() -> Self {
+        Self::new(&RequestDispatchConfig::default())
+    }
+}
+
+impl RequestDispatcher {
+    pub fn new(config: &RequestDispatchConfig) -> Self {
+        let mut pools = vec![ExecutorPool::new(&config.default_pool)];
+        let mut code_table = HashMap::new();
+        for (pool, request_codes) in config.pools.iter() {
+            pools.push(ExecutorPool::new(pool));
+            for request_code in request_codes {
+                code_table.insert(*request_code, pools.len() - 1);
+            }
+        }
+        Self {
+            inner: Arc::new(RequestDispatcherInner {
+                pools,
+                code_table,
+                max_in_flight_per_connection: config.max_in_flight_per_connection,
+            }),
+        }
+    }
+
+    /// The pool serving `request_code`, falling back to the default pool.
+    pub(crate) fn pool(&self, request_code: i32) -> &ExecutorPool {
+        let index = self
+            .inner
+            .code_table
+            .get(&request_code)
+            .copied()
+            .unwrap_or(0);
+        &self.inner.pools[index]
+    }
+
+    pub(crate) fn max_in_flight_per_connection(&self) -> usize {
+        self.inner.max_in_flight_per_connection
+    }
+
+    /// Creates the in-flight limiter of a new connection.
+    pub(crate) fn connection_limiter(&self) -> Arc<Semaphore> {
+        Arc::new(Semaphore::new(self.inner.max_in_flight_per_connection))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dispatcher() -> RequestDispatcher {
+        RequestDispatcher::new(
+            &RequestDispatchConfig::new(ExecutorPoolConfig::new(DEFAULT_POOL_NAME, 2, 2))
+                .register_pool(ExecutorPoolConfig::new("send", 1, 1), [10, 310])
+                .register_pool(ExecutorPoolConfig::new("pull", 1, 0), [11])
+                .set_max_in_flight_per_connection(3),
+        )
+    }
+
+    #[test]
+    fn routes_request_codes_to_registered_pools() {
+        let dispatcher = dispatcher();
+        assert_eq!(dispatcher.pool(10).name(), "send");
+        assert_eq!(dispatcher.pool(310).name(), "send");
+        assert_eq!(dispatcher.pool(11).name(), "pull");
+        assert_eq!(dispatcher.pool(34).name(), DEFAULT_POOL_NAME);
+        assert_eq!(dispatcher.connection_limiter().available_permits(), 3);
+    }
+
+    #[test]
+    fn saturated_pool_rejects_new_requests() {
+        let dispatcher = dispatcher();
+        let send = dispatcher.pool(10);
+        let first = send.try_enqueue();
+        let second = send.try_enqueue();
+        assert!(first.is_some() && second.is_some());
+        assert_eq!(send.pending(), 2);
+        assert!(send.try_enqueue().is_none());
+        // other pools are not affected by a busy one
+        assert!(dispatcher.pool(11).try_enqueue().is_some());
+
+        drop(first);
+        assert_eq!(send.pending(), 1);
+        assert!(send.try_enqueue().is_some());
+    }
+
+    #[tokio::test]
+    async fn queued_request_waits_for_free_worker() {
+        let dispatcher = dispatcher();
+        let send = dispatcher.pool(10);
+        let running = send.try_enqueue().unwrap();
+        let waiting = send.try_enqueue().unwrap();
+        let worker = running.acquire_worker().await;
+        assert!(tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            waiting.acquire_worker()
+        )
+        .await
+        .is_err());
+        drop(worker);
+        assert!(tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            waiting.acquire_worker()
+        )
+        .await
+        .is_ok());
+    }
+}
@@ -16,17 +16,20 @@
  */
 use std::collections::HashMap;
 use std::future::Future;
+use std::io;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
-use futures::SinkExt;
+use rocketmq_common::common::compression::compression_type::CompressionType;
+use rocketmq_common::common::compression::compression_type::DEFAULT_COMPRESSION_LEVEL;
 use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_common::ArcRefCellWrapper;
 use tokio::net::TcpListener;
-use tokio::net::TcpStream;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::sync::Semaphore;
 use tokio::time;
 use tokio_stream::StreamExt;
@@ -34,13 +37,23 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::base::remoting_metrics::RemotingMetrics;
+use crate::base::remoting_metrics::RemotingMetricsSink;
+use crate::base::remoting_metrics::RemotingRole;
+use crate::base::remoting_metrics::RequestMetrics;
 use crate::base::response_future::ResponseFuture;
 use crate::code::response_code::ResponseCode;
 use crate::connection::Connection;
+use crate::connection::ConnectionStream;
 use crate::error::Error;
 use crate::net::channel::Channel;
+use crate::net::tls::TlsServerContext;
 use crate::protocol::remoting_command::RemotingCommand;
+use crate::protocol::remoting_command::BODY_COMPRESSION_THRESHOLD;
 use crate::protocol::RemotingCommandType;
+use crate::remoting_server::request_dispatcher::RequestDispatcher;
+use crate::remoting_server::server_listener::AcceptedStream;
+use crate::remoting_server::server_listener::ServerListener;
 use crate::runtime::connection_handler_context::ConnectionHandlerContextWrapper;
 use crate::runtime::processor::RequestProcessor;
 use crate::runtime::RPCHook;
@@ -62,8 +75,14 @@ pub struct ConnectionHandler<RP> {
     shutdown: Shutdown,
     _shutdown_complete: mpsc::Sender<()>,
     conn_disconnect_notify: Option<broadcast::Sender<SocketAddr>>,
-    rpc_hooks: Arc<Vec<Box<dyn RPCHook>>>,
+    rpc_hooks: Arc<Vec<Arc<Box<dyn RPCHook>>>>,
     response_table: ArcRefCellWrapper<HashMap<i32, ResponseFuture>>,
+    request_dispatcher: RequestDispatcher,
+    /// Bounds the requests of this connection being processed at the same time.
+    in_flight: Arc<Semaphore>,
+    /// How long to drain the connection on shutdown, `None` to close it right away.
+    shutdown_wait: Option<Duration>,
+    metrics: RemotingMetrics,
 }
 
 impl<RP> Drop for ConnectionHandler<RP> {
@@ -96,145 +115,308 @@ impl<RP> ConnectionHandler<RP> {
         channel: &Channel,
         response: &mut RemotingCommand,
     ) -> Result<()> {
-        for hook in self.rpc_hooks.iter() {
-            hook.do_before_request(channel.remote_address(), response)?;
-        }
-        Ok(())
+        do_after_rpc_hooks(&self.rpc_hooks, channel, response)
+    }
+}
+
+fn do_after_rpc_hooks(
+    rpc_hooks: &[Arc<Box<dyn RPCHook>>],
+    channel: &Channel,
+    response: &mut RemotingCommand,
+) -> Result<()> {
+    for hook in rpc_hooks.iter() {
+        hook.do_after_response(channel.remote_address(), response)?;
+    }
+    Ok(())
+}
+
+/// Turns a hook or processor failure into the response sent back, `None` for oneway requests.
+fn exception_response(exception: Error, oneway_rpc: bool) -> Option<RemotingCommand> {
+    match exception {
+        Error::AbortProcessException(code, message) => Some(
+            RemotingCommand::create_response_command_with_code_remark(code, message),
+        ),
+        _ if oneway_rpc => None,
+        _ => Some(RemotingCommand::create_response_command_with_code_remark(
+            ResponseCode::SystemError,
+            exception.to_string(),
+        )),
+    }
+}
+
+/// Compresses a response body of at least [`BODY_COMPRESSION_THRESHOLD`] bytes for a requester
+/// that advertised it can decompress it. The response is sent uncompressed if that fails.
+fn compress_large_body(response: &mut RemotingCommand, compression_type: CompressionType) {
+    let large = response
+        .get_body()
+        .is_some_and(|body| body.len() >= *BODY_COMPRESSION_THRESHOLD);
+    if !large {
+        return;
+    }
+    if let Err(err) = response.compress_body(compression_type, DEFAULT_COMPRESSION_LEVEL) {
+        warn!(
+            "compress response body failed, send it uncompressed: {}",
+            err
+        );
     }
 }
 
-impl<RP: RequestProcessor + Sync + 'static> ConnectionHandler<RP> {
+impl<RP: RequestProcessor + Sync + Clone + 'static> ConnectionHandler<RP> {
     async fn handle(&mut self) -> Result<()> {
         while !self.shutdown.is_shutdown {
             let frame = tokio::select! {
                 res = self.connection_handler_context.channel.connection.reader.next() => res,
                 _ = self.shutdown.recv() =>{
-                    //If a shutdown signal is received, return from `handle`.
-                    return Ok(());
+                    //If a shutdown signal is received, stop reading new requests.
+                    break;
                 }
             };
 
-            let mut cmd = match frame {
+            let cmd = match frame {
                 Some(frame) => frame?,
                 None => {
                     //If the frame is None, it means the connection is closed.
                     return Ok(());
                 }
             };
-            //handle response
-            if cmd.get_type() == RemotingCommandType::RESPONSE {
-                let future_response = self.response_table.remove(&cmd.opaque());
-                if let Some(future_response) = future_response {
-                    let _ = future_response.tx.send(Ok(cmd));
-                } else {
-                    warn!(
-                        "receive response, cmd={}, but not matched any request, address={}",
-                        cmd,
-                        self.connection_handler_context.channel.remote_address()
-                    )
-                }
-                continue;
+            if !self.on_command(cmd, false).await {
+                return Ok(());
             }
+        }
+        if let Some(shutdown_wait) = self.shutdown_wait {
+            self.drain(shutdown_wait).await;
+        }
+        Ok(())
+    }
 
-            //handle request
-            let mut exception = match self.do_before_rpc_hooks(&self.channel, &mut cmd) {
-                Ok(_) => None,
-                Err(error) => Some(error),
-            };
-            let opaque = cmd.opaque();
-            let oneway_rpc = cmd.is_oneway_rpc();
-            let mut response = if exception.is_some() {
-                Some(RemotingCommand::create_remoting_command(
-                    ResponseCode::SystemError,
-                ))
+    /// Handles one inbound command. While `draining`, new requests are answered with
+    /// GO_AWAY instead of being processed. Returns `false` once the connection can no longer
+    /// be written.
+    async fn on_command(&mut self, mut cmd: RemotingCommand, draining: bool) -> bool {
+        //handle response
+        if cmd.get_type() == RemotingCommandType::RESPONSE {
+            let future_response = self.response_table.remove(&cmd.opaque());
+            if let Some(future_response) = future_response {
+                let _ = future_response.tx.send(Ok(cmd));
             } else {
-                let channel = self.channel.clone();
-                let ctx = ArcRefCellWrapper::downgrade(&self.connection_handler_context);
-                tokio::select! {
-                    result = self.request_processor.process_request(channel,ctx,cmd) =>  result?,
+                warn!(
+                    "receive response, cmd={}, but not matched any request, address={}",
+                    cmd,
+                    self.connection_handler_context.channel.remote_address()
+                )
+            }
+            return true;
+        }
+
+        //handle request
+        let opaque = cmd.opaque();
+        let oneway_rpc = cmd.is_oneway_rpc();
+        if draining {
+            if oneway_rpc {
+                return true;
+            }
+            let response = RemotingCommand::create_response_command_with_code_remark(
+                ResponseCode::GoAway,
+                "the server is shutting down, please go away",
+            );
+            return self.write_response(response.set_opaque(opaque)).await;
+        }
+        if let Err(exception) = self.do_before_rpc_hooks(&self.channel, &mut cmd) {
+            if let Some(response) = exception_response(exception, oneway_rpc) {
+                return self.write_response(response.set_opaque(opaque)).await;
+            }
+            return true;
+        }
+        self.dispatch(cmd, opaque, oneway_rpc).await
+    }
+
+    /// Keeps serving the connection until the requests in flight complete or `shutdown_wait`
+    /// elapses, answering new requests with GO_AWAY so clients move to another server. The
+    /// responses written by then are flushed before the connection is closed.
+    async fn drain(&mut self, shutdown_wait: Duration) {
+        let remote_address = self.channel.remote_address();
+        let deadline = time::sleep(shutdown_wait);
+        tokio::pin!(deadline);
+        let in_flight = self.in_flight.clone();
+        let completed =
+            in_flight.acquire_many(self.request_dispatcher.max_in_flight_per_connection() as u32);
+        tokio::pin!(completed);
+        loop {
+            let frame = tokio::select! {
+                _ = &mut completed => break,
+                _ = &mut deadline => {
+                    warn!(
+                        "connection {} still has requests in flight after {:?}, close it anyway",
+                        remote_address, shutdown_wait
+                    );
+                    break;
                 }
+                frame = self.connection_handler_context.channel.connection.reader.next() => frame,
             };
-            if response.is_none() {
-                continue;
+            match frame {
+                Some(Ok(cmd)) => {
+                    if !self.on_command(cmd, true).await {
+                        return;
+                    }
+                }
+                // The peer is gone, nothing left to deliver.
+                Some(Err(_)) | None => return,
             }
-            exception = match self.do_before_rpc_hooks(&self.channel, response.as_mut().unwrap()) {
-                Ok(_) => None,
-                Err(error) => Some(error),
-            };
+        }
+        if let Err(err) = self.channel.close().await {
+            warn!("close connection {} failed: {}", remote_address, err);
+        }
+    }
 
-            if let Some(exception_inner) = exception {
-                match exception_inner {
-                    Error::AbortProcessException(code, message) => {
-                        let response = RemotingCommand::create_response_command_with_code_remark(
-                            code, message,
-                        );
-                        tokio::select! {
-                            result =self.connection_handler_context.channel.connection.writer.send(response.set_opaque(opaque)) => match result{
-                                Ok(_) =>{},
-                                Err(err) => {
-                                    match err {
-                                        Error::Io(io_error) => {
-                                            error!("send response failed: {}", io_error);
-                                            return Ok(())
-                                        }
-                                        _ => { error!("send response failed: {}", err);}
-                                    }
-                                },
-                            },
-                        }
-                    }
-                    _ => {
-                        if !oneway_rpc {
-                            let response =
-                                RemotingCommand::create_response_command_with_code_remark(
-                                    ResponseCode::SystemError,
-                                    exception_inner.to_string(),
-                                );
-                            tokio::select! {
-                                result =self.connection_handler_context.channel.connection.writer.send(response.set_opaque(opaque)) => match result{
-                                    Ok(_) =>{},
-                                    Err(err) => {
-                                        match err {
-                                            Error::Io(io_error) => {
-                                                error!("send response failed: {}", io_error);
-                                                return Ok(())
-                                            }
-                                            _ => { error!("send response failed: {}", err);}
-                                        }
-                                    },
-                                },
-                            }
+    /// Hands a request over to the executor pool of its request code, so a slow request does
+    /// not hold up the ones behind it on the same connection. Returns `false` once the
+    /// connection can no longer be written.
+    async fn dispatch(&mut self, cmd: RemotingCommand, opaque: i32, oneway_rpc: bool) -> bool {
+        let begin = Instant::now();
+        let code = cmd.code();
+        let request_body_bytes = cmd.get_body().map_or(0, |body| body.len());
+        self.metrics.begin(code);
+        let Ok(in_flight) = self.in_flight.clone().try_acquire_owned() else {
+            warn!(
+                "too many requests in flight on connection {}, reject request code {}",
+                self.channel.remote_address(),
+                code
+            );
+            self.record_rejected(code, opaque, request_body_bytes, begin);
+            return self
+                .reject(
+                    opaque,
+                    oneway_rpc,
+                    "[OVERLOAD]system busy, too many requests in flight on this connection"
+                        .to_string(),
+                )
+                .await;
+        };
+        let pool = self.request_dispatcher.pool(code);
+        let Some(queued) = pool.try_enqueue() else {
+            warn!(
+                "executor pool {} is full, reject request code {} from {}",
+                pool.name(),
+                code,
+                self.channel.remote_address()
+            );
+            self.record_rejected(code, opaque, request_body_bytes, begin);
+            let remark = format!(
+                "[REJECTREQUEST]system busy, too many requests and {} executor pool busy",
+                pool.name()
+            );
+            return self.reject(opaque, oneway_rpc, remark).await;
+        };
+
+        let mut request_processor = self.request_processor.clone();
+        let channel = self.channel.clone();
+        let ctx = ArcRefCellWrapper::downgrade(&self.connection_handler_context);
+        let rpc_hooks = self.rpc_hooks.clone();
+        let metrics = self.metrics.clone();
+        let accepted_body_compression = cmd.accepted_body_compression();
+        tokio::spawn(async move {
+            let _in_flight = in_flight;
+            let _worker = queued.acquire_worker().await;
+            let queue_time = begin.elapsed();
+            let mut error = false;
+            let response = match request_processor
+                .process_request(channel.clone(), ctx, cmd)
+                .await
+            {
+                Ok(response) => response,
+                Err(exception) => {
+                    error!(
+                        "process request from {} failed: {}",
+                        channel.remote_address(),
+                        exception
+                    );
+                    error = true;
+                    exception_response(exception, oneway_rpc)
+                }
+            };
+            let response = match response {
+                Some(mut response) => {
+                    match do_after_rpc_hooks(&rpc_hooks, &channel, &mut response) {
+                        Ok(()) => Some(response),
+                        Err(exception) => {
+                            error = true;
+                            exception_response(exception, oneway_rpc)
                         }
                     }
                 }
-                continue;
+                None => None,
+            };
+            let (mut response_code, mut response_body_bytes) = (None, 0);
+            if let Some(mut response) = response {
+                if let Some(compression_type) = accepted_body_compression {
+                    compress_large_body(&mut response, compression_type);
+                }
+                response_code = Some(response.code());
+                response_body_bytes = response.get_body().map_or(0, |body| body.len());
+                if let Err(err) = channel.write_and_flush(response.set_opaque(opaque)).await {
+                    error!("send response failed: {}", err);
+                    error = true;
+                }
             }
+            metrics.end(&RequestMetrics {
+                role: metrics.role(),
+                code,
+                remote_addr: &channel.remote_address().to_string(),
+                opaque,
+                response_code,
+                error: error || response_code.is_some_and(RequestMetrics::is_system_error),
+                request_body_bytes,
+                response_body_bytes,
+                queue_time,
+                cost: begin.elapsed(),
+            });
+        });
+        true
+    }
 
-            let response = response.unwrap();
-            tokio::select! {
-                result =self.connection_handler_context.channel.connection.writer.send(response.set_opaque(opaque)) => match result{
-                    Ok(_) =>{},
-                    Err(err) => {
-                        match err {
-                            Error::Io(io_error) => {
-                                error!("send response failed: {}", io_error);
-                                return Ok(())
-                            }
-                            _ => { error!("send response failed: {}", err);}
-                        }
-                    },
-                },
+    /// Records a request rejected before reaching its executor pool.
+    fn record_rejected(&self, code: i32, opaque: i32, request_body_bytes: usize, begin: Instant) {
+        self.metrics.end(&RequestMetrics {
+            role: self.metrics.role(),
+            code,
+            remote_addr: &self.channel.remote_address().to_string(),
+            opaque,
+            response_code: Some(ResponseCode::SystemBusy as i32),
+            error: true,
+            request_body_bytes,
+            response_body_bytes: 0,
+            queue_time: Duration::ZERO,
+            cost: begin.elapsed(),
+        });
+    }
+
+    async fn reject(&mut self, opaque: i32, oneway_rpc: bool, remark: String) -> bool {
+        if oneway_rpc {
+            return true;
+        }
+        let response = RemotingCommand::create_response_command_with_code_remark(
+            ResponseCode::SystemBusy,
+            remark,
+        );
+        self.write_response(response.set_opaque(opaque)).await
+    }
+
+    async fn write_response(&mut self, response: RemotingCommand) -> bool {
+        match self.channel.write_and_flush(response).await {
+            Ok(_) => true,
+            Err(err) => {
+                error!("send response failed: {}", err);
+                false
             }
         }
-        Ok(())
     }
 }
 
 /// Server listener state. Created in the `run` call. It includes a `run` method
 /// which performs the TCP listening and initialization of per-connection state.
 struct ConnectionListener<RP> {
-    /// The TCP listener supplied by the `run` caller.
-    listener: TcpListener,
+    /// The TCP and Unix socket listeners supplied by the `run` caller.
+    listeners: Vec<ServerListener>,
 
     /// Limit the max number of connections.
     ///
@@ -254,7 +436,16 @@ struct ConnectionListener<RP> {
 
     request_processor: RP,
 
-    rpc_hooks: Arc<Vec<Box<dyn RPCHook>>>,
+    rpc_hooks: Arc<Vec<Arc<Box<dyn RPCHook>>>>,
+
+    /// TLS state of the listener, `None` when TLS is disabled.
+    tls_context: Option<TlsServerContext>,
+
+    request_dispatcher: RequestDispatcher,
+
+    shutdown_wait: Option<Duration>,
+
+    metrics: RemotingMetrics,
 }
 
 impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
@@ -271,36 +462,65 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
             // Accept a new socket. This will attempt to perform error handling.
             // The `accept` method internally attempts to recover errors, so an
             // error here is non-recoverable.
-            let (socket, remote_addr) = self.accept().await?;
+            let AcceptedStream {
+                stream,
+                local_addr,
+                remote_addr,
+            } = self.accept().await?;
             info!("Accepted connection, client ip:{}", remote_addr);
-            socket.set_nodelay(true).expect("set nodelay failed");
 
-            let response_table = ArcRefCellWrapper::new(HashMap::with_capacity(128));
-            let channel = Channel::new(
-                socket.local_addr()?,
-                remote_addr,
-                Connection::new(socket),
-                response_table.clone(),
-            );
-            //create per connection handler state
-            let mut handler = ConnectionHandler {
-                request_processor: self.request_processor.clone(),
-                //connection: Connection::new(socket, remote_addr),
-                connection_handler_context: ArcRefCellWrapper::new(
-                    ConnectionHandlerContextWrapper {
-                        // connection: Connection::new(socket),
-                        channel: channel.clone(),
-                    },
-                ),
-                channel,
-                shutdown: Shutdown::new(self.notify_shutdown.subscribe()),
-                _shutdown_complete: self.shutdown_complete_tx.clone(),
-                conn_disconnect_notify: self.conn_disconnect_notify.clone(),
-                rpc_hooks: self.rpc_hooks.clone(),
-                response_table,
-            };
+            let request_processor = self.request_processor.clone();
+            let shutdown = Shutdown::new(self.notify_shutdown.subscribe());
+            let shutdown_complete = self.shutdown_complete_tx.clone();
+            let conn_disconnect_notify = self.conn_disconnect_notify.clone();
+            let rpc_hooks = self.rpc_hooks.clone();
+            let tls_context = self.tls_context.clone();
+            let request_dispatcher = self.request_dispatcher.clone();
+            let shutdown_wait = self.shutdown_wait;
+            let metrics = self.metrics.clone();
 
             tokio::spawn(async move {
+                // The handshake runs in the connection task so a slow peer does not stall accept
+                let stream = match (tls_context, stream) {
+                    (Some(tls_context), ConnectionStream::Tcp(socket)) => {
+                        match tls_context.accept(socket).await {
+                            Ok(stream) => stream,
+                            Err(err) => {
+                                warn!("reject connection from {}: {}", remote_addr, err);
+                                drop(permit);
+                                return;
+                            }
+                        }
+                    }
+                    (_, stream) => stream,
+                };
+                let response_table = ArcRefCellWrapper::new(HashMap::with_capacity(128));
+                let channel = Channel::new(
+                    local_addr,
+                    remote_addr,
+                    Connection::with_stream(stream),
+                    response_table.clone(),
+                );
+                //create per connection handler state
+                let mut handler = ConnectionHandler {
+                    request_processor,
+                    connection_handler_context: ArcRefCellWrapper::new(
+                        ConnectionHandlerContextWrapper {
+                            channel: channel.clone(),
+                        },
+                    ),
+                    channel,
+                    shutdown,
+                    _shutdown_complete: shutdown_complete,
+                    conn_disconnect_notify,
+                    rpc_hooks,
+                    response_table,
+                    in_flight: request_dispatcher.connection_limiter(),
+                    request_dispatcher,
+                    shutdown_wait,
+                    metrics,
+                };
+
                 if let Err(err) = handler.handle().await {
                     error!(cause = ?err, "connection error");
                 }
@@ -317,15 +537,15 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
         }
     }
 
-    async fn accept(&mut self) -> anyhow::Result<(TcpStream, SocketAddr)> {
+    async fn accept(&mut self) -> anyhow::Result<AcceptedStream> {
         let mut backoff = 1;
 
         // Try to accept a few times
         loop {
             // Perform the accept operation. If a socket is successfully
             // accepted, return it. Otherwise, save the error.
-            match self.listener.accept().await {
-                Ok((socket, remote_addr)) => return Ok((socket, remote_addr)),
+            match ServerListener::accept_any(&self.listeners).await {
+                Ok(accepted) => return Ok(accepted),
                 Err(err) => {
                     if backoff > 64 {
                         // Accept has failed too many times. Return the error.
@@ -343,8 +563,35 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
     }
 }
 
+/// Stops a running [`RocketMQServer`] from another task.
+#[derive(Clone)]
+pub struct ServerShutdownHandle {
+    shutdown_tx: watch::Sender<bool>,
+    terminated_rx: watch::Receiver<bool>,
+}
+
+impl ServerShutdownHandle {
+    /// Asks the server to stop accepting connections and to close the open ones, draining
+    /// them first when `enable_shutdown_gracefully` is set. Returns without waiting.
+    pub fn shutdown(&self) {
+        self.shutdown_tx.send_replace(true);
+    }
+
+    /// Waits until the server has closed all of its connections, or was dropped without
+    /// being run.
+    pub async fn wait_for_termination(&self) {
+        let mut terminated_rx = self.terminated_rx.clone();
+        let _ = terminated_rx.wait_for(|terminated| *terminated).await;
+    }
+}
+
 pub struct RocketMQServer<RP> {
     config: Arc<ServerConfig>,
+    request_dispatcher: RequestDispatcher,
+    rpc_hooks: Vec<Arc<Box<dyn RPCHook>>>,
+    metrics_sink: Option<Arc<dyn RemotingMetricsSink>>,
+    shutdown_tx: watch::Sender<bool>,
+    terminated_tx: watch::Sender<bool>,
     _phantom_data: std::marker::PhantomData<RP>,
 }
 
@@ -352,53 +599,158 @@ impl<RP> RocketMQServer<RP> {
     pub fn new(config: Arc<ServerConfig>) -> Self {
         Self {
             config,
+            request_dispatcher: RequestDispatcher::default(),
+            rpc_hooks: Vec::new(),
+            metrics_sink: None,
+            shutdown_tx: watch::channel(false).0,
+            terminated_tx: watch::channel(false).0,
             _phantom_data: std::marker::PhantomData,
         }
     }
+
+    pub fn shutdown_handle(&self) -> ServerShutdownHandle {
+        ServerShutdownHandle {
+            shutdown_tx: self.shutdown_tx.clone(),
+            terminated_rx: self.terminated_tx.subscribe(),
+        }
+    }
+
+    /// How long connections are drained on shutdown, `None` when graceful shutdown is off.
+    fn shutdown_wait(&self) -> Option<Duration> {
+        self.config
+            .enable_shutdown_gracefully
+            .then(|| Duration::from_secs(self.config.shutdown_wait_time_seconds))
+    }
+
+    /// Sets the executor pools of the server. Servers sharing a dispatcher share its pools.
+    pub fn set_request_dispatcher(mut self, request_dispatcher: RequestDispatcher) -> Self {
+        self.request_dispatcher = request_dispatcher;
+        self
+    }
+
+    /// Adds a hook run around every request, in registration order.
+    pub fn register_rpc_hook(mut self, hook: Arc<Box<dyn RPCHook>>) -> Self {
+        self.rpc_hooks.push(hook);
+        self
+    }
+
+    /// Sets the sink receiving the metrics of every request the server processes.
+    pub fn set_metrics_sink(mut self, metrics_sink: Arc<dyn RemotingMetricsSink>) -> Self {
+        self.metrics_sink = Some(metrics_sink);
+        self
+    }
 }
 
 impl<RP: RequestProcessor + Sync + 'static + Clone> RocketMQServer<RP> {
-    pub async fn run(&self, request_processor: RP) {
-        let listener = TcpListener::bind(&format!(
-            "{}:{}",
-            self.config.bind_address, self.config.listen_port
-        ))
-        .await
-        .unwrap();
-        info!(
-            "Bind local address: {}",
-            format!("{}:{}", self.config.bind_address, self.config.listen_port)
-        );
+    /// Serves until shutdown, failing when a listener can not be bound.
+    pub async fn run(&self, request_processor: RP) -> io::Result<()> {
+        let listeners = match self.bind_listeners().await {
+            Ok(listeners) => listeners,
+            Err(err) => {
+                self.terminated_tx.send_replace(true);
+                return Err(err);
+            }
+        };
+        let tls_context = TlsServerContext::from_config(&self.config)
+            .expect("failed to initialize the TLS context of the remoting server");
         let (notify_conn_disconnect, _) = broadcast::channel::<SocketAddr>(100);
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let shutdown = async move {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = shutdown_rx.wait_for(|shutdown| *shutdown) => {}
+            }
+        };
         run(
-            listener,
-            tokio::signal::ctrl_c(),
+            listeners,
+            shutdown,
             request_processor,
             Some(notify_conn_disconnect),
-            vec![],
+            self.rpc_hooks.clone(),
+            tls_context,
+            self.request_dispatcher.clone(),
+            self.shutdown_wait(),
+            RemotingMetrics::new(RemotingRole::Server)
+                .set_sink(self.metrics_sink.clone())
+                .set_slow_threshold_millis(self.config.slow_request_threshold_millis),
         )
         .await;
+        self.terminated_tx.send_replace(true);
+        info!(
+            "remoting server {}:{} terminated",
+            self.config.bind_address, self.config.listen_port
+        );
+        Ok(())
+    }
+
+    async fn bind_listeners(&self) -> io::Result<Vec<ServerListener>> {
+        let listener = TcpListener::bind(&format!(
+            "{}:{}",
+            self.config.bind_address, self.config.listen_port
+        ))
+        .await?;
+        info!(
+            "Bind local address: {}, tls mode: {}",
+            format!("{}:{}", self.config.bind_address, self.config.listen_port),
+            self.config.tls_mode.name()
+        );
+        let mut listeners = vec![ServerListener::from(listener)];
+        if let Some(unix_socket_path) = self.config.unix_socket_path.as_deref() {
+            listeners.push(bind_unix_listener(unix_socket_path)?);
+            info!("Bind unix socket: {}", unix_socket_path);
+        }
+        Ok(listeners)
     }
 }
 
+#[cfg(unix)]
+fn bind_unix_listener(path: &str) -> io::Result<ServerListener> {
+    ServerListener::bind_unix(path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("failed to bind unix socket {}: {}", path, err),
+        )
+    })
+}
+
+#[cfg(not(unix))]
+fn bind_unix_listener(path: &str) -> io::Result<ServerListener> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "cannot listen on unix socket {}, unix domain sockets are not supported on this \
+             platform",
+            path
+        ),
+    ))
+}
+
 pub async fn run<RP: RequestProcessor + Sync + 'static + Clone>(
-    listener: TcpListener,
+    listeners: Vec<ServerListener>,
     shutdown: impl Future,
     request_processor: RP,
     conn_disconnect_notify: Option<broadcast::Sender<SocketAddr>>,
-    rpc_hooks: Vec<Box<dyn RPCHook>>,
+    rpc_hooks: Vec<Arc<Box<dyn RPCHook>>>,
+    tls_context: Option<TlsServerContext>,
+    request_dispatcher: RequestDispatcher,
+    shutdown_wait: Option<Duration>,
+    metrics: RemotingMetrics,
 ) {
     let (notify_shutdown, _) = broadcast::channel(1);
     let (shutdown_complete_tx, mut shutdown_complete_rx) = mpsc::channel(1);
     // Initialize the connection listener state
     let mut listener = ConnectionListener {
-        listener,
+        listeners,
         notify_shutdown,
         shutdown_complete_tx,
         conn_disconnect_notify,
         limit_connections: Arc::new(Semaphore::new(DEFAULT_MAX_CONNECTIONS)),
         request_processor,
         rpc_hooks: Arc::new(rpc_hooks),
+        tls_context,
+        request_dispatcher,
+        shutdown_wait,
+        metrics,
     };
 
     tokio::select! {
@@ -419,10 +771,13 @@ pub async fn run<RP: RequestProcessor + Sync + 'static + Clone>(
     }
 
     let ConnectionListener {
+        listeners,
         shutdown_complete_tx,
         notify_shutdown,
         ..
     } = listener;
+    // Stop accepting before the open connections are drained
+    drop(listeners);
     drop(notify_shutdown);
     drop(shutdown_complete_tx);
 
@@ -467,3 +822,350 @@ impl Shutdown {
         self.is_shutdown = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use bytes::BytesMut;
+    use futures::SinkExt;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpStream;
+    use tokio_util::codec::Encoder;
+
+    use super::*;
+    use crate::base::remoting_metrics::StatsRemotingMetricsSink;
+    use crate::codec::remoting_command_codec::RemotingCommandCodec;
+    use crate::remoting_server::request_dispatcher::ExecutorPoolConfig;
+    use crate::remoting_server::request_dispatcher::RequestDispatchConfig;
+    use crate::runtime::connection_handler_context::ConnectionHandlerContext;
+
+    const SLOW_REQUEST_CODE: i32 = 1;
+    const FAST_REQUEST_CODE: i32 = 2;
+    const LARGE_BODY_REQUEST_CODE: i32 = 3;
+
+    fn large_body() -> Bytes {
+        Bytes::from("topicConfigTable".repeat(*BODY_COMPRESSION_THRESHOLD))
+    }
+
+    #[derive(Clone)]
+    struct SleepingProcessor;
+
+    impl RequestProcessor for SleepingProcessor {
+        async fn process_request(
+            &mut self,
+            _channel: Channel,
+            _ctx: ConnectionHandlerContext,
+            request: RemotingCommand,
+        ) -> Result<Option<RemotingCommand>> {
+            if request.code() == SLOW_REQUEST_CODE {
+                time::sleep(Duration::from_millis(500)).await;
+            }
+            if request.code() == LARGE_BODY_REQUEST_CODE {
+                return Ok(Some(
+                    RemotingCommand::create_response_command().set_body(Some(large_body())),
+                ));
+            }
+            Ok(Some(RemotingCommand::create_response_command()))
+        }
+    }
+
+    struct TestServer {
+        addr: SocketAddr,
+        connection: Connection,
+        shutdown: tokio::sync::oneshot::Sender<()>,
+        terminated: tokio::task::JoinHandle<()>,
+        metrics_sink: Arc<StatsRemotingMetricsSink>,
+    }
+
+    async fn start_server(shutdown_wait: Option<Duration>) -> TestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let dispatcher = RequestDispatcher::new(
+            &RequestDispatchConfig::new(ExecutorPoolConfig::new("default", 4, 16))
+                .register_pool(ExecutorPoolConfig::new("slow", 1, 0), [SLOW_REQUEST_CODE]),
+        );
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let metrics_sink = Arc::new(StatsRemotingMetricsSink::new());
+        let metrics = RemotingMetrics::new(RemotingRole::Server)
+            .set_sink(Some(metrics_sink.clone() as Arc<dyn RemotingMetricsSink>))
+            .set_slow_threshold_millis(100);
+        let terminated = tokio::spawn(run(
+            vec![listener.into()],
+            shutdown_rx,
+            SleepingProcessor,
+            None,
+            vec![],
+            None,
+            dispatcher,
+            shutdown_wait,
+            metrics,
+        ));
+        let connection = Connection::new(TcpStream::connect(addr).await.unwrap());
+        TestServer {
+            addr,
+            connection,
+            shutdown: shutdown_tx,
+            terminated,
+            metrics_sink,
+        }
+    }
+
+    async fn send(connection: &mut Connection, code: i32, opaque: i32) {
+        let request = RemotingCommand::create_remoting_command(code).set_opaque(opaque);
+        connection.writer.send(request).await.unwrap();
+    }
+
+    async fn receive(connection: &mut Connection) -> RemotingCommand {
+        time::timeout(Duration::from_secs(5), connection.reader.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn slow_request_does_not_block_other_pools() {
+        let TestServer {
+            mut connection,
+            shutdown: _shutdown,
+            ..
+        } = start_server(None).await;
+        send(&mut connection, SLOW_REQUEST_CODE, 1).await;
+        send(&mut connection, FAST_REQUEST_CODE, 2).await;
+
+        let first = receive(&mut connection).await;
+        assert_eq!(first.opaque(), 2);
+        assert_eq!(first.code(), ResponseCode::Success as i32);
+        let second = receive(&mut connection).await;
+        assert_eq!(second.opaque(), 1);
+        assert_eq!(second.code(), ResponseCode::Success as i32);
+    }
+
+    #[tokio::test]
+    async fn saturated_pool_responds_system_busy() {
+        let TestServer {
+            mut connection,
+            shutdown: _shutdown,
+            ..
+        } = start_server(None).await;
+        send(&mut connection, SLOW_REQUEST_CODE, 1).await;
+        send(&mut connection, SLOW_REQUEST_CODE, 2).await;
+
+        let rejected = receive(&mut connection).await;
+        assert_eq!(rejected.opaque(), 2);
+        assert_eq!(rejected.code(), ResponseCode::SystemBusy as i32);
+        assert!(rejected
+            .remark()
+            .unwrap()
+            .contains("slow executor pool busy"));
+        let processed = receive(&mut connection).await;
+        assert_eq!(processed.opaque(), 1);
+        assert_eq!(processed.code(), ResponseCode::Success as i32);
+    }
+
+    #[tokio::test]
+    async fn records_per_code_metrics() {
+        let TestServer {
+            mut connection,
+            shutdown: _shutdown,
+            metrics_sink,
+            ..
+        } = start_server(None).await;
+        send(&mut connection, SLOW_REQUEST_CODE, 1).await;
+        send(&mut connection, SLOW_REQUEST_CODE, 2).await;
+        send(&mut connection, FAST_REQUEST_CODE, 3).await;
+        for _ in 0..3 {
+            receive(&mut connection).await;
+        }
+        // The last response is written before its metrics are recorded
+        time::sleep(Duration::from_millis(50)).await;
+
+        let slow_key = StatsRemotingMetricsSink::stats_key(RemotingRole::Server, SLOW_REQUEST_CODE);
+        let latency = metrics_sink.latency().get_stats_item(&slow_key).unwrap();
+        assert_eq!(latency.get_times(), 2);
+        assert!(latency.get_value() >= 500);
+        let errors = metrics_sink.errors().get_stats_item(&slow_key).unwrap();
+        assert_eq!(errors.get_times(), 1);
+        assert_eq!(
+            metrics_sink.in_flight(RemotingRole::Server, SLOW_REQUEST_CODE),
+            0
+        );
+
+        let fast_key = StatsRemotingMetricsSink::stats_key(RemotingRole::Server, FAST_REQUEST_CODE);
+        let latency = metrics_sink.latency().get_stats_item(&fast_key).unwrap();
+        assert_eq!(latency.get_times(), 1);
+        assert!(metrics_sink.errors().get_stats_item(&fast_key).is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn serves_tcp_and_unix_socket_connections() {
+        let dir = std::env::temp_dir().join(format!("rocketmq-server-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("server.sock");
+        let socket_path = socket.to_str().unwrap().to_string();
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let tcp_addr = tcp_listener.local_addr().unwrap();
+        let listeners = vec![
+            tcp_listener.into(),
+            ServerListener::bind_unix(&socket_path).unwrap(),
+        ];
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let terminated = tokio::spawn(run(
+            listeners,
+            shutdown_rx,
+            SleepingProcessor,
+            None,
+            vec![],
+            None,
+            RequestDispatcher::default(),
+            None,
+            RemotingMetrics::new(RemotingRole::Server),
+        ));
+
+        let unix_stream = crate::net::unix::connect(&socket_path).await.unwrap();
+        let mut unix_connection = Connection::with_stream(unix_stream);
+        send(&mut unix_connection, FAST_REQUEST_CODE, 1).await;
+        let response = receive(&mut unix_connection).await;
+        assert_eq!(response.opaque(), 1);
+        assert_eq!(response.code(), ResponseCode::Success as i32);
+
+        let mut tcp_connection = Connection::new(TcpStream::connect(tcp_addr).await.unwrap());
+        send(&mut tcp_connection, FAST_REQUEST_CODE, 2).await;
+        assert_eq!(receive(&mut tcp_connection).await.opaque(), 2);
+
+        drop(shutdown_tx);
+        terminated.await.unwrap();
+        assert!(!socket.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_fails_when_unix_socket_can_not_be_bound() {
+        // A regular file is never replaced by the socket
+        let file =
+            std::env::temp_dir().join(format!("rocketmq-server-file-{}", std::process::id()));
+        std::fs::write(&file, b"data").unwrap();
+        let server = RocketMQServer::new(Arc::new(ServerConfig {
+            bind_address: "127.0.0.1".to_string(),
+            listen_port: 0,
+            unix_socket_path: Some(file.to_string_lossy().to_string()),
+            ..ServerConfig::default()
+        }));
+        let shutdown_handle = server.shutdown_handle();
+        let err = server.run(SleepingProcessor).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        // Waiting for the server does not hang once it failed to start
+        time::timeout(
+            Duration::from_secs(5),
+            shutdown_handle.wait_for_termination(),
+        )
+        .await
+        .unwrap();
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn compress_large_body_skips_small_bodies() {
+        let mut small = RemotingCommand::create_response_command().set_body(Some("small"));
+        compress_large_body(&mut small, CompressionType::Zstd);
+        assert_eq!(small.body_compression(), None);
+
+        let mut large = RemotingCommand::create_response_command().set_body(Some(large_body()));
+        compress_large_body(&mut large, CompressionType::Zstd);
+        assert_eq!(large.body_compression(), Some(CompressionType::Zstd));
+        assert!(large.get_body().unwrap().len() < large_body().len());
+    }
+
+    /// Sends `request` over a raw socket and returns the length of the response frame as it was
+    /// written on the wire, before the codec restores a compressed body.
+    async fn response_frame_length(addr: SocketAddr, request: RemotingCommand) -> usize {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let mut frame = BytesMut::new();
+        RemotingCommandCodec::new()
+            .encode(request, &mut frame)
+            .unwrap();
+        stream.write_all(&frame).await.unwrap();
+        time::timeout(Duration::from_secs(5), stream.read_i32())
+            .await
+            .unwrap()
+            .unwrap() as usize
+    }
+
+    #[tokio::test]
+    async fn large_response_is_compressed_only_when_accepted() {
+        let TestServer {
+            addr,
+            mut connection,
+            shutdown: _shutdown,
+            ..
+        } = start_server(None).await;
+        let accepting = RemotingCommand::create_remoting_command(LARGE_BODY_REQUEST_CODE)
+            .accept_body_compression(CompressionType::Zstd);
+        assert!(response_frame_length(addr, accepting).await < large_body().len());
+        let plain = RemotingCommand::create_remoting_command(LARGE_BODY_REQUEST_CODE);
+        assert!(response_frame_length(addr, plain).await > large_body().len());
+
+        let request = RemotingCommand::create_remoting_command(LARGE_BODY_REQUEST_CODE)
+            .set_opaque(1)
+            .accept_body_compression(CompressionType::Zstd);
+        connection.writer.send(request).await.unwrap();
+        let response = receive(&mut connection).await;
+        assert_eq!(response.opaque(), 1);
+        assert_eq!(response.body_compression(), None);
+        assert_eq!(response.get_body(), Some(&large_body()));
+    }
+
+    #[tokio::test]
+    async fn graceful_shutdown_drains_in_flight_requests() {
+        let TestServer {
+            addr,
+            mut connection,
+            shutdown,
+            terminated,
+            ..
+        } = start_server(Some(Duration::from_secs(5))).await;
+        send(&mut connection, SLOW_REQUEST_CODE, 1).await;
+        time::sleep(Duration::from_millis(100)).await;
+        shutdown.send(()).unwrap();
+        time::sleep(Duration::from_millis(100)).await;
+        send(&mut connection, FAST_REQUEST_CODE, 2).await;
+
+        let go_away = receive(&mut connection).await;
+        assert_eq!(go_away.opaque(), 2);
+        assert_eq!(go_away.code(), ResponseCode::GoAway as i32);
+        let processed = receive(&mut connection).await;
+        assert_eq!(processed.opaque(), 1);
+        assert_eq!(processed.code(), ResponseCode::Success as i32);
+
+        let closed = time::timeout(Duration::from_secs(5), connection.reader.next()).await;
+        assert!(closed.unwrap().is_none());
+        time::timeout(Duration::from_secs(5), terminated)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn graceful_shutdown_gives_up_after_deadline() {
+        let TestServer {
+            mut connection,
+            shutdown,
+            terminated,
+            ..
+        } = start_server(Some(Duration::from_millis(100))).await;
+        send(&mut connection, SLOW_REQUEST_CODE, 1).await;
+        time::sleep(Duration::from_millis(50)).await;
+        shutdown.send(()).unwrap();
+
+        let closed = time::timeout(Duration::from_millis(400), connection.reader.next()).await;
+        assert!(closed.unwrap().is_none());
+        time::timeout(Duration::from_secs(5), terminated)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::io;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::Path;
+
+use futures::future::select_all;
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+use crate::connection::ConnectionStream;
+#[cfg(unix)]
+use crate::net::unix;
+#[cfg(unix)]
+use crate::net::unix::UNIX_ADDRESS_PREFIX;
+#[cfg(unix)]
+use crate::net::unix::UNIX_CHANNEL_ADDRESS;
+
+/// A socket a remoting server accepts connections on.
+pub enum ServerListener {
+    Tcp(TcpListener),
+    /// Connections over a Unix domain socket are always plaintext, access to them is controlled
+    /// by the permissions of the socket file.
+    #[cfg(unix)]
+    Unix {
+        listener: UnixListener,
+        path: String,
+    },
+}
+
+impl From<TcpListener> for ServerListener {
+    fn from(listener: TcpListener) -> Self {
+        ServerListener::Tcp(listener)
+    }
+}
+
+/// An accepted connection with the local and remote address of its channel.
+pub struct AcceptedStream {
+    pub stream: ConnectionStream,
+    pub local_addr: SocketAddr,
+    pub remote_addr: SocketAddr,
+}
+
+impl ServerListener {
+    /// The address to log, `host:port` or `unix://path`.
+    pub fn describe(&self) -> String {
+        match self {
+            ServerListener::Tcp(listener) => listener
+                .local_addr()
+                .map_or_else(|_| "tcp".to_string(), |addr| addr.to_string()),
+            #[cfg(unix)]
+            ServerListener::Unix { path, .. } => format!("{}{}", UNIX_ADDRESS_PREFIX, path),
+        }
+    }
+
+    pub async fn accept(&self) -> io::Result<AcceptedStream> {
+        match self {
+            ServerListener::Tcp(listener) => {
+                let (socket, remote_addr) = listener.accept().await?;
+                socket.set_nodelay(true)?;
+                Ok(AcceptedStream {
+                    local_addr: socket.local_addr()?,
+                    stream: socket.into(),
+                    remote_addr,
+                })
+            }
+            #[cfg(unix)]
+            ServerListener::Unix { listener, .. } => {
+                let (socket, _) = listener.accept().await?;
+                Ok(AcceptedStream {
+                    stream: socket.into(),
+                    local_addr: UNIX_CHANNEL_ADDRESS,
+                    remote_addr: unix::next_unix_channel_address(),
+                })
+            }
+        }
+    }
+
+    /// Accepts a connection on whichever of `listeners` gets one first.
+    pub async fn accept_any(listeners: &[ServerListener]) -> io::Result<AcceptedStream> {
+        let (accepted, _, _) =
+            select_all(listeners.iter().map(|listener| Box::pin(listener.accept()))).await;
+        accepted
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ServerListener {
+    fn drop(&mut self) {
+        if let ServerListener::Unix { path, .. } = self {
+            let _ = unix::remove_stale_socket(Path::new(path));
+        }
+    }
+}
+
+#[cfg(unix)]
+impl ServerListener {
+    /// Listens on the Unix domain socket at `path`, replacing the socket file a previous process
+    /// left behind. The file is removed when the listener is dropped.
+    pub fn bind_unix(path: &str) -> io::Result<ServerListener> {
+        Ok(ServerListener::Unix {
+            listener: unix::bind(path)?,
+            path: path.to_string(),
+        })
+    }
+}
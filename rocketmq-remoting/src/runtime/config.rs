@@ -18,3 +18,4 @@
 pub mod client_config;
 mod net_system_config;
 mod server_config;
+pub mod tls_system_config;
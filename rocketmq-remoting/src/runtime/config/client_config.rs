@@ -18,9 +18,11 @@
 use lazy_static::lazy_static;
 
 use crate::runtime::config::net_system_config::NetSystemConfig;
+use crate::runtime::config::tls_system_config::TlsSystemConfig;
 
 lazy_static! {
     static ref NET_SYSTEM_CONFIG: NetSystemConfig = NetSystemConfig::new();
+    static ref TLS_SYSTEM_CONFIG: TlsSystemConfig = TlsSystemConfig::new();
 }
 
 pub struct TokioClientConfig {
@@ -36,7 +38,15 @@ pub struct TokioClientConfig {
     pub client_socket_rcv_buf_size: i32,
     pub client_pooled_byte_buf_allocator_enable: bool,
     pub client_close_socket_if_timeout: bool,
-    //pub use_tls: bool,
+    pub use_tls: bool,
+    /// Skip verifying the server certificate, only for testing.
+    pub tls_test_mode_enable: bool,
+    /// PEM certificate chain presented to servers requiring mutual authentication.
+    pub tls_client_cert_path: Option<String>,
+    /// PEM private key of `tls_client_cert_path`.
+    pub tls_client_key_path: Option<String>,
+    /// PEM CA certificates used to verify server certificates.
+    pub tls_client_trust_cert_path: Option<String>,
     pub socks_proxy_config: String,
     pub write_buffer_high_water_mark: i32,
     pub write_buffer_low_water_mark: i32,
@@ -45,6 +55,9 @@ pub struct TokioClientConfig {
     pub max_reconnect_interval_time_seconds: i64,
     pub enable_reconnect_for_go_away: bool,
     pub enable_transparent_retry: bool,
+    /// Log the requests whose response takes longer than this, `0` disables the log. Off by
+    /// default since long polling requests are held by the server on purpose.
+    pub slow_request_threshold_millis: u64,
 }
 
 impl Default for TokioClientConfig {
@@ -61,7 +74,11 @@ impl Default for TokioClientConfig {
             client_socket_rcv_buf_size: NET_SYSTEM_CONFIG.socket_rcvbuf_size,
             client_pooled_byte_buf_allocator_enable: false,
             client_close_socket_if_timeout: NET_SYSTEM_CONFIG.client_close_socket_if_timeout,
-            //use_tls: TlsSystemConfig::is_tls_enabled(),
+            use_tls: TLS_SYSTEM_CONFIG.tls_enable,
+            tls_test_mode_enable: TLS_SYSTEM_CONFIG.tls_test_mode_enable,
+            tls_client_cert_path: TLS_SYSTEM_CONFIG.tls_client_cert_path.clone(),
+            tls_client_key_path: TLS_SYSTEM_CONFIG.tls_client_key_path.clone(),
+            tls_client_trust_cert_path: TLS_SYSTEM_CONFIG.tls_client_trust_cert_path.clone(),
             socks_proxy_config: "{}".to_string(),
             write_buffer_high_water_mark: NET_SYSTEM_CONFIG.write_buffer_high_water_mark_value,
             write_buffer_low_water_mark: NET_SYSTEM_CONFIG.write_buffer_low_water_mark,
@@ -70,6 +87,7 @@ impl Default for TokioClientConfig {
             max_reconnect_interval_time_seconds: 60,
             enable_reconnect_for_go_away: true,
             enable_transparent_retry: true,
+            slow_request_threshold_millis: 0,
         }
     }
 }
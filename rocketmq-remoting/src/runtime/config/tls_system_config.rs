@@ -0,0 +1,49 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::env;
+
+pub const TLS_ENABLE: &str = "tls.enable";
+pub const TLS_TEST_MODE_ENABLE: &str = "tls.test.mode.enable";
+pub const TLS_CLIENT_CERT_PATH: &str = "tls.client.certPath";
+pub const TLS_CLIENT_KEY_PATH: &str = "tls.client.keyPath";
+pub const TLS_CLIENT_TRUST_CERT_PATH: &str = "tls.client.trustCertPath";
+
+pub(crate) struct TlsSystemConfig {
+    pub(crate) tls_enable: bool,
+    pub(crate) tls_test_mode_enable: bool,
+    pub(crate) tls_client_cert_path: Option<String>,
+    pub(crate) tls_client_key_path: Option<String>,
+    pub(crate) tls_client_trust_cert_path: Option<String>,
+}
+
+impl TlsSystemConfig {
+    pub(crate) fn new() -> TlsSystemConfig {
+        TlsSystemConfig {
+            tls_enable: env::var(TLS_ENABLE)
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            tls_test_mode_enable: env::var(TLS_TEST_MODE_ENABLE)
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            tls_client_cert_path: env::var(TLS_CLIENT_CERT_PATH).ok(),
+            tls_client_key_path: env::var(TLS_CLIENT_KEY_PATH).ok(),
+            tls_client_trust_cert_path: env::var(TLS_CLIENT_TRUST_CERT_PATH).ok(),
+        }
+    }
+}
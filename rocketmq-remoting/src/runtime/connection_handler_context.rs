@@ -14,7 +14,6 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use futures_util::SinkExt;
 use rocketmq_common::WeakCellWrapper;
 use tracing::error;
 
@@ -44,11 +43,8 @@ impl ConnectionHandlerContextWrapper {
     }
 
     pub async fn write(&mut self, cmd: RemotingCommand) {
-        match self.channel.connection_mut().writer.send(cmd).await {
-            Ok(_) => {}
-            Err(error) => {
-                error!("send response failed: {}", error);
-            }
+        if let Err(error) = self.channel.write_and_flush(cmd).await {
+            error!("send response failed: {}", error);
         }
     }
 
@@ -24,6 +24,7 @@ pub mod compaction_append_msg_callback;
 pub(crate) mod dispatch_request;
 pub mod flush_manager;
 pub mod get_message_result;
+pub mod index_query;
 pub mod message_arriving_listener;
 pub mod message_result;
 pub mod message_status_enum;
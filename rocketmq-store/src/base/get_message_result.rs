@@ -16,6 +16,8 @@
  */
 use std::fmt;
 
+use bytes::Bytes;
+
 use crate::base::message_status_enum::GetMessageStatus;
 use crate::base::select_result::SelectMappedBufferResult;
 
@@ -24,9 +26,10 @@ use crate::base::select_result::SelectMappedBufferResult;
 pub struct GetMessageResult {
     /// The list of mapped buffer results.
     message_mapped_list: Vec<SelectMappedBufferResult>,
-    /// The list of message buffers.
-    /* message_buffer_list: Vec<Bytes>, *//* Using Vec<u8> as a simplified representation of
-     * ByteBuffer in Rust */
+    /// The list of messages read from the tiered storage. They always precede the messages of
+    /// `message_mapped_list` in queue order, because only files older than the local ones are
+    /// offloaded.
+    message_buffer_list: Vec<Bytes>,
     /// The list of message queue offsets.
     message_queue_offset: Vec<u64>,
     /// The status of getting the message.
@@ -72,7 +75,6 @@ impl GetMessageResult {
     pub fn new() -> Self {
         GetMessageResult {
             message_mapped_list: Vec::with_capacity(100),
-            // message_buffer_list: Vec::with_capacity(100),
             message_queue_offset: Vec::with_capacity(100),
             ..Default::default()
         }
@@ -81,7 +83,6 @@ impl GetMessageResult {
     pub fn new_result_size(result_size: usize) -> Self {
         GetMessageResult {
             message_mapped_list: Vec::with_capacity(result_size),
-            //  message_buffer_list: Vec::with_capacity(result_size),
             message_queue_offset: Vec::with_capacity(result_size),
             ..Default::default()
         }
@@ -123,9 +124,9 @@ impl GetMessageResult {
     pub fn set_message_mapped_list(&mut self, message_mapped_list: Vec<SelectMappedBufferResult>) {
         self.message_mapped_list = message_mapped_list;
     }
-    /*    pub fn set_message_buffer_list(&mut self, message_buffer_list: Vec<Bytes>) {
+    pub fn set_message_buffer_list(&mut self, message_buffer_list: Vec<Bytes>) {
         self.message_buffer_list = message_buffer_list;
-    }*/
+    }
     pub fn set_message_queue_offset(&mut self, message_queue_offset: Vec<u64>) {
         self.message_queue_offset = message_queue_offset;
     }
@@ -166,17 +167,27 @@ impl GetMessageResult {
         queue_offset: u64,
         batch_num: i32,
     ) {
-        let slice = maped_buffer.get_buffer();
-        // self.message_buffer_list.push(Bytes::copy_from_slice(slice));
         self.buffer_total_size += maped_buffer.size;
         self.message_count += batch_num;
         self.message_queue_offset.push(queue_offset);
         self.message_mapped_list.push(maped_buffer);
     }
 
+    /// Adds a message that is not backed by a local mapped file.
+    pub fn add_message_bytes(&mut self, message: Bytes, queue_offset: u64, batch_num: i32) {
+        self.buffer_total_size += message.len() as i32;
+        self.message_count += batch_num;
+        self.message_queue_offset.push(queue_offset);
+        self.message_buffer_list.push(message);
+    }
+
     pub fn message_mapped_list(&self) -> &[SelectMappedBufferResult] {
         self.message_mapped_list.as_slice()
     }
+
+    pub fn message_buffer_list(&self) -> &[Bytes] {
+        self.message_buffer_list.as_slice()
+    }
 }
 
 #[cfg(test)]
@@ -217,7 +228,7 @@ mod tests {
         let commercial_size_per_msg = 20;
         let cold_data_sum = 500;
 
-        //result.set_message_buffer_list(buffer_list);
+        result.set_message_buffer_list(buffer_list);
         result.set_message_queue_offset(queue_offset);
         result.set_status(status.clone());
         result.set_next_begin_offset(next_begin_offset);
@@ -231,7 +242,7 @@ mod tests {
         result.set_cold_data_sum(cold_data_sum);
 
         assert_eq!(result.message_mapped_list.len(), 0);
-        // assert_eq!(result.message_buffer_list.len(), 10);
+        assert_eq!(result.message_buffer_list.len(), 10);
         assert_eq!(result.message_queue_offset.len(), 10);
         assert_eq!(result.status, status);
         assert_eq!(result.next_begin_offset, next_begin_offset);
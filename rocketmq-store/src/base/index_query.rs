@@ -0,0 +1,156 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+
+use rocketmq_common::common::message::MessageConst;
+
+/// Which message property an index query matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndexKeyType {
+    /// Business keys, the `KEYS` property.
+    #[default]
+    Key,
+    /// Client message ids, the `UNIQ_KEY` property.
+    UniqKey,
+}
+
+/// A query of the message index.
+///
+/// `key` may hold several keys separated by [`MessageConst::KEY_SEPARATOR`]; messages matching
+/// any of them are returned. Results are ordered by descending commit log offset, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct IndexQuery {
+    pub topic: String,
+    pub key: String,
+    pub key_type: IndexKeyType,
+    pub max_num: i32,
+    pub begin_timestamp: i64,
+    pub end_timestamp: i64,
+    /// Where the previous page stopped, `None` for the first page.
+    pub cursor: Option<IndexQueryCursor>,
+}
+
+impl IndexQuery {
+    pub fn new(
+        topic: impl Into<String>,
+        key: impl Into<String>,
+        max_num: i32,
+        begin_timestamp: i64,
+        end_timestamp: i64,
+    ) -> Self {
+        Self {
+            topic: topic.into(),
+            key: key.into(),
+            max_num,
+            begin_timestamp,
+            end_timestamp,
+            ..Default::default()
+        }
+    }
+
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys = self
+            .key
+            .split(MessageConst::KEY_SEPARATOR)
+            .filter(|key| !key.is_empty())
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+}
+
+/// Position of a paginated index query: the next page only holds messages stored before
+/// `before_phy_offset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexQueryCursor {
+    pub before_phy_offset: i64,
+}
+
+impl IndexQueryCursor {
+    const PREFIX: &'static str = "idx-";
+
+    /// Encodes the cursor as an opaque continuation token.
+    pub fn encode(&self) -> String {
+        format!("{}{:x}", Self::PREFIX, self.before_phy_offset)
+    }
+
+    /// Decodes a continuation token, `None` when it was not produced by [`encode`](Self::encode).
+    pub fn decode(token: &str) -> Option<Self> {
+        let before_phy_offset = i64::from_str_radix(token.strip_prefix(Self::PREFIX)?, 16).ok()?;
+        (before_phy_offset >= 0).then_some(Self { before_phy_offset })
+    }
+}
+
+/// Statistics of one index file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexFileStats {
+    pub file_name: String,
+    pub begin_timestamp: i64,
+    pub end_timestamp: i64,
+    pub begin_phy_offset: i64,
+    pub end_phy_offset: i64,
+    pub hash_slot_count: i32,
+    pub hash_slot_num: usize,
+    pub index_count: i32,
+    pub index_num: usize,
+    pub write_full: bool,
+}
+
+impl fmt::Display for IndexFileStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "IndexFileStats [fileName={}, beginTimestamp={}, endTimestamp={}, beginPhyOffset={}, \
+             endPhyOffset={}, hashSlotCount={}/{}, indexCount={}/{}, writeFull={}]",
+            self.file_name,
+            self.begin_timestamp,
+            self.end_timestamp,
+            self.begin_phy_offset,
+            self.end_phy_offset,
+            self.hash_slot_count,
+            self.hash_slot_num,
+            self.index_count,
+            self.index_num,
+            self.write_full
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = IndexQueryCursor {
+            before_phy_offset: 4096,
+        };
+        assert_eq!(cursor.encode(), "idx-1000");
+        assert_eq!(IndexQueryCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(IndexQueryCursor::decode("1000"), None);
+        assert_eq!(IndexQueryCursor::decode("idx-zz"), None);
+        assert_eq!(IndexQueryCursor::decode("idx--1"), None);
+    }
+
+    #[test]
+    fn keys_are_split_and_deduplicated() {
+        let query = IndexQuery::new("TopicTest", "order-2 order-1  order-2", 32, 0, i64::MAX);
+        assert_eq!(query.keys(), vec!["order-1", "order-2"]);
+    }
+}
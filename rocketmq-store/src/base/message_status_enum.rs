@@ -44,6 +44,7 @@ pub enum PutMessageStatus {
     WheelTimerFlowControl,
     WheelTimerMsgIllegal,
     WheelTimerNotEnable,
+    TopicStorageQuotaExceeded,
 }
 
 impl std::fmt::Display for PutMessageStatus {
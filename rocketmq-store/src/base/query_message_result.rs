@@ -17,6 +17,7 @@
 use bytes::Bytes;
 use bytes::BytesMut;
 
+use crate::base::index_query::IndexQueryCursor;
 use crate::base::select_result::SelectMappedBufferResult;
 
 #[derive(Default)]
@@ -25,6 +26,8 @@ pub struct QueryMessageResult {
     pub index_last_update_timestamp: i64,
     pub index_last_update_phyoffset: i64,
     pub buffer_total_size: i32,
+    /// Where the next page starts, `None` when no message is left.
+    pub next_cursor: Option<IndexQueryCursor>,
 }
 
 impl QueryMessageResult {
@@ -35,9 +38,7 @@ impl QueryMessageResult {
 
         let mut bytes_mut = BytesMut::with_capacity(self.buffer_total_size as usize);
         for msg in self.message_maped_list.iter() {
-            let data = &msg.mapped_file.as_ref().unwrap().get_mapped_file()
-                [msg.start_offset as usize..(msg.start_offset + msg.size as u64) as usize];
-            bytes_mut.extend_from_slice(data);
+            bytes_mut.extend_from_slice(msg.get_buffer());
         }
         Some(bytes_mut.freeze())
     }
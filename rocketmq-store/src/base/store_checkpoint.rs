@@ -88,7 +88,8 @@ impl StoreCheckpoint {
     }
 
     pub fn flush(&self) -> std::io::Result<()> {
-        let mut buffer = &mut self.mmap.lock()[..8];
+        let mut mmap = self.mmap.lock();
+        let mut buffer = &mut mmap[..40];
         buffer.write_all(
             self.physic_msg_timestamp
                 .load(Ordering::Relaxed)
@@ -119,7 +120,7 @@ impl StoreCheckpoint {
                 .to_be_bytes()
                 .as_ref(),
         )?;
-        self.mmap.lock().flush()?;
+        mmap.flush()?;
         Ok(())
     }
 
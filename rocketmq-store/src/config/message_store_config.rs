@@ -205,6 +205,9 @@ pub struct MessageStoreConfig {
     pub enable_rocksdb_log: bool,
     pub topic_queue_lock_num: usize,
     pub max_filter_message_size: i32,
+    pub tiered_store_enable: bool,
+    pub tiered_store_file_path: Option<String>,
+    pub tiered_store_upload_interval: u64,
 }
 
 impl Default for MessageStoreConfig {
@@ -391,6 +394,9 @@ impl Default for MessageStoreConfig {
             enable_rocksdb_log: false,
             topic_queue_lock_num: 32,
             max_filter_message_size: 16000,
+            tiered_store_enable: false,
+            tiered_store_file_path: None,
+            tiered_store_upload_interval: 30_000,
         }
     }
 }
@@ -406,6 +412,17 @@ impl MessageStoreConfig {
         self.store_path_commit_log.clone().unwrap()
     }
 
+    /// Directory of the local tiered storage backend, `{storePathRootDir}/tiered` by default.
+    pub fn get_tiered_store_file_path(&self) -> String {
+        match self.tiered_store_file_path.as_ref() {
+            Some(path) => path.clone(),
+            None => PathBuf::from(self.store_path_root_dir.clone())
+                .join("tiered")
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
     pub fn is_enable_rocksdb_store(&self) -> bool {
         self.store_type == StoreType::RocksDB
     }
@@ -1090,6 +1107,18 @@ impl MessageStoreConfig {
             "maxFilterMessageSize".to_string(),
             self.max_filter_message_size.to_string(),
         );
+        properties.insert(
+            "tieredStoreEnable".to_string(),
+            self.tiered_store_enable.to_string(),
+        );
+        properties.insert(
+            "tieredStoreFilePath".to_string(),
+            self.get_tiered_store_file_path(),
+        );
+        properties.insert(
+            "tieredStoreUploadInterval".to_string(),
+            self.tiered_store_upload_interval.to_string(),
+        );
         properties
     }
 }
@@ -15,10 +15,15 @@
  * limitations under the License.
  */
 
-const MIN_EXT_UNIT_SIZE: i16 = 2  // size, 32k max
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+
+pub(crate) const MIN_EXT_UNIT_SIZE: i16 = 2  // size, 32k max
  + 8 * 2 // msg time + tagCode
   + 2; // bitMapSize
-const MAX_EXT_UNIT_SIZE: i16 = i16::MAX;
+pub(crate) const MAX_EXT_UNIT_SIZE: i16 = i16::MAX;
 
 #[derive(Clone, Default)]
 pub struct CqExtUnit {
@@ -62,3 +67,61 @@ impl CqExtUnit {
         &self.filter_bit_map
     }
 }
+
+impl CqExtUnit {
+    /// Serializes the unit as `size | tags_code | msg_store_time | bit_map_size | bit_map`.
+    pub fn write(&self) -> Bytes {
+        let mut buffer = BytesMut::with_capacity(self.size as usize);
+        buffer.put_i16(self.size);
+        buffer.put_i64(self.tags_code);
+        buffer.put_i64(self.msg_store_time);
+        buffer.put_i16(self.bit_map_size);
+        if let Some(filter_bit_map) = self.filter_bit_map.as_ref() {
+            buffer.put_slice(filter_bit_map);
+        }
+        buffer.freeze()
+    }
+
+    /// Fills this unit from `buffer`, returns false if the buffer does not hold a valid unit.
+    pub fn read(&mut self, mut buffer: Bytes) -> bool {
+        if buffer.remaining() < MIN_EXT_UNIT_SIZE as usize {
+            return false;
+        }
+        let size = buffer.get_i16();
+        if size < MIN_EXT_UNIT_SIZE {
+            return false;
+        }
+        self.size = size;
+        self.tags_code = buffer.get_i64();
+        self.msg_store_time = buffer.get_i64();
+        self.bit_map_size = buffer.get_i16();
+        if self.bit_map_size < 1 {
+            self.filter_bit_map = None;
+            return true;
+        }
+        if buffer.remaining() < self.bit_map_size as usize {
+            return false;
+        }
+        self.filter_bit_map = Some(buffer.split_to(self.bit_map_size as usize).to_vec());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read_round_trip() {
+        let unit = CqExtUnit::new(12, 1000, Some(vec![1, 2, 3]));
+        let bytes = unit.write();
+        assert_eq!(bytes.len(), unit.size() as usize);
+
+        let mut read = CqExtUnit::default();
+        assert!(read.read(bytes));
+        assert_eq!(read.size(), unit.size());
+        assert_eq!(read.tags_code(), 12);
+        assert_eq!(read.msg_store_time(), 1000);
+        assert_eq!(read.filter_bit_map(), &Some(vec![1, 2, 3]));
+    }
+}
@@ -25,6 +25,7 @@ use std::sync::Arc;
 use log::warn;
 use parking_lot::RwLock;
 use rocketmq_common::UtilAll::offset_to_file_name;
+use tracing::error;
 use tracing::info;
 
 use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
@@ -102,7 +103,18 @@ impl MappedFileQueue {
     }
 
     pub fn check_self(&self) {
-        println!("mapped_file_queue check self unimplemented")
+        let mapped_files = self.mapped_files.read();
+        for pair in mapped_files.windows(2) {
+            let (pre, cur) = (&pair[0], &pair[1]);
+            if cur.get_file_from_offset() - pre.get_file_from_offset() != self.mapped_file_size {
+                error!(
+                    "[BUG]The mappedFile queue's data is damaged, the adjacent mappedFile's \
+                     offset don't match. pre file {}, cur file {}",
+                    pre.get_file_name(),
+                    cur.get_file_name()
+                );
+            }
+        }
     }
 
     pub fn do_load(&mut self, files: Vec<std::path::PathBuf>) -> bool {
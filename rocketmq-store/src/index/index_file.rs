@@ -18,15 +18,15 @@
 use std::sync::Arc;
 
 use bytes::Buf;
-use bytes::Bytes;
 use rocketmq_common::common::hasher::string_hasher::JavaStringHasher;
 
+use crate::base::index_query::IndexFileStats;
 use crate::index::index_header::IndexHeader;
 use crate::index::index_header::INDEX_HEADER_SIZE;
 use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
 use crate::log_file::mapped_file::MappedFile;
 
-const HASH_SLOT_SIZE: usize = 4;
+pub(crate) const HASH_SLOT_SIZE: usize = 4;
 
 /**
  * Each index's store unit. Format:
@@ -41,8 +41,8 @@ const HASH_SLOT_SIZE: usize = 4;
  * Each index's store unit. Size:
  * Key HashCode(4) + Physical Offset(8) + Time Diff(4) + Next Index Pos(4) = 20 Bytes
  */
-const INDEX_SIZE: usize = 20;
-const INVALID_INDEX: i32 = 0;
+pub(crate) const INDEX_SIZE: usize = 20;
+pub(crate) const INVALID_INDEX: i32 = 0;
 
 pub struct IndexFile {
     hash_slot_num: usize,
@@ -137,11 +137,10 @@ impl IndexFile {
             let slot_pos = key_hash as usize % self.hash_slot_num;
             let abs_slot_pos = INDEX_HEADER_SIZE + slot_pos * HASH_SLOT_SIZE;
 
-            let mut buffer = self
+            let mut slot_value = self
                 .mapped_file
-                .get_data(abs_slot_pos, abs_slot_pos + 4)
-                .unwrap();
-            let mut slot_value = buffer.get_i32();
+                .get_bytes(abs_slot_pos, HASH_SLOT_SIZE)
+                .map_or(INVALID_INDEX, |mut buffer| buffer.get_i32());
             if slot_value <= INVALID_INDEX || slot_value > self.index_header.get_index_count() {
                 slot_value = INVALID_INDEX;
             }
@@ -160,30 +159,15 @@ impl IndexFile {
                 + self.hash_slot_num * HASH_SLOT_SIZE
                 + self.index_header.get_index_count() as usize * INDEX_SIZE;
 
-            self.mapped_file.append_message_offset_length(
-                &Bytes::copy_from_slice(&key_hash.to_be_bytes()),
-                abs_index_pos,
-                4,
-            );
-            self.mapped_file.append_message_offset_length(
-                &Bytes::copy_from_slice(&phy_offset.to_be_bytes()),
-                abs_index_pos,
-                8,
-            );
-            self.mapped_file.append_message_offset_length(
-                &Bytes::copy_from_slice(&(time_diff as i32).to_be_bytes()),
-                abs_index_pos,
-                4,
-            );
-            self.mapped_file.append_message_offset_length(
-                &Bytes::copy_from_slice(&slot_value.to_be_bytes()),
-                abs_index_pos,
-                4,
-            );
-            self.mapped_file.append_message_offset_length(
-                &Bytes::copy_from_slice(&self.index_header.get_index_count().to_be_bytes()),
+            let mut index = [0u8; INDEX_SIZE];
+            index[0..4].copy_from_slice(&key_hash.to_be_bytes());
+            index[4..12].copy_from_slice(&phy_offset.to_be_bytes());
+            index[12..16].copy_from_slice(&(time_diff as i32).to_be_bytes());
+            index[16..20].copy_from_slice(&slot_value.to_be_bytes());
+            self.mapped_file.put_slice(&index, abs_index_pos);
+            self.mapped_file.put_slice(
+                &self.index_header.get_index_count().to_be_bytes(),
                 abs_slot_pos,
-                4,
             );
 
             if self.index_header.get_index_count() <= 1 {
@@ -210,13 +194,7 @@ impl IndexFile {
     }
 
     pub fn index_key_hash_method(&self, key: &str) -> i32 {
-        let key_hash = JavaStringHasher::new().hash_str(key);
-        let key_hash_positive = key_hash.abs();
-        if key_hash_positive < 0 {
-            0
-        } else {
-            key_hash_positive
-        }
+        index_key_hash(key)
     }
 
     pub fn get_begin_timestamp(&self) -> i64 {
@@ -231,6 +209,26 @@ impl IndexFile {
         self.index_header.get_end_phy_offset()
     }
 
+    pub fn get_begin_phy_offset(&self) -> i64 {
+        self.index_header.get_begin_phy_offset()
+    }
+
+    pub fn get_stats(&self) -> IndexFileStats {
+        IndexFileStats {
+            file_name: self.get_file_name(),
+            begin_timestamp: self.index_header.get_begin_timestamp(),
+            end_timestamp: self.index_header.get_end_timestamp(),
+            begin_phy_offset: self.index_header.get_begin_phy_offset(),
+            end_phy_offset: self.index_header.get_end_phy_offset(),
+            hash_slot_count: self.index_header.get_hash_slot_count(),
+            hash_slot_num: self.hash_slot_num,
+            // the first index unit is reserved as the end of the hash chains
+            index_count: (self.index_header.get_index_count() - 1).max(0),
+            index_num: self.index_num,
+            write_full: self.is_write_full(),
+        }
+    }
+
     pub fn is_time_matched(&self, begin: i64, end: i64) -> bool {
         let begin_timestamp = self.index_header.get_begin_timestamp();
         let end_timestamp = self.index_header.get_end_timestamp();
@@ -239,6 +237,8 @@ impl IndexFile {
             || end >= begin_timestamp && end <= end_timestamp
     }
 
+    /// Collects the physical offsets of `key` stored in `[begin, end]`, newest first, skipping
+    /// the offsets not less than `before_phy_offset`.
     pub fn select_phy_offset(
         &self,
         phy_offsets: &mut Vec<i64>,
@@ -246,6 +246,7 @@ impl IndexFile {
         max_num: usize,
         begin: i64,
         end: i64,
+        before_phy_offset: i64,
     ) {
         if !self.mapped_file.hold() {
             return;
@@ -255,15 +256,15 @@ impl IndexFile {
         let slot_pos = key_hash as usize % self.hash_slot_num;
         let abs_slot_pos = INDEX_HEADER_SIZE + slot_pos * HASH_SLOT_SIZE;
 
-        let mut buffer = self
+        let slot_value = self
             .mapped_file
-            .get_data(abs_slot_pos, abs_slot_pos + 4)
-            .unwrap();
-        let slot_value = buffer.get_i32();
+            .get_bytes(abs_slot_pos, HASH_SLOT_SIZE)
+            .map_or(INVALID_INDEX, |mut buffer| buffer.get_i32());
         if slot_value <= INVALID_INDEX
             || slot_value > self.index_header.get_index_count()
             || self.index_header.get_index_count() <= 1
         {
+            self.mapped_file.release();
             return;
         }
 
@@ -273,23 +274,23 @@ impl IndexFile {
                 + self.hash_slot_num * HASH_SLOT_SIZE
                 + next_index_to_read as usize * INDEX_SIZE;
 
-            let key_hash_read = buffer.slice(abs_index_pos..abs_index_pos + 4).get_i32();
-            let phy_offset_read = buffer
-                .slice(abs_index_pos + 4..abs_index_pos + 12)
-                .get_i64();
-            let time_diff = buffer
-                .slice(abs_index_pos + 12..abs_index_pos + 16)
-                .get_i32();
-            let prev_index_read = buffer
-                .slice(abs_index_pos + 16..abs_index_pos + 20)
-                .get_i32();
+            let Some(mut buffer) = self.mapped_file.get_bytes(abs_index_pos, INDEX_SIZE) else {
+                break;
+            };
+            let key_hash_read = buffer.get_i32();
+            let phy_offset_read = buffer.get_i64();
+            let time_diff = buffer.get_i32();
+            let prev_index_read = buffer.get_i32();
 
             if time_diff < 0 {
                 break;
             }
 
             let time_read = self.index_header.get_begin_timestamp() + time_diff as i64 * 1000;
-            if key_hash == key_hash_read && (time_read >= begin && time_read <= end) {
+            if key_hash == key_hash_read
+                && (time_read >= begin && time_read <= end)
+                && phy_offset_read < before_phy_offset
+            {
                 phy_offsets.push(phy_offset_read);
             }
 
@@ -303,5 +304,16 @@ impl IndexFile {
 
             next_index_to_read = prev_index_read;
         }
+        self.mapped_file.release();
+    }
+}
+
+pub(crate) fn index_key_hash(key: &str) -> i32 {
+    let key_hash = JavaStringHasher::new().hash_str(key);
+    let key_hash_positive = key_hash.wrapping_abs();
+    if key_hash_positive < 0 {
+        0
+    } else {
+        key_hash_positive
     }
 }
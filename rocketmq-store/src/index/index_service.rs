@@ -31,6 +31,7 @@ use tracing::info;
 use tracing::warn;
 
 use crate::base::dispatch_request::DispatchRequest;
+use crate::base::index_query::IndexFileStats;
 use crate::base::store_checkpoint::StoreCheckpoint;
 use crate::config::message_store_config::MessageStoreConfig;
 use crate::index::index_file::IndexFile;
@@ -123,6 +124,36 @@ impl IndexService {
         }
     }
 
+    pub fn shutdown(&self) {
+        for index_file in self.index_file_list.read().iter() {
+            index_file.shutdown();
+        }
+    }
+
+    /// Destroys the index files holding entries at or after `phy_offset`. Returns the begin
+    /// physical offset of the first destroyed file, which is where replaying the commit log has
+    /// to start so that no key of the destroyed files is lost.
+    pub fn destroy_files_after(&self, phy_offset: i64) -> Option<i64> {
+        let mut index_file_list_lock = self.index_file_list.write();
+        let retained = index_file_list_lock
+            .iter()
+            .take_while(|index_file| index_file.get_end_phy_offset() < phy_offset)
+            .count();
+        let begin_phy_offset = index_file_list_lock
+            .get(retained)
+            .map(|index_file| index_file.get_begin_phy_offset());
+        for index_file in index_file_list_lock.drain(retained..) {
+            warn!(
+                "destroy index file {} from physical offset {} to {}",
+                index_file.get_file_name(),
+                index_file.get_begin_phy_offset(),
+                index_file.get_end_phy_offset()
+            );
+            index_file.destroy(0);
+        }
+        begin_phy_offset
+    }
+
     pub fn destroy(&self) {
         let mut index_file_list_lock = self.index_file_list.write();
         for index_file in index_file_list_lock.iter() {
@@ -138,45 +169,59 @@ impl IndexService {
         max_num: i32,
         begin: i64,
         end: i64,
+    ) -> QueryOffsetResult {
+        let max_num = max_num.min(self.message_store_config.max_msgs_num_batch as i32);
+        self.query_offset_page(topic, &[key], max_num.max(0) as usize, begin, end, i64::MAX)
+    }
+
+    /// Collects up to `limit` physical offsets of messages indexed under any of `keys`, stored in
+    /// `[begin, end]` at an offset less than `before_phy_offset`.
+    ///
+    /// The offsets are ordered from the newest to the oldest, so the last one is where the next
+    /// page starts.
+    pub fn query_offset_page(
+        &self,
+        topic: &str,
+        keys: &[&str],
+        limit: usize,
+        begin: i64,
+        end: i64,
+        before_phy_offset: i64,
     ) -> QueryOffsetResult {
         let mut phy_offsets = Vec::new();
         let mut index_last_update_timestamp = 0;
         let mut index_last_update_phyoffset = 0;
-        let max_num = max_num.min(self.message_store_config.max_msgs_num_batch as i32);
 
         let index_file_list = self.index_file_list.read();
-        let mut index = 0;
-        if !index_file_list.is_empty() {
-            for f in index_file_list.iter() {
-                let last_file = index == index_file_list.len() - 1;
-                if last_file {
-                    // Assuming IndexFile has methods to get end timestamp and phy offset
-                    index_last_update_timestamp = f.get_end_timestamp();
-                    index_last_update_phyoffset = f.get_end_phy_offset();
-                }
-
-                // Assuming IndexFile has a method to check if the file's timestamps match the query
-                if f.is_time_matched(begin, end) {
-                    // Assuming IndexFile has a method to select physical offsets based on the query
-                    f.select_phy_offset(
-                        &mut phy_offsets,
-                        build_key(topic, key).as_str(),
-                        max_num as usize,
-                        begin,
-                        end,
-                    );
-                }
-
-                if f.get_begin_timestamp() < begin {
-                    break;
-                }
-
-                if phy_offsets.len() as i32 >= max_num {
-                    break;
-                }
-                index += 1;
+        if let Some(last_file) = index_file_list.last() {
+            index_last_update_timestamp = last_file.get_end_timestamp();
+            index_last_update_phyoffset = last_file.get_end_phy_offset();
+        }
+        let keys = keys
+            .iter()
+            .map(|key| build_key(topic, key))
+            .collect::<Vec<_>>();
+        let mut key_offsets = vec![Vec::new(); keys.len()];
+        for f in index_file_list.iter().rev() {
+            if f.get_end_timestamp() < begin {
+                break;
+            }
+            if key_offsets.iter().all(|offsets| offsets.len() >= limit) {
+                break;
             }
+            if f.get_begin_phy_offset() >= before_phy_offset || !f.is_time_matched(begin, end) {
+                continue;
+            }
+            for (key, offsets) in keys.iter().zip(key_offsets.iter_mut()) {
+                f.select_phy_offset(offsets, key, limit, begin, end, before_phy_offset);
+            }
+        }
+        for offsets in key_offsets {
+            phy_offsets.extend(offsets);
         }
+        phy_offsets.sort_unstable_by(|a, b| b.cmp(a));
+        phy_offsets.dedup();
+        phy_offsets.truncate(limit);
         QueryOffsetResult::new(
             phy_offsets,
             index_last_update_timestamp,
@@ -184,6 +229,14 @@ impl IndexService {
         )
     }
 
+    pub fn get_index_file_stats(&self) -> Vec<IndexFileStats> {
+        self.index_file_list
+            .read()
+            .iter()
+            .map(|index_file| index_file.get_stats())
+            .collect()
+    }
+
     pub fn build_index(&self, dispatch_request: &DispatchRequest) {
         let index_file = self.retry_get_and_create_index_file();
         match index_file {
@@ -364,10 +417,131 @@ impl IndexService {
     }
 }
 
-fn build_key(topic: &str, key: &str) -> String {
+pub(crate) fn build_key(topic: &str, key: &str) -> String {
     let mut keys = String::new();
     keys.push_str(topic);
     keys.push('#');
     keys.push_str(key);
     keys
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
+
+    use super::*;
+    use crate::base::index_query::IndexKeyType;
+    use crate::base::index_query::IndexQuery;
+    use crate::config::message_store_config::MessageStoreConfig;
+    use crate::log_file::MessageStore;
+    use crate::message_store::default_message_store::DefaultMessageStore;
+    use crate::store_path_config_helper::get_store_path_consume_queue;
+    use crate::test_util::create_store;
+    use crate::test_util::TOPIC;
+
+    #[tokio::test]
+    async fn query_pages_by_keys_and_unique_keys() {
+        let store = create_store(6);
+        fs::remove_dir_all(get_store_path_consume_queue(
+            &store.config.store_path_root_dir,
+        ))
+        .unwrap();
+        let config = Arc::new(MessageStoreConfig {
+            mapped_file_size_commit_log: 4096,
+            ..store.config.as_ref().clone()
+        });
+        let mut message_store = DefaultMessageStore::new(
+            config,
+            Arc::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        assert!(message_store.load().await);
+        message_store.rebuild_logics(0, |_| {}).unwrap();
+        let offset_of = |index: usize| store.messages[index].0 as u64;
+
+        let mut query = IndexQuery::new(TOPIC, "order-1 order-2 order-4 UNIQ3", 2, 0, i64::MAX);
+        let page = message_store.query_message_page(&query).await.unwrap();
+        let offsets = page
+            .message_maped_list
+            .iter()
+            .map(|sbr| sbr.start_offset)
+            .collect::<Vec<_>>();
+        // UNIQ3 is not a business key, so the message it points to is filtered out
+        assert_eq!(offsets, vec![offset_of(4)]);
+        assert!(page.next_cursor.is_some());
+
+        query.cursor = page.next_cursor;
+        let page = message_store.query_message_page(&query).await.unwrap();
+        let offsets = page
+            .message_maped_list
+            .iter()
+            .map(|sbr| sbr.start_offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![offset_of(2), offset_of(1)]);
+        assert!(page.next_cursor.is_none());
+
+        let mut query = IndexQuery::new(TOPIC, "UNIQ3 order-1", 32, 0, i64::MAX);
+        query.key_type = IndexKeyType::UniqKey;
+        let page = message_store.query_message_page(&query).await.unwrap();
+        assert_eq!(page.message_maped_list.len(), 1);
+        assert_eq!(page.message_maped_list[0].start_offset, offset_of(3));
+        assert_eq!(
+            page.get_message_data().unwrap().len(),
+            store.messages[3].1 as usize
+        );
+
+        let stats = message_store.get_index_file_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].index_count, 12);
+        assert_eq!(stats[0].end_phy_offset, offset_of(5) as i64);
+    }
+
+    #[tokio::test]
+    async fn rebuild_from_mid_file_keeps_earlier_keys() {
+        let store = create_store(6);
+        fs::remove_dir_all(get_store_path_consume_queue(
+            &store.config.store_path_root_dir,
+        ))
+        .unwrap();
+        let config = Arc::new(MessageStoreConfig {
+            mapped_file_size_commit_log: 4096,
+            ..store.config.as_ref().clone()
+        });
+        let mut message_store = DefaultMessageStore::new(
+            config,
+            Arc::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        assert!(message_store.load().await);
+        message_store.rebuild_logics(0, |_| {}).unwrap();
+        let offset_of = |index: usize| store.messages[index].0 as u64;
+
+        // the only index file spans all messages, so it is destroyed and indexed again
+        let progress = message_store
+            .rebuild_logics(store.messages[3].0, |_| {})
+            .unwrap();
+        assert_eq!(progress.dispatched_messages, 3);
+
+        let query = IndexQuery::new(TOPIC, "order-1 order-4", 32, 0, i64::MAX);
+        let page = message_store.query_message_page(&query).await.unwrap();
+        let offsets = page
+            .message_maped_list
+            .iter()
+            .map(|sbr| sbr.start_offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![offset_of(4), offset_of(1)]);
+
+        let stats = message_store.get_index_file_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].index_count, 12);
+        assert_eq!(stats[0].begin_phy_offset, offset_of(0) as i64);
+        assert_eq!(stats[0].end_phy_offset, offset_of(5) as i64);
+    }
+}
@@ -32,6 +32,10 @@ mod queue;
 pub(crate) mod services;
 pub mod stats;
 pub mod store;
+pub mod store_check;
 pub mod store_path_config_helper;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod tiered;
 pub mod timer;
 pub mod utils;
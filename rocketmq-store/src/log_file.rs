@@ -19,6 +19,7 @@ use std::error::Error;
 use std::sync::Arc;
 
 use parking_lot::RwLock;
+use rocketmq_common::common::boundary_type::BoundaryType;
 use rocketmq_common::common::message::message_batch::MessageExtBatch;
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
@@ -26,6 +27,8 @@ use rocketmq_common::TimeUtils::get_current_millis;
 
 use crate::base::dispatch_request::DispatchRequest;
 use crate::base::get_message_result::GetMessageResult;
+use crate::base::index_query::IndexFileStats;
+use crate::base::index_query::IndexQuery;
 use crate::base::message_result::PutMessageResult;
 use crate::base::query_message_result::QueryMessageResult;
 use crate::base::select_result::SelectMappedBufferResult;
@@ -316,6 +319,21 @@ pub trait RocketMQMessageStore: Clone + 'static {
         end_timestamp: i64,
     ) -> Option<QueryMessageResult>;
 
+    /// Query one page of messages by key or unique key asynchronously.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The keys, key type, time range and cursor of the page.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the messages of the page, newest first, and the cursor of the
+    /// next page.
+    async fn query_message_page(&self, query: &IndexQuery) -> Option<QueryMessageResult>;
+
+    /// Get the statistics of every index file, oldest first.
+    fn get_index_file_stats(&self) -> Vec<IndexFileStats>;
+
     /// Select one message by offset asynchronously.
     ///
     /// # Arguments
@@ -391,6 +409,39 @@ pub trait RocketMQMessageStore: Clone + 'static {
         consume_queue_offset: i64,
     ) -> i64;
 
+    /// Look up the consume queue offset of the message stored at `timestamp`.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The message topic.
+    /// * `queue_id` - The queue ID.
+    /// * `timestamp` - The store timestamp to search for.
+    /// * `boundary_type` - `Lower` returns the first message stored at or after `timestamp`,
+    ///   `Upper` the last message stored at or before it.
+    ///
+    /// # Returns
+    ///
+    /// The consume queue offset, clamped to the range of the queue.
+    fn get_offset_in_queue_by_time(
+        &self,
+        topic: &str,
+        queue_id: i32,
+        timestamp: i64,
+        boundary_type: BoundaryType,
+    ) -> i64;
+
+    /// Get the store time of the earliest message in the given queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The message topic.
+    /// * `queue_id` - The queue ID.
+    ///
+    /// # Returns
+    ///
+    /// The store timestamp, or -1 if the queue holds no message.
+    fn get_earliest_message_time_in_queue(&self, topic: &str, queue_id: i32) -> i64;
+
     /// Message store runtime information, which should generally contains various statistical
     /// information.
     ///
@@ -1102,6 +1102,35 @@ impl CommitLog {
         }
     }
 
+    /// Reads the store timestamp of the message at `offset`, returns -1 if the message is no
+    /// longer in the commit log.
+    pub fn pickup_store_timestamp(&self, offset: i64, size: i32) -> i64 {
+        if offset < self.get_min_offset() || offset + size as i64 > self.get_max_offset() {
+            return -1;
+        }
+        let mapped_file_size = self.message_store_config.mapped_file_size_commit_log as i64;
+        let Some(mapped_file) = self
+            .mapped_file_queue
+            .find_mapped_file_by_offset(offset, false)
+        else {
+            return -1;
+        };
+        let pos = (offset % mapped_file_size) as usize;
+        let Some(mut sys_flag) = mapped_file.get_bytes(pos + SYSFLAG_POSITION, 4) else {
+            return -1;
+        };
+        let born_host_length = if sys_flag.get_i32() & MessageSysFlag::BORNHOST_V6_FLAG == 0 {
+            8
+        } else {
+            20
+        };
+        // sys flag, born timestamp and born host precede the store timestamp
+        let store_timestamp_pos = SYSFLAG_POSITION + 4 + 8 + born_host_length;
+        mapped_file
+            .get_bytes(pos + store_timestamp_pos, 8)
+            .map_or(-1, |mut store_timestamp| store_timestamp.get_i64())
+    }
+
     pub fn roll_next_file(&self, offset: i64) -> i64 {
         let mapped_file_size = self.message_store_config.mapped_file_size_commit_log as i64;
         offset + mapped_file_size - (offset % mapped_file_size)
@@ -17,3 +17,5 @@
 
 #[cfg(feature = "local_file_store")]
 pub mod default_message_store;
+#[cfg(feature = "local_file_store")]
+pub mod topic_retention_service;
@@ -30,8 +30,10 @@ use std::time::Instant;
 
 use bytes::Buf;
 use rocketmq_common::common::attribute::cleanup_policy::CleanupPolicy;
+use rocketmq_common::common::boundary_type::BoundaryType;
 use rocketmq_common::common::message::message_batch::MessageExtBatch;
 use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::MessageTrait;
 use rocketmq_common::common::mix_all::is_lmq;
 use rocketmq_common::common::mix_all::is_sys_consumer_group_for_no_cold_read_limit;
 use rocketmq_common::common::mix_all::MULTI_DISPATCH_QUEUE_SPLITTER;
@@ -62,6 +64,10 @@ use crate::base::allocate_mapped_file_service::AllocateMappedFileService;
 use crate::base::commit_log_dispatcher::CommitLogDispatcher;
 use crate::base::dispatch_request::DispatchRequest;
 use crate::base::get_message_result::GetMessageResult;
+use crate::base::index_query::IndexFileStats;
+use crate::base::index_query::IndexKeyType;
+use crate::base::index_query::IndexQuery;
+use crate::base::index_query::IndexQueryCursor;
 use crate::base::message_arriving_listener::MessageArrivingListener;
 use crate::base::message_result::PutMessageResult;
 use crate::base::message_status_enum::GetMessageStatus;
@@ -86,18 +92,30 @@ use crate::log_file::commit_log::CommitLog;
 use crate::log_file::mapped_file::MappedFile;
 use crate::log_file::MessageStore;
 use crate::log_file::MAX_PULL_MSG_SIZE;
+use crate::message_store::topic_retention_service::TopicRetentionService;
 use crate::queue::build_consume_queue::CommitLogDispatcherBuildConsumeQueue;
 use crate::queue::local_file_consume_queue_store::ConsumeQueueStore;
 use crate::queue::ArcConsumeQueue;
 use crate::queue::ConsumeQueueStoreTrait;
 use crate::stats::broker_stats_manager::BrokerStatsManager;
 use crate::store::running_flags::RunningFlags;
+use crate::store_check::check_result::CheckIssueKind;
+use crate::store_check::commit_log_scanner::CommitLogRecord;
+use crate::store_check::commit_log_scanner::CommitLogScanner;
+use crate::store_check::rebuild_progress::RebuildProgress;
 use crate::store_path_config_helper::get_abort_file;
 use crate::store_path_config_helper::get_store_checkpoint;
 use crate::store_path_config_helper::get_store_path_consume_queue;
+use crate::tiered::file_segment::FileSegmentType;
+use crate::tiered::file_segment::TieredStoreBackend;
+use crate::tiered::posix_backend::PosixTieredStoreBackend;
+use crate::tiered::tiered_store_service::TieredStoreService;
 use crate::timer::timer_message_store::TimerMessageStore;
 use crate::utils::store_util::TOTAL_PHYSICAL_MEMORY_SIZE;
 
+/// Number of dispatched messages between two progress reports of a rebuild.
+const REBUILD_PROGRESS_INTERVAL: u64 = 10_000;
+
 ///Using local files to store message data, which is also the default method.
 pub struct DefaultMessageStore {
     message_store_config: Arc<MessageStoreConfig>,
@@ -130,6 +148,8 @@ pub struct DefaultMessageStore {
     compaction_store: Arc<CompactionStore>,
     timer_message_store: Arc<TimerMessageStore>,
     transient_store_pool: TransientStorePool,
+    topic_retention_service: TopicRetentionService,
+    tiered_store_service: Option<TieredStoreService>,
 }
 
 impl Clone for DefaultMessageStore {
@@ -162,6 +182,8 @@ impl Clone for DefaultMessageStore {
             compaction_store: self.compaction_store.clone(),
             timer_message_store: self.timer_message_store.clone(),
             transient_store_pool: self.transient_store_pool.clone(),
+            topic_retention_service: self.topic_retention_service.clone(),
+            tiered_store_service: self.tiered_store_service.clone(),
         }
     }
 }
@@ -209,6 +231,23 @@ impl DefaultMessageStore {
             consume_queue_store.clone(),
         );
 
+        let topic_retention_service = TopicRetentionService::new(
+            commit_log.clone(),
+            consume_queue_store.clone(),
+            topic_config_table.clone(),
+        );
+
+        let tiered_store_service = if message_store_config.tiered_store_enable {
+            let backend =
+                PosixTieredStoreBackend::new(message_store_config.get_tiered_store_file_path());
+            Some(TieredStoreService::new(
+                message_store_config.clone(),
+                Arc::new(backend),
+            ))
+        } else {
+            None
+        };
+
         ensure_dir_ok(message_store_config.store_path_root_dir.as_str());
         ensure_dir_ok(Self::get_store_path_physic(&message_store_config).as_str());
         ensure_dir_ok(Self::get_store_path_logic(&message_store_config).as_str());
@@ -252,6 +291,8 @@ impl DefaultMessageStore {
             compaction_store: Arc::new(CompactionStore),
             timer_message_store: Arc::new(TimerMessageStore::new_empty()),
             transient_store_pool,
+            topic_retention_service,
+            tiered_store_service,
         }
     }
 
@@ -359,7 +400,15 @@ impl DefaultMessageStore {
     }
 
     pub fn recover_topic_queue_table(&mut self) {
-        let min_phy_offset = self.commit_log.get_min_offset();
+        let mut min_phy_offset = self.commit_log.get_min_offset();
+        // consume queue units of offloaded messages stay valid
+        if let Some(tiered_min_offset) = self
+            .tiered_store_service
+            .as_ref()
+            .and_then(|tiered| tiered.index().min_offset(FileSegmentType::CommitLog, ""))
+        {
+            min_phy_offset = min_phy_offset.min(tiered_min_offset);
+        }
         self.consume_queue_store
             .recover_offset_table(min_phy_offset);
     }
@@ -405,6 +454,232 @@ impl DefaultMessageStore {
         self.dispatcher.dispatch(dispatch_request)
     }
 
+    /// Rebuilds consume queues, consume queue extensions and index files by replaying the
+    /// commit log through the dispatcher chain, starting at `from_offset`.
+    ///
+    /// `from_offset` must be the start of a message; it is raised to the min offset of the
+    /// commit log. Consume queue units and index files at or after `from_offset` are dropped
+    /// before the replay; messages before `from_offset` in the dropped index files are indexed
+    /// again. `progress_listener` is called every `REBUILD_PROGRESS_INTERVAL`
+    /// messages and once at the end, after the store checkpoint has been flushed.
+    ///
+    /// This must be called after [`MessageStore::load`] and before [`MessageStore::start`].
+    pub fn rebuild_logics<F>(
+        &mut self,
+        from_offset: i64,
+        mut progress_listener: F,
+    ) -> std::io::Result<RebuildProgress>
+    where
+        F: FnMut(&RebuildProgress),
+    {
+        let from_offset = from_offset.max(self.commit_log.get_min_offset()).max(0);
+        let mut progress = RebuildProgress {
+            from_offset,
+            current_offset: from_offset,
+            max_offset: self.commit_log.get_max_offset(),
+            ..Default::default()
+        };
+        info!(
+            "rebuild consume queue and index from commit log offset {} to {}",
+            from_offset, progress.max_offset
+        );
+        self.truncate_dirty_logic_files(from_offset);
+        // the destroyed index files may hold keys of messages before `from_offset`, they are
+        // indexed again without being dispatched to the consume queues
+        let index_from_offset = self.index_service.destroy_files_after(from_offset).map_or(
+            from_offset,
+            |begin_phy_offset| {
+                begin_phy_offset
+                    .max(self.commit_log.get_min_offset())
+                    .min(from_offset)
+            },
+        );
+        if index_from_offset < from_offset {
+            info!("rebuild index from commit log offset {}", index_from_offset);
+        }
+
+        let scanner = CommitLogScanner::new(
+            self.message_store_config.get_store_path_commit_log(),
+            self.message_store_config.check_crc_on_recover,
+        );
+        let mut last_store_timestamp = 0i64;
+        let mut corrupted_offset = -1i64;
+        scanner.scan(index_from_offset, |record| {
+            match record {
+                CommitLogRecord::Message(request) if request.commit_log_offset < from_offset => {
+                    if request.commit_log_offset != corrupted_offset
+                        && self.message_store_config.message_index_enable
+                    {
+                        self.index_service.build_index(&request);
+                    }
+                    return true;
+                }
+                CommitLogRecord::Message(request) => {
+                    progress.current_offset = request.commit_log_offset + request.msg_size as i64;
+                    if request.commit_log_offset == corrupted_offset {
+                        progress.skipped_messages += 1;
+                    } else {
+                        self.dispatcher.dispatch(&request);
+                        last_store_timestamp = request.store_timestamp;
+                        progress.dispatched_messages += 1;
+                        if progress
+                            .dispatched_messages
+                            .is_multiple_of(REBUILD_PROGRESS_INTERVAL)
+                        {
+                            progress_listener(&progress);
+                        }
+                    }
+                }
+                CommitLogRecord::Issue(issue) => {
+                    warn!(
+                        "skip corrupted commit log entry while rebuilding, {}",
+                        issue
+                    );
+                    if issue.kind == CheckIssueKind::CommitLogCrcMismatch {
+                        corrupted_offset = issue.offset;
+                    }
+                }
+            }
+            progress.current_offset < progress.max_offset
+        })?;
+
+        for consume_queues in self
+            .consume_queue_store
+            .get_consume_queue_table()
+            .lock()
+            .values()
+        {
+            for consume_queue in consume_queues.values() {
+                while !consume_queue.flush(0) {}
+            }
+        }
+        self.index_service.shutdown();
+        self.recover_topic_queue_table();
+        if let Some(store_checkpoint) = self.store_checkpoint.as_ref() {
+            if last_store_timestamp > 0 {
+                store_checkpoint.set_logics_msg_timestamp(last_store_timestamp as u64);
+                store_checkpoint.set_index_msg_timestamp(last_store_timestamp as u64);
+            }
+            store_checkpoint.flush()?;
+        }
+
+        progress.finished = true;
+        progress_listener(&progress);
+        info!("rebuild consume queue and index finished, {}", progress);
+        Ok(progress)
+    }
+
+    pub fn topic_retention_service(&self) -> &TopicRetentionService {
+        &self.topic_retention_service
+    }
+
+    /// Offloads sealed files to `backend` instead of the local directory backend enabled by
+    /// `tiered_store_enable`. Must be called before [`load`](MessageStore::load).
+    pub fn set_tiered_store_backend(&mut self, backend: Arc<dyn TieredStoreBackend>) {
+        self.tiered_store_service = Some(TieredStoreService::new(
+            self.message_store_config.clone(),
+            backend,
+        ));
+    }
+
+    pub fn tiered_store_service(&self) -> Option<&TieredStoreService> {
+        self.tiered_store_service.as_ref()
+    }
+
+    fn tiered_offset_range_in_queue(&self, topic: &str, queue_id: i32) -> Option<(i64, i64)> {
+        let tiered_store_service = self.tiered_store_service.as_ref()?;
+        Some((
+            tiered_store_service.get_min_offset_in_queue(topic, queue_id)?,
+            tiered_store_service.get_max_offset_in_queue(topic, queue_id)?,
+        ))
+    }
+
+    /// Serves a pull whose consume queue files have been deleted locally, reading both the
+    /// consume queue and the messages from the tiered storage.
+    #[allow(clippy::too_many_arguments)]
+    async fn get_message_from_tiered(
+        &self,
+        tiered_store_service: &TieredStoreService,
+        topic: &str,
+        queue_id: i32,
+        offset: i64,
+        max_msg_nums: i32,
+        max_total_msg_size: i32,
+        message_filter: Option<&dyn MessageFilter>,
+        get_result: &mut GetMessageResult,
+    ) -> (GetMessageStatus, i64) {
+        let max_pull_size = max_total_msg_size.clamp(100, MAX_PULL_MSG_SIZE);
+        let cq_units = tiered_store_service
+            .read_cq_units(topic, queue_id, offset, max_msg_nums)
+            .await;
+        if cq_units.is_empty() {
+            return (GetMessageStatus::OffsetFoundNull, offset);
+        }
+        let mut status = GetMessageStatus::NoMatchedMessage;
+        let mut next_begin_offset = offset;
+        for cq_unit in cq_units {
+            if get_result.message_count() >= max_msg_nums
+                || (get_result.buffer_total_size() > 0
+                    && get_result.buffer_total_size() + cq_unit.size > max_pull_size)
+            {
+                break;
+            }
+            next_begin_offset = cq_unit.queue_offset + cq_unit.batch_num as i64;
+            // blank units filling the head of a consume queue
+            if cq_unit.size <= 0 || cq_unit.size == i32::MAX {
+                continue;
+            }
+            if let Some(filter) = message_filter {
+                if !filter.is_matched_by_consume_queue(cq_unit.get_valid_tags_code_as_long(), None)
+                {
+                    continue;
+                }
+            }
+            let message = match tiered_store_service
+                .read_commit_log(cq_unit.pos, cq_unit.size)
+                .await
+            {
+                Some(message) => Some(message),
+                None => self
+                    .commit_log
+                    .get_message(cq_unit.pos, cq_unit.size)
+                    .and_then(|select_result| select_result.get_bytes()),
+            };
+            let Some(message) = message else {
+                if get_result.buffer_total_size() == 0 {
+                    status = GetMessageStatus::MessageWasRemoving;
+                }
+                continue;
+            };
+            if let Some(filter) = message_filter {
+                if !filter.is_matched_by_commit_log(Some(message.as_ref()), None) {
+                    continue;
+                }
+            }
+            self.store_stats_service
+                .get_message_transferred_msg_count()
+                .fetch_add(cq_unit.batch_num as usize, Ordering::Relaxed);
+            get_result.add_message_bytes(
+                message,
+                cq_unit.queue_offset as u64,
+                cq_unit.batch_num as i32,
+            );
+            status = GetMessageStatus::Found;
+        }
+        (status, next_begin_offset)
+    }
+
+    fn on_topic_message_stored(&self, tracked_topic: Option<String>, result: &PutMessageResult) {
+        if let (Some(topic), Some(append_message_result)) =
+            (tracked_topic, result.append_message_result())
+        {
+            if result.is_ok() {
+                self.topic_retention_service
+                    .on_message_stored(topic.as_str(), append_message_result.wrote_bytes);
+            }
+        }
+    }
+
     pub fn truncate_dirty_logic_files(&mut self, phy_offset: i64) {
         self.consume_queue_store.truncate_dirty(phy_offset);
     }
@@ -580,6 +855,12 @@ impl MessageStore for DefaultMessageStore {
         if !result {
             return result;
         }
+        if let Some(tiered_store_service) = self.tiered_store_service.as_ref() {
+            if let Err(error) = tiered_store_service.load() {
+                error!("load tiered store segment index failed: {}", error);
+                return false;
+            }
+        }
         // load Consume Queue-- init Consume log mapped file queue
         result &= self.consume_queue_store.load();
 
@@ -630,6 +911,46 @@ impl MessageStore for DefaultMessageStore {
 
         self.commit_log.start();
 
+        let topic_retention_service = self.topic_retention_service.clone();
+        let shutdown = self.shutdown.clone();
+        let clean_resource_interval = self.message_store_config.clean_resource_interval as u64;
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_millis(clean_resource_interval));
+            loop {
+                interval.tick().await;
+                if shutdown.load(Ordering::Acquire) {
+                    break;
+                }
+                let service = topic_retention_service.clone();
+                if let Err(error) = tokio::task::spawn_blocking(move || service.run()).await {
+                    error!("topic retention task failed: {}", error);
+                }
+            }
+        });
+
+        if let Some(tiered_store_service) = self.tiered_store_service.clone() {
+            let shutdown = self.shutdown.clone();
+            let upload_interval = self.message_store_config.tiered_store_upload_interval;
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_millis(upload_interval));
+                loop {
+                    interval.tick().await;
+                    if shutdown.load(Ordering::Acquire) {
+                        break;
+                    }
+                    let service = tiered_store_service.clone();
+                    match tokio::task::spawn_blocking(move || service.upload_sealed_files()).await {
+                        Ok(Err(error)) => {
+                            error!("upload sealed files to tiered store failed: {}", error)
+                        }
+                        Err(error) => error!("tiered store upload task failed: {}", error),
+                        _ => {}
+                    }
+                }
+            });
+        }
+
         //self.add_schedule_task();
 
         Ok(())
@@ -694,6 +1015,20 @@ impl MessageStore for DefaultMessageStore {
                 return PutMessageResult::new_default(PutMessageStatus::MessageIllegal);
             }
         }
+        if self
+            .topic_retention_service
+            .is_storage_quota_exceeded(msg.topic())
+        {
+            warn!(
+                "topic {} reached its max storage bytes, reject the message",
+                msg.topic()
+            );
+            return PutMessageResult::new_default(PutMessageStatus::TopicStorageQuotaExceeded);
+        }
+        let tracked_topic = self
+            .topic_retention_service
+            .is_tracked(msg.topic())
+            .then(|| msg.topic().to_string());
         let begin_time = Instant::now();
         //put message to commit log
         let result = self.commit_log.put_message(msg).await;
@@ -704,6 +1039,7 @@ impl MessageStore for DefaultMessageStore {
                 elapsed_time,
             );
         }
+        self.on_topic_message_stored(tracked_topic, &result);
         self.store_stats_service
             .set_put_message_entire_time_max(elapsed_time as u64);
         if !result.is_ok() {
@@ -723,6 +1059,21 @@ impl MessageStore for DefaultMessageStore {
             }
         }
 
+        let topic = msg_batch.message_ext_broker_inner.topic();
+        if self
+            .topic_retention_service
+            .is_storage_quota_exceeded(topic)
+        {
+            warn!(
+                "topic {} reached its max storage bytes, reject the messages",
+                topic
+            );
+            return PutMessageResult::new_default(PutMessageStatus::TopicStorageQuotaExceeded);
+        }
+        let tracked_topic = self
+            .topic_retention_service
+            .is_tracked(topic)
+            .then(|| topic.to_string());
         let begin_time = Instant::now();
         //put message to commit log
         let result = self.commit_log.put_messages(msg_batch).await;
@@ -730,6 +1081,7 @@ impl MessageStore for DefaultMessageStore {
         if elapsed_time > 500 {
             warn!("not in lock eclipse time(ms) {}ms", elapsed_time,);
         }
+        self.on_topic_message_stored(tracked_topic, &result);
         self.store_stats_service
             .set_put_message_entire_time_max(elapsed_time as u64);
         if !result.is_ok() {
@@ -851,6 +1203,20 @@ impl MessageStore for DefaultMessageStore {
         if let Some(consume_queue) = consume_queue {
             min_offset = consume_queue.get_min_offset_in_queue();
             max_offset = consume_queue.get_max_offset_in_queue();
+            // offsets below the local min offset are served by the tiered storage
+            let mut local_min_offset = min_offset;
+            if let Some((tiered_min_offset, tiered_max_offset)) =
+                self.tiered_offset_range_in_queue(topic, queue_id)
+            {
+                if max_offset == 0 {
+                    local_min_offset = tiered_max_offset;
+                    min_offset = tiered_min_offset;
+                    max_offset = tiered_max_offset;
+                } else {
+                    min_offset = min_offset.min(tiered_min_offset);
+                    max_offset = max_offset.max(tiered_max_offset);
+                }
+            }
             if max_offset == 0 {
                 status = GetMessageStatus::NoMessageInQueue;
                 next_begin_offset = self.next_offset_correction(offset, 0);
@@ -863,6 +1229,22 @@ impl MessageStore for DefaultMessageStore {
             } else if offset > max_offset {
                 status = GetMessageStatus::OffsetOverflowBadly;
                 next_begin_offset = self.next_offset_correction(offset, max_offset);
+            } else if offset < local_min_offset {
+                (status, next_begin_offset) = self
+                    .get_message_from_tiered(
+                        self.tiered_store_service.as_ref().unwrap(),
+                        topic,
+                        queue_id,
+                        offset,
+                        max_msg_nums,
+                        max_total_msg_size,
+                        message_filter,
+                        get_result.as_mut().unwrap(),
+                    )
+                    .await;
+                if status == GetMessageStatus::OffsetFoundNull {
+                    next_begin_offset = self.next_offset_correction(offset, local_min_offset);
+                }
             } else {
                 let max_filter_message_size = self
                     .message_store_config
@@ -962,6 +1344,33 @@ impl MessageStore for DefaultMessageStore {
 
                             let select_result = self.commit_log.get_message(offset_py, size_py);
                             if select_result.is_none() {
+                                let message = match self.tiered_store_service.as_ref() {
+                                    Some(tiered) => {
+                                        tiered.read_commit_log(offset_py, size_py).await
+                                    }
+                                    None => None,
+                                };
+                                if let Some(message) = message {
+                                    if message_filter.is_some_and(|filter| {
+                                        !filter
+                                            .is_matched_by_commit_log(Some(message.as_ref()), None)
+                                    }) {
+                                        if get_result_ref.buffer_total_size() == 0 {
+                                            status = GetMessageStatus::NoMatchedMessage;
+                                        }
+                                        continue;
+                                    }
+                                    self.store_stats_service
+                                        .get_message_transferred_msg_count()
+                                        .fetch_add(cq_unit.batch_num as usize, Ordering::Relaxed);
+                                    get_result_ref.add_message_bytes(
+                                        message,
+                                        cq_unit.queue_offset as u64,
+                                        cq_unit.batch_num as i32,
+                                    );
+                                    status = GetMessageStatus::Found;
+                                    continue;
+                                }
                                 if get_result_ref.buffer_total_size() == 0 {
                                     status = GetMessageStatus::MessageWasRemoving;
                                 }
@@ -1156,49 +1565,82 @@ impl MessageStore for DefaultMessageStore {
         begin_timestamp: i64,
         end_timestamp: i64,
     ) -> Option<QueryMessageResult> {
-        let mut query_message_result = QueryMessageResult::default();
-        let mut last_query_msg_time = end_timestamp;
-        for i in 1..3 {
-            let mut query_offset_result = self.index_service.query_offset(
-                topic,
-                key,
-                max_num,
-                begin_timestamp,
-                end_timestamp,
-            );
-            if query_offset_result.get_phy_offsets().is_empty() {
-                break;
-            }
+        self.query_message_page(&IndexQuery::new(
+            topic,
+            key,
+            max_num,
+            begin_timestamp,
+            end_timestamp,
+        ))
+        .await
+    }
 
-            query_offset_result.get_phy_offsets_mut().sort();
-
-            query_message_result.index_last_update_timestamp =
-                query_offset_result.get_index_last_update_timestamp();
-            query_message_result.index_last_update_phyoffset =
-                query_offset_result.get_index_last_update_phyoffset();
-            let phy_offsets = query_offset_result.get_phy_offsets();
-            for m in 0..phy_offsets.len() {
-                let offset = *phy_offsets.get(m).unwrap();
-                let msg = self.look_message_by_offset(offset);
-                if m == 0 {
-                    last_query_msg_time = msg.as_ref().unwrap().store_timestamp;
-                }
-                let result = self.commit_log.get_data_with_option(offset, false);
-                if let Some(sbr) = result {
-                    query_message_result.add_message(sbr);
-                }
+    async fn query_message_page(&self, query: &IndexQuery) -> Option<QueryMessageResult> {
+        let mut query_message_result = QueryMessageResult::default();
+        let keys = query.keys();
+        let max_num = query
+            .max_num
+            .min(self.message_store_config.max_msgs_num_batch as i32);
+        if keys.is_empty() || max_num <= 0 {
+            return Some(query_message_result);
+        }
+        let max_num = max_num as usize;
+        let before_phy_offset = query
+            .cursor
+            .map_or(i64::MAX, |cursor| cursor.before_phy_offset);
+        // one more offset tells whether there is a next page
+        let query_offset_result = self.index_service.query_offset_page(
+            query.topic.as_str(),
+            &keys,
+            max_num + 1,
+            query.begin_timestamp,
+            query.end_timestamp,
+            before_phy_offset,
+        );
+        query_message_result.index_last_update_timestamp =
+            query_offset_result.get_index_last_update_timestamp();
+        query_message_result.index_last_update_phyoffset =
+            query_offset_result.get_index_last_update_phyoffset();
+
+        let phy_offsets = query_offset_result.get_phy_offsets();
+        let page = &phy_offsets[..phy_offsets.len().min(max_num)];
+        for offset in page {
+            // the index only stores key hashes, check the message really matches
+            let Some(msg) = self.look_message_by_offset(*offset) else {
+                continue;
+            };
+            if msg.get_topic() != query.topic {
+                continue;
             }
-            if query_message_result.buffer_total_size > 0 {
-                break;
+            let matched = match query.key_type {
+                IndexKeyType::Key => msg.get_keys().is_some_and(|msg_keys| {
+                    msg_keys
+                        .split(MessageConst::KEY_SEPARATOR)
+                        .any(|msg_key| keys.contains(&msg_key))
+                }),
+                IndexKeyType::UniqKey => msg
+                    .get_property(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX)
+                    .is_some_and(|uniq_key| keys.contains(&uniq_key.as_str())),
+            };
+            if !matched {
+                continue;
             }
-            if last_query_msg_time < begin_timestamp {
-                break;
+            if let Some(sbr) = self.commit_log.get_message(*offset, msg.store_size) {
+                query_message_result.add_message(sbr);
             }
         }
-
+        if phy_offsets.len() > max_num {
+            query_message_result.next_cursor = page.last().map(|offset| IndexQueryCursor {
+                before_phy_offset: *offset,
+            });
+        }
         Some(query_message_result)
     }
 
+    fn get_index_file_stats(&self) -> Vec<IndexFileStats> {
+        self.index_service.get_index_file_stats()
+    }
+
     async fn select_one_message_by_offset(
         &self,
         commit_log_offset: i64,
@@ -1251,18 +1693,34 @@ impl MessageStore for DefaultMessageStore {
         queue_id: i32,
         consume_queue_offset: i64,
     ) -> i64 {
-        let consume_queue = self.find_consume_queue(topic, queue_id);
-        if let Some(consume_queue) = consume_queue {
-            if let Some((_, store_time)) =
-                consume_queue.get_cq_unit_and_store_time(consume_queue_offset)
-            {
-                store_time
-            } else {
-                -1
-            }
-        } else {
-            -1
-        }
+        self.find_consume_queue(topic, queue_id)
+            .and_then(|consume_queue| consume_queue.get(consume_queue_offset))
+            .map_or(-1, |cq_unit| {
+                self.commit_log
+                    .pickup_store_timestamp(cq_unit.pos, cq_unit.size)
+            })
+    }
+
+    fn get_offset_in_queue_by_time(
+        &self,
+        topic: &str,
+        queue_id: i32,
+        timestamp: i64,
+        boundary_type: BoundaryType,
+    ) -> i64 {
+        self.find_consume_queue(topic, queue_id)
+            .map_or(0, |consume_queue| {
+                consume_queue.get_offset_in_queue_by_time_boundary(
+                    timestamp,
+                    boundary_type,
+                    &|offset, size| self.commit_log.pickup_store_timestamp(offset, size),
+                )
+            })
+    }
+
+    fn get_earliest_message_time_in_queue(&self, topic: &str, queue_id: i32) -> i64 {
+        let min_offset = self.get_min_offset_in_queue(topic, queue_id);
+        self.get_message_store_timestamp(topic, queue_id, min_offset)
     }
     fn get_runtime_info(&self) -> HashMap<String, String> {
         self.store_stats_service.get_runtime_info()
@@ -1657,3 +2115,56 @@ impl CorrectLogicOffsetService {
         println!("correct logic offset service run unimplemented!")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store_check::check_result::CheckIssueKind;
+    use crate::store_check::store_checker::StoreChecker;
+    use crate::test_util::create_store;
+    use crate::test_util::TOPIC;
+
+    #[tokio::test]
+    async fn rebuild_logics_replays_commit_log_through_message_store() {
+        let store = create_store(6);
+        fs::remove_dir_all(get_store_path_consume_queue(
+            &store.config.store_path_root_dir,
+        ))
+        .unwrap();
+        let config = Arc::new(MessageStoreConfig {
+            mapped_file_size_commit_log: 4096,
+            ..store.config.as_ref().clone()
+        });
+        let mut message_store = DefaultMessageStore::new(
+            config.clone(),
+            Arc::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        assert!(message_store.load().await);
+
+        let mut reports = Vec::new();
+        let progress = message_store
+            .rebuild_logics(0, |progress| reports.push(*progress))
+            .unwrap();
+        assert!(progress.finished);
+        assert_eq!(progress.dispatched_messages, 6);
+        assert_eq!(progress.skipped_messages, 0);
+        assert_eq!(reports, vec![progress]);
+        assert_eq!(message_store.get_max_offset_in_queue(TOPIC, 0), 6);
+
+        let result = StoreChecker::new(config.clone()).check().unwrap();
+        assert!(result.is_ok(), "{:?}", result.issues);
+        assert_eq!(result.consume_queue_units, 6);
+        assert_eq!(result.index_entries, 12);
+
+        // replaying from the fourth message keeps the earlier units
+        let (from_offset, _) = store.messages[3];
+        let progress = message_store.rebuild_logics(from_offset, |_| {}).unwrap();
+        assert_eq!(progress.dispatched_messages, 3);
+        let result = StoreChecker::new(config).check().unwrap();
+        assert_eq!(result.count_of(CheckIssueKind::ConsumeQueueUnitMissing), 0);
+        assert_eq!(result.consume_queue_units, 6);
+    }
+}
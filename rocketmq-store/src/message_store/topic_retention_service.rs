@@ -0,0 +1,406 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use rocketmq_common::common::attribute::cq_type::CQType;
+use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_common::TopicRetentionUtils::get_max_storage_bytes;
+use rocketmq_common::TopicRetentionUtils::get_message_ttl;
+use tracing::info;
+
+use crate::log_file::commit_log::CommitLog;
+use crate::queue::local_file_consume_queue_store::ConsumeQueueStore;
+use crate::queue::ArcConsumeQueue;
+use crate::queue::ConsumeQueueStoreTrait;
+use crate::queue::CqUnit;
+
+/// Bytes referenced by the consume queues of a topic with a storage quota.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TopicStorageUsage {
+    pub storage_bytes: i64,
+    pub max_storage_bytes: i64,
+}
+
+/// Storage usage of a topic with a quota, with the min offset of each of its consume queues
+/// the usage already accounts for.
+#[derive(Debug, Clone, Default)]
+struct TrackedTopic {
+    usage: TopicStorageUsage,
+    min_offsets: HashMap<i32 /* queue id */, i64>,
+}
+
+/// Enforces the `message.ttl.ms` and `max.storage.bytes` topic attributes on top of the
+/// global `file_reserved_time`.
+///
+/// Expired messages are logically deleted by advancing the min offset of their consume queues;
+/// the commit log files are still reclaimed by the global clean service. The storage usage of
+/// a topic with a quota is summed up once when its quota is first seen, then increased on every
+/// put and decreased by the messages below the min offsets on every [`run`](Self::run), so it
+/// may lag behind messages that are not dispatched yet.
+#[derive(Clone)]
+pub struct TopicRetentionService {
+    commit_log: CommitLog,
+    consume_queue_store: ConsumeQueueStore,
+    topic_config_table: Arc<parking_lot::Mutex<HashMap<String, TopicConfig>>>,
+    tracked_topics: Arc<RwLock<HashMap<String, TrackedTopic>>>,
+}
+
+impl TopicRetentionService {
+    pub fn new(
+        commit_log: CommitLog,
+        consume_queue_store: ConsumeQueueStore,
+        topic_config_table: Arc<parking_lot::Mutex<HashMap<String, TopicConfig>>>,
+    ) -> Self {
+        Self {
+            commit_log,
+            consume_queue_store,
+            topic_config_table,
+            tracked_topics: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn is_storage_quota_exceeded(&self, topic: &str) -> bool {
+        self.tracked_topics
+            .read()
+            .get(topic)
+            .is_some_and(|tracked| tracked.usage.storage_bytes >= tracked.usage.max_storage_bytes)
+    }
+
+    #[inline]
+    pub fn is_tracked(&self, topic: &str) -> bool {
+        self.tracked_topics.read().contains_key(topic)
+    }
+
+    pub fn get_topic_storage_usage(&self, topic: &str) -> Option<TopicStorageUsage> {
+        self.tracked_topics
+            .read()
+            .get(topic)
+            .map(|tracked| tracked.usage)
+    }
+
+    pub fn on_message_stored(&self, topic: &str, wrote_bytes: i32) {
+        if let Some(tracked) = self.tracked_topics.write().get_mut(topic) {
+            tracked.usage.storage_bytes += wrote_bytes as i64;
+        }
+    }
+
+    pub fn run(&self) {
+        self.run_at(get_current_millis() as i64);
+    }
+
+    pub(crate) fn run_at(&self, now: i64) {
+        let topics: Vec<(String, Option<i64>, Option<i64>)> = self
+            .topic_config_table
+            .lock()
+            .iter()
+            .filter_map(|(topic, topic_config)| {
+                let ttl = get_message_ttl(Some(topic_config));
+                let max_storage_bytes = get_max_storage_bytes(Some(topic_config));
+                (ttl.is_some() || max_storage_bytes.is_some())
+                    .then(|| (topic.clone(), ttl, max_storage_bytes))
+            })
+            .collect();
+
+        self.tracked_topics.write().retain(|topic, _| {
+            topics
+                .iter()
+                .any(|(name, _, max_storage_bytes)| name == topic && max_storage_bytes.is_some())
+        });
+        for (topic, ttl, max_storage_bytes) in topics {
+            let Some(consume_queues) = self.consume_queue_store.find_consume_queue_map(&topic)
+            else {
+                continue;
+            };
+            let min_offsets = self
+                .tracked_topics
+                .read()
+                .get(&topic)
+                .map(|tracked| tracked.min_offsets.clone());
+            let mut released_bytes = Some(0);
+            let mut new_min_offsets = HashMap::new();
+            for consume_queue in consume_queues.values() {
+                if consume_queue.get_cq_type() != CQType::SimpleCQ {
+                    continue;
+                }
+                let queue_id = consume_queue.get_queue_id();
+                let accounted_from = min_offsets
+                    .as_ref()
+                    .and_then(|min_offsets| min_offsets.get(&queue_id).copied());
+                // messages below a min offset advanced elsewhere can not be read anymore
+                if accounted_from.is_some_and(|accounted_from| {
+                    accounted_from != consume_queue.get_min_offset_in_queue()
+                }) {
+                    released_bytes = None;
+                }
+                let (min_offset, expired_bytes) =
+                    self.expire(&topic, consume_queue, ttl.map(|ttl| now - ttl));
+                released_bytes = released_bytes.map(|released| released + expired_bytes);
+                new_min_offsets.insert(queue_id, min_offset);
+            }
+            let Some(max_storage_bytes) = max_storage_bytes else {
+                continue;
+            };
+            match (min_offsets, released_bytes) {
+                (Some(_), Some(released_bytes)) => {
+                    let mut tracked_topics = self.tracked_topics.write();
+                    let tracked = tracked_topics.entry(topic).or_default();
+                    tracked.usage.storage_bytes -= released_bytes;
+                    tracked.usage.max_storage_bytes = max_storage_bytes;
+                    tracked.min_offsets = new_min_offsets;
+                }
+                // first seen, or the released bytes are unknown
+                _ => {
+                    let storage_bytes = consume_queues
+                        .values()
+                        .filter(|consume_queue| consume_queue.get_cq_type() == CQType::SimpleCQ)
+                        .filter_map(|consume_queue| {
+                            self.sum_unit_sizes(
+                                consume_queue,
+                                new_min_offsets[&consume_queue.get_queue_id()],
+                                consume_queue.get_max_offset_in_queue(),
+                            )
+                        })
+                        .sum();
+                    self.tracked_topics.write().insert(
+                        topic,
+                        TrackedTopic {
+                            usage: TopicStorageUsage {
+                                storage_bytes,
+                                max_storage_bytes,
+                            },
+                            min_offsets: new_min_offsets,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Logically deletes the messages of `consume_queue` stored before `expire_before`, the
+    /// scan stops at the first message that did not expire. Returns the new min offset and the
+    /// bytes of the expired messages.
+    fn expire(
+        &self,
+        topic: &str,
+        consume_queue: &ArcConsumeQueue,
+        expire_before: Option<i64>,
+    ) -> (i64, i64) {
+        let min_offset = consume_queue.get_min_offset_in_queue();
+        let Some(expire_before) = expire_before else {
+            return (min_offset, 0);
+        };
+        let mut expired_bytes = 0;
+        let expired_until = self.scan_units(
+            consume_queue,
+            min_offset,
+            consume_queue.get_max_offset_in_queue(),
+            |cq_unit| {
+                // messages already removed from the commit log expire too
+                let expired = self
+                    .commit_log
+                    .pickup_store_timestamp(cq_unit.pos, cq_unit.size)
+                    < expire_before;
+                if expired {
+                    expired_bytes += cq_unit.size as i64;
+                }
+                expired
+            },
+        );
+        if expired_until > min_offset {
+            info!(
+                "topic {} queue {} messages before offset {} expired",
+                topic,
+                consume_queue.get_queue_id(),
+                expired_until
+            );
+            consume_queue.advance_min_offset_in_queue(expired_until);
+        }
+        (expired_until, expired_bytes)
+    }
+
+    /// Bytes of the messages of `consume_queue` in `[from, until)`, `None` when some of them
+    /// can not be read.
+    fn sum_unit_sizes(
+        &self,
+        consume_queue: &ArcConsumeQueue,
+        from: i64,
+        until: i64,
+    ) -> Option<i64> {
+        let mut sum = 0;
+        let read_until = self.scan_units(consume_queue, from, until, |cq_unit| {
+            sum += cq_unit.size as i64;
+            true
+        });
+        (read_until >= until).then_some(sum)
+    }
+
+    /// Visits the units of `consume_queue` in `[from, until)` while `visit` returns true,
+    /// returns the offset of the first unit not visited.
+    fn scan_units(
+        &self,
+        consume_queue: &ArcConsumeQueue,
+        from: i64,
+        until: i64,
+        mut visit: impl FnMut(&CqUnit) -> bool,
+    ) -> i64 {
+        let mut index = from;
+        while index < until {
+            let Some(iter) = consume_queue.iterate_from(index) else {
+                break;
+            };
+            let begin = index;
+            for cq_unit in iter {
+                if index >= until || !visit(&cq_unit) {
+                    return index;
+                }
+                index += 1;
+            }
+            if index == begin {
+                break;
+            }
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
+    use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+    use rocketmq_common::common::message::MessageTrait;
+    use rocketmq_common::TopicAttributes;
+    use rocketmq_common::UtilAll::offset_to_file_name;
+
+    use super::*;
+    use crate::base::message_status_enum::PutMessageStatus;
+    use crate::config::message_store_config::MessageStoreConfig;
+    use crate::log_file::MessageStore;
+    use crate::message_store::default_message_store::DefaultMessageStore;
+    use crate::store_path_config_helper::get_store_path_consume_queue;
+    use crate::test_util::create_store;
+    use crate::test_util::TOPIC;
+
+    const BASE_STORE_TIMESTAMP: i64 = 1_700_000_000_000;
+
+    #[tokio::test]
+    async fn expires_messages_and_rejects_puts_over_quota() {
+        let store = create_store(6);
+        // consume queue files are loaded only with their full mapped size
+        let queue_file = Path::new(&get_store_path_consume_queue(
+            &store.config.store_path_root_dir,
+        ))
+        .join(TOPIC)
+        .join("0")
+        .join(offset_to_file_name(0));
+        let mut units = fs::read(&queue_file).unwrap();
+        units.resize(store.config.mapped_file_size_consume_queue, 0);
+        fs::write(&queue_file, units).unwrap();
+        let config = Arc::new(MessageStoreConfig {
+            mapped_file_size_commit_log: 4096,
+            ..store.config.as_ref().clone()
+        });
+        let mut topic_config = TopicConfig::new(TOPIC);
+        topic_config.attributes.insert(
+            TopicAttributes::MESSAGE_TTL_ATTRIBUTE
+                .get_name()
+                .to_string(),
+            "1000".to_string(),
+        );
+        let remaining_bytes: i64 = store.messages[3..]
+            .iter()
+            .map(|(_, size)| *size as i64)
+            .sum();
+        topic_config.attributes.insert(
+            TopicAttributes::MAX_STORAGE_BYTES_ATTRIBUTE
+                .get_name()
+                .to_string(),
+            remaining_bytes.to_string(),
+        );
+        let topic_config_table = Arc::new(parking_lot::Mutex::new(HashMap::from([(
+            TOPIC.to_string(),
+            topic_config,
+        )])));
+        let mut message_store = DefaultMessageStore::new(
+            config,
+            Arc::new(BrokerConfig::default()),
+            topic_config_table,
+            None,
+            false,
+        );
+        assert!(message_store.load().await);
+        assert_eq!(message_store.get_min_offset_in_queue(TOPIC, 0), 0);
+
+        // messages 0, 1 and 2 were stored more than 1000ms before
+        let service = message_store.topic_retention_service().clone();
+        service.run_at(BASE_STORE_TIMESTAMP + 1000 + 3);
+        assert_eq!(message_store.get_min_offset_in_queue(TOPIC, 0), 3);
+        assert_eq!(
+            service.get_topic_storage_usage(TOPIC),
+            Some(TopicStorageUsage {
+                storage_bytes: remaining_bytes,
+                max_storage_bytes: remaining_bytes,
+            })
+        );
+        assert!(service.is_storage_quota_exceeded(TOPIC));
+
+        let mut msg = MessageExtBrokerInner::default();
+        msg.set_topic(TOPIC);
+        let result = message_store.put_message(msg).await;
+        assert_eq!(
+            result.put_message_status(),
+            PutMessageStatus::TopicStorageQuotaExceeded
+        );
+
+        // the min offset never moves backwards
+        service.run_at(BASE_STORE_TIMESTAMP);
+        assert_eq!(message_store.get_min_offset_in_queue(TOPIC, 0), 3);
+        assert!(!service.is_storage_quota_exceeded("OtherTopic"));
+
+        // puts are counted until the next run, which releases the expired messages
+        service.on_message_stored(TOPIC, 100);
+        service.run_at(BASE_STORE_TIMESTAMP + 1000 + 4);
+        assert_eq!(message_store.get_min_offset_in_queue(TOPIC, 0), 4);
+        let message_size = store.messages[3].1 as i64;
+        assert_eq!(
+            service
+                .get_topic_storage_usage(TOPIC)
+                .unwrap()
+                .storage_bytes,
+            remaining_bytes + 100 - message_size
+        );
+
+        // the usage is summed up again once a min offset is advanced elsewhere
+        message_store
+            .find_consume_queue(TOPIC, 0)
+            .unwrap()
+            .advance_min_offset_in_queue(5);
+        service.run_at(BASE_STORE_TIMESTAMP);
+        assert_eq!(
+            service
+                .get_topic_storage_usage(TOPIC)
+                .unwrap()
+                .storage_bytes,
+            store.messages[5].1 as i64
+        );
+    }
+}
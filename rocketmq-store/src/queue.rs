@@ -676,16 +676,25 @@ pub trait ConsumeQueueTrait: Send + Sync + FileQueueLifeCycle {
     ///
     /// # Arguments
     /// * `timestamp` - The timestamp to query by.
+    /// * `pickup_store_timestamp` - Reads the store timestamp of the message at a commit log offset
+    ///   with a size, -1 once the message was removed from the commit log.
     ///
     /// # Returns
     /// The offset in the queue as a 64-bit integer.
-    fn get_offset_in_queue_by_time(&self, timestamp: i64) -> i64;
+    fn get_offset_in_queue_by_time(
+        &self,
+        timestamp: i64,
+        pickup_store_timestamp: &dyn Fn(i64, i32) -> i64,
+    ) -> i64;
 
     /// Retrieves the offset in the queue by a specific timestamp, considering boundary conditions.
     ///
     /// # Arguments
     /// * `timestamp` - The timestamp to query by.
-    /// * `boundary_type` - The boundary condition to apply (e.g., exact match, closest match).
+    /// * `boundary_type` - `Lower` returns the first offset stored at or after `timestamp`, `Upper`
+    ///   the last offset stored at or before it.
+    /// * `pickup_store_timestamp` - Reads the store timestamp of the message at a commit log offset
+    ///   with a size, -1 once the message was removed from the commit log.
     ///
     /// # Returns
     /// The offset in the queue as a 64-bit integer.
@@ -693,6 +702,7 @@ pub trait ConsumeQueueTrait: Send + Sync + FileQueueLifeCycle {
         &self,
         timestamp: i64,
         boundary_type: BoundaryType,
+        pickup_store_timestamp: &dyn Fn(i64, i32) -> i64,
     ) -> i64;
 
     /// Returns the maximum physical offset in the consume queue.
@@ -752,6 +762,16 @@ pub trait ConsumeQueueTrait: Send + Sync + FileQueueLifeCycle {
     ///   offset against.
     fn correct_min_offset(&self, min_commit_log_offset: i64);
 
+    /// Moves the minimum offset of the consume queue forward.
+    ///
+    /// Messages before `min_offset` are logically deleted: they stay on disk until the commit
+    /// log is cleaned, but are no longer served to consumers. Offsets at or below the current
+    /// minimum offset are ignored.
+    ///
+    /// # Arguments
+    /// * `min_offset` - The new minimum offset, in queue units.
+    fn advance_min_offset_in_queue(&self, min_offset: i64);
+
     /// Applies the dispatched request to the consume queue.
     ///
     /// This method is responsible for processing a dispatch request and applying it to the consume
@@ -818,7 +838,7 @@ pub trait ConsumeQueueTrait: Send + Sync + FileQueueLifeCycle {
     /// # Returns
     /// An optional box containing an iterator over `CqUnit` items, or `None` if iteration cannot
     /// start.
-    fn iterate_from(&self, start_index: i64) -> Option<Box<dyn Iterator<Item = CqUnit>>>;
+    fn iterate_from(&self, start_index: i64) -> Option<Box<dyn Iterator<Item = CqUnit> + Send>>;
 
     /// Iterates over a specified number of messages from a start index.
     ///
@@ -837,5 +857,5 @@ pub trait ConsumeQueueTrait: Send + Sync + FileQueueLifeCycle {
         &self,
         start_index: i64,
         count: i32,
-    ) -> Option<Box<dyn Iterator<Item = CqUnit>>>;
+    ) -> Option<Box<dyn Iterator<Item = CqUnit> + Send>>;
 }
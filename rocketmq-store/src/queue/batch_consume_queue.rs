@@ -241,7 +241,11 @@ impl ConsumeQueueTrait for BatchConsumeQueue {
         todo!()
     }
 
-    fn get_offset_in_queue_by_time(&self, timestamp: i64) -> i64 {
+    fn get_offset_in_queue_by_time(
+        &self,
+        timestamp: i64,
+        pickup_store_timestamp: &dyn Fn(i64, i32) -> i64,
+    ) -> i64 {
         todo!()
     }
 
@@ -249,6 +253,7 @@ impl ConsumeQueueTrait for BatchConsumeQueue {
         &self,
         timestamp: i64,
         boundary_type: BoundaryType,
+        pickup_store_timestamp: &dyn Fn(i64, i32) -> i64,
     ) -> i64 {
         todo!()
     }
@@ -277,6 +282,10 @@ impl ConsumeQueueTrait for BatchConsumeQueue {
         todo!()
     }
 
+    fn advance_min_offset_in_queue(&self, min_offset: i64) {
+        todo!()
+    }
+
     fn put_message_position_info_wrapper(&mut self, request: &DispatchRequest) {
         todo!()
     }
@@ -302,7 +311,7 @@ impl ConsumeQueueTrait for BatchConsumeQueue {
         todo!()
     }
 
-    fn iterate_from(&self, start_index: i64) -> Option<Box<dyn Iterator<Item = CqUnit>>> {
+    fn iterate_from(&self, start_index: i64) -> Option<Box<dyn Iterator<Item = CqUnit> + Send>> {
         todo!()
     }
 
@@ -310,7 +319,7 @@ impl ConsumeQueueTrait for BatchConsumeQueue {
         &self,
         start_index: i64,
         count: i32,
-    ) -> Option<Box<dyn Iterator<Item = CqUnit>>> {
+    ) -> Option<Box<dyn Iterator<Item = CqUnit> + Send>> {
         todo!()
     }
 }
@@ -15,11 +15,17 @@
  * limitations under the License.
  */
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use tracing::error;
 use tracing::info;
+use tracing::warn;
 
 use crate::consume_queue::consume_queue_ext::CqExtUnit;
+use crate::consume_queue::consume_queue_ext::MAX_EXT_UNIT_SIZE;
 use crate::consume_queue::mapped_file_queue::MappedFileQueue;
+use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
+use crate::log_file::mapped_file::MappedFile;
 
 const END_BLANK_DATA_LENGTH: usize = 4;
 
@@ -64,6 +70,22 @@ impl ConsumeQueueExt {
     pub fn is_ext_addr(address: i64) -> bool {
         address <= MAX_ADDR
     }
+
+    /// Transform an offset of the ext files into an address stored in the consume queue.
+    pub fn decorate(offset: i64) -> i64 {
+        if !Self::is_ext_addr(offset) {
+            return offset.wrapping_add(i64::MIN);
+        }
+        offset
+    }
+
+    /// Transform an address stored in the consume queue back into an offset of the ext files.
+    pub fn un_decorate(address: i64) -> i64 {
+        if Self::is_ext_addr(address) {
+            return address.wrapping_sub(i64::MIN);
+        }
+        address
+    }
 }
 
 impl ConsumeQueueExt {
@@ -85,15 +107,140 @@ impl ConsumeQueueExt {
 
     pub fn recover(&mut self) {}
 
-    pub fn put(&self, cq_ext_unit: CqExtUnit) -> i64 {
-        unimplemented!()
+    pub fn check_self(&self) {
+        self.mapped_file_queue.check_self();
+    }
+
+    pub fn flush(&self, flush_least_pages: i32) -> bool {
+        self.mapped_file_queue.flush(flush_least_pages)
+    }
+
+    /// Append `cq_ext_unit` to the ext files.
+    ///
+    /// Returns the decorated address of the unit, or 1 if it could not be written.
+    pub fn put(&mut self, cq_ext_unit: CqExtUnit) -> i64 {
+        const RETRY_TIMES: i32 = 3;
+        let size = cq_ext_unit.size() as i32;
+        if size > MAX_EXT_UNIT_SIZE as i32 {
+            error!(
+                "Size of cq ext unit is greater than {}, {}",
+                MAX_EXT_UNIT_SIZE, size
+            );
+            return 1;
+        }
+        if self.mapped_file_queue.get_max_offset() + size as i64 > MAX_REAL_OFFSET {
+            warn!("Capacity of ext is maximum!{}, {}", MAX_REAL_OFFSET, size);
+            return 1;
+        }
+        let data = cq_ext_unit.write();
+        for _ in 0..RETRY_TIMES {
+            let Some(mapped_file) = self
+                .mapped_file_queue
+                .get_last_mapped_file_mut_start_offset(0, true)
+            else {
+                error!(
+                    "Create mapped file when save consume queue extend, {}-{}",
+                    self.topic, self.queue_id
+                );
+                continue;
+            };
+            let wrote_position = mapped_file.get_wrote_position();
+            let blank_size = self.mapped_file_size - wrote_position - END_BLANK_DATA_LENGTH as i32;
+            if size > blank_size {
+                Self::full_fill_to_end(&mapped_file, wrote_position, self.mapped_file_size);
+                info!(
+                    "No enough space(need:{}, has:{}) of file {}, so fill to end",
+                    size,
+                    blank_size,
+                    mapped_file.get_file_name()
+                );
+                continue;
+            }
+            if mapped_file.append_message_bytes(&data) {
+                return Self::decorate(
+                    wrote_position as i64 + mapped_file.get_file_from_offset() as i64,
+                );
+            }
+        }
+        1
+    }
+
+    fn full_fill_to_end(
+        mapped_file: &Arc<DefaultMappedFile>,
+        wrote_position: i32,
+        mapped_file_size: i32,
+    ) {
+        mapped_file.put_slice(&(-1i16).to_be_bytes(), wrote_position as usize);
+        mapped_file.set_wrote_position(mapped_file_size);
     }
 
     pub fn destroy(&mut self) {
         self.mapped_file_queue.destroy();
     }
 
-    pub fn get(&self, address: i64, cq_ext_unit: &CqExtUnit) -> bool {
-        unimplemented!()
+    /// Read the unit stored at the decorated `address` into `cq_ext_unit`.
+    pub fn get(&self, address: i64, cq_ext_unit: &mut CqExtUnit) -> bool {
+        if !Self::is_ext_addr(address) {
+            return false;
+        }
+        let real_offset = Self::un_decorate(address);
+        let Some(mapped_file) = self
+            .mapped_file_queue
+            .find_mapped_file_by_offset(real_offset, real_offset == 0)
+        else {
+            return false;
+        };
+        let pos = (real_offset % self.mapped_file_size as i64) as usize;
+        let Some(size) = mapped_file.get_bytes(pos, 2) else {
+            return false;
+        };
+        let size = i16::from_be_bytes([size[0], size[1]]);
+        if size < 1 {
+            return false;
+        }
+        match mapped_file.get_bytes(pos, size as usize) {
+            None => false,
+            Some(bytes) => cq_ext_unit.read(bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn put_and_get_across_files() {
+        let dir = TempDir::new().unwrap();
+        let unit = CqExtUnit::new(7, 1000, Some(vec![0xAB; 8]));
+        let unit_size = unit.size() as i32;
+        let mapped_file_size = unit_size * 2 + END_BLANK_DATA_LENGTH as i32;
+        let mut ext = ConsumeQueueExt::new(
+            "TopicTest".to_string(),
+            0,
+            dir.path().to_string_lossy().to_string(),
+            mapped_file_size,
+            64,
+        );
+
+        let addresses: Vec<i64> = (0..3).map(|_| ext.put(unit.clone())).collect();
+        assert!(addresses
+            .iter()
+            .all(|address| ConsumeQueueExt::is_ext_addr(*address)));
+        assert_eq!(ConsumeQueueExt::un_decorate(addresses[0]), 0);
+        assert_eq!(
+            ConsumeQueueExt::un_decorate(addresses[2]),
+            mapped_file_size as i64
+        );
+
+        for address in addresses {
+            let mut read = CqExtUnit::default();
+            assert!(ext.get(address, &mut read));
+            assert_eq!(read.tags_code(), 7);
+            assert_eq!(read.filter_bit_map(), unit.filter_bit_map());
+        }
+        assert!(!ext.get(7, &mut CqExtUnit::default()));
     }
 }
@@ -157,7 +157,7 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     }
 
     fn flush(&self, consume_queue: &dyn ConsumeQueueTrait, flush_least_pages: i32) -> bool {
-        todo!()
+        consume_queue.flush(flush_least_pages)
     }
 
     fn clean_expired(&self, min_phy_offset: i64) {
@@ -165,7 +165,12 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     }
 
     fn check_self(&self) {
-        println!("ConsumeQueueStore::check_self unimplemented");
+        let consume_queue_table = self.inner.consume_queue_table.lock().clone();
+        for consume_queues in consume_queue_table.values() {
+            for consume_queue in consume_queues.values() {
+                consume_queue.check_self();
+            }
+        }
     }
 
     fn delete_expired_file(
@@ -366,7 +371,7 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     }
 
     fn find_consume_queue_map(&self, topic: &str) -> Option<HashMap<i32, ArcConsumeQueue>> {
-        todo!()
+        self.inner.consume_queue_table.lock().get(topic).cloned()
     }
 
     fn get_total_size(&self) -> i64 {
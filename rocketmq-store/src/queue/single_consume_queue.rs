@@ -439,11 +439,22 @@ impl FileQueueLifeCycle for ConsumeQueue {
     }
 
     fn check_self(&self) {
-        todo!()
+        self.mapped_file_queue.check_self();
+        if self.is_ext_read_enable() {
+            self.consume_queue_ext.as_ref().unwrap().check_self();
+        }
     }
 
     fn flush(&self, flush_least_pages: i32) -> bool {
-        todo!()
+        let mut result = self.mapped_file_queue.flush(flush_least_pages);
+        if self.is_ext_read_enable() {
+            result &= self
+                .consume_queue_ext
+                .as_ref()
+                .unwrap()
+                .flush(flush_least_pages);
+        }
+        result
     }
 
     fn destroy(&mut self) {
@@ -502,10 +513,7 @@ impl ConsumeQueueTrait for ConsumeQueue {
     }
 
     fn get(&self, index: i64) -> Option<CqUnit> {
-        match self.iterate_from(index) {
-            None => None,
-            Some(value) => None,
-        }
+        self.iterate_from(index).and_then(|mut iter| iter.next())
     }
 
     fn get_cq_unit_and_store_time(&self, index: i64) -> Option<(CqUnit, i64)> {
@@ -540,16 +548,50 @@ impl ConsumeQueueTrait for ConsumeQueue {
         todo!()
     }
 
-    fn get_offset_in_queue_by_time(&self, timestamp: i64) -> i64 {
-        todo!()
+    fn get_offset_in_queue_by_time(
+        &self,
+        timestamp: i64,
+        pickup_store_timestamp: &dyn Fn(i64, i32) -> i64,
+    ) -> i64 {
+        self.get_offset_in_queue_by_time_boundary(
+            timestamp,
+            BoundaryType::Lower,
+            pickup_store_timestamp,
+        )
     }
 
     fn get_offset_in_queue_by_time_boundary(
         &self,
         timestamp: i64,
         boundary_type: BoundaryType,
+        pickup_store_timestamp: &dyn Fn(i64, i32) -> i64,
     ) -> i64 {
-        todo!()
+        let min_offset = self.get_min_offset_in_queue();
+        let max_offset = self.get_max_offset_in_queue();
+        let store_timestamp = |offset: i64| {
+            self.get(offset).map_or(-1, |cq_unit| {
+                pickup_store_timestamp(cq_unit.pos, cq_unit.size)
+            })
+        };
+        // store timestamps grow with the queue offset, messages already removed from the commit
+        // log read as -1 and sort before everything else
+        let (mut low, mut high) = (min_offset, max_offset);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let before = match boundary_type {
+                BoundaryType::Lower => store_timestamp(mid) < timestamp,
+                BoundaryType::Upper => store_timestamp(mid) <= timestamp,
+            };
+            if before {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        match boundary_type {
+            BoundaryType::Lower => low,
+            BoundaryType::Upper => (low - 1).max(min_offset),
+        }
     }
 
     fn get_max_physic_offset(&self) -> i64 {
@@ -572,8 +614,26 @@ impl ConsumeQueueTrait for ConsumeQueue {
         CQ_STORE_UNIT_SIZE
     }
 
+    fn advance_min_offset_in_queue(&self, min_offset: i64) {
+        let min_logic_offset =
+            min_offset.min(self.get_max_offset_in_queue()) * CQ_STORE_UNIT_SIZE as i64;
+        let previous = self
+            .min_logic_offset
+            .fetch_max(min_logic_offset, Ordering::SeqCst);
+        if previous < min_logic_offset {
+            info!(
+                "ConsumeQueue[topic={}, queue-id={}] min offset is advanced from {} to {}",
+                self.topic,
+                self.queue_id,
+                previous / CQ_STORE_UNIT_SIZE as i64,
+                min_logic_offset / CQ_STORE_UNIT_SIZE as i64
+            );
+        }
+    }
+
     fn correct_min_offset(&self, min_commit_log_offset: i64) {
-        if min_commit_log_offset >= self.mapped_file_queue.get_max_offset() {
+        if self.min_logic_offset.load(Ordering::Acquire) >= self.mapped_file_queue.get_max_offset()
+        {
             info!(
                 "ConsumeQueue[Topic={}, queue-id={}] contains no valid entries",
                 self.topic, self.queue_id
@@ -595,13 +655,7 @@ impl ConsumeQueueTrait for ConsumeQueue {
             CQ_STORE_UNIT_SIZE,
         );
         if let Some(last_record) = last_record {
-            let mut bytes = last_record
-                .mapped_file
-                .as_ref()
-                .unwrap()
-                .get_bytes(last_record.start_offset as usize, last_record.size as usize)
-                .unwrap();
-            let commit_log_offset = bytes.get_i64();
+            let commit_log_offset = last_record.get_buffer().get_i64();
             if commit_log_offset < min_commit_log_offset {
                 self.min_logic_offset.store(
                     max_readable_position as i64 + last_mapped_file.get_file_from_offset() as i64,
@@ -655,11 +709,8 @@ impl ConsumeQueueTrait for ConsumeQueue {
                 );
                 return;
             }
-            let mapped = result.mapped_file.as_ref().unwrap();
-            let commit_log_offset = mapped
-                .get_bytes(result.start_offset as usize, 8)
-                .unwrap()
-                .get_i64();
+            let buffer = result.get_buffer();
+            let commit_log_offset = (&buffer[..]).get_i64();
             if intact && commit_log_offset >= min_commit_log_offset {
                 info!(
                     "Abort correction as previous min-offset points to {}, which is greater than \
@@ -675,7 +726,7 @@ impl ConsumeQueueTrait for ConsumeQueue {
                     break;
                 }
                 let mid = (low + high) / 2 / CQ_STORE_UNIT_SIZE * CQ_STORE_UNIT_SIZE;
-                let commit_log_offset = mapped.get_bytes(mid as usize, 8).unwrap().get_i64();
+                let commit_log_offset = (&buffer[mid as usize..]).get_i64();
 
                 match commit_log_offset.cmp(&min_commit_log_offset) {
                     std::cmp::Ordering::Greater => high = mid,
@@ -689,11 +740,11 @@ impl ConsumeQueueTrait for ConsumeQueue {
             }
             let mut i = low;
             while i <= high {
-                let offset_py = mapped.get_bytes(i as usize, 8).unwrap().get_i64();
-                let tags_code = mapped.get_bytes((i + 12) as usize, 8).unwrap().get_i64();
+                let offset_py = (&buffer[i as usize..]).get_i64();
+                let tags_code = (&buffer[(i + 12) as usize..]).get_i64();
                 if offset_py >= min_commit_log_offset {
                     self.min_logic_offset.store(
-                        mapped.get_file_from_offset() as i64 + i as i64 + start,
+                        mapped_file.get_file_from_offset() as i64 + i as i64 + start,
                         Ordering::SeqCst,
                     );
                     if Self::is_ext_addr(tags_code) {
@@ -720,7 +771,7 @@ impl ConsumeQueueTrait for ConsumeQueue {
         while i < max_retries && can_write {
             let mut tags_code = request.tags_code;
             if self.is_ext_write_enable() {
-                let ext_addr = self.consume_queue_ext.as_ref().unwrap().put(CqExtUnit::new(
+                let ext_addr = self.consume_queue_ext.as_mut().unwrap().put(CqExtUnit::new(
                     tags_code,
                     request.store_timestamp,
                     request.bit_map.clone(),
@@ -794,7 +845,7 @@ impl ConsumeQueueTrait for ConsumeQueue {
         todo!()
     }
 
-    fn iterate_from(&self, start_index: i64) -> Option<Box<dyn Iterator<Item = CqUnit>>> {
+    fn iterate_from(&self, start_index: i64) -> Option<Box<dyn Iterator<Item = CqUnit> + Send>> {
         match self.get_index_buffer(start_index) {
             None => None,
             Some(value) => Some(Box::new(ConsumeQueueIterator {
@@ -810,7 +861,7 @@ impl ConsumeQueueTrait for ConsumeQueue {
         &self,
         start_index: i64,
         _count: i32,
-    ) -> Option<Box<dyn Iterator<Item = CqUnit>>> {
+    ) -> Option<Box<dyn Iterator<Item = CqUnit> + Send>> {
         self.iterate_from(start_index)
     }
 }
@@ -823,7 +874,7 @@ struct ConsumeQueueIterator {
 }
 
 impl ConsumeQueueIterator {
-    fn get_ext(&self, offset: i64, cq_ext_unit: &CqExtUnit) -> bool {
+    fn get_ext(&self, offset: i64, cq_ext_unit: &mut CqExtUnit) -> bool {
         match self.consume_queue_ext.as_ref() {
             None => false,
             Some(value) => value.get(offset, cq_ext_unit),
@@ -841,12 +892,15 @@ impl Iterator for ConsumeQueueIterator {
                 if self.counter * CQ_STORE_UNIT_SIZE >= value.size {
                     return None;
                 }
-                let mmp = value.mapped_file.as_ref().unwrap().get_mapped_file();
+                let mapped_file = value.mapped_file.as_ref().unwrap();
+                let mmp = mapped_file.get_mapped_file();
+                // start offset of the buffer is a logic offset of the queue
                 let start =
                     value.start_offset as usize + (self.counter * CQ_STORE_UNIT_SIZE) as usize;
                 self.counter += 1;
-                let end = start + CQ_STORE_UNIT_SIZE as usize;
-                let mut bytes = Bytes::copy_from_slice(&mmp[start..end]);
+                let pos = start - mapped_file.get_file_from_offset() as usize;
+                let end = pos + CQ_STORE_UNIT_SIZE as usize;
+                let mut bytes = Bytes::copy_from_slice(&mmp[pos..end]);
                 let pos = bytes.get_i64();
                 let size = bytes.get_i32();
                 let tags_code = bytes.get_i64();
@@ -859,8 +913,8 @@ impl Iterator for ConsumeQueueIterator {
                 };
 
                 if ConsumeQueueExt::is_ext_addr(cq_unit.tags_code) {
-                    let cq_ext_unit = CqExtUnit::default();
-                    let ext_ret = self.get_ext(cq_unit.tags_code, &cq_ext_unit);
+                    let mut cq_ext_unit = CqExtUnit::default();
+                    let ext_ret = self.get_ext(cq_unit.tags_code, &mut cq_ext_unit);
                     if ext_ret {
                         cq_unit.tags_code = cq_ext_unit.tags_code();
                         cq_unit.cq_ext_unit = Some(cq_ext_unit);
@@ -877,3 +931,97 @@ impl Iterator for ConsumeQueueIterator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const MESSAGE_SIZE: i32 = 100;
+
+    /// Consume queue of messages stored with `store_timestamps`, one per `MESSAGE_SIZE` bytes of
+    /// the commit log.
+    fn consume_queue(dir: &TempDir, store_timestamps: &[i64]) -> ConsumeQueue {
+        let root_dir = dir.path().to_string_lossy().to_string();
+        let mut consume_queue = ConsumeQueue::new(
+            "TopicTest".to_string(),
+            0,
+            root_dir.clone(),
+            CQ_STORE_UNIT_SIZE * 100,
+            Arc::new(MessageStoreConfig {
+                store_path_root_dir: root_dir,
+                ..MessageStoreConfig::default()
+            }),
+            Arc::new(RunningFlags::new()),
+            Arc::new(StoreCheckpoint::new(dir.path().join("checkpoint")).unwrap()),
+        );
+        for offset in 0..store_timestamps.len() as i64 {
+            assert!(consume_queue.put_message_position_info(
+                offset * MESSAGE_SIZE as i64,
+                MESSAGE_SIZE,
+                0,
+                offset
+            ));
+        }
+        consume_queue
+    }
+
+    fn offset_by_time(
+        consume_queue: &ConsumeQueue,
+        store_timestamps: &[i64],
+        timestamp: i64,
+        boundary_type: BoundaryType,
+    ) -> i64 {
+        consume_queue.get_offset_in_queue_by_time_boundary(
+            timestamp,
+            boundary_type,
+            &|offset, _| store_timestamps[(offset / MESSAGE_SIZE as i64) as usize],
+        )
+    }
+
+    #[test]
+    fn offset_by_time_boundaries() {
+        let dir = TempDir::new().unwrap();
+        let store_timestamps = [1000, 2000, 2000, 3000];
+        let consume_queue = consume_queue(&dir, &store_timestamps);
+        let offset = |timestamp, boundary_type| {
+            offset_by_time(&consume_queue, &store_timestamps, timestamp, boundary_type)
+        };
+        assert_eq!(offset(2000, BoundaryType::Lower), 1);
+        assert_eq!(offset(2000, BoundaryType::Upper), 2);
+        assert_eq!(offset(2500, BoundaryType::Lower), 3);
+        assert_eq!(offset(2500, BoundaryType::Upper), 2);
+        // before the first message and after the last one
+        assert_eq!(offset(500, BoundaryType::Lower), 0);
+        assert_eq!(offset(500, BoundaryType::Upper), 0);
+        assert_eq!(offset(5000, BoundaryType::Lower), 4);
+        assert_eq!(offset(5000, BoundaryType::Upper), 3);
+        assert_eq!(
+            consume_queue.get_offset_in_queue_by_time(2000, &|_, _| 2000),
+            0
+        );
+    }
+
+    #[test]
+    fn offset_by_time_skips_messages_removed_from_commit_log() {
+        let dir = TempDir::new().unwrap();
+        let store_timestamps = [-1, -1, 2000, 3000];
+        let consume_queue = consume_queue(&dir, &store_timestamps);
+        let offset = |timestamp, boundary_type| {
+            offset_by_time(&consume_queue, &store_timestamps, timestamp, boundary_type)
+        };
+        assert_eq!(offset(500, BoundaryType::Lower), 2);
+        assert_eq!(offset(500, BoundaryType::Upper), 1);
+        assert_eq!(offset(3000, BoundaryType::Upper), 3);
+    }
+
+    #[test]
+    fn offset_by_time_in_empty_queue() {
+        let dir = TempDir::new().unwrap();
+        let consume_queue = consume_queue(&dir, &[]);
+        for boundary_type in [BoundaryType::Lower, BoundaryType::Upper] {
+            assert_eq!(offset_by_time(&consume_queue, &[], 1000, boundary_type), 0);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+/// The category of an inconsistency found while verifying the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckIssueKind {
+    /// A commit log entry starts with an unknown magic code.
+    CommitLogIllegalMagicCode,
+    /// The body CRC stored in a commit log entry does not match the body.
+    CommitLogCrcMismatch,
+    /// The total size of a commit log entry does not match its fields.
+    CommitLogSizeMismatch,
+    /// The physical offset recorded in a commit log entry is not its position.
+    CommitLogOffsetMismatch,
+    /// A commit log entry runs past the end of its file.
+    CommitLogTruncated,
+    /// A consume queue unit points to an offset that is not a message start.
+    ConsumeQueueUnitNotFound,
+    /// A consume queue unit points past the end of the commit log.
+    ConsumeQueueBeyondCommitLog,
+    /// A consume queue unit size differs from the message size.
+    ConsumeQueueSizeMismatch,
+    /// A consume queue unit points to a message of another topic or queue.
+    ConsumeQueueTopicMismatch,
+    /// A consume queue unit position differs from the message queue offset.
+    ConsumeQueueOffsetMismatch,
+    /// A dispatchable message is not referenced by its consume queue.
+    ConsumeQueueUnitMissing,
+    /// An index file has an unexpected size or header.
+    IndexFileCorrupted,
+    /// An index hash slot points outside the written index entries.
+    IndexSlotInvalid,
+    /// An index entry links to an index that was not written before it.
+    IndexLinkInvalid,
+    /// An index entry points to an offset that is not a message start.
+    IndexOffsetNotFound,
+    /// An index entry hash matches none of the keys of its message.
+    IndexKeyMismatch,
+}
+
+impl Display for CheckIssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// A single inconsistency found while verifying the store.
+///
+/// `offset` is the physical offset for commit log issues, the logical byte offset inside the
+/// queue for consume queue issues and the byte position inside the file for index issues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckIssue {
+    pub kind: CheckIssueKind,
+    pub file_name: String,
+    pub offset: i64,
+    pub detail: String,
+}
+
+impl CheckIssue {
+    pub fn new(
+        kind: CheckIssueKind,
+        file_name: impl Into<String>,
+        offset: i64,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            file_name: file_name.into(),
+            offset,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl Display for CheckIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] file: {}, offset: {}, {}",
+            self.kind, self.file_name, self.offset, self.detail
+        )
+    }
+}
+
+/// The outcome of a store verification run.
+///
+/// Only the first `max_reported_issues` issues are kept in `issues`, while `issue_counts`
+/// always reflects every issue found.
+#[derive(Debug, Default)]
+pub struct StoreCheckResult {
+    pub commit_log_files: usize,
+    pub commit_log_messages: u64,
+    pub commit_log_min_offset: i64,
+    pub commit_log_max_offset: i64,
+    pub consume_queue_count: usize,
+    pub consume_queue_units: u64,
+    pub index_files: usize,
+    pub index_entries: u64,
+    pub issues: Vec<CheckIssue>,
+    pub issue_counts: HashMap<CheckIssueKind, u64>,
+    pub(crate) max_reported_issues: usize,
+}
+
+impl StoreCheckResult {
+    pub(crate) fn new(max_reported_issues: usize) -> Self {
+        Self {
+            max_reported_issues,
+            ..Default::default()
+        }
+    }
+
+    pub fn add_issue(&mut self, issue: CheckIssue) {
+        *self.issue_counts.entry(issue.kind).or_insert(0) += 1;
+        if self.issues.len() < self.max_reported_issues {
+            self.issues.push(issue);
+        }
+    }
+
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.issue_counts.is_empty()
+    }
+
+    pub fn total_issues(&self) -> u64 {
+        self.issue_counts.values().sum()
+    }
+
+    pub fn count_of(&self, kind: CheckIssueKind) -> u64 {
+        self.issue_counts.get(&kind).copied().unwrap_or(0)
+    }
+}
+
+impl Display for StoreCheckResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "StoreCheckResult {{ commit_log_files: {}, commit_log_messages: {}, \
+             commit_log_min_offset: {}, commit_log_max_offset: {}, consume_queue_count: {}, \
+             consume_queue_units: {}, index_files: {}, index_entries: {}, total_issues: {} }}",
+            self.commit_log_files,
+            self.commit_log_messages,
+            self.commit_log_min_offset,
+            self.commit_log_max_offset,
+            self.consume_queue_count,
+            self.consume_queue_units,
+            self.index_files,
+            self.index_entries,
+            self.total_issues()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_issue_caps_reported_issues_but_counts_all() {
+        let mut result = StoreCheckResult::new(1);
+        result.add_issue(CheckIssue::new(
+            CheckIssueKind::CommitLogCrcMismatch,
+            "00000000000000000000",
+            0,
+            "crc",
+        ));
+        result.add_issue(CheckIssue::new(
+            CheckIssueKind::CommitLogCrcMismatch,
+            "00000000000000000000",
+            100,
+            "crc",
+        ));
+        assert!(!result.is_ok());
+        assert_eq!(result.issues.len(), 1);
+        assert_eq!(result.total_issues(), 2);
+        assert_eq!(result.count_of(CheckIssueKind::CommitLogCrcMismatch), 2);
+        assert_eq!(result.count_of(CheckIssueKind::IndexKeyMismatch), 0);
+    }
+}
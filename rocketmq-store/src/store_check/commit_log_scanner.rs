@@ -0,0 +1,444 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use memmap2::Mmap;
+use rocketmq_common::common::message::message_single::tags_string2tags_code;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageVersion;
+use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::CRC32Utils::crc32;
+use rocketmq_common::MessageDecoder::string_to_message_properties;
+
+use crate::base::dispatch_request::DispatchRequest;
+use crate::log_file::commit_log::BLANK_MAGIC_CODE;
+use crate::message_encoder::message_ext_encoder::MessageExtEncoder;
+use crate::store_check::check_result::CheckIssue;
+use crate::store_check::check_result::CheckIssueKind;
+
+/// TOTALSIZE(4) + MAGICCODE(4)
+const ENTRY_PREFIX_LENGTH: usize = 8;
+
+/// One item produced while walking the commit log.
+#[derive(Debug)]
+pub enum CommitLogRecord {
+    Message(DispatchRequest),
+    Issue(CheckIssue),
+}
+
+/// Summary of a commit log scan.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CommitLogScanSummary {
+    pub files: usize,
+    /// Offset of the first byte of the first commit log file, `-1` if there is no file.
+    pub min_offset: i64,
+    /// Offset right after the last readable entry, `-1` if there is no file.
+    pub max_offset: i64,
+    pub messages: u64,
+}
+
+/// Reads commit log files directly from disk without going through the mapped file queue, so it
+/// can be used while the broker is stopped and on files that can no longer be loaded.
+///
+/// Every read is bounds checked: corrupted entries are reported as [`CheckIssue`]s instead of
+/// panicking.
+pub struct CommitLogScanner {
+    store_path: String,
+    check_crc: bool,
+}
+
+impl CommitLogScanner {
+    pub fn new(store_path: impl Into<String>, check_crc: bool) -> Self {
+        Self {
+            store_path: store_path.into(),
+            check_crc,
+        }
+    }
+
+    /// Lists commit log files ordered by their start offset.
+    pub fn list_files(&self) -> io::Result<Vec<(i64, PathBuf)>> {
+        list_offset_named_files(Path::new(&self.store_path))
+    }
+
+    /// Walks every entry whose offset is not less than `from_offset` and hands it to `visitor`.
+    ///
+    /// `from_offset` must be an entry boundary. The walk stops early when `visitor` returns
+    /// `false`. When an entry can not be parsed, the rest of its file is skipped because the next
+    /// entry boundary is unknown.
+    pub fn scan<F>(&self, from_offset: i64, mut visitor: F) -> io::Result<CommitLogScanSummary>
+    where
+        F: FnMut(CommitLogRecord) -> bool,
+    {
+        let files = self.list_files()?;
+        let mut summary = CommitLogScanSummary {
+            files: files.len(),
+            min_offset: files.first().map_or(-1, |(offset, _)| *offset),
+            max_offset: -1,
+            messages: 0,
+        };
+        let file_count = files.len();
+        for (index, (file_from_offset, path)) in files.into_iter().enumerate() {
+            let file = File::open(&path)?;
+            let file_len = file.metadata()?.len() as i64;
+            summary.max_offset = file_from_offset;
+            if file_len == 0 || file_from_offset + file_len <= from_offset {
+                continue;
+            }
+            // SAFETY: the file is only read, and store files are not truncated while the
+            // broker is stopped.
+            let mmap = unsafe { Mmap::map(&file)? };
+            let file_name = file_name_of(&path);
+            let is_last_file = index + 1 == file_count;
+            let mut pos = if from_offset > file_from_offset {
+                (from_offset - file_from_offset) as usize
+            } else {
+                0
+            };
+            loop {
+                let phy_offset = file_from_offset + pos as i64;
+                summary.max_offset = phy_offset;
+                if pos + ENTRY_PREFIX_LENGTH > mmap.len() {
+                    break;
+                }
+                let total_size = read_i32(&mmap, pos);
+                let magic_code = read_i32(&mmap, pos + 4);
+                if total_size == 0 && magic_code == 0 {
+                    if !is_last_file
+                        && !visitor(CommitLogRecord::Issue(CheckIssue::new(
+                            CheckIssueKind::CommitLogTruncated,
+                            file_name.as_str(),
+                            phy_offset,
+                            "file ends without a blank entry",
+                        )))
+                    {
+                        return Ok(summary);
+                    }
+                    break;
+                }
+                if magic_code == BLANK_MAGIC_CODE {
+                    summary.max_offset = file_from_offset + mmap.len() as i64;
+                    break;
+                }
+                if MessageVersion::value_of_magic_code(magic_code).is_err() {
+                    let issue = CheckIssue::new(
+                        CheckIssueKind::CommitLogIllegalMagicCode,
+                        file_name.as_str(),
+                        phy_offset,
+                        format!("illegal magic code 0x{:X}", magic_code),
+                    );
+                    if !visitor(CommitLogRecord::Issue(issue)) {
+                        return Ok(summary);
+                    }
+                    break;
+                }
+                if total_size <= ENTRY_PREFIX_LENGTH as i32
+                    || pos + total_size as usize > mmap.len()
+                {
+                    let issue = CheckIssue::new(
+                        CheckIssueKind::CommitLogTruncated,
+                        file_name.as_str(),
+                        phy_offset,
+                        format!(
+                            "entry size {} exceeds the remaining {} bytes of the file",
+                            total_size,
+                            mmap.len() - pos
+                        ),
+                    );
+                    if !visitor(CommitLogRecord::Issue(issue)) {
+                        return Ok(summary);
+                    }
+                    break;
+                }
+                let entry = &mmap[pos..pos + total_size as usize];
+                match decode_entry(entry, phy_offset, self.check_crc) {
+                    Ok((request, issues)) => {
+                        summary.messages += 1;
+                        for issue in issues {
+                            let issue = CheckIssue {
+                                file_name: file_name.clone(),
+                                ..issue
+                            };
+                            if !visitor(CommitLogRecord::Issue(issue)) {
+                                return Ok(summary);
+                            }
+                        }
+                        if !visitor(CommitLogRecord::Message(request)) {
+                            summary.max_offset = phy_offset + total_size as i64;
+                            return Ok(summary);
+                        }
+                    }
+                    Err(issue) => {
+                        let issue = CheckIssue {
+                            file_name: file_name.clone(),
+                            ..issue
+                        };
+                        if !visitor(CommitLogRecord::Issue(issue)) {
+                            return Ok(summary);
+                        }
+                        break;
+                    }
+                }
+                pos += total_size as usize;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+/// Decodes one commit log entry into a [`DispatchRequest`].
+///
+/// Issues that do not affect the entry layout (CRC or recorded offset mismatches) are returned
+/// along with the request; layout errors are returned as `Err` because the entry boundary can no
+/// longer be trusted.
+pub fn decode_entry(
+    entry: &[u8],
+    phy_offset: i64,
+    check_crc: bool,
+) -> Result<(DispatchRequest, Vec<CheckIssue>), CheckIssue> {
+    let layout_error = |detail: String| {
+        CheckIssue::new(
+            CheckIssueKind::CommitLogSizeMismatch,
+            "",
+            phy_offset,
+            detail,
+        )
+    };
+    let mut reader = EntryReader::new(entry);
+    let mut issues = Vec::new();
+
+    let total_size = reader.get_i32().ok_or_else(|| layout_error(reader.eof()))?;
+    let magic_code = reader.get_i32().ok_or_else(|| layout_error(reader.eof()))?;
+    let message_version = MessageVersion::value_of_magic_code(magic_code).map_err(|_| {
+        CheckIssue::new(
+            CheckIssueKind::CommitLogIllegalMagicCode,
+            "",
+            phy_offset,
+            format!("illegal magic code 0x{:X}", magic_code),
+        )
+    })?;
+    let body_crc = reader.get_i32().ok_or_else(|| layout_error(reader.eof()))?;
+    let queue_id = reader.get_i32().ok_or_else(|| layout_error(reader.eof()))?;
+    let _flag = reader.get_i32().ok_or_else(|| layout_error(reader.eof()))?;
+    let queue_offset = reader.get_i64().ok_or_else(|| layout_error(reader.eof()))?;
+    let recorded_phy_offset = reader.get_i64().ok_or_else(|| layout_error(reader.eof()))?;
+    let sys_flag = reader.get_i32().ok_or_else(|| layout_error(reader.eof()))?;
+    let _born_timestamp = reader.get_i64().ok_or_else(|| layout_error(reader.eof()))?;
+    let born_host_length = if sys_flag & MessageSysFlag::BORNHOST_V6_FLAG == 0 {
+        8
+    } else {
+        20
+    };
+    reader
+        .take(born_host_length)
+        .ok_or_else(|| layout_error(reader.eof()))?;
+    let store_timestamp = reader.get_i64().ok_or_else(|| layout_error(reader.eof()))?;
+    let store_host_length = if sys_flag & MessageSysFlag::STOREHOSTADDRESS_V6_FLAG == 0 {
+        8
+    } else {
+        20
+    };
+    reader
+        .take(store_host_length)
+        .ok_or_else(|| layout_error(reader.eof()))?;
+    let _reconsume_times = reader.get_i32().ok_or_else(|| layout_error(reader.eof()))?;
+    let prepared_transaction_offset = reader.get_i64().ok_or_else(|| layout_error(reader.eof()))?;
+    let body_len = reader.get_i32().ok_or_else(|| layout_error(reader.eof()))?;
+    if body_len < 0 {
+        return Err(layout_error(format!("negative body length {}", body_len)));
+    }
+    let body = reader
+        .take(body_len as usize)
+        .ok_or_else(|| layout_error(reader.eof()))?;
+    let topic_len = if message_version.is_v1() {
+        reader.get_u8().map(|len| len as usize)
+    } else {
+        reader.get_i16().map(|len| len as u16 as usize)
+    }
+    .ok_or_else(|| layout_error(reader.eof()))?;
+    let topic = reader
+        .take(topic_len)
+        .ok_or_else(|| layout_error(reader.eof()))?;
+    let topic = String::from_utf8_lossy(topic).to_string();
+    let properties_length = reader.get_i16().ok_or_else(|| layout_error(reader.eof()))?;
+    let properties = reader
+        .take(properties_length.max(0) as usize)
+        .ok_or_else(|| layout_error(reader.eof()))?;
+
+    let read_length = MessageExtEncoder::cal_msg_length(
+        message_version,
+        sys_flag,
+        body_len,
+        topic_len as i32,
+        properties_length as i32,
+    );
+    if total_size != read_length || entry.len() != read_length as usize {
+        return Err(layout_error(format!(
+            "total size {} does not match read length {}",
+            total_size, read_length
+        )));
+    }
+
+    if check_crc && body_len > 0 {
+        let crc = crc32(body);
+        if crc != body_crc as u32 {
+            issues.push(CheckIssue::new(
+                CheckIssueKind::CommitLogCrcMismatch,
+                "",
+                phy_offset,
+                format!(
+                    "body crc {} does not match computed crc {}",
+                    body_crc as u32, crc
+                ),
+            ));
+        }
+    }
+    if recorded_phy_offset != phy_offset {
+        issues.push(CheckIssue::new(
+            CheckIssueKind::CommitLogOffsetMismatch,
+            "",
+            phy_offset,
+            format!("entry records physical offset {}", recorded_phy_offset),
+        ));
+    }
+
+    let properties_map = if properties.is_empty() {
+        Default::default()
+    } else {
+        let properties_content = String::from_utf8_lossy(properties).to_string();
+        string_to_message_properties(Some(&properties_content))
+    };
+    let mut request = DispatchRequest {
+        success: true,
+        topic,
+        queue_id,
+        commit_log_offset: phy_offset,
+        msg_size: total_size,
+        tags_code: tags_string2tags_code(properties_map.get(MessageConst::PROPERTY_TAGS)),
+        store_timestamp,
+        consume_queue_offset: queue_offset,
+        keys: properties_map
+            .get(MessageConst::PROPERTY_KEYS)
+            .cloned()
+            .unwrap_or_default(),
+        uniq_key: properties_map
+            .get(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX)
+            .cloned(),
+        sys_flag,
+        prepared_transaction_offset,
+        ..DispatchRequest::default()
+    };
+    if let (Some(base), Some(num)) = (
+        properties_map.get(MessageConst::PROPERTY_INNER_BASE),
+        properties_map.get(MessageConst::PROPERTY_INNER_NUM),
+    ) {
+        if let (Ok(base), Ok(num)) = (base.parse::<i64>(), num.parse::<i16>()) {
+            request.msg_base_offset = base;
+            request.batch_size = num;
+        }
+    }
+    request.properties_map = Some(properties_map);
+    Ok((request, issues))
+}
+
+/// Lists files named by their 20-digit start offset, ordered by offset.
+pub(crate) fn list_offset_named_files(dir: &Path) -> io::Result<Vec<(i64, PathBuf)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        if let Some(offset) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| name.len() == 20)
+            .and_then(|name| name.parse::<i64>().ok())
+        {
+            files.push((offset, path));
+        }
+    }
+    files.sort_by_key(|(offset, _)| *offset);
+    Ok(files)
+}
+
+pub(crate) fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[inline]
+pub(crate) fn read_i32(data: &[u8], pos: usize) -> i32 {
+    i32::from_be_bytes(data[pos..pos + 4].try_into().unwrap())
+}
+
+#[inline]
+pub(crate) fn read_i64(data: &[u8], pos: usize) -> i64 {
+    i64::from_be_bytes(data[pos..pos + 8].try_into().unwrap())
+}
+
+struct EntryReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> EntryReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let slice = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn get_u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn get_i16(&mut self) -> Option<i16> {
+        self.take(2)
+            .map(|bytes| i16::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn get_i32(&mut self) -> Option<i32> {
+        self.take(4)
+            .map(|bytes| i32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn get_i64(&mut self) -> Option<i64> {
+        self.take(8)
+            .map(|bytes| i64::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn eof(&self) -> String {
+        format!(
+            "entry ends unexpectedly at position {} of {}",
+            self.pos,
+            self.data.len()
+        )
+    }
+}
@@ -0,0 +1,76 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+/// Progress of a commit log replay started by
+/// [`DefaultMessageStore::rebuild_logics`](crate::message_store::default_message_store::DefaultMessageStore::rebuild_logics).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RebuildProgress {
+    pub from_offset: i64,
+    pub current_offset: i64,
+    pub max_offset: i64,
+    pub dispatched_messages: u64,
+    pub skipped_messages: u64,
+    pub finished: bool,
+}
+
+impl RebuildProgress {
+    /// Replayed share of the `[from_offset, max_offset)` range, between 0 and 100.
+    pub fn percent(&self) -> f64 {
+        let total = self.max_offset - self.from_offset;
+        if total <= 0 || self.finished {
+            return 100.0;
+        }
+        let done = (self.current_offset - self.from_offset).clamp(0, total);
+        done as f64 * 100.0 / total as f64
+    }
+}
+
+impl Display for RebuildProgress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rebuild {:.2}%, offset: {}/{}, dispatched: {}, skipped: {}",
+            self.percent(),
+            self.current_offset,
+            self.max_offset,
+            self.dispatched_messages,
+            self.skipped_messages
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_of_replayed_range() {
+        let mut progress = RebuildProgress {
+            from_offset: 100,
+            current_offset: 100,
+            max_offset: 300,
+            ..Default::default()
+        };
+        assert_eq!(progress.percent(), 0.0);
+        progress.current_offset = 200;
+        assert_eq!(progress.percent(), 50.0);
+        progress.finished = true;
+        assert_eq!(progress.percent(), 100.0);
+    }
+}
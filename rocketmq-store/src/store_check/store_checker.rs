@@ -0,0 +1,690 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::TimeUtils::get_current_millis;
+use tracing::info;
+use tracing::warn;
+
+use crate::base::commit_log_dispatcher::CommitLogDispatcher;
+use crate::base::dispatch_request::DispatchRequest;
+use crate::base::store_checkpoint::StoreCheckpoint;
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::index::index_dispatch::CommitLogDispatcherBuildIndex;
+use crate::index::index_file::index_key_hash;
+use crate::index::index_file::HASH_SLOT_SIZE;
+use crate::index::index_file::INDEX_SIZE;
+use crate::index::index_file::INVALID_INDEX;
+use crate::index::index_header::INDEX_HEADER_SIZE;
+use crate::index::index_service::build_key;
+use crate::index::index_service::IndexService;
+use crate::queue::build_consume_queue::CommitLogDispatcherBuildConsumeQueue;
+use crate::queue::local_file_consume_queue_store::ConsumeQueueStore;
+use crate::queue::single_consume_queue::CQ_STORE_UNIT_SIZE;
+use crate::queue::ConsumeQueueStoreTrait;
+use crate::store::running_flags::RunningFlags;
+use crate::store_check::check_result::CheckIssue;
+use crate::store_check::check_result::CheckIssueKind;
+use crate::store_check::check_result::StoreCheckResult;
+use crate::store_check::commit_log_scanner::file_name_of;
+use crate::store_check::commit_log_scanner::list_offset_named_files;
+use crate::store_check::commit_log_scanner::read_i32;
+use crate::store_check::commit_log_scanner::read_i64;
+use crate::store_check::commit_log_scanner::CommitLogRecord;
+use crate::store_check::commit_log_scanner::CommitLogScanner;
+use crate::store_path_config_helper::get_store_checkpoint;
+use crate::store_path_config_helper::get_store_path_consume_queue;
+use crate::store_path_config_helper::get_store_path_index;
+
+const DEFAULT_MAX_REPORTED_ISSUES: usize = 1000;
+
+/// What is remembered of each commit log entry to cross-check consume queues and indexes.
+struct MessageLocation {
+    size: i32,
+    topic: String,
+    queue_id: i32,
+    queue_offset: i64,
+    batch: bool,
+    dispatchable: bool,
+    key_hashes: Vec<i32>,
+}
+
+/// Logical range and references collected from one consume queue.
+#[derive(Default)]
+struct ConsumeQueueCoverage {
+    min_index: i64,
+    referenced: HashSet<i64>,
+}
+
+/// Outcome of [`StoreChecker::repair`].
+#[derive(Debug, Default)]
+pub struct StoreRepairResult {
+    pub dispatched_messages: u64,
+    pub skipped_messages: u64,
+    pub max_offset: i64,
+    pub backup_dirs: Vec<String>,
+}
+
+/// Offline verification of the commit log, consume queues and index files.
+///
+/// The checker reads store files directly, so it must run while the broker is stopped.
+pub struct StoreChecker {
+    message_store_config: Arc<MessageStoreConfig>,
+    check_crc: bool,
+    check_consume_queue: bool,
+    check_index: bool,
+    max_reported_issues: usize,
+}
+
+impl StoreChecker {
+    pub fn new(message_store_config: Arc<MessageStoreConfig>) -> Self {
+        Self {
+            message_store_config,
+            check_crc: true,
+            check_consume_queue: true,
+            check_index: true,
+            max_reported_issues: DEFAULT_MAX_REPORTED_ISSUES,
+        }
+    }
+
+    pub fn set_check_crc(&mut self, check_crc: bool) {
+        self.check_crc = check_crc;
+    }
+
+    pub fn set_check_consume_queue(&mut self, check_consume_queue: bool) {
+        self.check_consume_queue = check_consume_queue;
+    }
+
+    pub fn set_check_index(&mut self, check_index: bool) {
+        self.check_index = check_index;
+    }
+
+    pub fn set_max_reported_issues(&mut self, max_reported_issues: usize) {
+        self.max_reported_issues = max_reported_issues;
+    }
+
+    /// Scans the commit log and cross-checks every consume queue unit and index entry
+    /// against it.
+    pub fn check(&self) -> io::Result<StoreCheckResult> {
+        let mut result = StoreCheckResult::new(self.max_reported_issues);
+        let mut messages = HashMap::new();
+        let scanner = CommitLogScanner::new(
+            self.message_store_config.get_store_path_commit_log(),
+            self.check_crc,
+        );
+        let summary = scanner.scan(0, |record| {
+            match record {
+                CommitLogRecord::Message(request) => {
+                    messages.insert(request.commit_log_offset, to_location(&request));
+                }
+                CommitLogRecord::Issue(issue) => result.add_issue(issue),
+            }
+            true
+        })?;
+        result.commit_log_files = summary.files;
+        result.commit_log_messages = summary.messages;
+        result.commit_log_min_offset = summary.min_offset;
+        result.commit_log_max_offset = summary.max_offset;
+
+        if self.check_consume_queue {
+            self.check_consume_queues(&messages, &mut result)?;
+        }
+        if self.check_index {
+            self.check_index_files(&messages, &mut result)?;
+        }
+        info!("store check finished, {}", result);
+        Ok(result)
+    }
+
+    /// Rebuilds consume queues and/or index files by replaying the commit log.
+    ///
+    /// Existing directories are renamed with a `.bak.<timestamp>` suffix instead of being
+    /// deleted. Messages that fail their CRC check are not dispatched. Index building creates
+    /// files from a Tokio task, so this must be called from within a Tokio runtime.
+    pub fn repair(
+        &self,
+        rebuild_consume_queue: bool,
+        rebuild_index: bool,
+    ) -> io::Result<StoreRepairResult> {
+        let root_dir = self.message_store_config.store_path_root_dir.as_str();
+        let mut repair_result = StoreRepairResult::default();
+        if !rebuild_consume_queue && !rebuild_index {
+            return Ok(repair_result);
+        }
+        let suffix = format!(".bak.{}", get_current_millis());
+        if rebuild_consume_queue {
+            if let Some(backup) = backup_dir(&get_store_path_consume_queue(root_dir), &suffix)? {
+                repair_result.backup_dirs.push(backup);
+            }
+        }
+        if rebuild_index {
+            if let Some(backup) = backup_dir(&get_store_path_index(root_dir), &suffix)? {
+                repair_result.backup_dirs.push(backup);
+            }
+        }
+
+        let store_checkpoint = Arc::new(StoreCheckpoint::new(get_store_checkpoint(root_dir))?);
+        let mut dispatchers: Vec<Box<dyn CommitLogDispatcher>> = Vec::new();
+        let consume_queue_store = if rebuild_consume_queue {
+            let consume_queue_store = ConsumeQueueStore::new(
+                self.message_store_config.clone(),
+                Arc::new(BrokerConfig::default()),
+                Arc::new(parking_lot::Mutex::new(HashMap::new())),
+                Arc::new(RunningFlags::new()),
+                store_checkpoint.clone(),
+            );
+            dispatchers.push(Box::new(CommitLogDispatcherBuildConsumeQueue::new(
+                consume_queue_store.clone(),
+            )));
+            Some(consume_queue_store)
+        } else {
+            None
+        };
+        let index_service = if rebuild_index {
+            let index_service =
+                IndexService::new(self.message_store_config.clone(), store_checkpoint.clone());
+            dispatchers.push(Box::new(CommitLogDispatcherBuildIndex::new(
+                index_service.clone(),
+                self.message_store_config.clone(),
+            )));
+            Some(index_service)
+        } else {
+            None
+        };
+
+        let scanner = CommitLogScanner::new(
+            self.message_store_config.get_store_path_commit_log(),
+            self.check_crc,
+        );
+        let mut last_store_timestamp = 0i64;
+        let mut corrupted_offset = -1i64;
+        let summary = scanner.scan(0, |record| {
+            match record {
+                CommitLogRecord::Message(request) => {
+                    if request.commit_log_offset == corrupted_offset {
+                        repair_result.skipped_messages += 1;
+                    } else {
+                        for dispatcher in dispatchers.iter() {
+                            dispatcher.dispatch(&request);
+                        }
+                        last_store_timestamp = request.store_timestamp;
+                        repair_result.dispatched_messages += 1;
+                    }
+                }
+                CommitLogRecord::Issue(issue) => {
+                    warn!("skip corrupted commit log entry while repairing, {}", issue);
+                    if issue.kind == CheckIssueKind::CommitLogCrcMismatch {
+                        corrupted_offset = issue.offset;
+                    }
+                }
+            }
+            true
+        })?;
+        repair_result.max_offset = summary.max_offset;
+
+        if let Some(consume_queue_store) = consume_queue_store {
+            for consume_queues in consume_queue_store
+                .get_consume_queue_table()
+                .lock()
+                .values()
+            {
+                for consume_queue in consume_queues.values() {
+                    while !consume_queue.flush(0) {}
+                }
+            }
+            store_checkpoint.set_logics_msg_timestamp(last_store_timestamp as u64);
+        }
+        if let Some(index_service) = index_service {
+            index_service.shutdown();
+            store_checkpoint.set_index_msg_timestamp(last_store_timestamp as u64);
+        }
+        store_checkpoint.flush()?;
+        info!("store repair finished, {:?}", repair_result);
+        Ok(repair_result)
+    }
+
+    fn check_consume_queues(
+        &self,
+        messages: &HashMap<i64, MessageLocation>,
+        result: &mut StoreCheckResult,
+    ) -> io::Result<()> {
+        let root = get_store_path_consume_queue(&self.message_store_config.store_path_root_dir);
+        let root = Path::new(&root);
+        let mut coverages: HashMap<(String, i32), ConsumeQueueCoverage> = HashMap::new();
+        if root.exists() {
+            for topic_dir in fs::read_dir(root)? {
+                let topic_dir = topic_dir?.path();
+                if !topic_dir.is_dir() {
+                    continue;
+                }
+                let topic = file_name_of(&topic_dir);
+                for queue_dir in fs::read_dir(&topic_dir)? {
+                    let queue_dir = queue_dir?.path();
+                    let Ok(queue_id) = file_name_of(&queue_dir).parse::<i32>() else {
+                        continue;
+                    };
+                    let coverage =
+                        self.check_consume_queue(&topic, queue_id, &queue_dir, messages, result)?;
+                    result.consume_queue_count += 1;
+                    coverages.insert((topic.clone(), queue_id), coverage);
+                }
+            }
+        }
+
+        let mut missing_queues: HashMap<(String, i32), u64> = HashMap::new();
+        for (phy_offset, location) in messages {
+            if !location.dispatchable {
+                continue;
+            }
+            let key = (location.topic.clone(), location.queue_id);
+            match coverages.get(&key) {
+                None => *missing_queues.entry(key).or_insert(0) += 1,
+                Some(coverage) => {
+                    if !coverage.referenced.contains(phy_offset)
+                        && location.queue_offset >= coverage.min_index
+                    {
+                        result.add_issue(CheckIssue::new(
+                            CheckIssueKind::ConsumeQueueUnitMissing,
+                            format!("{}/{}", location.topic, location.queue_id),
+                            location.queue_offset * CQ_STORE_UNIT_SIZE as i64,
+                            format!(
+                                "message at physical offset {} is not in the consume queue",
+                                phy_offset
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        for ((topic, queue_id), count) in missing_queues {
+            result.add_issue(CheckIssue::new(
+                CheckIssueKind::ConsumeQueueUnitMissing,
+                format!("{}/{}", topic, queue_id),
+                0,
+                format!("consume queue does not exist for {} messages", count),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_consume_queue(
+        &self,
+        topic: &str,
+        queue_id: i32,
+        queue_dir: &Path,
+        messages: &HashMap<i64, MessageLocation>,
+        result: &mut StoreCheckResult,
+    ) -> io::Result<ConsumeQueueCoverage> {
+        let unit_size = CQ_STORE_UNIT_SIZE as usize;
+        let mut coverage = ConsumeQueueCoverage {
+            min_index: i64::MAX,
+            ..Default::default()
+        };
+        for (file_from_offset, path) in list_offset_named_files(queue_dir)? {
+            let file = File::open(&path)?;
+            if file.metadata()?.len() == 0 {
+                continue;
+            }
+            // SAFETY: the file is only read while the broker is stopped.
+            let mmap = unsafe { Mmap::map(&file)? };
+            let file_name = format!("{}/{}/{}", topic, queue_id, file_name_of(&path));
+            let mut pos = 0usize;
+            while pos + unit_size <= mmap.len() {
+                let phy_offset = read_i64(&mmap, pos);
+                let size = read_i32(&mmap, pos + 8);
+                let logic_offset = file_from_offset + pos as i64;
+                pos += unit_size;
+                if phy_offset == 0 && size == i32::MAX {
+                    // blank unit filled before the first written unit of a queue
+                    continue;
+                }
+                if phy_offset == 0 && size == 0 {
+                    break;
+                }
+                let queue_index = logic_offset / unit_size as i64;
+                coverage.min_index = coverage.min_index.min(queue_index);
+                result.consume_queue_units += 1;
+                if phy_offset < result.commit_log_min_offset {
+                    continue;
+                }
+                if phy_offset >= result.commit_log_max_offset {
+                    result.add_issue(CheckIssue::new(
+                        CheckIssueKind::ConsumeQueueBeyondCommitLog,
+                        file_name.as_str(),
+                        logic_offset,
+                        format!(
+                            "physical offset {} is beyond commit log max offset {}",
+                            phy_offset, result.commit_log_max_offset
+                        ),
+                    ));
+                    continue;
+                }
+                let Some(location) = messages.get(&phy_offset) else {
+                    result.add_issue(CheckIssue::new(
+                        CheckIssueKind::ConsumeQueueUnitNotFound,
+                        file_name.as_str(),
+                        logic_offset,
+                        format!("no message starts at physical offset {}", phy_offset),
+                    ));
+                    continue;
+                };
+                coverage.referenced.insert(phy_offset);
+                if location.size != size {
+                    result.add_issue(CheckIssue::new(
+                        CheckIssueKind::ConsumeQueueSizeMismatch,
+                        file_name.as_str(),
+                        logic_offset,
+                        format!("unit size {}, message size {}", size, location.size),
+                    ));
+                }
+                if location.topic != topic || location.queue_id != queue_id {
+                    result.add_issue(CheckIssue::new(
+                        CheckIssueKind::ConsumeQueueTopicMismatch,
+                        file_name.as_str(),
+                        logic_offset,
+                        format!(
+                            "message belongs to {}/{}",
+                            location.topic, location.queue_id
+                        ),
+                    ));
+                } else if !location.batch && location.queue_offset != queue_index {
+                    result.add_issue(CheckIssue::new(
+                        CheckIssueKind::ConsumeQueueOffsetMismatch,
+                        file_name.as_str(),
+                        logic_offset,
+                        format!(
+                            "unit index {}, message queue offset {}",
+                            queue_index, location.queue_offset
+                        ),
+                    ));
+                }
+            }
+        }
+        if coverage.min_index == i64::MAX {
+            coverage.min_index = 0;
+        }
+        Ok(coverage)
+    }
+
+    fn check_index_files(
+        &self,
+        messages: &HashMap<i64, MessageLocation>,
+        result: &mut StoreCheckResult,
+    ) -> io::Result<()> {
+        let index_dir = get_store_path_index(&self.message_store_config.store_path_root_dir);
+        let index_dir = Path::new(&index_dir);
+        if !index_dir.exists() {
+            return Ok(());
+        }
+        let hash_slot_num = self.message_store_config.max_hash_slot_num as usize;
+        let index_num = self.message_store_config.max_index_num as usize;
+        let expected_size =
+            INDEX_HEADER_SIZE + hash_slot_num * HASH_SLOT_SIZE + index_num * INDEX_SIZE;
+        let mut files = fs::read_dir(index_dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        files.sort();
+        for path in files {
+            result.index_files += 1;
+            let file_name = file_name_of(&path);
+            let file = File::open(&path)?;
+            let file_len = file.metadata()?.len() as usize;
+            if file_len != expected_size {
+                result.add_issue(CheckIssue::new(
+                    CheckIssueKind::IndexFileCorrupted,
+                    file_name.as_str(),
+                    0,
+                    format!("file size {}, expected {}", file_len, expected_size),
+                ));
+                continue;
+            }
+            // SAFETY: the file is only read while the broker is stopped.
+            let mmap = unsafe { Mmap::map(&file)? };
+            let index_count = read_i32(&mmap, 36);
+            if index_count < 0 || index_count as usize > index_num {
+                result.add_issue(CheckIssue::new(
+                    CheckIssueKind::IndexFileCorrupted,
+                    file_name.as_str(),
+                    36,
+                    format!("illegal index count {}", index_count),
+                ));
+                continue;
+            }
+            let index_count = index_count.max(1);
+
+            for slot in 0..hash_slot_num {
+                let slot_pos = INDEX_HEADER_SIZE + slot * HASH_SLOT_SIZE;
+                let slot_value = read_i32(&mmap, slot_pos);
+                if slot_value < INVALID_INDEX || slot_value >= index_count {
+                    result.add_issue(CheckIssue::new(
+                        CheckIssueKind::IndexSlotInvalid,
+                        file_name.as_str(),
+                        slot_pos as i64,
+                        format!(
+                            "slot points to index {}, index count {}",
+                            slot_value, index_count
+                        ),
+                    ));
+                }
+            }
+
+            let index_base = INDEX_HEADER_SIZE + hash_slot_num * HASH_SLOT_SIZE;
+            for index in 1..index_count {
+                let index_pos = index_base + index as usize * INDEX_SIZE;
+                let key_hash = read_i32(&mmap, index_pos);
+                let phy_offset = read_i64(&mmap, index_pos + 4);
+                let prev_index = read_i32(&mmap, index_pos + 16);
+                result.index_entries += 1;
+                if prev_index < INVALID_INDEX || prev_index >= index {
+                    result.add_issue(CheckIssue::new(
+                        CheckIssueKind::IndexLinkInvalid,
+                        file_name.as_str(),
+                        index_pos as i64,
+                        format!("index {} links to index {}", index, prev_index),
+                    ));
+                }
+                if phy_offset < result.commit_log_min_offset {
+                    continue;
+                }
+                match messages.get(&phy_offset) {
+                    None => result.add_issue(CheckIssue::new(
+                        CheckIssueKind::IndexOffsetNotFound,
+                        file_name.as_str(),
+                        index_pos as i64,
+                        format!("no message starts at physical offset {}", phy_offset),
+                    )),
+                    Some(location) => {
+                        if !location.key_hashes.contains(&key_hash) {
+                            result.add_issue(CheckIssue::new(
+                                CheckIssueKind::IndexKeyMismatch,
+                                file_name.as_str(),
+                                index_pos as i64,
+                                format!(
+                                    "key hash {} matches no key of the message at physical offset \
+                                     {}",
+                                    key_hash, phy_offset
+                                ),
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_location(request: &DispatchRequest) -> MessageLocation {
+    let tran_type = MessageSysFlag::get_transaction_value(request.sys_flag);
+    let mut key_hashes = Vec::new();
+    if let Some(ref uniq_key) = request.uniq_key {
+        key_hashes.push(index_key_hash(&build_key(&request.topic, uniq_key)));
+    }
+    for key in request.keys.split(MessageConst::KEY_SEPARATOR) {
+        if !key.is_empty() {
+            key_hashes.push(index_key_hash(&build_key(&request.topic, key)));
+        }
+    }
+    MessageLocation {
+        size: request.msg_size,
+        topic: request.topic.clone(),
+        queue_id: request.queue_id,
+        queue_offset: request.consume_queue_offset,
+        batch: request.batch_size > 1,
+        dispatchable: tran_type == MessageSysFlag::TRANSACTION_NOT_TYPE
+            || tran_type == MessageSysFlag::TRANSACTION_COMMIT_TYPE,
+        key_hashes,
+    }
+}
+
+fn backup_dir(dir: &str, suffix: &str) -> io::Result<Option<String>> {
+    if !Path::new(dir).exists() {
+        return Ok(None);
+    }
+    let backup = format!("{}{}", dir, suffix);
+    fs::rename(dir, &backup)?;
+    info!("backup {} to {}", dir, backup);
+    Ok(Some(backup))
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::UtilAll::offset_to_file_name;
+
+    use super::*;
+    use crate::test_util::create_store;
+    use crate::test_util::TestStore;
+    use crate::test_util::TOPIC;
+
+    fn consume_queue_file(store: &TestStore) -> std::path::PathBuf {
+        Path::new(&get_store_path_consume_queue(
+            &store.config.store_path_root_dir,
+        ))
+        .join(TOPIC)
+        .join("0")
+        .join(offset_to_file_name(0))
+    }
+
+    #[test]
+    fn check_consistent_store() {
+        let store = create_store(5);
+        let result = StoreChecker::new(store.config.clone()).check().unwrap();
+        assert!(result.is_ok(), "{:?}", result.issues);
+        assert_eq!(result.commit_log_files, 1);
+        assert_eq!(result.commit_log_messages, 5);
+        assert_eq!(result.consume_queue_count, 1);
+        assert_eq!(result.consume_queue_units, 5);
+        let (last_offset, last_size) = store.messages[4];
+        assert_eq!(result.commit_log_max_offset, last_offset + last_size as i64);
+    }
+
+    #[test]
+    fn check_reports_crc_mismatch() {
+        let store = create_store(3);
+        let path =
+            Path::new(&store.config.get_store_path_commit_log()).join(offset_to_file_name(0));
+        let mut data = fs::read(&path).unwrap();
+        let (phy_offset, size) = store.messages[1];
+        // the body starts right after the fixed-length fields of a V1 entry with IPv4 hosts
+        let body_pos = phy_offset as usize + 92;
+        assert!(body_pos < (phy_offset + size as i64) as usize);
+        data[body_pos] ^= 0xFF;
+        fs::write(&path, &data).unwrap();
+
+        let result = StoreChecker::new(store.config.clone()).check().unwrap();
+        assert_eq!(result.count_of(CheckIssueKind::CommitLogCrcMismatch), 1);
+        assert_eq!(result.issues[0].offset, phy_offset);
+        assert_eq!(result.commit_log_messages, 3);
+    }
+
+    #[test]
+    fn check_reports_illegal_magic_code() {
+        let store = create_store(3);
+        let path =
+            Path::new(&store.config.get_store_path_commit_log()).join(offset_to_file_name(0));
+        let mut data = fs::read(&path).unwrap();
+        let (phy_offset, _) = store.messages[2];
+        data[phy_offset as usize + 4] = 0;
+        fs::write(&path, &data).unwrap();
+
+        let result = StoreChecker::new(store.config.clone()).check().unwrap();
+        assert_eq!(
+            result.count_of(CheckIssueKind::CommitLogIllegalMagicCode),
+            1
+        );
+        assert_eq!(result.commit_log_messages, 2);
+        assert_eq!(
+            result.count_of(CheckIssueKind::ConsumeQueueBeyondCommitLog),
+            1
+        );
+    }
+
+    #[test]
+    fn check_reports_consume_queue_mismatches() {
+        let store = create_store(4);
+        let path = consume_queue_file(&store);
+        let mut data = fs::read(&path).unwrap();
+        let unit_size = CQ_STORE_UNIT_SIZE as usize;
+        // unit 1 has a wrong size, unit 2 points into the middle of a message
+        data[unit_size + 8..unit_size + 12].copy_from_slice(&7i32.to_be_bytes());
+        let (phy_offset, _) = store.messages[2];
+        data[2 * unit_size..2 * unit_size + 8].copy_from_slice(&(phy_offset + 1).to_be_bytes());
+        fs::write(&path, &data).unwrap();
+
+        let mut checker = StoreChecker::new(store.config.clone());
+        checker.set_check_index(false);
+        let result = checker.check().unwrap();
+        assert_eq!(result.count_of(CheckIssueKind::ConsumeQueueSizeMismatch), 1);
+        assert_eq!(result.count_of(CheckIssueKind::ConsumeQueueUnitNotFound), 1);
+        assert_eq!(result.count_of(CheckIssueKind::ConsumeQueueUnitMissing), 1);
+        assert_eq!(result.total_issues(), 3);
+    }
+
+    #[tokio::test]
+    async fn repair_rebuilds_consume_queue_and_index() {
+        let store = create_store(6);
+        fs::remove_file(consume_queue_file(&store)).unwrap();
+        let result = StoreChecker::new(store.config.clone()).check().unwrap();
+        assert_eq!(result.count_of(CheckIssueKind::ConsumeQueueUnitMissing), 6);
+
+        let repair_result = StoreChecker::new(store.config.clone())
+            .repair(true, true)
+            .unwrap();
+        assert_eq!(repair_result.dispatched_messages, 6);
+        assert_eq!(repair_result.skipped_messages, 0);
+        assert_eq!(repair_result.backup_dirs.len(), 1);
+
+        let result = StoreChecker::new(store.config.clone()).check().unwrap();
+        assert!(result.is_ok(), "{:?}", result.issues);
+        assert_eq!(result.consume_queue_units, 6);
+        assert_eq!(result.index_files, 1);
+        // one entry for the unique key and one for the business key of each message
+        assert_eq!(result.index_entries, 12);
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! Fixtures shared by the store tests.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use bytes::BufMut;
+use bytes::BytesMut;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageVersion;
+use rocketmq_common::CRC32Utils::crc32;
+use rocketmq_common::MessageDecoder::NAME_VALUE_SEPARATOR;
+use rocketmq_common::MessageDecoder::PROPERTY_SEPARATOR;
+use rocketmq_common::UtilAll::offset_to_file_name;
+use tempfile::TempDir;
+
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::message_encoder::message_ext_encoder::MessageExtEncoder;
+use crate::queue::single_consume_queue::CQ_STORE_UNIT_SIZE;
+use crate::store_path_config_helper::get_store_path_consume_queue;
+
+pub(crate) const TOPIC: &str = "TopicTest";
+
+fn encode_message(phy_offset: i64, queue_offset: i64, body: &[u8], key: &str) -> Vec<u8> {
+    let properties = format!(
+        "{}{}{}{}{}{}UNIQ{}{}",
+        MessageConst::PROPERTY_KEYS,
+        NAME_VALUE_SEPARATOR,
+        key,
+        PROPERTY_SEPARATOR,
+        MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX,
+        NAME_VALUE_SEPARATOR,
+        queue_offset,
+        PROPERTY_SEPARATOR
+    );
+    let total_size = MessageExtEncoder::cal_msg_length(
+        MessageVersion::V1,
+        0,
+        body.len() as i32,
+        TOPIC.len() as i32,
+        properties.len() as i32,
+    );
+    let mut buf = BytesMut::new();
+    buf.put_i32(total_size);
+    buf.put_i32(MessageVersion::V1.get_magic_code());
+    buf.put_u32(crc32(body));
+    buf.put_i32(0); // queue id
+    buf.put_i32(0); // flag
+    buf.put_i64(queue_offset);
+    buf.put_i64(phy_offset);
+    buf.put_i32(0); // sys flag
+    buf.put_i64(1_700_000_000_000); // born timestamp
+    buf.put_slice(&[127, 0, 0, 1, 0, 0, 0x27, 0x10]);
+    buf.put_i64(1_700_000_000_000 + queue_offset); // store timestamp
+    buf.put_slice(&[127, 0, 0, 1, 0, 0, 0x27, 0x11]);
+    buf.put_i32(0); // reconsume times
+    buf.put_i64(0); // prepared transaction offset
+    buf.put_i32(body.len() as i32);
+    buf.put_slice(body);
+    buf.put_u8(TOPIC.len() as u8);
+    buf.put_slice(TOPIC.as_bytes());
+    buf.put_i16(properties.len() as i16);
+    buf.put_slice(properties.as_bytes());
+    assert_eq!(buf.len(), total_size as usize);
+    buf.to_vec()
+}
+
+pub(crate) struct TestStore {
+    _dir: TempDir,
+    pub(crate) config: Arc<MessageStoreConfig>,
+    /// (physical offset, size) of each written message
+    pub(crate) messages: Vec<(i64, i32)>,
+}
+
+/// Writes `message_count` messages of [`TOPIC`] queue 0 to a 4096 bytes commit log file,
+/// the store timestamp of each message is 1_700_000_000_000 plus its queue offset.
+pub(crate) fn create_store(message_count: i64) -> TestStore {
+    let dir = TempDir::new().unwrap();
+    let config = MessageStoreConfig {
+        store_path_root_dir: dir.path().to_string_lossy().to_string(),
+        mapped_file_size_consume_queue: CQ_STORE_UNIT_SIZE as usize * 100,
+        max_hash_slot_num: 16,
+        max_index_num: 64,
+        ..MessageStoreConfig::default()
+    };
+    let commit_log_dir = Path::new(&config.get_store_path_commit_log()).to_path_buf();
+    fs::create_dir_all(&commit_log_dir).unwrap();
+    let mut commit_log = Vec::new();
+    let mut messages = Vec::new();
+    for queue_offset in 0..message_count {
+        let phy_offset = commit_log.len() as i64;
+        let data = encode_message(
+            phy_offset,
+            queue_offset,
+            format!("body-{}", queue_offset).as_bytes(),
+            &format!("order-{}", queue_offset),
+        );
+        messages.push((phy_offset, data.len() as i32));
+        commit_log.extend_from_slice(&data);
+    }
+    commit_log.resize(4096, 0);
+    fs::write(commit_log_dir.join(offset_to_file_name(0)), &commit_log).unwrap();
+
+    let queue_dir = Path::new(&get_store_path_consume_queue(&config.store_path_root_dir))
+        .join(TOPIC)
+        .join("0");
+    fs::create_dir_all(&queue_dir).unwrap();
+    let mut units = BytesMut::new();
+    for (phy_offset, size) in &messages {
+        units.put_i64(*phy_offset);
+        units.put_i32(*size);
+        units.put_i64(0);
+    }
+    fs::write(queue_dir.join(offset_to_file_name(0)), &units).unwrap();
+    TestStore {
+        _dir: dir,
+        config: Arc::new(config),
+        messages,
+    }
+}
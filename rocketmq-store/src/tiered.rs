@@ -0,0 +1,28 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Offloading of sealed commit log and consume queue files to a secondary storage.
+//!
+//! Files are uploaded once they are sealed, so the remote copy of a file never changes after it
+//! has been committed. Reads of offsets whose local files have been deleted are served from the
+//! backend through [`tiered_store_service::TieredStoreService`].
+
+pub mod file_segment;
+pub mod object_store_backend;
+pub mod posix_backend;
+pub mod segment_index;
+pub mod tiered_store_service;
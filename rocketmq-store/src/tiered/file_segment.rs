@@ -0,0 +1,150 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+/// Kind of local file a tiered segment is a copy of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FileSegmentType {
+    CommitLog,
+    ConsumeQueue,
+}
+
+impl FileSegmentType {
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            FileSegmentType::CommitLog => "commitlog",
+            FileSegmentType::ConsumeQueue => "consumequeue",
+        }
+    }
+}
+
+impl fmt::Display for FileSegmentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.dir_name())
+    }
+}
+
+/// Returns the backend relative path of a segment.
+///
+/// Commit log segments ignore `prefix`; consume queue segments use `{topic}/{queue_id}`, the same
+/// layout as the local consume queue directory.
+pub fn segment_path(file_type: FileSegmentType, prefix: &str, base_offset: i64) -> String {
+    if prefix.is_empty() {
+        format!("{}/{:020}", file_type.dir_name(), base_offset)
+    } else {
+        format!("{}/{}/{:020}", file_type.dir_name(), prefix, base_offset)
+    }
+}
+
+/// A sealed local file stored in a tiered storage backend.
+///
+/// A segment is written once by [`commit`](TieredFileSegment::commit) and is read-only
+/// afterwards.
+pub trait TieredFileSegment: Send + Sync {
+    fn file_type(&self) -> FileSegmentType;
+
+    /// Offset of the first byte of the segment, the name of the local file it was copied from.
+    fn base_offset(&self) -> i64;
+
+    /// Backend relative path of the segment.
+    fn path(&self) -> &str;
+
+    /// Size of the committed segment, `0` when it does not exist.
+    fn size(&self) -> io::Result<i64>;
+
+    fn exists(&self) -> bool;
+
+    /// Stores `data` as the whole content of the segment, replacing any previous content.
+    fn commit(&self, data: Bytes) -> io::Result<()>;
+
+    /// Reads `length` bytes starting at `position`, relative to the segment start.
+    fn read(&self, position: i64, length: i32) -> io::Result<Bytes>;
+
+    fn destroy(&self) -> io::Result<()>;
+}
+
+/// Factory of [`TieredFileSegment`]s, one implementation per kind of secondary storage.
+pub trait TieredStoreBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn open_segment(
+        &self,
+        file_type: FileSegmentType,
+        prefix: &str,
+        base_offset: i64,
+    ) -> Arc<dyn TieredFileSegment>;
+
+    /// Lists the base offsets of the committed segments under `prefix`, in ascending order.
+    fn list_segments(&self, file_type: FileSegmentType, prefix: &str) -> io::Result<Vec<i64>>;
+
+    /// Lists the prefixes that have at least one committed segment of `file_type`.
+    fn list_prefixes(&self, file_type: FileSegmentType) -> io::Result<Vec<String>>;
+}
+
+/// Checks that `[position, position + length)` lies inside a segment of `size` bytes.
+pub(crate) fn check_read_range(
+    path: &str,
+    size: i64,
+    position: i64,
+    length: i32,
+) -> io::Result<()> {
+    if position < 0 || length < 0 || position + length as i64 > size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "read [{}, {}) out of segment {} of size {}",
+                position,
+                position + length as i64,
+                path,
+                size
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Parses a 20-digit offset file name.
+pub(crate) fn parse_offset_name(name: &str) -> Option<i64> {
+    if name.len() != 20 {
+        return None;
+    }
+    name.parse::<i64>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_path_follows_local_layout() {
+        assert_eq!(
+            segment_path(FileSegmentType::CommitLog, "", 1024),
+            "commitlog/00000000000000001024"
+        );
+        assert_eq!(
+            segment_path(FileSegmentType::ConsumeQueue, "TopicTest/3", 0),
+            "consumequeue/TopicTest/3/00000000000000000000"
+        );
+        assert_eq!(parse_offset_name("00000000000000001024"), Some(1024));
+        assert_eq!(parse_offset_name("1024"), None);
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::io;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use parking_lot::RwLock;
+
+use crate::tiered::file_segment::parse_offset_name;
+use crate::tiered::file_segment::segment_path;
+use crate::tiered::file_segment::FileSegmentType;
+use crate::tiered::file_segment::TieredFileSegment;
+use crate::tiered::file_segment::TieredStoreBackend;
+
+/// The subset of an S3-like object storage API the tiered store relies on.
+///
+/// Implementations wrap a concrete SDK; keys are `/` separated and objects are immutable once
+/// put.
+pub trait ObjectStoreClient: Send + Sync {
+    fn put_object(&self, key: &str, data: Bytes) -> io::Result<()>;
+
+    /// Reads `length` bytes of the object starting at `start`, like a ranged GET.
+    fn get_object_range(&self, key: &str, start: u64, length: u64) -> io::Result<Bytes>;
+
+    /// Returns the size of the object, `None` when it does not exist.
+    fn head_object(&self, key: &str) -> io::Result<Option<u64>>;
+
+    fn delete_object(&self, key: &str) -> io::Result<()>;
+
+    /// Lists the keys starting with `prefix`, in lexicographic order.
+    fn list_objects(&self, prefix: &str) -> io::Result<Vec<String>>;
+}
+
+/// In-memory [`ObjectStoreClient`], for tests and local experiments.
+#[derive(Default)]
+pub struct MemoryObjectStoreClient {
+    objects: RwLock<BTreeMap<String, Bytes>>,
+    get_requests: AtomicU64,
+}
+
+impl MemoryObjectStoreClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of ranged GET requests served so far.
+    pub fn get_requests(&self) -> u64 {
+        self.get_requests.load(Ordering::Relaxed)
+    }
+
+    pub fn object_count(&self) -> usize {
+        self.objects.read().len()
+    }
+}
+
+impl ObjectStoreClient for MemoryObjectStoreClient {
+    fn put_object(&self, key: &str, data: Bytes) -> io::Result<()> {
+        self.objects.write().insert(key.to_string(), data);
+        Ok(())
+    }
+
+    fn get_object_range(&self, key: &str, start: u64, length: u64) -> io::Result<Bytes> {
+        self.get_requests.fetch_add(1, Ordering::Relaxed);
+        let objects = self.objects.read();
+        let object = objects.get(key).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no such key {}", key))
+        })?;
+        let end = start + length;
+        if end > object.len() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("range [{}, {}) out of object {}", start, end, key),
+            ));
+        }
+        Ok(object.slice(start as usize..end as usize))
+    }
+
+    fn head_object(&self, key: &str) -> io::Result<Option<u64>> {
+        Ok(self
+            .objects
+            .read()
+            .get(key)
+            .map(|object| object.len() as u64))
+    }
+
+    fn delete_object(&self, key: &str) -> io::Result<()> {
+        self.objects.write().remove(key);
+        Ok(())
+    }
+
+    fn list_objects(&self, prefix: &str) -> io::Result<Vec<String>> {
+        Ok(self
+            .objects
+            .read()
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, _)| key.clone())
+            .collect())
+    }
+}
+
+/// Stores every segment as one object, under `key_prefix` of the bucket wrapped by the client.
+pub struct ObjectStoreBackend<C> {
+    client: Arc<C>,
+    key_prefix: String,
+}
+
+impl<C: ObjectStoreClient + 'static> ObjectStoreBackend<C> {
+    /// `key_prefix` separates the segments of several brokers sharing a bucket, e.g.
+    /// `"cluster/broker-a/"`.
+    pub fn new(client: Arc<C>, key_prefix: impl Into<String>) -> Self {
+        Self {
+            client,
+            key_prefix: key_prefix.into(),
+        }
+    }
+
+    pub fn client(&self) -> &Arc<C> {
+        &self.client
+    }
+
+    fn dir_key(&self, file_type: FileSegmentType, prefix: &str) -> String {
+        if prefix.is_empty() {
+            format!("{}{}/", self.key_prefix, file_type.dir_name())
+        } else {
+            format!("{}{}/{}/", self.key_prefix, file_type.dir_name(), prefix)
+        }
+    }
+}
+
+impl<C: ObjectStoreClient + 'static> TieredStoreBackend for ObjectStoreBackend<C> {
+    fn name(&self) -> &'static str {
+        "object-store"
+    }
+
+    fn open_segment(
+        &self,
+        file_type: FileSegmentType,
+        prefix: &str,
+        base_offset: i64,
+    ) -> Arc<dyn TieredFileSegment> {
+        let path = segment_path(file_type, prefix, base_offset);
+        Arc::new(ObjectFileSegment {
+            client: self.client.clone(),
+            file_type,
+            base_offset,
+            key: format!("{}{}", self.key_prefix, path),
+            path,
+        })
+    }
+
+    fn list_segments(&self, file_type: FileSegmentType, prefix: &str) -> io::Result<Vec<i64>> {
+        let dir_key = self.dir_key(file_type, prefix);
+        let mut offsets = self
+            .client
+            .list_objects(&dir_key)?
+            .iter()
+            .filter_map(|key| parse_offset_name(&key[dir_key.len()..]))
+            .collect::<Vec<_>>();
+        offsets.sort_unstable();
+        Ok(offsets)
+    }
+
+    fn list_prefixes(&self, file_type: FileSegmentType) -> io::Result<Vec<String>> {
+        let dir_key = self.dir_key(file_type, "");
+        let mut prefixes = BTreeSet::new();
+        for key in self.client.list_objects(&dir_key)? {
+            let relative = &key[dir_key.len()..];
+            let (prefix, name) = relative.rsplit_once('/').unwrap_or(("", relative));
+            if parse_offset_name(name).is_some() {
+                prefixes.insert(prefix.to_string());
+            }
+        }
+        Ok(prefixes.into_iter().collect())
+    }
+}
+
+struct ObjectFileSegment<C> {
+    client: Arc<C>,
+    file_type: FileSegmentType,
+    base_offset: i64,
+    path: String,
+    key: String,
+}
+
+impl<C: ObjectStoreClient> TieredFileSegment for ObjectFileSegment<C> {
+    fn file_type(&self) -> FileSegmentType {
+        self.file_type
+    }
+
+    fn base_offset(&self) -> i64 {
+        self.base_offset
+    }
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn size(&self) -> io::Result<i64> {
+        Ok(self.client.head_object(&self.key)?.unwrap_or(0) as i64)
+    }
+
+    fn exists(&self) -> bool {
+        matches!(self.client.head_object(&self.key), Ok(Some(_)))
+    }
+
+    fn commit(&self, data: Bytes) -> io::Result<()> {
+        self.client.put_object(&self.key, data)
+    }
+
+    fn read(&self, position: i64, length: i32) -> io::Result<Bytes> {
+        if position < 0 || length < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid read [{}, +{}) of segment {}",
+                    position, length, self.path
+                ),
+            ));
+        }
+        self.client
+            .get_object_range(&self.key, position as u64, length as u64)
+    }
+
+    fn destroy(&self) -> io::Result<()> {
+        self.client.delete_object(&self.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_are_stored_as_objects() {
+        let client = Arc::new(MemoryObjectStoreClient::new());
+        let backend = ObjectStoreBackend::new(client.clone(), "cluster/broker-a/");
+
+        let segment = backend.open_segment(FileSegmentType::CommitLog, "", 1024);
+        assert!(!segment.exists());
+        segment.commit(Bytes::from_static(b"0123456789")).unwrap();
+        assert_eq!(segment.size().unwrap(), 10);
+        assert_eq!(segment.read(6, 4).unwrap(), Bytes::from_static(b"6789"));
+        assert!(segment.read(8, 4).is_err());
+        assert_eq!(client.get_requests(), 2);
+        assert_eq!(
+            client
+                .head_object("cluster/broker-a/commitlog/00000000000000001024")
+                .unwrap(),
+            Some(10)
+        );
+
+        backend
+            .open_segment(FileSegmentType::ConsumeQueue, "TopicTest/0", 0)
+            .commit(Bytes::from_static(&[0u8; 20]))
+            .unwrap();
+        backend
+            .open_segment(FileSegmentType::ConsumeQueue, "TopicTest/0", 20)
+            .commit(Bytes::from_static(&[0u8; 20]))
+            .unwrap();
+        backend
+            .open_segment(FileSegmentType::ConsumeQueue, "Other/2", 0)
+            .commit(Bytes::from_static(&[0u8; 20]))
+            .unwrap();
+        assert_eq!(
+            backend
+                .list_segments(FileSegmentType::ConsumeQueue, "TopicTest/0")
+                .unwrap(),
+            vec![0, 20]
+        );
+        assert_eq!(
+            backend
+                .list_prefixes(FileSegmentType::ConsumeQueue)
+                .unwrap(),
+            vec!["Other/2".to_string(), "TopicTest/0".to_string()]
+        );
+        assert_eq!(
+            backend.list_prefixes(FileSegmentType::CommitLog).unwrap(),
+            vec![String::new()]
+        );
+
+        segment.destroy().unwrap();
+        assert!(backend
+            .list_segments(FileSegmentType::CommitLog, "")
+            .unwrap()
+            .is_empty());
+        assert_eq!(client.object_count(), 3);
+    }
+}
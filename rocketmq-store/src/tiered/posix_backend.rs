@@ -0,0 +1,252 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use crate::tiered::file_segment::check_read_range;
+use crate::tiered::file_segment::parse_offset_name;
+use crate::tiered::file_segment::segment_path;
+use crate::tiered::file_segment::FileSegmentType;
+use crate::tiered::file_segment::TieredFileSegment;
+use crate::tiered::file_segment::TieredStoreBackend;
+
+/// Stores segments as plain files under a local directory, typically a mounted network or
+/// cheaper disk.
+pub struct PosixTieredStoreBackend {
+    root_dir: PathBuf,
+}
+
+impl PosixTieredStoreBackend {
+    pub fn new(root_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            root_dir: root_dir.into(),
+        }
+    }
+
+    pub fn root_dir(&self) -> &Path {
+        &self.root_dir
+    }
+}
+
+impl TieredStoreBackend for PosixTieredStoreBackend {
+    fn name(&self) -> &'static str {
+        "posix"
+    }
+
+    fn open_segment(
+        &self,
+        file_type: FileSegmentType,
+        prefix: &str,
+        base_offset: i64,
+    ) -> Arc<dyn TieredFileSegment> {
+        let path = segment_path(file_type, prefix, base_offset);
+        Arc::new(PosixFileSegment {
+            file_type,
+            base_offset,
+            full_path: self.root_dir.join(&path),
+            path,
+        })
+    }
+
+    fn list_segments(&self, file_type: FileSegmentType, prefix: &str) -> io::Result<Vec<i64>> {
+        let dir = self.root_dir.join(file_type.dir_name()).join(prefix);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut offsets = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            if let Some(offset) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(parse_offset_name)
+            {
+                offsets.push(offset);
+            }
+        }
+        offsets.sort_unstable();
+        Ok(offsets)
+    }
+
+    fn list_prefixes(&self, file_type: FileSegmentType) -> io::Result<Vec<String>> {
+        let root = self.root_dir.join(file_type.dir_name());
+        if !root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut prefixes = Vec::new();
+        match file_type {
+            FileSegmentType::CommitLog => {
+                if !self.list_segments(file_type, "")?.is_empty() {
+                    prefixes.push(String::new());
+                }
+            }
+            FileSegmentType::ConsumeQueue => {
+                for topic_dir in fs::read_dir(&root)? {
+                    let topic_dir = topic_dir?.path();
+                    if !topic_dir.is_dir() {
+                        continue;
+                    }
+                    for queue_dir in fs::read_dir(&topic_dir)? {
+                        let queue_dir = queue_dir?.path();
+                        if !queue_dir.is_dir() {
+                            continue;
+                        }
+                        let prefix = queue_dir
+                            .strip_prefix(&root)
+                            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+                            .unwrap_or_default();
+                        if !self.list_segments(file_type, &prefix)?.is_empty() {
+                            prefixes.push(prefix);
+                        }
+                    }
+                }
+            }
+        }
+        prefixes.sort();
+        Ok(prefixes)
+    }
+}
+
+struct PosixFileSegment {
+    file_type: FileSegmentType,
+    base_offset: i64,
+    path: String,
+    full_path: PathBuf,
+}
+
+impl TieredFileSegment for PosixFileSegment {
+    fn file_type(&self) -> FileSegmentType {
+        self.file_type
+    }
+
+    fn base_offset(&self) -> i64 {
+        self.base_offset
+    }
+
+    fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn size(&self) -> io::Result<i64> {
+        match fs::metadata(&self.full_path) {
+            Ok(metadata) => Ok(metadata.len() as i64),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn exists(&self) -> bool {
+        self.full_path.is_file()
+    }
+
+    fn commit(&self, data: Bytes) -> io::Result<()> {
+        if let Some(parent) = self.full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // write aside and rename, so a crash never leaves a truncated segment behind
+        let tmp_path = self.full_path.with_extension("tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.full_path)
+    }
+
+    fn read(&self, position: i64, length: i32) -> io::Result<Bytes> {
+        let mut file = File::open(&self.full_path)?;
+        check_read_range(&self.path, file.metadata()?.len() as i64, position, length)?;
+        file.seek(SeekFrom::Start(position as u64))?;
+        let mut buffer = vec![0u8; length as usize];
+        file.read_exact(&mut buffer)?;
+        Ok(Bytes::from(buffer))
+    }
+
+    fn destroy(&self) -> io::Result<()> {
+        match fs::remove_file(&self.full_path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commit_read_list_and_destroy() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = PosixTieredStoreBackend::new(dir.path());
+
+        let segment = backend.open_segment(FileSegmentType::CommitLog, "", 4096);
+        assert!(!segment.exists());
+        assert_eq!(segment.size().unwrap(), 0);
+        segment.commit(Bytes::from_static(b"0123456789")).unwrap();
+        assert!(segment.exists());
+        assert_eq!(segment.size().unwrap(), 10);
+        assert_eq!(segment.read(2, 4).unwrap(), Bytes::from_static(b"2345"));
+        assert!(segment.read(8, 4).is_err());
+        assert!(dir.path().join("commitlog/00000000000000004096").is_file());
+
+        backend
+            .open_segment(FileSegmentType::ConsumeQueue, "TopicTest/1", 0)
+            .commit(Bytes::from_static(&[0u8; 20]))
+            .unwrap();
+        backend
+            .open_segment(FileSegmentType::CommitLog, "", 0)
+            .commit(Bytes::from_static(b"x"))
+            .unwrap();
+        assert_eq!(
+            backend
+                .list_segments(FileSegmentType::CommitLog, "")
+                .unwrap(),
+            vec![0, 4096]
+        );
+        assert_eq!(
+            backend
+                .list_prefixes(FileSegmentType::ConsumeQueue)
+                .unwrap(),
+            vec!["TopicTest/1".to_string()]
+        );
+        assert_eq!(
+            backend.list_prefixes(FileSegmentType::CommitLog).unwrap(),
+            vec![String::new()]
+        );
+
+        segment.destroy().unwrap();
+        assert!(!segment.exists());
+        assert_eq!(
+            backend
+                .list_segments(FileSegmentType::CommitLog, "")
+                .unwrap(),
+            vec![0]
+        );
+    }
+}
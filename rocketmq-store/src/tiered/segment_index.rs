@@ -0,0 +1,205 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+
+use parking_lot::RwLock;
+
+use crate::tiered::file_segment::FileSegmentType;
+use crate::tiered::file_segment::TieredStoreBackend;
+
+/// Offset range covered by a committed segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TieredSegmentMeta {
+    pub base_offset: i64,
+    pub size: i64,
+}
+
+impl TieredSegmentMeta {
+    #[inline]
+    pub fn end_offset(&self) -> i64 {
+        self.base_offset + self.size
+    }
+
+    #[inline]
+    pub fn contains(&self, offset: i64) -> bool {
+        self.base_offset <= offset && offset < self.end_offset()
+    }
+}
+
+/// In-memory index of the offsets that live in the tiered storage, per file type and prefix.
+///
+/// The backend is the source of truth: the index is rebuilt from its listing on startup and
+/// kept up to date by the uploader afterwards.
+#[derive(Default)]
+pub struct TieredSegmentIndex {
+    segments: RwLock<HashMap<(FileSegmentType, String), BTreeMap<i64, i64>>>,
+}
+
+impl TieredSegmentIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the content of the index with the segments listed by `backend`, returns the
+    /// number of segments found.
+    pub fn rebuild(&self, backend: &dyn TieredStoreBackend) -> io::Result<usize> {
+        let mut segments = HashMap::new();
+        let mut count = 0;
+        for file_type in [FileSegmentType::CommitLog, FileSegmentType::ConsumeQueue] {
+            for prefix in backend.list_prefixes(file_type)? {
+                let mut files = BTreeMap::new();
+                for base_offset in backend.list_segments(file_type, &prefix)? {
+                    let size = backend
+                        .open_segment(file_type, &prefix, base_offset)
+                        .size()?;
+                    if size > 0 {
+                        files.insert(base_offset, size);
+                        count += 1;
+                    }
+                }
+                if !files.is_empty() {
+                    segments.insert((file_type, prefix), files);
+                }
+            }
+        }
+        *self.segments.write() = segments;
+        Ok(count)
+    }
+
+    pub fn add(&self, file_type: FileSegmentType, prefix: &str, base_offset: i64, size: i64) {
+        self.segments
+            .write()
+            .entry((file_type, prefix.to_string()))
+            .or_default()
+            .insert(base_offset, size);
+    }
+
+    pub fn remove(&self, file_type: FileSegmentType, prefix: &str, base_offset: i64) -> bool {
+        let mut segments = self.segments.write();
+        let key = (file_type, prefix.to_string());
+        let Some(files) = segments.get_mut(&key) else {
+            return false;
+        };
+        let removed = files.remove(&base_offset).is_some();
+        if files.is_empty() {
+            segments.remove(&key);
+        }
+        removed
+    }
+
+    pub fn contains(&self, file_type: FileSegmentType, prefix: &str, base_offset: i64) -> bool {
+        self.segments
+            .read()
+            .get(&(file_type, prefix.to_string()))
+            .is_some_and(|files| files.contains_key(&base_offset))
+    }
+
+    /// Finds the segment holding `offset`.
+    pub fn find(
+        &self,
+        file_type: FileSegmentType,
+        prefix: &str,
+        offset: i64,
+    ) -> Option<TieredSegmentMeta> {
+        self.segments
+            .read()
+            .get(&(file_type, prefix.to_string()))?
+            .range(..=offset)
+            .next_back()
+            .map(|(base_offset, size)| TieredSegmentMeta {
+                base_offset: *base_offset,
+                size: *size,
+            })
+            .filter(|meta| meta.contains(offset))
+    }
+
+    /// Returns the first offset stored in the tiered storage under `prefix`.
+    pub fn min_offset(&self, file_type: FileSegmentType, prefix: &str) -> Option<i64> {
+        self.segments
+            .read()
+            .get(&(file_type, prefix.to_string()))?
+            .keys()
+            .next()
+            .copied()
+    }
+
+    /// Returns the offset following the last byte stored in the tiered storage under `prefix`.
+    pub fn max_offset(&self, file_type: FileSegmentType, prefix: &str) -> Option<i64> {
+        self.segments
+            .read()
+            .get(&(file_type, prefix.to_string()))?
+            .iter()
+            .next_back()
+            .map(|(base_offset, size)| base_offset + size)
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.segments.read().values().map(BTreeMap::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::tiered::object_store_backend::MemoryObjectStoreClient;
+    use crate::tiered::object_store_backend::ObjectStoreBackend;
+
+    #[test]
+    fn find_and_rebuild_from_backend() {
+        let index = TieredSegmentIndex::new();
+        index.add(FileSegmentType::CommitLog, "", 0, 100);
+        index.add(FileSegmentType::CommitLog, "", 100, 100);
+        assert_eq!(
+            index.find(FileSegmentType::CommitLog, "", 150),
+            Some(TieredSegmentMeta {
+                base_offset: 100,
+                size: 100
+            })
+        );
+        assert_eq!(index.find(FileSegmentType::CommitLog, "", 200), None);
+        assert_eq!(index.find(FileSegmentType::ConsumeQueue, "", 0), None);
+        assert_eq!(index.min_offset(FileSegmentType::CommitLog, ""), Some(0));
+        assert_eq!(index.max_offset(FileSegmentType::CommitLog, ""), Some(200));
+        assert!(index.remove(FileSegmentType::CommitLog, "", 0));
+        assert_eq!(index.find(FileSegmentType::CommitLog, "", 50), None);
+
+        let backend = ObjectStoreBackend::new(Arc::new(MemoryObjectStoreClient::new()), "");
+        backend
+            .open_segment(FileSegmentType::CommitLog, "", 4096)
+            .commit(Bytes::from_static(&[1u8; 4096]))
+            .unwrap();
+        backend
+            .open_segment(FileSegmentType::ConsumeQueue, "TopicTest/0", 40)
+            .commit(Bytes::from_static(&[1u8; 40]))
+            .unwrap();
+        assert_eq!(index.rebuild(&backend).unwrap(), 2);
+        assert_eq!(index.segment_count(), 2);
+        assert!(!index.contains(FileSegmentType::CommitLog, "", 100));
+        assert!(index.contains(FileSegmentType::CommitLog, "", 4096));
+        assert_eq!(
+            index.max_offset(FileSegmentType::ConsumeQueue, "TopicTest/0"),
+            Some(80)
+        );
+    }
+}
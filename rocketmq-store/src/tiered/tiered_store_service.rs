@@ -0,0 +1,405 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use bytes::Buf;
+use bytes::Bytes;
+use tracing::info;
+use tracing::warn;
+
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::queue::single_consume_queue::CQ_STORE_UNIT_SIZE;
+use crate::queue::CqUnit;
+use crate::store_check::commit_log_scanner::file_name_of;
+use crate::store_check::commit_log_scanner::list_offset_named_files;
+use crate::store_path_config_helper::get_store_path_consume_queue;
+use crate::tiered::file_segment::FileSegmentType;
+use crate::tiered::file_segment::TieredStoreBackend;
+use crate::tiered::segment_index::TieredSegmentIndex;
+
+/// Uploads sealed local files to a [`TieredStoreBackend`] and serves reads of the offsets it
+/// holds.
+///
+/// A commit log or consume queue file is sealed once a newer file exists next to it. Local files
+/// are never deleted here: once uploaded they can be reclaimed by the usual clean services and
+/// the message store falls back to this service for the offsets they covered.
+#[derive(Clone)]
+pub struct TieredStoreService {
+    message_store_config: Arc<MessageStoreConfig>,
+    backend: Arc<dyn TieredStoreBackend>,
+    index: Arc<TieredSegmentIndex>,
+}
+
+impl TieredStoreService {
+    pub fn new(
+        message_store_config: Arc<MessageStoreConfig>,
+        backend: Arc<dyn TieredStoreBackend>,
+    ) -> Self {
+        Self {
+            message_store_config,
+            backend,
+            index: Arc::new(TieredSegmentIndex::new()),
+        }
+    }
+
+    pub fn backend(&self) -> &Arc<dyn TieredStoreBackend> {
+        &self.backend
+    }
+
+    pub fn index(&self) -> &TieredSegmentIndex {
+        &self.index
+    }
+
+    /// Rebuilds the segment index from the backend.
+    pub fn load(&self) -> io::Result<usize> {
+        let segments = self.index.rebuild(self.backend.as_ref())?;
+        info!(
+            "load {} tiered segments from {} backend",
+            segments,
+            self.backend.name()
+        );
+        Ok(segments)
+    }
+
+    /// Uploads every sealed commit log and consume queue file that is not in the backend yet,
+    /// returns the number of uploaded files.
+    pub fn upload_sealed_files(&self) -> io::Result<usize> {
+        let mut uploaded = self.upload_sealed_files_in(
+            FileSegmentType::CommitLog,
+            "",
+            Path::new(&self.message_store_config.get_store_path_commit_log()),
+            self.message_store_config.mapped_file_size_commit_log as u64,
+        )?;
+
+        let root = get_store_path_consume_queue(&self.message_store_config.store_path_root_dir);
+        let root = Path::new(&root);
+        if root.is_dir() {
+            let file_size = self
+                .message_store_config
+                .get_mapped_file_size_consume_queue() as u64;
+            for topic_dir in fs::read_dir(root)? {
+                let topic_dir = topic_dir?.path();
+                if !topic_dir.is_dir() {
+                    continue;
+                }
+                let topic = file_name_of(&topic_dir);
+                for queue_dir in fs::read_dir(&topic_dir)? {
+                    let queue_dir = queue_dir?.path();
+                    let Ok(queue_id) = file_name_of(&queue_dir).parse::<i32>() else {
+                        continue;
+                    };
+                    uploaded += self.upload_sealed_files_in(
+                        FileSegmentType::ConsumeQueue,
+                        &Self::queue_prefix(&topic, queue_id),
+                        &queue_dir,
+                        file_size,
+                    )?;
+                }
+            }
+        }
+        Ok(uploaded)
+    }
+
+    fn upload_sealed_files_in(
+        &self,
+        file_type: FileSegmentType,
+        prefix: &str,
+        dir: &Path,
+        file_size: u64,
+    ) -> io::Result<usize> {
+        let mut files = list_offset_named_files(dir)?;
+        // the last file is still being written
+        files.pop();
+        let mut uploaded = 0;
+        for (base_offset, path) in files {
+            if self.index.contains(file_type, prefix, base_offset) {
+                continue;
+            }
+            if fs::metadata(&path)?.len() != file_size {
+                warn!(
+                    "skip uploading {} to tiered storage, its size is not {}",
+                    path.display(),
+                    file_size
+                );
+                continue;
+            }
+            self.backend
+                .open_segment(file_type, prefix, base_offset)
+                .commit(Bytes::from(fs::read(&path)?))?;
+            self.index
+                .add(file_type, prefix, base_offset, file_size as i64);
+            uploaded += 1;
+            info!(
+                "upload {} to {} tiered storage",
+                path.display(),
+                self.backend.name()
+            );
+        }
+        Ok(uploaded)
+    }
+
+    /// Returns `true` when `[offset, offset + size)` of the commit log is in the tiered storage.
+    pub fn contains_commit_log(&self, offset: i64, size: i32) -> bool {
+        self.index
+            .find(FileSegmentType::CommitLog, "", offset)
+            .is_some_and(|meta| offset + size as i64 <= meta.end_offset())
+    }
+
+    /// Reads a message of the commit log from the tiered storage, on a blocking thread.
+    pub async fn read_commit_log(&self, offset: i64, size: i32) -> Option<Bytes> {
+        let service = self.clone();
+        self.spawn_read(move || service.read_commit_log_blocking(offset, size))
+            .await
+            .flatten()
+    }
+
+    fn read_commit_log_blocking(&self, offset: i64, size: i32) -> Option<Bytes> {
+        let meta = self.index.find(FileSegmentType::CommitLog, "", offset)?;
+        if offset + size as i64 > meta.end_offset() {
+            return None;
+        }
+        match self
+            .backend
+            .open_segment(FileSegmentType::CommitLog, "", meta.base_offset)
+            .read(offset - meta.base_offset, size)
+        {
+            Ok(data) => Some(data),
+            Err(error) => {
+                warn!(
+                    "read commit log [{}, +{}) from tiered storage failed: {}",
+                    offset, size, error
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns the first queue offset of a consume queue kept in the tiered storage.
+    pub fn get_min_offset_in_queue(&self, topic: &str, queue_id: i32) -> Option<i64> {
+        self.index
+            .min_offset(
+                FileSegmentType::ConsumeQueue,
+                &Self::queue_prefix(topic, queue_id),
+            )
+            .map(|offset| offset / CQ_STORE_UNIT_SIZE as i64)
+    }
+
+    /// Returns the queue offset following the last unit of a consume queue kept in the tiered
+    /// storage.
+    pub fn get_max_offset_in_queue(&self, topic: &str, queue_id: i32) -> Option<i64> {
+        self.index
+            .max_offset(
+                FileSegmentType::ConsumeQueue,
+                &Self::queue_prefix(topic, queue_id),
+            )
+            .map(|offset| offset / CQ_STORE_UNIT_SIZE as i64)
+    }
+
+    /// Reads up to `max_units` consume queue units starting at `queue_offset`, stopping at the
+    /// end of the segment holding `queue_offset` so that one pull is a single backend read. The
+    /// read runs on a blocking thread.
+    pub(crate) async fn read_cq_units(
+        &self,
+        topic: &str,
+        queue_id: i32,
+        queue_offset: i64,
+        max_units: i32,
+    ) -> Vec<CqUnit> {
+        let service = self.clone();
+        let topic = topic.to_string();
+        self.spawn_read(move || {
+            service.read_cq_units_blocking(&topic, queue_id, queue_offset, max_units)
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    fn read_cq_units_blocking(
+        &self,
+        topic: &str,
+        queue_id: i32,
+        queue_offset: i64,
+        max_units: i32,
+    ) -> Vec<CqUnit> {
+        let prefix = Self::queue_prefix(topic, queue_id);
+        let unit_size = CQ_STORE_UNIT_SIZE as i64;
+        let offset = queue_offset * unit_size;
+        let Some(meta) = self
+            .index
+            .find(FileSegmentType::ConsumeQueue, &prefix, offset)
+        else {
+            return Vec::new();
+        };
+        let units = ((meta.end_offset() - offset) / unit_size).min(max_units.max(1) as i64);
+        let mut data = match self
+            .backend
+            .open_segment(FileSegmentType::ConsumeQueue, &prefix, meta.base_offset)
+            .read(offset - meta.base_offset, (units * unit_size) as i32)
+        {
+            Ok(data) => data,
+            Err(error) => {
+                warn!(
+                    "read consume queue {} from tiered storage at {} failed: {}",
+                    prefix, queue_offset, error
+                );
+                return Vec::new();
+            }
+        };
+        let mut cq_units = Vec::with_capacity(units as usize);
+        for index in 0..units {
+            let pos = data.get_i64();
+            let size = data.get_i32();
+            let tags_code = data.get_i64();
+            cq_units.push(CqUnit {
+                queue_offset: queue_offset + index,
+                size,
+                pos,
+                tags_code,
+                ..CqUnit::default()
+            });
+        }
+        cq_units
+    }
+
+    /// Runs a backend read on a blocking thread, backends may do network or disk IO.
+    async fn spawn_read<T: Send + 'static>(
+        &self,
+        read: impl FnOnce() -> T + Send + 'static,
+    ) -> Option<T> {
+        match tokio::task::spawn_blocking(read).await {
+            Ok(result) => Some(result),
+            Err(error) => {
+                warn!("tiered storage read task failed: {}", error);
+                None
+            }
+        }
+    }
+
+    #[inline]
+    fn queue_prefix(topic: &str, queue_id: i32) -> String {
+        format!("{}/{}", topic, queue_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
+    use rocketmq_common::UtilAll::offset_to_file_name;
+
+    use super::*;
+    use crate::base::message_status_enum::GetMessageStatus;
+    use crate::log_file::MessageStore;
+    use crate::message_store::default_message_store::DefaultMessageStore;
+    use crate::test_util::create_store;
+    use crate::test_util::TOPIC;
+    use crate::tiered::object_store_backend::MemoryObjectStoreClient;
+    use crate::tiered::object_store_backend::ObjectStoreBackend;
+
+    #[tokio::test]
+    async fn serves_offloaded_messages_after_local_files_are_deleted() {
+        let store = create_store(6);
+        let config = Arc::new(MessageStoreConfig {
+            mapped_file_size_commit_log: 4096,
+            mapped_file_size_consume_queue: CQ_STORE_UNIT_SIZE as usize * 3,
+            ..store.config.as_ref().clone()
+        });
+        // seal the first commit log file and split the consume queue into two sealed files
+        let commit_log_dir = Path::new(&config.get_store_path_commit_log()).to_path_buf();
+        fs::write(commit_log_dir.join(offset_to_file_name(4096)), [0u8; 4096]).unwrap();
+        let queue_dir = Path::new(&get_store_path_consume_queue(&config.store_path_root_dir))
+            .join(TOPIC)
+            .join("0");
+        let units = fs::read(queue_dir.join(offset_to_file_name(0))).unwrap();
+        fs::write(queue_dir.join(offset_to_file_name(0)), &units[..60]).unwrap();
+        fs::write(queue_dir.join(offset_to_file_name(60)), &units[60..]).unwrap();
+        let commit_log =
+            Bytes::from(fs::read(commit_log_dir.join(offset_to_file_name(0))).unwrap());
+        let local_messages = store
+            .messages
+            .iter()
+            .map(|(offset, size)| {
+                commit_log.slice(*offset as usize..(*offset + *size as i64) as usize)
+            })
+            .collect::<Vec<_>>();
+
+        let client = Arc::new(MemoryObjectStoreClient::new());
+        let backend = Arc::new(ObjectStoreBackend::new(client.clone(), "broker-a/"));
+        let service = TieredStoreService::new(config.clone(), backend.clone());
+        assert_eq!(service.upload_sealed_files().unwrap(), 2);
+        assert_eq!(service.upload_sealed_files().unwrap(), 0);
+        assert_eq!(client.object_count(), 2);
+        let (offset, size) = store.messages[1];
+        assert!(service.contains_commit_log(offset, size));
+        assert!(!service.contains_commit_log(4096, 10));
+        assert_eq!(
+            service.read_commit_log(offset, size).await,
+            Some(local_messages[1].clone())
+        );
+        assert_eq!(service.get_min_offset_in_queue(TOPIC, 0), Some(0));
+        assert_eq!(service.get_max_offset_in_queue(TOPIC, 0), Some(3));
+        let cq_units = service.read_cq_units(TOPIC, 0, 1, 32).await;
+        assert_eq!(cq_units.len(), 2);
+        assert_eq!((cq_units[0].queue_offset, cq_units[0].pos), (1, offset));
+
+        // the cleanup services reclaimed the uploaded files
+        fs::remove_file(commit_log_dir.join(offset_to_file_name(0))).unwrap();
+        fs::remove_file(queue_dir.join(offset_to_file_name(0))).unwrap();
+
+        let mut message_store = DefaultMessageStore::new(
+            config,
+            Arc::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        message_store.set_tiered_store_backend(backend);
+        assert!(message_store.load().await);
+        assert_eq!(
+            message_store
+                .tiered_store_service()
+                .unwrap()
+                .index()
+                .segment_count(),
+            2
+        );
+
+        let result = message_store
+            .get_message("group", TOPIC, 0, 0, 32, 1024 * 1024, None)
+            .await
+            .unwrap();
+        assert_eq!(result.status(), Some(GetMessageStatus::Found));
+        assert_eq!(result.min_offset(), 0);
+        assert_eq!(result.next_begin_offset(), 3);
+        assert_eq!(result.message_buffer_list(), &local_messages[..3]);
+
+        // the consume queue is still local but the commit log file is not
+        assert_eq!(message_store.get_min_offset_in_queue(TOPIC, 0), 3);
+        let result = message_store
+            .get_message("group", TOPIC, 0, 3, 32, 1024 * 1024, None)
+            .await
+            .unwrap();
+        assert_eq!(result.status(), Some(GetMessageStatus::Found));
+        assert_eq!(result.next_begin_offset(), 6);
+        assert_eq!(result.message_buffer_list(), &local_messages[3..]);
+    }
+}